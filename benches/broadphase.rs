@@ -0,0 +1,129 @@
+//! Compares the broadphases on crowds of small bodies, to help choose one for a scene.
+//!
+//! Run with `cargo bench --bench broadphase`. Each scene is timed with sweep and prune and with spatial hashes of a few
+//! cell sizes, first finding pairs on their own, and then running whole physics steps.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use megavertex::{Aabb, Broadphase, Collider, Object, Plane, Rigidbody, Vec3, World};
+
+// How many physics steps each run of a world takes
+const STEPS: usize = 10;
+
+///
+/// A tiny random number generator, so the scenes are the same every run.
+///
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> f64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn point(&mut self, extent: Vec3) -> Vec3 {
+        Vec3::new(
+            self.next() * extent.x,
+            self.next() * extent.y,
+            self.next() * extent.z,
+        )
+    }
+}
+
+fn broadphases() -> Vec<(String, Broadphase)> {
+    let mut broadphases = vec![("sweep and prune".to_string(), Broadphase::SweepAndPrune)];
+    for cell_size in [0.5, 1.0, 4.0] {
+        let name = format!("spatial hash {}", cell_size);
+        broadphases.push((name, Broadphase::SpatialHash { cell_size }));
+    }
+    broadphases
+}
+
+///
+/// Times finding the pairs among `count` boxes of debris scattered through a space, `size` across each.
+///
+fn bench_pairs(c: &mut Criterion, name: &str, count: usize, extent: Vec3, size: f64) {
+    let mut random = Random(count as u64);
+    let bounds: Vec<Option<Aabb>> = (0..count)
+        .map(|_| {
+            let min = random.point(extent);
+            Some(Aabb::new(min, min + Vec3::new(size, size, size)))
+        })
+        .collect();
+
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Elements(count as u64));
+    for (label, broadphase) in broadphases() {
+        group.bench_function(label, |b| b.iter(|| broadphase.pairs(&bounds)));
+    }
+    group.finish();
+}
+
+///
+/// Builds a world with `count` small balls about to drop in a heap onto the ground.
+///
+fn heap(cube: &Object, broadphase: Broadphase, count: usize, extent: Vec3) -> World {
+    let mut world = World::new();
+    world.physics.broadphase = broadphase;
+    let ground = world.add_object(cube.clone(), Vec3::default());
+    world.insert_component(
+        ground,
+        Rigidbody::new(cube.clone())
+            .with_mass(0.0)
+            .with_collider(Collider::Plane(Plane::new(Vec3::new(0.0, 1.0, 0.0), 0.0))),
+    );
+
+    let mut random = Random(count as u64);
+    for _ in 0..count {
+        let id = world.add_object(cube.clone(), random.point(extent));
+        let ball = Collider::Sphere {
+            centre: Vec3::default(),
+            radius: 0.2,
+        };
+        world.insert_component(id, Rigidbody::new(cube.clone()).with_collider(ball));
+    }
+    world
+}
+
+///
+/// Times the first few physics steps of a heap of `count` balls, starting from a fresh world each run.
+///
+fn bench_steps(c: &mut Criterion, count: usize, extent: Vec3) {
+    let cube = Object::from_obj("./resources/cube").expect("the cube model is in resources");
+    let mut group = c.benchmark_group(format!("{} balls, {} physics steps", count, STEPS));
+    // Each run steps a whole world several times, so criterion's fewest samples are plenty
+    group.sample_size(10);
+    for (label, broadphase) in broadphases() {
+        group.bench_function(label, |b| {
+            b.iter_batched(
+                || heap(&cube, broadphase, count, extent),
+                |mut world| {
+                    for _ in 0..STEPS {
+                        world.step_physics(1.0 / 60.0);
+                    }
+                    world
+                },
+                BatchSize::PerIteration,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn broadphase(c: &mut Criterion) {
+    bench_pairs(
+        c,
+        "scattered debris",
+        2000,
+        Vec3::new(40.0, 10.0, 40.0),
+        0.4,
+    );
+    bench_pairs(c, "debris in a line", 2000, Vec3::new(2.0, 2.0, 200.0), 0.4);
+    bench_pairs(c, "mixed sizes", 1000, Vec3::new(20.0, 20.0, 20.0), 1.5);
+    bench_steps(c, 500, Vec3::new(10.0, 10.0, 10.0));
+}
+
+criterion_group!(benches, broadphase);
+criterion_main!(benches);
@@ -0,0 +1,35 @@
+//! Renders the standard scenes without a window, to measure changes to the rasteriser.
+//!
+//! Run with `cargo bench --bench render`, optionally followed by `--` and a filter such as `render/terrain`. Each scene
+//! is drawn at a fixed size, with criterion timing whole frames and reporting how many triangles are drawn a second.
+//! The `render_bench` binary renders the same scenes with other sizes and breaks each frame down into its stages.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+// The timing breakdown in the shared scenes is only printed by the `render_bench` binary
+#[allow(dead_code)]
+mod scenes;
+
+// The size every scene is drawn at
+const WIDTH: usize = 600;
+const HEIGHT: usize = 400;
+
+fn render(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render");
+    // Whole frames take long enough that criterion's fewest samples still give steady numbers
+    group.sample_size(10);
+    for scene in &scenes::SCENES {
+        let mut headless = scene.headless(WIDTH, HEIGHT);
+        let triangles = headless.profile_frame().stats.triangles;
+        group.throughput(Throughput::Elements(triangles as u64));
+        group.bench_function(scene.name, |b| {
+            b.iter(|| {
+                headless.render_frame();
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, render);
+criterion_main!(benches);
@@ -0,0 +1,198 @@
+//! Standard scenes for measuring the renderer, shared by the `render` bench and the `render_bench` binary so their
+//! numbers can be compared.
+
+use std::{sync::Arc, time::Duration};
+
+use megavertex::{
+    EntityId, Fog, FrameProfile, Headless, Light, Object, RenderStats, Terrain, Vec3, Water,
+};
+
+// Frames drawn before timing starts, so caches are warm and the physics has settled
+const WARMUP_FRAMES: usize = 5;
+
+///
+/// A scene to render, and what it's meant to stress.
+///
+pub struct BenchScene {
+    pub name: &'static str,
+    pub description: &'static str,
+    build: fn(&mut Headless),
+}
+
+pub const SCENES: [BenchScene; 3] = [
+    BenchScene {
+        name: "cubes",
+        description: "a grid of 400 lit, textured cubes",
+        build: cubes,
+    },
+    BenchScene {
+        name: "terrain",
+        description: "rolling terrain filling the lower half of the screen",
+        build: terrain,
+    },
+    BenchScene {
+        name: "blended",
+        description: "see-through cubes over water, in fog",
+        build: blended,
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static BenchScene> {
+    SCENES.iter().find(|scene| scene.name == name)
+}
+
+fn cube() -> Object {
+    Object::from_obj("./resources/cube").expect("the cube model is in resources")
+}
+
+fn sun() -> Light {
+    Light::Directional {
+        direction: Vec3::new(-0.4, -1.0, 0.6),
+        colour: Vec3::new(1.0, 1.0, 1.0),
+        intensity: 1.0,
+    }
+}
+
+///
+/// Adds a 20 by 20 grid of cubes in front of the camera, returning their IDs.
+///
+fn cube_grid(headless: &mut Headless) -> Vec<EntityId> {
+    let cube = cube();
+    let mut ids = vec![];
+    for row in 0..20 {
+        for column in 0..20 {
+            let position = Vec3::new(column as f64 * 3.0 - 30.0, -3.0, row as f64 * 3.0 + 8.0);
+            ids.push(headless.world.add_object(cube.clone(), position));
+        }
+    }
+    ids
+}
+
+fn cubes(headless: &mut Headless) {
+    headless.world.lights.push(sun());
+    cube_grid(headless);
+}
+
+fn terrain(headless: &mut Headless) {
+    headless.world.lights.push(sun());
+    let texture = Arc::unwrap_or_clone(cube().texture);
+    let mut terrain = Terrain::from_fn(8, 8, 16, 1.0, texture, |x, z| {
+        (x * 0.2).sin() * 2.0 + (z * 0.15).cos() * 2.0
+    });
+    terrain.origin = Vec3::new(-64.0, -8.0, 0.0);
+    headless.world.set_terrain(terrain);
+}
+
+fn blended(headless: &mut Headless) {
+    headless.world.lights.push(sun());
+    headless.world.environment.fog = Some(Fog {
+        colour: Vec3::new(0.6, 0.7, 0.8),
+        start: 20.0,
+        end: 60.0,
+    });
+    for id in cube_grid(headless) {
+        headless.world.set_opacity(id, 0.5);
+    }
+    headless
+        .world
+        .add_water(Water::new(80.0, 32), Vec3::new(0.0, -5.0, 40.0));
+}
+
+///
+/// How long a scene took to render on average, from `BenchScene::profile`.
+///
+pub struct Summary {
+    pub frames: usize,
+    pub average: FrameProfile,
+}
+
+impl BenchScene {
+    ///
+    /// Builds the scene, ready to be drawn at the given size.
+    ///
+    pub fn headless(&self, width: usize, height: usize) -> Headless {
+        let mut headless = Headless::new(width, height);
+        (self.build)(&mut headless);
+        headless
+    }
+
+    ///
+    /// Builds the scene and renders it for `frames` frames at the given size, averaging each stage's time.
+    ///
+    pub fn profile(&self, width: usize, height: usize, frames: usize) -> Summary {
+        let mut headless = self.headless(width, height);
+        for _ in 0..WARMUP_FRAMES {
+            headless.render_frame();
+        }
+
+        let mut total = FrameProfile::default();
+        for _ in 0..frames {
+            let profile = headless.profile_frame();
+            total.physics += profile.physics;
+            total.update += profile.update;
+            total.cull += profile.cull;
+            total.draw += profile.draw;
+            total.stats.triangles += profile.stats.triangles;
+            total.stats.rasterised += profile.stats.rasterised;
+            total.stats.pixels += profile.stats.pixels;
+            total.stats.raster_time += profile.stats.raster_time;
+        }
+
+        let count = frames.max(1);
+        let average = FrameProfile {
+            physics: total.physics / count as u32,
+            update: total.update / count as u32,
+            cull: total.cull / count as u32,
+            draw: total.draw / count as u32,
+            stats: RenderStats {
+                triangles: total.stats.triangles / count,
+                rasterised: total.stats.rasterised / count,
+                pixels: total.stats.pixels / count,
+                raster_time: total.stats.raster_time / count as u32,
+            },
+        };
+        Summary { frames, average }
+    }
+}
+
+fn ms(time: Duration) -> f64 {
+    time.as_secs_f64() * 1000.0
+}
+
+impl Summary {
+    ///
+    /// Returns how many triangles were drawn a second, counting the whole frame rather than just filling them in.
+    ///
+    pub fn triangles_per_second(&self) -> f64 {
+        let frame = self.average.total().as_secs_f64();
+        if frame > 0.0 {
+            self.average.stats.triangles as f64 / frame
+        } else {
+            0.0
+        }
+    }
+
+    pub fn print(&self, scene: &BenchScene) {
+        let average = &self.average;
+        println!(
+            "{} - {} ({} frames)",
+            scene.name, scene.description, self.frames
+        );
+        println!(
+            "  {:>8.3} ms/frame  (physics {:.3}, update {:.3}, cull {:.3}, draw {:.3}, of which raster {:.3})",
+            ms(average.total()),
+            ms(average.physics),
+            ms(average.update),
+            ms(average.cull),
+            ms(average.draw),
+            ms(average.stats.raster_time),
+        );
+        println!(
+            "  {:>8} triangles, {} rasterised, {} pixels a frame, {:.0} triangles/sec",
+            average.stats.triangles,
+            average.stats.rasterised,
+            average.stats.pixels,
+            self.triangles_per_second(),
+        );
+    }
+}
@@ -0,0 +1,39 @@
+/*
+ * Draws the cube from resources/ through the C interface, and prints how much of the screen it covers.
+ *
+ * From the root of the project:
+ *     cargo rustc --release --lib --crate-type staticlib --no-default-features --features ffi
+ *     cc examples/c/main.c -Iinclude target/release/libmegavertex.a -lm -lpthread -ldl -o cube
+ *     ./cube
+ */
+#include <stdio.h>
+
+#include "megavertex.h"
+
+int main(void) {
+    MvWorld *world = mv_world_new();
+    MvCamera *camera = mv_camera_new(0.0, 0.0, 0.0);
+    MvRenderer *renderer = mv_renderer_new(80, 60);
+
+    MvEntity cube;
+    if (!mv_world_load_obj(world, "./resources/cube", 0.0, 0.0, 6.0, &cube)) {
+        fprintf(stderr, "failed to load the cube: %s\n", mv_last_error());
+        return 1;
+    }
+
+    mv_world_advance(world, 1.0 / 60.0);
+    mv_render_frame(renderer, world, camera);
+
+    const uint32_t *pixels = mv_renderer_buffer(renderer);
+    size_t count = mv_renderer_width(renderer) * mv_renderer_height(renderer);
+    size_t drawn = 0;
+    for (size_t i = 0; i < count; i++) {
+        drawn += pixels[i] != 0;
+    }
+    printf("the cube covers %zu of %zu pixels\n", drawn, count);
+
+    mv_renderer_free(renderer);
+    mv_camera_free(camera);
+    mv_world_free(world);
+    return 0;
+}
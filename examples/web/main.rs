@@ -0,0 +1,38 @@
+//! A spinning cube drawn in the browser.
+//!
+//! Build with `cargo build --example web --target wasm32-unknown-unknown --no-default-features --features web
+//! --release`, then generate the JavaScript bindings next to `index.html` with `wasm-bindgen --target web --out-dir
+//! examples/web/pkg target/wasm32-unknown-unknown/release/examples/web.wasm` and serve `examples/web` over HTTP.
+
+use megavertex::{animate, Canvas, Headless, Light, Mat4, Object, PresentTarget, Vec3};
+use wasm_bindgen::prelude::*;
+
+// How far the cube turns each frame, in radians
+const SPIN: f64 = 0.02;
+
+#[wasm_bindgen(start)]
+pub fn start() -> Result<(), JsValue> {
+    let mut canvas = Canvas::from_id("megavertex")?;
+    let cube = Object::from_obj_bytes(
+        include_bytes!("../../resources/cube.obj"),
+        include_bytes!("../../resources/cube.png"),
+    )
+    .map_err(|error| error.to_string())?;
+
+    let mut headless = Headless::new(320, 200);
+    headless.world.lights.push(Light::Directional {
+        direction: Vec3::new(-0.4, -1.0, 0.6),
+        colour: Vec3::new(1.0, 1.0, 1.0),
+        intensity: 1.0,
+    });
+    let id = headless.world.add_object(cube, Vec3::new(0.0, 0.0, 5.0));
+
+    animate(move |_| {
+        if let Some(mut cube) = headless.world.get_mut(id) {
+            cube.transform(Mat4::identity().rotate(Vec3::new(0.3, 1.0, 0.0), SPIN));
+        }
+        headless.render_frame();
+        canvas.present_frame(&headless.renderer).is_ok()
+    });
+    Ok(())
+}
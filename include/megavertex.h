@@ -0,0 +1,147 @@
+/* The C interface to megavertex, built with `--features ffi`. Generated by cbindgen - don't edit by hand. */
+
+#ifndef MEGAVERTEX_H
+#define MEGAVERTEX_H
+
+
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ *
+ * Draws a world object
+ *
+ * The camera looks down the negative Z axis. `fov` is its horizontal field of view in radians, and the vertical
+ * field of view follows from the shape of the screen.
+ *
+ */
+typedef struct MvCamera MvCamera;
+
+typedef struct MvRenderer MvRenderer;
+
+typedef struct MvWorld MvWorld;
+
+/**
+ *
+ * A handle to an entity in a world, as given out by `mv_world_load_obj`. It stays valid until the entity is removed.
+ *
+ */
+typedef struct {
+  size_t index;
+  uint32_t generation;
+} MvEntity;
+
+/**
+ *
+ * Returns a description of the last error on this thread, or null if nothing has failed. The string belongs to the
+ * library, and is only valid until the next call which fails.
+ *
+ */
+const char *mv_last_error(void);
+
+/**
+ *
+ * Creates an empty world.
+ *
+ */
+MvWorld *mv_world_new(void);
+
+void mv_world_free(MvWorld *world);
+
+/**
+ *
+ * Loads a model as with `Object::from_obj`, where `name` is the path without an extension, and adds it to the world
+ * at the given position. The new entity is written to `entity`.
+ *
+ * # Safety
+ *
+ * `name` must be a nul terminated string, and `entity` must be null or point to somewhere an `MvEntity` can be
+ * written.
+ *
+ */
+bool mv_world_load_obj(MvWorld *world,
+                       const char *name,
+                       double x,
+                       double y,
+                       double z,
+                       MvEntity *entity);
+
+/**
+ *
+ * Removes an entity from the world, returning false if it had already been removed.
+ *
+ */
+bool mv_world_remove(MvWorld *world, MvEntity entity);
+
+/**
+ *
+ * Sets where an entity is and how it's turned and scaled, from 16 numbers making up a matrix row by row.
+ *
+ * # Safety
+ *
+ * `matrix` must point to 16 `double`s.
+ *
+ */
+bool mv_world_set_transform(MvWorld *world,
+                            MvEntity entity,
+                            const double *matrix);
+
+/**
+ *
+ * Moves the world on by `elapsed` seconds, advancing its physics and updating it, as `Headless` does each frame.
+ *
+ */
+void mv_world_advance(MvWorld *world,
+                      double elapsed);
+
+/**
+ *
+ * Creates a camera at the given position, looking down the negative Z axis.
+ *
+ */
+MvCamera *mv_camera_new(double x, double y, double z);
+
+void mv_camera_free(MvCamera *camera);
+
+void mv_camera_set_position(MvCamera *camera, double x, double y, double z);
+
+/**
+ *
+ * Turns the camera to the given pitch and yaw, in radians.
+ *
+ */
+void mv_camera_set_rotation(MvCamera *camera, double pitch, double yaw);
+
+/**
+ *
+ * Creates a renderer drawing images of the given size, with the default settings.
+ *
+ */
+MvRenderer *mv_renderer_new(size_t width, size_t height);
+
+void mv_renderer_free(MvRenderer *renderer);
+
+/**
+ *
+ * Clears the renderer's image and draws the world into it from the camera.
+ *
+ */
+bool mv_render_frame(MvRenderer *renderer, const MvWorld *world, const MvCamera *camera);
+
+/**
+ *
+ * Returns the renderer's image, as `width * height` pixels packed as 0RGB, row by row from the top left. The pointer
+ * is valid until the next frame is rendered.
+ *
+ */
+const uint32_t *mv_renderer_buffer(const MvRenderer *renderer);
+
+size_t mv_renderer_width(const MvRenderer *renderer);
+
+size_t mv_renderer_height(const MvRenderer *renderer);
+
+#endif  /* MEGAVERTEX_H */
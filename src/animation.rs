@@ -0,0 +1,363 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+use crate::{mat4::Mat4, transform::Transform, vec::vec3::Vec3};
+
+///
+/// A single procedural motion, evaluated from the world's time.
+/// - `Rotate` spins about `axis` at `speed` radians per second
+/// - `Oscillate` moves back and forth along `direction`, `amplitude` units either side of the rest position
+/// - `Pulse` scales uniformly between `1 - amplitude` and `1 + amplitude`
+///
+/// Frequencies are in cycles per second.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Channel {
+    Rotate {
+        axis: Vec3,
+        speed: f64,
+    },
+    Oscillate {
+        direction: Vec3,
+        amplitude: f64,
+        frequency: f64,
+    },
+    Pulse {
+        amplitude: f64,
+        frequency: f64,
+    },
+}
+
+impl Channel {
+    ///
+    /// Computes the channel's transformation at a given time.
+    ///
+    pub fn evaluate(&self, time: f64) -> Mat4 {
+        match *self {
+            Channel::Rotate { axis, speed } => Mat4::identity().rotate(axis, speed * time),
+            Channel::Oscillate {
+                direction,
+                amplitude,
+                frequency,
+            } => {
+                let offset = (2.0 * PI * frequency * time).sin() * amplitude;
+                Mat4::identity().translate(direction.normalise() * offset)
+            }
+            Channel::Pulse {
+                amplitude,
+                frequency,
+            } => {
+                let scale = 1.0 + (2.0 * PI * frequency * time).sin() * amplitude;
+                Mat4::identity().scale(Vec3::new(scale, scale, scale))
+            }
+        }
+    }
+}
+
+///
+/// A set of channels animating a single entity. Channels are applied in the order scale, rotation, translation,
+/// regardless of the order they were added in, so that e.g. a bobbing object spins about its own centre.
+///
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Animation {
+    pub channels: Vec<Channel>,
+}
+
+impl Animation {
+    pub fn new(channels: Vec<Channel>) -> Animation {
+        Animation { channels }
+    }
+
+    ///
+    /// Computes the combined transformation of every channel at a given time.
+    ///
+    pub fn evaluate(&self, time: f64) -> Mat4 {
+        let order = |channel: &Channel| match channel {
+            Channel::Pulse { .. } => 0,
+            Channel::Rotate { .. } => 1,
+            Channel::Oscillate { .. } => 2,
+        };
+
+        let mut channels: Vec<&Channel> = self.channels.iter().collect();
+        channels.sort_by_key(|channel| order(channel));
+
+        // Each matrix is multiplied on the left, so it's applied after those before it
+        channels.iter().fold(Mat4::identity(), |mat, channel| {
+            channel.evaluate(time).mult(mat)
+        })
+    }
+}
+
+///
+/// A transform an entity should have at a given time in a clip.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Keyframe {
+    pub time: f64,
+    pub transform: Transform,
+}
+
+///
+/// How a clip moves between keyframes.
+/// - `Step` holds each keyframe until the next one is reached
+/// - `Linear` blends smoothly from each keyframe to the next
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Interpolation {
+    Step,
+    Linear,
+}
+
+///
+/// What a clip does when it reaches its last keyframe.
+/// - `Once` holds the last keyframe
+/// - `Loop` starts again from the first keyframe
+/// - `PingPong` plays backwards to the first keyframe, then forwards again
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LoopMode {
+    Once,
+    Loop,
+    PingPong,
+}
+
+///
+/// A sequence of keyframes describing how an entity's transform changes over time.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationClip {
+    keyframes: Vec<Keyframe>,
+    pub interpolation: Interpolation,
+    pub loop_mode: LoopMode,
+}
+
+impl AnimationClip {
+    ///
+    /// Creates a clip from a set of keyframes, which don't need to be in order.
+    ///
+    pub fn new(
+        mut keyframes: Vec<Keyframe>,
+        interpolation: Interpolation,
+        loop_mode: LoopMode,
+    ) -> AnimationClip {
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        AnimationClip {
+            keyframes,
+            interpolation,
+            loop_mode,
+        }
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe] {
+        &self.keyframes
+    }
+
+    ///
+    /// The time of the last keyframe.
+    ///
+    pub fn duration(&self) -> f64 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    ///
+    /// Maps a playback time onto the clip's timeline according to its loop mode.
+    ///
+    fn local_time(&self, time: f64) -> f64 {
+        let duration = self.duration();
+        if duration <= 0.0 {
+            return 0.0;
+        }
+        match self.loop_mode {
+            LoopMode::Once => time.clamp(0.0, duration),
+            LoopMode::Loop => time.rem_euclid(duration),
+            LoopMode::PingPong => {
+                let t = time.rem_euclid(duration * 2.0);
+                if t > duration {
+                    duration * 2.0 - t
+                } else {
+                    t
+                }
+            }
+        }
+    }
+
+    ///
+    /// Computes the transform at a given playback time.
+    ///
+    pub fn sample(&self, time: f64) -> Transform {
+        let (Some(first), Some(last)) = (self.keyframes.first(), self.keyframes.last()) else {
+            return Transform::identity();
+        };
+
+        let time = self.local_time(time);
+        if time <= first.time {
+            return first.transform;
+        }
+        if time >= last.time {
+            return last.transform;
+        }
+
+        // Find the pair of keyframes either side of the current time
+        let next = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time > time)
+            .unwrap_or(self.keyframes.len() - 1);
+        let (from, to) = (self.keyframes[next - 1], self.keyframes[next]);
+
+        match self.interpolation {
+            Interpolation::Step => from.transform,
+            Interpolation::Linear => {
+                let t = (time - from.time) / (to.time - from.time);
+                from.transform.lerp(to.transform, t)
+            }
+        }
+    }
+}
+
+///
+/// Plays an `AnimationClip`, keeping track of the playback time.
+/// `speed` scales how quickly time passes in the clip - negative speeds play it in reverse.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClipPlayer {
+    pub clip: AnimationClip,
+    pub time: f64,
+    pub speed: f64,
+    pub playing: bool,
+}
+
+impl ClipPlayer {
+    ///
+    /// Creates a player at the start of a clip. Clips start playing straight away.
+    ///
+    pub fn new(clip: AnimationClip) -> ClipPlayer {
+        ClipPlayer {
+            clip,
+            time: 0.0,
+            speed: 1.0,
+            playing: true,
+        }
+    }
+
+    pub fn play(&mut self) {
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn seek(&mut self, time: f64) {
+        self.time = time;
+    }
+
+    ///
+    /// Moves playback forward by `delta`, if the clip is playing.
+    ///
+    pub fn advance(&mut self, delta: f64) {
+        if self.playing {
+            self.time += delta * self.speed;
+        }
+    }
+
+    ///
+    /// Returns true once a clip which doesn't loop has played to the end.
+    ///
+    pub fn is_finished(&self) -> bool {
+        self.clip.loop_mode == LoopMode::Once && self.time >= self.clip.duration()
+    }
+
+    pub fn sample(&self) -> Transform {
+        self.clip.sample(self.time)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::vec::vec3::{ORIGIN, X_AXIS, Y_AXIS};
+
+    #[test]
+    fn oscillate_peaks() {
+        let channel = Channel::Oscillate {
+            direction: Y_AXIS,
+            amplitude: 2.0,
+            frequency: 1.0,
+        };
+        let result = channel.evaluate(0.25).transform(ORIGIN);
+        assert!((result - Vec3::new(0.0, 2.0, 0.0)).length() < 1e-9);
+    }
+
+    fn slide() -> AnimationClip {
+        AnimationClip::new(
+            vec![
+                Keyframe {
+                    time: 2.0,
+                    transform: Transform::from_translation(Vec3::new(2.0, 0.0, 0.0)),
+                },
+                Keyframe {
+                    time: 0.0,
+                    transform: Transform::identity(),
+                },
+            ],
+            Interpolation::Linear,
+            LoopMode::Once,
+        )
+    }
+
+    #[test]
+    fn sample_linear_clip() {
+        let clip = slide();
+        assert_eq!(clip.duration(), 2.0);
+        assert_eq!(clip.sample(1.0).translation, Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(clip.sample(5.0).translation, Vec3::new(2.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn sample_step_clip() {
+        let mut clip = slide();
+        clip.interpolation = Interpolation::Step;
+        assert_eq!(clip.sample(1.5).translation, ORIGIN);
+    }
+
+    #[test]
+    fn loop_modes() {
+        let mut clip = slide();
+        clip.loop_mode = LoopMode::Loop;
+        assert_eq!(clip.sample(2.5).translation, Vec3::new(0.5, 0.0, 0.0));
+
+        clip.loop_mode = LoopMode::PingPong;
+        assert_eq!(clip.sample(2.5).translation, Vec3::new(1.5, 0.0, 0.0));
+    }
+
+    #[test]
+    fn paused_player_holds_time() {
+        let mut player = ClipPlayer::new(slide());
+        player.advance(1.0);
+        player.pause();
+        player.advance(1.0);
+        assert_eq!(player.time, 1.0);
+
+        player.seek(2.0);
+        assert!(player.is_finished());
+    }
+
+    #[test]
+    fn channels_apply_in_order() {
+        // Rotating after oscillating would swing the offset round, so the rotation must happen first
+        let animation = Animation::new(vec![
+            Channel::Oscillate {
+                direction: X_AXIS,
+                amplitude: 1.0,
+                frequency: 1.0,
+            },
+            Channel::Rotate {
+                axis: Y_AXIS,
+                speed: PI,
+            },
+        ]);
+        let result = animation.evaluate(0.25).transform(ORIGIN);
+        assert!((result - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+    }
+}
@@ -0,0 +1,400 @@
+use std::time::{Duration, Instant};
+
+use minifb::{Key, KeyRepeat};
+
+use crate::{
+    backend::{self, Backend},
+    camera::Camera,
+    config::{Config, ConfigError, RendererConfig, WindowConfig},
+    error::Error,
+    input::{Bindings, Input},
+    palette::Palette,
+    recording::{RecordMode, RecordedFrame, Recording},
+    renderer::Renderer,
+    timing::{DynamicResolution, FramePacing, FrameStats, TimeControl},
+    vec::{vec2::Vec2, vec3::Vec3},
+    world::World,
+};
+
+///
+/// How `run` runs an app.
+/// - `window` is the window that's opened, and `renderer` is how the world is drawn into it. The window is the
+///   renderer's size, before its render scale is applied
+/// - `exit_key` closes the window when pressed. With no exit key, the window stays open until it's closed
+/// - `bindings` are the actions the app's `Input` starts with, which it can change while running
+/// - `update_rate` is how many times a second the world and the app are updated, however fast frames are drawn
+/// - `max_updates` is how many updates are run at most for one frame. If a frame takes longer than that, the rest of
+///   its time is dropped and the app slows down, rather than falling further and further behind
+/// - `target_fps` is how many frames are drawn a second at most. Waiting between frames leaves the CPU free when the
+///   scene is quick to draw, rather than drawing it as fast as possible. With no target, frames aren't limited
+/// - `pacing` is how the wait between frames is done
+/// - `time` is how fast time passes when the app starts, and `time_keys` are the keys for pausing, stepping, slowing
+///   down and speeding it up while it runs
+/// - `record` records the input the app is run with to a file, or replays a recording
+/// - `seed` is handed to the app to seed anything random. With no seed, one is picked from the clock. Replays use the
+///   recording's seed instead
+/// - `deterministic` takes the clock out of the app entirely. Every frame moves time on by exactly one update, however
+///   long it took, and with no seed the seed is 0, so the same input draws the same frames, pixel for pixel, every run.
+///   Time still runs at the speed set by `time`, and frames are still paced by `target_fps`
+/// - `dynamic_resolution` changes the renderer's render scale as the app runs to hold a frame rate. It's left alone in
+///   deterministic runs, where frames have to be drawn the same however long they take
+/// - `palette` reduces every frame to a fixed set of colours, cycling them with the world's time. Overlays are drawn
+///   over the reduced frame in full colour
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppConfig {
+    pub window: WindowConfig,
+    pub renderer: RendererConfig,
+    pub exit_key: Option<Key>,
+    pub bindings: Bindings,
+    pub update_rate: f64,
+    pub max_updates: u32,
+    pub target_fps: Option<f64>,
+    pub pacing: FramePacing,
+    pub time: TimeControl,
+    pub time_keys: TimeKeys,
+    pub record: RecordMode,
+    pub seed: Option<u64>,
+    pub deterministic: bool,
+    pub dynamic_resolution: Option<DynamicResolution>,
+    pub palette: Option<Palette>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            window: WindowConfig::default(),
+            renderer: RendererConfig::default(),
+            exit_key: Some(Key::Escape),
+            bindings: Bindings::default(),
+            update_rate: 60.0,
+            max_updates: 5,
+            target_fps: Some(60.0),
+            pacing: FramePacing::default(),
+            time: TimeControl::default(),
+            time_keys: TimeKeys::default(),
+            record: RecordMode::default(),
+            seed: None,
+            deterministic: false,
+            dynamic_resolution: None,
+            palette: None,
+        }
+    }
+}
+
+///
+/// The keys `run` controls time with, for debugging animation and physics. Any of them can be turned off with `None`.
+/// - `pause` pauses time, and starts it again
+/// - `step` runs a single update while the rest of the app is paused, pausing it first if it isn't. Holding it down
+///   keeps stepping
+/// - `slower` and `faster` change how fast time passes, between 0.1x and 2x
+///
+/// The window shows when time is paused or running at another speed.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TimeKeys {
+    pub pause: Option<Key>,
+    pub step: Option<Key>,
+    pub slower: Option<Key>,
+    pub faster: Option<Key>,
+}
+
+impl Default for TimeKeys {
+    fn default() -> Self {
+        TimeKeys {
+            pause: Some(Key::F5),
+            step: Some(Key::F6),
+            slower: Some(Key::F7),
+            faster: Some(Key::F8),
+        }
+    }
+}
+
+impl AppConfig {
+    ///
+    /// Loads the window and renderer settings from a TOML file, as described by `Config`, leaving everything else at
+    /// its default.
+    ///
+    pub fn load(path: &str) -> Result<AppConfig, ConfigError> {
+        let Config { window, renderer } = Config::load(path)?;
+        Ok(AppConfig {
+            window,
+            renderer,
+            ..AppConfig::default()
+        })
+    }
+}
+
+///
+/// A program run by `run`, which owns the window, world, camera and renderer, and calls the app's methods each frame.
+///
+/// The app runs at a fixed rate, set by `AppConfig::update_rate`, so it behaves the same however fast frames are
+/// drawn. Each frame the physics simulation is advanced by the time the frame took, then the world and the app's
+/// `update` are run as many times as fit in it, which may be none at all on fast machines. Whatever time is left over
+/// is carried to the next frame. The world is then drawn from the camera, with the camera and every entity blended
+/// between where they were at the last two updates by how far the frame falls between them, and the app can draw over
+/// it with `draw_overlay`. Since the
+/// world's debug lines are cleared by its own update, lines added in the app's `update` are drawn until the next one.
+/// Time can also be paused, slowed down, sped up and stepped through one update at a time with `AppConfig::time_keys`,
+/// which scales the time the physics simulation and updates see while frames carry on being drawn.
+///
+pub trait App {
+    ///
+    /// Called before `setup` with the seed for anything random in the app, so runs can be replayed exactly. See
+    /// `AppConfig::seed`.
+    ///
+    fn set_seed(&mut self, _seed: u64) {}
+
+    ///
+    /// Fills the world and places the camera before the first frame. Errors stop the app before the window opens.
+    ///
+    fn setup(
+        &mut self,
+        world: &mut World,
+        camera: &mut Camera,
+    ) -> Result<(), Box<dyn std::error::Error>>;
+
+    ///
+    /// Runs the app's logic for one update. `delta` is the time between updates in seconds, as given to
+    /// `World::update`, and is the same every time. Keys pressed and let go since the last update are reported once,
+    /// even if several frames were drawn in between. The input's bindings can be changed here, such as from a controls
+    /// menu, and stay changed for the following updates.
+    ///
+    fn update(&mut self, world: &mut World, camera: &mut Camera, input: &mut Input, delta: f64);
+
+    ///
+    /// Draws over the rendered scene, for text and other overlays. `stats` holds how long recent frames took, for
+    /// showing the frame rate.
+    ///
+    fn draw_overlay(&mut self, _renderer: &mut Renderer, _stats: &FrameStats) {}
+
+    ///
+    /// Called each frame before the world is drawn, with how far the frame falls between the last update and the
+    /// next, from 0 to 1. Apps can blend anything they draw themselves by it to keep its motion smooth when frames
+    /// are drawn faster than updates. The camera and the world's entities are already blended for them.
+    ///
+    fn interpolate(&mut self, _world: &mut World, _alpha: f64) {}
+}
+
+///
+/// Counts out fixed length updates from the time frames take, in the same way as the physics simulation's steps.
+///
+struct UpdateClock {
+    timestep: f64,
+    max_updates: u32,
+    accumulator: f64,
+}
+
+impl UpdateClock {
+    fn new(rate: f64, max_updates: u32) -> UpdateClock {
+        UpdateClock {
+            timestep: 1.0 / rate,
+            max_updates,
+            accumulator: 0.0,
+        }
+    }
+
+    ///
+    /// Adds a frame's time, in seconds, and returns how many updates should be run to catch up.
+    ///
+    fn take_updates(&mut self, elapsed: f64) -> u32 {
+        if self.timestep <= 0.0 || !self.timestep.is_finite() {
+            return 0;
+        }
+        self.accumulator += elapsed.max(0.0);
+        let mut updates = 0;
+        while self.accumulator >= self.timestep && updates < self.max_updates {
+            self.accumulator -= self.timestep;
+            updates += 1;
+        }
+        if self.accumulator >= self.timestep {
+            self.accumulator = 0.0;
+        }
+        updates
+    }
+
+    fn alpha(&self) -> f64 {
+        if self.timestep <= 0.0 || !self.timestep.is_finite() {
+            return 1.0;
+        }
+        (self.accumulator / self.timestep).clamp(0.0, 1.0)
+    }
+}
+
+///
+/// Opens a window and runs an app in it until the window is closed or the exit key is pressed. An error from the app's
+/// `setup` is returned as `Error::AppError`.
+///
+pub fn run(config: AppConfig, mut app: impl App) -> Result<(), Error> {
+    let mut world = World::new();
+    let mut camera = Camera::new(Vec3::default());
+    let replay = match &config.record {
+        RecordMode::Replay(path) => Some(Recording::load(path)?),
+        _ => None,
+    };
+    let seed = replay
+        .as_ref()
+        .map(|replay| replay.seed)
+        .or(config.seed)
+        .or(config.deterministic.then_some(0));
+    let mut recording = Recording::new(seed);
+    let mut replay = replay.map(|replay| replay.frames.into_iter());
+    app.set_seed(recording.seed);
+    app.setup(&mut world, &mut camera)
+        .map_err(Error::AppError)?;
+
+    let mut window = backend::open(&config)?;
+    let mut renderer = Renderer::new(config.renderer);
+    renderer.set_palette(config.palette.clone());
+    let mut input = Input::new(config.bindings.clone());
+
+    let mut clock = UpdateClock::new(config.update_rate, config.max_updates);
+    let mut time = config.time;
+    // Where the camera was before the last update, to blend from when drawing
+    let mut previous_camera = camera;
+    let mut cursor_captured = false;
+    let mut stats = FrameStats::new();
+    let mut resolution = config.dynamic_resolution.filter(|_| !config.deterministic);
+    let mut last_frame = Instant::now();
+    let mut frame_due = last_frame;
+    loop {
+        window.poll_events();
+        if !window.is_open() || config.exit_key.is_some_and(|key| window.is_key_down(key)) {
+            break;
+        }
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("frame").entered();
+
+        let now = Instant::now();
+        let elapsed = if config.deterministic {
+            Duration::try_from_secs_f64(1.0 / config.update_rate).unwrap_or_default()
+        } else {
+            now.duration_since(last_frame)
+        };
+        stats.record(elapsed);
+        let elapsed = elapsed.as_secs_f64();
+        last_frame = now;
+
+        renderer.clear();
+        // Replays use the recorded time and input rather than the window's, so time keys are ignored while replaying
+        let frame = match &mut replay {
+            Some(frames) => match frames.next() {
+                Some(frame) => frame,
+                None => break,
+            },
+            None => {
+                let keys = config.time_keys;
+                if is_key_pressed(&*window, keys.pause, KeyRepeat::No) {
+                    time.toggle_pause();
+                }
+                if is_key_pressed(&*window, keys.step, KeyRepeat::Yes) {
+                    time.step();
+                }
+                if is_key_pressed(&*window, keys.slower, KeyRepeat::No) {
+                    time.slower();
+                }
+                if is_key_pressed(&*window, keys.faster, KeyRepeat::No) {
+                    time.faster();
+                }
+                RecordedFrame {
+                    elapsed: time.scaled(elapsed),
+                    step: time.take_step(),
+                    input: window.read_input(),
+                }
+            }
+        };
+        if let RecordMode::Record(_) = config.record {
+            recording.frames.push(frame.clone());
+        }
+
+        world.advance_physics(frame.elapsed);
+        let mut updates = clock.take_updates(frame.elapsed);
+        if frame.step {
+            // A step is one update's worth of time, however long the frame took
+            world.advance_physics(clock.timestep);
+            updates += 1;
+        }
+
+        input.update(frame.input);
+        for _ in 0..updates {
+            previous_camera = camera;
+            world.update(clock.timestep);
+            app.update(&mut world, &mut camera, &mut input, clock.timestep);
+            input.clear_edges();
+        }
+        if input.capture_mouse != cursor_captured {
+            cursor_captured = input.capture_mouse;
+            window.set_cursor_captured(cursor_captured);
+        }
+
+        let alpha = clock.alpha();
+        world.set_update_alpha(alpha);
+        app.interpolate(&mut world, alpha);
+        previous_camera
+            .interpolate(camera, alpha)
+            .render_world(&mut renderer, &world);
+        app.draw_overlay(&mut renderer, &stats);
+        let speed = if replay.is_some() {
+            Some("replaying".to_string())
+        } else if time.is_paused() {
+            Some("paused".to_string())
+        } else {
+            (time.scale() != 1.0).then(|| format!("{}x", time.scale()))
+        };
+        if let Some(speed) = speed {
+            let bottom_left = Vec2::new(5.0, renderer.height() as f64 - 20.0);
+            renderer.write_text(&speed, bottom_left, 14.0);
+        }
+        {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("present").entered();
+            window.present_frame(&renderer)?;
+        }
+        if let Some(resolution) = &mut resolution {
+            renderer.set_render_scale(resolution.record(now.elapsed()));
+        }
+
+        if let Some(fps) = config.target_fps.filter(|fps| *fps > 0.0) {
+            frame_due = FramePacing::next_frame(
+                frame_due,
+                Instant::now(),
+                Duration::from_secs_f64(1.0 / fps),
+            );
+            #[cfg(feature = "tracing")]
+            let _span = tracing::trace_span!("wait").entered();
+            config.pacing.wait_until(frame_due);
+        }
+    }
+
+    if let RecordMode::Record(path) = &config.record {
+        recording.save(path)?;
+    }
+    Ok(())
+}
+
+fn is_key_pressed(window: &dyn Backend, key: Option<Key>, repeat: KeyRepeat) -> bool {
+    key.is_some_and(|key| window.is_key_pressed(key, repeat))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fixed_updates() {
+        let mut clock = UpdateClock::new(50.0, 4);
+        assert!((clock.timestep - 0.02).abs() < 1e-9);
+
+        // Fast frames build up until there's enough for an update
+        assert_eq!(clock.take_updates(0.015), 0);
+        assert!((clock.alpha() - 0.75).abs() < 1e-9);
+        assert_eq!(clock.take_updates(0.015), 1);
+        assert!((clock.alpha() - 0.5).abs() < 1e-9);
+
+        // Slow frames run several, and frames too slow to catch up with are dropped
+        assert_eq!(clock.take_updates(0.04), 2);
+        assert!((clock.alpha() - 0.5).abs() < 1e-9);
+        assert_eq!(clock.take_updates(1.0), 4);
+        assert_eq!(clock.alpha(), 0.0);
+    }
+}
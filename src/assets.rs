@@ -0,0 +1,353 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use crate::object::{ModelLoadError, Object, TextureFormat, TextureLayout};
+
+#[cfg(feature = "hot-reload")]
+use std::{fs, time::SystemTime};
+
+///
+/// Loads and caches objects by name. Names follow the same convention as `Object::from_obj` - the path to the model
+/// without its extension.
+///
+/// Models can be loaded synchronously with `load`, or on a worker thread with `load_async`, which returns an
+/// `AssetHandle` straight away so large models don't freeze the window while they're parsed. Browsers have no
+/// threads or files, so on the web models are loaded from bytes and added with `insert` instead.
+///
+/// Objects loaded on a worker thread join the cache once they've finished, the next time the manager is used or
+/// `poll_loads` is called. Until then, asking for the same object again shares the load already under way rather than
+/// starting another.
+///
+/// `with_texture_storage` converts the textures of every model loaded from then on, e.g. to keep large textures in
+/// half the memory with `TextureFormat::Rgb565`.
+///
+#[derive(Default)]
+pub struct AssetManager {
+    cache: HashMap<String, Object>,
+    loading: HashMap<String, AssetHandle>,
+    finished: Arc<Mutex<Vec<(String, Object)>>>,
+    texture_storage: Option<(TextureFormat, TextureLayout)>,
+    #[cfg(feature = "hot-reload")]
+    modified: HashMap<String, Option<SystemTime>>,
+}
+
+impl AssetManager {
+    pub fn new() -> AssetManager {
+        AssetManager::default()
+    }
+
+    pub fn with_texture_storage(
+        mut self,
+        format: TextureFormat,
+        layout: TextureLayout,
+    ) -> AssetManager {
+        self.texture_storage = Some((format, layout));
+        self
+    }
+
+    ///
+    /// Loads an object, returning a copy of the cached version if it has been loaded before.
+    ///
+    pub fn load(&mut self, name: &str) -> Result<Object, ModelLoadError> {
+        self.poll_loads();
+        if let Some(object) = self.cache.get(name) {
+            return Ok(object.clone());
+        }
+        let object = load_object(name, self.texture_storage)?;
+        self.cache_object(name, object.clone());
+        Ok(object)
+    }
+
+    ///
+    /// Adds every object which has finished loading on a worker thread to the cache, so later loads of it return the
+    /// cached version, and it's watched by `poll_changes`.
+    ///
+    pub fn poll_loads(&mut self) {
+        let finished = match self.finished.lock() {
+            Ok(mut finished) => std::mem::take(&mut *finished),
+            Err(_) => return,
+        };
+        for (name, object) in finished {
+            self.cache_object(&name, object);
+        }
+        // Failed loads are forgotten too, so asking for them again tries again
+        self.loading.retain(|_, handle| handle.is_loading());
+    }
+
+    fn cache_object(&mut self, name: &str, object: Object) {
+        self.cache.insert(String::from(name), object);
+        #[cfg(feature = "hot-reload")]
+        self.modified
+            .insert(String::from(name), last_modified(name));
+    }
+
+    ///
+    /// Starts loading an object on a worker thread. If the object is already cached, the handle resolves immediately,
+    /// and if it's still being loaded, the handle shares that load.
+    ///
+    pub fn load_async(&mut self, name: &str) -> AssetHandle {
+        self.poll_loads();
+        if let Some(handle) = self.loading.get(name) {
+            return handle.clone();
+        }
+
+        let handle = AssetHandle::new(name);
+        if let Some(object) = self.cache.get(name) {
+            handle.resolve(Ok(object.clone()));
+            return handle;
+        }
+        self.loading.insert(String::from(name), handle.clone());
+
+        let worker_handle = handle.clone();
+        let finished = self.finished.clone();
+        let name = String::from(name);
+        let storage = self.texture_storage;
+        let load = move || {
+            let result = load_object(&name, storage);
+            if let (Ok(object), Ok(mut finished)) = (&result, finished.lock()) {
+                finished.push((name, object.clone()));
+            }
+            worker_handle.resolve(result);
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        std::thread::spawn(load);
+        // There are no threads to load on, so the object is loaded straight away
+        #[cfg(target_arch = "wasm32")]
+        load();
+
+        handle
+    }
+
+    ///
+    /// Adds an object that was loaded some other way (e.g. with `Object::from_obj_bytes`) to the cache, so later loads
+    /// of `name` return it without touching the filesystem.
+    ///
+    pub fn insert(&mut self, name: &str, object: Object) {
+        self.cache.insert(String::from(name), object);
+    }
+
+    ///
+    /// Checks every cached object's model and texture files for changes, reloading any that have been modified.
+    ///
+    /// Returns the reloaded objects, which can be swapped into the world with `World::reload`. Objects that fail to
+    /// reload (e.g. because the file was saved half-written) keep their previous version and are retried next poll.
+    ///
+    #[cfg(feature = "hot-reload")]
+    pub fn poll_changes(&mut self) -> Vec<Object> {
+        self.poll_loads();
+        let mut reloaded = vec![];
+
+        for (name, modified) in self.modified.iter_mut() {
+            let current = last_modified(name);
+            if current == *modified {
+                continue;
+            }
+            match load_object(name, self.texture_storage) {
+                Ok(object) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::info!(asset = name.as_str(), "reloaded asset");
+                    *modified = current;
+                    self.cache.insert(name.clone(), object.clone());
+                    reloaded.push(object);
+                }
+                // The file may still be being written, so it's tried again on the next poll
+                #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+                Err(error) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(asset = name.as_str(), %error, "failed to reload asset");
+                }
+            }
+        }
+
+        reloaded
+    }
+
+    ///
+    /// Returns the names of every object in the cache.
+    ///
+    pub fn loaded(&self) -> impl Iterator<Item = &str> {
+        self.cache.keys().map(|name| name.as_str())
+    }
+}
+
+///
+/// Loads an object from its files, converting its textures to the manager's storage if one has been chosen.
+///
+fn load_object(
+    name: &str,
+    storage: Option<(TextureFormat, TextureLayout)>,
+) -> Result<Object, ModelLoadError> {
+    let mut object = Object::from_obj(name)?;
+    if let Some((format, layout)) = storage {
+        object.convert_textures(format, layout);
+    }
+    Ok(object)
+}
+
+///
+/// Returns the latest modification time of an object's model and texture files.
+///
+#[cfg(feature = "hot-reload")]
+fn last_modified(name: &str) -> Option<SystemTime> {
+    let modified = |path: String| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    modified(String::from(name) + ".obj").max(modified(String::from(name) + ".png"))
+}
+
+///
+/// A shared handle to an object being loaded in the background. Clones of a handle share the same load, and each can
+/// take its own copy of the result.
+///
+pub struct AssetHandle {
+    name: String,
+    result: Arc<Mutex<AssetState>>,
+    taken: AtomicBool,
+}
+
+enum AssetState {
+    Loading,
+    Finished(Box<Result<Object, ModelLoadError>>),
+}
+
+impl Clone for AssetHandle {
+    fn clone(&self) -> Self {
+        AssetHandle {
+            name: self.name.clone(),
+            result: self.result.clone(),
+            taken: AtomicBool::new(false),
+        }
+    }
+}
+
+impl AssetHandle {
+    fn new(name: &str) -> AssetHandle {
+        AssetHandle {
+            name: String::from(name),
+            result: Arc::new(Mutex::new(AssetState::Loading)),
+            taken: AtomicBool::new(false),
+        }
+    }
+
+    fn resolve(&self, result: Result<Object, ModelLoadError>) {
+        if let Ok(mut state) = self.result.lock() {
+            *state = AssetState::Finished(Box::new(result));
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    ///
+    /// Returns true while the worker thread is still loading the object.
+    ///
+    pub fn is_loading(&self) -> bool {
+        matches!(self.result.lock().as_deref(), Ok(AssetState::Loading))
+    }
+
+    ///
+    /// Takes the result of the load if it has finished. The result can only be taken once from each handle -
+    /// subsequent calls return `None`.
+    ///
+    pub fn take(&self) -> Option<Result<Object, ModelLoadError>> {
+        let state = self.result.lock().ok()?;
+        match &*state {
+            AssetState::Finished(result) if !self.taken.swap(true, Ordering::Relaxed) => {
+                Some((**result).clone())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn missing_asset_fails() {
+        let mut assets = AssetManager::new();
+        assert!(assets.load("./resources/does-not-exist").is_err());
+    }
+
+    #[test]
+    fn inserted_asset_loads() {
+        let mut assets = AssetManager::new();
+        let cube = Object::from_obj_bytes(
+            include_bytes!("../resources/cube.obj"),
+            include_bytes!("../resources/cube.png"),
+        )
+        .unwrap();
+        assets.insert("cube", cube);
+
+        assert!(assets.load("cube").is_ok());
+        assert!(matches!(assets.load_async("cube").take(), Some(Ok(_))));
+    }
+
+    #[test]
+    fn converts_textures() {
+        let mut assets =
+            AssetManager::new().with_texture_storage(TextureFormat::Rgb565, TextureLayout::Tiled);
+        let cube = assets.load("./resources/cube").unwrap();
+        assert_eq!(cube.texture.format(), TextureFormat::Rgb565);
+        assert_eq!(cube.texture.layout(), TextureLayout::Tiled);
+    }
+
+    #[test]
+    fn async_load_resolves() {
+        let mut assets = AssetManager::new();
+        let handle = assets.load_async("./resources/does-not-exist");
+
+        while handle.is_loading() {
+            std::thread::yield_now();
+        }
+        assert!(matches!(handle.take(), Some(Err(_))));
+        assert!(handle.take().is_none());
+        assets.poll_loads();
+        assert_eq!(assets.loaded().count(), 0);
+    }
+
+    #[test]
+    fn async_loads_are_shared() {
+        let mut assets = AssetManager::new();
+        let first = assets.load_async("./resources/cube");
+        let second = assets.load_async("./resources/cube");
+        assert_eq!(assets.loading.len(), 1);
+        while first.is_loading() {
+            std::thread::yield_now();
+        }
+
+        // Both handles get the object, which was only parsed once
+        let (first, second) = (
+            first.take().unwrap().unwrap(),
+            second.take().unwrap().unwrap(),
+        );
+        assert!(Arc::ptr_eq(&first.vertices, &second.vertices));
+        assets.poll_loads();
+        assert!(assets.loading.is_empty());
+    }
+
+    #[test]
+    fn async_loads_are_cached() {
+        let mut assets = AssetManager::new();
+        let handle = assets.load_async("./resources/cube");
+        while handle.is_loading() {
+            std::thread::yield_now();
+        }
+        let loaded = handle.take().unwrap().unwrap();
+
+        // Loading it again comes straight from the cache, even once the handle's result has been taken
+        assets.poll_loads();
+        assert_eq!(assets.loaded().collect::<Vec<_>>(), ["./resources/cube"]);
+        let cached = assets.load("./resources/cube").unwrap();
+        assert_eq!(cached.vertices, loaded.vertices);
+        assert!(matches!(
+            assets.load_async("./resources/cube").take(),
+            Some(Ok(_))
+        ));
+    }
+}
@@ -0,0 +1,63 @@
+pub mod minifb;
+#[cfg(feature = "sdl2")]
+pub mod sdl2;
+#[cfg(feature = "winit")]
+pub mod winit;
+
+use ::minifb::{Key, KeyRepeat};
+
+use crate::{
+    app::AppConfig, config::WindowBackend, error::Error, input::InputFrame, present::PresentTarget,
+};
+
+///
+/// A window that `run` shows frames in and reads the keyboard and mouse from. Each frame `run` calls `poll_events`
+/// first, then reads input, and finally presents the rendered image, which is stretched to fill the window.
+///
+pub trait Backend: PresentTarget {
+    ///
+    /// Handles whatever has happened to the window since the last frame.
+    ///
+    fn poll_events(&mut self);
+
+    fn is_open(&self) -> bool;
+
+    fn is_key_down(&self, key: Key) -> bool;
+
+    ///
+    /// Returns whether a key was pressed since the last frame, counting the key repeating while it's held down if
+    /// `repeat` is `KeyRepeat::Yes`.
+    ///
+    fn is_key_pressed(&self, key: Key, repeat: KeyRepeat) -> bool;
+
+    ///
+    /// Reads the keyboard and mouse as they are this frame.
+    ///
+    fn read_input(&mut self) -> InputFrame;
+
+    ///
+    /// Hides the cursor and keeps it in the window for first person controls, or lets it go again.
+    ///
+    fn set_cursor_captured(&mut self, captured: bool);
+}
+
+///
+/// Opens the window chosen by `AppConfig::window`.
+///
+pub fn open(config: &AppConfig) -> Result<Box<dyn Backend>, Error> {
+    match config.window.backend {
+        WindowBackend::Minifb => Ok(Box::new(minifb::MinifbWindow::open(config)?)),
+        #[cfg(feature = "winit")]
+        WindowBackend::Winit => Ok(Box::new(winit::WinitWindow::open(config)?)),
+        #[cfg(not(feature = "winit"))]
+        WindowBackend::Winit => Err(Error::window(
+            "the winit backend needs megavertex's winit feature",
+        )),
+        #[cfg(feature = "sdl2")]
+        WindowBackend::Sdl2 => Ok(Box::new(sdl2::Sdl2Window::open(config)?)),
+        #[cfg(not(feature = "sdl2"))]
+        WindowBackend::Sdl2 => Err(Error::window(
+            "the sdl2 backend needs megavertex's sdl2 feature",
+        )),
+    }
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use minifb::{Key, KeyRepeat, Scale, ScaleMode, Window, WindowOptions};
+
+use crate::{
+    app::AppConfig, backend::Backend, config::WindowScale, error::Error, input::InputFrame,
+    present::PresentTarget,
+};
+
+// How often the window is shown with vsync on, as it can't find out how often the display refreshes
+const VSYNC_RATE: f64 = 60.0;
+
+///
+/// A window opened with minifb, which is simple and has few dependencies, but doesn't handle high DPI displays.
+///
+pub struct MinifbWindow {
+    window: Window,
+}
+
+impl MinifbWindow {
+    pub fn open(config: &AppConfig) -> Result<MinifbWindow, Error> {
+        let mut window = Window::new(
+            &config.window.title,
+            config.renderer.width,
+            config.renderer.height,
+            WindowOptions {
+                borderless: false,
+                transparency: false,
+                title: true,
+                resize: config.window.resizable,
+                scale: match config.window.scale {
+                    WindowScale::X1 => Scale::X1,
+                    WindowScale::X2 => Scale::X2,
+                    WindowScale::X4 => Scale::X4,
+                    WindowScale::X8 => Scale::X8,
+                    WindowScale::FitScreen => Scale::FitScreen,
+                },
+                scale_mode: ScaleMode::Stretch,
+                topmost: false,
+                none: false,
+            },
+        )
+        .map_err(Error::window)?;
+        // Frames are paced by `run` rather than by the window, so they can be uncapped, unless they're held to the
+        // display
+        let vsync = config
+            .renderer
+            .vsync
+            .then(|| Duration::from_secs_f64(1.0 / VSYNC_RATE));
+        window.limit_update_rate(vsync);
+        Ok(MinifbWindow { window })
+    }
+}
+
+impl Backend for MinifbWindow {
+    // minifb handles the window's events as frames are presented
+    fn poll_events(&mut self) {}
+
+    fn is_open(&self) -> bool {
+        self.window.is_open()
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.window.is_key_down(key)
+    }
+
+    fn is_key_pressed(&self, key: Key, repeat: KeyRepeat) -> bool {
+        self.window.is_key_pressed(key, repeat)
+    }
+
+    fn read_input(&mut self) -> InputFrame {
+        InputFrame::read(&mut self.window)
+    }
+
+    fn set_cursor_captured(&mut self, captured: bool) {
+        self.window.set_cursor_visibility(!captured);
+    }
+}
+
+impl PresentTarget for MinifbWindow {
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize) -> Result<(), Error> {
+        self.window
+            .update_with_buffer(buffer, width, height)
+            .map_err(Error::window)
+    }
+}
@@ -0,0 +1,401 @@
+use minifb::{Key, KeyRepeat, MouseButton};
+use sdl2::{
+    event::{Event, WindowEvent},
+    keyboard::Scancode,
+    mouse::MouseButton as SdlButton,
+    pixels::PixelFormatEnum,
+    render::{Canvas, Texture},
+    video::Window,
+    EventPump, Sdl,
+};
+
+use crate::{
+    app::AppConfig, backend::Backend, config::WindowScale, error::Error, input::InputFrame,
+    present::PresentTarget, vec::vec2::Vec2,
+};
+
+///
+/// A window opened with SDL2, with frames copied into a streaming texture that SDL stretches to fill it. SDL talks to
+/// Wayland and X11 directly, so it works on setups where minifb doesn't, and like winit it handles high DPI displays
+/// and can really capture the mouse.
+///
+/// SDL can only be started once at a time, so only one window can be opened this way in a program.
+///
+pub struct Sdl2Window {
+    // SDL shuts down when its context is dropped, so it's kept for as long as the window
+    sdl: Sdl,
+    events: EventPump,
+    canvas: Canvas<Window>,
+    // Created by the first frame, and again whenever the frame size changes
+    texture: Option<(Texture, usize, usize)>,
+    // The frame as bytes, kept between frames to save reallocating them
+    bytes: Vec<u8>,
+    open: bool,
+    focused: bool,
+    // The size of the image before the render scale, which mouse positions are measured in
+    size: (usize, usize),
+    down: Vec<Key>,
+    pressed: Vec<Key>,
+    released: Vec<Key>,
+    // Keys pressed along with keys repeating while they're held down
+    repeated: Vec<Key>,
+    mouse: Option<Vec2>,
+    // Every movement of the mouse added up, so it can be followed however far it goes while it's captured
+    cursor: Vec2,
+    buttons: Vec<MouseButton>,
+    scroll: f64,
+}
+
+impl Sdl2Window {
+    pub fn open(config: &AppConfig) -> Result<Sdl2Window, Error> {
+        let sdl = sdl2::init().map_err(Error::window)?;
+        let video = sdl.video().map_err(Error::window)?;
+        let (width, height) = (config.renderer.width, config.renderer.height);
+
+        let scale = match config.window.scale {
+            WindowScale::X1 => 1,
+            WindowScale::X2 => 2,
+            WindowScale::X4 => 4,
+            WindowScale::X8 => 8,
+            WindowScale::FitScreen => {
+                let screen = video.desktop_display_mode(0).map_err(Error::window)?;
+                let fits = |scale: &usize| {
+                    (width * scale) as i32 <= screen.w && (height * scale) as i32 <= screen.h
+                };
+                [8, 4, 2].into_iter().find(fits).unwrap_or(1)
+            }
+        };
+        let mut window = video.window(
+            &config.window.title,
+            (width * scale) as u32,
+            (height * scale) as u32,
+        );
+        window.position_centered().allow_highdpi();
+        if config.window.resizable {
+            window.resizable();
+        }
+
+        let mut canvas = window.build().map_err(Error::window)?.into_canvas();
+        if config.renderer.vsync {
+            canvas = canvas.present_vsync();
+        }
+        Ok(Sdl2Window {
+            events: sdl.event_pump().map_err(Error::window)?,
+            sdl,
+            canvas: canvas.build().map_err(Error::window)?,
+            texture: None,
+            bytes: vec![],
+            open: true,
+            focused: true,
+            size: (width, height),
+            down: vec![],
+            pressed: vec![],
+            released: vec![],
+            repeated: vec![],
+            mouse: None,
+            cursor: Vec2::default(),
+            buttons: vec![],
+            scroll: 0.0,
+        })
+    }
+
+    ///
+    /// Turns a position in the window into one in the image's pixels.
+    ///
+    fn to_image(&self, x: i32, y: i32) -> Option<Vec2> {
+        let (width, height) = self.canvas.window().size();
+        if width == 0 || height == 0 {
+            return None;
+        }
+        Some(Vec2::new(
+            x as f64 * self.size.0 as f64 / width as f64,
+            y as f64 * self.size.1 as f64 / height as f64,
+        ))
+    }
+
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Quit { .. } => self.open = false,
+            Event::Window { win_event, .. } => match win_event {
+                WindowEvent::Close => self.open = false,
+                WindowEvent::FocusGained => self.focused = true,
+                WindowEvent::FocusLost => {
+                    self.focused = false;
+                    // Keys let go of while the window was in the background would otherwise be stuck down
+                    self.released.append(&mut self.down);
+                }
+                WindowEvent::Leave => self.mouse = None,
+                _ => {}
+            },
+            Event::KeyDown {
+                scancode, repeat, ..
+            } => {
+                let Some(key) = scancode.and_then(to_key) else {
+                    return;
+                };
+                self.repeated.push(key);
+                if !repeat && !self.down.contains(&key) {
+                    self.down.push(key);
+                    self.pressed.push(key);
+                }
+            }
+            Event::KeyUp { scancode, .. } => {
+                let Some(key) = scancode.and_then(to_key) else {
+                    return;
+                };
+                self.down.retain(|down| *down != key);
+                self.released.push(key);
+            }
+            Event::MouseMotion {
+                x, y, xrel, yrel, ..
+            } => {
+                self.mouse = self.to_image(x, y);
+                // The relative movement keeps coming when the cursor is locked in place, unlike its position
+                if self.focused {
+                    self.cursor = self.cursor + Vec2::new(xrel as f64, yrel as f64);
+                }
+            }
+            Event::MouseButtonDown { mouse_btn, .. } => {
+                if let Some(button) = to_button(mouse_btn) {
+                    self.buttons.retain(|held| *held != button);
+                    self.buttons.push(button);
+                }
+            }
+            Event::MouseButtonUp { mouse_btn, .. } => {
+                if let Some(button) = to_button(mouse_btn) {
+                    self.buttons.retain(|held| *held != button);
+                }
+            }
+            Event::MouseWheel { y, .. } => self.scroll += y as f64,
+            _ => {}
+        }
+    }
+}
+
+impl Backend for Sdl2Window {
+    fn poll_events(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+        self.repeated.clear();
+        self.scroll = 0.0;
+
+        while let Some(event) = self.events.poll_event() {
+            self.handle(event);
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.down.contains(&key)
+    }
+
+    fn is_key_pressed(&self, key: Key, repeat: KeyRepeat) -> bool {
+        match repeat {
+            KeyRepeat::Yes => self.repeated.contains(&key),
+            KeyRepeat::No => self.pressed.contains(&key),
+        }
+    }
+
+    fn read_input(&mut self) -> InputFrame {
+        InputFrame {
+            keys_down: self.down.clone(),
+            keys_pressed: self.pressed.clone(),
+            keys_released: self.released.clone(),
+            cursor: self.focused.then_some(self.cursor),
+            mouse: self.mouse,
+            size: self.size,
+            buttons: self.buttons.clone(),
+            scroll: self.scroll,
+        }
+    }
+
+    fn set_cursor_captured(&mut self, captured: bool) {
+        // Relative mouse mode hides the cursor and keeps it in place, while still reporting how the mouse moves
+        self.sdl.mouse().set_relative_mouse_mode(captured);
+    }
+}
+
+impl PresentTarget for Sdl2Window {
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize) -> Result<(), Error> {
+        if !matches!(self.texture, Some((_, w, h)) if (w, h) == (width, height)) {
+            if let Some((texture, _, _)) = self.texture.take() {
+                // Safe as the renderer the texture was made by is still alive
+                unsafe { texture.destroy() };
+            }
+            let texture = self
+                .canvas
+                .texture_creator()
+                .create_texture_streaming(PixelFormatEnum::RGB888, width as u32, height as u32)
+                .map_err(Error::window)?;
+            self.texture = Some((texture, width, height));
+        }
+        let Some((texture, _, _)) = &mut self.texture else {
+            return Ok(());
+        };
+
+        // RGB888 is a 32 bit pixel with its top byte unused, so 0RGB pixels are copied as they are
+        self.bytes.clear();
+        self.bytes
+            .extend(buffer.iter().flat_map(|pixel| pixel.to_ne_bytes()));
+        texture
+            .update(None, &self.bytes, width * 4)
+            .map_err(Error::window)?;
+        // The image is stretched to fill the window, which is usually bigger on high DPI displays
+        self.canvas
+            .copy(texture, None, None)
+            .map_err(Error::window)?;
+        self.canvas.present();
+        Ok(())
+    }
+}
+
+impl Drop for Sdl2Window {
+    fn drop(&mut self) {
+        if let Some((texture, _, _)) = self.texture.take() {
+            // Safe as the canvas is only dropped after this
+            unsafe { texture.destroy() };
+        }
+    }
+}
+
+fn to_button(button: SdlButton) -> Option<MouseButton> {
+    match button {
+        SdlButton::Left => Some(MouseButton::Left),
+        SdlButton::Middle => Some(MouseButton::Middle),
+        SdlButton::Right => Some(MouseButton::Right),
+        _ => None,
+    }
+}
+
+///
+/// Finds the key matching a key on the keyboard, going by where it is rather than what it types.
+///
+fn to_key(code: Scancode) -> Option<Key> {
+    let key = match code {
+        Scancode::Num0 => Key::Key0,
+        Scancode::Num1 => Key::Key1,
+        Scancode::Num2 => Key::Key2,
+        Scancode::Num3 => Key::Key3,
+        Scancode::Num4 => Key::Key4,
+        Scancode::Num5 => Key::Key5,
+        Scancode::Num6 => Key::Key6,
+        Scancode::Num7 => Key::Key7,
+        Scancode::Num8 => Key::Key8,
+        Scancode::Num9 => Key::Key9,
+        Scancode::A => Key::A,
+        Scancode::B => Key::B,
+        Scancode::C => Key::C,
+        Scancode::D => Key::D,
+        Scancode::E => Key::E,
+        Scancode::F => Key::F,
+        Scancode::G => Key::G,
+        Scancode::H => Key::H,
+        Scancode::I => Key::I,
+        Scancode::J => Key::J,
+        Scancode::K => Key::K,
+        Scancode::L => Key::L,
+        Scancode::M => Key::M,
+        Scancode::N => Key::N,
+        Scancode::O => Key::O,
+        Scancode::P => Key::P,
+        Scancode::Q => Key::Q,
+        Scancode::R => Key::R,
+        Scancode::S => Key::S,
+        Scancode::T => Key::T,
+        Scancode::U => Key::U,
+        Scancode::V => Key::V,
+        Scancode::W => Key::W,
+        Scancode::X => Key::X,
+        Scancode::Y => Key::Y,
+        Scancode::Z => Key::Z,
+        Scancode::F1 => Key::F1,
+        Scancode::F2 => Key::F2,
+        Scancode::F3 => Key::F3,
+        Scancode::F4 => Key::F4,
+        Scancode::F5 => Key::F5,
+        Scancode::F6 => Key::F6,
+        Scancode::F7 => Key::F7,
+        Scancode::F8 => Key::F8,
+        Scancode::F9 => Key::F9,
+        Scancode::F10 => Key::F10,
+        Scancode::F11 => Key::F11,
+        Scancode::F12 => Key::F12,
+        Scancode::F13 => Key::F13,
+        Scancode::F14 => Key::F14,
+        Scancode::F15 => Key::F15,
+        Scancode::Down => Key::Down,
+        Scancode::Left => Key::Left,
+        Scancode::Right => Key::Right,
+        Scancode::Up => Key::Up,
+        Scancode::Apostrophe => Key::Apostrophe,
+        Scancode::Grave => Key::Backquote,
+        Scancode::Backslash => Key::Backslash,
+        Scancode::Comma => Key::Comma,
+        Scancode::Equals => Key::Equal,
+        Scancode::LeftBracket => Key::LeftBracket,
+        Scancode::Minus => Key::Minus,
+        Scancode::Period => Key::Period,
+        Scancode::RightBracket => Key::RightBracket,
+        Scancode::Semicolon => Key::Semicolon,
+        Scancode::Slash => Key::Slash,
+        Scancode::Backspace => Key::Backspace,
+        Scancode::Delete => Key::Delete,
+        Scancode::End => Key::End,
+        Scancode::Return => Key::Enter,
+        Scancode::Escape => Key::Escape,
+        Scancode::Home => Key::Home,
+        Scancode::Insert => Key::Insert,
+        Scancode::Application => Key::Menu,
+        Scancode::PageDown => Key::PageDown,
+        Scancode::PageUp => Key::PageUp,
+        Scancode::Pause => Key::Pause,
+        Scancode::Space => Key::Space,
+        Scancode::Tab => Key::Tab,
+        Scancode::NumLockClear => Key::NumLock,
+        Scancode::CapsLock => Key::CapsLock,
+        Scancode::ScrollLock => Key::ScrollLock,
+        Scancode::LShift => Key::LeftShift,
+        Scancode::RShift => Key::RightShift,
+        Scancode::LCtrl => Key::LeftCtrl,
+        Scancode::RCtrl => Key::RightCtrl,
+        Scancode::Kp0 => Key::NumPad0,
+        Scancode::Kp1 => Key::NumPad1,
+        Scancode::Kp2 => Key::NumPad2,
+        Scancode::Kp3 => Key::NumPad3,
+        Scancode::Kp4 => Key::NumPad4,
+        Scancode::Kp5 => Key::NumPad5,
+        Scancode::Kp6 => Key::NumPad6,
+        Scancode::Kp7 => Key::NumPad7,
+        Scancode::Kp8 => Key::NumPad8,
+        Scancode::Kp9 => Key::NumPad9,
+        Scancode::KpPeriod => Key::NumPadDot,
+        Scancode::KpDivide => Key::NumPadSlash,
+        Scancode::KpMultiply => Key::NumPadAsterisk,
+        Scancode::KpMinus => Key::NumPadMinus,
+        Scancode::KpPlus => Key::NumPadPlus,
+        Scancode::KpEnter => Key::NumPadEnter,
+        Scancode::LAlt => Key::LeftAlt,
+        Scancode::RAlt => Key::RightAlt,
+        Scancode::LGui => Key::LeftSuper,
+        Scancode::RGui => Key::RightSuper,
+        _ => return None,
+    };
+    Some(key)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keys_match_by_position() {
+        assert_eq!(to_key(Scancode::W), Some(Key::W));
+        assert_eq!(to_key(Scancode::Num7), Some(Key::Key7));
+        assert_eq!(to_key(Scancode::LShift), Some(Key::LeftShift));
+        assert_eq!(to_key(Scancode::KpEnter), Some(Key::NumPadEnter));
+        assert_eq!(to_key(Scancode::AudioPlay), None);
+    }
+}
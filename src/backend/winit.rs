@@ -0,0 +1,480 @@
+use std::{
+    num::NonZeroU32,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use minifb::{Key, KeyRepeat, MouseButton};
+use softbuffer::{Context, Surface};
+use winit::{
+    application::ApplicationHandler,
+    dpi::{LogicalSize, PhysicalPosition},
+    event::{
+        DeviceEvent, DeviceId, ElementState, MouseButton as WinitButton, MouseScrollDelta,
+        WindowEvent,
+    },
+    event_loop::{ActiveEventLoop, EventLoop},
+    keyboard::{KeyCode, PhysicalKey},
+    platform::pump_events::{EventLoopExtPumpEvents, PumpStatus},
+    window::{CursorGrabMode, Window, WindowAttributes, WindowId},
+};
+
+use crate::{
+    app::AppConfig, backend::Backend, config::WindowScale, error::Error, input::InputFrame,
+    present::PresentTarget, timing::FramePacing, vec::vec2::Vec2,
+};
+
+// How often frames are shown with vsync on, as softbuffer can't wait for the display to refresh
+const VSYNC_RATE: f64 = 60.0;
+
+// How many pixels of scrolling on a touchpad count as one step of a mouse wheel
+const PIXELS_PER_LINE: f64 = 20.0;
+
+///
+/// A window opened with winit, with frames copied into it by softbuffer. Unlike minifb, the window is sized in logical
+/// pixels so it's the same size on high DPI displays, and the mouse can really be captured.
+///
+/// winit has to be run from the main thread, and its event loop can only be created once, so only one window can be
+/// opened this way in a program.
+///
+pub struct WinitWindow {
+    event_loop: EventLoop<()>,
+    state: WindowState,
+}
+
+///
+/// The window, and everything its events have reported since the last frame.
+///
+struct WindowState {
+    // The window is only created once the event loop has started
+    attributes: WindowAttributes,
+    scale: WindowScale,
+    window: Option<Rc<Window>>,
+    surface: Option<Surface<Rc<Window>, Rc<Window>>>,
+    error: Option<Error>,
+    open: bool,
+    focused: bool,
+    captured: bool,
+    // The size of the image before the render scale, which mouse positions are measured in
+    size: (usize, usize),
+    down: Vec<Key>,
+    pressed: Vec<Key>,
+    released: Vec<Key>,
+    // Keys pressed along with keys repeating while they're held down
+    repeated: Vec<Key>,
+    mouse: Option<Vec2>,
+    // Every movement of the mouse added up, so it can be followed however far it goes while it's captured
+    cursor: Vec2,
+    buttons: Vec<MouseButton>,
+    scroll: f64,
+    vsync: Option<Duration>,
+    last_present: Instant,
+}
+
+impl WinitWindow {
+    pub fn open(config: &AppConfig) -> Result<WinitWindow, Error> {
+        let event_loop = EventLoop::new().map_err(Error::window)?;
+        let (width, height) = (config.renderer.width, config.renderer.height);
+        let attributes = Window::default_attributes()
+            .with_title(&config.window.title)
+            .with_resizable(config.window.resizable)
+            .with_inner_size(LogicalSize::new(width as f64, height as f64));
+
+        let mut window = WinitWindow {
+            event_loop,
+            state: WindowState {
+                attributes,
+                scale: config.window.scale,
+                window: None,
+                surface: None,
+                error: None,
+                open: true,
+                focused: false,
+                captured: false,
+                size: (width, height),
+                down: vec![],
+                pressed: vec![],
+                released: vec![],
+                repeated: vec![],
+                mouse: None,
+                cursor: Vec2::default(),
+                buttons: vec![],
+                scroll: 0.0,
+                vsync: config
+                    .renderer
+                    .vsync
+                    .then(|| Duration::from_secs_f64(1.0 / VSYNC_RATE)),
+                last_present: Instant::now(),
+            },
+        };
+
+        // The window is created when the event loop first starts, so it's run until then
+        while window.state.window.is_none() && window.state.open {
+            window.poll_events();
+        }
+        match window.state.error.take() {
+            Some(error) => Err(error),
+            None => Ok(window),
+        }
+    }
+}
+
+impl Backend for WinitWindow {
+    fn poll_events(&mut self) {
+        self.state.pressed.clear();
+        self.state.released.clear();
+        self.state.repeated.clear();
+        self.state.scroll = 0.0;
+
+        let status = self
+            .event_loop
+            .pump_app_events(Some(Duration::ZERO), &mut self.state);
+        if let PumpStatus::Exit(_) = status {
+            self.state.open = false;
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        self.state.open
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.state.down.contains(&key)
+    }
+
+    fn is_key_pressed(&self, key: Key, repeat: KeyRepeat) -> bool {
+        match repeat {
+            KeyRepeat::Yes => self.state.repeated.contains(&key),
+            KeyRepeat::No => self.state.pressed.contains(&key),
+        }
+    }
+
+    fn read_input(&mut self) -> InputFrame {
+        let state = &self.state;
+        InputFrame {
+            keys_down: state.down.clone(),
+            keys_pressed: state.pressed.clone(),
+            keys_released: state.released.clone(),
+            cursor: state.focused.then_some(state.cursor),
+            mouse: state.mouse,
+            size: state.size,
+            buttons: state.buttons.clone(),
+            scroll: state.scroll,
+        }
+    }
+
+    fn set_cursor_captured(&mut self, captured: bool) {
+        self.state.captured = captured;
+        self.state.apply_capture();
+    }
+}
+
+impl PresentTarget for WinitWindow {
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize) -> Result<(), Error> {
+        if let Some(frame_time) = self.state.vsync {
+            FramePacing::Hybrid.wait_until(self.state.last_present + frame_time);
+            self.state.last_present = Instant::now();
+        }
+
+        let (Some(window), Some(surface)) = (&self.state.window, &mut self.state.surface) else {
+            return Ok(());
+        };
+        // Minimised windows have nothing to draw into
+        let inner = window.inner_size();
+        let (Some(out_width), Some(out_height)) =
+            (NonZeroU32::new(inner.width), NonZeroU32::new(inner.height))
+        else {
+            return Ok(());
+        };
+        surface
+            .resize(out_width, out_height)
+            .map_err(Error::window)?;
+
+        // The image is stretched to fill the window, which is usually bigger on high DPI displays
+        let mut pixels = surface.buffer_mut().map_err(Error::window)?;
+        let (out_width, out_height) = (inner.width as usize, inner.height as usize);
+        for y in 0..out_height {
+            let row = (y * height / out_height) * width;
+            for x in 0..out_width {
+                pixels[y * out_width + x] = buffer[row + x * width / out_width];
+            }
+        }
+        window.pre_present_notify();
+        pixels.present().map_err(Error::window)?;
+        Ok(())
+    }
+}
+
+impl WindowState {
+    ///
+    /// Turns a position in the window's physical pixels into one in the image's pixels.
+    ///
+    fn to_image(&self, position: PhysicalPosition<f64>) -> Option<Vec2> {
+        let inner = self.window.as_ref()?.inner_size();
+        if inner.width == 0 || inner.height == 0 {
+            return None;
+        }
+        Some(Vec2::new(
+            position.x * self.size.0 as f64 / inner.width as f64,
+            position.y * self.size.1 as f64 / inner.height as f64,
+        ))
+    }
+
+    fn apply_capture(&self) {
+        let Some(window) = &self.window else {
+            return;
+        };
+        if self.captured {
+            // Not every platform can lock the cursor in place, but keeping it in the window is nearly as good
+            let _ = window
+                .set_cursor_grab(CursorGrabMode::Locked)
+                .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined));
+        } else {
+            let _ = window.set_cursor_grab(CursorGrabMode::None);
+        }
+        window.set_cursor_visible(!self.captured);
+    }
+
+    ///
+    /// Returns how many times bigger than the image the window should be opened.
+    ///
+    fn window_scale(&self, event_loop: &ActiveEventLoop) -> f64 {
+        match self.scale {
+            WindowScale::X1 => 1.0,
+            WindowScale::X2 => 2.0,
+            WindowScale::X4 => 4.0,
+            WindowScale::X8 => 8.0,
+            WindowScale::FitScreen => {
+                let Some(monitor) = event_loop.primary_monitor() else {
+                    return 1.0;
+                };
+                let screen = monitor.size().to_logical::<f64>(monitor.scale_factor());
+                let fits = |scale: &f64| {
+                    self.size.0 as f64 * scale <= screen.width
+                        && self.size.1 as f64 * scale <= screen.height
+                };
+                [8.0, 4.0, 2.0].into_iter().find(fits).unwrap_or(1.0)
+            }
+        }
+    }
+
+    fn create_window(&mut self, event_loop: &ActiveEventLoop) -> Result<(), Error> {
+        let scale = self.window_scale(event_loop);
+        let size = LogicalSize::new(self.size.0 as f64 * scale, self.size.1 as f64 * scale);
+        let window = event_loop
+            .create_window(self.attributes.clone().with_inner_size(size))
+            .map_err(Error::window)?;
+        let window = Rc::new(window);
+        let context = Context::new(window.clone()).map_err(Error::window)?;
+        self.surface = Some(Surface::new(&context, window.clone()).map_err(Error::window)?);
+        self.window = Some(window);
+        self.apply_capture();
+        Ok(())
+    }
+}
+
+impl ApplicationHandler for WindowState {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+        if let Err(error) = self.create_window(event_loop) {
+            self.error = Some(error);
+            self.open = false;
+        }
+    }
+
+    fn window_event(&mut self, _event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+        match event {
+            WindowEvent::CloseRequested => self.open = false,
+            WindowEvent::Focused(focused) => {
+                self.focused = focused;
+                // Keys let go of while the window was in the background would otherwise be stuck down
+                if !focused {
+                    self.released.append(&mut self.down);
+                }
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                let PhysicalKey::Code(code) = event.physical_key else {
+                    return;
+                };
+                let Some(key) = to_key(code) else {
+                    return;
+                };
+                match event.state {
+                    ElementState::Pressed => {
+                        self.repeated.push(key);
+                        if !event.repeat && !self.down.contains(&key) {
+                            self.down.push(key);
+                            self.pressed.push(key);
+                        }
+                    }
+                    ElementState::Released => {
+                        self.down.retain(|down| *down != key);
+                        self.released.push(key);
+                    }
+                }
+            }
+            WindowEvent::CursorMoved { position, .. } => self.mouse = self.to_image(position),
+            WindowEvent::CursorLeft { .. } => self.mouse = None,
+            WindowEvent::MouseInput { state, button, .. } => {
+                let button = match button {
+                    WinitButton::Left => MouseButton::Left,
+                    WinitButton::Middle => MouseButton::Middle,
+                    WinitButton::Right => MouseButton::Right,
+                    _ => return,
+                };
+                self.buttons.retain(|held| *held != button);
+                if state == ElementState::Pressed {
+                    self.buttons.push(button);
+                }
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.scroll += match delta {
+                    MouseScrollDelta::LineDelta(_, lines) => lines as f64,
+                    MouseScrollDelta::PixelDelta(pixels) => pixels.y / PIXELS_PER_LINE,
+                };
+            }
+            _ => {}
+        }
+    }
+
+    fn device_event(&mut self, _event_loop: &ActiveEventLoop, _id: DeviceId, event: DeviceEvent) {
+        // The mouse's own movement keeps coming when the cursor is locked in place, unlike the cursor's position
+        if let DeviceEvent::MouseMotion { delta: (x, y) } = event {
+            if self.focused {
+                self.cursor = self.cursor + Vec2::new(x, y);
+            }
+        }
+    }
+}
+
+///
+/// Finds the key matching a key on the keyboard, going by where it is rather than what it types.
+///
+fn to_key(code: KeyCode) -> Option<Key> {
+    let key = match code {
+        KeyCode::Digit0 => Key::Key0,
+        KeyCode::Digit1 => Key::Key1,
+        KeyCode::Digit2 => Key::Key2,
+        KeyCode::Digit3 => Key::Key3,
+        KeyCode::Digit4 => Key::Key4,
+        KeyCode::Digit5 => Key::Key5,
+        KeyCode::Digit6 => Key::Key6,
+        KeyCode::Digit7 => Key::Key7,
+        KeyCode::Digit8 => Key::Key8,
+        KeyCode::Digit9 => Key::Key9,
+        KeyCode::KeyA => Key::A,
+        KeyCode::KeyB => Key::B,
+        KeyCode::KeyC => Key::C,
+        KeyCode::KeyD => Key::D,
+        KeyCode::KeyE => Key::E,
+        KeyCode::KeyF => Key::F,
+        KeyCode::KeyG => Key::G,
+        KeyCode::KeyH => Key::H,
+        KeyCode::KeyI => Key::I,
+        KeyCode::KeyJ => Key::J,
+        KeyCode::KeyK => Key::K,
+        KeyCode::KeyL => Key::L,
+        KeyCode::KeyM => Key::M,
+        KeyCode::KeyN => Key::N,
+        KeyCode::KeyO => Key::O,
+        KeyCode::KeyP => Key::P,
+        KeyCode::KeyQ => Key::Q,
+        KeyCode::KeyR => Key::R,
+        KeyCode::KeyS => Key::S,
+        KeyCode::KeyT => Key::T,
+        KeyCode::KeyU => Key::U,
+        KeyCode::KeyV => Key::V,
+        KeyCode::KeyW => Key::W,
+        KeyCode::KeyX => Key::X,
+        KeyCode::KeyY => Key::Y,
+        KeyCode::KeyZ => Key::Z,
+        KeyCode::F1 => Key::F1,
+        KeyCode::F2 => Key::F2,
+        KeyCode::F3 => Key::F3,
+        KeyCode::F4 => Key::F4,
+        KeyCode::F5 => Key::F5,
+        KeyCode::F6 => Key::F6,
+        KeyCode::F7 => Key::F7,
+        KeyCode::F8 => Key::F8,
+        KeyCode::F9 => Key::F9,
+        KeyCode::F10 => Key::F10,
+        KeyCode::F11 => Key::F11,
+        KeyCode::F12 => Key::F12,
+        KeyCode::F13 => Key::F13,
+        KeyCode::F14 => Key::F14,
+        KeyCode::F15 => Key::F15,
+        KeyCode::ArrowDown => Key::Down,
+        KeyCode::ArrowLeft => Key::Left,
+        KeyCode::ArrowRight => Key::Right,
+        KeyCode::ArrowUp => Key::Up,
+        KeyCode::Quote => Key::Apostrophe,
+        KeyCode::Backquote => Key::Backquote,
+        KeyCode::Backslash => Key::Backslash,
+        KeyCode::Comma => Key::Comma,
+        KeyCode::Equal => Key::Equal,
+        KeyCode::BracketLeft => Key::LeftBracket,
+        KeyCode::Minus => Key::Minus,
+        KeyCode::Period => Key::Period,
+        KeyCode::BracketRight => Key::RightBracket,
+        KeyCode::Semicolon => Key::Semicolon,
+        KeyCode::Slash => Key::Slash,
+        KeyCode::Backspace => Key::Backspace,
+        KeyCode::Delete => Key::Delete,
+        KeyCode::End => Key::End,
+        KeyCode::Enter => Key::Enter,
+        KeyCode::Escape => Key::Escape,
+        KeyCode::Home => Key::Home,
+        KeyCode::Insert => Key::Insert,
+        KeyCode::ContextMenu => Key::Menu,
+        KeyCode::PageDown => Key::PageDown,
+        KeyCode::PageUp => Key::PageUp,
+        KeyCode::Pause => Key::Pause,
+        KeyCode::Space => Key::Space,
+        KeyCode::Tab => Key::Tab,
+        KeyCode::NumLock => Key::NumLock,
+        KeyCode::CapsLock => Key::CapsLock,
+        KeyCode::ScrollLock => Key::ScrollLock,
+        KeyCode::ShiftLeft => Key::LeftShift,
+        KeyCode::ShiftRight => Key::RightShift,
+        KeyCode::ControlLeft => Key::LeftCtrl,
+        KeyCode::ControlRight => Key::RightCtrl,
+        KeyCode::Numpad0 => Key::NumPad0,
+        KeyCode::Numpad1 => Key::NumPad1,
+        KeyCode::Numpad2 => Key::NumPad2,
+        KeyCode::Numpad3 => Key::NumPad3,
+        KeyCode::Numpad4 => Key::NumPad4,
+        KeyCode::Numpad5 => Key::NumPad5,
+        KeyCode::Numpad6 => Key::NumPad6,
+        KeyCode::Numpad7 => Key::NumPad7,
+        KeyCode::Numpad8 => Key::NumPad8,
+        KeyCode::Numpad9 => Key::NumPad9,
+        KeyCode::NumpadDecimal => Key::NumPadDot,
+        KeyCode::NumpadDivide => Key::NumPadSlash,
+        KeyCode::NumpadMultiply => Key::NumPadAsterisk,
+        KeyCode::NumpadSubtract => Key::NumPadMinus,
+        KeyCode::NumpadAdd => Key::NumPadPlus,
+        KeyCode::NumpadEnter => Key::NumPadEnter,
+        KeyCode::AltLeft => Key::LeftAlt,
+        KeyCode::AltRight => Key::RightAlt,
+        KeyCode::SuperLeft => Key::LeftSuper,
+        KeyCode::SuperRight => Key::RightSuper,
+        _ => return None,
+    };
+    Some(key)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keys_match_by_position() {
+        assert_eq!(to_key(KeyCode::KeyW), Some(Key::W));
+        assert_eq!(to_key(KeyCode::Digit7), Some(Key::Key7));
+        assert_eq!(to_key(KeyCode::ShiftLeft), Some(Key::LeftShift));
+        assert_eq!(to_key(KeyCode::NumpadEnter), Some(Key::NumPadEnter));
+        assert_eq!(to_key(KeyCode::MediaPlayPause), None);
+    }
+}
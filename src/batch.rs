@@ -0,0 +1,225 @@
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    object::Texture,
+    renderer::{Blend, Renderer},
+    vec::{vec2::Vec2, vec3::Vec3},
+};
+
+///
+/// The order a batch of triangles is rasterised in.
+/// - `Submitted` draws them in the order they were added
+/// - `Material` groups them by texture, so each texture is read in one run while it's in the cache, drawing the nearest
+///   first within each group
+/// - `FrontToBack` draws the nearest first whatever their texture, so pixels they hide fail the depth test before
+///   they're shaded
+///
+/// Apart from `Submitted`, triangles which blend with what's behind them, by being transparent or reflective, are
+/// drawn after the rest, furthest first, so there's something behind each of them to blend with. Lower draw orders are
+/// always drawn first.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TriangleOrder {
+    Submitted,
+    #[default]
+    Material,
+    FrontToBack,
+}
+
+// A triangle, or a quad if it has a fourth corner
+struct Batched<'a> {
+    vertices: [Vec3; 3],
+    tex_coords: [Vec2; 3],
+    fourth: Option<(Vec3, Vec2)>,
+    texture: &'a Texture,
+    blend: Blend,
+    draw_order: i32,
+}
+
+impl Batched<'_> {
+    fn blended(&self) -> bool {
+        self.blend.opacity < 1.0 || self.blend.reflection.is_some()
+    }
+
+    // How near the triangle is, by the depth of its centre. Depths are negative, so larger values are nearer
+    fn nearness(&self) -> f64 {
+        let fourth = self.fourth.map(|(vertex, _)| vertex);
+        let corners = self.vertices.iter().chain(&fourth);
+        corners.clone().map(|vertex| vertex.z).sum::<f64>() / corners.count() as f64
+    }
+
+    // Textures are told apart by where their pixels are, which is the same for every triangle sharing one
+    fn texture_key(&self) -> usize {
+        self.texture.pixels.as_ptr() as usize
+    }
+}
+
+///
+/// Triangles collected to be rasterised together, in the order set by `RendererConfig::triangle_order`, rather than
+/// one at a time as they're worked out. `Camera::draw_entities` collects each layer into a batch.
+///
+#[derive(Default)]
+pub struct TriangleBatch<'a> {
+    triangles: Vec<Batched<'a>>,
+}
+
+impl<'a> TriangleBatch<'a> {
+    pub fn new() -> TriangleBatch<'a> {
+        TriangleBatch::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.triangles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.triangles.is_empty()
+    }
+
+    ///
+    /// Adds a triangle, as it would be passed to `Renderer::draw_triangle_blended`. Triangles with a lower
+    /// `draw_order` are drawn first, as with `World::set_draw_order`.
+    ///
+    pub fn push(
+        &mut self,
+        vertices: [Vec3; 3],
+        texture: &'a Texture,
+        tex_coords: [Vec2; 3],
+        blend: Blend,
+        draw_order: i32,
+    ) {
+        self.triangles.push(Batched {
+            vertices,
+            tex_coords,
+            fourth: None,
+            texture,
+            blend,
+            draw_order,
+        });
+    }
+
+    ///
+    /// Adds a quad, as it would be passed to `Renderer::draw_quad_blended`, which is sorted and drawn as one piece.
+    ///
+    pub fn push_quad(
+        &mut self,
+        vertices: [Vec3; 4],
+        texture: &'a Texture,
+        tex_coords: [Vec2; 4],
+        blend: Blend,
+        draw_order: i32,
+    ) {
+        let [a, b, c, d] = vertices;
+        let [ta, tb, tc, td] = tex_coords;
+        self.triangles.push(Batched {
+            vertices: [a, b, c],
+            tex_coords: [ta, tb, tc],
+            fourth: Some((d, td)),
+            texture,
+            blend,
+            draw_order,
+        });
+    }
+
+    ///
+    /// Sorts and rasterises every triangle in the batch, leaving it empty to be filled again.
+    ///
+    pub fn draw(&mut self, renderer: &mut Renderer) {
+        let order = renderer.config().triangle_order;
+        if order != TriangleOrder::Submitted {
+            // The sort is stable, so triangles which compare equal keep the order they were added in
+            self.triangles.sort_by(|a, b| {
+                let nearest_first = || b.nearness().total_cmp(&a.nearness());
+                a.blended()
+                    .cmp(&b.blended())
+                    .then(a.draw_order.cmp(&b.draw_order))
+                    .then_with(|| match (a.blended(), order) {
+                        (true, _) => a.nearness().total_cmp(&b.nearness()),
+                        (false, TriangleOrder::Material) => a
+                            .texture_key()
+                            .cmp(&b.texture_key())
+                            .then_with(nearest_first),
+                        (false, _) => nearest_first(),
+                    })
+            });
+        }
+        for triangle in self.triangles.drain(..) {
+            let ([a, b, c], [ta, tb, tc]) = (triangle.vertices, triangle.tex_coords);
+            match triangle.fourth {
+                Some((d, td)) => renderer.draw_quad_blended(
+                    [a, b, c, d],
+                    triangle.texture,
+                    [ta, tb, tc, td],
+                    triangle.blend,
+                ),
+                None => renderer.draw_triangle_blended(
+                    triangle.vertices,
+                    triangle.texture,
+                    triangle.tex_coords,
+                    triangle.blend,
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+
+    use super::*;
+    use crate::config::RendererConfig;
+
+    fn solid(colour: u32) -> Texture {
+        Texture::new(1, 1, vec![colour])
+    }
+
+    // A triangle covering the middle of a small screen at the given depth
+    fn covering(z: f64) -> [Vec3; 3] {
+        [
+            Vec3::new(-2.0, -2.0, z),
+            Vec3::new(2.0, -2.0, z),
+            Vec3::new(0.0, 4.0, z),
+        ]
+    }
+
+    #[test]
+    fn sorting() {
+        let (red, blue, green) = (solid(0xff0000), solid(0x0000ff), solid(0x00ff00));
+        let coords = [Vec2::new(0.0, 0.0); 3];
+        let glass = Blend {
+            opacity: 0.5,
+            ..Blend::OPAQUE
+        };
+
+        for order in [
+            TriangleOrder::Submitted,
+            TriangleOrder::Material,
+            TriangleOrder::FrontToBack,
+        ] {
+            let mut renderer = Renderer::new(RendererConfig {
+                triangle_order: order,
+                ..RendererConfig::new(10, 10)
+            });
+            let mut batch = TriangleBatch::new();
+            // A see-through triangle in front, added before the opaque one behind it
+            batch.push(covering(-1.0), &blue, coords, glass, 0);
+            batch.push(covering(-5.0), &red, coords, Blend::OPAQUE, 0);
+            // An opaque triangle drawn later in a lower draw order, which the one in front still hides
+            batch.push(covering(-8.0), &green, coords, Blend::OPAQUE, -1);
+            assert_eq!(batch.len(), 3);
+            batch.draw(&mut renderer);
+            assert!(batch.is_empty());
+
+            let expected = match order {
+                // Drawn in order, the see-through triangle has nothing behind it but the clear colour
+                TriangleOrder::Submitted => 0xff0000,
+                _ => 0x800080,
+            };
+            assert_eq!(renderer.buffer[55], expected, "{:?}", order);
+            assert_eq!(renderer.stats().triangles, 3);
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use crate::world::{EntityId, World};
+
+///
+/// Per-entity logic run every frame by `World::update`, before animations are evaluated.
+///
+/// Behaviours get the whole world, so they can move their entity, look at or spawn others, or remove their entity
+/// altogether. `time` is the world's time after this frame's `delta` has been added.
+///
+/// Closures taking the same arguments can be used as behaviours, e.g.
+///
+/// ```
+/// # use megavertex::{Object, Texture, Vec3, World};
+/// # let mut world = World::new();
+/// # let object = Object::new(vec![], vec![], vec![], vec![], Texture::new(1, 1, vec![0xffffff]));
+/// # let id = world.add_object(object, Vec3::new(0.0, 0.0, 0.0));
+/// world.add_behaviour(id, |id, world: &mut World, _time, delta| {
+///     if let Some(base) = world.base_transform(id) {
+///         world.set_base_transform(id, base.translate(Vec3::new(0.0, delta, 0.0)));
+///     }
+/// });
+/// ```
+///
+pub trait Behaviour: Send + Sync {
+    fn update(&mut self, entity: EntityId, world: &mut World, time: f64, delta: f64);
+}
+
+impl<F> Behaviour for F
+where
+    F: FnMut(EntityId, &mut World, f64, f64) + Send + Sync,
+{
+    fn update(&mut self, entity: EntityId, world: &mut World, time: f64, delta: f64) {
+        self(entity, world, time, delta)
+    }
+}
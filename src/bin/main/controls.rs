@@ -0,0 +1,140 @@
+//! Controls shared by the demo's scenes, for flying and walking around, pushing bodies and throwing things.
+
+use megavertex::{
+    Camera, CharacterController, Colour, Decal, DecalMode, Input, MouseButton, Object, Projectile,
+    Rigidbody, Texture, Vec2, Vec3, World,
+};
+
+// Flying parameters, in units and radians per second
+const SPEED: f64 = 16.7;
+const LOOK_SPEED: f64 = 3.3;
+
+// Walking parameters, in units per second
+const WALK_SPEED: f64 = 5.0;
+const JUMP_SPEED: f64 = 5.0;
+
+// Thrown balls, in units and seconds
+const THROW_SPEED: f64 = 15.0;
+const THROW_LIFETIME: f64 = 5.0;
+const AIM_COLOUR: Colour = Colour::from_hex(0xffffff);
+
+// How hard clicking on a body pushes it
+const PUSH_IMPULSE: f64 = 5.0;
+
+// Clicks leave scorch marks this wide on whatever they hit, and only the newest few are kept
+const MARK_SIZE: f64 = 0.4;
+const MAX_MARKS: usize = 32;
+
+///
+/// Turns the camera with the look actions, and with the mouse while Tab has captured it, like a first person game.
+///
+pub fn look(camera: &mut Camera, input: &mut Input, delta: f64) {
+    let pitch = input.action_axis("look_down", "look_up");
+    let yaw = input.action_axis("look_left", "look_right");
+    camera.rotate(Vec3::new(pitch, yaw, 0.0) * (LOOK_SPEED * delta));
+
+    if input.is_action_pressed("capture_mouse") {
+        input.capture_mouse = !input.capture_mouse;
+    }
+    if input.capture_mouse {
+        camera.rotate(input.mouse_look());
+    }
+}
+
+///
+/// Flies the camera with the movement actions, and turns it with `look`.
+///
+pub fn fly(camera: &mut Camera, input: &mut Input, delta: f64) {
+    let forward = input.action_axis("move_forward", "move_back");
+    let right = input.action_axis("move_right", "move_left");
+    camera.translate(forward * SPEED * delta, right * SPEED * delta);
+    look(camera, input, delta);
+}
+
+///
+/// Walks a character around with the movement actions, jumping with the jump action, and puts the camera at its eyes.
+///
+pub fn walk(
+    character: &mut CharacterController,
+    world: &World,
+    camera: &mut Camera,
+    input: &Input,
+    delta: f64,
+) {
+    // The camera's position is the opposite of where it is in the world, so the way it would move is flipped to get
+    // the direction to walk in
+    let forward = input.action_axis("move_forward", "move_back");
+    let right = input.action_axis("move_right", "move_left");
+    let mut moved = *camera;
+    moved.translate(forward, right);
+    let direction = camera.position() - moved.position();
+
+    if input.is_action_pressed("jump") {
+        character.jump(JUMP_SPEED);
+    }
+    character.move_and_slide(world, direction * WALK_SPEED, delta);
+    camera.set_position(-character.eye());
+}
+
+///
+/// Pushes the body under the mouse away from the camera when it's clicked, leaving a scorch mark where the click
+/// lands. `width` and `height` are the size of the window, for finding what the mouse is pointing at.
+///
+pub fn push(world: &mut World, camera: &Camera, input: &Input, width: usize, height: usize) {
+    let clicked = input.is_mouse_pressed(MouseButton::Left);
+    if let (true, Some(pixel)) = (clicked, input.mouse_position()) {
+        let ray = camera.screen_ray(pixel, width, height);
+        if let Some(hit) = world.raycast_bodies(&ray) {
+            if let Some(body) = world.component_mut::<Rigidbody>(hit.entity) {
+                body.apply_impulse(ray.direction * PUSH_IMPULSE);
+            }
+            world.wake(hit.entity);
+        }
+        if let Some(hit) = world.raycast(&ray) {
+            let size = Vec2::new(MARK_SIZE, MARK_SIZE);
+            let mark = Decal::on_surface(scorch(), hit.point, hit.normal, size, MARK_SIZE / 4.0)
+                .with_mode(DecalMode::Multiply);
+            world.decals.push(mark);
+            if world.decals.len() > MAX_MARKS {
+                world.decals.remove(0);
+            }
+        }
+    }
+}
+
+///
+/// A round scorch mark, dark in the middle and fading to white (which leaves a surface alone) at the edges.
+///
+fn scorch() -> Texture {
+    const SIZE: usize = 16;
+    let pixels = (0..SIZE * SIZE)
+        .map(|i| {
+            let centre = (SIZE as f64 - 1.0) / 2.0;
+            let (x, y) = ((i % SIZE) as f64 - centre, (i / SIZE) as f64 - centre);
+            let distance = (x.hypot(y) / centre).min(1.0);
+            let shade = (64.0 + 191.0 * distance * distance) as u8;
+            Colour::new(shade, shade, shade).to_0rgb()
+        })
+        .collect();
+    Texture::new(SIZE, SIZE, pixels)
+}
+
+///
+/// Draws where `shot` would land while the throw action is held, and throws it from the camera when it's let go.
+///
+pub fn throw(world: &mut World, camera: &Camera, input: &Input, shot: &Object) {
+    // The camera looks down its negative forward direction, which is up crossed with right
+    let eye = -camera.position();
+    let throw = Projectile::new(camera.up.cross_product(camera.right) * THROW_SPEED)
+        .with_radius(0.2)
+        .with_lifetime(THROW_LIFETIME);
+    if input.is_action_down("throw") {
+        let arc = throw.trajectory(eye, world.physics.gravity, 2.0, 20);
+        for pair in arc.windows(2) {
+            world.debug_line(pair[0], pair[1], AIM_COLOUR);
+        }
+    }
+    if input.is_action_released("throw") {
+        world.spawn_projectile(shot.clone(), eye, &throw);
+    }
+}
@@ -0,0 +1,133 @@
+use std::{env, error::Error, process};
+
+use megavertex::{
+    AppConfig, Bindings, DynamicResolution, FrameStats, Key, RecordMode, Renderer, SceneRegistry,
+    Vec2,
+};
+
+mod controls;
+mod particles;
+mod playground;
+mod stack;
+mod terrain;
+mod viewer;
+
+const USAGE: &str = "usage: main [options] [scene]
+
+Runs one of the demo scenes, which F2 switches between while running.
+
+options:
+    --record <file>  saves the input the demo is played with to a file
+    --replay <file>  plays back input saved with --record
+    --dynamic-resolution
+                     lowers the resolution while frames are slow to hold the frame rate
+    --list           lists the scenes and exits";
+
+///
+/// The options given on the command line.
+///
+struct Options {
+    scene: Option<String>,
+    record: RecordMode,
+    dynamic_resolution: bool,
+    list: bool,
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options {
+            scene: None,
+            record: RecordMode::Off,
+            dynamic_resolution: false,
+            list: false,
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--record" => options.record = RecordMode::Record(value()?),
+                "--replay" => options.record = RecordMode::Replay(value()?),
+                "--dynamic-resolution" => options.dynamic_resolution = true,
+                "--list" => options.list = true,
+                "-h" | "--help" => return Err(String::new()),
+                _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+                _ => options.scene = Some(arg),
+            }
+        }
+        Ok(options)
+    }
+}
+
+///
+/// Writes the engine's name and how quickly frames are being drawn in the top left corner, as every scene does, along
+/// with the render scale when the world is drawn below full size.
+///
+fn draw_title(renderer: &mut Renderer, stats: &FrameStats) {
+    renderer.write_text("megavertex", Vec2::new(5.0, 5.0), 24.0);
+    let mut timing = format!(
+        "{:.0} fps ({:.1}ms, worst {:.1}ms)",
+        stats.fps(),
+        stats.average().as_secs_f64() * 1000.0,
+        stats.worst().as_secs_f64() * 1000.0
+    );
+    let render_scale = renderer.config().render_scale;
+    if render_scale != 1.0 {
+        timing += &format!(" at {:.0}%", render_scale * 100.0);
+    }
+    renderer.write_text(&timing, Vec2::new(5.0, 30.0), 14.0);
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            if !error.is_empty() {
+                eprintln!("{}\n", error);
+            }
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    // The standard movement controls, along with the ones used by the scenes
+    let bindings = Bindings::default()
+        .with_action("toggle_walk", [Key::F])
+        .with_action("toggle_physics", [Key::P])
+        .with_action("throw", [Key::E])
+        .with_action("capture_mouse", [Key::Tab])
+        .with_action("toggle_settings", [Key::F1])
+        .with_action("reset_view", [Key::R]);
+    // The window and renderer are set up in resources/config.toml
+    let mut config = AppConfig {
+        bindings,
+        record: options.record,
+        ..AppConfig::load("./resources/config.toml")?
+    };
+    if options.dynamic_resolution {
+        let target_fps = config.target_fps.unwrap_or(60.0);
+        config.dynamic_resolution = Some(DynamicResolution::new(target_fps));
+    }
+
+    let (width, height) = (config.renderer.width, config.renderer.height);
+    let mut scenes = SceneRegistry::new()
+        .with_scene("playground", move || {
+            playground::Playground::new(width, height)
+        })
+        .with_scene("viewer", move || viewer::ModelViewer::new(width, height))
+        .with_scene("stack", move || stack::Stack::new(width, height))
+        .with_scene("terrain", terrain::TerrainWalk::new)
+        .with_scene("particles", particles::Particles::new);
+    if options.list {
+        for name in scenes.names() {
+            println!("{}", name);
+        }
+        return Ok(());
+    }
+    if let Some(scene) = options.scene {
+        if !scenes.select(&scene) {
+            return Err(format!("there's no scene called {}", scene).into());
+        }
+    }
+    Ok(megavertex::run(config, scenes)?)
+}
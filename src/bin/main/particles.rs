@@ -0,0 +1,127 @@
+use std::{error::Error, sync::Arc};
+
+use megavertex::{
+    App, Camera, EntityId, FrameStats, Input, Mat4, Object, Renderer, Sprite, Vec2, Vec3, World,
+};
+
+use crate::controls;
+
+// How many particles are launched each update, and how long they last in seconds
+const SPAWN_RATE: usize = 3;
+const LIFETIME: f64 = 3.0;
+
+// How fast particles are launched upwards, and how far they spread out sideways, in units per second
+const LAUNCH_SPEED: f64 = 9.0;
+const SPREAD: f64 = 2.5;
+
+// How much of a particle's speed is kept when it bounces off the ground
+const BOUNCE: f64 = 0.5;
+
+// How big each particle is, in units
+const SIZE: f64 = 0.3;
+
+///
+/// A particle launched by the fountain, moved by hand rather than by the physics simulation.
+///
+struct Particle {
+    id: EntityId,
+    position: Vec3,
+    velocity: Vec3,
+    age: f64,
+}
+
+///
+/// A fountain of sprites, which fly up, fall back down, bounce and fade away. Hundreds are alive at once, so this
+/// shows how well lots of small, blended, camera facing sprites are drawn.
+///
+pub struct Particles {
+    sprite: Option<Sprite>,
+    particles: Vec<Particle>,
+    // Picks where each particle goes, seeded by `run` so replays launch them the same way
+    random: u64,
+}
+
+impl Particles {
+    pub fn new() -> Particles {
+        Particles {
+            sprite: None,
+            particles: vec![],
+            random: 0,
+        }
+    }
+
+    ///
+    /// Returns a number between -1 and 1, from the splitmix64 generator.
+    ///
+    fn random(&mut self) -> f64 {
+        self.random = self.random.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.random;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+    }
+}
+
+impl App for Particles {
+    fn set_seed(&mut self, seed: u64) {
+        self.random = seed;
+    }
+
+    fn setup(&mut self, _world: &mut World, camera: &mut Camera) -> Result<(), Box<dyn Error>> {
+        *camera = Camera::new(Vec3::new(0.0, -4.0, 16.0));
+        let texture = Arc::unwrap_or_clone(Object::from_obj("./resources/cube")?.texture);
+        self.sprite = Some(Sprite::new(texture, Vec2::new(SIZE, SIZE)));
+        Ok(())
+    }
+
+    fn update(&mut self, world: &mut World, camera: &mut Camera, input: &mut Input, delta: f64) {
+        controls::fly(camera, input, delta);
+        // Debug lines are cleared by every update, so the ground is marked out again each time
+        world.debug_grid(1.0);
+        let gravity = world.physics.gravity;
+
+        for _ in 0..SPAWN_RATE {
+            let Some(sprite) = self.sprite.clone() else {
+                break;
+            };
+            let velocity = Vec3::new(
+                self.random() * SPREAD,
+                LAUNCH_SPEED * (1.0 + self.random() * 0.2),
+                self.random() * SPREAD,
+            );
+            let position = Vec3::new(0.0, SIZE, 0.0);
+            let id = world.add_sprite(sprite, position);
+            self.particles.push(Particle {
+                id,
+                position,
+                velocity,
+                age: 0.0,
+            });
+        }
+
+        self.particles.retain_mut(|particle| {
+            particle.age += delta;
+            if particle.age >= LIFETIME {
+                world.remove(particle.id);
+                return false;
+            }
+            particle.velocity = particle.velocity + gravity * delta;
+            particle.position = particle.position + particle.velocity * delta;
+            if particle.position.y < SIZE / 2.0 && particle.velocity.y < 0.0 {
+                particle.position.y = SIZE / 2.0;
+                particle.velocity.y = -particle.velocity.y * BOUNCE;
+            }
+            let transformation = Mat4::identity().translate(particle.position);
+            world.set_base_transform(particle.id, transformation);
+            world.set_opacity(particle.id, 1.0 - particle.age / LIFETIME);
+            true
+        });
+    }
+
+    fn draw_overlay(&mut self, renderer: &mut Renderer, stats: &FrameStats) {
+        crate::draw_title(renderer, stats);
+        let count = format!("{} particles", self.particles.len());
+        renderer.write_text(&count, Vec2::new(5.0, 50.0), 14.0);
+    }
+}
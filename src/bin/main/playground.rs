@@ -0,0 +1,238 @@
+use std::error::Error;
+
+use megavertex::{
+    App, AssetManager, Camera, CharacterController, Cloth, Collider, ConvexHull, Fog, FrameStats,
+    Input, Light, Mat4, Object, Plane, Renderer, Rigidbody, Scene, Toon, Ui, Vec2, Vec3, World,
+};
+
+use crate::controls;
+
+// The settings panel, in pixels from the top right of the window
+const PANEL_WIDTH: f64 = 170.0;
+const PANEL_MARGIN: f64 = 5.0;
+const FOG_COLOUR: Vec3 = Vec3 {
+    x: 0.6,
+    y: 0.7,
+    z: 0.8,
+};
+
+///
+/// What can be changed from the settings panel, which F1 shows and hides. Angles are in degrees.
+///
+#[derive(Debug, Copy, Clone, Default)]
+struct Settings {
+    fov: f64,
+    // Which way the sun shines, around the vertical axis
+    sun_angle: f64,
+    fog: bool,
+    fog_end: f64,
+}
+
+impl Settings {
+    fn apply(&self, world: &mut World, camera: &mut Camera) {
+        camera.fov = self.fov.to_radians();
+        for light in &mut world.lights {
+            if let Light::Directional { direction, .. } = light {
+                let (sin, cos) = self.sun_angle.to_radians().sin_cos();
+                let horizontal = direction.x.hypot(direction.z);
+                *direction = Vec3::new(cos * horizontal, direction.y, sin * horizontal);
+            }
+        }
+        world.environment.fog = self.fog.then_some(Fog {
+            colour: FOG_COLOUR,
+            start: self.fog_end / 4.0,
+            end: self.fog_end,
+        });
+    }
+}
+
+///
+/// The scene from resources/scene.ron, along with a stack of cubes and a banner, which can be flown or walked around.
+///
+pub struct Playground {
+    assets: AssetManager,
+    // Thrown when the throw action is let go
+    shot: Option<Object>,
+    // Set while walking around the scene rather than flying
+    walker: Option<CharacterController>,
+    // Set while the physics simulation is drawn over the scene
+    show_physics: bool,
+    // The size of the window, for finding what the mouse is pointing at
+    width: usize,
+    height: usize,
+    ui: Ui,
+    show_ui: bool,
+    settings: Settings,
+    // What the settings were when the scene was loaded, for putting them back
+    loaded_settings: Settings,
+    #[cfg(feature = "scripting")]
+    scripts: megavertex::ScriptHost,
+}
+
+impl Playground {
+    pub fn new(width: usize, height: usize) -> Playground {
+        Playground {
+            assets: AssetManager::new(),
+            shot: None,
+            walker: None,
+            show_physics: false,
+            width,
+            height,
+            ui: Ui::new(
+                Vec2::new(width as f64 - PANEL_WIDTH - PANEL_MARGIN, PANEL_MARGIN),
+                PANEL_WIDTH,
+            ),
+            show_ui: false,
+            settings: Settings::default(),
+            loaded_settings: Settings::default(),
+            #[cfg(feature = "scripting")]
+            scripts: megavertex::ScriptHost::new(),
+        }
+    }
+}
+
+impl App for Playground {
+    fn setup(&mut self, world: &mut World, camera: &mut Camera) -> Result<(), Box<dyn Error>> {
+        // Models are added to the scene in resources/scene.ron
+        let scene = Scene::load("./resources/scene.ron")?;
+        *camera = scene.spawn(world, &mut self.assets)?;
+
+        // A stack of cel shaded cubes dropped onto an invisible floor, which bounce and settle on their hulls
+        let cube = self.assets.load("./resources/cube")?;
+        let hull = ConvexHull::from_object(&cube).ok_or("the cube model is flat")?;
+        let floor = world.add_object(cube.clone(), Vec3::new(-6.0, -2.0, 0.0));
+        world.set_visible(floor, false);
+        world.insert_component(
+            floor,
+            Rigidbody::new(cube.clone())
+                .with_mass(0.0)
+                .with_collider(Collider::Plane(Plane::new(Vec3::new(0.0, 1.0, 0.0), 0.0))),
+        );
+        for height in [4.0, 7.0, 10.0] {
+            let id = world.add_object(cube.clone(), Vec3::new(-6.0, height, 0.0));
+            let collider = Collider::Hull(hull.clone());
+            world.insert_component(id, Rigidbody::new(cube.clone()).with_collider(collider));
+            world.set_toon(id, Toon::default());
+        }
+
+        // Holding E aims a small cube, which is thrown when it's let go
+        let mut shot = cube.clone();
+        shot.transform(Mat4::identity().scale(Vec3::new(0.2, 0.2, 0.2)));
+        shot.bake_transform();
+        self.shot = Some(shot);
+
+        // A banner hanging from its top corners beside the cubes
+        let mut banner = Cloth::new(
+            Vec3::new(-12.0, 4.0, 0.0),
+            Vec3::new(3.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 2.0),
+            12,
+            8,
+        );
+        banner.pin(0, 0);
+        banner.pin(12, 0);
+        world.add_cloth(banner);
+
+        let sun = world.lights.iter().find_map(|light| match light {
+            Light::Directional { direction, .. } => Some(direction.z.atan2(direction.x)),
+            _ => None,
+        });
+        self.settings = Settings {
+            fov: camera.fov.to_degrees(),
+            sun_angle: sun.unwrap_or_default().to_degrees(),
+            fog: world.environment.fog.is_some(),
+            fog_end: world.environment.fog.map_or(60.0, |fog| fog.end),
+        };
+        self.loaded_settings = self.settings;
+
+        #[cfg(feature = "scripting")]
+        self.scripts.load("./resources/scene.rhai")?;
+        Ok(())
+    }
+
+    fn update(&mut self, world: &mut World, camera: &mut Camera, input: &mut Input, delta: f64) {
+        self.ui.read_input(input);
+        for (name, error) in world.poll_assets() {
+            eprintln!("failed to load {}: {}", name, error);
+        }
+        #[cfg(feature = "hot-reload")]
+        for object in self.assets.poll_changes() {
+            world.reload(&object);
+        }
+
+        // F switches between flying and walking on the ground
+        if input.is_action_pressed("toggle_walk") {
+            self.walker = match self.walker {
+                Some(_) => None,
+                None => {
+                    let mut character = CharacterController::new(Vec3::default(), 0.4, 1.8);
+                    character.position =
+                        -camera.position() - (character.eye() - character.position);
+                    Some(character)
+                }
+            };
+        }
+
+        // P shows and hides the colliders, contacts and velocities of the physics simulation
+        if input.is_action_pressed("toggle_physics") {
+            self.show_physics = !self.show_physics;
+        }
+
+        // F1 shows and hides the settings panel, which changes the scene live
+        if input.is_action_pressed("toggle_settings") {
+            self.show_ui = !self.show_ui;
+        }
+        if self.show_ui {
+            self.settings.apply(world, camera);
+        }
+
+        match &mut self.walker {
+            Some(character) => {
+                controls::walk(character, world, camera, input, delta);
+                controls::look(camera, input, delta);
+            }
+            None => controls::fly(camera, input, delta),
+        }
+
+        // Clicking on a body pushes it away from the camera, unless the click was on the settings panel
+        if !(self.show_ui && self.ui.wants_mouse()) {
+            controls::push(world, camera, input, self.width, self.height);
+        }
+
+        if self.show_physics {
+            world.debug_physics();
+        }
+
+        if let Some(shot) = &self.shot {
+            controls::throw(world, camera, input, shot);
+        }
+
+        #[cfg(feature = "scripting")]
+        {
+            let keys = input.keys().iter().map(|key| format!("{:?}", key));
+            self.scripts.set_keys(keys);
+            for (path, error) in self.scripts.update(world, delta) {
+                eprintln!("{}: {}", path, error);
+            }
+        }
+    }
+
+    fn draw_overlay(&mut self, renderer: &mut Renderer, stats: &FrameStats) {
+        crate::draw_title(renderer, stats);
+
+        if self.show_ui {
+            let settings = &mut self.settings;
+            let loaded = self.loaded_settings;
+            self.ui.panel(renderer, |panel| {
+                panel.label("settings (F1 to hide)");
+                panel.slider("fov", &mut settings.fov, 30.0..=120.0);
+                panel.slider("sun angle", &mut settings.sun_angle, -180.0..=180.0);
+                panel.checkbox("fog", &mut settings.fog);
+                panel.slider("fog distance", &mut settings.fog_end, 10.0..=200.0);
+                if panel.button("reset") {
+                    *settings = loaded;
+                }
+            });
+        }
+    }
+}
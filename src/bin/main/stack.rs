@@ -0,0 +1,104 @@
+use std::error::Error;
+
+use megavertex::{
+    App, BlobShadow, Camera, Collider, ConvexHull, FrameStats, Input, Light, Mat4, Object, Plane,
+    Renderer, Rigidbody, Vec2, Vec3, World,
+};
+
+use crate::controls;
+
+// How many cubes are in the bottom row of the pyramid, which has one fewer in each row above
+const BASE: usize = 6;
+
+// The space left between cubes, so they don't start off touching and pushing each other apart
+const GAP: f64 = 0.05;
+
+// How far the floor reaches from the middle of the pyramid each way
+const FLOOR_SIZE: f64 = 20.0;
+
+///
+/// A pyramid of cubes on a floor, for knocking down by clicking on it and throwing things at it.
+///
+pub struct Stack {
+    // Thrown when the throw action is let go
+    shot: Option<Object>,
+    show_physics: bool,
+    // The size of the window, for finding what the mouse is pointing at
+    width: usize,
+    height: usize,
+}
+
+impl Stack {
+    pub fn new(width: usize, height: usize) -> Stack {
+        Stack {
+            shot: None,
+            show_physics: false,
+            width,
+            height,
+        }
+    }
+}
+
+impl App for Stack {
+    fn setup(&mut self, world: &mut World, camera: &mut Camera) -> Result<(), Box<dyn Error>> {
+        *camera = Camera::new(Vec3::new(0.0, -5.0, 24.0));
+        world.lights.push(Light::Directional {
+            direction: Vec3::new(-0.5, -1.0, -0.3),
+            colour: Vec3::new(1.0, 1.0, 1.0),
+            intensity: 1.0,
+        });
+
+        // The floor is a flattened cube, with its top at zero
+        let cube = Object::from_obj("./resources/cube")?;
+        let hull = ConvexHull::from_object(&cube).ok_or("the cube model is flat")?;
+        let mut floor = cube.clone();
+        floor.transform(Mat4::identity().scale(Vec3::new(FLOOR_SIZE, 0.1, FLOOR_SIZE)));
+        floor.bake_transform();
+        let id = world.add_object(floor.clone(), Vec3::new(0.0, -0.1, 0.0));
+        world.insert_component(
+            id,
+            Rigidbody::new(floor)
+                .with_mass(0.0)
+                .with_collider(Collider::Plane(Plane::new(Vec3::new(0.0, 1.0, 0.0), -0.1))),
+        );
+
+        // Cubes are two units across, and cast blob shadows onto the floor and each other
+        for row in 0..BASE {
+            let count = BASE - row;
+            for column in 0..count {
+                let x = (column as f64 - (count - 1) as f64 / 2.0) * (2.0 + GAP);
+                let y = 1.0 + row as f64 * (2.0 + GAP);
+                let id = world.add_object(cube.clone(), Vec3::new(x, y, 0.0));
+                let collider = Collider::Hull(hull.clone());
+                world.insert_component(id, Rigidbody::new(cube.clone()).with_collider(collider));
+                world.set_blob_shadow(id, BlobShadow::default());
+            }
+        }
+
+        let mut shot = cube;
+        shot.transform(Mat4::identity().scale(Vec3::new(0.2, 0.2, 0.2)));
+        shot.bake_transform();
+        self.shot = Some(shot);
+        Ok(())
+    }
+
+    fn update(&mut self, world: &mut World, camera: &mut Camera, input: &mut Input, delta: f64) {
+        if input.is_action_pressed("toggle_physics") {
+            self.show_physics = !self.show_physics;
+        }
+        controls::fly(camera, input, delta);
+        controls::push(world, camera, input, self.width, self.height);
+        if let Some(shot) = &self.shot {
+            controls::throw(world, camera, input, shot);
+        }
+        if self.show_physics {
+            world.debug_physics();
+        }
+    }
+
+    fn draw_overlay(&mut self, renderer: &mut Renderer, stats: &FrameStats) {
+        crate::draw_title(renderer, stats);
+        let hint = "click to push, hold E to throw, P shows the physics";
+        renderer.write_text(hint, Vec2::new(5.0, 50.0), 14.0);
+    }
+}
@@ -0,0 +1,123 @@
+use std::error::Error;
+
+use megavertex::{
+    App, Camera, CharacterController, Colour, Fog, FrameStats, Input, Light, Mat4, Object,
+    Renderer, Scatter, ScatterModel, Sprite, Terrain, Texture, Vec2, Vec3, Water, World,
+};
+
+use crate::controls;
+
+// The terrain is this many chunks of 16 by 16 samples each way, a unit apart, centred on the origin
+const CHUNKS: usize = 8;
+const HALF_SIZE: f64 = 64.0;
+
+// How high the sea is, which fills the lowest valleys
+const SEA_LEVEL: f64 = -1.5;
+
+// How many grass tufts and rocks are scattered over the hills, and how far away they're still drawn
+const TUFTS: usize = 6000;
+const ROCKS: usize = 300;
+const SCATTER_DISTANCE: f64 = 30.0;
+
+///
+/// Rolling hills around a sea, for walking over the terrain with a character controller, or flying over it with F.
+///
+pub struct TerrainWalk {
+    // Set while walking, rather than flying
+    walker: Option<CharacterController>,
+}
+
+impl TerrainWalk {
+    pub fn new() -> TerrainWalk {
+        TerrainWalk { walker: None }
+    }
+}
+
+///
+/// Returns how high the hills are at a point, measured from the terrain's corner.
+///
+fn hills(x: f64, z: f64) -> f64 {
+    (x * 0.1).sin() * 3.0 + (z * 0.08).cos() * 3.0 + (x * 0.3 + z * 0.2).sin() * 0.5
+}
+
+impl App for TerrainWalk {
+    fn setup(&mut self, world: &mut World, camera: &mut Camera) -> Result<(), Box<dyn Error>> {
+        world.lights.push(Light::Directional {
+            direction: Vec3::new(-0.4, -1.0, 0.6),
+            colour: Vec3::new(1.0, 1.0, 1.0),
+            intensity: 1.0,
+        });
+        world.environment.fog = Some(Fog {
+            colour: Vec3::new(0.6, 0.7, 0.8),
+            start: 20.0,
+            end: 80.0,
+        });
+
+        let mut rock = Object::from_obj("./resources/cube")?;
+        let mut terrain = Terrain::from_fn(
+            CHUNKS,
+            CHUNKS,
+            16,
+            1.0,
+            Texture::clone(&rock.texture),
+            hills,
+        );
+        terrain.origin = Vec3::new(-HALF_SIZE, 0.0, -HALF_SIZE);
+
+        // Grass and rocks are scattered in chunks the size of the terrain's, so they're culled alongside them
+        let grass = Sprite::new(
+            Texture::solid(Colour::from_hex(0x4f8a2b)),
+            Vec2::new(0.15, 0.3),
+        );
+        let mut tufts = Scatter::new(ScatterModel::Billboard(grass), 16.0, SCATTER_DISTANCE);
+        tufts.scatter_over(&terrain, TUFTS, (0.6, 1.4), 1);
+        rock.transform(Mat4::identity().scale(Vec3::new(0.2, 0.12, 0.2)));
+        let mut rocks = Scatter::new(ScatterModel::Mesh(Box::new(rock)), 16.0, SCATTER_DISTANCE);
+        rocks.scatter_over(&terrain, ROCKS, (0.5, 1.5), 2);
+        world.scatters.extend([tufts, rocks]);
+        world.set_terrain(terrain);
+        world.add_water(
+            Water::new(HALF_SIZE * 2.0, 32),
+            Vec3::new(0.0, SEA_LEVEL, 0.0),
+        );
+
+        // The walk starts in the middle, standing on the ground
+        let ground = world.terrain_height(0.0, 0.0).unwrap_or_default();
+        let character = CharacterController::new(Vec3::new(0.0, ground, 0.0), 0.4, 1.8);
+        camera.set_position(-character.eye());
+        self.walker = Some(character);
+        Ok(())
+    }
+
+    fn update(&mut self, world: &mut World, camera: &mut Camera, input: &mut Input, delta: f64) {
+        // F switches between walking and flying, landing wherever the camera is
+        if input.is_action_pressed("toggle_walk") {
+            self.walker = match self.walker {
+                Some(_) => None,
+                None => {
+                    let mut character = CharacterController::new(Vec3::default(), 0.4, 1.8);
+                    character.position =
+                        -camera.position() - (character.eye() - character.position);
+                    Some(character)
+                }
+            };
+        }
+
+        match &mut self.walker {
+            Some(character) => {
+                controls::walk(character, world, camera, input, delta);
+                controls::look(camera, input, delta);
+            }
+            None => controls::fly(camera, input, delta),
+        }
+    }
+
+    fn draw_overlay(&mut self, renderer: &mut Renderer, stats: &FrameStats) {
+        crate::draw_title(renderer, stats);
+        let hint = match self.walker {
+            Some(_) => "walking - space to jump, F to fly",
+            None => "flying - F to walk",
+        };
+        renderer.write_text(hint, Vec2::new(5.0, 50.0), 14.0);
+    }
+}
@@ -0,0 +1,122 @@
+use std::{error::Error, f64::consts::PI, fs};
+
+use megavertex::{
+    App, Camera, FrameStats, Input, Light, MouseButton, Object, Renderer, Vec3, World,
+};
+
+// How fast the arrow keys orbit around the model, and how fast it turns by itself, in radians per update
+const ORBIT_SPEED: f64 = 0.05;
+const SPIN_SPEED: f64 = 0.005;
+
+// How much of the distance to the model each update of zooming with the keys, or step of the mouse wheel, covers
+const ZOOM_SPEED: f64 = 0.05;
+
+// How far the view can tilt up or down, just short of straight over the model where the orbit would flip
+const MAX_PITCH: f64 = PI / 2.0 - 0.01;
+
+// How much room is left around the model when it's framed
+const FRAME_MARGIN: f64 = 1.1;
+
+///
+/// Orbits the camera around the cow, which turns slowly until it's dragged around with the mouse or arrow keys.
+///
+pub struct ModelViewer {
+    width: usize,
+    height: usize,
+    // How far away the camera starts, so the whole model fits on screen
+    home_distance: f64,
+    distance: f64,
+    yaw: f64,
+    pitch: f64,
+    // Set once the view has been moved by hand, which stops the model turning by itself
+    moved: bool,
+}
+
+impl ModelViewer {
+    pub fn new(width: usize, height: usize) -> ModelViewer {
+        ModelViewer {
+            width,
+            height,
+            home_distance: 1.0,
+            distance: 1.0,
+            yaw: 0.0,
+            pitch: 0.0,
+            moved: false,
+        }
+    }
+
+    fn reset_view(&mut self) {
+        self.distance = self.home_distance;
+        self.yaw = -PI / 2.0;
+        self.pitch = 0.0;
+        self.moved = false;
+    }
+
+    ///
+    /// Places the camera `distance` away from the origin, looking at it.
+    ///
+    fn place_camera(&self, camera: &mut Camera) {
+        camera.set_rotation(Vec3::new(self.pitch, self.yaw, 0.0));
+        // The camera looks down its negative forward direction, which is up crossed with right
+        let look = camera.up.cross_product(camera.right);
+        camera.set_position(look * self.distance);
+    }
+}
+
+impl App for ModelViewer {
+    fn setup(&mut self, world: &mut World, camera: &mut Camera) -> Result<(), Box<dyn Error>> {
+        // The cow has no texture coordinates of its own, so it's drawn in a single colour from the cube's texture
+        let cow = Object::from_obj_bytes(
+            &fs::read("./resources/cow.obj")?,
+            &fs::read("./resources/cube.png")?,
+        )?;
+        let bounds = cow.local_bounds().ok_or("the cow model has no vertices")?;
+        world.add_object(cow, -bounds.centre());
+        world.lights.push(Light::Directional {
+            direction: Vec3::new(-0.5, -1.0, -0.3),
+            colour: Vec3::new(1.0, 1.0, 1.0),
+            intensity: 1.0,
+        });
+
+        // Back off until a sphere around the model fits in the narrower of the two fields of view
+        let horizontal = (camera.fov / 2.0).tan();
+        let vertical = horizontal * self.height as f64 / self.width as f64;
+        let half_angle = horizontal.min(vertical).atan();
+        let radius = bounds.size().length() / 2.0;
+        self.home_distance = (radius / half_angle.sin()).max(radius) * FRAME_MARGIN;
+        self.reset_view();
+        self.place_camera(camera);
+        Ok(())
+    }
+
+    fn update(&mut self, _world: &mut World, camera: &mut Camera, input: &mut Input, _delta: f64) {
+        if input.is_action_pressed("reset_view") {
+            self.reset_view();
+        }
+
+        // Dragging turns the model the way the mouse moves, so the camera orbits the other way
+        let yaw = input.action_axis("look_left", "look_right");
+        let pitch = input.action_axis("look_down", "look_up");
+        if input.is_mouse_down(MouseButton::Left) {
+            let drag = input.mouse_look();
+            self.pitch -= drag.x;
+            self.yaw -= drag.y;
+            self.moved = true;
+        }
+        self.moved |= yaw != 0.0 || pitch != 0.0;
+        self.yaw += yaw * ORBIT_SPEED;
+        self.pitch += pitch * ORBIT_SPEED;
+        self.pitch = self.pitch.clamp(-MAX_PITCH, MAX_PITCH);
+        if !self.moved {
+            self.yaw += SPIN_SPEED;
+        }
+
+        let zoom = input.scroll() + input.action_axis("move_forward", "move_back");
+        self.distance *= (1.0 - ZOOM_SPEED).powf(zoom);
+        self.place_camera(camera);
+    }
+
+    fn draw_overlay(&mut self, renderer: &mut Renderer, stats: &FrameStats) {
+        crate::draw_title(renderer, stats);
+    }
+}
@@ -0,0 +1,95 @@
+use std::{env, process};
+
+#[path = "../../benches/scenes/mod.rs"]
+mod scenes;
+
+const USAGE: &str = "usage: render_bench [options] [scene]...
+
+Renders standard scenes without a window and reports how long each stage of a frame takes, and how many triangles
+are drawn a second. With no scenes given, all of them are rendered. Run with --release for meaningful numbers.
+
+options:
+    --frames <count>         how many frames to time for each scene (default 100)
+    --size <width>x<height>  the size of the rendered image in pixels (default 600x400)
+    --list                   lists the scenes and exits";
+
+///
+/// The options given on the command line.
+///
+struct Options {
+    scenes: Vec<&'static scenes::BenchScene>,
+    frames: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options {
+            scenes: vec![],
+            frames: 100,
+            width: 600,
+            height: 400,
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--frames" => options.frames = parse_count(&value()?)?,
+                "--size" => (options.width, options.height) = parse_size(&value()?)?,
+                "--list" => {
+                    for scene in &scenes::SCENES {
+                        println!("{:<10} {}", scene.name, scene.description);
+                    }
+                    process::exit(0);
+                }
+                "-h" | "--help" => return Err(String::new()),
+                _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+                _ => options
+                    .scenes
+                    .push(scenes::find(&arg).ok_or(format!("there's no scene called {}", arg))?),
+            }
+        }
+
+        if options.scenes.is_empty() {
+            options.scenes = scenes::SCENES.iter().collect();
+        }
+        Ok(options)
+    }
+}
+
+fn parse_count(text: &str) -> Result<usize, String> {
+    text.parse::<usize>()
+        .ok()
+        .filter(|count| *count > 0)
+        .ok_or(format!("{} isn't a positive whole number", text))
+}
+
+fn parse_size(text: &str) -> Result<(usize, usize), String> {
+    let size = text.split_once('x').and_then(|(width, height)| {
+        let (width, height) = (width.parse().ok()?, height.parse().ok()?);
+        (width > 0 && height > 0).then_some((width, height))
+    });
+    size.ok_or(format!("{} isn't a size like 600x400", text))
+}
+
+fn main() {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            if !error.is_empty() {
+                eprintln!("{}\n", error);
+            }
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    println!("rendering at {}x{}\n", options.width, options.height);
+    for scene in options.scenes {
+        scene
+            .profile(options.width, options.height, options.frames)
+            .print(scene);
+    }
+}
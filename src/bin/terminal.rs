@@ -0,0 +1,124 @@
+use std::{
+    env, process,
+    time::{Duration, Instant},
+};
+
+use megavertex::{FramePacing, PresentTarget, Terminal, Vec3};
+
+// Only the scenes are needed, not the code for timing them
+#[allow(dead_code)]
+#[path = "../../benches/scenes/mod.rs"]
+mod scenes;
+
+const USAGE: &str = "usage: terminal [options] [scene]
+
+Draws one of the standard scenes in the terminal with coloured half blocks, two pixels to a character, slowly panning
+the camera from side to side. The terminal needs to support 24-bit colour. Press Ctrl-C to stop.
+
+options:
+    --size <width>x<height>  the size of the rendered image in pixels, and so in columns and twice the rows
+                             (default 80x48)
+    --fps <rate>             how many frames to draw a second (default 30)
+    --frames <count>         stops after this many frames, rather than running until stopped
+    --list                   lists the scenes and exits";
+
+// How far the camera pans each way, and how quickly, in radians
+const PAN_ANGLE: f64 = 0.4;
+const PAN_SPEED: f64 = 0.5;
+
+///
+/// The options given on the command line.
+///
+struct Options {
+    scene: &'static scenes::BenchScene,
+    width: usize,
+    height: usize,
+    fps: usize,
+    frames: Option<usize>,
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options {
+            scene: &scenes::SCENES[0],
+            width: 80,
+            height: 48,
+            fps: 30,
+            frames: None,
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--size" => (options.width, options.height) = parse_size(&value()?)?,
+                "--fps" => options.fps = parse_count(&value()?)?,
+                "--frames" => options.frames = Some(parse_count(&value()?)?),
+                "--list" => {
+                    for scene in &scenes::SCENES {
+                        println!("{:<10} {}", scene.name, scene.description);
+                    }
+                    process::exit(0);
+                }
+                "-h" | "--help" => return Err(String::new()),
+                _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+                _ => {
+                    options.scene =
+                        scenes::find(&arg).ok_or(format!("there's no scene called {}", arg))?
+                }
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn parse_count(text: &str) -> Result<usize, String> {
+    text.parse::<usize>()
+        .ok()
+        .filter(|count| *count > 0)
+        .ok_or(format!("{} isn't a positive whole number", text))
+}
+
+fn parse_size(text: &str) -> Result<(usize, usize), String> {
+    let size = text.split_once('x').and_then(|(width, height)| {
+        let (width, height) = (width.parse().ok()?, height.parse().ok()?);
+        (width > 0 && height > 0).then_some((width, height))
+    });
+    size.ok_or(format!("{} isn't a size like 80x48", text))
+}
+
+fn main() {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            if !error.is_empty() {
+                eprintln!("{}\n", error);
+            }
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    let mut headless = options.scene.headless(options.width, options.height);
+    let mut terminal = Terminal::stdout();
+    let frame_time = Duration::from_secs_f64(1.0 / options.fps as f64);
+    headless.frame_time = frame_time.as_secs_f64();
+    let mut yaw = 0.0;
+    let mut frame = 0;
+
+    while options.frames.is_none_or(|frames| frame < frames) {
+        let start = Instant::now();
+
+        let target = (frame as f64 * headless.frame_time * PAN_SPEED).sin() * PAN_ANGLE;
+        headless.camera.rotate(Vec3::new(0.0, target - yaw, 0.0));
+        yaw = target;
+        headless.render_frame();
+        if let Err(error) = terminal.present_frame(&headless.renderer) {
+            eprintln!("failed to draw in the terminal: {}", error);
+            process::exit(1);
+        }
+
+        frame += 1;
+        FramePacing::Sleep.wait_until(start + frame_time);
+    }
+}
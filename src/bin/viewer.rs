@@ -0,0 +1,249 @@
+use std::{env, error::Error, f64::consts::PI, process};
+
+use megavertex::{
+    Aabb, App, AppConfig, Bindings, Camera, Colour, Input, Key, Mat4, MouseButton, Object,
+    RendererConfig, Vec3, WindowConfig, World,
+};
+
+const USAGE: &str = "usage: viewer [options] <model>...
+
+Shows one or more models side by side, framed to fit the window. Models are .obj files with a .png texture of the
+same name. Drag with the mouse or use the arrow keys to orbit, scroll or use W and S to zoom, and press R to reset
+the view.
+
+options:
+    --size <width>x<height>  the size of the window in pixels (default 800x600)
+    --scale <factor>         scales every model (default 1)
+    --fov <degrees>          the horizontal field of view (default 53)
+    --background <rrggbb>    the background colour, in hex (default 202020)";
+
+// How fast the arrow keys orbit around the models, in radians per update
+const ORBIT_SPEED: f64 = 0.05;
+
+// How much of the distance to the models each update of zooming with the keys, or step of the mouse wheel, covers
+const ZOOM_SPEED: f64 = 0.05;
+
+// How far the view can tilt up or down, just short of straight over the models where the orbit would flip
+const MAX_PITCH: f64 = PI / 2.0 - 0.01;
+
+// The space left between models shown side by side, as a fraction of the widest one
+const MODEL_GAP: f64 = 0.2;
+
+// How much room is left around the models when they're framed
+const FRAME_MARGIN: f64 = 1.1;
+
+///
+/// The options given on the command line.
+///
+struct Options {
+    models: Vec<String>,
+    width: usize,
+    height: usize,
+    scale: f64,
+    fov: Option<f64>,
+    background: Colour,
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options {
+            models: vec![],
+            width: 800,
+            height: 600,
+            scale: 1.0,
+            fov: None,
+            background: Colour::from_hex(0x202020),
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--size" => (options.width, options.height) = parse_size(&value()?)?,
+                "--scale" => options.scale = parse_number(&value()?)?,
+                "--fov" => options.fov = Some(parse_number(&value()?)?.to_radians()),
+                "--background" => options.background = parse_colour(&value()?)?,
+                "-h" | "--help" => return Err(String::new()),
+                _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+                _ => options.models.push(arg),
+            }
+        }
+
+        if options.models.is_empty() {
+            return Err(String::from("no models given"));
+        }
+        Ok(options)
+    }
+}
+
+fn parse_number(text: &str) -> Result<f64, String> {
+    text.parse::<f64>()
+        .ok()
+        .filter(|number| *number > 0.0)
+        .ok_or(format!("{} isn't a positive number", text))
+}
+
+fn parse_size(text: &str) -> Result<(usize, usize), String> {
+    let size = text.split_once('x').and_then(|(width, height)| {
+        let (width, height) = (width.parse().ok()?, height.parse().ok()?);
+        (width > 0 && height > 0).then_some((width, height))
+    });
+    size.ok_or(format!("{} isn't a size like 800x600", text))
+}
+
+fn parse_colour(text: &str) -> Result<Colour, String> {
+    let hex = text.trim_start_matches('#');
+    let colour = u32::from_str_radix(hex, 16)
+        .ok()
+        .filter(|_| hex.len() == 6)
+        .ok_or(format!("{} isn't a colour like 202020", text))?;
+    Ok(Colour::from_hex(colour))
+}
+
+///
+/// Loads a model from a path, with or without its extension.
+///
+fn load(path: &str) -> Result<Object, Box<dyn Error>> {
+    #[cfg(feature = "gltf")]
+    for extension in [".glb", ".gltf"] {
+        if let Some(name) = path.strip_suffix(extension) {
+            return Ok(Object::from_gltf(name)?);
+        }
+    }
+    let name = path.strip_suffix(".obj").unwrap_or(path);
+    Object::from_obj(name).map_err(|error| format!("failed to load {}: {}", path, error).into())
+}
+
+///
+/// Orbits the camera around the models it was given.
+///
+struct Viewer {
+    options: Options,
+    // The point the camera orbits around, and how far away it starts so everything fits on screen
+    target: Vec3,
+    home_distance: f64,
+    distance: f64,
+    yaw: f64,
+    pitch: f64,
+}
+
+impl Viewer {
+    fn reset_view(&mut self) {
+        self.distance = self.home_distance;
+        self.yaw = -PI / 2.0;
+        self.pitch = 0.0;
+    }
+
+    ///
+    /// Places the camera `distance` away from the target, looking at it.
+    ///
+    fn place_camera(&self, camera: &mut Camera) {
+        camera.set_rotation(Vec3::new(self.pitch, self.yaw, 0.0));
+        // The camera looks down its negative forward direction, which is up crossed with right
+        let look = camera.up.cross_product(camera.right);
+        camera.set_position(-(self.target - look * self.distance));
+    }
+}
+
+impl App for Viewer {
+    fn setup(&mut self, world: &mut World, camera: &mut Camera) -> Result<(), Box<dyn Error>> {
+        if let Some(fov) = self.options.fov {
+            camera.fov = fov;
+        }
+
+        let mut models = vec![];
+        for path in &self.options.models {
+            let mut model = load(path)?;
+            let scale = self.options.scale;
+            model.transform(Mat4::identity().scale(Vec3::new(scale, scale, scale)));
+            model.bake_transform();
+            let bounds = model
+                .local_bounds()
+                .ok_or(format!("{} has no vertices", path))?;
+            models.push((model, bounds));
+        }
+
+        // Models are lined up along the X axis, centred on it
+        let widest = models
+            .iter()
+            .map(|(_, bounds)| bounds.size().x)
+            .fold(0.0, f64::max);
+        let mut left = 0.0;
+        let mut all: Option<Aabb> = None;
+        for (model, bounds) in models {
+            let centre = bounds.centre();
+            let offset = Vec3::new(left - bounds.min.x, -centre.y, -centre.z);
+            let placed = Aabb::new(bounds.min + offset, bounds.max + offset);
+            all = Some(all.map_or(placed, |all| all.union(placed)));
+            left += bounds.size().x + widest * MODEL_GAP;
+            world.add_object(model, offset);
+        }
+        let all = all.ok_or("no models given")?;
+
+        // Back off until a sphere around the models fits in the narrower of the two fields of view
+        let horizontal = (camera.fov / 2.0).tan();
+        let vertical = horizontal * self.options.height as f64 / self.options.width as f64;
+        let half_angle = horizontal.min(vertical).atan();
+        let radius = all.size().length() / 2.0;
+        self.target = all.centre();
+        self.home_distance = (radius / half_angle.sin()).max(radius) * FRAME_MARGIN;
+        self.reset_view();
+        self.place_camera(camera);
+        Ok(())
+    }
+
+    fn update(&mut self, _world: &mut World, camera: &mut Camera, input: &mut Input, _delta: f64) {
+        if input.is_action_pressed("reset_view") {
+            self.reset_view();
+        }
+
+        // Dragging turns the models the way the mouse moves, so the camera orbits the other way
+        if input.is_mouse_down(MouseButton::Left) {
+            let drag = input.mouse_look();
+            self.pitch -= drag.x;
+            self.yaw -= drag.y;
+        }
+        self.yaw += input.action_axis("look_left", "look_right") * ORBIT_SPEED;
+        self.pitch += input.action_axis("look_down", "look_up") * ORBIT_SPEED;
+        self.pitch = self.pitch.clamp(-MAX_PITCH, MAX_PITCH);
+
+        let zoom = input.scroll() + input.action_axis("move_forward", "move_back");
+        self.distance *= (1.0 - ZOOM_SPEED).powf(zoom);
+        self.place_camera(camera);
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            if !error.is_empty() {
+                eprintln!("{}\n", error);
+            }
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    let config = AppConfig {
+        window: WindowConfig {
+            title: format!("megavertex viewer - {}", options.models.join(", ")),
+            ..WindowConfig::default()
+        },
+        renderer: RendererConfig {
+            clear_colour: options.background,
+            ..RendererConfig::new(options.width, options.height)
+        },
+        bindings: Bindings::default().with_action("reset_view", [Key::R]),
+        ..AppConfig::default()
+    };
+    let viewer = Viewer {
+        options,
+        target: Vec3::default(),
+        home_distance: 1.0,
+        distance: 1.0,
+        yaw: 0.0,
+        pitch: 0.0,
+    };
+    Ok(megavertex::run(config, viewer)?)
+}
@@ -0,0 +1,355 @@
+use crate::geometry::{aabb::Aabb, frustum::Frustum, ray::Ray};
+use alloc::{vec, vec::Vec};
+
+///
+/// A node in the hierarchy. Leaves hold a single item, and branches always have two children.
+///
+#[derive(Debug, Clone)]
+struct Node {
+    bounds: Aabb,
+    parent: Option<usize>,
+    kind: NodeKind,
+}
+
+#[derive(Debug, Clone)]
+enum NodeKind {
+    Leaf { item: usize },
+    Branch { left: usize, right: usize },
+}
+
+///
+/// A bounding volume hierarchy - a binary tree of boxes, where each box contains everything below it.
+/// Queries skip any subtree whose box doesn't match, so only a fraction of the items need to be tested.
+///
+/// `T` is whatever identifies an item, e.g. an `EntityId` for objects in a world, or a face index for the triangles of
+/// a single object.
+///
+#[derive(Debug, Clone)]
+pub struct Bvh<T> {
+    nodes: Vec<Node>,
+    items: Vec<T>,
+    leaves: Vec<usize>,
+}
+
+impl<T: Copy> Bvh<T> {
+    ///
+    /// Builds a hierarchy over a set of items and their bounds. Items are split in half along the longest axis of their
+    /// centres at each level, which keeps the tree balanced.
+    ///
+    pub fn build(items: Vec<(T, Aabb)>) -> Bvh<T> {
+        let mut bvh = Bvh {
+            nodes: Vec::with_capacity(items.len() * 2),
+            items: items.iter().map(|(item, _)| *item).collect(),
+            leaves: vec![0; items.len()],
+        };
+
+        if !items.is_empty() {
+            let mut indices: Vec<usize> = (0..items.len()).collect();
+            let bounds: Vec<Aabb> = items.iter().map(|(_, aabb)| *aabb).collect();
+            bvh.build_node(&mut indices, &bounds, None);
+        }
+        bvh
+    }
+
+    fn build_node(
+        &mut self,
+        indices: &mut [usize],
+        bounds: &[Aabb],
+        parent: Option<usize>,
+    ) -> usize {
+        let node = self.nodes.len();
+        let node_bounds = indices
+            .iter()
+            .map(|&i| bounds[i])
+            .reduce(Aabb::union)
+            .expect("nodes are never built from an empty set of items");
+
+        if let [item] = indices {
+            self.nodes.push(Node {
+                bounds: node_bounds,
+                parent,
+                kind: NodeKind::Leaf { item: *item },
+            });
+            self.leaves[*item] = node;
+            return node;
+        }
+
+        // Reserve this node's place before its children, so parents always come before their children
+        self.nodes.push(Node {
+            bounds: node_bounds,
+            parent,
+            kind: NodeKind::Branch { left: 0, right: 0 },
+        });
+
+        let centres = Aabb::from_points(indices.iter().map(|&i| bounds[i].centre()))
+            .expect("indices is not empty");
+        let size = centres.size();
+        let axis = if size.x >= size.y && size.x >= size.z {
+            0
+        } else if size.y >= size.z {
+            1
+        } else {
+            2
+        };
+        let key = |i: &usize| {
+            let centre = bounds[*i].centre();
+            [centre.x, centre.y, centre.z][axis]
+        };
+
+        let middle = indices.len() / 2;
+        indices.select_nth_unstable_by(middle, |a, b| key(a).total_cmp(&key(b)));
+        let (left_indices, right_indices) = indices.split_at_mut(middle);
+
+        let left = self.build_node(left_indices, bounds, Some(node));
+        let right = self.build_node(right_indices, bounds, Some(node));
+        self.nodes[node].kind = NodeKind::Branch { left, right };
+        node
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    ///
+    /// Returns the bounds of everything in the hierarchy, or `None` if it's empty.
+    ///
+    pub fn bounds(&self) -> Option<Aabb> {
+        self.nodes.first().map(|node| node.bounds)
+    }
+
+    ///
+    /// Iterates over every item in the order they were passed to `build`.
+    ///
+    pub fn items(&self) -> impl Iterator<Item = T> + '_ {
+        self.items.iter().copied()
+    }
+
+    ///
+    /// Changes the bounds of a single item, given its position in the list passed to `build`, and grows or shrinks
+    /// the boxes above it to match. The shape of the tree is kept, so this is much cheaper than rebuilding, but the tree
+    /// gets less efficient if items move far from where they started.
+    ///
+    pub fn update(&mut self, index: usize, bounds: Aabb) {
+        let mut node = self.leaves[index];
+        self.nodes[node].bounds = bounds;
+
+        while let Some(parent) = self.nodes[node].parent {
+            if let NodeKind::Branch { left, right } = self.nodes[parent].kind {
+                self.nodes[parent].bounds = self.nodes[left].bounds.union(self.nodes[right].bounds);
+            }
+            node = parent;
+        }
+    }
+
+    ///
+    /// Recalculates the bounds of every item, then every box above them. Items for which `bounds` returns `None` keep
+    /// their current bounds.
+    ///
+    pub fn refit<F>(&mut self, mut bounds: F)
+    where
+        F: FnMut(T) -> Option<Aabb>,
+    {
+        // Children always come after their parents, so working backwards visits children first
+        for node in (0..self.nodes.len()).rev() {
+            self.nodes[node].bounds = match self.nodes[node].kind {
+                NodeKind::Leaf { item } => {
+                    bounds(self.items[item]).unwrap_or(self.nodes[node].bounds)
+                }
+                NodeKind::Branch { left, right } => {
+                    self.nodes[left].bounds.union(self.nodes[right].bounds)
+                }
+            };
+        }
+    }
+
+    ///
+    /// Visits every item whose bounds pass a test. The test is also applied to the boxes containing groups of items,
+    /// so it must pass for any box which contains a box that passes.
+    ///
+    pub fn traverse<F, V>(&self, mut test: F, mut visit: V)
+    where
+        F: FnMut(&Aabb) -> bool,
+        V: FnMut(T, &Aabb),
+    {
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            if !test(&node.bounds) {
+                continue;
+            }
+            match node.kind {
+                NodeKind::Leaf { item } => visit(self.items[item], &node.bounds),
+                NodeKind::Branch { left, right } => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+            }
+        }
+    }
+
+    ///
+    /// Returns every item whose bounds overlap a box.
+    ///
+    pub fn query_aabb(&self, aabb: &Aabb) -> Vec<T> {
+        let mut result = vec![];
+        self.traverse(
+            |bounds| bounds.intersects(aabb),
+            |item, _| result.push(item),
+        );
+        result
+    }
+
+    ///
+    /// Returns every item whose bounds may be inside a frustum.
+    ///
+    pub fn query_frustum(&self, frustum: &Frustum) -> Vec<T> {
+        let mut result = vec![];
+        self.traverse(
+            |bounds| frustum.intersects_aabb(bounds),
+            |item, _| result.push(item),
+        );
+        result
+    }
+
+    ///
+    /// Returns every item whose bounds a ray passes through within `max_distance`, along with the distance at which the
+    /// ray enters them, nearest first.
+    ///
+    pub fn query_ray(&self, ray: &Ray, max_distance: f64) -> Vec<(T, f64)> {
+        let hits = |bounds: &Aabb| {
+            bounds
+                .ray_distance(ray)
+                .filter(|distance| *distance <= max_distance)
+        };
+
+        let mut result = vec![];
+        self.traverse(
+            |bounds| hits(bounds).is_some(),
+            |item, bounds| {
+                if let Some(distance) = hits(bounds) {
+                    result.push((item, distance));
+                }
+            },
+        );
+        result.sort_by(|a, b| a.1.total_cmp(&b.1));
+        result
+    }
+
+    ///
+    /// Returns every pair of items whose bounds overlap, each pair once. This is the broadphase of collision detection -
+    /// only these pairs need their actual shapes tested against each other.
+    ///
+    pub fn overlapping_pairs(&self) -> Vec<(T, T)> {
+        let mut pairs = vec![];
+        for (index, &leaf) in self.leaves.iter().enumerate() {
+            let bounds = self.nodes[leaf].bounds;
+            let mut stack = vec![0];
+            while let Some(node) = stack.pop() {
+                let node = &self.nodes[node];
+                if !node.bounds.intersects(&bounds) {
+                    continue;
+                }
+                match node.kind {
+                    // Only pair with items after this one, so each pair is found once
+                    NodeKind::Leaf { item } if item > index => {
+                        pairs.push((self.items[index], self.items[item]))
+                    }
+                    NodeKind::Leaf { .. } => (),
+                    NodeKind::Branch { left, right } => {
+                        stack.push(right);
+                        stack.push(left);
+                    }
+                }
+            }
+        }
+        pairs
+    }
+}
+
+impl<T: Copy> Default for Bvh<T> {
+    fn default() -> Self {
+        Bvh::build(vec![])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::vec::vec3::Vec3;
+
+    fn unit_box(x: f64) -> Aabb {
+        Aabb::new(Vec3::new(x, 0.0, 0.0), Vec3::new(x + 1.0, 1.0, 1.0))
+    }
+
+    fn row(count: usize) -> Bvh<usize> {
+        Bvh::build((0..count).map(|i| (i, unit_box(i as f64 * 2.0))).collect())
+    }
+
+    #[test]
+    fn query_box() {
+        let bvh = row(10);
+        let mut result = bvh.query_aabb(&Aabb::new(
+            Vec3::new(3.5, 0.0, 0.0),
+            Vec3::new(6.5, 1.0, 1.0),
+        ));
+        result.sort();
+
+        assert_eq!(result, vec![2, 3]);
+        assert_eq!(bvh.bounds(), Some(unit_box(0.0).union(unit_box(18.0))));
+    }
+
+    #[test]
+    fn query_ray_nearest_first() {
+        let bvh = row(10);
+        let ray = Ray::new(Vec3::new(30.0, 0.5, 0.5), Vec3::new(-1.0, 0.0, 0.0));
+        let result: Vec<usize> = bvh
+            .query_ray(&ray, 14.0)
+            .iter()
+            .map(|(item, _)| *item)
+            .collect();
+
+        assert_eq!(result, vec![9, 8]);
+    }
+
+    #[test]
+    fn update_moves_item() {
+        let mut bvh = row(10);
+        bvh.update(0, unit_box(100.0));
+
+        assert!(bvh.query_aabb(&unit_box(0.0)).is_empty());
+        assert_eq!(bvh.query_aabb(&unit_box(100.0)), vec![0]);
+        assert_eq!(bvh.bounds().unwrap().max.x, 101.0);
+    }
+
+    #[test]
+    fn refit_all() {
+        let mut bvh = row(5);
+        bvh.refit(|i| Some(unit_box(i as f64 * -2.0)));
+
+        assert_eq!(bvh.bounds().unwrap().min.x, -8.0);
+        assert_eq!(bvh.query_aabb(&unit_box(-4.0)), vec![2]);
+    }
+
+    #[test]
+    fn find_overlapping_pairs() {
+        let bvh = Bvh::build(vec![
+            (0, unit_box(0.0)),
+            (1, unit_box(0.5)),
+            (2, unit_box(5.0)),
+            (3, unit_box(5.5)),
+            (4, unit_box(10.0)),
+        ]);
+        let mut pairs = bvh.overlapping_pairs();
+        pairs.sort();
+
+        assert_eq!(pairs, vec![(0, 1), (2, 3)]);
+    }
+}
@@ -1,16 +1,46 @@
 use std::f64::consts::PI;
 
 use crate::{
+    batch::TriangleBatch,
+    decal::Decal,
+    geometry::{
+        aabb::Aabb,
+        frustum::{Frustum, Plane},
+        ray::Ray,
+    },
     mat4::Mat4,
-    renderer::Renderer,
-    vec::vec3::{Vec3, ORIGIN, X_AXIS, Y_AXIS},
-    world::World,
+    object::Object,
+    renderer::{Blend, Renderer},
+    scatter::ScatterModel,
+    sprite::Sprite,
+    vec::{
+        vec2::Vec2,
+        vec3::{Vec3, ORIGIN, X_AXIS, Y_AXIS},
+    },
+    world::{EntityId, Layer, World},
 };
 
+// How squarely a face has to face along a decal's projection for the decal to land on it, as the cosine of the angle
+// between them
+const DECAL_MIN_FACING: f64 = 0.2;
+
+// How close to the camera lines are cut off, as points too near the camera project far off screen
+const NEAR_Z: f64 = 0.01;
+
+// How many vertices or faces a mesh needs before they're split across threads, as handing out less work costs more
+// than it saves
+#[cfg(feature = "parallel")]
+const PARALLEL_MIN_ITEMS: usize = 2048;
+
+// The horizontal field of view cameras start with, in radians. Points half as far to the side as they are deep are at
+// the edge of the screen, which is about 53 degrees
+const DEFAULT_FOV: f64 = 0.927_295_218_001_612_2;
+
 ///
 /// Draws a world object
 ///
-/// The camera looks down the negative Z axis.
+/// The camera looks down the negative Z axis. `fov` is its horizontal field of view in radians, and the vertical
+/// field of view follows from the shape of the screen.
 ///
 #[derive(Copy, Clone)]
 pub struct Camera {
@@ -19,6 +49,7 @@ pub struct Camera {
     pub right: Vec3,
     pub up: Vec3,
     rot: Vec3,
+    pub fov: f64,
 }
 
 impl Camera {
@@ -32,11 +63,41 @@ impl Camera {
             right: X_AXIS,
             up: Y_AXIS,
             rot: Vec3::new(0.0, -PI / 2.0, 0.0),
+            fov: DEFAULT_FOV,
         };
         cam.recalc_vectors();
         cam
     }
 
+    ///
+    /// Returns the camera's position.
+    ///
+    pub fn position(&self) -> Vec3 {
+        self.pos
+    }
+
+    ///
+    /// Moves the camera to a new position.
+    ///
+    pub fn set_position(&mut self, pos: Vec3) {
+        self.pos = pos;
+    }
+
+    ///
+    /// Returns the camera's rotation in radians, where X is pitch and Y is yaw.
+    ///
+    pub fn rotation(&self) -> Vec3 {
+        self.rot
+    }
+
+    ///
+    /// Sets the camera's rotation in radians, where X is pitch and Y is yaw.
+    ///
+    pub fn set_rotation(&mut self, rot: Vec3) {
+        self.rot = Vec3::new(0.0, 0.0, 0.0);
+        self.rotate(rot);
+    }
+
     ///
     /// Recalculates the camera's 'right' and 'up' directions based on the current direction
     ///
@@ -68,6 +129,58 @@ impl Camera {
         rotation.mult(translation)
     }
 
+    ///
+    /// Computes the volume of world space visible on a screen of the given size.
+    ///
+    /// Points are projected by dividing by their depth and scaling to fit the field of view across the screen's width,
+    /// so a point is on screen when its X coordinate in camera space is within `tan(fov / 2)` of its depth, and its Y
+    /// coordinate is within `height / width` times that.
+    ///
+    pub fn frustum(self, width: usize, height: usize) -> Frustum {
+        let horizontal = (self.fov / 2.0).tan();
+        let vertical = horizontal * height as f64 / width as f64;
+        let view = self.look_at();
+
+        // In camera space, visible points have negative Z
+        let planes = [
+            Plane::new(Vec3::new(0.0, 0.0, -1.0), 0.0),
+            Plane::new(Vec3::new(1.0, 0.0, -horizontal), 0.0),
+            Plane::new(Vec3::new(-1.0, 0.0, -horizontal), 0.0),
+            Plane::new(Vec3::new(0.0, 1.0, -vertical), 0.0),
+            Plane::new(Vec3::new(0.0, -1.0, -vertical), 0.0),
+        ];
+        Frustum::new(planes.iter().map(|plane| plane.untransform(view)).collect())
+    }
+
+    ///
+    /// Returns a camera `t` of the way from this one to another, blending their positions and rotations.
+    ///
+    pub fn interpolate(self, to: Camera, t: f64) -> Camera {
+        let mut camera = self;
+        camera.pos = self.pos + (to.pos - self.pos) * t;
+        camera.rot = self.rot + (to.rot - self.rot) * t;
+        camera.fov = self.fov + (to.fov - self.fov) * t;
+        camera.recalc_vectors();
+        camera
+    }
+
+    ///
+    /// Returns the ray from the camera through a pixel on a screen of the given size, for picking what's under the
+    /// mouse with `World::raycast` or `World::raycast_bodies`. The ray starts at the camera and undoes the projection
+    /// used by the renderer, so it passes through whatever is drawn at that pixel.
+    ///
+    pub fn screen_ray(self, pixel: Vec2, width: usize, height: usize) -> Ray {
+        // Camera space points are projected by dividing by their depth, which is negative in front of the camera
+        let scale = width as f64 / (2.0 * (self.fov / 2.0).tan());
+        let direction = Vec3::new(
+            (width as f64 / 2.0 - pixel.x) / scale,
+            (height as f64 / 2.0 - pixel.y) / scale,
+            -1.0,
+        );
+        let camera_to_world = self.look_at().inverse().unwrap_or(Mat4::identity());
+        Ray::new(-self.pos, camera_to_world.transform_direction(direction))
+    }
+
     ///
     /// Translate the camera by X and Z on its current plane, based on the direction and right vectors.
     ///
@@ -91,60 +204,748 @@ impl Camera {
     ///
     pub fn rotate(&mut self, offset: Vec3) {
         self.rot = self.rot + offset;
+        self.rot.x = self.rot.x.clamp(-PI / 2.0, PI / 2.0);
+
+        self.recalc_vectors();
+    }
+
+    ///
+    /// Renders each visible object in the world, one layer at a time, over the world's skybox. Objects whose bounds are
+    /// entirely off screen are skipped.
+    ///
+    pub fn render_world(self, renderer: &mut Renderer, world: &World) {
+        let visible = self.visible_entities(world, renderer.width(), renderer.height());
+        self.draw_entities(renderer, world, &visible);
+    }
+
+    ///
+    /// Finds the visible entities whose bounds are at least partly on a screen of the given size, sorted into the order
+    /// they're drawn in. This is the first half of `render_world`.
+    ///
+    pub fn visible_entities(self, world: &World, width: usize, height: usize) -> Vec<EntityId> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("cull").entered();
+        let frustum = self.frustum(width, height);
+        let mut visible = world.query_frustum(&frustum);
+        visible.retain(|id| world.is_visible(*id));
+        world.sort_for_drawing(&mut visible);
+        visible
+    }
+
+    ///
+    /// Draws the world's sky, the given entities in order, and the world's debug lines, then finishes the frame with
+    /// `Renderer::resolve`. This is the second half of `render_world`, taking the entities found by
+    /// `visible_entities`.
+    ///
+    pub fn draw_entities(self, renderer: &mut Renderer, world: &World, visible: &[EntityId]) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("draw", entities = visible.len()).entered();
+        renderer.set_fov(self.fov);
+
+        // The sky goes behind everything, so it's drawn first without touching the depth buffer. Without a sky or fog,
+        // the renderer's clear colour is left showing
+        let environment = &world.environment;
+        let fog = environment.fog.or(renderer.config().fog);
+        if let Some(skybox) = &environment.skybox {
+            let camera_to_world = self.look_at().inverse().unwrap_or(Mat4::identity());
+            renderer.draw_skybox(skybox, camera_to_world);
+        } else if let Some(fog) = fog {
+            renderer.fill(fog.colour.into());
+        }
+
+        let view = self.look_at();
+        // Kept between entities so their memory is reused
+        let mut projected = vec![];
+        let mut triangles = vec![];
+        // Each layer's triangles are collected and rasterised together, in the order set by the renderer's config
+        let mut batch = TriangleBatch::new();
+        let mut current_layer = None;
+        // Scatters are part of the world layer, so they're drawn as soon as it's reached, or after every entity if it
+        // never is
+        let world_blend = Blend {
+            fog,
+            ambient: environment.ambient,
+            ..Blend::OPAQUE
+        };
+        let mut scattered = world.scatters.is_empty();
+        // Decals are drawn over the opaque world entities they reach once the world layer is finished, so the meshes
+        // they land on are kept as they were projected
+        let decal_bounds: Vec<_> = world
+            .decals
+            .iter()
+            .chain(world.blob_shadow_decals())
+            .map(Decal::bounds)
+            .collect();
+        let mut decal_targets = vec![];
+        let mut world_finished = false;
+        for &id in visible {
+            let (Some(object), Some(layer)) = (world.get(id), world.layer(id)) else {
+                continue;
+            };
+            if !scattered && layer >= Layer::World {
+                enter_layer(&mut batch, renderer, &mut current_layer, Layer::World);
+                self.render_scatters(&mut batch, renderer, world, world_blend);
+                scattered = true;
+            }
+            // The world is finished once a layer in front of it is reached, so its decals and depth of field go on
+            // before anything is drawn over it
+            if !world_finished && layer > Layer::World {
+                batch.draw(renderer);
+                self.render_decals(renderer, world, &mut decal_targets, world_blend);
+                renderer.apply_depth_of_field();
+                world_finished = true;
+            }
+            enter_layer(&mut batch, renderer, &mut current_layer, layer);
+
+            // Bodies stepped at a fixed rate are drawn part of the way between their last two steps
+            let transformation = world
+                .render_transformation(id)
+                .unwrap_or(object.transformation);
+
+            // Water reflects the scene as if it were flat, using the normal of its rest plane
+            let reflection = world
+                .water(id)
+                .filter(|water| water.reflectivity > 0.0)
+                .map(|water| {
+                    let up = transformation.transform_direction(Y_AXIS);
+                    let normal = view.transform_direction(up).normalise();
+                    (normal, water.reflectivity)
+                });
+            let blend = Blend {
+                opacity: world.opacity(id).unwrap_or(1.0),
+                reflection,
+                ambient: environment.ambient,
+                fog,
+                texture_mapping: world.texture_mapping(id),
+            };
+            let draw_order = world.draw_order(id).unwrap_or(0);
+
+            if let Some(sprite) = world.sprite(id) {
+                let placement = Mat4::identity().translate(transformation.transform(ORIGIN));
+                self.render_sprite(&mut batch, sprite, placement, world.time, blend, draw_order);
+                continue;
+            }
+
+            // Far away objects can be swapped for simpler ones, which are drawn with the entity's own transformation
+            let mesh = match (world.lod(id), world.bounds(id)) {
+                (Some(lod), Some(bounds)) => {
+                    let distance = view.transform(bounds.centre()).length();
+                    let screen_size = bounds.size().length() / distance.max(f64::EPSILON);
+                    lod.object(lod.select(distance, screen_size))
+                        .unwrap_or(object)
+                }
+                _ => object,
+            };
+
+            // Skinned objects have their vertices posed before being moved into world space
+            let skinned;
+            let vertices = match &mesh.skin {
+                Some(skin) => {
+                    skinned = skin.apply(&mesh.vertices);
+                    &skinned
+                }
+                None => &mesh.vertices,
+            };
+
+            // Vertex modifiers then move the posed vertices, still in the object's own space
+            let modified;
+            let vertices = match world.vertex_modifier(id) {
+                Some(modifier) => {
+                    let normals = mesh.vertex_normals();
+                    modified = vertices
+                        .iter()
+                        .zip(normals)
+                        .map(|(&vertex, normal)| modifier.modify(vertex, normal, world.time))
+                        .collect::<Vec<_>>();
+                    &modified
+                }
+                None => vertices,
+            };
 
-        if self.rot.x > PI / 2.0 {
-            self.rot.x = PI / 2.0;
+            // Each vertex is moved to world space, then camera space, and projected once, however many faces share it
+            map_into(vertices, &mut projected, |&vertex| {
+                project(view.transform(transformation.transform(vertex)))
+            });
+            let decal_target = world.bounds(id).filter(|bounds| {
+                layer == Layer::World
+                    && blend.opacity >= 1.0
+                    && decal_bounds.iter().any(|decal| decal.intersects(bounds))
+            });
+            if let Some(bounds) = decal_target {
+                decal_targets.push((bounds, mesh, projected.clone()));
+            }
+            map_into(&mesh.faces, &mut triangles, |face| {
+                let (a, b, c) = face.vertices;
+                let screen_vertices = [projected[a], projected[b], projected[c]];
+                let (a, b, c) = face.tex_coords;
+                let tex_coords = [mesh.tex_coords[a], mesh.tex_coords[b], mesh.tex_coords[c]];
+                (screen_vertices, tex_coords)
+            });
+
+            // Toon shaded faces are lit in bands by the world's lights, which is worked out in world space
+            let toon = world.toon_shading(id);
+            let shades: Vec<Vec3> = match toon {
+                Some((toon, _)) => mesh
+                    .faces
+                    .iter()
+                    .map(|face| {
+                        let (a, b, c) = face.vertices;
+                        let [a, b, c] = [a, b, c].map(|i| transformation.transform(vertices[i]));
+                        toon.shade(
+                            &world.lights,
+                            (a + b + c) / 3.0,
+                            (b - a).cross_product(c - a),
+                        )
+                    })
+                    .collect(),
+                None => vec![],
+            };
+            let face_blend = |face: usize| match shades.get(face) {
+                Some(shade) => Blend {
+                    ambient: Vec3::new(
+                        blend.ambient.x * shade.x,
+                        blend.ambient.y * shade.y,
+                        blend.ambient.z * shade.z,
+                    ),
+                    ..blend
+                },
+                None => blend,
+            };
+
+            // Affine textures are interpolated across whole quads, so their halves don't bend apart along the cut
+            let quads = match renderer.maps_affinely(blend) {
+                true => mesh.quads(),
+                false => vec![],
+            };
+            for quad in &quads {
+                batch.push_quad(
+                    quad.vertices.map(|vertex| projected[vertex]),
+                    mesh.face_texture(&mesh.faces[quad.faces.0]),
+                    quad.tex_coords.map(|tex_coord| mesh.tex_coords[tex_coord]),
+                    face_blend(quad.faces.0),
+                    draw_order,
+                );
+            }
+            // Quads are in the same order as their faces, so the faces already drawn in one can be skipped in a single pass
+            let mut quads = quads.iter().peekable();
+            for (i, &(screen_vertices, tex_coords)) in triangles.iter().enumerate() {
+                if quads.peek().is_some_and(|quad| quad.faces.0 == i) {
+                    continue;
+                }
+                if quads.next_if(|quad| quad.faces.1 == i).is_some() {
+                    continue;
+                }
+                batch.push(
+                    screen_vertices,
+                    mesh.face_texture(&mesh.faces[i]),
+                    tex_coords,
+                    face_blend(i),
+                    draw_order,
+                );
+            }
+            if let Some((toon, outline)) = toon.filter(|(toon, _)| toon.outline_width > 0.0) {
+                let tex_coords = [Vec2::default(); 3];
+                for triangle in self.outline(mesh, vertices, transformation, toon.outline_width) {
+                    batch.push(triangle, outline, tex_coords, blend, draw_order);
+                }
+            }
+        }
+        if !scattered {
+            enter_layer(&mut batch, renderer, &mut current_layer, Layer::World);
+            self.render_scatters(&mut batch, renderer, world, world_blend);
         }
-        if self.rot.x < -PI / 2.0 {
-            self.rot.x = -PI / 2.0;
+        batch.draw(renderer);
+        if !world_finished {
+            self.render_decals(renderer, world, &mut decal_targets, world_blend);
+            renderer.apply_depth_of_field();
         }
 
-        self.recalc_vectors();
+        self.render_debug(renderer, world);
+        renderer.resolve(world.time);
     }
 
     ///
-    /// Renders each object in the world.
+    /// Draws the world's decals and blob shadows over the meshes they reach, given with their bounds and their vertices
+    /// as they were projected when the meshes were drawn, so the faces drawn again line up exactly with what's on
+    /// screen. Only faces which reach into a decal's box and face along its projection are drawn again. The targets
+    /// are emptied afterwards.
     ///
-    pub fn render_world(self, renderer: &mut Renderer, world: &World) {
-        for object in &world.objects {
-            for face in &object.faces {
-                let face_vertex_indices = face.vertices;
-                let face_vertices = vec![
-                    object.vertices[face_vertex_indices.0],
-                    object.vertices[face_vertex_indices.1],
-                    object.vertices[face_vertex_indices.2],
-                ];
-
-                let mut screen_vertices = vec![];
-
-                for mut point in face_vertices {
-                    // point = Mat4::identity()
-                    //     .rotate(Vec3::new(0.0, 1.0, 0.0), 0.05 * time)
-                    //     .transform(point);
-
-                    // Transform each vertex to world space
-                    point = Mat4::identity()
-                        .mult(object.transformation)
-                        .transform(point);
-
-                    // Transform the point to camera space
-                    point = self.look_at().transform(point);
-                    let z = point.z;
-                    point = point / point.z;
-                    point.z = z;
-
-                    screen_vertices.push(point);
+    fn render_decals(
+        self,
+        renderer: &mut Renderer,
+        world: &World,
+        targets: &mut Vec<(Aabb, &Object, Vec<Vec3>)>,
+        blend: Blend,
+    ) {
+        if targets.is_empty() {
+            return;
+        }
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("decals", decals = world.decals.len()).entered();
+        let view = self.look_at();
+        let camera_to_world = view.inverse().unwrap_or(Mat4::identity());
+        let mut triangles = vec![];
+        for decal in world.decals.iter().chain(world.blob_shadow_decals()) {
+            let Some(to_local) = decal.to_local() else {
+                continue;
+            };
+            let to_decal = to_local.mult(camera_to_world);
+            let projection = view.transform_direction(decal.volume.axes[2]).normalise();
+            let blend = Blend {
+                opacity: decal.opacity,
+                ..blend
+            };
+            triangles.clear();
+            let bounds = decal.bounds();
+            let reached = targets
+                .iter()
+                .filter(|(target, ..)| target.intersects(&bounds));
+            for (_, mesh, projected) in reached {
+                for face in mesh.faces.iter() {
+                    let (a, b, c) = face.vertices;
+                    let vertices = [projected[a], projected[b], projected[c]];
+                    // Projected points keep their depth, so they can be moved back into camera space
+                    let points = vertices.map(|vertex| {
+                        Vec3::new(vertex.x * vertex.z, vertex.y * vertex.z, vertex.z)
+                    });
+                    let local = points.map(|point| to_decal.transform(point));
+                    let outside = |axis: fn(Vec3) -> f64| {
+                        local.iter().all(|point| axis(*point) < -0.5)
+                            || local.iter().all(|point| axis(*point) > 0.5)
+                    };
+                    if outside(|point| point.x)
+                        || outside(|point| point.y)
+                        || outside(|point| point.z)
+                    {
+                        continue;
+                    }
+                    // Faces seen side-on by the decal would smear it, and ones facing away are on the far side of the
+                    // surface it's projected onto
+                    let normal = (points[1] - points[0]).cross_product(points[2] - points[0]);
+                    if normal.normalise().dot(projection) < DECAL_MIN_FACING {
+                        continue;
+                    }
+                    triangles.push(vertices);
                 }
+            }
+            renderer.draw_decal(&triangles, &decal.texture, to_decal, decal.mode, blend);
+        }
+        targets.clear();
+    }
+
+    ///
+    /// Finds the triangles of an outline around a mesh, as a copy of the mesh pushed out along its vertex normals by
+    /// `width` in world space, projected. Only the copy's faces turned away from the camera are kept, so the mesh hides
+    /// all of it except where it reaches past the mesh's silhouette.
+    ///
+    fn outline(
+        self,
+        mesh: &Object,
+        vertices: &[Vec3],
+        transformation: Mat4,
+        width: f64,
+    ) -> Vec<[Vec3; 3]> {
+        let view = self.look_at();
+        let normals = mesh.vertex_normals();
+        let pushed: Vec<Vec3> = vertices
+            .iter()
+            .zip(normals)
+            .map(|(&vertex, normal)| {
+                // Meshes without normals aren't pushed out, leaving no outline
+                let normal = transformation.transform_direction(normal);
+                let normal = match normal.length() > 0.0 {
+                    true => normal.normalise(),
+                    false => normal,
+                };
+                view.transform(transformation.transform(vertex) + normal * width)
+            })
+            .collect();
+        mesh.faces
+            .iter()
+            .map(|face| {
+                let (a, b, c) = face.vertices;
+                [pushed[a], pushed[b], pushed[c]]
+            })
+            // The camera is at the origin, so faces pointing the same way as the direction to them face away from it
+            .filter(|[a, b, c]| (*b - *a).cross_product(*c - *a).dot(*a) > 0.0)
+            .map(|triangle| triangle.map(project))
+            .collect()
+    }
+
+    ///
+    /// Adds the instances of the world's scatters in view to a batch. Billboards are scaled by their instance, and
+    /// meshes are placed with the instance's transformation.
+    ///
+    fn render_scatters<'a>(
+        self,
+        batch: &mut TriangleBatch<'a>,
+        renderer: &Renderer,
+        world: &'a World,
+        blend: Blend,
+    ) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("scatter", scatters = world.scatters.len()).entered();
+        let view = self.look_at();
+        let frustum = self.frustum(renderer.width(), renderer.height());
+        let viewer = view.inverse().unwrap_or(Mat4::identity()).transform(ORIGIN);
+        let mut projected = vec![];
+        for scatter in &world.scatters {
+            for instance in scatter.visible(&frustum, viewer) {
+                match &scatter.model {
+                    ScatterModel::Billboard(sprite) => {
+                        let scale = instance.scale();
+                        let placement = Mat4::identity()
+                            .scale(Vec3::new(scale, scale, scale))
+                            .translate(instance.position());
+                        self.render_sprite(batch, sprite, placement, world.time, blend, 0);
+                    }
+                    ScatterModel::Mesh(object) => {
+                        let transformation = view
+                            .mult(instance.transformation())
+                            .mult(object.transformation);
+                        projected.clear();
+                        projected.extend(
+                            object
+                                .vertices
+                                .iter()
+                                .map(|&vertex| project(transformation.transform(vertex))),
+                        );
+                        for face in object.faces.iter() {
+                            let (a, b, c) = face.vertices;
+                            let (ta, tb, tc) = face.tex_coords;
+                            batch.push(
+                                [projected[a], projected[b], projected[c]],
+                                object.face_texture(face),
+                                [
+                                    object.tex_coords[ta],
+                                    object.tex_coords[tb],
+                                    object.tex_coords[tc],
+                                ],
+                                blend,
+                                0,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    ///
+    /// Adds a sprite to a batch as a quad facing the camera. `placement` moves the sprite's centre to its world space
+    /// position, and scales it.
+    ///
+    fn render_sprite<'a>(
+        self,
+        batch: &mut TriangleBatch<'a>,
+        sprite: &'a Sprite,
+        placement: Mat4,
+        time: f64,
+        blend: Blend,
+        draw_order: i32,
+    ) {
+        let centre = self.look_at().transform(placement.transform(ORIGIN));
+        let x = sprite.size.x * placement.transform_direction(X_AXIS).length() / 2.0;
+        let y = sprite.size.y * placement.transform_direction(Y_AXIS).length() / 2.0;
+
+        // Positive X in camera space is towards the left of the screen
+        let corners = [
+            centre + Vec3::new(x, y, 0.0),
+            centre + Vec3::new(-x, y, 0.0),
+            centre + Vec3::new(-x, -y, 0.0),
+            centre + Vec3::new(x, -y, 0.0),
+        ]
+        .map(project);
+
+        let (min, max) = sprite.frame_coords(time);
+        let tex_coords = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+
+        for (a, b, c) in [(0, 1, 2), (0, 2, 3)] {
+            batch.push(
+                [corners[a], corners[b], corners[c]],
+                &sprite.texture,
+                [tex_coords[a], tex_coords[b], tex_coords[c]],
+                blend,
+                draw_order,
+            );
+        }
+    }
 
-                let tex_coord_indices = face.tex_coords;
-                let tex_coords = vec![
-                    object.tex_coords[tex_coord_indices.0],
-                    object.tex_coords[tex_coord_indices.1],
-                    object.tex_coords[tex_coord_indices.2],
-                ];
+    ///
+    /// Draws the world's debug lines over the scene. Lines are still hidden behind objects, but are drawn after every
+    /// layer so nothing is drawn over them.
+    ///
+    fn render_debug(self, renderer: &mut Renderer, world: &World) {
+        let view = self.look_at();
+        for line in world.debug_lines() {
+            let (mut a, mut b) = (view.transform(line.from), view.transform(line.to));
 
-                renderer.draw_triangle(screen_vertices, &object.texture, tex_coords);
+            // Cut off any part of the line behind the camera, which can't be projected
+            if a.z > -NEAR_Z && b.z > -NEAR_Z {
+                continue;
+            }
+            if a.z > -NEAR_Z {
+                a = b + (a - b) * ((-NEAR_Z - b.z) / (a.z - b.z));
+            } else if b.z > -NEAR_Z {
+                b = a + (b - a) * ((-NEAR_Z - a.z) / (b.z - a.z));
             }
+
+            renderer.draw_line_depth(project(a), project(b), line.colour);
         }
     }
 }
+
+///
+/// Moves into the next layer of entities, drawing what's been batched so far and clearing the depth buffer first if the
+/// new layer is drawn over the one before it.
+///
+fn enter_layer(
+    batch: &mut TriangleBatch,
+    renderer: &mut Renderer,
+    current: &mut Option<Layer>,
+    layer: Layer,
+) {
+    if current.is_some_and(|current| current != layer) && layer.clears_depth() {
+        batch.draw(renderer);
+        renderer.clear_depth();
+    }
+    *current = Some(layer);
+}
+
+///
+/// Divides a point in camera space by its depth, keeping the depth itself, which is what the renderer is given.
+///
+fn project(point: Vec3) -> Vec3 {
+    let mut projected = point / point.z;
+    projected.z = point.z;
+    projected
+}
+
+///
+/// Replaces what's in `out` with `f` applied to each item in order. With the `parallel` feature, long lists are split
+/// across threads.
+///
+fn map_into<T: Sync, U: Send>(items: &[T], out: &mut Vec<U>, f: impl Fn(&T) -> U + Send + Sync) {
+    #[cfg(feature = "parallel")]
+    if items.len() >= PARALLEL_MIN_ITEMS {
+        use rayon::prelude::*;
+        items.par_iter().map(f).collect_into_vec(out);
+        return;
+    }
+    out.clear();
+    out.extend(items.iter().map(f));
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn transforms_keep_their_order() {
+        // Long enough to be split across threads when they're in use, and reusing a buffer which is already full
+        let items: Vec<usize> = (0..10_000).collect();
+        let mut out = vec![0; 3];
+        map_into(&items, &mut out, |item| item * 2);
+        assert_eq!(out.len(), items.len());
+        assert!(out.iter().enumerate().all(|(i, item)| *item == i * 2));
+
+        map_into(&items[..2], &mut out, |item| item + 1);
+        assert_eq!(out, [1, 2]);
+    }
+
+    #[test]
+    fn screen_rays_pass_through_what_is_drawn() {
+        let mut camera = Camera::new(Vec3::new(1.0, -2.0, 3.0));
+        camera.rotate(Vec3::new(0.3, 1.2, 0.0));
+        camera.fov = 1.2;
+        let (width, height) = (600, 400);
+        let scale = width as f64 / (2.0 * (camera.fov / 2.0).tan());
+
+        // The middle of the screen looks straight ahead from the camera
+        let centre = camera.screen_ray(Vec2::new(300.0, 200.0), width, height);
+        assert!((centre.origin - Vec3::new(-1.0, 2.0, -3.0)).length() < 1e-9);
+        let ahead = camera.up.cross_product(camera.right);
+        assert!((centre.direction - ahead).length() < 1e-9);
+
+        // A point projected the same way as the renderer lies on the ray through its pixel
+        let point = Vec3::new(2.0, 1.0, 4.0);
+        let view = camera.look_at().transform(point);
+        let pixel = Vec2::new(
+            view.x / view.z * scale + width as f64 / 2.0,
+            view.y / view.z * scale + height as f64 / 2.0,
+        );
+        let ray = camera.screen_ray(pixel, width, height);
+        let along = ray.direction.dot(point - ray.origin);
+        assert!(along > 0.0);
+        assert!((ray.origin + ray.direction * along - point).length() < 1e-9);
+    }
+
+    #[test]
+    fn decals_land_on_the_faces_they_project_onto() {
+        use crate::{
+            decal::DecalMode, framebuffer::Colour, headless::Headless, object::Texture,
+            vec::vec3::Z_AXIS, world::Layer,
+        };
+
+        let solid = |hex: u32| Texture::solid(Colour::from_hex(hex));
+        let render = |decal: Option<Decal>, layer: Layer| {
+            let mut headless = Headless::new(60, 40);
+            let mut cube = Object::from_obj("./resources/cube").unwrap();
+            cube.texture = Arc::new(solid(0xffffff));
+            let id = headless.world.add_object(cube, Vec3::new(0.0, 0.0, 6.0));
+            headless.world.set_layer(id, layer);
+            headless.world.decals.extend(decal);
+            headless.render().to_vec()
+        };
+        let count =
+            |frame: &[u32], colour: u32| frame.iter().filter(|pixel| **pixel == colour).count();
+
+        // A poster on the side of the cube facing the camera covers the middle of it, leaving its edges alone
+        let front = Vec3::new(0.0, 0.0, 5.0);
+        let poster = Decal::on_surface(solid(0xff0000), front, -Z_AXIS, Vec2::new(1.0, 1.0), 0.1);
+        let frame = render(Some(poster.clone()), Layer::World);
+        let plain = render(None, Layer::World);
+        let white = count(&plain, 0xffffff);
+        assert!(count(&frame, 0xff0000) > 0);
+        assert_eq!(frame[20 * 60 + 30], 0xff0000);
+        assert_eq!(count(&frame, 0xff0000) + count(&frame, 0xffffff), white);
+
+        // Decals facing into the surface, or on entities outside the world layer, aren't drawn
+        let backwards = Decal::on_surface(solid(0xff0000), front, Z_AXIS, Vec2::new(1.0, 1.0), 0.1);
+        assert_eq!(render(Some(backwards), Layer::World), plain);
+        assert_eq!(
+            render(Some(poster), Layer::Foreground),
+            render(None, Layer::Foreground)
+        );
+
+        // Multiplying by a mid grey darkens the surface under it
+        let shadow = Decal::on_surface(solid(0x808080), front, -Z_AXIS, Vec2::new(1.0, 1.0), 0.1)
+            .with_mode(DecalMode::Multiply);
+        assert_eq!(render(Some(shadow), Layer::World)[20 * 60 + 30], 0x808080);
+    }
+
+    #[test]
+    fn depth_of_field_blurs_the_world_out_of_focus() {
+        use crate::{
+            config::RendererConfig, depth_of_field::DepthOfField, framebuffer::Colour,
+            headless::Headless, object::Texture, world::Layer,
+        };
+
+        let render = |focal_distance: Option<f64>, layer: Layer| {
+            let config = RendererConfig {
+                depth_of_field: focal_distance.map(|focal_distance| DepthOfField {
+                    focal_distance,
+                    focus_range: 0.5,
+                    aperture: 3.0,
+                }),
+                ..RendererConfig::new(60, 40)
+            };
+            let mut headless = Headless::from_config(config);
+            let mut cube = Object::from_obj("./resources/cube").unwrap();
+            cube.texture = Arc::new(Texture::solid(Colour::from_hex(0xffffff)));
+            let id = headless.world.add_object(cube, Vec3::new(0.0, 0.0, 6.0));
+            headless.world.set_layer(id, layer);
+            headless.render().to_vec()
+        };
+
+        // In focus, the cube is as sharp as without depth of field, and out of focus its edges are softened
+        let sharp = render(None, Layer::World);
+        assert_eq!(render(Some(5.0), Layer::World), sharp);
+        let blurred = render(Some(20.0), Layer::World);
+        assert_ne!(blurred, sharp);
+        assert_eq!(blurred[20 * 60 + 30], 0xffffff);
+
+        // Layers in front of the world are drawn after it's blurred, so they stay sharp
+        assert_eq!(render(Some(20.0), Layer::Foreground), sharp);
+    }
+
+    #[test]
+    fn toon_shading_and_outlines() {
+        use crate::{
+            framebuffer::Colour, headless::Headless, light::Light, object::Texture, toon::Toon,
+            vec::vec3::Z_AXIS,
+        };
+
+        let render = |toon: Option<Toon>, direction: Vec3| {
+            let mut headless = Headless::new(60, 40);
+            let mut cube = Object::from_obj("./resources/cube").unwrap();
+            cube.texture = Arc::new(Texture::solid(Colour::from_hex(0xffffff)));
+            let id = headless.world.add_object(cube, Vec3::new(0.0, 0.0, 6.0));
+            headless.world.lights.push(Light::Directional {
+                direction,
+                colour: Vec3::new(1.0, 1.0, 1.0),
+                intensity: 1.0,
+            });
+            if let Some(toon) = toon {
+                headless.world.set_toon(id, toon);
+            }
+            headless.render().to_vec()
+        };
+        let count =
+            |frame: &[u32], colour: u32| frame.iter().filter(|pixel| **pixel == colour).count();
+        let toon = Toon::default().with_outline(Colour::from_hex(0xff0000), 0.1);
+
+        // The face towards the light is fully lit, and the outline surrounds the cube without covering any of it
+        let plain = render(None, Z_AXIS);
+        let lit = render(Some(toon), Z_AXIS);
+        assert_eq!(lit[20 * 60 + 30], 0xffffff);
+        assert!(count(&lit, 0xff0000) > 0);
+        let background = plain[0];
+        assert!(lit
+            .iter()
+            .zip(&plain)
+            .all(|(lit, plain)| *lit != 0xff0000 || *plain == background));
+        assert_eq!(count(&plain, 0xff0000), 0);
+
+        // Facing away from the light drops the face to the darkest band, and unlit entities ignore the lights
+        let shadowed = render(
+            Some(toon.with_outline(Colour::from_hex(0xff0000), 0.0)),
+            -Z_AXIS,
+        );
+        assert_eq!(shadowed[20 * 60 + 30], 0x595959);
+        assert_eq!(count(&shadowed, 0xff0000), 0);
+        assert_eq!(render(None, -Z_AXIS), plain);
+    }
+
+    #[test]
+    fn blob_shadows_darken_the_ground_under_entities() {
+        use crate::{
+            framebuffer::Colour, headless::Headless, object::Texture, shadow::BlobShadow,
+            terrain::Terrain,
+        };
+
+        let render = |shadow: Option<BlobShadow>| {
+            let mut headless = Headless::new(60, 40);
+            let ground = Texture::solid(Colour::from_hex(0xffffff));
+            let mut terrain = Terrain::from_fn(2, 4, 4, 2.0, ground, |_, _| -3.0);
+            terrain.origin = Vec3::new(-8.0, 0.0, 0.0);
+            headless.world.set_terrain(terrain);
+            let mut cube = Object::from_obj("./resources/cube").unwrap();
+            cube.texture = Arc::new(Texture::solid(Colour::from_hex(0xff0000)));
+            let id = headless.world.add_object(cube, Vec3::new(0.0, -1.5, 14.0));
+            if let Some(shadow) = shadow {
+                headless.world.set_blob_shadow(id, shadow);
+            }
+            headless.render().to_vec()
+        };
+        let darker = |frame: &[u32], plain: &[u32]| {
+            let grey = |pixel: u32| (pixel & 0xff) as i32;
+            frame
+                .iter()
+                .zip(plain)
+                .filter(|(pixel, plain)| grey(**pixel) < grey(**plain))
+                .count()
+        };
+
+        // Only the ground is darkened - the entity casting the shadow is left alone
+        let plain = render(None);
+        let shadowed = render(Some(BlobShadow::default().with_scale(2.0)));
+        assert!(darker(&shadowed, &plain) > 0);
+        assert_eq!(
+            shadowed.iter().filter(|pixel| **pixel == 0xff0000).count(),
+            plain.iter().filter(|pixel| **pixel == 0xff0000).count()
+        );
+    }
+}
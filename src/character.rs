@@ -0,0 +1,391 @@
+use crate::{
+    collider::{Collider, Contact},
+    vec::vec3::{Vec3, Y_AXIS},
+    world::World,
+};
+
+// How many times a move is pushed back out of the geometry it runs into before giving up
+const MAX_PUSHES: usize = 8;
+
+// Overlaps shallower than this are left alone, so resting on the ground doesn't count as being blocked
+const TOLERANCE: f64 = 1e-6;
+
+///
+/// Walks a capsule through a world's solid geometry, for players and other characters which shouldn't be simulated as
+/// rigidbodies. Characters slide along walls, climb steps and gentle slopes, and fall under the world's gravity.
+/// - `position` is the bottom of the capsule, where the character's feet are
+/// - `velocity` is how fast the character is moving in units per second. Only its vertical part carries over between
+///   moves, as walking sets the rest
+/// - `radius` and `height` are the size of the capsule, with the height including both rounded ends
+/// - `step_height` is the tallest ledge the character steps straight up onto
+/// - `max_slope` is the steepest slope in radians the character can stand on. Anything steeper is treated as a wall
+///
+/// Characters push out of collision meshes, static or immovable rigidbodies and the terrain, but pass through bodies
+/// which are being simulated.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CharacterController {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub radius: f64,
+    pub height: f64,
+    pub step_height: f64,
+    pub max_slope: f64,
+    grounded: bool,
+}
+
+///
+/// What a capsule ran into while it was pushed out of the world.
+///
+#[derive(Debug, Default, Copy, Clone)]
+struct Hits {
+    ground: bool,
+    ceiling: bool,
+    blocked: bool,
+}
+
+impl CharacterController {
+    pub fn new(position: Vec3, radius: f64, height: f64) -> CharacterController {
+        CharacterController {
+            position,
+            velocity: Vec3::default(),
+            radius,
+            height: height.max(radius * 2.0),
+            step_height: 0.3,
+            max_slope: 45f64.to_radians(),
+            grounded: false,
+        }
+    }
+
+    pub fn with_step_height(mut self, step_height: f64) -> CharacterController {
+        self.step_height = step_height;
+        self
+    }
+
+    pub fn with_max_slope(mut self, max_slope: f64) -> CharacterController {
+        self.max_slope = max_slope;
+        self
+    }
+
+    ///
+    /// Returns whether the character was standing on something walkable at the end of its last move.
+    ///
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+
+    ///
+    /// Launches the character upwards, if it's standing on the ground.
+    ///
+    pub fn jump(&mut self, speed: f64) {
+        if self.grounded {
+            self.velocity.y = speed;
+            self.grounded = false;
+        }
+    }
+
+    ///
+    /// Returns where the character's eyes are, at the centre of the top of its capsule.
+    ///
+    pub fn eye(&self) -> Vec3 {
+        self.position + Y_AXIS * (self.height - self.radius)
+    }
+
+    ///
+    /// Moves the character by `dt` seconds, walking at `walk` units per second across the ground and falling under
+    /// the world's gravity. The vertical part of `walk` is ignored.
+    ///
+    /// The character is moved across first, then up or down. Whenever the capsule ends up inside something it's pushed
+    /// straight back out, so moving into a wall at an angle slides along it. If a ledge blocks the way, the move is
+    /// tried again from `step_height` higher up, and kept if the character lands further along on solid ground. While
+    /// grounded, characters also stick to slopes and stairs going down rather than running off them into the air.
+    ///
+    pub fn move_and_slide(&mut self, world: &World, walk: Vec3, dt: f64) {
+        let across = Vec3::new(walk.x, 0.0, walk.z) * dt;
+        let was_grounded = self.grounded;
+
+        // Across
+        let start = self.position;
+        let (mut position, hits) = self.slide(world, start, across, f64::NEG_INFINITY);
+        let mut stepped_up = false;
+        if hits.blocked && was_grounded && self.step_height > 0.0 {
+            let raised = start + Y_AXIS * self.step_height;
+            let (stepped, _) = self.slide(world, raised, across, f64::NEG_INFINITY);
+            let (landed, step_hits) =
+                self.slide(world, stepped, -Y_AXIS * self.step_height, raised.y);
+            let progress = |end: Vec3| {
+                let moved = end - start;
+                Vec3::new(moved.x, 0.0, moved.z).length()
+            };
+            if step_hits.ground && progress(landed) > progress(position) + TOLERANCE {
+                position = landed;
+                stepped_up = true;
+            }
+        }
+
+        // Having just stepped up, the character is stood on the edge of the step, so it doesn't fall this move
+        if stepped_up {
+            self.velocity = Vec3::new(walk.x, 0.0, walk.z);
+            self.grounded = true;
+            self.position = position;
+            return;
+        }
+
+        // Up or down
+        self.velocity = Vec3::new(walk.x, self.velocity.y, walk.z) + world.physics.gravity * dt;
+        let (mut fallen, hits) = self.slide(
+            world,
+            position,
+            Y_AXIS * (self.velocity.y * dt),
+            f64::NEG_INFINITY,
+        );
+        let mut grounded = hits.ground;
+        if hits.ceiling && self.velocity.y > 0.0 {
+            self.velocity.y = 0.0;
+        }
+
+        // Walking off the top of a slope or a stair, stay on the ground beneath if it's close enough
+        if was_grounded && !grounded && self.velocity.y <= 0.0 {
+            let (snapped, snap_hits) =
+                self.slide(world, fallen, -Y_AXIS * self.step_height, f64::NEG_INFINITY);
+            if snap_hits.ground {
+                fallen = snapped;
+                grounded = true;
+            }
+        }
+
+        if grounded {
+            self.velocity.y = self.velocity.y.max(0.0);
+        }
+        self.grounded = grounded;
+        self.position = fallen;
+    }
+
+    ///
+    /// Moves the capsule from `start` by `offset`, then pushes it out of the world one overlap at a time, deepest first.
+    /// Walkable ground pushes the capsule straight up so it doesn't slide down slopes, and walls push it straight
+    /// across so it doesn't climb them.
+    ///
+    /// Anything facing upwards which the capsule touches below `footing` holds it up like the ground does, however
+    /// steep it is. This lets characters stepping up land on the edge of a ledge, which their rounded bottom would
+    /// otherwise slide off.
+    ///
+    fn slide(&self, world: &World, start: Vec3, offset: Vec3, footing: f64) -> (Vec3, Hits) {
+        let mut position = start + offset;
+        let mut hits = Hits::default();
+        let walkable = self.max_slope.cos();
+
+        for _ in 0..MAX_PUSHES {
+            let Some(contact) = self.deepest_contact(world, position) else {
+                break;
+            };
+            // Contact normals point into what the capsule hit, so the way out is the other way
+            let out = -contact.normal;
+            let push = if out.y >= walkable || (out.y > TOLERANCE && contact.point.y <= footing) {
+                hits.ground = true;
+                Y_AXIS * (contact.depth / out.y).min(self.step_height.max(contact.depth))
+            } else if out.y < -walkable {
+                hits.ceiling = true;
+                out * contact.depth
+            } else {
+                hits.blocked = true;
+                let flat = Vec3::new(out.x, 0.0, out.z);
+                if flat.length() > TOLERANCE {
+                    flat.normalise() * (contact.depth / flat.length())
+                } else {
+                    out * contact.depth
+                }
+            };
+            position = position + push;
+        }
+
+        (position, hits)
+    }
+
+    ///
+    /// Returns the deepest overlap between the capsule at `position` and the world.
+    ///
+    fn deepest_contact(&self, world: &World, position: Vec3) -> Option<Contact> {
+        self.spheres(position)
+            .into_iter()
+            .flat_map(|collider| world.solid_contacts(&collider))
+            .filter(|contact| contact.depth > TOLERANCE)
+            .max_by(|a, b| a.depth.total_cmp(&b.depth))
+    }
+
+    ///
+    /// Approximates the capsule with spheres along its middle, close enough together that there are no gaps between
+    /// them.
+    ///
+    fn spheres(&self, position: Vec3) -> Vec<Collider> {
+        let length = self.height - self.radius * 2.0;
+        let count = (length / self.radius).ceil().max(0.0) as usize + 1;
+        (0..count)
+            .map(|i| {
+                let along = if count > 1 {
+                    length * i as f64 / (count - 1) as f64
+                } else {
+                    0.0
+                };
+                Collider::Sphere {
+                    centre: position + Y_AXIS * (self.radius + along),
+                    radius: self.radius,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::{
+        geometry::aabb::Aabb,
+        object::{Object, Texture},
+        rigidbody::Rigidbody,
+        terrain::Terrain,
+        vec::vec3::{ORIGIN, X_AXIS},
+    };
+
+    const DT: f64 = 1.0 / 60.0;
+
+    fn point() -> Object {
+        Object::new(
+            vec![ORIGIN],
+            vec![],
+            vec![],
+            vec![],
+            Texture::new(1, 1, vec![0]),
+        )
+    }
+
+    ///
+    /// Adds an immovable box to the world between two corners.
+    ///
+    fn add_box(world: &mut World, min: Vec3, max: Vec3) {
+        let id = world.add_object(point(), ORIGIN);
+        let body = Rigidbody::new(point())
+            .with_mass(0.0)
+            .with_collider(Collider::Box(Aabb::new(min, max)));
+        world.insert_component(id, body);
+    }
+
+    fn floor() -> World {
+        let mut world = World::new();
+        add_box(
+            &mut world,
+            Vec3::new(-50.0, -1.0, -50.0),
+            Vec3::new(50.0, 0.0, 50.0),
+        );
+        world
+    }
+
+    #[test]
+    fn falls_onto_the_ground() {
+        let world = floor();
+        let mut character = CharacterController::new(Vec3::new(0.0, 3.0, 0.0), 0.5, 1.8);
+        assert!(!character.is_grounded());
+
+        for _ in 0..120 {
+            character.move_and_slide(&world, ORIGIN, DT);
+        }
+        assert!(character.is_grounded());
+        assert!(
+            character.position.y.abs() < 0.01,
+            "{}",
+            character.position.y
+        );
+        assert_eq!(character.velocity.y, 0.0);
+        assert!((character.eye().y - 1.3).abs() < 0.01);
+
+        // Jumping leaves the ground, and gravity brings the character back down
+        character.jump(5.0);
+        character.move_and_slide(&world, ORIGIN, DT);
+        assert!(!character.is_grounded());
+        assert!(character.position.y > 0.0);
+        for _ in 0..120 {
+            character.move_and_slide(&world, ORIGIN, DT);
+        }
+        assert!(character.is_grounded());
+    }
+
+    #[test]
+    fn slides_along_walls() {
+        let mut world = floor();
+        add_box(
+            &mut world,
+            Vec3::new(2.0, 0.0, -50.0),
+            Vec3::new(3.0, 5.0, 50.0),
+        );
+        let mut character = CharacterController::new(ORIGIN, 0.5, 1.8);
+
+        // Walking diagonally into the wall keeps the part of the move along it
+        let walk = Vec3::new(1.0, 0.0, -1.0) * 4.0;
+        for _ in 0..120 {
+            character.move_and_slide(&world, walk, DT);
+        }
+        assert!(
+            character.position.x <= 1.5 + 0.01,
+            "{}",
+            character.position.x
+        );
+        assert!(character.position.x > 1.4);
+        assert!(character.position.z < -7.0, "{}", character.position.z);
+        assert!(character.is_grounded());
+    }
+
+    #[test]
+    fn climbs_steps_but_not_walls() {
+        let climb = |step: f64| {
+            let mut world = floor();
+            add_box(
+                &mut world,
+                Vec3::new(2.0, 0.0, -50.0),
+                Vec3::new(10.0, step, 50.0),
+            );
+            let mut character = CharacterController::new(ORIGIN, 0.5, 1.8).with_step_height(0.4);
+            for _ in 0..120 {
+                character.move_and_slide(&world, X_AXIS * 3.0, DT);
+            }
+            assert!(character.is_grounded());
+            character.position
+        };
+
+        let stepped = climb(0.3);
+        assert!(stepped.x > 4.0, "{}", stepped.x);
+        assert!((stepped.y - 0.3).abs() < 0.01, "{}", stepped.y);
+
+        let blocked = climb(1.0);
+        assert!(blocked.x < 1.51, "{}", blocked.x);
+        assert!(blocked.y.abs() < 0.01, "{}", blocked.y);
+    }
+
+    #[test]
+    fn walks_on_terrain() {
+        let mut world = World::new();
+        let texture = Texture::new(1, 1, vec![0]);
+        // A gentle hill rising along X
+        let mut terrain = Terrain::from_fn(2, 2, 8, 1.0, texture, |x, _| x * 0.25);
+        terrain.origin = Vec3::new(-8.0, 0.0, -8.0);
+        world.set_terrain(terrain);
+
+        let mut character = CharacterController::new(Vec3::new(-6.0, 5.0, 0.0), 0.5, 1.8);
+        for _ in 0..240 {
+            character.move_and_slide(&world, X_AXIS * 3.0, DT);
+        }
+        assert!(character.is_grounded());
+        let ground = world
+            .terrain_height(character.position.x, character.position.z)
+            .unwrap();
+        assert!(character.position.x > 4.0, "{}", character.position.x);
+        assert!((character.position.y - ground).abs() < 0.05);
+
+        // Standing still on the slope doesn't slide back down it
+        let resting = character.position;
+        for _ in 0..60 {
+            character.move_and_slide(&world, ORIGIN, DT);
+        }
+        assert!((character.position - resting).length() < 0.01);
+    }
+}
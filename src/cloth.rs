@@ -0,0 +1,401 @@
+use std::sync::Arc;
+
+use crate::{
+    collider::Collider,
+    framebuffer::Colour,
+    object::{Face, Object, Texture},
+    vec::{vec2::Vec2, vec3::Vec3},
+};
+
+///
+/// A spring between two particles, which pulls them back towards its rest length.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Spring {
+    a: usize,
+    b: usize,
+    length: f64,
+}
+
+///
+/// A sheet of cloth, simulated as a grid of particles held together by springs. Each particle is tied to its
+/// neighbours to stop the cloth stretching, to its diagonal neighbours to stop it shearing, and to the particles two
+/// along to stop it folding too sharply.
+/// - `stiffness` is how much of each spring's stretch is corrected at a time, from 0 for no springs to 1 for rigid ones
+/// - `iterations` is how many times the springs are corrected each step. More iterations make the cloth stiffer
+/// - `damping` is how quickly the cloth slows down, like air resistance. Each second, its particles' speed falls
+///   by a factor of e raised to the damping
+/// - `thickness` is how far the cloth is kept from the colliders it touches
+/// - `friction` is how much of a particle's speed across a collider it loses each step it touches it, from 0 for
+///   sliding freely to 1 for sticking in place
+/// - `colour` is the colour the cloth is drawn with
+///
+/// Particles are kept in world space, so a cloth added to a world isn't moved by its entity's transformation. Pinned
+/// particles stay where they're put, for hanging cloth up.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cloth {
+    pub stiffness: f64,
+    pub iterations: usize,
+    pub damping: f64,
+    pub thickness: f64,
+    pub friction: f64,
+    pub colour: Colour,
+    columns: usize,
+    rows: usize,
+    positions: Vec<Vec3>,
+    previous: Vec<Vec3>,
+    pinned: Vec<bool>,
+    springs: Vec<Spring>,
+}
+
+impl Cloth {
+    ///
+    /// Creates a flat sheet of cloth with `columns` by `rows` quads, with one corner at `corner` and sides along
+    /// `across` and `down`. The particles along the top edge are indexed from `corner` along `across`.
+    ///
+    pub fn new(corner: Vec3, across: Vec3, down: Vec3, columns: usize, rows: usize) -> Cloth {
+        let (columns, rows) = (columns.max(1), rows.max(1));
+        let mut positions = vec![];
+        for row in 0..=rows {
+            for column in 0..=columns {
+                let (u, v) = (column as f64 / columns as f64, row as f64 / rows as f64);
+                positions.push(corner + across * u + down * v);
+            }
+        }
+
+        let mut cloth = Cloth {
+            stiffness: 1.0,
+            iterations: 8,
+            damping: 0.2,
+            thickness: 0.05,
+            friction: 0.5,
+            colour: Colour::from_hex(0xb03a2e),
+            columns,
+            rows,
+            previous: positions.clone(),
+            pinned: vec![false; positions.len()],
+            positions,
+            springs: vec![],
+        };
+
+        // Structural, shear and bending springs, each only added once from the particle above or to the left
+        let ties = [(1, 0), (0, 1), (1, 1), (1, -1), (2, 0), (0, 2)];
+        for row in 0..=rows as isize {
+            for column in 0..=columns as isize {
+                for (dc, dr) in ties {
+                    if let (Some(a), Some(b)) =
+                        (cloth.index(column, row), cloth.index(column + dc, row + dr))
+                    {
+                        let length = (cloth.positions[b] - cloth.positions[a]).length();
+                        cloth.springs.push(Spring { a, b, length });
+                    }
+                }
+            }
+        }
+        cloth
+    }
+
+    pub fn with_stiffness(mut self, stiffness: f64) -> Cloth {
+        self.stiffness = stiffness.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_damping(mut self, damping: f64) -> Cloth {
+        self.damping = damping;
+        self
+    }
+
+    ///
+    /// Returns the number of quads along the cloth's top edge, and down its side.
+    ///
+    pub fn size(&self) -> (usize, usize) {
+        (self.columns, self.rows)
+    }
+
+    fn index(&self, column: isize, row: isize) -> Option<usize> {
+        let inside = (0..=self.columns as isize).contains(&column)
+            && (0..=self.rows as isize).contains(&row);
+        inside.then(|| row as usize * (self.columns + 1) + column as usize)
+    }
+
+    ///
+    /// Returns the world space positions of the cloth's particles, a row at a time from the top.
+    ///
+    pub fn positions(&self) -> &[Vec3] {
+        &self.positions
+    }
+
+    pub fn position(&self, column: usize, row: usize) -> Option<Vec3> {
+        let index = self.index(column as isize, row as isize)?;
+        Some(self.positions[index])
+    }
+
+    ///
+    /// Moves a particle without giving it any speed, for dragging pinned particles around.
+    ///
+    pub fn set_position(&mut self, column: usize, row: usize, position: Vec3) {
+        if let Some(index) = self.index(column as isize, row as isize) {
+            self.positions[index] = position;
+            self.previous[index] = position;
+        }
+    }
+
+    ///
+    /// Fixes a particle in place, so it's no longer moved by gravity, springs or collisions.
+    ///
+    pub fn pin(&mut self, column: usize, row: usize) {
+        if let Some(index) = self.index(column as isize, row as isize) {
+            self.pinned[index] = true;
+        }
+    }
+
+    pub fn unpin(&mut self, column: usize, row: usize) {
+        if let Some(index) = self.index(column as isize, row as isize) {
+            self.pinned[index] = false;
+        }
+    }
+
+    pub fn is_pinned(&self, column: usize, row: usize) -> bool {
+        self.index(column as isize, row as isize)
+            .is_some_and(|index| self.pinned[index])
+    }
+
+    ///
+    /// Advances the cloth by `dt`. Particles keep moving at the speed they moved last step, pulled by gravity and
+    /// slowed by damping, then the springs pull them back into shape, and finally they're pushed out of the colliders.
+    ///
+    pub fn step(&mut self, gravity: Vec3, colliders: &[Collider], dt: f64) {
+        let damping = (-self.damping * dt).exp();
+        for i in 0..self.positions.len() {
+            if self.pinned[i] {
+                self.previous[i] = self.positions[i];
+                continue;
+            }
+            let velocity = (self.positions[i] - self.previous[i]) * damping;
+            self.previous[i] = self.positions[i];
+            self.positions[i] = self.positions[i] + velocity + gravity * (dt * dt);
+        }
+
+        // Each spring moves particles its neighbours have already corrected, so going through them in the same order
+        // every time would drag the cloth towards one corner. Alternating the order evens this out.
+        for iteration in 0..self.iterations.max(1) {
+            for i in 0..self.springs.len() {
+                let i = if iteration % 2 == 0 {
+                    i
+                } else {
+                    self.springs.len() - 1 - i
+                };
+                self.relax(self.springs[i]);
+            }
+        }
+
+        for i in 0..self.positions.len() {
+            if !self.pinned[i] {
+                self.collide(i, colliders);
+            }
+        }
+    }
+
+    ///
+    /// Moves the ends of a spring towards its rest length, sharing the correction between them unless one is pinned.
+    ///
+    fn relax(&mut self, spring: Spring) {
+        let (a, b) = (self.positions[spring.a], self.positions[spring.b]);
+        let between = b - a;
+        let length = between.length();
+        if length <= f64::EPSILON {
+            return;
+        }
+        let correction = between * ((length - spring.length) / length * self.stiffness);
+        let (share_a, share_b) = match (self.pinned[spring.a], self.pinned[spring.b]) {
+            (false, false) => (0.5, 0.5),
+            (false, true) => (1.0, 0.0),
+            (true, false) => (0.0, 1.0),
+            (true, true) => return,
+        };
+        self.positions[spring.a] = a + correction * share_a;
+        self.positions[spring.b] = b - correction * share_b;
+    }
+
+    ///
+    /// Pushes a particle out of any colliders it's within `thickness` of, and slows it sliding across them.
+    ///
+    fn collide(&mut self, i: usize, colliders: &[Collider]) {
+        for collider in colliders {
+            let particle = Collider::Sphere {
+                centre: self.positions[i],
+                radius: self.thickness,
+            };
+            let Some(contact) = particle.collide(collider) else {
+                continue;
+            };
+            self.positions[i] = self.positions[i] - contact.normal * contact.depth;
+
+            // The particle's speed is how far it moved since the last step, so moving where it was towards where it is
+            // takes away some of its speed across the surface
+            let velocity = self.positions[i] - self.previous[i];
+            let sliding = velocity - contact.normal * velocity.dot(contact.normal);
+            self.previous[i] = self.previous[i] + sliding * self.friction.clamp(0.0, 1.0);
+        }
+    }
+
+    ///
+    /// Generates a mesh of the cloth as it is now, with two triangles for each quad.
+    ///
+    pub fn mesh(&self) -> Object {
+        let mut tex_coords = vec![];
+        for row in 0..=self.rows {
+            for column in 0..=self.columns {
+                tex_coords.push(Vec2::new(
+                    column as f64 / self.columns as f64,
+                    row as f64 / self.rows as f64,
+                ));
+            }
+        }
+
+        let mut faces = vec![];
+        let index = |column: usize, row: usize| row * (self.columns + 1) + column;
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let (a, b, c, d) = (
+                    index(column, row),
+                    index(column, row + 1),
+                    index(column + 1, row + 1),
+                    index(column + 1, row),
+                );
+                for vertices in [(a, b, c), (a, c, d)] {
+                    faces.push(Face {
+                        vertices,
+                        tex_coords: vertices,
+                        normals: vertices,
+                        material: 0,
+                    });
+                }
+            }
+        }
+
+        let texture = Texture::solid(self.colour);
+        let normals = vec![Vec3::default(); self.positions.len()];
+        let mut obj = Object::new(self.positions.clone(), tex_coords, normals, faces, texture);
+        self.update_mesh(&mut obj);
+        obj
+    }
+
+    ///
+    /// Moves the vertices of a mesh made by `mesh` to where the particles are now, and updates its normals from the
+    /// faces around each vertex.
+    ///
+    pub fn update_mesh(&self, obj: &mut Object) {
+        Arc::make_mut(&mut obj.vertices).clone_from(&self.positions);
+        let mut normals = vec![Vec3::default(); obj.vertices.len()];
+        for face in obj.faces.iter() {
+            let (a, b, c) = face.vertices;
+            let (pa, pb, pc) = (obj.vertices[a], obj.vertices[b], obj.vertices[c]);
+            let normal = (pb - pa).cross_product(pc - pa);
+            for vertex in [a, b, c] {
+                normals[vertex] = normals[vertex] + normal;
+            }
+        }
+        let normals = normals.into_iter().map(|normal| {
+            if normal.length() > f64::EPSILON {
+                normal.normalise()
+            } else {
+                normal
+            }
+        });
+        obj.normals = Arc::new(normals.collect());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::{
+        geometry::frustum::Plane,
+        vec::vec3::{ORIGIN, X_AXIS, Y_AXIS, Z_AXIS},
+    };
+
+    const GRAVITY: Vec3 = Vec3 {
+        x: 0.0,
+        y: -9.81,
+        z: 0.0,
+    };
+
+    fn flag() -> Cloth {
+        // A 2x2 sheet hanging down from its top edge
+        let mut cloth = Cloth::new(ORIGIN, X_AXIS * 2.0, -Y_AXIS * 2.0, 8, 8);
+        cloth.pin(0, 0);
+        cloth.pin(8, 0);
+        cloth
+    }
+
+    #[test]
+    fn build_grid() {
+        let cloth = flag();
+        assert_eq!(cloth.size(), (8, 8));
+        assert_eq!(cloth.positions().len(), 81);
+        assert_eq!(cloth.position(8, 8), Some(Vec3::new(2.0, -2.0, 0.0)));
+        assert_eq!(cloth.position(9, 0), None);
+        assert!(cloth.is_pinned(8, 0));
+        assert!(!cloth.is_pinned(4, 0));
+
+        let mesh = cloth.mesh();
+        assert_eq!(mesh.faces.len(), 128);
+        assert!(mesh.validate().is_ok());
+        // The sheet faces towards +Z
+        assert!((mesh.normals[40] - Z_AXIS).length() < 1e-9);
+    }
+
+    #[test]
+    fn hangs_from_pins() {
+        // Held by its top corners, and swinging out to the side
+        let mut cloth = Cloth::new(ORIGIN, X_AXIS * 2.0, Z_AXIS * 2.0, 8, 8);
+        cloth.pin(0, 0);
+        cloth.pin(8, 0);
+        for _ in 0..600 {
+            cloth.step(GRAVITY, &[], 1.0 / 60.0);
+        }
+
+        assert_eq!(cloth.position(0, 0), Some(ORIGIN));
+        assert_eq!(cloth.position(8, 0), Some(Vec3::new(2.0, 0.0, 0.0)));
+
+        // It swings down below its pins, without stretching much
+        let bottom = cloth.position(4, 8).unwrap();
+        assert!(bottom.y < -1.5, "{:?}", bottom);
+        let side = (cloth.position(0, 8).unwrap() - ORIGIN).length();
+        assert!(side < 2.2, "{}", side);
+    }
+
+    #[test]
+    fn drapes_over_colliders() {
+        // Dropped flat onto a ball sitting on the ground
+        let mut cloth = Cloth::new(
+            Vec3::new(-1.2, 1.5, -1.2),
+            X_AXIS * 2.4,
+            Z_AXIS * 2.4,
+            10,
+            10,
+        );
+        let colliders = [
+            Collider::Sphere {
+                centre: ORIGIN,
+                radius: 1.0,
+            },
+            Collider::Plane(Plane::new(Y_AXIS, 1.0)),
+        ];
+        for _ in 0..300 {
+            cloth.step(GRAVITY, &colliders, 1.0 / 60.0);
+        }
+
+        // The middle rests on top of the ball, and the rest falls around it without passing through anything
+        let top = cloth.position(5, 5).unwrap();
+        assert!((top.y - 1.0 - cloth.thickness).abs() < 0.05, "{:?}", top);
+        for position in cloth.positions() {
+            assert!(position.length() >= 1.0, "{:?}", position);
+            assert!(position.y >= -1.0, "{:?}", position);
+        }
+        assert!(cloth.position(0, 0).unwrap().y < 0.5);
+    }
+}
@@ -0,0 +1,1026 @@
+use std::f64::consts::PI;
+
+use crate::{
+    geometry::{
+        aabb::Aabb,
+        frustum::Plane,
+        hull::{closest_point_on_triangle, ConvexHull},
+        obb::Obb,
+        ray::Ray,
+    },
+    mat4::Mat4,
+    vec::vec3::{Vec3, Y_AXIS},
+};
+
+///
+/// The shape a rigidbody collides with, in the local space of its entity.
+/// - `Sphere` is a ball of `radius` around `centre`. Radii are in world units, and aren't affected by scaling
+/// - `Box` is an axis-aligned box. Boxes stay lined up with the world axes, so a rotated entity's box grows to
+///   contain its rotated corners
+/// - `Obb` is a box which turns with its entity, for rotated crates and walls
+/// - `Plane` is an infinite plane, which things collide with from the side its normal faces. Planes are best used on
+///   bodies which can't be moved, like the ground
+/// - `Hull` is a convex hull, which fits odd shapes like rocks and barrels far more closely than a box, while being
+///   much cheaper to collide with than every triangle of their mesh
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Collider {
+    Sphere { centre: Vec3, radius: f64 },
+    Box(Aabb),
+    Obb(Obb),
+    Plane(Plane),
+    Hull(ConvexHull),
+}
+
+///
+/// Where two colliders overlap. `normal` points from the first collider towards the second, and `depth` is how far
+/// they'd need to move apart along it to stop touching.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Contact {
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub depth: f64,
+}
+
+impl Contact {
+    ///
+    /// Swaps which collider the contact is from, so the normal points the other way.
+    ///
+    fn flip(self) -> Contact {
+        Contact {
+            normal: -self.normal,
+            ..self
+        }
+    }
+}
+
+impl Collider {
+    ///
+    /// Moves the collider from its entity's local space into world space.
+    ///
+    pub fn transformed(&self, transformation: Mat4) -> Collider {
+        match self {
+            Collider::Sphere { centre, radius } => Collider::Sphere {
+                centre: transformation.transform(*centre),
+                radius: *radius,
+            },
+            Collider::Box(aabb) => Collider::Box(aabb.transform(transformation)),
+            Collider::Obb(obb) => Collider::Obb(obb.transform(transformation)),
+            Collider::Plane(plane) => {
+                let Some(inverse) = transformation.inverse() else {
+                    return self.clone();
+                };
+                let plane = plane.untransform(inverse);
+                let length = plane.normal.length();
+                Collider::Plane(Plane::new(plane.normal / length, plane.d / length))
+            }
+            Collider::Hull(hull) => Collider::Hull(hull.transform(transformation)),
+        }
+    }
+
+    ///
+    /// Returns the box containing the collider, or nothing for planes, which go on forever.
+    ///
+    pub fn bounds(&self) -> Option<Aabb> {
+        match self {
+            Collider::Sphere { centre, radius } => {
+                let extent = Vec3::new(*radius, *radius, *radius);
+                Some(Aabb::new(*centre - extent, *centre + extent))
+            }
+            Collider::Box(aabb) => Some(*aabb),
+            Collider::Obb(obb) => Some(obb.bounds()),
+            Collider::Plane(_) => None,
+            Collider::Hull(hull) => Some(hull.bounds()),
+        }
+    }
+
+    ///
+    /// Returns whether a point is inside the collider, or behind it for planes.
+    ///
+    pub fn contains(&self, point: Vec3) -> bool {
+        match self {
+            Collider::Sphere { centre, radius } => (point - *centre).length() <= *radius,
+            Collider::Box(aabb) => aabb.contains(point),
+            Collider::Obb(obb) => obb.contains(point),
+            Collider::Plane(plane) => plane.distance(point) <= 0.0,
+            Collider::Hull(hull) => hull.contains(point),
+        }
+    }
+
+    ///
+    /// Returns the space the collider takes up, or nothing for planes, which go on forever.
+    ///
+    pub fn volume(&self) -> Option<f64> {
+        match self {
+            Collider::Sphere { radius, .. } => Some(4.0 / 3.0 * PI * radius.powi(3)),
+            Collider::Box(aabb) => {
+                let size = aabb.size();
+                Some(size.x * size.y * size.z)
+            }
+            Collider::Obb(obb) => {
+                let size = obb.half_extents * 2.0;
+                Some(size.x * size.y * size.z)
+            }
+            Collider::Plane(_) => None,
+            Collider::Hull(hull) => Some(hull.mass_properties()?.volume),
+        }
+    }
+
+    ///
+    /// Finds where the collider overlaps a triangle in the same space, if it does. The contact's normal points from
+    /// the collider towards the triangle. Planes never collide with triangles.
+    ///
+    pub fn collide_triangle(&self, a: Vec3, b: Vec3, c: Vec3) -> Option<Contact> {
+        match *self {
+            Collider::Sphere { centre, radius } => {
+                let closest = closest_point_on_triangle(centre, a, b, c);
+                let between = closest - centre;
+                let distance = between.length();
+                if distance >= radius {
+                    return None;
+                }
+                // A centre lying exactly on the triangle is pushed out the front
+                let normal = if distance > f64::EPSILON {
+                    between / distance
+                } else {
+                    -(b - a).cross_product(c - a).normalise()
+                };
+                Some(Contact {
+                    point: closest,
+                    normal,
+                    depth: radius - distance,
+                })
+            }
+            Collider::Box(aabb) => obb_triangle(Obb::from_aabb(aabb), [a, b, c]),
+            Collider::Obb(obb) => obb_triangle(obb, [a, b, c]),
+            Collider::Plane(_) => None,
+            Collider::Hull(ref hull) => hull_triangle(hull, [a, b, c]),
+        }
+    }
+
+    ///
+    /// Finds where two colliders in the same space overlap, if they do.
+    ///
+    pub fn collide(&self, other: &Collider) -> Option<Contact> {
+        match (self, other) {
+            (
+                &Collider::Sphere { centre, radius },
+                &Collider::Sphere {
+                    centre: other,
+                    radius: other_radius,
+                },
+            ) => sphere_sphere(centre, radius, other, other_radius),
+            (&Collider::Sphere { centre, radius }, &Collider::Plane(plane)) => {
+                sphere_plane(centre, radius, plane)
+            }
+            (&Collider::Plane(plane), &Collider::Sphere { centre, radius }) => {
+                sphere_plane(centre, radius, plane).map(Contact::flip)
+            }
+            (&Collider::Sphere { centre, radius }, &Collider::Box(aabb)) => {
+                sphere_box(centre, radius, aabb)
+            }
+            (&Collider::Box(aabb), &Collider::Sphere { centre, radius }) => {
+                sphere_box(centre, radius, aabb).map(Contact::flip)
+            }
+            (&Collider::Box(a), &Collider::Box(b)) => box_box(a, b),
+            (&Collider::Box(aabb), &Collider::Plane(plane)) => box_plane(aabb, plane),
+            (&Collider::Plane(plane), &Collider::Box(aabb)) => {
+                box_plane(aabb, plane).map(Contact::flip)
+            }
+            (&Collider::Sphere { centre, radius }, &Collider::Obb(obb)) => {
+                sphere_obb(centre, radius, obb)
+            }
+            (&Collider::Obb(obb), &Collider::Sphere { centre, radius }) => {
+                sphere_obb(centre, radius, obb).map(Contact::flip)
+            }
+            (&Collider::Obb(a), &Collider::Obb(b)) => obb_obb(a, b),
+            (&Collider::Obb(obb), &Collider::Box(aabb)) => obb_obb(obb, Obb::from_aabb(aabb)),
+            (&Collider::Box(aabb), &Collider::Obb(obb)) => obb_obb(Obb::from_aabb(aabb), obb),
+            (&Collider::Obb(obb), &Collider::Plane(plane)) => obb_plane(obb, plane),
+            (&Collider::Plane(plane), &Collider::Obb(obb)) => {
+                obb_plane(obb, plane).map(Contact::flip)
+            }
+            (Collider::Plane(_), Collider::Plane(_)) => None,
+            (&Collider::Sphere { centre, radius }, Collider::Hull(hull)) => {
+                sphere_hull(centre, radius, hull)
+            }
+            (Collider::Hull(hull), &Collider::Sphere { centre, radius }) => {
+                sphere_hull(centre, radius, hull).map(Contact::flip)
+            }
+            (Collider::Hull(hull), &Collider::Plane(plane)) => {
+                corners_plane(hull.points().iter().copied(), plane)
+            }
+            (&Collider::Plane(plane), Collider::Hull(hull)) => {
+                corners_plane(hull.points().iter().copied(), plane).map(Contact::flip)
+            }
+            (Collider::Hull(a), Collider::Hull(b)) => hull_hull(a, b),
+            (Collider::Hull(hull), &Collider::Box(aabb)) => {
+                hull_hull(hull, &obb_hull(Obb::from_aabb(aabb)))
+            }
+            (&Collider::Box(aabb), Collider::Hull(hull)) => {
+                hull_hull(&obb_hull(Obb::from_aabb(aabb)), hull)
+            }
+            (Collider::Hull(hull), &Collider::Obb(obb)) => hull_hull(hull, &obb_hull(obb)),
+            (&Collider::Obb(obb), Collider::Hull(hull)) => hull_hull(&obb_hull(obb), hull),
+        }
+    }
+
+    ///
+    /// Returns the distance along a ray at which it hits the collider, and the collider's surface normal there. Rays
+    /// starting inside the collider hit it straight away, with a normal pointing back along the ray.
+    ///
+    pub fn raycast(&self, ray: &Ray) -> Option<(f64, Vec3)> {
+        self.sphere_cast(ray, 0.0)
+    }
+
+    ///
+    /// Returns how far a sphere can travel along a ray before it hits the collider, and the collider's surface
+    /// normal where it touches. The touching point is `radius` back along the normal from the sphere's centre.
+    ///
+    pub fn sphere_cast(&self, ray: &Ray, radius: f64) -> Option<(f64, Vec3)> {
+        let hit = match *self {
+            Collider::Sphere {
+                centre,
+                radius: own_radius,
+            } => ray_sphere(ray, centre, own_radius + radius),
+            Collider::Plane(plane) => ray_plane(ray, Plane::new(plane.normal, plane.d - radius)),
+            Collider::Box(aabb) => sphere_cast_obb(ray, radius, Obb::from_aabb(aabb)),
+            Collider::Obb(obb) => sphere_cast_obb(ray, radius, obb),
+            Collider::Hull(ref hull) => sphere_cast_convex(
+                ray,
+                radius,
+                hull.ray_distance_within(ray, radius),
+                |point| hull.contains_within(point, radius),
+                |point| hull.closest_point(point),
+                |point| hull.nearest_face(point).0,
+            ),
+        };
+        hit.map(|(distance, normal)| match normal {
+            Some(normal) => (distance, normal),
+            None => (distance, -ray.direction),
+        })
+    }
+}
+
+// Ray queries return the distance along the ray, and the surface normal if the ray started outside the shape.
+
+fn ray_sphere(ray: &Ray, centre: Vec3, radius: f64) -> Option<(f64, Option<Vec3>)> {
+    let to_centre = centre - ray.origin;
+    if to_centre.length() <= radius {
+        return Some((0.0, None));
+    }
+    let along = to_centre.dot(ray.direction);
+    let squared_miss = to_centre.dot(to_centre) - along * along;
+    if along < 0.0 || squared_miss > radius * radius {
+        return None;
+    }
+    let distance = along - (radius * radius - squared_miss).sqrt();
+    let normal = (ray.at(distance) - centre).normalise();
+    Some((distance, Some(normal)))
+}
+
+fn ray_plane(ray: &Ray, plane: Plane) -> Option<(f64, Option<Vec3>)> {
+    let height = plane.distance(ray.origin);
+    if height <= 0.0 {
+        return Some((0.0, None));
+    }
+    let approach = plane.normal.dot(ray.direction);
+    if approach >= 0.0 {
+        return None;
+    }
+    Some((-height / approach, Some(plane.normal)))
+}
+
+///
+/// Sweeps a sphere along a ray into a box, which is grown by the sphere's radius to find where it might first touch.
+///
+fn sphere_cast_obb(ray: &Ray, radius: f64, obb: Obb) -> Option<(f64, Option<Vec3>)> {
+    let grown = Obb {
+        half_extents: obb.half_extents + Vec3::new(radius, radius, radius),
+        ..obb
+    };
+    sphere_cast_convex(
+        ray,
+        radius,
+        grown.ray_distance(ray),
+        |point| grown.contains(point),
+        |point| obb.closest_point(point),
+        |point| obb.nearest_face(point).0,
+    )
+}
+
+///
+/// Sweeps a sphere along a ray into a convex shape. The sphere first jumps to `entry`, where it would enter the shape
+/// grown by its radius, and then creeps forward by its distance from the shape until it touches. Each step is as far
+/// as it can move without going into the shape, so it can't overshoot, and near the grown shape's corners, where the
+/// sphere might still pass by, it runs out of the grown shape if it misses. Rays, which have no radius, land right on
+/// the shape's surface, so its normal is taken from `surface_normal` instead.
+///
+fn sphere_cast_convex(
+    ray: &Ray,
+    radius: f64,
+    entry: Option<f64>,
+    inside_grown: impl Fn(Vec3) -> bool,
+    closest_point: impl Fn(Vec3) -> Vec3,
+    surface_normal: impl Fn(Vec3) -> Vec3,
+) -> Option<(f64, Option<Vec3>)> {
+    const MAX_STEPS: usize = 32;
+    const TOLERANCE: f64 = 1e-9;
+    let mut distance = entry?;
+    for _ in 0..MAX_STEPS {
+        let centre = ray.at(distance);
+        if !inside_grown(centre) {
+            return None;
+        }
+        let closest = closest_point(centre);
+        let between = centre - closest;
+        let gap = between.length() - radius;
+        if gap <= TOLERANCE {
+            if between.length() <= TOLERANCE {
+                let normal = (distance > 0.0).then(|| surface_normal(centre));
+                return Some((distance, normal));
+            }
+            if distance == 0.0 && gap < -TOLERANCE {
+                return Some((0.0, None));
+            }
+            return Some((distance, Some(between / between.length())));
+        }
+        distance += gap;
+    }
+    None
+}
+
+fn sphere_sphere(a: Vec3, a_radius: f64, b: Vec3, b_radius: f64) -> Option<Contact> {
+    let between = b - a;
+    let distance = between.length();
+    if distance >= a_radius + b_radius {
+        return None;
+    }
+    // Spheres with the same centre are pushed apart vertically
+    let normal = if distance > f64::EPSILON {
+        between / distance
+    } else {
+        Y_AXIS
+    };
+    let depth = a_radius + b_radius - distance;
+    Some(Contact {
+        point: a + normal * (a_radius - depth / 2.0),
+        normal,
+        depth,
+    })
+}
+
+fn sphere_plane(centre: Vec3, radius: f64, plane: Plane) -> Option<Contact> {
+    let distance = plane.distance(centre);
+    if distance >= radius {
+        return None;
+    }
+    // The plane pushes the sphere out along its normal, so the normal from the sphere to the plane faces away from it
+    Some(Contact {
+        point: centre - plane.normal * distance,
+        normal: -plane.normal,
+        depth: radius - distance,
+    })
+}
+
+fn sphere_box(centre: Vec3, radius: f64, aabb: Aabb) -> Option<Contact> {
+    let closest = aabb.closest_point(centre);
+    let between = closest - centre;
+    let distance = between.length();
+    if distance >= radius {
+        return None;
+    }
+    if distance > f64::EPSILON {
+        return Some(Contact {
+            point: closest,
+            normal: between / distance,
+            depth: radius - distance,
+        });
+    }
+
+    // The centre is inside the box, so push it out through the nearest face
+    let (normal, inside) = nearest_face(&aabb, centre);
+    Some(Contact {
+        point: centre,
+        normal: -normal,
+        depth: inside + radius,
+    })
+}
+
+///
+/// Returns the outward normal of the face of a box nearest to a point inside it, and how far the point is from it.
+///
+fn nearest_face(aabb: &Aabb, point: Vec3) -> (Vec3, f64) {
+    [
+        (Vec3::new(-1.0, 0.0, 0.0), point.x - aabb.min.x),
+        (Vec3::new(1.0, 0.0, 0.0), aabb.max.x - point.x),
+        (Vec3::new(0.0, -1.0, 0.0), point.y - aabb.min.y),
+        (Vec3::new(0.0, 1.0, 0.0), aabb.max.y - point.y),
+        (Vec3::new(0.0, 0.0, -1.0), point.z - aabb.min.z),
+        (Vec3::new(0.0, 0.0, 1.0), aabb.max.z - point.z),
+    ]
+    .into_iter()
+    .min_by(|a, b| a.1.total_cmp(&b.1))
+    .expect("boxes have six faces")
+}
+
+fn box_box(a: Aabb, b: Aabb) -> Option<Contact> {
+    if !a.intersects(&b) {
+        return None;
+    }
+    // Separate the boxes along whichever axis they overlap the least on
+    let overlap = Aabb::new(
+        Vec3::new(
+            a.min.x.max(b.min.x),
+            a.min.y.max(b.min.y),
+            a.min.z.max(b.min.z),
+        ),
+        Vec3::new(
+            a.max.x.min(b.max.x),
+            a.max.y.min(b.max.y),
+            a.max.z.min(b.max.z),
+        ),
+    );
+    let size = overlap.size();
+    let between = b.centre() - a.centre();
+    let (normal, depth) = [
+        (Vec3::new(between.x.signum(), 0.0, 0.0), size.x),
+        (Vec3::new(0.0, between.y.signum(), 0.0), size.y),
+        (Vec3::new(0.0, 0.0, between.z.signum()), size.z),
+    ]
+    .into_iter()
+    .min_by(|a, b| a.1.total_cmp(&b.1))
+    .expect("boxes have three axes");
+    if depth <= 0.0 {
+        return None;
+    }
+    Some(Contact {
+        point: overlap.centre(),
+        normal,
+        depth,
+    })
+}
+
+fn box_plane(aabb: Aabb, plane: Plane) -> Option<Contact> {
+    corners_plane(aabb.corners(), plane)
+}
+
+fn sphere_obb(centre: Vec3, radius: f64, obb: Obb) -> Option<Contact> {
+    let closest = obb.closest_point(centre);
+    let between = closest - centre;
+    let distance = between.length();
+    if distance >= radius {
+        return None;
+    }
+    if distance > f64::EPSILON {
+        return Some(Contact {
+            point: closest,
+            normal: between / distance,
+            depth: radius - distance,
+        });
+    }
+
+    let (normal, inside) = obb.nearest_face(centre);
+    Some(Contact {
+        point: centre,
+        normal: -normal,
+        depth: inside + radius,
+    })
+}
+
+///
+/// Tests two boxes with the separating axis theorem - if there's any axis the boxes don't overlap on when projected
+/// onto it, they aren't touching. Only the boxes' face normals and the cross products of their edges need checking.
+/// Otherwise, the axis they overlap least on is the contact normal.
+///
+fn obb_obb(a: Obb, b: Obb) -> Option<Contact> {
+    let between = b.centre - a.centre;
+
+    let mut axes = vec![];
+    axes.extend(a.axes);
+    axes.extend(b.axes);
+    for a_axis in a.axes {
+        for b_axis in b.axes {
+            // Parallel edges don't give a new axis
+            let cross = a_axis.cross_product(b_axis);
+            if cross.length() > 1e-6 {
+                axes.push(cross.normalise());
+            }
+        }
+    }
+
+    let mut best: Option<(Vec3, f64)> = None;
+    for axis in axes {
+        let distance = between.dot(axis);
+        let overlap = a.radius_along(axis) + b.radius_along(axis) - distance.abs();
+        if overlap <= 0.0 {
+            return None;
+        }
+        // Face axes come first, so edge axes only win when they're clearly better
+        if best.is_none_or(|(_, depth)| overlap < depth - 1e-9) {
+            let normal = if distance < 0.0 { -axis } else { axis };
+            best = Some((normal, overlap));
+        }
+    }
+    let (normal, depth) = best?;
+
+    // Corners poking into the other box make up the contact, or where the nearest edges cross if none do
+    let inside: Vec<Vec3> = b
+        .corners()
+        .into_iter()
+        .filter(|corner| a.contains(*corner))
+        .chain(a.corners().into_iter().filter(|corner| b.contains(*corner)))
+        .collect();
+    let point = if inside.is_empty() {
+        (a.support(normal) + b.support(-normal)) / 2.0
+    } else {
+        inside.iter().copied().sum::<Vec3>() / inside.len() as f64
+    };
+
+    Some(Contact {
+        point,
+        normal,
+        depth,
+    })
+}
+
+///
+/// Tests a box against a triangle with the separating axis theorem, using the box's face normals, the triangle's
+/// normal, and the cross products of their edges.
+///
+fn obb_triangle(obb: Obb, triangle: [Vec3; 3]) -> Option<Contact> {
+    let [a, b, c] = triangle;
+    let edges = [b - a, c - b, a - c];
+    let face_normal = edges[0].cross_product(edges[1]);
+
+    let mut axes = vec![];
+    axes.extend(obb.axes);
+    axes.push(face_normal);
+    for axis in obb.axes {
+        for edge in edges {
+            axes.push(axis.cross_product(edge));
+        }
+    }
+
+    let mut best: Option<(Vec3, f64)> = None;
+    for axis in axes {
+        // Degenerate axes, from parallel edges or zero-area triangles, can't separate anything
+        if axis.length() < 1e-9 {
+            continue;
+        }
+        let axis = axis.normalise();
+        let centre = obb.centre.dot(axis);
+        let radius = obb.radius_along(axis);
+        let projected = triangle.map(|point| point.dot(axis));
+        let min = projected[0].min(projected[1]).min(projected[2]);
+        let max = projected[0].max(projected[1]).max(projected[2]);
+
+        // Flat triangles have no overlap to speak of, so measure how far the box would have to move either way
+        let (forwards, backwards) = (centre + radius - min, max - (centre - radius));
+        if forwards <= 0.0 || backwards <= 0.0 {
+            return None;
+        }
+        let (normal, overlap) = if forwards < backwards {
+            (axis, forwards)
+        } else {
+            (-axis, backwards)
+        };
+        if best.is_none_or(|(_, depth)| overlap < depth - 1e-9) {
+            best = Some((normal, overlap));
+        }
+    }
+    let (normal, depth) = best?;
+
+    Some(Contact {
+        point: obb.support(normal),
+        normal,
+        depth,
+    })
+}
+
+fn obb_plane(obb: Obb, plane: Plane) -> Option<Contact> {
+    corners_plane(obb.corners(), plane)
+}
+
+///
+/// Finds the contact between a shape's corners and a plane. The contact point is the middle of every corner behind the
+/// plane, so a box lying flat is pushed from its centre rather than tipped up by one corner.
+///
+fn corners_plane(corners: impl IntoIterator<Item = Vec3>, plane: Plane) -> Option<Contact> {
+    let behind: Vec<(Vec3, f64)> = corners
+        .into_iter()
+        .map(|corner| (corner, plane.distance(corner)))
+        .filter(|(_, distance)| *distance < 0.0)
+        .collect();
+    if behind.is_empty() {
+        return None;
+    }
+    let depth = behind
+        .iter()
+        .map(|(_, distance)| -distance)
+        .fold(0.0, f64::max);
+    let point = behind
+        .iter()
+        .map(|(corner, distance)| *corner - plane.normal * *distance)
+        .sum::<Vec3>()
+        / behind.len() as f64;
+    Some(Contact {
+        point,
+        normal: -plane.normal,
+        depth,
+    })
+}
+
+fn sphere_hull(centre: Vec3, radius: f64, hull: &ConvexHull) -> Option<Contact> {
+    // The face the centre is furthest in front of, or least far behind
+    let (normal, height) = hull
+        .planes()
+        .iter()
+        .map(|plane| (plane.normal, plane.distance(centre)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+    if height <= 0.0 {
+        // The centre is inside the hull, so push it out through the nearest face
+        return Some(Contact {
+            point: centre,
+            normal: -normal,
+            depth: radius - height,
+        });
+    }
+
+    let closest = hull.closest_point(centre);
+    let between = closest - centre;
+    let distance = between.length();
+    if distance >= radius || distance <= f64::EPSILON {
+        return None;
+    }
+    Some(Contact {
+        point: closest,
+        normal: between / distance,
+        depth: radius - distance,
+    })
+}
+
+///
+/// Turns a box into a hull, so it can be tested against other hulls.
+///
+fn obb_hull(obb: Obb) -> ConvexHull {
+    ConvexHull::from_points(&obb.corners()).unwrap_or_else(|| {
+        // A flat box has no volume, so it's given a sliver of one
+        let padding = Vec3::new(1e-6, 1e-6, 1e-6);
+        let obb = Obb {
+            half_extents: obb.half_extents + padding,
+            ..obb
+        };
+        ConvexHull::from_points(&obb.corners()).expect("padded boxes have volume")
+    })
+}
+
+///
+/// Finds the axis two sets of points overlap least along out of `axes`, if they overlap along all of them. The
+/// returned normal points from `a` towards `b`, and earlier axes are preferred unless a later one is clearly better.
+///
+fn least_overlap(a: &[Vec3], b: &[Vec3], axes: &[Vec3]) -> Option<(Vec3, f64)> {
+    let project = |points: &[Vec3], axis: Vec3| {
+        points
+            .iter()
+            .map(|point| point.dot(axis))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+                (min.min(value), max.max(value))
+            })
+    };
+
+    let mut best: Option<(Vec3, f64)> = None;
+    for axis in axes {
+        // Degenerate axes, from parallel edges or zero-area triangles, can't separate anything
+        if axis.length() < 1e-9 {
+            continue;
+        }
+        let axis = axis.normalise();
+        let ((a_min, a_max), (b_min, b_max)) = (project(a, axis), project(b, axis));
+        let (forwards, backwards) = (a_max - b_min, b_max - a_min);
+        if forwards <= 0.0 || backwards <= 0.0 {
+            return None;
+        }
+        let (normal, overlap) = if forwards < backwards {
+            (axis, forwards)
+        } else {
+            (-axis, backwards)
+        };
+        if best.is_none_or(|(_, depth)| overlap < depth - 1e-9) {
+            best = Some((normal, overlap));
+        }
+    }
+    best
+}
+
+///
+/// Tests two hulls with the separating axis theorem, using their face normals and the cross products of their edges,
+/// in the same way as `obb_obb`.
+///
+fn hull_hull(a: &ConvexHull, b: &ConvexHull) -> Option<Contact> {
+    let mut axes: Vec<Vec3> = a.planes().iter().map(|plane| plane.normal).collect();
+    axes.extend(b.planes().iter().map(|plane| plane.normal));
+    let b_edges = b.edge_directions();
+    for a_edge in a.edge_directions() {
+        axes.extend(b_edges.iter().map(|b_edge| a_edge.cross_product(*b_edge)));
+    }
+    let (normal, depth) = least_overlap(a.points(), b.points(), &axes)?;
+
+    // Corners poking into the other hull make up the contact, or where the nearest features meet if none do
+    let inside: Vec<Vec3> = b
+        .points()
+        .iter()
+        .copied()
+        .filter(|point| a.contains(*point))
+        .chain(
+            a.points()
+                .iter()
+                .copied()
+                .filter(|point| b.contains(*point)),
+        )
+        .collect();
+    let point = if inside.is_empty() {
+        (a.support(normal) + b.support(-normal)) / 2.0
+    } else {
+        inside.iter().copied().sum::<Vec3>() / inside.len() as f64
+    };
+
+    Some(Contact {
+        point,
+        normal,
+        depth,
+    })
+}
+
+///
+/// Tests a hull against a triangle with the separating axis theorem. The contact point is the middle of the hull's
+/// corners deepest into the triangle, so a flat face resting on it is pushed from its middle.
+///
+fn hull_triangle(hull: &ConvexHull, triangle: [Vec3; 3]) -> Option<Contact> {
+    let [a, b, c] = triangle;
+    let edges = [b - a, c - b, a - c];
+
+    let mut axes: Vec<Vec3> = hull.planes().iter().map(|plane| plane.normal).collect();
+    axes.push(edges[0].cross_product(edges[1]));
+    for hull_edge in hull.edge_directions() {
+        axes.extend(edges.iter().map(|edge| hull_edge.cross_product(*edge)));
+    }
+    let (normal, depth) = least_overlap(hull.points(), &triangle, &axes)?;
+
+    let deepest = hull.support(normal).dot(normal);
+    let touching: Vec<Vec3> = hull
+        .points()
+        .iter()
+        .copied()
+        .filter(|point| point.dot(normal) >= deepest - 1e-6)
+        .collect();
+    Some(Contact {
+        point: touching.iter().copied().sum::<Vec3>() / touching.len() as f64,
+        normal,
+        depth,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::vec::vec3::ORIGIN;
+
+    #[test]
+    fn ray_and_sphere_casts() {
+        let ray = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let sphere = Collider::Sphere {
+            centre: ORIGIN,
+            radius: 1.0,
+        };
+        assert_eq!(sphere.raycast(&ray), Some((4.0, Vec3::new(-1.0, 0.0, 0.0))));
+        assert_eq!(sphere.sphere_cast(&ray, 0.5).unwrap().0, 3.5);
+
+        let cube = Collider::Box(Aabb::new(
+            Vec3::new(-1.0, -1.0, -1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+        ));
+        assert_eq!(cube.raycast(&ray), Some((4.0, Vec3::new(-1.0, 0.0, 0.0))));
+
+        // Passing diagonally past a corner, a sphere only touches the box once it reaches the rounded corner
+        let past_corner = Ray::new(Vec3::new(-5.0, 1.5, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(cube.raycast(&past_corner), None);
+        let (distance, normal) = cube.sphere_cast(&past_corner, 1.0).unwrap();
+        let expected = 4.0 - 0.75f64.sqrt();
+        assert!((distance - expected).abs() < 1e-6, "{}", distance);
+        assert!((normal - Vec3::new(-0.75f64.sqrt(), 0.5, 0.0)).length() < 1e-6);
+        let above = Ray::new(Vec3::new(-5.0, 2.5, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(cube.sphere_cast(&above, 1.0), None);
+
+        let floor = Collider::Plane(Plane::new(Y_AXIS, 0.0));
+        let down = Ray::new(Vec3::new(3.0, 4.0, 0.0), -Y_AXIS);
+        assert_eq!(floor.sphere_cast(&down, 1.0), Some((3.0, Y_AXIS)));
+        assert_eq!(floor.raycast(&ray), Some((0.0, -ray.direction)));
+        let up = Ray::new(Vec3::new(3.0, 4.0, 0.0), Y_AXIS);
+        assert_eq!(floor.raycast(&up), None);
+    }
+
+    #[test]
+    fn sphere_contacts() {
+        let sphere = |x: f64| Collider::Sphere {
+            centre: Vec3::new(x, 0.0, 0.0),
+            radius: 1.0,
+        };
+        let contact = sphere(0.0).collide(&sphere(1.5)).unwrap();
+        assert_eq!(contact.normal, Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(contact.depth, 0.5);
+        assert_eq!(contact.point, Vec3::new(0.75, 0.0, 0.0));
+        assert_eq!(sphere(0.0).collide(&sphere(3.0)), None);
+
+        // A floor at y = -0.5
+        let floor = Collider::Plane(Plane::new(Y_AXIS, 0.5));
+        let contact = floor.collide(&sphere(0.0)).unwrap();
+        assert_eq!(contact.normal, Y_AXIS);
+        assert_eq!(contact.depth, 0.5);
+        assert_eq!(contact.point, Vec3::new(0.0, -0.5, 0.0));
+    }
+
+    #[test]
+    fn box_contacts() {
+        let unit = |x: f64, y: f64| {
+            let centre = Vec3::new(x, y, 0.0);
+            let half = Vec3::new(0.5, 0.5, 0.5);
+            Collider::Box(Aabb::new(centre - half, centre + half))
+        };
+        let contact = unit(0.0, 0.0).collide(&unit(0.25, 0.9)).unwrap();
+        assert_eq!(contact.normal, Y_AXIS);
+        assert!((contact.depth - 0.1).abs() < 1e-9);
+        assert_eq!(unit(0.0, 0.0).collide(&unit(2.0, 0.0)), None);
+
+        let sphere = Collider::Sphere {
+            centre: Vec3::new(1.0, 0.0, 0.0),
+            radius: 1.0,
+        };
+        let contact = sphere.collide(&unit(0.0, 0.0)).unwrap();
+        assert_eq!(contact.normal, Vec3::new(-1.0, 0.0, 0.0));
+        assert_eq!(contact.depth, 0.5);
+
+        let floor = Collider::Plane(Plane::new(Y_AXIS, 0.0));
+        let contact = unit(0.0, 0.25).collide(&floor).unwrap();
+        assert_eq!(contact.normal, -Y_AXIS);
+        assert_eq!(contact.depth, 0.25);
+    }
+
+    #[test]
+    fn rotated_box_contacts() {
+        let turned = |x: f64, degrees: f64| {
+            let transformation = Mat4::identity()
+                .rotate(Y_AXIS, degrees.to_radians())
+                .translate(Vec3::new(x, 0.0, 0.0));
+            Collider::Obb(Obb::new(ORIGIN, Vec3::new(0.5, 0.5, 0.5))).transformed(transformation)
+        };
+
+        // A box turned 45 degrees reaches further along X, so these touch where unturned boxes wouldn't
+        assert_eq!(turned(0.0, 0.0).collide(&turned(1.1, 0.0)), None);
+        let contact = turned(0.0, 45.0).collide(&turned(1.1, 0.0)).unwrap();
+        assert!((contact.normal - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!((contact.depth - (0.5f64.sqrt() + 0.5 - 1.1)).abs() < 1e-9);
+        // The turned box's corner is the one poking in
+        assert!((contact.point.x - 0.5f64.sqrt()).abs() < 1e-9);
+
+        // Turning about Y doesn't change how far down the box reaches
+        let floor = Collider::Plane(Plane::new(Y_AXIS, 0.25));
+        let contact = turned(0.0, 30.0).collide(&floor).unwrap();
+        assert_eq!(contact.normal, -Y_AXIS);
+        assert!((contact.depth - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn triangle_contacts() {
+        // A large triangle lying flat at y = 0
+        let (a, b, c) = (
+            Vec3::new(-10.0, 0.0, -10.0),
+            Vec3::new(0.0, 0.0, 10.0),
+            Vec3::new(10.0, 0.0, -10.0),
+        );
+        let sphere = Collider::Sphere {
+            centre: Vec3::new(0.0, 0.5, 0.0),
+            radius: 1.0,
+        };
+        let contact = sphere.collide_triangle(a, b, c).unwrap();
+        assert_eq!(contact.normal, -Y_AXIS);
+        assert_eq!(contact.depth, 0.5);
+
+        let cube = Collider::Obb(Obb::new(
+            Vec3::new(0.0, 0.25, 0.0),
+            Vec3::new(0.5, 0.5, 0.5),
+        ));
+        let contact = cube.collide_triangle(a, b, c).unwrap();
+        assert!((contact.normal + Y_AXIS).length() < 1e-9);
+        assert!((contact.depth - 0.25).abs() < 1e-9);
+
+        let above = Collider::Obb(Obb::new(Vec3::new(0.0, 2.0, 0.0), Vec3::new(0.5, 0.5, 0.5)));
+        assert_eq!(above.collide_triangle(a, b, c), None);
+    }
+
+    #[test]
+    fn volumes() {
+        let sphere = Collider::Sphere {
+            centre: ORIGIN,
+            radius: 1.0,
+        };
+        assert!((sphere.volume().unwrap() - 4.0 / 3.0 * PI).abs() < 1e-9);
+        assert!(sphere.contains(Vec3::new(0.0, 0.9, 0.0)));
+        assert!(!sphere.contains(Vec3::new(0.8, 0.8, 0.0)));
+
+        let aabb = Aabb::new(ORIGIN, Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(Collider::Box(aabb).volume(), Some(6.0));
+        let turned = Obb::from_aabb(aabb).transform(Mat4::identity().rotate(Y_AXIS, 1.0));
+        assert!((Collider::Obb(turned).volume().unwrap() - 6.0).abs() < 1e-9);
+        let hull = ConvexHull::from_points(&aabb.corners()).unwrap();
+        assert!((Collider::Hull(hull).volume().unwrap() - 6.0).abs() < 1e-9);
+
+        let plane = Collider::Plane(Plane::new(Y_AXIS, 0.0));
+        assert_eq!(plane.volume(), None);
+        assert!(plane.contains(Vec3::new(5.0, -1.0, 5.0)));
+    }
+
+    #[test]
+    fn hull_contacts() {
+        // A unit cube and a pyramid sitting 0.1 into the top of it
+        let cube = |centre: Vec3| {
+            let half = Vec3::new(0.5, 0.5, 0.5);
+            Collider::Box(Aabb::new(centre - half, centre + half))
+        };
+        let pyramid = ConvexHull::from_points(&[
+            Vec3::new(-0.5, 0.4, -0.5),
+            Vec3::new(0.5, 0.4, -0.5),
+            Vec3::new(0.5, 0.4, 0.5),
+            Vec3::new(-0.5, 0.4, 0.5),
+            Vec3::new(0.0, 1.4, 0.0),
+        ])
+        .map(Collider::Hull)
+        .unwrap();
+
+        let contact = cube(ORIGIN).collide(&pyramid).unwrap();
+        assert!((contact.normal - Y_AXIS).length() < 1e-9);
+        assert!((contact.depth - 0.1).abs() < 1e-9);
+        // The corners of the pyramid's base are inside the cube
+        assert!((contact.point - Vec3::new(0.0, 0.4, 0.0)).length() < 1e-9);
+        let contact = pyramid.collide(&cube(ORIGIN)).unwrap();
+        assert!((contact.normal + Y_AXIS).length() < 1e-9);
+        assert_eq!(pyramid.collide(&cube(Vec3::new(0.0, 3.0, 0.0))), None);
+        // Clear of the pyramid's sloping side, even though the boxes around them would overlap
+        assert_eq!(pyramid.collide(&cube(Vec3::new(0.95, 1.35, 0.0))), None);
+
+        let sphere = Collider::Sphere {
+            centre: Vec3::new(0.0, 2.0, 0.0),
+            radius: 0.75,
+        };
+        let contact = sphere.collide(&pyramid).unwrap();
+        assert!((contact.normal + Y_AXIS).length() < 1e-9);
+        assert!((contact.depth - 0.15).abs() < 1e-9);
+
+        let floor = Collider::Plane(Plane::new(Y_AXIS, -0.5));
+        let contact = pyramid.collide(&floor).unwrap();
+        assert_eq!(contact.normal, -Y_AXIS);
+        assert!((contact.depth - 0.1).abs() < 1e-9);
+        assert!((contact.point - Vec3::new(0.0, 0.5, 0.0)).length() < 1e-9);
+
+        // Resting flat on a triangle, the pyramid is pushed up from the middle of its base
+        let raised = pyramid.transformed(Mat4::identity().translate(Vec3::new(0.0, -0.5, 0.0)));
+        let (a, b, c) = (
+            Vec3::new(-10.0, 0.0, -10.0),
+            Vec3::new(0.0, 0.0, 10.0),
+            Vec3::new(10.0, 0.0, -10.0),
+        );
+        let contact = raised.collide_triangle(a, b, c).unwrap();
+        assert!((contact.normal + Y_AXIS).length() < 1e-9);
+        assert!((contact.depth - 0.1).abs() < 1e-9);
+        assert!((contact.point - Vec3::new(0.0, -0.1, 0.0)).length() < 1e-9);
+
+        // Coming down onto one of the pyramid's sides
+        let down = Ray::new(Vec3::new(0.1, 5.0, 0.0), -Y_AXIS);
+        let (distance, normal) = pyramid.raycast(&down).unwrap();
+        assert!((distance - 3.8).abs() < 1e-6, "{}", distance);
+        assert!((normal - Vec3::new(2.0, 1.0, 0.0).normalise()).length() < 1e-6);
+        let (distance, _) = pyramid.sphere_cast(&down, 0.5).unwrap();
+        assert!(distance < 3.8 - 0.5, "{}", distance);
+        assert_eq!(
+            pyramid.raycast(&Ray::new(Vec3::new(0.6, 5.0, 0.0), -Y_AXIS)),
+            None
+        );
+    }
+
+    #[test]
+    fn transform_colliders() {
+        let moved = Mat4::identity().translate(Vec3::new(0.0, 2.0, 0.0));
+        let floor = Collider::Plane(Plane::new(Y_AXIS, 0.0)).transformed(moved);
+        assert_eq!(floor, Collider::Plane(Plane::new(Y_AXIS, -2.0)));
+
+        let sphere = Collider::Sphere {
+            centre: Vec3::new(1.0, 0.0, 0.0),
+            radius: 1.0,
+        };
+        let Collider::Sphere { centre, .. } = sphere.transformed(moved) else {
+            panic!("spheres stay spheres");
+        };
+        assert_eq!(centre, Vec3::new(1.0, 2.0, 0.0));
+    }
+}
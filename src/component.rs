@@ -0,0 +1,154 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+};
+
+///
+/// A column of components of a single type, indexed by the slot of the entity they belong to.
+///
+struct Column<T> {
+    values: Vec<Option<T>>,
+}
+
+///
+/// The parts of a column which don't depend on its type, so every column can be stored together.
+///
+trait Storage: Send + Sync {
+    fn clear(&mut self, index: usize);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Send + Sync + 'static> Storage for Column<T> {
+    fn clear(&mut self, index: usize) {
+        if let Some(value) = self.values.get_mut(index) {
+            *value = None;
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+///
+/// Optional components attached to entities, stored in one column per component type. Any `Send + Sync + 'static`
+/// type can be a component, so whatever holds them can be sent between threads, and each entity can have at most one
+/// component of each type.
+///
+/// Entities are identified by their slot index here - it's up to the owner to clear an entity's components when its
+/// slot is reused.
+///
+#[derive(Default)]
+pub struct Components {
+    columns: HashMap<TypeId, Box<dyn Storage>>,
+}
+
+impl Components {
+    pub fn new() -> Components {
+        Components::default()
+    }
+
+    ///
+    /// Attaches a component to an entity, returning the component of the same type it had before.
+    ///
+    pub fn insert<T: Send + Sync + 'static>(&mut self, index: usize, component: T) -> Option<T> {
+        let column = self
+            .columns
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(Column::<T> { values: vec![] }))
+            .as_any_mut()
+            .downcast_mut::<Column<T>>()
+            .expect("columns are keyed by the type they hold");
+
+        if column.values.len() <= index {
+            column.values.resize_with(index + 1, || None);
+        }
+        column.values[index].replace(component)
+    }
+
+    pub fn remove<T: Send + Sync + 'static>(&mut self, index: usize) -> Option<T> {
+        self.column_mut::<T>()?.values.get_mut(index)?.take()
+    }
+
+    ///
+    /// Removes every component attached to an entity.
+    ///
+    pub fn clear(&mut self, index: usize) {
+        for column in self.columns.values_mut() {
+            column.clear(index);
+        }
+    }
+
+    pub fn get<T: Send + Sync + 'static>(&self, index: usize) -> Option<&T> {
+        self.column::<T>()?.values.get(index)?.as_ref()
+    }
+
+    pub fn get_mut<T: Send + Sync + 'static>(&mut self, index: usize) -> Option<&mut T> {
+        self.column_mut::<T>()?.values.get_mut(index)?.as_mut()
+    }
+
+    ///
+    /// Iterates over every component of a type, along with the index of the entity it belongs to.
+    ///
+    pub fn iter<T: Send + Sync + 'static>(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.column::<T>()
+            .into_iter()
+            .flat_map(|column| column.values.iter().enumerate())
+            .filter_map(|(index, value)| Some((index, value.as_ref()?)))
+    }
+
+    pub fn iter_mut<T: Send + Sync + 'static>(&mut self) -> impl Iterator<Item = (usize, &mut T)> {
+        self.column_mut::<T>()
+            .into_iter()
+            .flat_map(|column| column.values.iter_mut().enumerate())
+            .filter_map(|(index, value)| Some((index, value.as_mut()?)))
+    }
+
+    fn column<T: Send + Sync + 'static>(&self) -> Option<&Column<T>> {
+        self.columns
+            .get(&TypeId::of::<T>())?
+            .as_any()
+            .downcast_ref()
+    }
+
+    fn column_mut<T: Send + Sync + 'static>(&mut self) -> Option<&mut Column<T>> {
+        self.columns
+            .get_mut(&TypeId::of::<T>())?
+            .as_any_mut()
+            .downcast_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn components_by_type() {
+        let mut components = Components::new();
+        components.insert(0, 1.0_f64);
+        components.insert(2, 3.0_f64);
+        components.insert(2, "two");
+
+        assert_eq!(components.get::<f64>(2), Some(&3.0));
+        assert_eq!(components.get::<&str>(0), None);
+        assert_eq!(
+            components.iter::<f64>().collect::<Vec<_>>(),
+            vec![(0, &1.0), (2, &3.0)]
+        );
+
+        for (_, value) in components.iter_mut::<f64>() {
+            *value *= 2.0;
+        }
+        assert_eq!(components.remove::<f64>(0), Some(2.0));
+
+        components.clear(2);
+        assert_eq!(components.iter::<f64>().count(), 0);
+        assert_eq!(components.get::<&str>(2), None);
+    }
+}
@@ -0,0 +1,328 @@
+use alloc::string::{String, ToString};
+#[cfg(feature = "std")]
+use std::{error::Error, fmt::Display, fs, io};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::float::Float;
+use crate::{
+    batch::TriangleOrder, depth_of_field::DepthOfField, environment::Fog, framebuffer::Colour,
+    hdr::Hdr, retro::Retro,
+};
+
+///
+/// How triangles are drawn.
+/// - `Solid` fills them in
+/// - `Wireframe` only draws their edges, which shows how a model is built and how much is being drawn
+/// - `Outlined` fills them in and draws their edges over the top
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FillMode {
+    #[default]
+    Solid,
+    Wireframe,
+    Outlined,
+}
+
+///
+/// How a frame drawn below full size is scaled up to fill it.
+/// - `Nearest` copies the nearest pixel, which keeps hard edges and suits a blocky, retro look
+/// - `Bilinear` blends the four nearest pixels, which is smoother but blurrier
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Upscale {
+    #[default]
+    Nearest,
+    Bilinear,
+}
+
+///
+/// Which pixels are drawn each frame, with the rest kept from the last one.
+/// - `Off` draws every pixel
+/// - `Scanlines` draws every other row, swapping between odd and even rows each frame
+/// - `Checkerboard` draws every other pixel in a checkerboard pattern, swapping between its squares each frame. Moving
+///   edges break up into a fine mesh rather than combing
+///
+/// Interlacing roughly halves the time spent filling in triangles, at the cost of smearing things that move quickly.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Interlace {
+    #[default]
+    Off,
+    Scanlines,
+    Checkerboard,
+}
+
+///
+/// How a `Renderer` draws.
+/// - `width` and `height` are the size of the image shown, in pixels
+/// - `render_scale` is how much of that size the world is drawn at. Scales below 1 draw fewer pixels, trading sharpness
+///   for speed, and text and overlays drawn once the world is finished are still drawn at full size
+/// - `upscale` is how the world is scaled up to full size when the render scale isn't 1
+/// - `vsync` holds frames to the display's refresh rate to stop them tearing. The window can't ask the display when
+///   it refreshes, so this assumes 60Hz
+/// - `fill_mode` is whether triangles are filled in, outlined, or both
+/// - `interlace` draws only half the pixels each frame, filling in the rest from the frame before
+/// - `triangle_order` is the order each frame's triangles are rasterised in once they've all been worked out
+/// - `fog` fades out distant objects in worlds whose environment has no fog of its own
+/// - `clear_colour` fills the screen at the start of each frame, showing wherever nothing is drawn when there's no
+///   skybox or fog
+/// - `hdr` draws in high dynamic range, tonemapping each frame once it's drawn. Without it, colours are clamped as
+///   they're drawn
+/// - `retro` draws like a mid-90s console, with wobbling vertices, warped textures and fewer colours. Without it,
+///   textures are mapped with perspective correction
+/// - `depth_of_field` blurs the world where it's out of focus, once it's drawn. Without it, everything is sharp
+///
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RendererConfig {
+    pub width: usize,
+    pub height: usize,
+    pub render_scale: f64,
+    pub upscale: Upscale,
+    pub vsync: bool,
+    pub fill_mode: FillMode,
+    pub interlace: Interlace,
+    pub triangle_order: TriangleOrder,
+    pub fog: Option<Fog>,
+    pub clear_colour: Colour,
+    pub hdr: Option<Hdr>,
+    pub retro: Option<Retro>,
+    pub depth_of_field: Option<DepthOfField>,
+}
+
+impl RendererConfig {
+    ///
+    /// Creates a config for drawing images of the given size, with everything else left at its default.
+    ///
+    pub fn new(width: usize, height: usize) -> RendererConfig {
+        RendererConfig {
+            width,
+            height,
+            ..RendererConfig::default()
+        }
+    }
+
+    ///
+    /// Returns the size of the image actually drawn, after `render_scale` is applied. Neither side is ever less than a
+    /// pixel.
+    ///
+    pub fn render_size(&self) -> (usize, usize) {
+        let scale = if self.render_scale > 0.0 {
+            self.render_scale
+        } else {
+            1.0
+        };
+        let scaled = |size: usize| ((size as f64 * scale).round() as usize).max(1);
+        (scaled(self.width), scaled(self.height))
+    }
+}
+
+impl Default for RendererConfig {
+    fn default() -> Self {
+        RendererConfig {
+            width: 600,
+            height: 400,
+            render_scale: 1.0,
+            upscale: Upscale::default(),
+            vsync: false,
+            fill_mode: FillMode::default(),
+            interlace: Interlace::default(),
+            triangle_order: TriangleOrder::default(),
+            fog: None,
+            clear_colour: Colour::BLACK,
+            hdr: None,
+            retro: None,
+            depth_of_field: None,
+        }
+    }
+}
+
+///
+/// How much bigger than the rendered image a window is opened. `FitScreen` picks the largest size that fits on the
+/// screen.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum WindowScale {
+    #[default]
+    X1,
+    X2,
+    X4,
+    X8,
+    FitScreen,
+}
+
+///
+/// What opens the window and shows frames in it. All of them show the same image drawn by the same renderer.
+/// - `Minifb` is simple and quick to build, but doesn't handle high DPI displays and can only emulate capturing the
+///   mouse
+/// - `Winit` uses winit and softbuffer, which scale the window properly on high DPI displays and really capture the
+///   mouse. It needs the `winit` feature
+/// - `Sdl2` uses SDL2, which also handles high DPI displays and captures the mouse, and works on some Wayland setups
+///   where minifb doesn't. It needs the `sdl2` feature and the SDL2 library
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum WindowBackend {
+    #[default]
+    Minifb,
+    Winit,
+    Sdl2,
+}
+
+///
+/// The window opened by `run`.
+/// - `title` is shown in the window's title bar
+/// - `scale` is how much bigger than the rendered image the window is
+/// - `resizable` lets the window be resized, stretching the image to fill it
+/// - `backend` is what opens the window
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowConfig {
+    pub title: String,
+    pub scale: WindowScale,
+    pub resizable: bool,
+    pub backend: WindowBackend,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            title: "megavertex".to_string(),
+            scale: WindowScale::default(),
+            resizable: false,
+            backend: WindowBackend::default(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum ConfigError {
+    IoError(io::Error),
+    ParseError(toml::de::Error),
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for ConfigError {
+    fn from(error: io::Error) -> Self {
+        ConfigError::IoError(error)
+    }
+}
+#[cfg(feature = "std")]
+impl From<toml::de::Error> for ConfigError {
+    fn from(error: toml::de::Error) -> Self {
+        ConfigError::ParseError(error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ConfigError::IoError(error) => write!(f, "failed to read config file: {}", error),
+            ConfigError::ParseError(error) => write!(f, "failed to parse config: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for ConfigError {}
+
+///
+/// Window and renderer settings, which can be kept in a TOML file rather than set in code. Anything left out of the
+/// file keeps its default, so a file only needs the settings it changes:
+///
+/// ```text
+/// [window]
+/// title = "my game"
+/// scale = "X2"
+///
+/// [renderer]
+/// width = 320
+/// height = 200
+/// fill_mode = "Outlined"
+/// clear_colour = { r = 25, g = 25, b = 51 }
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub window: WindowConfig,
+    pub renderer: RendererConfig,
+}
+
+///
+/// Reading config files needs the `std` feature, for the file system and the TOML parser.
+///
+#[cfg(feature = "std")]
+impl Config {
+    pub fn load(path: &str) -> Result<Config, ConfigError> {
+        Config::from_toml(&fs::read_to_string(path)?)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Config, ConfigError> {
+        Ok(toml::from_str(text)?)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::hdr::Tonemapper;
+
+    #[test]
+    fn config_from_toml() {
+        let config = Config::from_toml(
+            r#"
+            [window]
+            title = "test"
+            resizable = true
+            backend = "Winit"
+
+            [renderer]
+            width = 320
+            render_scale = 0.5
+            upscale = "Bilinear"
+            interlace = "Checkerboard"
+            fill_mode = "Wireframe"
+            fog = { colour = { x = 1.0, y = 1.0, z = 1.0 }, start = 5.0, end = 20.0 }
+            hdr = { tonemapper = "Aces" }
+            "#,
+        )
+        .unwrap();
+
+        // Settings left out keep their defaults
+        assert_eq!(config.window.title, "test");
+        assert!(config.window.resizable);
+        assert_eq!(config.window.scale, WindowScale::X1);
+        assert_eq!(config.window.backend, WindowBackend::Winit);
+        assert_eq!(config.renderer.width, 320);
+        assert_eq!(config.renderer.height, 400);
+        assert_eq!(config.renderer.fill_mode, FillMode::Wireframe);
+        assert_eq!(config.renderer.fog.map(|fog| fog.end), Some(20.0));
+        assert_eq!(config.renderer.render_size(), (160, 200));
+        assert_eq!(config.renderer.upscale, Upscale::Bilinear);
+        assert_eq!(config.renderer.interlace, Interlace::Checkerboard);
+        assert_eq!(
+            config.renderer.hdr,
+            Some(Hdr {
+                tonemapper: Tonemapper::Aces,
+                exposure: 1.0
+            })
+        );
+
+        assert_eq!(Config::from_toml("").unwrap(), Config::default());
+        // The demo's config spells out every default
+        assert_eq!(
+            Config::load("./resources/config.toml").unwrap(),
+            Config::default()
+        );
+        assert!(matches!(
+            Config::from_toml("[renderer]\nwidth = \"wide\""),
+            Err(ConfigError::ParseError(_))
+        ));
+    }
+}
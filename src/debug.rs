@@ -0,0 +1,265 @@
+use std::f64::consts::TAU;
+
+use crate::{
+    collider::Collider,
+    framebuffer::Colour,
+    geometry::{aabb::Aabb, frustum::Plane, hull::ConvexHull, obb::Obb},
+    mat4::Mat4,
+    vec::vec3::{Vec3, ORIGIN, X_AXIS, Y_AXIS, Z_AXIS},
+};
+
+pub const RED: Colour = Colour::from_hex(0xff0000);
+pub const GREEN: Colour = Colour::from_hex(0x00ff00);
+pub const BLUE: Colour = Colour::from_hex(0x0000ff);
+pub const GREY: Colour = Colour::from_hex(0x808080);
+
+// How many lines a debug grid has on each side of the origin
+const GRID_HALF_LINES: i32 = 10;
+
+// How many straight lines make up a debug circle
+const CIRCLE_SEGMENTS: usize = 24;
+
+// How far a debug plane is drawn out from the point nearest the origin. Planes go on forever, so this is just enough
+// to see which way they face
+const PLANE_EXTENT: f64 = 5.0;
+
+///
+/// A world space line segment drawn over the scene for debugging.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DebugLine {
+    pub from: Vec3,
+    pub to: Vec3,
+    pub colour: Colour,
+}
+
+///
+/// A list of debug lines to draw on the next frame. Shapes are added with immediate calls each frame, and the list is
+/// cleared once they've been drawn.
+///
+#[derive(Debug, Clone, Default)]
+pub struct DebugDraw {
+    lines: Vec<DebugLine>,
+}
+
+impl DebugDraw {
+    pub fn new() -> DebugDraw {
+        DebugDraw { lines: vec![] }
+    }
+
+    pub fn lines(&self) -> &[DebugLine] {
+        &self.lines
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    pub fn line(&mut self, from: Vec3, to: Vec3, colour: Colour) {
+        self.lines.push(DebugLine { from, to, colour });
+    }
+
+    ///
+    /// Draws the X, Y and Z axes of a transformation in red, green and blue, each one unit long before the
+    /// transformation is applied.
+    ///
+    pub fn axes(&mut self, transformation: Mat4) {
+        let origin = transformation.transform(ORIGIN);
+        for (axis, colour) in [(X_AXIS, RED), (Y_AXIS, GREEN), (Z_AXIS, BLUE)] {
+            self.line(origin, transformation.transform(axis), colour);
+        }
+    }
+
+    ///
+    /// Draws the twelve edges of a box.
+    ///
+    pub fn aabb(&mut self, aabb: &Aabb, colour: Colour) {
+        self.box_edges(aabb.corners(), colour);
+    }
+
+    ///
+    /// Draws the twelve edges of an oriented box.
+    ///
+    pub fn obb(&mut self, obb: &Obb, colour: Colour) {
+        self.box_edges(obb.corners(), colour);
+    }
+
+    fn box_edges(&mut self, corners: [Vec3; 8], colour: Colour) {
+        // Corners are numbered so that each bit of the index picks the max of one axis, so edges join corners which
+        // differ by a single bit
+        for a in 0..corners.len() {
+            for bit in [1, 2, 4] {
+                if a & bit == 0 {
+                    self.line(corners[a], corners[a | bit], colour);
+                }
+            }
+        }
+    }
+
+    ///
+    /// Draws a circle around an axis.
+    ///
+    pub fn circle(&mut self, centre: Vec3, axis: Vec3, radius: f64, colour: Colour) {
+        let (u, v) = perpendiculars(axis);
+        let point = |i: usize| {
+            let angle = TAU * i as f64 / CIRCLE_SEGMENTS as f64;
+            centre + (u * angle.cos() + v * angle.sin()) * radius
+        };
+        for i in 0..CIRCLE_SEGMENTS {
+            self.line(point(i), point(i + 1), colour);
+        }
+    }
+
+    ///
+    /// Draws a sphere as three circles, one around each axis.
+    ///
+    pub fn sphere(&mut self, centre: Vec3, radius: f64, colour: Colour) {
+        for axis in [X_AXIS, Y_AXIS, Z_AXIS] {
+            self.circle(centre, axis, radius, colour);
+        }
+    }
+
+    ///
+    /// Draws the edges of a convex hull. Edges between two faces lying flat against each other are drawn too, so
+    /// the triangles making up each side can be seen.
+    ///
+    pub fn hull(&mut self, hull: &ConvexHull, colour: Colour) {
+        let points = hull.points();
+        for [a, b, c] in hull.faces() {
+            // Each edge is shared by two faces, which run along it in opposite directions
+            for (from, to) in [(*a, *b), (*b, *c), (*c, *a)] {
+                if from < to {
+                    self.line(points[from], points[to], colour);
+                }
+            }
+        }
+    }
+
+    ///
+    /// Draws a square patch of a plane around the point on it nearest the origin, with a line showing which way it
+    /// faces.
+    ///
+    pub fn plane(&mut self, plane: &Plane, colour: Colour) {
+        let centre = plane.normal * -plane.d;
+        let (u, v) = perpendiculars(plane.normal);
+        let corners = [u + v, u - v, -u - v, -u + v].map(|offset| centre + offset * PLANE_EXTENT);
+        for i in 0..corners.len() {
+            self.line(corners[i], corners[(i + 1) % corners.len()], colour);
+        }
+        self.line(centre, centre + plane.normal, colour);
+    }
+
+    ///
+    /// Draws the outline of a collider in world space.
+    ///
+    pub fn collider(&mut self, collider: &Collider, colour: Colour) {
+        match collider {
+            Collider::Sphere { centre, radius } => self.sphere(*centre, *radius, colour),
+            Collider::Box(aabb) => self.aabb(aabb, colour),
+            Collider::Obb(obb) => self.obb(obb, colour),
+            Collider::Plane(plane) => self.plane(plane, colour),
+            Collider::Hull(hull) => self.hull(hull, colour),
+        }
+    }
+
+    ///
+    /// Marks a point with a small cross, `size` across.
+    ///
+    pub fn point(&mut self, point: Vec3, size: f64, colour: Colour) {
+        for axis in [X_AXIS, Y_AXIS, Z_AXIS] {
+            let offset = axis * (size / 2.0);
+            self.line(point - offset, point + offset, colour);
+        }
+    }
+
+    ///
+    /// Draws a grid of lines `spacing` apart on the XZ plane, centred on the origin.
+    ///
+    pub fn grid(&mut self, spacing: f64) {
+        let extent = spacing * GRID_HALF_LINES as f64;
+        for i in -GRID_HALF_LINES..=GRID_HALF_LINES {
+            let offset = i as f64 * spacing;
+            self.line(
+                Vec3::new(offset, 0.0, -extent),
+                Vec3::new(offset, 0.0, extent),
+                GREY,
+            );
+            self.line(
+                Vec3::new(-extent, 0.0, offset),
+                Vec3::new(extent, 0.0, offset),
+                GREY,
+            );
+        }
+    }
+}
+
+///
+/// Returns two unit vectors at right angles to a direction and each other.
+///
+fn perpendiculars(direction: Vec3) -> (Vec3, Vec3) {
+    let direction = direction.normalise();
+    let other = if direction.x.abs() < 0.9 {
+        X_AXIS
+    } else {
+        Y_AXIS
+    };
+    let u = direction.cross_product(other).normalise();
+    (u, direction.cross_product(u))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn aabb_edges() {
+        let mut debug = DebugDraw::new();
+        let aabb = Aabb::new(ORIGIN, Vec3::new(1.0, 2.0, 3.0));
+        debug.aabb(&aabb, RED);
+
+        assert_eq!(debug.lines().len(), 12);
+        for line in debug.lines() {
+            // Every edge runs along exactly one axis
+            let length = (line.to - line.from).length();
+            assert!([1.0, 2.0, 3.0].contains(&length));
+        }
+
+        debug.clear();
+        assert!(debug.lines().is_empty());
+    }
+
+    #[test]
+    fn collider_outlines() {
+        let mut debug = DebugDraw::new();
+        let centre = Vec3::new(1.0, 2.0, 3.0);
+        debug.collider(
+            &Collider::Sphere {
+                centre,
+                radius: 2.0,
+            },
+            RED,
+        );
+        assert_eq!(debug.lines().len(), CIRCLE_SEGMENTS * 3);
+        for line in debug.lines() {
+            assert!(((line.from - centre).length() - 2.0).abs() < 1e-9);
+        }
+
+        // A cube's hull is made of twelve triangles, which have eighteen edges between them
+        debug.clear();
+        let corners: Vec<Vec3> = Aabb::new(ORIGIN, Vec3::new(1.0, 1.0, 1.0))
+            .corners()
+            .to_vec();
+        let hull = ConvexHull::from_points(&corners).unwrap();
+        debug.collider(&Collider::Hull(hull), RED);
+        assert_eq!(debug.lines().len(), 18);
+
+        // Planes are drawn flat, with a line sticking out of them
+        debug.clear();
+        let plane = Plane::new(Y_AXIS, -1.0);
+        debug.collider(&Collider::Plane(plane), RED);
+        assert_eq!(debug.lines().len(), 5);
+        let normal = debug.lines()[4];
+        assert_eq!(normal.from, Y_AXIS);
+        assert_eq!(normal.to, Y_AXIS * 2.0);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{
+    geometry::{aabb::Aabb, obb::Obb},
+    mat4::Mat4,
+    object::Texture,
+    vec::{
+        vec2::Vec2,
+        vec3::{Vec3, X_AXIS, Y_AXIS},
+    },
+};
+
+///
+/// How a decal's texture is combined with the surface under it. Textures have no alpha channel, so the mode decides
+/// what shows through.
+/// - `Mix` covers the surface with the texture, blended by the decal's opacity, for posters and signs
+/// - `Multiply` darkens the surface by the texture, so white parts leave it untouched, for bullet holes, scorch marks
+///   and blob shadows
+///
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum DecalMode {
+    #[default]
+    Mix,
+    Multiply,
+}
+
+///
+/// A texture projected onto whatever geometry lies inside a box. The texture is laid across the box's first two axes,
+/// left to right and top to bottom, and projected along the third, which points out of the surfaces it lands on.
+/// Faces turned away from the projection are left alone, so a decal doesn't bleed out of the far side of a wall.
+///
+/// Add decals to `World::decals` to draw them. They land on opaque entities in the world layer, and are drawn over
+/// them after the layer is finished, without changing the depth buffer.
+///
+#[derive(Clone)]
+pub struct Decal {
+    pub texture: Texture,
+    pub volume: Obb,
+    pub mode: DecalMode,
+    pub opacity: f64,
+}
+
+impl Decal {
+    pub fn new(texture: Texture, volume: Obb) -> Decal {
+        Decal {
+            texture,
+            volume,
+            mode: DecalMode::Mix,
+            opacity: 1.0,
+        }
+    }
+
+    ///
+    /// Creates a decal `size` across, lying flat on a surface at `point` with the given normal, e.g. where a ray hit
+    /// it. The box reaches `depth` in front of and behind the surface, so it still covers slightly bumpy ground. On
+    /// walls the texture stays upright, and on floors and ceilings its top points along the Z axis.
+    ///
+    pub fn on_surface(
+        texture: Texture,
+        point: Vec3,
+        normal: Vec3,
+        size: Vec2,
+        depth: f64,
+    ) -> Decal {
+        Decal::new(texture, surface_volume(point, normal, size, depth))
+    }
+
+    pub fn with_mode(mut self, mode: DecalMode) -> Decal {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_opacity(mut self, opacity: f64) -> Decal {
+        self.opacity = opacity;
+        self
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        self.volume.bounds()
+    }
+
+    ///
+    /// Computes the matrix moving points in world space into the decal's box, which spans -0.5 to 0.5 along each axis.
+    /// Returns `None` if the box is flat.
+    ///
+    pub fn to_local(&self) -> Option<Mat4> {
+        let Obb {
+            centre,
+            axes,
+            half_extents,
+        } = self.volume;
+        let extents = [half_extents.x, half_extents.y, half_extents.z];
+        if extents.iter().any(|extent| *extent <= 0.0) {
+            return None;
+        }
+        let row = |axis: Vec3, extent: f64| {
+            let axis = axis / (2.0 * extent);
+            [axis.x, axis.y, axis.z, -axis.dot(centre)]
+        };
+        Some(Mat4 {
+            m: [
+                row(axes[0], extents[0]),
+                row(axes[1], extents[1]),
+                row(axes[2], extents[2]),
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        })
+    }
+}
+
+///
+/// Builds the box for a decal lying on a surface, as described in `Decal::on_surface`.
+///
+pub(crate) fn surface_volume(point: Vec3, normal: Vec3, size: Vec2, depth: f64) -> Obb {
+    let normal = normal.normalise();
+    let up = match normal.dot(Y_AXIS).abs() > 0.99 {
+        true => -normal.cross_product(X_AXIS),
+        false => Y_AXIS,
+    };
+    let right = up.cross_product(normal).normalise();
+    Obb {
+        centre: point,
+        axes: [right, normal.cross_product(right), normal],
+        half_extents: Vec3::new(size.x / 2.0, size.y / 2.0, depth),
+    }
+}
+
+///
+/// Finds where a point in a decal's box lands on its texture, or `None` if the point is outside the box.
+///
+pub(crate) fn decal_coords(local: Vec3) -> Option<Vec2> {
+    let inside = |value: f64| (-0.5..=0.5).contains(&value);
+    (inside(local.x) && inside(local.y) && inside(local.z))
+        .then(|| Vec2::new(local.x + 0.5, 0.5 - local.y))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::{framebuffer::Colour, vec::vec3::Z_AXIS};
+
+    #[test]
+    fn surface_decals() {
+        let texture = Texture::solid(Colour::from_hex(0xffffff));
+
+        // A poster on a wall facing the camera keeps its top up and its left on the left
+        let wall = Decal::on_surface(
+            texture.clone(),
+            Vec3::new(1.0, 2.0, 3.0),
+            Z_AXIS,
+            Vec2::new(2.0, 1.0),
+            0.25,
+        );
+        let lands_at = |decal: &Decal, point: Vec3, expected: Vec2| {
+            let coords = decal_coords(decal.to_local().unwrap().transform(point)).unwrap();
+            assert!((coords - expected).dot(coords - expected) < 1e-12);
+        };
+        lands_at(&wall, Vec3::new(1.0, 2.0, 3.0), Vec2::new(0.5, 0.5));
+        lands_at(&wall, Vec3::new(0.01, 2.49, 3.0), Vec2::new(0.005, 0.01));
+        lands_at(&wall, Vec3::new(1.99, 1.51, 3.2), Vec2::new(0.995, 0.99));
+        let to_local = wall.to_local().unwrap();
+        assert_eq!(
+            decal_coords(to_local.transform(Vec3::new(1.0, 2.0, 3.3))),
+            None
+        );
+        assert_eq!(
+            decal_coords(to_local.transform(Vec3::new(2.1, 2.0, 3.0))),
+            None
+        );
+
+        // On the floor, the top of the texture points along the Z axis
+        let floor = Decal::on_surface(
+            texture,
+            Vec3::new(0.0, 0.0, 0.0),
+            Y_AXIS,
+            Vec2::new(1.0, 1.0),
+            0.1,
+        );
+        lands_at(&floor, Vec3::new(0.0, 0.0, 0.49), Vec2::new(0.5, 0.01));
+        assert!(floor.bounds().contains(Vec3::new(0.5, 0.1, 0.5)));
+    }
+}
@@ -0,0 +1,125 @@
+use std::f64::consts::PI;
+
+use crate::vec::vec3::Vec3;
+
+///
+/// Moves an entity's vertices every time it's drawn, e.g. to wave a flag, make a creature breathe or ripple the
+/// ground, without changing its mesh. Modifiers are given each vertex's position and normal in the object's own space,
+/// after any skinning, along with the world's time, and return where the vertex should be drawn.
+///
+/// Modifiers only move what's drawn - collisions and raycasts still use the undeformed mesh.
+///
+/// Closures taking the same arguments can be used as modifiers, e.g.
+///
+/// ```
+/// # use megavertex::{Object, Texture, Vec3, World};
+/// # let mut world = World::new();
+/// # let object = Object::new(vec![], vec![], vec![], vec![], Texture::new(1, 1, vec![0xffffff]));
+/// # let id = world.add_object(object, Vec3::new(0.0, 0.0, 0.0));
+/// // Wobble like jelly, more at the top than the bottom
+/// world.set_vertex_modifier(id, 0.2, |position: Vec3, _normal, time: f64| {
+///     let sway = (time * 6.0 + position.y).sin() * 0.2 * position.y.max(0.0);
+///     position + Vec3::new(sway, 0.0, 0.0)
+/// });
+/// ```
+///
+pub trait VertexModifier: Send + Sync {
+    fn modify(&self, position: Vec3, normal: Vec3, time: f64) -> Vec3;
+}
+
+impl<F> VertexModifier for F
+where
+    F: Fn(Vec3, Vec3, f64) -> Vec3 + Send + Sync,
+{
+    fn modify(&self, position: Vec3, normal: Vec3, time: f64) -> Vec3 {
+        self(position, normal, time)
+    }
+}
+
+///
+/// Swells and shrinks an object along its normals, `amount` units each way, `rate` times per second.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Breathe {
+    pub amount: f64,
+    pub rate: f64,
+}
+
+impl VertexModifier for Breathe {
+    fn modify(&self, position: Vec3, normal: Vec3, time: f64) -> Vec3 {
+        position + normal * (self.amount * (2.0 * PI * self.rate * time).sin())
+    }
+}
+
+///
+/// Pushes vertices in and out along their normals with a sine wave travelling across the object, like a flag in the
+/// wind or a ripple through the ground.
+/// - `direction` is the direction the wave travels in, in the object's own space
+/// - `amplitude` is how far vertices move at the wave's peaks
+/// - `wavelength` is the distance between peaks
+/// - `speed` is how far the peaks move per second
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ripple {
+    pub direction: Vec3,
+    pub amplitude: f64,
+    pub wavelength: f64,
+    pub speed: f64,
+}
+
+impl VertexModifier for Ripple {
+    fn modify(&self, position: Vec3, normal: Vec3, time: f64) -> Vec3 {
+        let length = self.direction.length();
+        if length == 0.0 || self.wavelength == 0.0 {
+            return position;
+        }
+        let distance = position.dot(self.direction / length);
+        let phase = 2.0 * PI * (distance - self.speed * time) / self.wavelength;
+        position + normal * (self.amplitude * phase.sin())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::vec::vec3::{ORIGIN, Y_AXIS as UP};
+
+    #[test]
+    fn closures() {
+        let lift = |position: Vec3, normal: Vec3, time: f64| position + normal * time;
+        assert_eq!(
+            lift.modify(Vec3::new(1.0, 0.0, 0.0), UP, 2.0),
+            Vec3::new(1.0, 2.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn presets() {
+        let breathe = Breathe {
+            amount: 0.5,
+            rate: 1.0,
+        };
+        assert_eq!(breathe.modify(ORIGIN, UP, 0.0), ORIGIN);
+        assert!((breathe.modify(ORIGIN, UP, 0.25).y - 0.5).abs() < 1e-9);
+
+        // A quarter of a wavelength along, the wave is at its peak, and it moves along with time
+        let ripple = Ripple {
+            direction: Vec3::new(2.0, 0.0, 0.0),
+            amplitude: 0.1,
+            wavelength: 4.0,
+            speed: 1.0,
+        };
+        assert!((ripple.modify(Vec3::new(1.0, 0.0, 0.0), UP, 0.0).y - 0.1).abs() < 1e-9);
+        assert!((ripple.modify(Vec3::new(2.0, 0.0, 0.0), UP, 1.0).y - 0.1).abs() < 1e-9);
+
+        // Without a direction there's no wave
+        let still = Ripple {
+            direction: ORIGIN,
+            ..ripple
+        };
+        assert_eq!(
+            still.modify(Vec3::new(1.0, 0.0, 0.0), UP, 0.0),
+            Vec3::new(1.0, 0.0, 0.0)
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::float::Float;
+use crate::vec::vec3::Vec3;
+
+// How many samples are gathered on each of the two rings around a blurred pixel, the inner one halfway out
+const INNER_TAPS: usize = 6;
+const OUTER_TAPS: usize = 12;
+
+// Pixels blurred less than this are left sharp, as they'd only gather themselves
+const MIN_RADIUS: f64 = 0.5;
+
+///
+/// Settings for blurring what's out of focus, as a camera lens does, using how far away each pixel is in the depth
+/// buffer.
+/// - `focal_distance` is how far from the camera things are sharpest
+/// - `focus_range` is how far either side of the focal distance things stay completely sharp
+/// - `aperture` is how many pixels things are blurred across at most, which things far beyond the focus approach.
+///   Wider apertures blur more, and start blurring more sharply away from the focus
+///
+/// Each out of focus pixel gathers a ring of its neighbours, so large apertures look grainy rather than costing more.
+/// Sharp pixels in front aren't gathered into a blurred background, so in-focus edges don't bleed outwards.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DepthOfField {
+    pub focal_distance: f64,
+    pub focus_range: f64,
+    pub aperture: f64,
+}
+
+impl Default for DepthOfField {
+    fn default() -> Self {
+        DepthOfField {
+            focal_distance: 10.0,
+            focus_range: 2.0,
+            aperture: 4.0,
+        }
+    }
+}
+
+impl DepthOfField {
+    ///
+    /// Returns how many pixels something `distance` away from the camera is blurred across.
+    ///
+    pub fn blur_radius(&self, distance: f64) -> f64 {
+        let out_of_focus = ((distance - self.focal_distance).abs() - self.focus_range).max(0.0);
+        (self.aperture * out_of_focus / distance.max(f64::EPSILON))
+            .clamp(0.0, self.aperture.max(0.0))
+    }
+
+    ///
+    /// Blurs a frame of colours, `width` pixels wide, each of which is the given distance from the camera, returning
+    /// the blurred frame.
+    ///
+    pub(crate) fn blur(&self, colours: &[Vec3], distances: &[f64], width: usize) -> Vec<Vec3> {
+        let height = colours.len() / width.max(1);
+        let radii: Vec<f64> = distances
+            .iter()
+            .map(|distance| self.blur_radius(*distance))
+            .collect();
+        let taps = taps();
+
+        let mut blurred = colours.to_vec();
+        for (i, colour) in blurred.iter_mut().enumerate() {
+            let radius = radii[i];
+            if radius < MIN_RADIUS {
+                continue;
+            }
+            let (x, y) = ((i % width) as f64, (i / width) as f64);
+            let mut sum = colours[i];
+            let mut count = 1.0;
+            for &(dx, dy, reach) in &taps {
+                let sx = (x + dx * radius).round().clamp(0.0, (width - 1) as f64) as usize;
+                let sy = (y + dy * radius).round().clamp(0.0, (height - 1) as f64) as usize;
+                let j = sy * width + sx;
+                // Sharper neighbours in front of the pixel are left out, or they'd bleed over it
+                if distances[j] < distances[i] && radii[j] < reach * radius {
+                    continue;
+                }
+                sum = sum + colours[j];
+                count += 1.0;
+            }
+            *colour = sum / count;
+        }
+        blurred
+    }
+}
+
+// The offsets gathered around a pixel blurred across a radius of 1, and how far out each is
+fn taps() -> Vec<(f64, f64, f64)> {
+    let ring = |count: usize, reach: f64| {
+        (0..count).map(move |tap| {
+            let angle = core::f64::consts::TAU * tap as f64 / count as f64;
+            let (sin, cos) = angle.sin_cos();
+            (cos * reach, sin * reach, reach)
+        })
+    };
+    ring(INNER_TAPS, 0.5).chain(ring(OUTER_TAPS, 1.0)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn blur_radius() {
+        let dof = DepthOfField {
+            focal_distance: 10.0,
+            focus_range: 2.0,
+            aperture: 4.0,
+        };
+        assert_eq!(dof.blur_radius(10.0), 0.0);
+        assert_eq!(dof.blur_radius(12.0), 0.0);
+        assert_eq!(dof.blur_radius(16.0), 1.0);
+        assert_eq!(dof.blur_radius(1.0), 4.0);
+        assert!(dof.blur_radius(1e6) < 4.0 && dof.blur_radius(1e6) > 3.9);
+    }
+
+    #[test]
+    fn blurring() {
+        let dof = DepthOfField {
+            focal_distance: 5.0,
+            focus_range: 1.0,
+            aperture: 2.0,
+        };
+        // A white stripe down the middle of a black frame, in focus in the top half and far away in the bottom half
+        let (width, height) = (16, 8);
+        let colours: Vec<Vec3> = (0..width * height)
+            .map(|i| match i % width {
+                7 | 8 => Vec3::new(1.0, 1.0, 1.0),
+                _ => Vec3::new(0.0, 0.0, 0.0),
+            })
+            .collect();
+        let far = |i: usize| i / width >= height / 2;
+        let distances: Vec<f64> = (0..width * height)
+            .map(|i| if far(i) { 1000.0 } else { 5.0 })
+            .collect();
+        let blurred = dof.blur(&colours, &distances, width);
+
+        // The sharp half is untouched, and the far half is spread out without losing its overall brightness
+        assert_eq!(blurred[..width * height / 2], colours[..width * height / 2]);
+        let row = |y: usize| &blurred[y * width..(y + 1) * width];
+        let far_row = row(height - 1);
+        assert!(far_row[8].x < 1.0 && far_row[6].x > 0.0 && far_row[9].x > 0.0);
+        let total: f64 = far_row.iter().map(|colour| colour.x).sum();
+        assert!((total - 2.0).abs() < 0.5, "{}", total);
+
+        // A sharp stripe in front of a blurred background isn't smeared across it
+        let distances: Vec<f64> = (0..width * height)
+            .map(|i| {
+                if (7..=8).contains(&(i % width)) {
+                    5.0
+                } else {
+                    1000.0
+                }
+            })
+            .collect();
+        let blurred = dof.blur(&colours, &distances, width);
+        assert_eq!(blurred, colours);
+    }
+}
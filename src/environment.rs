@@ -0,0 +1,86 @@
+use alloc::string::String;
+#[cfg(feature = "std")]
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{object::Texture, vec::vec3::Vec3};
+
+///
+/// Fades objects into `colour` with distance from the camera. Objects closer than `start` are unaffected, and
+/// anything beyond `end` is hidden completely. Colours are RGB values between 0 and 1.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fog {
+    pub colour: Vec3,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl Fog {
+    ///
+    /// Returns how much a point `distance` away from the camera is covered by fog, between 0 and 1.
+    ///
+    pub fn amount(&self, distance: f64) -> f64 {
+        if self.end <= self.start {
+            return if distance >= self.end { 1.0 } else { 0.0 };
+        }
+        ((distance - self.start) / (self.end - self.start)).clamp(0.0, 1.0)
+    }
+}
+
+///
+/// The surroundings a world is drawn in.
+/// - `skybox` is an equirectangular image drawn behind everything, wrapping around horizontally. `skybox_source` is the
+///   file it was loaded from, if any, so it can be saved in a scene
+/// - `ambient` multiplies the colour of everything drawn, as an RGB value between 0 and 1, or above 1 to overexpose
+///   things when drawing in HDR
+/// - `fog` fades distant objects out, and fills the background when there's no skybox
+///
+#[derive(Clone)]
+pub struct Environment {
+    pub skybox: Option<Texture>,
+    pub skybox_source: Option<String>,
+    pub ambient: Vec3,
+    pub fog: Option<Fog>,
+}
+
+impl Environment {
+    ///
+    /// Loads a PNG to use as the skybox.
+    ///
+    #[cfg(feature = "std")]
+    pub fn load_skybox(&mut self, path: &str) -> Result<(), io::Error> {
+        self.skybox = Some(Texture::load_from(path)?);
+        self.skybox_source = Some(String::from(path));
+        Ok(())
+    }
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Environment {
+            skybox: None,
+            skybox_source: None,
+            ambient: Vec3::new(1.0, 1.0, 1.0),
+            fog: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fog_amount() {
+        let fog = Fog {
+            colour: Vec3::new(0.5, 0.5, 0.5),
+            start: 10.0,
+            end: 20.0,
+        };
+        assert_eq!(fog.amount(5.0), 0.0);
+        assert_eq!(fog.amount(15.0), 0.5);
+        assert_eq!(fog.amount(30.0), 1.0);
+    }
+}
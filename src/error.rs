@@ -0,0 +1,102 @@
+use alloc::boxed::Box;
+use core::fmt::Display;
+#[cfg(feature = "std")]
+use std::io;
+
+use crate::object::ModelLoadError;
+#[cfg(feature = "window")]
+use crate::recording::RecordingError;
+
+///
+/// Anything that can go wrong loading, rendering or running an app, so callers can handle every failure in one place.
+/// The more specific errors returned by loaders like `Object::from_obj` convert into this with `?`.
+/// - `ModelError` is a model which couldn't be read or made sense of
+/// - `TextureError` is an image which couldn't be read, decoded or written
+/// - `FontError` is a font which couldn't be parsed
+/// - `WindowError` is a window which couldn't be opened or shown a frame
+/// - `RenderError` is a frame which couldn't be handed on to wherever it's shown, like a terminal or canvas
+/// - `RecordingError` is a recording of a run which couldn't be read or saved
+/// - `AppError` is whatever an app's `setup` failed with
+///
+#[derive(Debug)]
+pub enum Error {
+    ModelError(ModelLoadError),
+    #[cfg(feature = "std")]
+    TextureError(io::Error),
+    FontError(&'static str),
+    WindowError(Box<dyn core::error::Error>),
+    RenderError(Box<dyn core::error::Error>),
+    #[cfg(feature = "window")]
+    RecordingError(RecordingError),
+    AppError(Box<dyn core::error::Error>),
+}
+
+impl Error {
+    ///
+    /// Wraps an error from a window library, for use with `map_err`.
+    ///
+    pub fn window(error: impl Into<Box<dyn core::error::Error>>) -> Error {
+        Error::WindowError(error.into())
+    }
+
+    ///
+    /// Wraps an error from wherever frames are being shown, for use with `map_err`.
+    ///
+    pub fn render(error: impl Into<Box<dyn core::error::Error>>) -> Error {
+        Error::RenderError(error.into())
+    }
+}
+
+impl From<ModelLoadError> for Error {
+    fn from(error: ModelLoadError) -> Self {
+        Error::ModelError(error)
+    }
+}
+#[cfg(feature = "window")]
+impl From<RecordingError> for Error {
+    fn from(error: RecordingError) -> Self {
+        Error::RecordingError(error)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::ModelError(error) => write!(f, "{}", error),
+            #[cfg(feature = "std")]
+            Error::TextureError(error) => write!(f, "failed to access texture: {}", error),
+            Error::FontError(error) => write!(f, "failed to parse font: {}", error),
+            Error::WindowError(error) => write!(f, "window failed: {}", error),
+            Error::RenderError(error) => write!(f, "failed to present frame: {}", error),
+            #[cfg(feature = "window")]
+            Error::RecordingError(error) => write!(f, "{}", error),
+            Error::AppError(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn wraps_errors() {
+        let error = Error::from(ModelLoadError::MalformedFace { line: 3 });
+        assert!(matches!(
+            error,
+            Error::ModelError(ModelLoadError::MalformedFace { line: 3 })
+        ));
+        assert_eq!(
+            error.to_string(),
+            "face on line 3 needs at least three valid vertices"
+        );
+        assert_eq!(
+            Error::window("no display").to_string(),
+            "window failed: no display"
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! A small C interface to the renderer, so it can be embedded in programs written in other languages. The header for it
+//! is `include/megavertex.h`, which is generated by cbindgen from this file.
+//!
+//! Worlds, cameras and renderers are handed out as pointers by their `_new` functions, and must be given back to the
+//! matching `_free` function once they're finished with. Everything else borrows them, and treats a null pointer as
+//! nothing to work on. Functions which can fail return `false`, and `mv_last_error` describes what went wrong.
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    ptr,
+};
+
+use crate::{
+    camera::Camera, config::RendererConfig, mat4::Mat4, object::Object, renderer::Renderer,
+    vec::vec3::Vec3, world::EntityId, world::World,
+};
+
+thread_local! {
+    // The error from the last call on this thread which failed, kept so the pointer to it stays valid
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error(error: impl ToString) -> bool {
+    // Interior nul bytes can't be represented in a C string, so they're dropped
+    let message = error.to_string().replace('\0', "");
+    LAST_ERROR.with(|last| *last.borrow_mut() = CString::new(message).ok());
+    false
+}
+
+///
+/// A handle to an entity in a world, as given out by `mv_world_load_obj`. It stays valid until the entity is removed.
+///
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Entity {
+    pub index: usize,
+    pub generation: u32,
+}
+
+impl From<EntityId> for Entity {
+    fn from(id: EntityId) -> Self {
+        let (index, generation) = id.to_parts();
+        Entity { index, generation }
+    }
+}
+
+impl From<Entity> for EntityId {
+    fn from(entity: Entity) -> Self {
+        EntityId::from_parts(entity.index, entity.generation)
+    }
+}
+
+///
+/// Returns a description of the last error on this thread, or null if nothing has failed. The string belongs to the
+/// library, and is only valid until the next call which fails.
+///
+#[no_mangle]
+pub extern "C" fn mv_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |error| error.as_ptr())
+    })
+}
+
+///
+/// Creates an empty world.
+///
+#[no_mangle]
+pub extern "C" fn mv_world_new() -> Box<World> {
+    Box::new(World::new())
+}
+
+#[no_mangle]
+pub extern "C" fn mv_world_free(world: Option<Box<World>>) {
+    drop(world);
+}
+
+///
+/// Loads a model as with `Object::from_obj`, where `name` is the path without an extension, and adds it to the world
+/// at the given position. The new entity is written to `entity`.
+///
+/// # Safety
+///
+/// `name` must be a nul terminated string, and `entity` must be null or point to somewhere an `MvEntity` can be
+/// written.
+///
+#[no_mangle]
+pub unsafe extern "C" fn mv_world_load_obj(
+    world: Option<&mut World>,
+    name: *const c_char,
+    x: f64,
+    y: f64,
+    z: f64,
+    entity: *mut Entity,
+) -> bool {
+    let Some(world) = world else {
+        return set_error("no world given");
+    };
+    if name.is_null() {
+        return set_error("no model name given");
+    }
+    let Ok(name) = CStr::from_ptr(name).to_str() else {
+        return set_error("model names must be UTF-8");
+    };
+    match Object::from_obj(name) {
+        Ok(object) => {
+            let id = world.add_object(object, Vec3::new(x, y, z));
+            if let Some(entity) = entity.as_mut() {
+                *entity = id.into();
+            }
+            true
+        }
+        Err(error) => set_error(error),
+    }
+}
+
+///
+/// Removes an entity from the world, returning false if it had already been removed.
+///
+#[no_mangle]
+pub extern "C" fn mv_world_remove(world: Option<&mut World>, entity: Entity) -> bool {
+    let Some(world) = world else {
+        return set_error("no world given");
+    };
+    if world.remove(entity.into()).is_none() {
+        return set_error("the entity doesn't exist");
+    }
+    true
+}
+
+///
+/// Sets where an entity is and how it's turned and scaled, from 16 numbers making up a matrix row by row.
+///
+/// # Safety
+///
+/// `matrix` must point to 16 `double`s.
+///
+#[no_mangle]
+pub unsafe extern "C" fn mv_world_set_transform(
+    world: Option<&mut World>,
+    entity: Entity,
+    matrix: *const f64,
+) -> bool {
+    let Some(world) = world else {
+        return set_error("no world given");
+    };
+    if matrix.is_null() {
+        return set_error("no matrix given");
+    }
+    let id = entity.into();
+    if !world.contains(id) {
+        return set_error("the entity doesn't exist");
+    }
+    let values = std::slice::from_raw_parts(matrix, 16);
+    let mut m = [[0.0; 4]; 4];
+    for (row, values) in m.iter_mut().zip(values.chunks_exact(4)) {
+        row.copy_from_slice(values);
+    }
+    world.set_base_transform(id, Mat4 { m });
+    true
+}
+
+///
+/// Moves the world on by `elapsed` seconds, advancing its physics and updating it, as `Headless` does each frame.
+///
+#[no_mangle]
+pub extern "C" fn mv_world_advance(world: Option<&mut World>, elapsed: f64) {
+    if let Some(world) = world {
+        world.advance_physics(elapsed);
+        world.update(elapsed);
+    }
+}
+
+///
+/// Creates a camera at the given position, looking down the negative Z axis.
+///
+#[no_mangle]
+pub extern "C" fn mv_camera_new(x: f64, y: f64, z: f64) -> Box<Camera> {
+    Box::new(Camera::new(Vec3::new(x, y, z)))
+}
+
+#[no_mangle]
+pub extern "C" fn mv_camera_free(camera: Option<Box<Camera>>) {
+    drop(camera);
+}
+
+#[no_mangle]
+pub extern "C" fn mv_camera_set_position(camera: Option<&mut Camera>, x: f64, y: f64, z: f64) {
+    if let Some(camera) = camera {
+        camera.set_position(Vec3::new(x, y, z));
+    }
+}
+
+///
+/// Turns the camera to the given pitch and yaw, in radians.
+///
+#[no_mangle]
+pub extern "C" fn mv_camera_set_rotation(camera: Option<&mut Camera>, pitch: f64, yaw: f64) {
+    if let Some(camera) = camera {
+        camera.set_rotation(Vec3::new(pitch, yaw, 0.0));
+    }
+}
+
+///
+/// Creates a renderer drawing images of the given size, with the default settings.
+///
+#[no_mangle]
+pub extern "C" fn mv_renderer_new(width: usize, height: usize) -> Box<Renderer> {
+    Box::new(Renderer::new(RendererConfig::new(width, height)))
+}
+
+#[no_mangle]
+pub extern "C" fn mv_renderer_free(renderer: Option<Box<Renderer>>) {
+    drop(renderer);
+}
+
+///
+/// Clears the renderer's image and draws the world into it from the camera.
+///
+#[no_mangle]
+pub extern "C" fn mv_render_frame(
+    renderer: Option<&mut Renderer>,
+    world: Option<&World>,
+    camera: Option<&Camera>,
+) -> bool {
+    let (Some(renderer), Some(world), Some(camera)) = (renderer, world, camera) else {
+        return set_error("a renderer, world and camera are all needed to render a frame");
+    };
+    renderer.clear();
+    camera.render_world(renderer, world);
+    true
+}
+
+///
+/// Returns the renderer's image, as `width * height` pixels packed as 0RGB, row by row from the top left. The pointer
+/// is valid until the next frame is rendered.
+///
+#[no_mangle]
+pub extern "C" fn mv_renderer_buffer(renderer: Option<&Renderer>) -> *const u32 {
+    renderer.map_or(ptr::null(), |renderer| renderer.buffer.as_ptr())
+}
+
+#[no_mangle]
+pub extern "C" fn mv_renderer_width(renderer: Option<&Renderer>) -> usize {
+    renderer.map_or(0, |renderer| renderer.width())
+}
+
+#[no_mangle]
+pub extern "C" fn mv_renderer_height(renderer: Option<&Renderer>) -> usize {
+    renderer.map_or(0, |renderer| renderer.height())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn renders_through_the_c_interface() {
+        let mut world = mv_world_new();
+        let mut camera = mv_camera_new(0.0, 0.0, 0.0);
+        let mut renderer = mv_renderer_new(60, 40);
+
+        let mut cube = Entity {
+            index: 0,
+            generation: 0,
+        };
+        let name = CString::new("./resources/cube").unwrap();
+        assert!(unsafe {
+            mv_world_load_obj(Some(&mut world), name.as_ptr(), 0.0, 0.0, 6.0, &mut cube)
+        });
+        let missing = CString::new("./resources/missing").unwrap();
+        assert!(!unsafe {
+            mv_world_load_obj(
+                Some(&mut world),
+                missing.as_ptr(),
+                0.0,
+                0.0,
+                0.0,
+                ptr::null_mut(),
+            )
+        });
+        assert!(!mv_last_error().is_null());
+
+        let drawn = |renderer: &Renderer| {
+            let pixels = unsafe {
+                std::slice::from_raw_parts(
+                    mv_renderer_buffer(Some(renderer)),
+                    mv_renderer_width(Some(renderer)) * mv_renderer_height(Some(renderer)),
+                )
+            };
+            pixels.iter().filter(|pixel| **pixel != 0).count()
+        };
+        assert!(mv_render_frame(
+            Some(&mut renderer),
+            Some(&world),
+            Some(&camera)
+        ));
+        assert!(drawn(&renderer) > 0);
+
+        // Moving the cube behind the camera leaves nothing to draw
+        #[rustfmt::skip]
+        let behind = [
+            1.0, 0.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, -6.0,
+            0.0, 0.0, 0.0, 1.0,
+        ];
+        assert!(unsafe { mv_world_set_transform(Some(&mut world), cube, behind.as_ptr()) });
+        mv_world_advance(Some(&mut world), 1.0 / 60.0);
+        mv_camera_set_position(Some(&mut camera), 0.0, 0.0, 0.0);
+        assert!(mv_render_frame(
+            Some(&mut renderer),
+            Some(&world),
+            Some(&camera)
+        ));
+        assert_eq!(drawn(&renderer), 0);
+
+        assert!(mv_world_remove(Some(&mut world), cube));
+        assert!(!mv_world_remove(Some(&mut world), cube));
+        assert!(!mv_render_frame(None, Some(&world), Some(&camera)));
+        mv_renderer_free(Some(renderer));
+        mv_camera_free(Some(camera));
+        mv_world_free(Some(world));
+    }
+}
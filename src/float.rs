@@ -0,0 +1,71 @@
+///
+/// The floating point functions which `f64` only has with the standard library, filled in from libm for `no_std`
+/// builds. Modules which call any of them bring this into scope when the `std` feature is off. Tests link the standard
+/// library, which brings the methods back, so they don't need it either. Building the library alongside the benches
+/// links it through criterion's dependencies too, so the trait and its imports are allowed to go unused.
+///
+#[allow(dead_code)]
+pub(crate) trait Float {
+    fn sqrt(self) -> f64;
+    fn powf(self, n: f64) -> f64;
+    fn floor(self) -> f64;
+    fn ceil(self) -> f64;
+    fn round(self) -> f64;
+    fn sin(self) -> f64;
+    fn cos(self) -> f64;
+    fn tan(self) -> f64;
+    fn sin_cos(self) -> (f64, f64);
+    fn asin(self) -> f64;
+    fn acos(self) -> f64;
+    fn atan2(self, other: f64) -> f64;
+}
+
+impl Float for f64 {
+    fn sqrt(self) -> f64 {
+        libm::sqrt(self)
+    }
+
+    fn powf(self, n: f64) -> f64 {
+        libm::pow(self, n)
+    }
+
+    fn floor(self) -> f64 {
+        libm::floor(self)
+    }
+
+    fn ceil(self) -> f64 {
+        libm::ceil(self)
+    }
+
+    fn round(self) -> f64 {
+        libm::round(self)
+    }
+
+    fn sin(self) -> f64 {
+        libm::sin(self)
+    }
+
+    fn cos(self) -> f64 {
+        libm::cos(self)
+    }
+
+    fn tan(self) -> f64 {
+        libm::tan(self)
+    }
+
+    fn sin_cos(self) -> (f64, f64) {
+        libm::sincos(self)
+    }
+
+    fn asin(self) -> f64 {
+        libm::asin(self)
+    }
+
+    fn acos(self) -> f64 {
+        libm::acos(self)
+    }
+
+    fn atan2(self, other: f64) -> f64 {
+        libm::atan2(self, other)
+    }
+}
@@ -0,0 +1,294 @@
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::float::Float;
+use crate::{object::Texture, renderer::Renderer, vec::vec3::Vec3};
+
+///
+/// A colour with 8 bits for each of red, green, blue and alpha. Frames and textures are stored as 0RGB, so alpha is
+/// dropped when a colour is drawn - it's kept for colours passed to and from other libraries.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Colour {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    #[serde(default = "opaque")]
+    pub a: u8,
+}
+
+// Colours written without an alpha, like those in config files, are fully opaque
+fn opaque() -> u8 {
+    u8::MAX
+}
+
+impl Colour {
+    pub const BLACK: Colour = Colour::new(0, 0, 0);
+    pub const WHITE: Colour = Colour::new(255, 255, 255);
+
+    ///
+    /// Creates a fully opaque colour.
+    ///
+    pub const fn new(r: u8, g: u8, b: u8) -> Colour {
+        Colour {
+            r,
+            g,
+            b,
+            a: u8::MAX,
+        }
+    }
+
+    pub const fn with_alpha(self, a: u8) -> Colour {
+        Colour { a, ..self }
+    }
+
+    ///
+    /// Creates a fully opaque colour from a hex code written as in CSS, e.g. `0xff8000` for orange.
+    ///
+    pub const fn from_hex(hex: u32) -> Colour {
+        let [_, r, g, b] = hex.to_be_bytes();
+        Colour::new(r, g, b)
+    }
+
+    ///
+    /// Packs the colour as 0RGB, the way frames and textures hold their pixels, dropping alpha.
+    ///
+    pub const fn to_0rgb(self) -> u32 {
+        u32::from_be_bytes([0, self.r, self.g, self.b])
+    }
+
+    ///
+    /// Blends between this colour and another, where `t` is between 0 and 1.
+    ///
+    pub fn lerp(self, to: Colour, t: f64) -> Colour {
+        let channel =
+            |from: u8, to: u8| (from as f64 + (to as f64 - from as f64) * t).round() as u8;
+        Colour {
+            r: channel(self.r, to.r),
+            g: channel(self.g, to.g),
+            b: channel(self.b, to.b),
+            a: channel(self.a, to.a),
+        }
+    }
+
+    ///
+    /// Multiplies each channel by the matching component of an RGB value between 0 and 1, e.g. to light or shade a
+    /// colour. Alpha is left alone.
+    ///
+    pub fn multiply(self, by: Vec3) -> Colour {
+        let channel = |value: u8, scale: f64| (value as f64 * scale.clamp(0.0, 1.0)).round() as u8;
+        Colour {
+            r: channel(self.r, by.x),
+            g: channel(self.g, by.y),
+            b: channel(self.b, by.z),
+            a: self.a,
+        }
+    }
+
+    ///
+    /// Adds two colours together, channel by channel, stopping at full brightness.
+    ///
+    pub fn saturating_add(self, other: Colour) -> Colour {
+        Colour {
+            r: self.r.saturating_add(other.r),
+            g: self.g.saturating_add(other.g),
+            b: self.b.saturating_add(other.b),
+            a: self.a.saturating_add(other.a),
+        }
+    }
+
+    ///
+    /// Returns the colour as red, green, blue and alpha bytes.
+    ///
+    pub fn to_rgba8(self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    ///
+    /// Returns the colour as blue, green, red and alpha bytes.
+    ///
+    pub fn to_bgra8(self) -> [u8; 4] {
+        [self.b, self.g, self.r, self.a]
+    }
+}
+
+impl Default for Colour {
+    fn default() -> Self {
+        Colour::BLACK
+    }
+}
+
+///
+/// Reads a pixel packed as 0RGB, the way frames and textures hold them, with the top byte unused.
+///
+impl From<u32> for Colour {
+    fn from(pixel: u32) -> Colour {
+        Colour::from_hex(pixel)
+    }
+}
+
+///
+/// Converts an RGB value between 0 and 1, as lights and fog are given in, to a colour.
+///
+impl From<Vec3> for Colour {
+    fn from(colour: Vec3) -> Colour {
+        Colour::WHITE.multiply(colour)
+    }
+}
+
+impl From<Colour> for u32 {
+    fn from(colour: Colour) -> u32 {
+        colour.to_0rgb()
+    }
+}
+
+///
+/// An image made up of rows of pixels from the top left, such as a rendered frame or a texture, which can be read out
+/// with a well defined channel order for encoders and GUI toolkits. The pixels are packed as 0RGB internally, but that
+/// can change, so it's better to read them through `colours` or one of the byte orders here than from `buffer`.
+///
+pub trait Framebuffer {
+    fn width(&self) -> usize;
+
+    fn height(&self) -> usize;
+
+    ///
+    /// Returns the pixels as they're stored. Frames are packed as 0RGB, but textures are packed however they were
+    /// stored by `Texture::convert`, so read them through `colours` instead.
+    ///
+    fn buffer(&self) -> &[u32];
+
+    ///
+    /// Iterates over the colour of each pixel, row by row from the top left.
+    ///
+    fn colours(&self) -> impl Iterator<Item = Colour> + '_ {
+        self.buffer().iter().map(|pixel| Colour::from(*pixel))
+    }
+
+    ///
+    /// Returns four bytes for each pixel in the order red, green, blue, alpha. Alpha is always fully opaque.
+    ///
+    fn pixels_rgba8(&self) -> Vec<u8> {
+        self.colours().flat_map(Colour::to_rgba8).collect()
+    }
+
+    ///
+    /// Returns four bytes for each pixel in the order blue, green, red, alpha. Alpha is always fully opaque.
+    ///
+    fn pixels_bgra8(&self) -> Vec<u8> {
+        self.colours().flat_map(Colour::to_bgra8).collect()
+    }
+}
+
+impl Framebuffer for Renderer {
+    fn width(&self) -> usize {
+        self.width()
+    }
+
+    fn height(&self) -> usize {
+        self.height()
+    }
+
+    fn buffer(&self) -> &[u32] {
+        &self.buffer
+    }
+}
+
+impl Framebuffer for Texture {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn buffer(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    // Converted textures pack and reorder their texels, so they're read one at a time
+    fn colours(&self) -> impl Iterator<Item = Colour> + '_ {
+        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| self.texel(x, y)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+
+    use super::*;
+    use crate::{
+        config::RendererConfig,
+        object::{TextureFormat, TextureLayout},
+    };
+
+    #[test]
+    fn channel_orders() {
+        let colour = Colour::from(0x00112233);
+        assert_eq!(colour, Colour::new(0x11, 0x22, 0x33));
+        assert_eq!(u32::from(colour), 0x00112233);
+
+        let texture = Texture::new(2, 1, vec![0x00ff8000, 0x00010203]);
+        assert_eq!(
+            texture.colours().collect::<Vec<_>>(),
+            [Colour::new(255, 128, 0), Colour::new(1, 2, 3)]
+        );
+        assert_eq!(texture.pixels_rgba8(), [255, 128, 0, 255, 1, 2, 3, 255]);
+        assert_eq!(texture.pixels_bgra8(), [0, 128, 255, 255, 3, 2, 1, 255]);
+
+        let renderer = Renderer::new(RendererConfig::new(3, 2));
+        assert_eq!(renderer.pixels_rgba8().len(), 3 * 2 * 4);
+    }
+
+    #[test]
+    fn converted_textures_read_the_same() {
+        // Colours which survive being packed into 5 and 6 bits, on a texture which doesn't fill its tiles
+        let widen = |value: u32, bits: u32| (value << (8 - bits)) | (value >> (2 * bits - 8));
+        let pixels = (0..9 * 3)
+            .map(|i| widen(i, 5) << 16 | widen(i * 2, 6) << 8 | widen(31 - i, 5))
+            .collect();
+        let texture = Texture::new(9, 3, pixels);
+        for (format, layout) in [
+            (TextureFormat::Rgb8, TextureLayout::Tiled),
+            (TextureFormat::Rgb565, TextureLayout::Linear),
+            (TextureFormat::Rgb565, TextureLayout::Tiled),
+        ] {
+            let converted = texture.convert(format, layout);
+            assert_eq!(converted.pixels_rgba8(), texture.pixels_rgba8());
+            assert_eq!(converted.pixels_bgra8(), texture.pixels_bgra8());
+        }
+    }
+
+    #[test]
+    fn colour_maths() {
+        let black = Colour::BLACK;
+        assert_eq!(
+            black.lerp(Colour::from_hex(0xff8040), 0.5),
+            Colour::from_hex(0x804020)
+        );
+        let colour = Colour::from_hex(0x123456);
+        assert_eq!(colour.lerp(Colour::from_hex(0xabcdef), 0.0), colour);
+        assert_eq!(
+            colour.lerp(Colour::from_hex(0xabcdef), 1.0),
+            Colour::from_hex(0xabcdef)
+        );
+        assert_eq!(colour.with_alpha(0).lerp(colour, 0.5).a, 128);
+
+        // Multiplying clamps each channel, and leaves alpha alone
+        let tinted = Colour::WHITE
+            .with_alpha(10)
+            .multiply(Vec3::new(0.5, 2.0, -1.0));
+        assert_eq!(tinted, Colour::new(128, 255, 0).with_alpha(10));
+        assert_eq!(
+            Colour::from(Vec3::new(1.0, 0.0, 0.5)),
+            Colour::from_hex(0xff0080)
+        );
+
+        let sum = Colour::from_hex(0x80f010).saturating_add(Colour::from_hex(0x902001));
+        assert_eq!(sum.to_0rgb(), 0xffff11);
+    }
+}
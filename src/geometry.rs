@@ -0,0 +1,5 @@
+pub mod aabb;
+pub mod frustum;
+pub mod hull;
+pub mod obb;
+pub mod ray;
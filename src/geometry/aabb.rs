@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{geometry::ray::Ray, mat4::Mat4, vec::vec3::Vec3};
+
+///
+/// An axis-aligned bounding box, described by its minimum and maximum corners.
+///
+#[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Aabb {
+        Aabb { min, max }
+    }
+
+    ///
+    /// Computes the smallest box containing every point, or `None` if there are no points.
+    ///
+    pub fn from_points<I: IntoIterator<Item = Vec3>>(points: I) -> Option<Aabb> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Aabb::new(first, first), |aabb, point| aabb.expand(point)))
+    }
+
+    ///
+    /// Returns a box grown to contain `point`.
+    ///
+    pub fn expand(self, point: Vec3) -> Aabb {
+        Aabb {
+            min: Vec3::new(
+                self.min.x.min(point.x),
+                self.min.y.min(point.y),
+                self.min.z.min(point.z),
+            ),
+            max: Vec3::new(
+                self.max.x.max(point.x),
+                self.max.y.max(point.y),
+                self.max.z.max(point.z),
+            ),
+        }
+    }
+
+    ///
+    /// Returns the smallest box containing both boxes.
+    ///
+    pub fn union(self, other: Aabb) -> Aabb {
+        self.expand(other.min).expand(other.max)
+    }
+
+    ///
+    /// Returns a box grown by `margin` in every direction.
+    ///
+    pub fn inflate(self, margin: f64) -> Aabb {
+        let margin = Vec3::new(margin, margin, margin);
+        Aabb::new(self.min - margin, self.max + margin)
+    }
+
+    pub fn centre(&self) -> Vec3 {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn size(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        let size = self.size();
+        2.0 * (size.x * size.y + size.y * size.z + size.z * size.x)
+    }
+
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (min, max) = (self.min, self.max);
+        [
+            Vec3::new(min.x, min.y, min.z),
+            Vec3::new(max.x, min.y, min.z),
+            Vec3::new(min.x, max.y, min.z),
+            Vec3::new(max.x, max.y, min.z),
+            Vec3::new(min.x, min.y, max.z),
+            Vec3::new(max.x, min.y, max.z),
+            Vec3::new(min.x, max.y, max.z),
+            Vec3::new(max.x, max.y, max.z),
+        ]
+    }
+
+    pub fn contains(&self, point: Vec3) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+            && point.z >= self.min.z
+            && point.z <= self.max.z
+    }
+
+    ///
+    /// Returns the distance from a point to the nearest point in the box, which is 0 if the point is inside it.
+    ///
+    pub fn distance(&self, point: Vec3) -> f64 {
+        (point - self.closest_point(point)).length()
+    }
+
+    ///
+    /// Returns the point in the box nearest to a point, which is the point itself if it's inside the box.
+    ///
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        Vec3::new(
+            point.x.clamp(self.min.x, self.max.x),
+            point.y.clamp(self.min.y, self.max.y),
+            point.z.clamp(self.min.z, self.max.z),
+        )
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+            && self.min.z <= other.max.z
+            && self.max.z >= other.min.z
+    }
+
+    ///
+    /// Computes the box containing this box after a transformation.
+    ///
+    pub fn transform(&self, mat: Mat4) -> Aabb {
+        let corners = self.corners().map(|corner| mat.transform(corner));
+        Aabb::from_points(corners).unwrap_or(*self)
+    }
+
+    ///
+    /// Returns the distance along the ray at which it enters the box, using the slab method.
+    /// Rays starting inside the box hit it at a distance of 0.
+    ///
+    pub fn ray_distance(&self, ray: &Ray) -> Option<f64> {
+        let mut t_min: f64 = 0.0;
+        let mut t_max = f64::INFINITY;
+
+        let axes = [
+            (ray.origin.x, ray.direction.x, self.min.x, self.max.x),
+            (ray.origin.y, ray.direction.y, self.min.y, self.max.y),
+            (ray.origin.z, ray.direction.z, self.min.z, self.max.z),
+        ];
+        for (origin, direction, min, max) in axes {
+            if direction.abs() < f64::EPSILON {
+                if origin < min || origin > max {
+                    return None;
+                }
+                continue;
+            }
+            let t1 = (min - origin) / direction;
+            let t2 = (max - origin) / direction;
+            t_min = t_min.max(t1.min(t2));
+            t_max = t_max.min(t1.max(t2));
+            if t_min > t_max {
+                return None;
+            }
+        }
+
+        Some(t_min)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bounds_from_points() {
+        let aabb = Aabb::from_points([
+            Vec3::new(1.0, -2.0, 0.0),
+            Vec3::new(-1.0, 3.0, 2.0),
+            Vec3::new(0.0, 0.0, -4.0),
+        ])
+        .unwrap();
+        assert_eq!(aabb.min, Vec3::new(-1.0, -2.0, -4.0));
+        assert_eq!(aabb.max, Vec3::new(1.0, 3.0, 2.0));
+    }
+
+    #[test]
+    fn intersecting_boxes() {
+        let a = Aabb::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+        let b = Aabb::new(Vec3::new(0.5, 0.5, 0.5), Vec3::new(2.0, 2.0, 2.0));
+        let c = Aabb::new(Vec3::new(1.5, 0.0, 0.0), Vec3::new(2.0, 1.0, 1.0));
+        assert!(a.intersects(&b));
+        assert!(!a.intersects(&c));
+    }
+
+    #[test]
+    fn distance_to_point() {
+        let aabb = Aabb::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+        assert_eq!(aabb.distance(Vec3::new(0.5, 0.5, 0.5)), 0.0);
+        assert_eq!(aabb.distance(Vec3::new(4.0, 5.0, 0.5)), 5.0);
+    }
+
+    #[test]
+    fn ray_hits_box() {
+        let aabb = Aabb::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        let hit = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let miss = Ray::new(Vec3::new(-5.0, 2.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        assert_eq!(aabb.ray_distance(&hit), Some(4.0));
+        assert_eq!(aabb.ray_distance(&miss), None);
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{geometry::aabb::Aabb, mat4::Mat4, vec::vec3::Vec3};
+use alloc::vec::Vec;
+
+///
+/// A plane with a facing direction. Points on the side the normal faces have positive distances.
+///
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Plane {
+    pub normal: Vec3,
+    pub d: f64,
+}
+
+impl Plane {
+    pub fn new(normal: Vec3, d: f64) -> Plane {
+        Plane { normal, d }
+    }
+
+    pub fn distance(&self, point: Vec3) -> f64 {
+        self.normal.dot(point) + self.d
+    }
+
+    ///
+    /// Moves a plane defined in the space a matrix transforms vectors *into* back into the space it transforms them
+    /// from. For example, given a camera's view matrix, this turns a camera space plane into a world space plane.
+    ///
+    pub fn untransform(&self, mat: Mat4) -> Plane {
+        let t = mat.transpose().m;
+        let p = [self.normal.x, self.normal.y, self.normal.z, self.d];
+        let row = |i: usize| t[i][0] * p[0] + t[i][1] * p[1] + t[i][2] * p[2] + t[i][3] * p[3];
+        Plane::new(Vec3::new(row(0), row(1), row(2)), row(3))
+    }
+}
+
+///
+/// The volume visible from a camera, described by the planes bounding it. Each plane faces into the volume.
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct Frustum {
+    pub planes: Vec<Plane>,
+}
+
+impl Frustum {
+    pub fn new(planes: Vec<Plane>) -> Frustum {
+        Frustum { planes }
+    }
+
+    pub fn contains(&self, point: Vec3) -> bool {
+        self.planes.iter().all(|plane| plane.distance(point) >= 0.0)
+    }
+
+    ///
+    /// Returns false only if the box is definitely outside the frustum. Boxes near the corners may be kept even though
+    /// they aren't visible, which is fine for culling.
+    ///
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            // Test the corner furthest along the plane's normal - if even that is behind it, the whole box is
+            let furthest = Vec3::new(
+                if plane.normal.x >= 0.0 {
+                    aabb.max.x
+                } else {
+                    aabb.min.x
+                },
+                if plane.normal.y >= 0.0 {
+                    aabb.max.y
+                } else {
+                    aabb.min.y
+                },
+                if plane.normal.z >= 0.0 {
+                    aabb.max.z
+                } else {
+                    aabb.min.z
+                },
+            );
+            plane.distance(furthest) >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn cull_boxes() {
+        // A box shaped frustum between x = -1 and x = 1
+        let frustum = Frustum::new(vec![
+            Plane::new(Vec3::new(1.0, 0.0, 0.0), 1.0),
+            Plane::new(Vec3::new(-1.0, 0.0, 0.0), 1.0),
+        ]);
+        let inside = Aabb::new(Vec3::new(0.5, 0.0, 0.0), Vec3::new(3.0, 1.0, 1.0));
+        let outside = Aabb::new(Vec3::new(2.0, 0.0, 0.0), Vec3::new(3.0, 1.0, 1.0));
+
+        assert!(frustum.intersects_aabb(&inside));
+        assert!(!frustum.intersects_aabb(&outside));
+    }
+
+    #[test]
+    fn untransform_plane() {
+        // A plane facing +X at the origin of a space which is shifted 2 units along X
+        let mat = Mat4::identity().translate(Vec3::new(-2.0, 0.0, 0.0));
+        let plane = Plane::new(Vec3::new(1.0, 0.0, 0.0), 0.0).untransform(mat);
+
+        assert_eq!(plane.distance(Vec3::new(2.0, 0.0, 0.0)), 0.0);
+        assert!(plane.distance(Vec3::new(3.0, 0.0, 0.0)) > 0.0);
+    }
+}
@@ -0,0 +1,572 @@
+use alloc::{collections::BTreeSet, vec, vec::Vec};
+
+use crate::{
+    framebuffer::Colour,
+    geometry::{aabb::Aabb, frustum::Plane, ray::Ray},
+    mass::MassProperties,
+    mat4::Mat4,
+    object::{Face, Object, Texture},
+    vec::{vec2::Vec2, vec3::Vec3},
+};
+
+// A little leeway, so points resting exactly on a face count as inside
+const TOLERANCE: f64 = 1e-9;
+
+///
+/// A convex polyhedron wrapped tightly around a set of points, made of triangles wound anticlockwise when seen from
+/// outside. Each face has a plane facing outwards, so a point is inside the hull when it's behind all of them.
+///
+/// Hulls are a cheap stand-in for an object's full mesh in collisions, as they only need checking against a handful
+/// of faces and edges.
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct ConvexHull {
+    points: Vec<Vec3>,
+    faces: Vec<[usize; 3]>,
+    planes: Vec<Plane>,
+}
+
+///
+/// A face of a hull being built, along with the points which are still outside it.
+///
+struct BuildFace {
+    vertices: [usize; 3],
+    plane: Plane,
+    outside: Vec<usize>,
+    alive: bool,
+}
+
+impl BuildFace {
+    fn new(points: &[Vec3], vertices: [usize; 3]) -> BuildFace {
+        BuildFace {
+            vertices,
+            plane: face_plane(points, vertices),
+            outside: vec![],
+            alive: true,
+        }
+    }
+}
+
+fn face_plane(points: &[Vec3], [a, b, c]: [usize; 3]) -> Plane {
+    let normal = (points[b] - points[a])
+        .cross_product(points[c] - points[a])
+        .normalise();
+    Plane::new(normal, -normal.dot(points[a]))
+}
+
+impl ConvexHull {
+    ///
+    /// Builds the convex hull of a set of points with the quickhull algorithm. It starts from a tetrahedron between
+    /// four of the outermost points, then repeatedly takes the point furthest outside any face, and replaces every
+    /// face which can see it with new faces joining it to the edge of the hole they leave.
+    ///
+    /// Returns `None` if the points are all on one plane, as they don't enclose any volume.
+    ///
+    pub fn from_points(points: &[Vec3]) -> Option<ConvexHull> {
+        let bounds = Aabb::from_points(points.iter().copied())?;
+        let epsilon = bounds.size().length() * TOLERANCE;
+        if epsilon <= 0.0 {
+            return None;
+        }
+
+        // Start from the two furthest apart of the extreme points along each axis
+        let extremes: Vec<usize> = [|p: Vec3| p.x, |p: Vec3| p.y, |p: Vec3| p.z]
+            .into_iter()
+            .flat_map(|axis| {
+                let key = |i: &usize| axis(points[*i]);
+                let indices = 0..points.len();
+                [
+                    indices.clone().min_by(|a, b| key(a).total_cmp(&key(b))),
+                    indices.max_by(|a, b| key(a).total_cmp(&key(b))),
+                ]
+            })
+            .flatten()
+            .collect();
+        let (a, b) = extremes
+            .iter()
+            .flat_map(|a| extremes.iter().map(move |b| (*a, *b)))
+            .max_by(|x, y| {
+                let length = |(a, b): (usize, usize)| (points[b] - points[a]).length();
+                length(*x).total_cmp(&length(*y))
+            })?;
+
+        // Then the point furthest from the line between them, and the point furthest from the plane through all three
+        let line = (points[b] - points[a]).normalise();
+        let from_line = |i: usize| {
+            let offset = points[i] - points[a];
+            (offset - line * offset.dot(line)).length()
+        };
+        let c = (0..points.len()).max_by(|x, y| from_line(*x).total_cmp(&from_line(*y)))?;
+        if from_line(c) <= epsilon {
+            return None;
+        }
+        let base = face_plane(points, [a, b, c]);
+        let d = (0..points.len()).max_by(|x, y| {
+            base.distance(points[*x])
+                .abs()
+                .total_cmp(&base.distance(points[*y]).abs())
+        })?;
+        if base.distance(points[d]).abs() <= epsilon {
+            return None;
+        }
+
+        // Wind the tetrahedron's faces so they face away from its fourth corner
+        let mut faces: Vec<BuildFace> = if base.distance(points[d]) > 0.0 {
+            [[a, c, b], [a, b, d], [b, c, d], [c, a, d]]
+        } else {
+            [[a, b, c], [a, d, b], [b, d, c], [c, d, a]]
+        }
+        .into_iter()
+        .map(|vertices| BuildFace::new(points, vertices))
+        .collect();
+
+        let corners = [a, b, c, d];
+        let remaining: Vec<usize> = (0..points.len()).filter(|i| !corners.contains(i)).collect();
+        assign_outside(points, &mut faces, remaining, 0, epsilon);
+
+        while let Some(next) = faces
+            .iter()
+            .position(|face| face.alive && !face.outside.is_empty())
+        {
+            let face = &faces[next];
+            let eye = *face
+                .outside
+                .iter()
+                .max_by(|x, y| {
+                    let height = |i: usize| face.plane.distance(points[i]);
+                    height(**x).total_cmp(&height(**y))
+                })
+                .expect("faces being expanded have points outside them");
+
+            // Every face the point can see is replaced, and the edges which only one of them has are the edge of the
+            // hole. Those edges keep their winding, so the new faces still face outwards.
+            let visible: Vec<usize> = (0..faces.len())
+                .filter(|i| faces[*i].alive && faces[*i].plane.distance(points[eye]) > epsilon)
+                .collect();
+            let edges: Vec<(usize, usize)> = visible
+                .iter()
+                .flat_map(|i| {
+                    let [a, b, c] = faces[*i].vertices;
+                    [(a, b), (b, c), (c, a)]
+                })
+                .collect();
+            let shared: BTreeSet<(usize, usize)> = edges.iter().copied().collect();
+
+            let mut orphans = vec![];
+            for i in &visible {
+                faces[*i].alive = false;
+                orphans.append(&mut faces[*i].outside);
+            }
+            orphans.retain(|i| *i != eye);
+
+            let first_new = faces.len();
+            for (a, b) in edges.iter().copied() {
+                if !shared.contains(&(b, a)) {
+                    faces.push(BuildFace::new(points, [a, b, eye]));
+                }
+            }
+            assign_outside(points, &mut faces, orphans, first_new, epsilon);
+        }
+
+        // Keep only the points the hull's faces use
+        let mut remap = vec![None; points.len()];
+        let mut hull_points = vec![];
+        let mut hull_faces = vec![];
+        for face in faces.iter().filter(|face| face.alive) {
+            hull_faces.push(face.vertices.map(|i| {
+                *remap[i].get_or_insert_with(|| {
+                    hull_points.push(points[i]);
+                    hull_points.len() - 1
+                })
+            }));
+        }
+        let planes = hull_faces
+            .iter()
+            .map(|face| face_plane(&hull_points, *face))
+            .collect();
+        Some(ConvexHull {
+            points: hull_points,
+            faces: hull_faces,
+            planes,
+        })
+    }
+
+    ///
+    /// Builds the convex hull of an object's vertices, in the object's local space.
+    ///
+    pub fn from_object(obj: &Object) -> Option<ConvexHull> {
+        ConvexHull::from_points(&obj.vertices)
+    }
+
+    ///
+    /// Returns the corners of the hull.
+    ///
+    pub fn points(&self) -> &[Vec3] {
+        &self.points
+    }
+
+    ///
+    /// Returns the hull's triangles, as indices into `points`.
+    ///
+    pub fn faces(&self) -> &[[usize; 3]] {
+        &self.faces
+    }
+
+    ///
+    /// Returns the plane of each face, facing outwards, in the same order as `faces`.
+    ///
+    pub fn planes(&self) -> &[Plane] {
+        &self.planes
+    }
+
+    ///
+    /// Computes the hull after a transformation. Transformations which mirror the hull turn its faces inside out, so
+    /// their winding is swapped back.
+    ///
+    pub fn transform(&self, mat: Mat4) -> ConvexHull {
+        let points: Vec<Vec3> = self
+            .points
+            .iter()
+            .map(|point| mat.transform(*point))
+            .collect();
+        let centre = points.iter().copied().sum::<Vec3>() / points.len() as f64;
+        let mut faces = self.faces.clone();
+        let planes = faces
+            .iter_mut()
+            .map(|face| {
+                let plane = face_plane(&points, *face);
+                if plane.distance(centre) <= 0.0 {
+                    return plane;
+                }
+                face.swap(1, 2);
+                face_plane(&points, *face)
+            })
+            .collect();
+        ConvexHull {
+            points,
+            faces,
+            planes,
+        }
+    }
+
+    ///
+    /// Returns the volume, centre of mass and inertia of the solid hull.
+    ///
+    pub fn mass_properties(&self) -> Option<MassProperties> {
+        MassProperties::from_mesh(&self.points, self.faces.iter().copied())
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        Aabb::from_points(self.points.iter().copied()).expect("hulls have at least four points")
+    }
+
+    pub fn contains(&self, point: Vec3) -> bool {
+        self.contains_within(point, 0.0)
+    }
+
+    ///
+    /// Returns whether a point is inside the hull grown by `margin` on every face.
+    ///
+    pub fn contains_within(&self, point: Vec3, margin: f64) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.distance(point) <= margin + TOLERANCE)
+    }
+
+    ///
+    /// Returns the outward normal of the face nearest to a point inside the hull, and how far the point is from it.
+    ///
+    pub fn nearest_face(&self, point: Vec3) -> (Vec3, f64) {
+        self.planes
+            .iter()
+            .map(|plane| (plane.normal, -plane.distance(point)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .expect("hulls have at least four faces")
+    }
+
+    ///
+    /// Returns the point on or in the hull nearest to a point, which is the point itself if it's inside the hull.
+    ///
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        if self.contains(point) {
+            return point;
+        }
+        self.faces
+            .iter()
+            .map(|[a, b, c]| {
+                closest_point_on_triangle(point, self.points[*a], self.points[*b], self.points[*c])
+            })
+            .min_by(|x, y| (*x - point).length().total_cmp(&(*y - point).length()))
+            .expect("hulls have at least four faces")
+    }
+
+    ///
+    /// Returns the corner of the hull furthest in a direction.
+    ///
+    pub fn support(&self, direction: Vec3) -> Vec3 {
+        *self
+            .points
+            .iter()
+            .max_by(|a, b| a.dot(direction).total_cmp(&b.dot(direction)))
+            .expect("hulls have at least four points")
+    }
+
+    ///
+    /// Returns the distance along a ray at which it enters the hull, which is zero if it starts inside.
+    ///
+    pub fn ray_distance(&self, ray: &Ray) -> Option<f64> {
+        self.ray_distance_within(ray, 0.0)
+    }
+
+    ///
+    /// Returns the distance along a ray at which it enters the hull grown by `margin` on every face. The ray is cut
+    /// down by each face in turn, and if there's anything left of it, its start is where it enters.
+    ///
+    pub fn ray_distance_within(&self, ray: &Ray, margin: f64) -> Option<f64> {
+        let (mut enter, mut exit) = (0.0, f64::INFINITY);
+        for plane in &self.planes {
+            let height = plane.distance(ray.origin) - margin;
+            let approach = plane.normal.dot(ray.direction);
+            if approach.abs() < f64::EPSILON {
+                if height > 0.0 {
+                    return None;
+                }
+                continue;
+            }
+            let distance = -height / approach;
+            if approach < 0.0 {
+                enter = f64::max(enter, distance);
+            } else {
+                exit = f64::min(exit, distance);
+            }
+            if enter > exit {
+                return None;
+            }
+        }
+        Some(enter)
+    }
+
+    ///
+    /// Returns the directions of the hull's edges, leaving out any parallel to one already found.
+    ///
+    pub fn edge_directions(&self) -> Vec<Vec3> {
+        let mut directions: Vec<Vec3> = vec![];
+        for [a, b, c] in &self.faces {
+            for (from, to) in [(a, b), (b, c), (c, a)] {
+                let direction = (self.points[*to] - self.points[*from]).normalise();
+                if directions
+                    .iter()
+                    .all(|other| other.cross_product(direction).length() > 1e-6)
+                {
+                    directions.push(direction);
+                }
+            }
+        }
+        directions
+    }
+
+    ///
+    /// Generates a flat shaded mesh of the hull, for seeing the shape a collider has been given.
+    ///
+    pub fn mesh(&self, colour: Colour) -> Object {
+        let mut vertices = vec![];
+        let mut normals = vec![];
+        let mut faces = vec![];
+        for (face, plane) in self.faces.iter().zip(&self.planes) {
+            let start = vertices.len();
+            vertices.extend(face.map(|i| self.points[i]));
+            normals.push(plane.normal);
+            faces.push(Face {
+                vertices: (start, start + 1, start + 2),
+                tex_coords: (0, 0, 0),
+                normals: (normals.len() - 1, normals.len() - 1, normals.len() - 1),
+                material: 0,
+            });
+        }
+        let texture = Texture::solid(colour);
+        Object::new(vertices, vec![Vec2::new(0.0, 0.0)], normals, faces, texture)
+    }
+}
+
+///
+/// Hands each point to the first face from `first` on which it's outside of. Points inside all of them are dropped.
+///
+fn assign_outside(
+    points: &[Vec3],
+    faces: &mut [BuildFace],
+    candidates: Vec<usize>,
+    first: usize,
+    epsilon: f64,
+) {
+    for i in candidates {
+        if let Some(face) = faces[first..]
+            .iter_mut()
+            .find(|face| face.alive && face.plane.distance(points[i]) > epsilon)
+        {
+            face.outside.push(i);
+        }
+    }
+}
+
+///
+/// Returns the point on a triangle nearest to a point, by working out which of the triangle's corners, edges or face
+/// it's closest to.
+///
+pub fn closest_point_on_triangle(point: Vec3, a: Vec3, b: Vec3, c: Vec3) -> Vec3 {
+    let (ab, ac, ap) = (b - a, c - a, point - a);
+    let (d1, d2) = (ab.dot(ap), ac.dot(ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+
+    let bp = point - b;
+    let (d3, d4) = (ab.dot(bp), ac.dot(bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = point - c;
+    let (d5, d6) = (ab.dot(cp), ac.dot(cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let denom = 1.0 / (va + vb + vc);
+    a + ab * (vb * denom) + ac * (vc * denom)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::vec::vec3::{ORIGIN, X_AXIS, Y_AXIS};
+
+    fn cube_points() -> Vec<Vec3> {
+        let mut points = vec![];
+        for x in [-1.0, 1.0] {
+            for y in [-1.0, 1.0] {
+                for z in [-1.0, 1.0] {
+                    points.push(Vec3::new(x, y, z));
+                }
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn wrap_points() {
+        // A cube's corners, plus points inside it, on its faces, and repeated
+        let mut points = cube_points();
+        points.extend([
+            ORIGIN,
+            Vec3::new(0.5, -0.2, 0.3),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.5),
+            Vec3::new(1.0, 1.0, 1.0),
+        ]);
+        let hull = ConvexHull::from_points(&points).unwrap();
+
+        assert_eq!(hull.points().len(), 8);
+        assert_eq!(hull.faces().len(), 12);
+        for point in &points {
+            assert!(hull.contains(*point), "{:?}", point);
+        }
+        assert!(!hull.contains(Vec3::new(1.1, 0.0, 0.0)));
+        assert_eq!(
+            hull.bounds(),
+            Aabb::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0))
+        );
+        assert!(hull.mesh(Colour::BLACK).validate().is_ok());
+
+        // Every face faces outwards, away from the middle
+        for plane in hull.planes() {
+            assert!((plane.distance(ORIGIN) + 1.0).abs() < 1e-9);
+        }
+
+        // The edges of a cube split into triangles run along its axes and the diagonals of its faces
+        assert_eq!(hull.edge_directions().len(), 9);
+    }
+
+    #[test]
+    fn wrap_a_ball() {
+        // Points spread over a sphere, and scattered inside it
+        let mut points = vec![];
+        for i in 0..200 {
+            let t = i as f64 / 200.0;
+            let (polar, azimuth) = ((1.0 - 2.0 * t).acos(), i as f64 * 2.399963);
+            let radius = if i % 3 == 0 { 0.5 } else { 2.0 };
+            points.push(
+                Vec3::new(
+                    polar.sin() * azimuth.cos(),
+                    polar.cos(),
+                    polar.sin() * azimuth.sin(),
+                ) * radius,
+            );
+        }
+        let hull = ConvexHull::from_points(&points).unwrap();
+
+        // Only the outer points are corners, and a closed surface of triangles has twice as many faces as corners, less 4
+        assert!(hull
+            .points()
+            .iter()
+            .all(|point| (point.length() - 2.0).abs() < 1e-9));
+        assert_eq!(hull.faces().len(), 2 * hull.points().len() - 4);
+        for point in &points {
+            assert!(hull.contains(*point));
+        }
+    }
+
+    #[test]
+    fn flat_points_have_no_hull() {
+        let square = [ORIGIN, X_AXIS, Y_AXIS, X_AXIS + Y_AXIS];
+        assert_eq!(ConvexHull::from_points(&square), None);
+        assert_eq!(ConvexHull::from_points(&[]), None);
+    }
+
+    #[test]
+    fn query_hull() {
+        let hull = ConvexHull::from_points(&cube_points()).unwrap();
+        assert_eq!(
+            hull.closest_point(Vec3::new(3.0, 0.5, 0.0)),
+            Vec3::new(1.0, 0.5, 0.0)
+        );
+        assert_eq!(hull.closest_point(ORIGIN), ORIGIN);
+        assert_eq!(
+            hull.support(Vec3::new(1.0, 1.0, -1.0)),
+            Vec3::new(1.0, 1.0, -1.0)
+        );
+
+        let ray = Ray::new(Vec3::new(-5.0, 0.5, 0.0), X_AXIS);
+        assert!((hull.ray_distance(&ray).unwrap() - 4.0).abs() < 1e-9);
+        assert!((hull.ray_distance_within(&ray, 1.0).unwrap() - 3.0).abs() < 1e-9);
+        assert_eq!(hull.ray_distance(&Ray::new(ORIGIN, X_AXIS)), Some(0.0));
+        assert_eq!(
+            hull.ray_distance(&Ray::new(Vec3::new(-5.0, 1.5, 0.0), X_AXIS)),
+            None
+        );
+
+        // Mirroring the hull keeps its faces facing outwards
+        let mirrored = hull.transform(
+            Mat4::identity()
+                .scale(Vec3::new(-1.0, 1.0, 1.0))
+                .translate(Vec3::new(5.0, 0.0, 0.0)),
+        );
+        assert!(mirrored.contains(Vec3::new(5.5, 0.0, 0.0)));
+        assert!(!mirrored.contains(ORIGIN));
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{
+    geometry::{aabb::Aabb, ray::Ray},
+    mat4::Mat4,
+    vec::vec3::{Vec3, X_AXIS, Y_AXIS, Z_AXIS},
+};
+
+///
+/// An oriented bounding box - a box which can be rotated, described by its centre, three perpendicular unit axes,
+/// and how far it extends along each of them.
+///
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Obb {
+    pub centre: Vec3,
+    pub axes: [Vec3; 3],
+    pub half_extents: Vec3,
+}
+
+impl Obb {
+    ///
+    /// Creates a box lined up with the X, Y and Z axes.
+    ///
+    pub fn new(centre: Vec3, half_extents: Vec3) -> Obb {
+        Obb {
+            centre,
+            axes: [X_AXIS, Y_AXIS, Z_AXIS],
+            half_extents,
+        }
+    }
+
+    pub fn from_aabb(aabb: Aabb) -> Obb {
+        Obb::new(aabb.centre(), aabb.size() / 2.0)
+    }
+
+    fn extents(&self) -> [f64; 3] {
+        [
+            self.half_extents.x,
+            self.half_extents.y,
+            self.half_extents.z,
+        ]
+    }
+
+    ///
+    /// Computes the box after a transformation. Scaling stretches the box along its axes, but shearing is ignored.
+    ///
+    pub fn transform(&self, mat: Mat4) -> Obb {
+        let extents = self.extents();
+        let mut axes = self.axes;
+        let mut scaled = [0.0; 3];
+        for i in 0..3 {
+            let axis = mat.transform_direction(self.axes[i]);
+            let length = axis.length();
+            if length > f64::EPSILON {
+                axes[i] = axis / length;
+            }
+            scaled[i] = extents[i] * length;
+        }
+        Obb {
+            centre: mat.transform(self.centre),
+            axes,
+            half_extents: Vec3::new(scaled[0], scaled[1], scaled[2]),
+        }
+    }
+
+    pub fn corners(&self) -> [Vec3; 8] {
+        let [x, y, z] = self.extents();
+        let [ax, ay, az] = self.axes;
+        [0, 1, 2, 3, 4, 5, 6, 7].map(|i| {
+            let sign = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+            self.centre + ax * (x * sign(1)) + ay * (y * sign(2)) + az * (z * sign(4))
+        })
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        Aabb::from_points(self.corners()).expect("boxes have eight corners")
+    }
+
+    ///
+    /// Returns how far the box reaches from its centre when projected onto an axis.
+    ///
+    pub fn radius_along(&self, axis: Vec3) -> f64 {
+        let extents = self.extents();
+        (0..3)
+            .map(|i| self.axes[i].dot(axis).abs() * extents[i])
+            .sum()
+    }
+
+    ///
+    /// Converts a point into the box's space, where its axes are X, Y and Z and its centre is the origin.
+    ///
+    fn local_point(&self, point: Vec3) -> Vec3 {
+        let offset = point - self.centre;
+        Vec3::new(
+            offset.dot(self.axes[0]),
+            offset.dot(self.axes[1]),
+            offset.dot(self.axes[2]),
+        )
+    }
+
+    fn world_point(&self, local: Vec3) -> Vec3 {
+        self.centre + self.axes[0] * local.x + self.axes[1] * local.y + self.axes[2] * local.z
+    }
+
+    ///
+    /// Returns the point in the box nearest to a point, which is the point itself if it's inside the box.
+    ///
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        let local = self.local_point(point);
+        let half = self.half_extents;
+        self.world_point(Vec3::new(
+            local.x.clamp(-half.x, half.x),
+            local.y.clamp(-half.y, half.y),
+            local.z.clamp(-half.z, half.z),
+        ))
+    }
+
+    pub fn contains(&self, point: Vec3) -> bool {
+        // A little leeway, so corners resting exactly on a face count as inside
+        const TOLERANCE: f64 = 1e-9;
+        let local = self.local_point(point);
+        let half = self.half_extents;
+        local.x.abs() <= half.x + TOLERANCE
+            && local.y.abs() <= half.y + TOLERANCE
+            && local.z.abs() <= half.z + TOLERANCE
+    }
+
+    ///
+    /// Returns the outward normal of the face nearest to a point inside the box, and how far the point is from it.
+    ///
+    pub fn nearest_face(&self, point: Vec3) -> (Vec3, f64) {
+        let local = self.local_point(point);
+        let local = [local.x, local.y, local.z];
+        let extents = self.extents();
+        (0..3)
+            .map(|i| {
+                let sign = if local[i] >= 0.0 { 1.0 } else { -1.0 };
+                (self.axes[i] * sign, extents[i] - local[i].abs())
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .expect("boxes have three axes")
+    }
+
+    ///
+    /// Returns the distance along a ray at which it enters the box, which is zero if it starts inside.
+    ///
+    pub fn ray_distance(&self, ray: &Ray) -> Option<f64> {
+        let local = Ray {
+            origin: self.local_point(ray.origin),
+            direction: Vec3::new(
+                ray.direction.dot(self.axes[0]),
+                ray.direction.dot(self.axes[1]),
+                ray.direction.dot(self.axes[2]),
+            ),
+        };
+        Aabb::new(-self.half_extents, self.half_extents).ray_distance(&local)
+    }
+
+    ///
+    /// Returns the corner of the box furthest in a direction.
+    ///
+    pub fn support(&self, direction: Vec3) -> Vec3 {
+        let extents = self.extents();
+        (0..3).fold(self.centre, |point, i| {
+            let sign = if self.axes[i].dot(direction) >= 0.0 {
+                1.0
+            } else {
+                -1.0
+            };
+            point + self.axes[i] * (extents[i] * sign)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rotated_boxes() {
+        let obb = Obb::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 1.0, 1.0))
+            .transform(Mat4::identity().rotate(Z_AXIS, core::f64::consts::FRAC_PI_2));
+
+        // The long side now points along Y
+        let bounds = obb.bounds();
+        assert!((bounds.max.y - 2.0).abs() < 1e-9);
+        assert!((bounds.max.x - 1.0).abs() < 1e-9);
+        assert!(obb.contains(Vec3::new(0.0, 1.5, 0.0)));
+        assert!(!obb.contains(Vec3::new(1.5, 0.0, 0.0)));
+        assert!((obb.radius_along(Y_AXIS) - 2.0).abs() < 1e-9);
+
+        let ray = Ray::new(Vec3::new(0.0, 5.0, 0.0), -Y_AXIS);
+        assert!((obb.ray_distance(&ray).unwrap() - 3.0).abs() < 1e-9);
+        let ray = Ray::new(Vec3::new(1.5, 5.0, 0.0), -Y_AXIS);
+        assert_eq!(obb.ray_distance(&ray), None);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::vec::vec3::Vec3;
+
+///
+/// A half-line starting at `origin` and travelling along `direction`, which is always normalised.
+///
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Ray {
+        Ray {
+            origin,
+            direction: direction.normalise(),
+        }
+    }
+
+    ///
+    /// Returns the point a given distance along the ray.
+    ///
+    pub fn at(&self, distance: f64) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    ///
+    /// Returns the distance along the ray at which it hits a triangle, using the Möller-Trumbore algorithm.
+    /// Triangles are hit from either side.
+    ///
+    pub fn intersect_triangle(&self, a: Vec3, b: Vec3, c: Vec3) -> Option<f64> {
+        let edge_1 = b - a;
+        let edge_2 = c - a;
+        let p = self.direction.cross_product(edge_2);
+        let determinant = edge_1.dot(p);
+
+        // The ray is parallel to the triangle
+        if determinant.abs() < f64::EPSILON {
+            return None;
+        }
+
+        let to_origin = self.origin - a;
+        let u = to_origin.dot(p) / determinant;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = to_origin.cross_product(edge_1);
+        let v = self.direction.dot(q) / determinant;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let distance = edge_2.dot(q) / determinant;
+        (distance >= 0.0).then_some(distance)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hit_triangle() {
+        let (a, b, c) = (
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(1.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let front = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let back = Ray::new(Vec3::new(0.0, 0.0, -2.0), Vec3::new(0.0, 0.0, 1.0));
+        let miss = Ray::new(Vec3::new(2.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let away = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, 1.0));
+
+        assert_eq!(front.intersect_triangle(a, b, c), Some(5.0));
+        assert_eq!(back.intersect_triangle(a, b, c), Some(2.0));
+        assert_eq!(miss.intersect_triangle(a, b, c), None);
+        assert_eq!(away.intersect_triangle(a, b, c), None);
+    }
+}
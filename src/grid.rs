@@ -0,0 +1,255 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::{
+    geometry::{aabb::Aabb, frustum::Frustum, ray::Ray},
+    vec::vec3::Vec3,
+};
+
+type Cell = (i64, i64, i64);
+
+///
+/// A uniform grid dividing space into cubes of `cell_size`, where each item is listed in every cell its bounds touch.
+///
+/// Unlike a `Bvh`, items can be added and removed individually without rebuilding anything, but moving an item means
+/// removing and re-inserting it. This makes the grid a good fit for large numbers of objects which never move.
+/// Items much larger than a cell are listed in a lot of cells, so the cell size should be around the size of a typical
+/// item.
+///
+/// Queries give items in the same order each time they're run, cell by cell, so that anything done with them (such as
+/// resolving contacts or drawing in turn) comes out the same on every run.
+///
+#[derive(Debug, Clone)]
+pub struct Grid<T> {
+    cell_size: f64,
+    cells: BTreeMap<Cell, Vec<T>>,
+    bounds: HashMap<T, Aabb>,
+}
+
+impl<T: Copy + Eq + Hash> Grid<T> {
+    pub fn new(cell_size: f64) -> Grid<T> {
+        Grid {
+            cell_size,
+            cells: BTreeMap::new(),
+            bounds: HashMap::new(),
+        }
+    }
+
+    pub fn cell_size(&self) -> f64 {
+        self.cell_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.bounds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+
+    pub fn contains(&self, item: T) -> bool {
+        self.bounds.contains_key(&item)
+    }
+
+    ///
+    /// Adds an item to every cell its bounds touch. Inserting an item which is already in the grid moves it.
+    ///
+    pub fn insert(&mut self, item: T, bounds: Aabb) {
+        self.remove(item);
+        for cell in self.cells_in(&bounds) {
+            self.cells.entry(cell).or_default().push(item);
+        }
+        self.bounds.insert(item, bounds);
+    }
+
+    ///
+    /// Removes an item from the grid, returning its bounds if it was there.
+    ///
+    pub fn remove(&mut self, item: T) -> Option<Aabb> {
+        let bounds = self.bounds.remove(&item)?;
+        for cell in self.cells_in(&bounds) {
+            if let Some(items) = self.cells.get_mut(&cell) {
+                items.retain(|other| *other != item);
+                if items.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+        Some(bounds)
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.bounds.clear();
+    }
+
+    ///
+    /// Returns every item whose bounds overlap a box.
+    ///
+    pub fn query_aabb(&self, aabb: &Aabb) -> Vec<T> {
+        // Large boxes can cover far more cells than are occupied, in which case it's quicker to check the occupied ones
+        let candidates: Vec<&Vec<T>> = if self.cell_count(aabb) > self.cells.len() {
+            self.cells
+                .iter()
+                .filter(|(cell, _)| self.cell_bounds(**cell).intersects(aabb))
+                .map(|(_, items)| items)
+                .collect()
+        } else {
+            self.cells_in(aabb)
+                .filter_map(|cell| self.cells.get(&cell))
+                .collect()
+        };
+
+        let mut seen = HashSet::new();
+        candidates
+            .into_iter()
+            .flatten()
+            .filter(|item| seen.insert(**item) && self.bounds[item].intersects(aabb))
+            .copied()
+            .collect()
+    }
+
+    ///
+    /// Returns every item whose bounds may be inside a frustum. Only occupied cells are visited, so empty space costs
+    /// nothing.
+    ///
+    pub fn query_frustum(&self, frustum: &Frustum) -> Vec<T> {
+        let mut seen = HashSet::new();
+        self.cells
+            .iter()
+            .filter(|(cell, _)| frustum.intersects_aabb(&self.cell_bounds(**cell)))
+            .flat_map(|(_, items)| items)
+            .filter(|item| seen.insert(**item) && frustum.intersects_aabb(&self.bounds[item]))
+            .copied()
+            .collect()
+    }
+
+    ///
+    /// Returns every item whose bounds a ray passes through within `max_distance`, along with the distance at which the
+    /// ray enters them, nearest first.
+    ///
+    pub fn query_ray(&self, ray: &Ray, max_distance: f64) -> Vec<(T, f64)> {
+        let hits = |bounds: &Aabb| {
+            bounds
+                .ray_distance(ray)
+                .filter(|distance| *distance <= max_distance)
+        };
+
+        let mut seen = HashSet::new();
+        let mut result: Vec<(T, f64)> = self
+            .cells
+            .iter()
+            .filter(|(cell, _)| hits(&self.cell_bounds(**cell)).is_some())
+            .flat_map(|(_, items)| items)
+            .filter(|item| seen.insert(**item))
+            .filter_map(|item| Some((*item, hits(&self.bounds[item])?)))
+            .collect();
+        result.sort_by(|a, b| a.1.total_cmp(&b.1));
+        result
+    }
+
+    fn cell_of(&self, value: f64) -> i64 {
+        (value / self.cell_size).floor() as i64
+    }
+
+    fn cell_bounds(&self, (x, y, z): Cell) -> Aabb {
+        let min = Vec3::new(x as f64, y as f64, z as f64) * self.cell_size;
+        Aabb::new(min, min + Vec3::new(1.0, 1.0, 1.0) * self.cell_size)
+    }
+
+    fn cell_count(&self, bounds: &Aabb) -> usize {
+        let span = |min: f64, max: f64| (self.cell_of(max) - self.cell_of(min) + 1) as usize;
+        span(bounds.min.x, bounds.max.x)
+            .saturating_mul(span(bounds.min.y, bounds.max.y))
+            .saturating_mul(span(bounds.min.z, bounds.max.z))
+    }
+
+    fn cells_in(&self, bounds: &Aabb) -> impl Iterator<Item = Cell> {
+        let (min_x, max_x) = (self.cell_of(bounds.min.x), self.cell_of(bounds.max.x));
+        let (min_y, max_y) = (self.cell_of(bounds.min.y), self.cell_of(bounds.max.y));
+        let (min_z, max_z) = (self.cell_of(bounds.min.z), self.cell_of(bounds.max.z));
+        (min_x..=max_x).flat_map(move |x| {
+            (min_y..=max_y).flat_map(move |y| (min_z..=max_z).map(move |z| (x, y, z)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::geometry::frustum::Plane;
+
+    fn unit_box(x: f64) -> Aabb {
+        Aabb::new(Vec3::new(x, 0.0, 0.0), Vec3::new(x + 1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn items_spanning_cells_are_found_once() {
+        let mut grid = Grid::new(1.0);
+        grid.insert(
+            0,
+            Aabb::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(5.0, 5.0, 5.0)),
+        );
+        grid.insert(1, unit_box(10.0));
+
+        let result = grid.query_aabb(&Aabb::new(
+            Vec3::new(-1.0, -1.0, -1.0),
+            Vec3::new(6.0, 6.0, 6.0),
+        ));
+        assert_eq!(result, vec![0]);
+    }
+
+    #[test]
+    fn move_and_remove() {
+        let mut grid = Grid::new(4.0);
+        grid.insert(0, unit_box(0.0));
+        grid.insert(0, unit_box(20.0));
+
+        assert!(grid.query_aabb(&unit_box(0.0)).is_empty());
+        assert_eq!(grid.query_aabb(&unit_box(20.0)), vec![0]);
+
+        assert_eq!(grid.remove(0), Some(unit_box(20.0)));
+        assert!(grid.is_empty());
+        assert!(grid.cells.is_empty());
+    }
+
+    #[test]
+    fn query_frustum() {
+        let mut grid = Grid::new(4.0);
+        for i in 0..10 {
+            grid.insert(i, unit_box(i as f64 * 3.0));
+        }
+        // Everything with X between 5 and 10
+        let frustum = Frustum::new(vec![
+            Plane::new(Vec3::new(1.0, 0.0, 0.0), -5.0),
+            Plane::new(Vec3::new(-1.0, 0.0, 0.0), 10.0),
+        ]);
+
+        let mut result = grid.query_frustum(&frustum);
+        result.sort();
+        assert_eq!(result, vec![2, 3]);
+    }
+
+    #[test]
+    fn queries_are_ordered() {
+        // Two grids built the same way give the same order, rather than one depending on how cells happen to hash
+        let build = || {
+            let mut grid = Grid::new(1.0);
+            for i in 0..50 {
+                grid.insert(i, unit_box((i * 7 % 50) as f64 * 2.0));
+            }
+            grid
+        };
+        let everything = Frustum::new(vec![]);
+        assert_eq!(
+            build().query_frustum(&everything),
+            build().query_frustum(&everything)
+        );
+        let result = build().query_frustum(&everything);
+        let mut sorted = result.clone();
+        sorted.sort_by_key(|i| i * 7 % 50);
+        assert_eq!(result, sorted);
+    }
+}
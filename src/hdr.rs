@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{framebuffer::Colour, vec::vec3::Vec3};
+
+///
+/// How colours brighter than the screen can show are brought back into range.
+/// - `Reinhard` divides each channel by one more than itself, which rolls off gently but never quite reaches white
+/// - `Aces` is a fitted approximation of the ACES filmic curve, with more contrast, which saturates to white
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Tonemapper {
+    #[default]
+    Reinhard,
+    Aces,
+}
+
+///
+/// Settings for drawing in high dynamic range. Colours are worked out without being clamped, so lighting brighter than
+/// white isn't lost, and are brought back into range as the frame is finished.
+/// - `tonemapper` is the curve used to bring colours back into range
+/// - `exposure` scales every colour before it's tonemapped, so higher values brighten the image
+///
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Hdr {
+    pub tonemapper: Tonemapper,
+    pub exposure: f64,
+}
+
+impl Hdr {
+    ///
+    /// Exposes and tonemaps an HDR colour, with channels from 0 upwards, to one which can be shown.
+    ///
+    pub fn tonemap(&self, colour: [f32; 3]) -> Colour {
+        let exposure = self.exposure as f32;
+        let [r, g, b] = colour.map(|channel| {
+            let x = (channel * exposure).max(0.0);
+            let mapped = match self.tonemapper {
+                Tonemapper::Reinhard => x / (1.0 + x),
+                Tonemapper::Aces => (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14),
+            };
+            // Channels are never negative, so adding a half before truncating rounds them
+            (mapped.clamp(0.0, 1.0) * 255.0 + 0.5) as u8
+        });
+        Colour::new(r, g, b)
+    }
+}
+
+impl Default for Hdr {
+    fn default() -> Self {
+        Hdr {
+            tonemapper: Tonemapper::default(),
+            exposure: 1.0,
+        }
+    }
+}
+
+///
+/// Converts a colour to an RGB value between 0 and 1, the way it's held while drawing in HDR.
+///
+pub(crate) fn linear(colour: Colour) -> Vec3 {
+    let channel = |value: u8| value as f64 / u8::MAX as f64;
+    Vec3::new(channel(colour.r), channel(colour.g), channel(colour.b))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tonemapping() {
+        let reinhard = Hdr::default();
+        assert_eq!(reinhard.tonemap([0.0, 1.0, 3.0]), Colour::new(0, 128, 191));
+        // Negative light has nothing to show
+        assert_eq!(reinhard.tonemap([-1.0, 0.0, 0.0]), Colour::BLACK);
+
+        let aces = Hdr {
+            tonemapper: Tonemapper::Aces,
+            exposure: 1.0,
+        };
+        assert_eq!(aces.tonemap([0.0, 100.0, 1000.0]), Colour::new(0, 255, 255));
+        // Brighter inputs always come out brighter, until they saturate
+        let grey = |hdr: &Hdr, value: f32| hdr.tonemap([value; 3]).r;
+        assert!(grey(&aces, 0.5) < grey(&aces, 1.0));
+        assert!(grey(&aces, 1.0) < grey(&aces, 2.0));
+
+        // Exposure scales colours before they're mapped
+        let exposed = Hdr {
+            exposure: 2.0,
+            ..reinhard
+        };
+        assert_eq!(exposed.tonemap([0.5; 3]), reinhard.tonemap([1.0; 3]));
+
+        assert_eq!(linear(Colour::WHITE), Vec3::new(1.0, 1.0, 1.0));
+    }
+}
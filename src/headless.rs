@@ -0,0 +1,225 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    camera::Camera,
+    config::RendererConfig,
+    error::Error,
+    present::PresentTarget,
+    renderer::{RenderStats, Renderer},
+    vec::vec3::Vec3,
+    world::World,
+};
+
+///
+/// How long each stage of a frame took, and how much was drawn, from `Headless::profile_frame`.
+/// - `physics` is advancing the physics simulation, and `update` is updating the world
+/// - `cull` is finding the objects on screen and sorting them for drawing
+/// - `draw` is drawing them. `stats.raster_time` is the part of it spent filling in triangles, and the rest is mostly
+///   spent transforming vertices
+/// - `stats` counts the triangles and pixels drawn
+///
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct FrameProfile {
+    pub physics: Duration,
+    pub update: Duration,
+    pub cull: Duration,
+    pub draw: Duration,
+    pub stats: RenderStats,
+}
+
+impl FrameProfile {
+    pub fn total(&self) -> Duration {
+        self.physics + self.update + self.cull + self.draw
+    }
+}
+
+///
+/// A world, camera and renderer driven without a window, for rendering frames to buffers or PNG files. This is useful
+/// for comparing renders against saved images in tests, generating thumbnails on a server, and benchmarking.
+/// - `frame_time` is how much time passes each frame, in seconds, so the same frames are rendered however long they
+///   take to draw
+///
+/// Each frame the physics simulation is advanced and the world is updated, in the same way as `run`, then the world is
+/// drawn from the camera.
+///
+pub struct Headless {
+    pub world: World,
+    pub camera: Camera,
+    pub renderer: Renderer,
+    pub frame_time: f64,
+}
+
+impl Headless {
+    ///
+    /// Creates an empty world, with a camera at the origin, rendering images of the given size at 60 frames a second.
+    ///
+    pub fn new(width: usize, height: usize) -> Headless {
+        Headless::from_config(RendererConfig::new(width, height))
+    }
+
+    ///
+    /// Creates an empty world as with `new`, rendering images as set out in `config`. Images come out at the config's
+    /// size after its render scale is applied.
+    ///
+    pub fn from_config(config: RendererConfig) -> Headless {
+        Headless {
+            world: World::new(),
+            camera: Camera::new(Vec3::default()),
+            renderer: Renderer::new(config),
+            frame_time: 1.0 / 60.0,
+        }
+    }
+
+    ///
+    /// Moves the world on by `elapsed` seconds without drawing anything.
+    ///
+    pub fn advance(&mut self, elapsed: f64) {
+        self.world.advance_physics(elapsed);
+        self.world.update(elapsed);
+    }
+
+    ///
+    /// Draws the world as it is now, returning the pixels as RGB values, row by row from the top left.
+    ///
+    pub fn render(&mut self) -> &[u32] {
+        self.renderer.clear();
+        self.camera.render_world(&mut self.renderer, &self.world);
+        &self.renderer.buffer
+    }
+
+    ///
+    /// Moves the world on by one frame, then draws it.
+    ///
+    pub fn render_frame(&mut self) -> &[u32] {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("frame").entered();
+        self.advance(self.frame_time);
+        self.render()
+    }
+
+    ///
+    /// Moves the world on by one frame and draws it as with `render_frame`, timing each stage. Triangles are timed as
+    /// they're filled in, which slows drawing down a little.
+    ///
+    pub fn profile_frame(&mut self) -> FrameProfile {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("frame").entered();
+        let start = Instant::now();
+        self.world.advance_physics(self.frame_time);
+        let physics = start.elapsed();
+
+        let start = Instant::now();
+        self.world.update(self.frame_time);
+        let update = start.elapsed();
+
+        let start = Instant::now();
+        let (width, height) = (self.renderer.width(), self.renderer.height());
+        let visible = self.camera.visible_entities(&self.world, width, height);
+        let cull = start.elapsed();
+
+        let start = Instant::now();
+        self.renderer.clear();
+        self.renderer.set_profiling(true);
+        self.camera
+            .draw_entities(&mut self.renderer, &self.world, &visible);
+        self.renderer.set_profiling(false);
+        let draw = start.elapsed();
+
+        FrameProfile {
+            physics,
+            update,
+            cull,
+            draw,
+            stats: self.renderer.stats(),
+        }
+    }
+
+    ///
+    /// Renders `count` frames one after another, returning a copy of each.
+    ///
+    pub fn render_frames(&mut self, count: usize) -> Vec<Vec<u32>> {
+        (0..count).map(|_| self.render_frame().to_vec()).collect()
+    }
+
+    ///
+    /// Renders `count` frames, presenting each one to `target` - e.g. a `PngRecorder` to save them, or a `Terminal` to
+    /// watch them.
+    ///
+    pub fn present_frames(
+        &mut self,
+        count: usize,
+        target: &mut impl PresentTarget,
+    ) -> Result<(), Error> {
+        for _ in 0..count {
+            self.render_frame();
+            target.present_frame(&self.renderer)?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Renders `count` frames, saving each one as a PNG. `path` is called with each frame's index to name its file.
+    ///
+    pub fn save_frames(
+        &mut self,
+        count: usize,
+        path: impl Fn(usize) -> String,
+    ) -> Result<(), Error> {
+        for index in 0..count {
+            self.render_frame();
+            self.renderer
+                .save_png(&path(index))
+                .map_err(Error::TextureError)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        object::{Object, Texture},
+        rigidbody::Rigidbody,
+    };
+
+    #[test]
+    fn renders_without_a_window() {
+        let render = || {
+            let mut headless = Headless::new(60, 40);
+            let cube = Object::from_obj("./resources/cube").unwrap();
+            let id = headless
+                .world
+                .add_object(cube.clone(), Vec3::new(0.0, 0.0, 6.0));
+            headless.world.insert_component(id, Rigidbody::new(cube));
+            headless.render_frames(10)
+        };
+
+        // The falling cube is drawn over the black background, lower down every frame
+        let frames = render();
+        assert_eq!(frames.len(), 10);
+        let lowest = |frame: &Vec<u32>| frame.iter().rposition(|pixel| *pixel != 0).unwrap();
+        assert!(lowest(&frames[9]) > lowest(&frames[0]));
+
+        // Frames don't depend on how long they took to draw, so renders can be compared against saved images
+        assert_eq!(render(), frames);
+
+        // Profiling counts the cube's faces, some of which face away and are drawn off screen
+        let mut headless = Headless::new(60, 40);
+        let cube = Object::from_obj("./resources/cube").unwrap();
+        let faces = cube.faces.len();
+        headless.world.add_object(cube, Vec3::new(0.0, 0.0, 6.0));
+        let profile = headless.profile_frame();
+        assert_eq!(profile.stats.triangles, faces);
+        assert!(profile.stats.rasterised > 0 && profile.stats.pixels > 0);
+        assert!(profile.total() >= profile.draw);
+
+        let path = std::env::temp_dir().join("megavertex-headless-frame.png");
+        let path = path.to_str().unwrap();
+        let mut headless = Headless::new(60, 40);
+        headless.save_frames(1, |_| path.to_string()).unwrap();
+        let saved = Texture::load_from(path).unwrap();
+        assert_eq!((saved.width, saved.height), (60, 40));
+        let _ = std::fs::remove_file(path);
+    }
+}
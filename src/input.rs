@@ -0,0 +1,720 @@
+use std::collections::HashMap;
+
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window};
+use serde::{Deserialize, Serialize};
+
+use crate::vec::{vec2::Vec2, vec3::Vec3};
+
+// How far the camera turns for each pixel the mouse moves, in radians
+const MOUSE_SENSITIVITY: f64 = 0.003;
+
+// Every key and mouse button, for reading them back from their names
+const KEYS: [Key; 107] = [
+    Key::Key0,
+    Key::Key1,
+    Key::Key2,
+    Key::Key3,
+    Key::Key4,
+    Key::Key5,
+    Key::Key6,
+    Key::Key7,
+    Key::Key8,
+    Key::Key9,
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+    Key::F1,
+    Key::F2,
+    Key::F3,
+    Key::F4,
+    Key::F5,
+    Key::F6,
+    Key::F7,
+    Key::F8,
+    Key::F9,
+    Key::F10,
+    Key::F11,
+    Key::F12,
+    Key::F13,
+    Key::F14,
+    Key::F15,
+    Key::Down,
+    Key::Left,
+    Key::Right,
+    Key::Up,
+    Key::Apostrophe,
+    Key::Backquote,
+    Key::Backslash,
+    Key::Comma,
+    Key::Equal,
+    Key::LeftBracket,
+    Key::Minus,
+    Key::Period,
+    Key::RightBracket,
+    Key::Semicolon,
+    Key::Slash,
+    Key::Backspace,
+    Key::Delete,
+    Key::End,
+    Key::Enter,
+    Key::Escape,
+    Key::Home,
+    Key::Insert,
+    Key::Menu,
+    Key::PageDown,
+    Key::PageUp,
+    Key::Pause,
+    Key::Space,
+    Key::Tab,
+    Key::NumLock,
+    Key::CapsLock,
+    Key::ScrollLock,
+    Key::LeftShift,
+    Key::RightShift,
+    Key::LeftCtrl,
+    Key::RightCtrl,
+    Key::NumPad0,
+    Key::NumPad1,
+    Key::NumPad2,
+    Key::NumPad3,
+    Key::NumPad4,
+    Key::NumPad5,
+    Key::NumPad6,
+    Key::NumPad7,
+    Key::NumPad8,
+    Key::NumPad9,
+    Key::NumPadDot,
+    Key::NumPadSlash,
+    Key::NumPadAsterisk,
+    Key::NumPadMinus,
+    Key::NumPadPlus,
+    Key::NumPadEnter,
+    Key::LeftAlt,
+    Key::RightAlt,
+    Key::LeftSuper,
+    Key::RightSuper,
+    Key::Unknown,
+];
+const MOUSE_BUTTONS: [MouseButton; 3] =
+    [MouseButton::Left, MouseButton::Middle, MouseButton::Right];
+
+///
+/// Named actions, like "move_forward" or "jump", and the keys which trigger them. An action can have several keys,
+/// and a key can trigger several actions.
+///
+/// The default bindings move with WASD, look around with the arrow keys, and jump with space:
+/// - `move_forward`, `move_back`, `move_left` and `move_right`
+/// - `look_up`, `look_down`, `look_left` and `look_right`
+/// - `jump`
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bindings {
+    actions: HashMap<String, Vec<Key>>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings::new()
+            .with_action("move_forward", [Key::W])
+            .with_action("move_back", [Key::S])
+            .with_action("move_left", [Key::A])
+            .with_action("move_right", [Key::D])
+            .with_action("look_up", [Key::Up])
+            .with_action("look_down", [Key::Down])
+            .with_action("look_left", [Key::Left])
+            .with_action("look_right", [Key::Right])
+            .with_action("jump", [Key::Space])
+    }
+}
+
+impl Bindings {
+    ///
+    /// Creates bindings with no actions. Use `Bindings::default` for the standard movement controls.
+    ///
+    pub fn new() -> Bindings {
+        Bindings {
+            actions: HashMap::new(),
+        }
+    }
+
+    pub fn with_action(mut self, action: &str, keys: impl IntoIterator<Item = Key>) -> Bindings {
+        self.rebind(action, keys);
+        self
+    }
+
+    ///
+    /// Adds a key to an action, keeping any keys it already has.
+    ///
+    pub fn bind(&mut self, action: &str, key: Key) {
+        let keys = self.actions.entry(action.to_string()).or_default();
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+
+    ///
+    /// Replaces the keys bound to an action.
+    ///
+    pub fn rebind(&mut self, action: &str, keys: impl IntoIterator<Item = Key>) {
+        self.actions.remove(action);
+        for key in keys {
+            self.bind(action, key);
+        }
+    }
+
+    ///
+    /// Removes an action, so nothing triggers it.
+    ///
+    pub fn unbind(&mut self, action: &str) {
+        self.actions.remove(action);
+    }
+
+    ///
+    /// Stops a key triggering any action, for freeing it up before binding it to another.
+    ///
+    pub fn unbind_key(&mut self, key: Key) {
+        for keys in self.actions.values_mut() {
+            keys.retain(|bound| *bound != key);
+        }
+    }
+
+    ///
+    /// Returns the keys bound to an action, which is empty for actions that don't exist.
+    ///
+    pub fn keys(&self, action: &str) -> &[Key] {
+        self.actions.get(action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    ///
+    /// Returns the names of every action, in no particular order.
+    ///
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.actions.keys().map(String::as_str)
+    }
+}
+
+///
+/// What a window reports about the keyboard and mouse for one frame, which `Input::update` builds on. Positions are
+/// in pixels of the rendered image, from the top left.
+/// - `keys_down` are the keys held down, and `keys_pressed` and `keys_released` are the ones pressed or let go since
+///   the last frame, not counting key repeats
+/// - `cursor` is where the cursor really is, even outside the window, for measuring how far it moves. It's `None` while
+///   the window isn't focused
+/// - `mouse` is where the mouse is in the window, or `None` when it's outside
+/// - `size` is the size of the window, for pinning captured mice to its middle
+/// - `buttons` are the mouse buttons held down, and `scroll` is how far the wheel was turned since the last frame
+///
+/// Frames can be saved and loaded, with keys and buttons written out by name, which is how a `Recording` keeps them.
+///
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputFrame {
+    #[serde(with = "names")]
+    pub keys_down: Vec<Key>,
+    #[serde(with = "names")]
+    pub keys_pressed: Vec<Key>,
+    #[serde(with = "names")]
+    pub keys_released: Vec<Key>,
+    pub cursor: Option<Vec2>,
+    pub mouse: Option<Vec2>,
+    pub size: (usize, usize),
+    #[serde(with = "names")]
+    pub buttons: Vec<MouseButton>,
+    pub scroll: f64,
+}
+
+impl InputFrame {
+    ///
+    /// Reads the state of the keyboard and mouse from a minifb window.
+    ///
+    pub fn read(window: &mut Window) -> InputFrame {
+        // The cursor isn't clamped to the window, so it can be followed past the edges while it's captured
+        let active = window.is_active();
+        let position = |(x, y): (f32, f32)| Vec2::new(x as f64, y as f64);
+        InputFrame {
+            keys_down: window.get_keys(),
+            keys_pressed: window.get_keys_pressed(KeyRepeat::No),
+            keys_released: window.get_keys_released(),
+            cursor: window
+                .get_mouse_pos(MouseMode::Pass)
+                .filter(|_| active)
+                .map(position),
+            mouse: window.get_mouse_pos(MouseMode::Discard).map(position),
+            size: window.get_size(),
+            buttons: MOUSE_BUTTONS
+                .into_iter()
+                .filter(|button| window.get_mouse_down(*button))
+                .collect(),
+            scroll: window
+                .get_scroll_wheel()
+                .filter(|_| active)
+                .map_or(0.0, |(_, scroll)| scroll as f64),
+        }
+    }
+}
+
+///
+/// Keys and mouse buttons, which aren't serialisable themselves, saved and loaded by name.
+///
+mod names {
+    use std::fmt::Debug;
+
+    use minifb::{Key, MouseButton};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use super::{KEYS, MOUSE_BUTTONS};
+
+    pub trait Named: Debug + Copy + 'static {
+        const ALL: &'static [Self];
+    }
+
+    impl Named for Key {
+        const ALL: &'static [Key] = &KEYS;
+    }
+
+    impl Named for MouseButton {
+        const ALL: &'static [MouseButton] = &MOUSE_BUTTONS;
+    }
+
+    pub fn serialize<T: Named, S: Serializer>(
+        items: &[T],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(items.iter().map(|item| format!("{:?}", item)))
+    }
+
+    pub fn deserialize<'de, T: Named, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<T>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|name| {
+                T::ALL
+                    .iter()
+                    .find(|item| format!("{:?}", item) == *name)
+                    .copied()
+                    .ok_or_else(|| {
+                        D::Error::custom(format!("there's no key or button called {}", name))
+                    })
+            })
+            .collect()
+    }
+}
+
+///
+/// The keys and mouse buttons held down this frame, and the keys which were pressed or let go since the last update.
+///
+/// Keys can be checked directly, or through the named actions in `bindings`, so controls can be changed without
+/// touching the code that reads them.
+/// - `mouse_sensitivity` is how far `mouse_look` turns for each pixel the mouse moves, in radians
+/// - `capture_mouse` hides the cursor and pins `mouse_position` to the middle of the window, for first person
+///   controls where the mouse turns the camera and clicks aim down the crosshair
+///
+/// Windows can't move the cursor back to the middle themselves, so capturing is emulated. The cursor keeps being
+/// tracked after it leaves the window, so turning doesn't stop at its edge, but it can still run into the edge of the
+/// screen.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Input {
+    pub bindings: Bindings,
+    pub mouse_sensitivity: f64,
+    pub capture_mouse: bool,
+    down: Vec<Key>,
+    pressed: Vec<Key>,
+    released: Vec<Key>,
+    mouse: Option<Vec2>,
+    // Where the cursor really is, even outside the window, for measuring how far it moves
+    cursor: Option<Vec2>,
+    mouse_delta: Vec2,
+    scroll: f64,
+    mouse_buttons: Vec<MouseButton>,
+    mouse_pressed: Vec<MouseButton>,
+    mouse_released: Vec<MouseButton>,
+}
+
+impl Default for Input {
+    fn default() -> Self {
+        Input::new(Bindings::default())
+    }
+}
+
+impl Input {
+    pub fn new(bindings: Bindings) -> Input {
+        Input {
+            bindings,
+            mouse_sensitivity: MOUSE_SENSITIVITY,
+            capture_mouse: false,
+            down: Vec::new(),
+            pressed: Vec::new(),
+            released: Vec::new(),
+            mouse: None,
+            cursor: None,
+            mouse_delta: Vec2::default(),
+            scroll: 0.0,
+            mouse_buttons: Vec::new(),
+            mouse_pressed: Vec::new(),
+            mouse_released: Vec::new(),
+        }
+    }
+
+    ///
+    /// Reads the state of the keyboard and mouse from a minifb window, as with `update`.
+    ///
+    pub fn read(&mut self, window: &mut Window) {
+        self.update(InputFrame::read(window));
+    }
+
+    ///
+    /// Moves on to a new frame of keyboard and mouse state read from a window. What's held down replaces last frame's,
+    /// but keys and buttons pressed or let go, and how far the mouse moved or scrolled, build up over every frame until
+    /// `clear_edges` is called, so none are missed if they aren't checked every frame.
+    ///
+    pub fn update(&mut self, frame: InputFrame) {
+        self.down = frame.keys_down;
+        add_new(&mut self.pressed, frame.keys_pressed);
+        add_new(&mut self.released, frame.keys_released);
+
+        let mouse = if self.capture_mouse {
+            let (width, height) = frame.size;
+            Some(Vec2::new(width as f64 / 2.0, height as f64 / 2.0))
+        } else {
+            frame.mouse
+        };
+        self.update_mouse(frame.cursor, mouse, frame.buttons);
+        self.scroll += frame.scroll;
+    }
+
+    ///
+    /// Moves the mouse on to a new frame, working out how far it moved and which buttons changed since the last one.
+    ///
+    fn update_mouse(
+        &mut self,
+        cursor: Option<Vec2>,
+        mouse: Option<Vec2>,
+        buttons: Vec<MouseButton>,
+    ) {
+        if let (Some(previous), Some(current)) = (self.cursor, cursor) {
+            self.mouse_delta = self.mouse_delta + (current - previous);
+        }
+        self.cursor = cursor;
+        self.mouse = mouse;
+
+        let previous = std::mem::replace(&mut self.mouse_buttons, buttons);
+        let pressed = self
+            .mouse_buttons
+            .iter()
+            .filter(|button| !previous.contains(button))
+            .copied()
+            .collect();
+        let released = previous
+            .into_iter()
+            .filter(|button| !self.mouse_buttons.contains(button))
+            .collect();
+        add_new(&mut self.mouse_pressed, pressed);
+        add_new(&mut self.mouse_released, released);
+    }
+
+    ///
+    /// Forgets the keys and buttons pressed and let go, and how far the mouse moved and scrolled, once they've been
+    /// handled. `run` calls this after each of the app's updates.
+    ///
+    pub fn clear_edges(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+        self.mouse_delta = Vec2::default();
+        self.scroll = 0.0;
+        self.mouse_pressed.clear();
+        self.mouse_released.clear();
+    }
+
+    pub fn is_key_down(&self, key: Key) -> bool {
+        self.down.contains(&key)
+    }
+
+    ///
+    /// Returns whether a key was pressed since the last update. Holding a key down doesn't repeat it.
+    ///
+    pub fn is_key_pressed(&self, key: Key) -> bool {
+        self.pressed.contains(&key)
+    }
+
+    pub fn is_key_released(&self, key: Key) -> bool {
+        self.released.contains(&key)
+    }
+
+    ///
+    /// Returns every key held down this frame.
+    ///
+    pub fn keys(&self) -> &[Key] {
+        &self.down
+    }
+
+    ///
+    /// Returns where the mouse is in pixels from the top left of the window, or `None` if it's outside it.
+    ///
+    pub fn mouse_position(&self) -> Option<Vec2> {
+        self.mouse
+    }
+
+    ///
+    /// Returns how far the mouse moved since the last update in pixels, with Y increasing downwards. It's zero while
+    /// the window isn't focused.
+    ///
+    pub fn mouse_delta(&self) -> Vec2 {
+        self.mouse_delta
+    }
+
+    ///
+    /// Returns how far the mouse wheel was scrolled since the last update, positive when scrolled up, away from the
+    /// user.
+    ///
+    pub fn scroll(&self) -> f64 {
+        self.scroll
+    }
+
+    ///
+    /// Turns the mouse's movement since the last update into a rotation to pass to `Camera::rotate`, scaled by the
+    /// mouse sensitivity. Moving the mouse right turns right, and moving it down looks down.
+    ///
+    pub fn mouse_look(&self) -> Vec3 {
+        Vec3::new(self.mouse_delta.y, -self.mouse_delta.x, 0.0) * self.mouse_sensitivity
+    }
+
+    pub fn is_mouse_down(&self, button: MouseButton) -> bool {
+        self.mouse_buttons.contains(&button)
+    }
+
+    ///
+    /// Returns whether a mouse button was clicked since the last update.
+    ///
+    pub fn is_mouse_pressed(&self, button: MouseButton) -> bool {
+        self.mouse_pressed.contains(&button)
+    }
+
+    pub fn is_mouse_released(&self, button: MouseButton) -> bool {
+        self.mouse_released.contains(&button)
+    }
+
+    ///
+    /// Returns 1 if the positive key is held, -1 if the negative key is held, and 0 if both or neither are, for
+    /// turning pairs of keys into movement along an axis.
+    ///
+    pub fn axis(&self, positive: Key, negative: Key) -> f64 {
+        axis_value(self.is_key_down(positive), self.is_key_down(negative))
+    }
+
+    ///
+    /// Returns whether any key bound to an action is held down.
+    ///
+    pub fn is_action_down(&self, action: &str) -> bool {
+        self.bindings
+            .keys(action)
+            .iter()
+            .any(|key| self.is_key_down(*key))
+    }
+
+    ///
+    /// Returns whether one of an action's keys was pressed since the last update.
+    ///
+    pub fn is_action_pressed(&self, action: &str) -> bool {
+        self.bindings
+            .keys(action)
+            .iter()
+            .any(|key| self.is_key_pressed(*key))
+    }
+
+    ///
+    /// Returns whether an action was let go since the last update. Letting go of one of its keys while another is still
+    /// held doesn't count.
+    ///
+    pub fn is_action_released(&self, action: &str) -> bool {
+        let keys = self.bindings.keys(action);
+        keys.iter().any(|key| self.is_key_released(*key)) && !self.is_action_down(action)
+    }
+
+    ///
+    /// Like `axis`, but for a pair of actions, such as "move_forward" and "move_back".
+    ///
+    pub fn action_axis(&self, positive: &str, negative: &str) -> f64 {
+        axis_value(self.is_action_down(positive), self.is_action_down(negative))
+    }
+}
+
+///
+/// Adds the items which aren't in a list already, keeping it free of repeats.
+///
+fn add_new<T: PartialEq>(list: &mut Vec<T>, items: Vec<T>) {
+    for item in items {
+        if !list.contains(&item) {
+            list.push(item);
+        }
+    }
+}
+
+fn axis_value(positive: bool, negative: bool) -> f64 {
+    match (positive, negative) {
+        (true, false) => 1.0,
+        (false, true) => -1.0,
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn input_queries() {
+        let mut input = Input {
+            down: vec![Key::W, Key::A, Key::D],
+            pressed: vec![Key::W],
+            released: vec![Key::Space],
+            ..Default::default()
+        };
+        input.update_mouse(None, Some(Vec2::new(10.0, 20.0)), vec![MouseButton::Left]);
+        assert!(input.is_key_down(Key::A) && !input.is_key_down(Key::S));
+        assert!(input.is_key_pressed(Key::W) && !input.is_key_pressed(Key::A));
+        assert!(input.is_key_released(Key::Space));
+        assert!(input.is_mouse_down(MouseButton::Left) && !input.is_mouse_down(MouseButton::Right));
+
+        // Opposite keys cancel out
+        assert_eq!(input.axis(Key::W, Key::S), 1.0);
+        assert_eq!(input.axis(Key::D, Key::A), 0.0);
+        assert_eq!(input.axis(Key::Down, Key::Up), 0.0);
+    }
+
+    #[test]
+    fn frames() {
+        let mut input = Input::default();
+        let frame = InputFrame {
+            keys_down: vec![Key::W],
+            keys_pressed: vec![Key::W],
+            cursor: Some(Vec2::new(5.0, 5.0)),
+            mouse: Some(Vec2::new(5.0, 5.0)),
+            size: (100, 50),
+            scroll: 1.0,
+            ..Default::default()
+        };
+        input.update(frame.clone());
+
+        // Presses and scrolling build up until they're handled, while what's held down is replaced
+        input.update(InputFrame {
+            keys_down: vec![],
+            keys_pressed: vec![],
+            keys_released: vec![Key::W],
+            cursor: Some(Vec2::new(8.0, 1.0)),
+            ..frame.clone()
+        });
+        assert!(input.is_key_pressed(Key::W) && input.is_key_released(Key::W));
+        assert!(!input.is_key_down(Key::W));
+        assert_eq!(input.scroll(), 2.0);
+        assert_eq!(input.mouse_delta(), Vec2::new(3.0, -4.0));
+        assert_eq!(input.mouse_position(), Some(Vec2::new(5.0, 5.0)));
+
+        // Captured mice stay in the middle of the window
+        input.capture_mouse = true;
+        input.update(frame);
+        assert_eq!(input.mouse_position(), Some(Vec2::new(50.0, 25.0)));
+    }
+
+    #[test]
+    fn actions() {
+        let mut input = Input {
+            down: vec![Key::W, Key::Up],
+            pressed: vec![Key::Up],
+            released: vec![Key::Space, Key::S],
+            ..Default::default()
+        };
+        assert!(input.is_action_down("move_forward") && !input.is_action_down("move_back"));
+        assert!(input.is_action_released("jump") && !input.is_action_pressed("jump"));
+        assert_eq!(input.action_axis("move_forward", "move_back"), 1.0);
+        assert!(!input.is_action_down("fly"));
+
+        // Actions can share keys, and letting go of S doesn't release moving back while W is still held
+        input.bindings.bind("move_forward", Key::Up);
+        input.bindings.rebind("move_back", [Key::S, Key::W]);
+        assert_eq!(input.bindings.keys("move_forward"), &[Key::W, Key::Up]);
+        assert!(input.is_action_pressed("move_forward"));
+        assert!(!input.is_action_released("move_back"));
+        assert_eq!(input.action_axis("move_forward", "move_back"), 0.0);
+
+        // Freeing up W leaves only the arrow moving forwards
+        input.bindings.unbind_key(Key::W);
+        assert_eq!(input.bindings.keys("move_back"), &[Key::S]);
+        assert_eq!(input.action_axis("move_forward", "move_back"), 1.0);
+
+        input.bindings.unbind("move_forward");
+        assert!(input.bindings.keys("move_forward").is_empty());
+        assert_eq!(input.bindings.actions().count(), 8);
+    }
+
+    #[test]
+    fn mouse() {
+        let mut input = Input::default();
+        input.update_mouse(Some(Vec2::new(100.0, 100.0)), None, vec![]);
+        assert_eq!(input.mouse_delta(), Vec2::default());
+
+        // Movement is measured from where the cursor really was, even outside the window
+        let centre = Some(Vec2::new(50.0, 50.0));
+        input.update_mouse(
+            Some(Vec2::new(110.0, 95.0)),
+            centre,
+            vec![MouseButton::Left],
+        );
+        assert_eq!(input.mouse_delta(), Vec2::new(10.0, -5.0));
+        assert_eq!(input.mouse_position(), centre);
+        assert!(
+            input.is_mouse_pressed(MouseButton::Left) && input.is_mouse_down(MouseButton::Left)
+        );
+
+        // Moving right and up turns right and looks up
+        input.mouse_sensitivity = 0.1;
+        let look = input.mouse_look();
+        assert!((look.x + 0.5).abs() < 1e-9 && (look.y + 1.0).abs() < 1e-9);
+
+        // Holding a button isn't a click once it's been handled, and letting go releases it
+        input.clear_edges();
+        let held = vec![MouseButton::Left];
+        input.update_mouse(Some(Vec2::new(110.0, 95.0)), centre, held);
+        assert!(!input.is_mouse_pressed(MouseButton::Left));
+        assert_eq!(input.mouse_look(), Vec3::default());
+        input.update_mouse(Some(Vec2::new(110.0, 95.0)), centre, vec![]);
+        assert!(input.is_mouse_released(MouseButton::Left));
+
+        // Movement and clicks build up over several reads until they're handled
+        input.update_mouse(Some(Vec2::new(120.0, 95.0)), centre, vec![]);
+        assert_eq!(input.mouse_delta(), Vec2::new(10.0, 0.0));
+        assert!(
+            input.is_mouse_released(MouseButton::Left) && !input.is_mouse_down(MouseButton::Left)
+        );
+
+        // Losing track of the cursor doesn't jump when it comes back
+        input.clear_edges();
+        input.update_mouse(None, None, vec![]);
+        input.update_mouse(Some(Vec2::new(0.0, 0.0)), None, vec![]);
+        assert_eq!(input.mouse_delta(), Vec2::default());
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{vec::vec3::Vec3, world::EntityId};
+
+///
+/// Ties a rigidbody to another body, or to a fixed point in the world if `other` is `None`. Joints are added with
+/// `World::add_joint`, and belong to the body they were added to.
+/// - `anchor` is where the joint attaches, in the body's local space
+/// - `other_anchor` is where it attaches to the other body, in that body's local space, or in world space if it's
+///   attached to the world
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Joint {
+    pub other: Option<EntityId>,
+    pub anchor: Vec3,
+    pub other_anchor: Vec3,
+    pub kind: JointKind,
+}
+
+///
+/// How a joint holds its bodies together.
+/// - `Distance` keeps the anchors `length` apart, like a rigid rod. A `rope` only stops them getting further apart
+/// - `Hinge` pins the anchors together, and only lets the bodies turn around `axis`. The axis is in each body's own
+///   space, or world space for the world, so bodies are best jointed while they're lined up with each other
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum JointKind {
+    Distance { length: f64, rope: bool },
+    Hinge { axis: Vec3 },
+}
+
+impl Joint {
+    pub fn distance(other: Option<EntityId>, length: f64) -> Joint {
+        Joint::new(
+            other,
+            JointKind::Distance {
+                length,
+                rope: false,
+            },
+        )
+    }
+
+    pub fn rope(other: Option<EntityId>, length: f64) -> Joint {
+        Joint::new(other, JointKind::Distance { length, rope: true })
+    }
+
+    pub fn hinge(other: Option<EntityId>, axis: Vec3) -> Joint {
+        Joint::new(
+            other,
+            JointKind::Hinge {
+                axis: axis.normalise(),
+            },
+        )
+    }
+
+    fn new(other: Option<EntityId>, kind: JointKind) -> Joint {
+        Joint {
+            other,
+            anchor: Vec3::default(),
+            other_anchor: Vec3::default(),
+            kind,
+        }
+    }
+
+    pub fn with_anchors(mut self, anchor: Vec3, other_anchor: Vec3) -> Joint {
+        self.anchor = anchor;
+        self.other_anchor = other_anchor;
+        self
+    }
+}
@@ -1,16 +1,221 @@
+// The maths, geometry, object data and rasteriser only need `core` and `alloc`, so frames can be drawn on embedded
+// framebuffers and other targets without an operating system. Everything else needs the `std` feature
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 // Module imports
+#[cfg(feature = "std")]
+mod animation;
+#[cfg(feature = "window")]
+mod app;
+#[cfg(feature = "std")]
+mod assets;
+#[cfg(feature = "window")]
+mod backend;
+mod batch;
+#[cfg(feature = "std")]
+mod behaviour;
+mod bvh;
+#[cfg(feature = "std")]
 mod camera;
+#[cfg(feature = "std")]
+mod character;
+#[cfg(feature = "std")]
+mod cloth;
+#[cfg(feature = "std")]
+mod collider;
+#[cfg(feature = "std")]
+mod component;
+mod config;
+#[cfg(feature = "std")]
+mod debug;
+mod decal;
+#[cfg(feature = "std")]
+mod deform;
+mod depth_of_field;
+mod environment;
+mod error;
+#[cfg(feature = "ffi")]
+mod ffi;
+#[cfg(not(any(feature = "std", test)))]
+mod float;
+mod framebuffer;
+mod geometry;
+#[cfg(feature = "std")]
+mod grid;
+mod hdr;
+#[cfg(feature = "std")]
+mod headless;
+#[cfg(feature = "window")]
+mod input;
+#[cfg(feature = "std")]
+mod joint;
+#[cfg(feature = "std")]
+mod light;
+#[cfg(feature = "std")]
+mod lod;
+mod mass;
 mod mat4;
 mod object;
+mod palette;
+#[cfg(feature = "std")]
+mod physics;
+#[cfg(feature = "std")]
+mod prefab;
+#[cfg(feature = "std")]
+mod present;
+#[cfg(feature = "std")]
+mod projectile;
+#[cfg(feature = "python")]
+mod python;
+mod quat;
+#[cfg(feature = "window")]
+mod recording;
 mod renderer;
+mod retro;
+#[cfg(feature = "std")]
 mod rigidbody;
+#[cfg(feature = "std")]
+mod scatter;
+#[cfg(feature = "std")]
+mod scene;
+#[cfg(feature = "window")]
+mod scene_registry;
+#[cfg(feature = "scripting")]
+mod script;
+#[cfg(feature = "std")]
+mod shadow;
+mod skeleton;
+#[cfg(feature = "std")]
+mod sprite;
+#[cfg(feature = "std")]
+mod streaming;
+#[cfg(feature = "std")]
+mod terminal;
+#[cfg(feature = "std")]
+mod terrain;
+#[cfg(feature = "std")]
+mod timing;
+#[cfg(feature = "std")]
+mod toon;
+mod transform;
+#[cfg(feature = "window")]
+mod ui;
 mod vec;
+#[cfg(feature = "std")]
+mod water;
+#[cfg(feature = "web")]
+mod web;
+#[cfg(feature = "std")]
 mod world;
 
+#[cfg(feature = "window")]
+pub use minifb::{Key, MouseButton};
+
+#[cfg(feature = "std")]
+pub use self::animation::{
+    Animation, AnimationClip, Channel, ClipPlayer, Interpolation, Keyframe, LoopMode,
+};
+#[cfg(feature = "window")]
+pub use self::app::{run, App, AppConfig, TimeKeys};
+#[cfg(feature = "std")]
+pub use self::assets::{AssetHandle, AssetManager};
+pub use self::batch::{TriangleBatch, TriangleOrder};
+#[cfg(feature = "std")]
+pub use self::behaviour::Behaviour;
+pub use self::bvh::Bvh;
+#[cfg(feature = "std")]
 pub use self::camera::Camera;
+#[cfg(feature = "std")]
+pub use self::character::CharacterController;
+#[cfg(feature = "std")]
+pub use self::cloth::Cloth;
+#[cfg(feature = "std")]
+pub use self::collider::{Collider, Contact};
+#[cfg(feature = "std")]
+pub use self::config::ConfigError;
+pub use self::config::{
+    Config, FillMode, Interlace, RendererConfig, Upscale, WindowBackend, WindowConfig, WindowScale,
+};
+#[cfg(feature = "std")]
+pub use self::debug::{DebugDraw, DebugLine};
+pub use self::decal::{Decal, DecalMode};
+#[cfg(feature = "std")]
+pub use self::deform::{Breathe, Ripple, VertexModifier};
+pub use self::depth_of_field::DepthOfField;
+pub use self::environment::{Environment, Fog};
+pub use self::error::Error;
+pub use self::framebuffer::{Colour, Framebuffer};
+pub use self::geometry::{
+    aabb::Aabb,
+    frustum::{Frustum, Plane},
+    hull::ConvexHull,
+    obb::Obb,
+    ray::Ray,
+};
+#[cfg(feature = "std")]
+pub use self::grid::Grid;
+pub use self::hdr::{Hdr, Tonemapper};
+#[cfg(feature = "std")]
+pub use self::headless::{FrameProfile, Headless};
+#[cfg(feature = "window")]
+pub use self::input::{Bindings, Input, InputFrame};
+#[cfg(feature = "std")]
+pub use self::joint::{Joint, JointKind};
+#[cfg(feature = "std")]
+pub use self::light::Light;
+#[cfg(feature = "std")]
+pub use self::lod::{Lod, LodLevel, LodMetric};
+pub use self::mass::MassProperties;
 pub use self::mat4::Mat4;
-pub use self::object::Object;
-pub use self::renderer::Renderer;
+pub use self::object::{Object, Quad, Texture, TextureFormat, TextureLayout};
+pub use self::palette::{Dither, Palette, PaletteCycle};
+#[cfg(feature = "std")]
+pub use self::physics::{Broadphase, Physics};
+#[cfg(feature = "std")]
+pub use self::prefab::Prefab;
+#[cfg(feature = "std")]
+pub use self::present::{PngRecorder, PresentTarget};
+#[cfg(feature = "std")]
+pub use self::projectile::Projectile;
+pub use self::quat::Quat;
+#[cfg(feature = "window")]
+pub use self::recording::{RecordMode, RecordedFrame, Recording, RecordingError};
+pub use self::renderer::{Blend, RenderStats, Renderer, TextureMapping};
+pub use self::retro::Retro;
+#[cfg(feature = "std")]
 pub use self::rigidbody::Rigidbody;
+#[cfg(feature = "std")]
+pub use self::scatter::{Scatter, ScatterInstance, ScatterModel};
+#[cfg(feature = "std")]
+pub use self::scene::{CameraSpawn, Scene, SceneEnvironment, SceneError, SceneObject, SceneRegion};
+#[cfg(feature = "window")]
+pub use self::scene_registry::SceneRegistry;
+#[cfg(feature = "scripting")]
+pub use self::script::{ScriptError, ScriptHost};
+#[cfg(feature = "std")]
+pub use self::shadow::BlobShadow;
+pub use self::skeleton::{Bone, Pose, Skeleton, Skin, VertexWeights};
+#[cfg(feature = "std")]
+pub use self::sprite::{Sprite, SpriteAnimation};
+#[cfg(feature = "std")]
+pub use self::streaming::RegionStreamer;
+#[cfg(feature = "std")]
+pub use self::terminal::Terminal;
+#[cfg(feature = "std")]
+pub use self::terrain::Terrain;
+#[cfg(feature = "std")]
+pub use self::timing::{DynamicResolution, FramePacing, FrameStats, TimeControl};
+#[cfg(feature = "std")]
+pub use self::toon::Toon;
+pub use self::transform::Transform;
+#[cfg(feature = "window")]
+pub use self::ui::{Panel, Ui};
 pub use self::vec::{vec2::Vec2, vec3::Vec3};
-pub use self::world::World;
+#[cfg(feature = "std")]
+pub use self::water::{Water, Wave};
+#[cfg(feature = "web")]
+pub use self::web::{animate, Canvas};
+#[cfg(feature = "std")]
+pub use self::world::{BodyHit, CollisionEvent, EntityId, Layer, ObjectMut, RayHit, World};
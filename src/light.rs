@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use crate::vec::vec3::Vec3;
+
+///
+/// A light source in the world. Colours are RGB values between 0 and 1.
+/// - `Directional` lights shine along `direction` from infinitely far away, like the sun
+/// - `Point` lights shine in every direction from `position`, fading out by `range`
+///
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Light {
+    Directional {
+        direction: Vec3,
+        colour: Vec3,
+        intensity: f64,
+    },
+    Point {
+        position: Vec3,
+        colour: Vec3,
+        intensity: f64,
+        range: f64,
+    },
+}
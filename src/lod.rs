@@ -0,0 +1,181 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::object::Object;
+
+///
+/// What the thresholds of a `Lod` are measured in.
+/// - `Distance` is the distance from the camera to the centre of the entity's bounds. Levels are used when the entity
+///   is at least their threshold away
+/// - `ScreenSize` is how much of the screen's width the entity's bounds cover, between 0 and 1. Levels are used when
+///   the entity covers no more than their threshold
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum LodMetric {
+    #[default]
+    Distance,
+    ScreenSize,
+}
+
+///
+/// A simplified version of an entity's object, and the threshold at which it replaces the more detailed ones.
+///
+#[derive(Clone)]
+pub struct LodLevel {
+    pub object: Object,
+    pub threshold: f64,
+}
+
+///
+/// A set of simplified objects drawn in place of an entity's own object as it gets further from the camera.
+///
+/// The entity's own object is level 0, and each added level is numbered after it, from most to least detailed. The
+/// level objects are drawn with the entity's transformation, so their own is ignored.
+///
+/// `hysteresis` stops entities popping back and forth when they sit right on a threshold - a level is only switched to
+/// once the entity is past its threshold by this fraction of it, and only switched away from once the entity is back
+/// by the same amount.
+///
+pub struct Lod {
+    levels: Vec<LodLevel>,
+    pub metric: LodMetric,
+    pub hysteresis: f64,
+    // Atomic so the level can be remembered while drawing, without stopping the world being shared between threads
+    current: AtomicUsize,
+}
+
+impl Clone for Lod {
+    fn clone(&self) -> Self {
+        Lod {
+            levels: self.levels.clone(),
+            metric: self.metric,
+            hysteresis: self.hysteresis,
+            current: AtomicUsize::new(self.current()),
+        }
+    }
+}
+
+impl Lod {
+    pub fn new(metric: LodMetric) -> Lod {
+        Lod {
+            levels: vec![],
+            metric,
+            hysteresis: 0.1,
+            current: AtomicUsize::new(0),
+        }
+    }
+
+    ///
+    /// Adds a less detailed level, keeping the levels sorted from most to least detailed.
+    ///
+    pub fn add_level(&mut self, object: Object, threshold: f64) {
+        let level = LodLevel { object, threshold };
+        let index = self
+            .levels
+            .iter()
+            .position(|other| self.key(other.threshold) > self.key(threshold))
+            .unwrap_or(self.levels.len());
+        self.levels.insert(index, level);
+        self.current.store(0, Ordering::Relaxed);
+    }
+
+    pub fn with_level(mut self, object: Object, threshold: f64) -> Lod {
+        self.add_level(object, threshold);
+        self
+    }
+
+    ///
+    /// The levels after the entity's own object, from most to least detailed.
+    ///
+    pub fn levels(&self) -> &[LodLevel] {
+        &self.levels
+    }
+
+    ///
+    /// The number of the level chosen by the last call to `select`, where 0 is the entity's own object.
+    ///
+    pub fn current(&self) -> usize {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    ///
+    /// Returns the object for a level, or `None` for level 0.
+    ///
+    pub fn object(&self, level: usize) -> Option<&Object> {
+        self.levels
+            .get(level.checked_sub(1)?)
+            .map(|level| &level.object)
+    }
+
+    ///
+    /// Picks the level to draw for a distance and screen size, remembering it so the next selection can apply
+    /// hysteresis.
+    ///
+    pub fn select(&self, distance: f64, screen_size: f64) -> usize {
+        // Work in terms of a value which grows as detail should drop, whatever the metric
+        let value = match self.metric {
+            LodMetric::Distance => distance,
+            LodMetric::ScreenSize => 1.0 / screen_size.max(f64::EPSILON),
+        };
+
+        let mut level = self.current.load(Ordering::Relaxed).min(self.levels.len());
+        while level < self.levels.len()
+            && value >= self.key(self.levels[level].threshold) * (1.0 + self.hysteresis)
+        {
+            level += 1;
+        }
+        while level > 0
+            && value < self.key(self.levels[level - 1].threshold) * (1.0 - self.hysteresis)
+        {
+            level -= 1;
+        }
+
+        self.current.store(level, Ordering::Relaxed);
+        level
+    }
+
+    fn key(&self, threshold: f64) -> f64 {
+        match self.metric {
+            LodMetric::Distance => threshold,
+            LodMetric::ScreenSize => 1.0 / threshold.max(f64::EPSILON),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::object::Texture;
+
+    fn object() -> Object {
+        Object::new(vec![], vec![], vec![], vec![], Texture::new(0, 0, vec![]))
+    }
+
+    #[test]
+    fn select_by_distance() {
+        let lod = Lod::new(LodMetric::Distance)
+            .with_level(object(), 20.0)
+            .with_level(object(), 10.0);
+        assert_eq!(lod.levels()[0].threshold, 10.0);
+
+        assert_eq!(lod.select(5.0, 0.0), 0);
+        assert_eq!(lod.select(25.0, 0.0), 2);
+        // Within the hysteresis band around 20, so the level stays where it was from either side
+        assert_eq!(lod.select(19.0, 0.0), 2);
+        assert_eq!(lod.select(17.0, 0.0), 1);
+        assert_eq!(lod.select(21.0, 0.0), 1);
+        assert_eq!(lod.select(0.0, 0.0), 0);
+    }
+
+    #[test]
+    fn select_by_screen_size() {
+        let lod = Lod::new(LodMetric::ScreenSize)
+            .with_level(object(), 0.5)
+            .with_level(object(), 0.1);
+
+        assert_eq!(lod.select(0.0, 0.8), 0);
+        assert_eq!(lod.select(0.0, 0.3), 1);
+        assert_eq!(lod.select(0.0, 0.05), 2);
+        assert!(lod.object(0).is_none());
+        assert!(lod.object(2).is_some());
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{mat4::Mat4, object::Object, vec::vec3::Vec3};
+
+///
+/// The volume, centre of mass and inertia tensor of a solid shape with an even density, worked out from a closed
+/// triangle mesh.
+/// - `volume` is the space enclosed by the mesh
+/// - `centre` is its centre of mass, in the mesh's own space
+/// - `inertia` is the inertia tensor of the shape around its centre of mass, for a mass of one. Multiplying it by a
+///   body's mass gives that body's inertia
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MassProperties {
+    pub volume: f64,
+    pub centre: Vec3,
+    pub inertia: Mat4,
+}
+
+impl MassProperties {
+    ///
+    /// Works out the mass properties of a closed mesh, given as vertices and triangles indexing into them.
+    ///
+    /// Each triangle forms a tetrahedron with the origin, whose volume is positive or negative depending on which way
+    /// the triangle faces. Adding them together cancels out everything outside the mesh, leaving the volume, centre
+    /// and spread of the solid it encloses. Meshes wound either way round give the same result, but meshes with holes
+    /// or overlapping parts don't enclose a solid, and give nonsense. Returns `None` if the mesh encloses no volume,
+    /// such as flat meshes and sprites.
+    ///
+    pub fn from_mesh(
+        vertices: &[Vec3],
+        faces: impl IntoIterator<Item = [usize; 3]>,
+    ) -> Option<MassProperties> {
+        let mut volume = 0.0;
+        let mut moment = Vec3::default();
+        // The covariance of the solid around the origin, the sum of x * xᵀ over every point inside it
+        let mut covariance = [[0.0; 3]; 3];
+
+        for face in faces {
+            let [a, b, c] = face.map(|index| vertices[index]);
+            let tetrahedron = a.dot(b.cross_product(c)) / 6.0;
+            let sum = a + b + c;
+            volume += tetrahedron;
+            moment = moment + sum * (tetrahedron / 4.0);
+
+            let points = [a, b, c, sum].map(|point| [point.x, point.y, point.z]);
+            for (i, row) in covariance.iter_mut().enumerate() {
+                for (j, value) in row.iter_mut().enumerate() {
+                    let products = points.iter().map(|p| p[i] * p[j]).sum::<f64>();
+                    *value += tetrahedron / 20.0 * products;
+                }
+            }
+        }
+
+        if volume.abs() < f64::EPSILON {
+            return None;
+        }
+
+        // Everything was summed with the same sign, so dividing through by the volume copes with inside out meshes
+        let centre = moment / volume;
+        let centre_values = [centre.x, centre.y, centre.z];
+        let mut spread = [[0.0; 3]; 3];
+        for (i, row) in spread.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = covariance[i][j] / volume - centre_values[i] * centre_values[j];
+            }
+        }
+
+        let trace = spread[0][0] + spread[1][1] + spread[2][2];
+        let mut inertia = Mat4::identity();
+        for (i, row) in spread.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                let identity = if i == j { trace } else { 0.0 };
+                inertia.m[i][j] = identity - value;
+            }
+        }
+
+        Some(MassProperties {
+            volume: volume.abs(),
+            centre,
+            inertia,
+        })
+    }
+
+    ///
+    /// Works out the mass properties of an object's mesh.
+    ///
+    pub fn from_object(object: &Object) -> Option<MassProperties> {
+        let faces = object.faces.iter().map(|face| {
+            let (a, b, c) = face.vertices;
+            [a, b, c]
+        });
+        MassProperties::from_mesh(&object.vertices, faces)
+    }
+
+    ///
+    /// Returns the inertia tensor around another point rather than the centre of mass, for a mass of one. Spinning
+    /// around a point off the centre is always harder.
+    ///
+    pub fn inertia_about(&self, point: Vec3) -> Mat4 {
+        let offset = self.centre - point;
+        let offset_values = [offset.x, offset.y, offset.z];
+        let squared = offset.dot(offset);
+        let mut inertia = self.inertia;
+        for (i, row) in inertia.m.iter_mut().take(3).enumerate() {
+            for (j, value) in row.iter_mut().take(3).enumerate() {
+                let identity = if i == j { squared } else { 0.0 };
+                *value += identity - offset_values[i] * offset_values[j];
+            }
+        }
+        inertia
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::{
+        geometry::hull::ConvexHull,
+        vec::vec3::{X_AXIS, Y_AXIS, Z_AXIS},
+    };
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn boxes_match_the_formula() {
+        let corners: Vec<Vec3> = (0..8)
+            .map(|i| {
+                let pick = |bit: usize, size: f64| if i & bit == 0 { 0.0 } else { size };
+                Vec3::new(pick(1, 6.0), pick(2, 1.0), pick(4, 1.0))
+            })
+            .collect();
+        let hull = ConvexHull::from_points(&corners).unwrap();
+        let mass = hull.mass_properties().unwrap();
+
+        assert!((mass.volume - 6.0).abs() < 1e-9);
+        assert_near(mass.centre, Vec3::new(3.0, 0.5, 0.5));
+        // The inertia of a box with sides x, y and z is (y² + z²) / 12 around X, and so on
+        assert_near(
+            mass.inertia.transform_direction(X_AXIS),
+            X_AXIS * (2.0 / 12.0),
+        );
+        assert_near(
+            mass.inertia.transform_direction(Y_AXIS),
+            Y_AXIS * (37.0 / 12.0),
+        );
+        assert_near(
+            mass.inertia.transform_direction(Z_AXIS),
+            Z_AXIS * (37.0 / 12.0),
+        );
+
+        // Moving the pivot to the end of the box makes it harder to swing
+        let end = mass.inertia_about(Vec3::new(0.0, 0.5, 0.5));
+        assert_near(
+            end.transform_direction(Y_AXIS),
+            Y_AXIS * (37.0 / 12.0 + 9.0),
+        );
+        assert_near(end.transform_direction(X_AXIS), X_AXIS * (2.0 / 12.0));
+    }
+
+    #[test]
+    fn off_centre_solids() {
+        // A pyramid's centre of mass is a quarter of the way up from its base, wherever its vertices bunch up
+        let pyramid = [
+            Vec3::new(-1.0, 0.0, -1.0),
+            Vec3::new(1.0, 0.0, -1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(-1.0, 0.0, 1.0),
+            Vec3::new(0.0, 4.0, 0.0),
+        ];
+        let hull = ConvexHull::from_points(&pyramid).unwrap();
+        let mass = hull.mass_properties().unwrap();
+        assert!((mass.volume - 16.0 / 3.0).abs() < 1e-9);
+        assert_near(mass.centre, Vec3::new(0.0, 1.0, 0.0));
+
+        // Turning the mesh inside out doesn't change anything
+        let inside_out = hull.faces().iter().map(|[a, b, c]| [*a, *c, *b]);
+        let flipped = MassProperties::from_mesh(hull.points(), inside_out).unwrap();
+        assert!((flipped.volume - mass.volume).abs() < 1e-9);
+        assert_near(flipped.centre, mass.centre);
+
+        // Flat meshes don't enclose anything
+        let flat = MassProperties::from_mesh(&pyramid, [[0, 1, 2], [0, 2, 3]]);
+        assert_eq!(flat, None);
+    }
+}
@@ -1,15 +1,25 @@
-use std::fmt::{Debug, Display};
+use core::fmt::{Debug, Display};
 
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::float::Float;
 use crate::vec::vec3::Vec3;
 
 ///
 /// An implementation of a 4x4 matrix. It can be used to apply transformations to vectors.
 ///
 /// An arbitrary number of transformations can be applied fluently. For example, the following
-/// applies a scaling, rotation, and translation to a vector in that order:
+/// applies a translation and then a rotation to a vector:
 ///
 /// ```
-/// Mat4::identity().translate(_).rotate(_).transform(vector)
+/// # use megavertex::{Mat4, Vec3};
+/// let vector = Vec3::new(1.0, 0.0, 0.0);
+/// let moved = Mat4::identity()
+///     .translate(Vec3::new(0.0, 0.0, 2.0))
+///     .rotate(Vec3::new(0.0, 1.0, 0.0), std::f64::consts::PI)
+///     .transform(vector);
+///
+/// assert!((moved.x + 1.0).abs() < 1e-9 && (moved.z + 2.0).abs() < 1e-9);
 /// ```
 ///
 #[derive(Debug, PartialEq, Copy, Clone)]
@@ -48,6 +58,22 @@ impl Mat4 {
         trans_mat.mult(self)
     }
 
+    ///
+    /// Computes a matrix with which to scale a vector along each axis.
+    ///
+    pub fn scale(self, vec: Vec3) -> Mat4 {
+        let scale_mat = Mat4 {
+            m: [
+                [vec.x, 0.0, 0.0, 0.0],
+                [0.0, vec.y, 0.0, 0.0],
+                [0.0, 0.0, vec.z, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        };
+
+        scale_mat.mult(self)
+    }
+
     ///
     /// Computes a matrix with which to rotate a vector.
     ///
@@ -114,6 +140,60 @@ impl Mat4 {
         res
     }
 
+    ///
+    /// Swaps the rows and columns of this matrix.
+    ///
+    pub fn transpose(self) -> Mat4 {
+        let mut res = Mat4::identity();
+        for i in 0..4 {
+            for j in 0..4 {
+                res.m[i][j] = self.m[j][i];
+            }
+        }
+        res
+    }
+
+    ///
+    /// Computes the inverse of this matrix using Gauss-Jordan elimination.
+    ///
+    /// Returns `None` if the matrix is singular.
+    ///
+    pub fn inverse(self) -> Option<Mat4> {
+        let mut a = self.m;
+        let mut inv = Mat4::identity().m;
+
+        for col in 0..4 {
+            // Pick the row with the largest value in this column to keep things numerically stable
+            let pivot = (col..4)
+                .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+                .unwrap_or(col);
+            if a[pivot][col].abs() < f64::EPSILON {
+                return None;
+            }
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+
+            let scale = a[col][col];
+            for j in 0..4 {
+                a[col][j] /= scale;
+                inv[col][j] /= scale;
+            }
+
+            for row in 0..4 {
+                if row == col {
+                    continue;
+                }
+                let factor = a[row][col];
+                for j in 0..4 {
+                    a[row][j] -= factor * a[col][j];
+                    inv[row][j] -= factor * inv[col][j];
+                }
+            }
+        }
+
+        Some(Mat4 { m: inv })
+    }
+
     ///
     /// Apply this matrix as a transformation to a vector.
     ///
@@ -121,18 +201,30 @@ impl Mat4 {
         let vec4 = [vec.x, vec.y, vec.z, 1.0];
         let mut product = [0.0, 0.0, 0.0, 0.0];
 
-        for i in 0..4 {
-            for j in 0..4 {
-                product[i] = product[i] + (vec4[j] * self.m[i][j]);
+        for (i, row) in self.m.iter().enumerate() {
+            for (j, component) in vec4.iter().enumerate() {
+                product[i] += component * row[j];
             }
         }
 
         Vec3::new(product[0], product[1], product[2])
     }
+
+    ///
+    /// Apply this matrix to a direction vector. Unlike `transform`, translation is ignored.
+    ///
+    pub fn transform_direction(self, vec: Vec3) -> Vec3 {
+        let m = self.m;
+        Vec3::new(
+            m[0][0] * vec.x + m[0][1] * vec.y + m[0][2] * vec.z,
+            m[1][0] * vec.x + m[1][1] * vec.y + m[1][2] * vec.z,
+            m[2][0] * vec.x + m[2][1] * vec.y + m[2][2] * vec.z,
+        )
+    }
 }
 
 impl Display for Mat4 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "\n{:?}\n{:?}\n{:?}\n{:?}\n",
@@ -143,7 +235,7 @@ impl Display for Mat4 {
 
 #[cfg(test)]
 mod test {
-    use std::f64::consts::PI;
+    use core::f64::consts::PI;
 
     use super::*;
 
@@ -220,6 +312,16 @@ mod test {
         assert_vec_eq(expected, result);
     }
 
+    #[test]
+    fn scale_vector() {
+        let expected = Vec3::new(2.0, 3.0, 4.0);
+        let result = Mat4::identity()
+            .scale(Vec3::new(2.0, 3.0, 4.0))
+            .transform(Vec3::new(1.0, 1.0, 1.0));
+
+        assert_eq!(expected, result);
+    }
+
     #[test]
     fn rotate_vector_about_x() {
         let expected = Vec3::new(1.0, -0.11950238978550387, 1.4091554842655063);
@@ -242,6 +344,29 @@ mod test {
         assert_vec_eq(expected, result)
     }
 
+    #[test]
+    fn invert_matrix() {
+        let mat = Mat4::identity()
+            .rotate(Vec3::new(1.0, 2.0, 3.0), 0.6)
+            .translate(Vec3::new(4.0, -2.0, 1.0));
+        let inverse = mat.inverse().unwrap();
+        let point = Vec3::new(3.0, 7.0, -5.0);
+
+        assert_vec_eq(point, inverse.transform(mat.transform(point)));
+    }
+
+    #[test]
+    fn invert_singular_matrix() {
+        let mat = Mat4 { m: [[0.0; 4]; 4] };
+        assert_eq!(mat.inverse(), None);
+    }
+
+    #[test]
+    fn transpose_matrix() {
+        let mat = Mat4::identity().translate(Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(mat.transpose().m[3], [1.0, 2.0, 3.0, 1.0]);
+    }
+
     ///
     /// Performs an equality assertion on the individual components of a vector, after rounding.
     /// This avoids test failures due to floating point inequality in more complex matrix calculations.
@@ -1,15 +1,96 @@
+#[cfg(feature = "gltf")]
+mod gltf;
+#[cfg(feature = "image")]
+mod image;
 pub mod texture;
-use crate::{Mat4, Vec2, Vec3};
-use std::{fs, io, num::ParseIntError, vec};
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::float::Float;
+use crate::{bvh::Bvh, geometry::aabb::Aabb, skeleton::Skin, Mat4, Vec2, Vec3};
+use alloc::{collections::BTreeMap, string::String, sync::Arc, vec, vec::Vec};
+use core::{error::Error, fmt::Display, num::ParseIntError};
+#[cfg(feature = "std")]
+use std::{fmt::Write as _, fs, io, path::Path};
 
-pub use self::texture::Texture;
+pub use self::texture::{Texture, TextureFormat, TextureLayout};
+
+// How far two faces can bend away from each other, relative to the size of the quad they make, or their texture
+// coordinates stray from a parallelogram, and still be treated as one quad
+const QUAD_TOLERANCE: f64 = 1e-6;
 
 #[derive(Debug)]
 pub enum ModelLoadError {
+    #[cfg(feature = "std")]
     IoError(io::Error),
     ParseError(ParseIntError),
+    /// A face on the given line had fewer than three vertices, or a vertex index of 0
+    MalformedFace {
+        line: usize,
+    },
+    /// A face refers to an element that doesn't exist
+    IndexOutOfRange {
+        face: usize,
+        element: &'static str,
+        index: usize,
+        len: usize,
+    },
+}
+
+impl Display for ModelLoadError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            ModelLoadError::IoError(error) => write!(f, "failed to read model: {}", error),
+            ModelLoadError::ParseError(error) => write!(f, "failed to parse face index: {}", error),
+            ModelLoadError::MalformedFace { line } => {
+                write!(
+                    f,
+                    "face on line {} needs at least three valid vertices",
+                    line
+                )
+            }
+            ModelLoadError::IndexOutOfRange {
+                face,
+                element,
+                index,
+                len,
+            } => write!(
+                f,
+                "face {} refers to {} {}, but the model only has {}",
+                face, element, index, len
+            ),
+        }
+    }
 }
 
+impl Error for ModelLoadError {}
+
+impl Clone for ModelLoadError {
+    fn clone(&self) -> Self {
+        match self {
+            // IO errors can't be cloned, so the copy keeps the kind of error and its message
+            #[cfg(feature = "std")]
+            ModelLoadError::IoError(error) => {
+                ModelLoadError::IoError(io::Error::new(error.kind(), error.to_string()))
+            }
+            ModelLoadError::ParseError(error) => ModelLoadError::ParseError(error.clone()),
+            ModelLoadError::MalformedFace { line } => ModelLoadError::MalformedFace { line: *line },
+            ModelLoadError::IndexOutOfRange {
+                face,
+                element,
+                index,
+                len,
+            } => ModelLoadError::IndexOutOfRange {
+                face: *face,
+                element,
+                index: *index,
+                len: *len,
+            },
+        }
+    }
+}
+
+#[cfg(feature = "std")]
 impl From<io::Error> for ModelLoadError {
     fn from(error: io::Error) -> Self {
         ModelLoadError::IoError(error)
@@ -22,13 +103,26 @@ impl From<ParseIntError> for ModelLoadError {
 }
 
 ///
-/// Holds data relating to a single face of an object - the tuples refer to lists of indexes
+/// Holds data relating to a single face of an object - the tuples refer to lists of indexes. `material` picks the
+/// texture the face is drawn with, as with `Object::face_texture`
 ///
 #[derive(Clone)]
 pub struct Face {
     pub vertices: (usize, usize, usize),
     pub tex_coords: (usize, usize, usize),
     pub normals: (usize, usize, usize),
+    pub material: usize,
+}
+
+///
+/// Two neighbouring faces which together make up a flat quad, from `Object::quads`. `faces` are the indexes of the two
+/// faces, and `vertices` and `tex_coords` index the quad's corners, in order around its edge.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Quad {
+    pub faces: (usize, usize),
+    pub vertices: [usize; 4],
+    pub tex_coords: [usize; 4],
 }
 
 ///
@@ -36,139 +130,995 @@ pub struct Face {
 /// - `vertices` are the 3D coordinates that make up the object
 /// - `tex_coords` are coordinates within the texture
 /// - `faces` is a list of the faces that make up the object
-///     - The data in a `Face` object is a set of indexes referring to vertices and texture coordinates - when each face is drawn,
-///         its texture and vertices must be accessed from their corresponding fields
-/// - `texture` is a pixel buffer containing a texture for the object
+///   - The data in a `Face` object is a set of indexes referring to vertices and texture coordinates - when each face is drawn,
+///     its texture and vertices must be accessed from their corresponding fields
+/// - `texture` is a pixel buffer containing a texture for the object, used by faces with material 0
+/// - `materials` are the textures for any further materials, so `materials[0]` is used by faces with material 1
 /// - `transformation` is the transformation applied to this object in world space
+/// - `source` is the name the object was loaded from, if it came from a file
+/// - `skin` binds the vertices to an animated skeleton, for objects loaded with bones
+///
+/// The geometry and textures are shared between clones, so spawning the same object many times doesn't copy its mesh.
+/// Changing them copies them for the object being changed first, e.g. with `Arc::make_mut(&mut object.vertices)`.
 ///
 #[derive(Clone)]
 pub struct Object {
-    pub vertices: Vec<Vec3>,
-    pub tex_coords: Vec<Vec2>,
-    pub normals: Vec<Vec3>,
-    pub faces: Vec<Face>,
-    pub texture: Texture,
+    pub vertices: Arc<Vec<Vec3>>,
+    pub tex_coords: Arc<Vec<Vec2>>,
+    pub normals: Arc<Vec<Vec3>>,
+    pub faces: Arc<Vec<Face>>,
+    pub texture: Arc<Texture>,
+    pub materials: Arc<Vec<Texture>>,
     pub transformation: Mat4,
+    pub source: Option<String>,
+    pub skin: Option<Skin>,
 }
 
 impl Object {
+    ///
+    /// Creates an object from raw geometry, with no transformation applied
+    ///
+    pub fn new(
+        vertices: Vec<Vec3>,
+        tex_coords: Vec<Vec2>,
+        normals: Vec<Vec3>,
+        faces: Vec<Face>,
+        texture: Texture,
+    ) -> Object {
+        Object {
+            vertices: Arc::new(vertices),
+            tex_coords: Arc::new(tex_coords),
+            normals: Arc::new(normals),
+            faces: Arc::new(faces),
+            texture: Arc::new(texture),
+            materials: Arc::default(),
+            transformation: Mat4::identity(),
+            source: None,
+            skin: None,
+        }
+    }
+
     ///
     /// Loads in a 3D model from a .obj file
     ///
+    /// Each material the file uses (with `usemtl`) is drawn with the `map_Kd` texture given for it in the file's
+    /// material libraries (from `mtllib`). Models without materials, and materials without a texture, use `name.png`.
+    ///
+    #[cfg(feature = "std")]
     pub fn from_obj(name: &str) -> Result<Object, ModelLoadError> {
         // IO operations
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("load_model", name).entered();
         let obj_path = String::from(name) + ".obj";
         let obj_str = fs::read_to_string(obj_path)?;
         let texture_path = String::from(name) + ".png";
-        let texture = Texture::load_from(&texture_path)?;
-
-        let mut vertices: Vec<Vec3> = vec![];
-        let mut tex_coords: Vec<Vec2> = vec![];
-        let mut normals: Vec<Vec3> = vec![];
-
-        let mut faces: Vec<Face> = vec![];
-
-        for line in obj_str.split("\r\n") {
-            let tokens: Vec<&str> = line.split(" ").into_iter().collect::<Vec<&str>>();
-            let line_type = tokens[0];
-            match line_type {
-                "v" => {
-                    // Parse each token as a float - if 3 floats are found on the line, add them to a new Vec3
-                    let mut vec_buffer: Vec<f64> = vec![];
-                    for token in tokens {
-                        if let Ok(f) = token.parse::<f64>() {
-                            vec_buffer.push(f)
-                        };
-                    }
-                    if vec_buffer.len() >= 3 {
-                        vertices.push(Vec3::new(vec_buffer[0], vec_buffer[1], vec_buffer[2]));
+
+        let placeholder = Texture::new(0, 0, vec![]);
+        let (mut object, materials) = parse_obj(&obj_str, placeholder)?;
+
+        // Material libraries are found relative to the model, and the textures they refer to relative to the library
+        let directory = Path::new(name).parent().unwrap_or(Path::new(""));
+        let mut maps = BTreeMap::new();
+        for library in &materials.libraries {
+            let path = directory.join(library);
+            let Ok(mtl_str) = fs::read_to_string(&path) else {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(library, "couldn't read material library");
+                continue;
+            };
+            let library_directory = path.parent().unwrap_or(directory);
+            for (material, map) in parse_mtl(&mtl_str) {
+                maps.insert(material, library_directory.join(map));
+            }
+        }
+
+        // The model's own texture is only loaded if something needs it, so models whose materials all have textures
+        // don't need one
+        let mut fallback = None;
+        let mut textures = vec![];
+        for material in &materials.names {
+            let texture = match (maps.get(material), &fallback) {
+                (Some(path), _) => Texture::load_from(&path.to_string_lossy())?,
+                (None, Some(texture)) => Texture::clone(texture),
+                (None, None) => fallback.insert(Texture::load_from(&texture_path)?).clone(),
+            };
+            textures.push(texture);
+        }
+        if textures.is_empty() {
+            textures.push(Texture::load_from(&texture_path)?);
+        }
+        object.texture = Arc::new(textures.remove(0));
+        object.materials = Arc::new(textures);
+        object.source = Some(String::from(name));
+        Ok(object)
+    }
+
+    ///
+    /// Loads in a 3D model from the contents of a .obj file and a PNG texture held in memory, e.g. from `include_bytes!`
+    ///
+    #[cfg(feature = "std")]
+    pub fn from_obj_bytes(obj: &[u8], texture: &[u8]) -> Result<Object, ModelLoadError> {
+        let obj_str = core::str::from_utf8(obj)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        let texture = Texture::from_bytes(texture)?;
+        Object::from_obj_str(obj_str, texture)
+    }
+
+    ///
+    /// Parses the contents of a .obj file, pairing it with an already loaded texture. This needs no files or PNG
+    /// decoding, so it works without the `std` feature, e.g. on a model from `include_str!`
+    ///
+    /// Materials are numbered in the order the file first uses them, and every face is drawn with `texture` until
+    /// textures for the further materials are added to `materials`.
+    ///
+    pub fn from_obj_str(obj_str: &str, texture: Texture) -> Result<Object, ModelLoadError> {
+        parse_obj(obj_str, texture).map(|(object, _)| object)
+    }
+
+    ///
+    /// Checks that every face refers to vertices, texture coordinates and normals that exist, so that
+    /// drawing the object can't index out of bounds.
+    ///
+    pub fn validate(&self) -> Result<(), ModelLoadError> {
+        let check =
+            |face: usize, element: &'static str, indices: (usize, usize, usize), len: usize| {
+                for index in [indices.0, indices.1, indices.2] {
+                    if index >= len {
+                        return Err(ModelLoadError::IndexOutOfRange {
+                            face,
+                            element,
+                            index,
+                            len,
+                        });
                     }
                 }
-                "vt" => {
-                    // As above
-                    let mut coord_buffer: Vec<f64> = vec![];
-                    for token in tokens {
-                        if let Ok(f) = token.parse::<f64>() {
-                            coord_buffer.push(f)
-                        }
-                    }
-                    if coord_buffer.len() >= 2 {
-                        tex_coords.push(Vec2::new(coord_buffer[0], coord_buffer[1]));
-                    }
+                Ok(())
+            };
+
+        let checked = self.faces.iter().enumerate().try_for_each(|(i, face)| {
+            check(i, "vertex", face.vertices, self.vertices.len())?;
+            check(
+                i,
+                "texture coordinate",
+                face.tex_coords,
+                self.tex_coords.len(),
+            )?;
+            check(i, "normal", face.normals, self.normals.len())
+        });
+        #[cfg(feature = "tracing")]
+        if let Err(error) = &checked {
+            tracing::warn!(%error, "rejected model");
+        }
+        checked
+    }
+
+    pub fn transform(&mut self, mat: Mat4) {
+        self.transformation = self.transformation.mult(mat);
+    }
+
+    ///
+    /// Computes the bounds of the object's vertices in its own local space, or `None` if it has no vertices.
+    ///
+    pub fn local_bounds(&self) -> Option<Aabb> {
+        Aabb::from_points(self.vertices.iter().copied())
+    }
+
+    ///
+    /// Computes one normal for each vertex, averaging the normals of every face corner that uses it. Vertices no face
+    /// uses are given a zero normal.
+    ///
+    pub fn vertex_normals(&self) -> Vec<Vec3> {
+        let mut normals = vec![Vec3::new(0.0, 0.0, 0.0); self.vertices.len()];
+        for face in self.faces.iter() {
+            let corners = [
+                (face.vertices.0, face.normals.0),
+                (face.vertices.1, face.normals.1),
+                (face.vertices.2, face.normals.2),
+            ];
+            for (vertex, normal) in corners {
+                if let (Some(sum), Some(normal)) =
+                    (normals.get_mut(vertex), self.normals.get(normal))
+                {
+                    *sum = *sum + *normal;
                 }
-                "vn" => {
-                    // As above
-                    let mut coord_buffer: Vec<f64> = vec![];
-                    for token in tokens {
-                        if let Ok(f) = token.parse::<f64>() {
-                            coord_buffer.push(f)
-                        }
-                    }
-                    if coord_buffer.len() >= 3 {
-                        normals.push(Vec3::new(coord_buffer[0], coord_buffer[1], coord_buffer[2]));
-                    }
+            }
+        }
+        for normal in &mut normals {
+            if normal.length() > 0.0 {
+                *normal = normal.normalise();
+            }
+        }
+        normals
+    }
+
+    ///
+    /// Finds the quads that were split into pairs of triangles, as quads in OBJ files are when they're loaded and as
+    /// modelling tools often export them. Faces are paired with the face after them when the two share an edge, lie
+    /// flat against each other, and have texture coordinates which form a parallelogram across the quad, so a texture
+    /// can be stretched across it without a bend along the cut. Both faces must use the same material, and each face
+    /// is in at most one quad.
+    ///
+    pub fn quads(&self) -> Vec<Quad> {
+        let mut quads = vec![];
+        let mut i = 0;
+        while i + 1 < self.faces.len() {
+            match self.quad(i, i + 1) {
+                Some(quad) => {
+                    quads.push(quad);
+                    i += 2;
                 }
-                "f" => {
-                    // As the renderer only deals with triangles, faces of more than three points must be split into triangles
-                    let mut slice_offset = 1;
+                None => i += 1,
+            }
+        }
+        quads
+    }
 
-                    // Loop over triangular faces on the current line
-                    while slice_offset + 2 < tokens.len() {
-                        let mut vertices: Vec<usize> = vec![];
-                        let mut tex_coords: Vec<usize> = vec![];
-                        let mut normals: Vec<usize> = vec![];
+    // Puts two faces back together into a quad, if they're the two halves of one
+    fn quad(&self, first: usize, second: usize) -> Option<Quad> {
+        let corners = |face: &Face| {
+            [
+                (face.vertices.0, face.tex_coords.0),
+                (face.vertices.1, face.tex_coords.1),
+                (face.vertices.2, face.tex_coords.2),
+            ]
+        };
+        if self.faces[first].material != self.faces[second].material {
+            return None;
+        }
+        let (a, b) = (corners(&self.faces[first]), corners(&self.faces[second]));
+        let vertex = |i: usize| self.vertices.get(i).copied();
+        let tex_coord = |i: usize| self.tex_coords.get(i).copied();
 
-                        // Parses three sets of vertex and texture coord from the current offset
-                        for i in 0..3 {
-                            let line_index = slice_offset + i;
+        // The shared edge runs one way around the first face and the other way around the second, so turn the first
+        // until the edge is its last two corners
+        let (p, q, r) = (0..3)
+            .map(|turn| (a[turn], a[(turn + 1) % 3], a[(turn + 2) % 3]))
+            .find(|&(_, q, r)| (0..3).any(|i| b[i].0 == r.0 && b[(i + 1) % 3].0 == q.0))?;
+        let s = *b.iter().find(|corner| corner.0 != q.0 && corner.0 != r.0)?;
+        let quad = [p, q, s, r];
 
-                            // .obj file indices start at 1, so make sure to normalise this type to zero indexed
-                            if let Ok(vertex) = get_face_data(tokens[line_index], 0) {
-                                vertices.push(vertex - 1);
-                            }
-                            if let Ok(tex_coord) = get_face_data(tokens[line_index], 1) {
-                                tex_coords.push(tex_coord - 1);
-                            }
-                            if let Ok(normal) = get_face_data(tokens[line_index], 2) {
-                                normals.push(normal - 1);
+        // Both halves have to agree on the texture along the shared edge
+        let same_coords = |corner: (usize, usize)| {
+            b.iter()
+                .filter(|other| other.0 == corner.0)
+                .all(|other| tex_coord(other.1) == tex_coord(corner.1))
+        };
+        if !same_coords(q) || !same_coords(r) {
+            return None;
+        }
+
+        let [pv, qv, sv, rv] = quad.map(|corner| vertex(corner.0));
+        let (pv, qv, sv, rv) = (pv?, qv?, sv?, rv?);
+        let normal = (qv - pv).cross_product(rv - pv);
+        let size = (sv - pv).length().max((rv - qv).length());
+        if normal.length() == 0.0 || normal.normalise().dot(sv - pv).abs() > size * QUAD_TOLERANCE {
+            return None;
+        }
+
+        let [pt, qt, st, rt] = quad.map(|corner| tex_coord(corner.1));
+        let (pt, qt, st, rt) = (pt?, qt?, st?, rt?);
+        let skew = (pt + st) - (qt + rt);
+        if skew.dot(skew).sqrt() > QUAD_TOLERANCE {
+            return None;
+        }
+
+        Some(Quad {
+            faces: (first, second),
+            vertices: quad.map(|corner| corner.0),
+            tex_coords: quad.map(|corner| corner.1),
+        })
+    }
+
+    ///
+    /// Returns the texture a face is drawn with - `texture` for material 0, or the entry in `materials` for any other.
+    /// Faces whose material has no texture fall back to `texture`.
+    ///
+    pub fn face_texture(&self, face: &Face) -> &Texture {
+        match face.material {
+            0 => &self.texture,
+            material => self.materials.get(material - 1).unwrap_or(&self.texture),
+        }
+    }
+
+    ///
+    /// Stores the object's texture and materials in another format and layout, as with `Texture::convert`.
+    ///
+    pub fn convert_textures(&mut self, format: TextureFormat, layout: TextureLayout) {
+        self.texture = Arc::new(self.texture.convert(format, layout));
+        let materials = self.materials.iter();
+        self.materials = Arc::new(
+            materials
+                .map(|material| material.convert(format, layout))
+                .collect(),
+        );
+    }
+
+    ///
+    /// Computes a box containing the object after its transformation is applied.
+    ///
+    pub fn world_bounds(&self) -> Option<Aabb> {
+        Some(self.local_bounds()?.transform(self.transformation))
+    }
+
+    ///
+    /// Builds a hierarchy over the object's faces in local space, for testing rays or shapes against its triangles.
+    ///
+    pub fn face_bvh(&self) -> Bvh<usize> {
+        let faces = self
+            .faces
+            .iter()
+            .enumerate()
+            .filter_map(|(i, face)| {
+                let (a, b, c) = face.vertices;
+                let bounds =
+                    Aabb::from_points([self.vertices[a], self.vertices[b], self.vertices[c]])?;
+                Some((i, bounds))
+            })
+            .collect();
+        Bvh::build(faces)
+    }
+
+    ///
+    /// Applies the current transformation directly to the object's vertices and normals, then resets it to the identity.
+    ///
+    /// Normals are transformed by the inverse-transpose of the transformation, so they stay perpendicular to their faces
+    /// under non-uniform scaling.
+    ///
+    pub fn bake_transform(&mut self) {
+        let mat = self.transformation;
+        for vertex in Arc::make_mut(&mut self.vertices) {
+            *vertex = mat.transform(*vertex);
+        }
+
+        if let Some(inverse) = mat.inverse() {
+            let normal_mat = inverse.transpose();
+            for normal in Arc::make_mut(&mut self.normals) {
+                *normal = normal_mat.transform_direction(*normal).normalise();
+            }
+        }
+
+        self.transformation = Mat4::identity();
+    }
+
+    ///
+    /// Appends another object's geometry to this one, so both can be drawn as a single unit.
+    ///
+    /// The other object's vertices are moved into this object's local space, so the merged geometry keeps its world
+    /// position. Merged faces keep their textures - the other object's materials are added to this one's, sharing any
+    /// texture this object already has.
+    ///
+    pub fn merge(&mut self, other: &Object) {
+        let to_local = self.transformation.inverse().unwrap_or(Mat4::identity());
+        let mat = to_local.mult(other.transformation);
+        let normal_mat = mat
+            .inverse()
+            .map(|inverse| inverse.transpose())
+            .unwrap_or(Mat4::identity());
+
+        let vertex_offset = self.vertices.len();
+        let tex_coord_offset = self.tex_coords.len();
+        let normal_offset = self.normals.len();
+
+        Arc::make_mut(&mut self.vertices)
+            .extend(other.vertices.iter().map(|vertex| mat.transform(*vertex)));
+        if let Some(skin) = &mut self.skin {
+            skin.weights.resize(self.vertices.len(), Default::default());
+        }
+        Arc::make_mut(&mut self.tex_coords).extend(other.tex_coords.iter().copied());
+        Arc::make_mut(&mut self.normals).extend(
+            other
+                .normals
+                .iter()
+                .map(|normal| normal_mat.transform_direction(*normal).normalise()),
+        );
+
+        let offset = |indices: (usize, usize, usize), offset: usize| {
+            (indices.0 + offset, indices.1 + offset, indices.2 + offset)
+        };
+        // Each of the other object's materials is matched to an identical texture here, or added as a new material
+        let materials: Vec<usize> = core::iter::once(&*other.texture)
+            .chain(other.materials.iter())
+            .map(|texture| {
+                let existing = core::iter::once(&*self.texture)
+                    .chain(self.materials.iter())
+                    .position(|own| same_texture(own, texture));
+                existing.unwrap_or_else(|| {
+                    let materials = Arc::make_mut(&mut self.materials);
+                    materials.push(texture.clone());
+                    materials.len()
+                })
+            })
+            .collect();
+
+        Arc::make_mut(&mut self.faces).extend(other.faces.iter().map(|face| Face {
+            vertices: offset(face.vertices, vertex_offset),
+            tex_coords: offset(face.tex_coords, tex_coord_offset),
+            normals: offset(face.normals, normal_offset),
+            material: materials.get(face.material).copied().unwrap_or(0),
+        }));
+    }
+
+    ///
+    /// Writes the object to `name.obj`, along with its materials in `name.mtl` and its texture in `name.png`, so it can
+    /// be loaded back with `from_obj` or inspected in other tools. Further materials' textures are written to
+    /// `name_1.png`, `name_2.png` and so on.
+    ///
+    /// The object's geometry is written in local space - call `bake_transform` first to export it as placed in the world.
+    ///
+    #[cfg(feature = "std")]
+    pub fn save_obj(&self, name: &str) -> Result<(), io::Error> {
+        // The .mtl file refers to the texture relative to itself
+        let file_name = Path::new(name)
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+            .unwrap_or(name);
+
+        let mut obj = String::new();
+        let mut mtl = String::new();
+
+        // Writing to a String can't fail, so the results are ignored
+        let texture_name = |material: usize| match material {
+            0 => String::from(file_name),
+            material => format!("{}_{}", file_name, material),
+        };
+        for material in 0..=self.materials.len() {
+            let _ = writeln!(mtl, "newmtl material{}", material);
+            let _ = writeln!(mtl, "Kd 1.0 1.0 1.0");
+            let _ = writeln!(mtl, "map_Kd {}.png", texture_name(material));
+        }
+
+        let _ = writeln!(obj, "mtllib {}.mtl", file_name);
+        let _ = writeln!(obj, "o {}", file_name);
+        for v in self.vertices.iter() {
+            let _ = writeln!(obj, "v {} {} {}", v.x, v.y, v.z);
+        }
+        for vt in self.tex_coords.iter() {
+            let _ = writeln!(obj, "vt {} {}", vt.x, vt.y);
+        }
+        for vn in self.normals.iter() {
+            let _ = writeln!(obj, "vn {} {} {}", vn.x, vn.y, vn.z);
+        }
+
+        // .obj indices start at 1
+        let has_tex_coords = !self.tex_coords.is_empty();
+        let has_normals = !self.normals.is_empty();
+        let tuple = |v: usize, vt: usize, vn: usize| match (has_tex_coords, has_normals) {
+            (true, true) => format!("{}/{}/{}", v + 1, vt + 1, vn + 1),
+            (true, false) => format!("{}/{}", v + 1, vt + 1),
+            (false, true) => format!("{}//{}", v + 1, vn + 1),
+            (false, false) => format!("{}", v + 1),
+        };
+        let mut material = None;
+        for face in self.faces.iter() {
+            if material != Some(face.material) {
+                material = Some(face.material);
+                let _ = writeln!(obj, "usemtl material{}", face.material);
+            }
+            let _ = writeln!(
+                obj,
+                "f {} {} {}",
+                tuple(face.vertices.0, face.tex_coords.0, face.normals.0),
+                tuple(face.vertices.1, face.tex_coords.1, face.normals.1),
+                tuple(face.vertices.2, face.tex_coords.2, face.normals.2),
+            );
+        }
+
+        fs::write(String::from(name) + ".obj", obj)?;
+        fs::write(String::from(name) + ".mtl", mtl)?;
+        for (material, texture) in self.materials.iter().enumerate() {
+            texture.save_png(&format!("{}_{}.png", name, material + 1))?;
+        }
+        self.texture.save_png(&(String::from(name) + ".png"))
+    }
+
+    ///
+    /// Merges vertices that lie within `epsilon` of each other, remapping faces to the remaining vertices.
+    ///
+    /// Returns the number of vertices removed.
+    ///
+    pub fn weld(&mut self, epsilon: f64) -> usize {
+        // Vertices are bucketed into a grid of `epsilon` sized cells, so only neighbouring cells need to be searched
+        let cell_size = epsilon.max(f64::EPSILON);
+        let cell = |vertex: Vec3| {
+            (
+                (vertex.x / cell_size).floor() as i64,
+                (vertex.y / cell_size).floor() as i64,
+                (vertex.z / cell_size).floor() as i64,
+            )
+        };
+
+        let mut grid: BTreeMap<(i64, i64, i64), Vec<usize>> = BTreeMap::new();
+        let mut welded: Vec<Vec3> = vec![];
+        let mut remap: Vec<usize> = Vec::with_capacity(self.vertices.len());
+
+        for vertex in self.vertices.iter() {
+            let (cx, cy, cz) = cell(*vertex);
+            let mut found = None;
+
+            'search: for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        if let Some(candidates) = grid.get(&(cx + dx, cy + dy, cz + dz)) {
+                            for &candidate in candidates {
+                                if (welded[candidate] - *vertex).length() <= epsilon {
+                                    found = Some(candidate);
+                                    break 'search;
+                                }
                             }
                         }
+                    }
+                }
+            }
+
+            let index = found.unwrap_or_else(|| {
+                welded.push(*vertex);
+                let index = welded.len() - 1;
+                grid.entry((cx, cy, cz)).or_default().push(index);
+                index
+            });
+            remap.push(index);
+        }
+
+        for face in Arc::make_mut(&mut self.faces) {
+            face.vertices = (
+                remap[face.vertices.0],
+                remap[face.vertices.1],
+                remap[face.vertices.2],
+            );
+        }
+
+        // Welded vertices keep the bone weights of the first vertex at their position
+        if let Some(skin) = &mut self.skin {
+            let mut weights = vec![Default::default(); welded.len()];
+            for (i, index) in remap.iter().enumerate().rev() {
+                if let Some(weight) = skin.weights.get(i) {
+                    weights[*index] = *weight;
+                }
+            }
+            skin.weights = weights;
+        }
+
+        let removed = self.vertices.len() - welded.len();
+        self.vertices = Arc::new(welded);
+        removed
+    }
+}
+
+// The material libraries a .obj file refers to, and the names of the materials it uses, in the order they're numbered
+struct ObjMaterials {
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    libraries: Vec<String>,
+    names: Vec<String>,
+}
+
+// Checks whether two textures hold the same pixels, so merged objects don't keep copies of one texture
+fn same_texture(a: &Texture, b: &Texture) -> bool {
+    (a.width, a.height, a.format(), a.layout()) == (b.width, b.height, b.format(), b.layout())
+        && a.pixels == b.pixels
+}
+
+// Parses the contents of a .obj file, along with the materials it uses
+fn parse_obj(obj_str: &str, texture: Texture) -> Result<(Object, ObjMaterials), ModelLoadError> {
+    let mut vertices: Vec<Vec3> = vec![];
+    let mut tex_coords: Vec<Vec2> = vec![];
+    let mut normals: Vec<Vec3> = vec![];
+
+    let mut faces: Vec<Face> = vec![];
+    let mut materials = ObjMaterials {
+        libraries: vec![],
+        names: vec![],
+    };
+    let mut material = 0;
+
+    // Faces without texture coordinates or normals refer to a default one, which is added if the file has none
+    let mut needs_default_tex_coord = false;
+    let mut needs_default_normal = false;
 
-                        let face = Face {
-                            vertices: (vertices[0], vertices[1], vertices[2]),
-                            tex_coords: (tex_coords[0], tex_coords[1], tex_coords[2]),
-                            normals: (normals[0], normals[1], normals[2]),
-                        };
-                        faces.push(face);
-                        slice_offset += 1;
+    for (i, line) in obj_str.lines().enumerate() {
+        let line_number = i + 1;
+        let tokens: Vec<&str> = line.split_whitespace().collect::<Vec<&str>>();
+        let Some(&line_type) = tokens.first() else {
+            continue;
+        };
+        match line_type {
+            "v" => {
+                // Parse each token as a float - if 3 floats are found on the line, add them to a new Vec3
+                let mut vec_buffer: Vec<f64> = vec![];
+                for token in tokens {
+                    if let Ok(f) = token.parse::<f64>() {
+                        vec_buffer.push(f)
+                    };
+                }
+                if vec_buffer.len() >= 3 {
+                    vertices.push(Vec3::new(vec_buffer[0], vec_buffer[1], vec_buffer[2]));
+                } else {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(
+                        line = line_number,
+                        "dropped `{line_type}` line with fewer than 3 values"
+                    );
+                }
+            }
+            "vt" => {
+                // As above
+                let mut coord_buffer: Vec<f64> = vec![];
+                for token in tokens {
+                    if let Ok(f) = token.parse::<f64>() {
+                        coord_buffer.push(f)
+                    }
+                }
+                if coord_buffer.len() >= 2 {
+                    tex_coords.push(Vec2::new(coord_buffer[0], coord_buffer[1]));
+                } else {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(
+                        line = line_number,
+                        "dropped `{line_type}` line with fewer than 2 values"
+                    );
+                }
+            }
+            "vn" => {
+                // As above
+                let mut coord_buffer: Vec<f64> = vec![];
+                for token in tokens {
+                    if let Ok(f) = token.parse::<f64>() {
+                        coord_buffer.push(f)
+                    }
+                }
+                if coord_buffer.len() >= 3 {
+                    normals.push(Vec3::new(coord_buffer[0], coord_buffer[1], coord_buffer[2]));
+                } else {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(
+                        line = line_number,
+                        "dropped `{line_type}` line with fewer than 3 values"
+                    );
+                }
+            }
+            "mtllib" => materials
+                .libraries
+                .extend(tokens[1..].iter().map(|&library| String::from(library))),
+            "usemtl" => {
+                // Faces before the first material keep one to themselves
+                if materials.names.is_empty() && !faces.is_empty() {
+                    materials.names.push(String::new());
+                }
+                let name = tokens[1..].join(" ");
+                material = match materials.names.iter().position(|other| *other == name) {
+                    Some(index) => index,
+                    None => {
+                        materials.names.push(name);
+                        materials.names.len() - 1
                     }
+                };
+            }
+            "f" => {
+                let mut tuples = vec![];
+                for token in &tokens[1..] {
+                    let (vertex, tex_coord, normal) = parse_face_tuple(token, line_number)?;
+                    needs_default_tex_coord |= tex_coord.is_none();
+                    needs_default_normal |= normal.is_none();
+                    tuples.push((vertex, tex_coord.unwrap_or(0), normal.unwrap_or(0)));
+                }
+                if tuples.len() < 3 {
+                    return Err(ModelLoadError::MalformedFace { line: line_number });
+                }
+
+                // Faces of more than three points are split into a fan of triangles around their first point. The two
+                // halves of a quad are kept next to each other, so `Object::quads` can put them back together
+                let first = tuples[0];
+                for pair in tuples[1..].windows(2) {
+                    faces.push(Face {
+                        vertices: (first.0, pair[0].0, pair[1].0),
+                        tex_coords: (first.1, pair[0].1, pair[1].1),
+                        normals: (first.2, pair[0].2, pair[1].2),
+                        material,
+                    });
                 }
-                _ => (),
             }
+            #[cfg(feature = "tracing")]
+            "l" | "p" => tracing::warn!(
+                line = line_number,
+                "ignored `{line_type}` element, only faces are drawn"
+            ),
+            _ => (),
         }
+    }
 
-        Ok(Object {
-            vertices,
-            tex_coords,
-            normals,
-            faces,
-            texture,
-            transformation: Mat4::identity(),
-        })
+    if needs_default_tex_coord && tex_coords.is_empty() {
+        tex_coords.push(Vec2::new(0.0, 0.0));
+    }
+    if needs_default_normal && normals.is_empty() {
+        normals.push(Vec3::new(0.0, 0.0, 1.0));
     }
 
-    pub fn transform(&mut self, mat: Mat4) {
-        self.transformation = self.transformation.mult(mat);
+    let object = Object::new(vertices, tex_coords, normals, faces, texture);
+    object.validate()?;
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        vertices = object.vertices.len(),
+        faces = object.faces.len(),
+        "parsed model"
+    );
+    Ok((object, materials))
+}
+
+// Finds the texture each material in a .mtl file is drawn with, from its `map_Kd` line. Options before the file name
+// (like `-s 1 1 1`) are skipped
+#[cfg(feature = "std")]
+fn parse_mtl(mtl_str: &str) -> Vec<(String, String)> {
+    let mut maps = vec![];
+    let mut material = None;
+    for line in mtl_str.lines() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["newmtl", name @ ..] => material = Some(name.join(" ")),
+            ["map_Kd", .., file] => {
+                if let Some(material) = &material {
+                    maps.push((material.clone(), String::from(*file)));
+                }
+            }
+            _ => (),
+        }
     }
+    maps
 }
 
 ///
-/// Extracts a 'usize' value from a slash delimited string at a given index2
+/// Parses a slash delimited `vertex/tex_coord/normal` tuple from a face line into zero indexed values.
+/// Texture coordinates and normals are optional, e.g. `1`, `1/2`, `1//3` and `1/2/3` are all valid.
 ///
-fn get_face_data(face_string: &str, index: usize) -> Result<usize, ParseIntError> {
-    let slash_split = face_string.split('/').collect::<Vec<&str>>();
-    if slash_split.len() > index {
-        return slash_split[index].parse::<usize>();
+fn parse_face_tuple(
+    face_string: &str,
+    line: usize,
+) -> Result<(usize, Option<usize>, Option<usize>), ModelLoadError> {
+    let mut parts = face_string.split('/');
+
+    // .obj file indices start at 1, so make sure to normalise them to zero indexed
+    let mut next_index = || -> Result<Option<usize>, ModelLoadError> {
+        match parts.next() {
+            None | Some("") => Ok(None),
+            Some(part) => match part.parse::<usize>()? {
+                0 => Err(ModelLoadError::MalformedFace { line }),
+                index => Ok(Some(index - 1)),
+            },
+        }
+    };
+
+    let vertex = next_index()?.ok_or(ModelLoadError::MalformedFace { line })?;
+    let tex_coord = next_index()?;
+    let normal = next_index()?;
+    Ok((vertex, tex_coord, normal))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::framebuffer::Colour;
+
+    fn triangle(offset: Vec3) -> Object {
+        Object::new(
+            vec![
+                Vec3::new(0.0, 0.0, 0.0) + offset,
+                Vec3::new(1.0, 0.0, 0.0) + offset,
+                Vec3::new(0.0, 1.0, 0.0) + offset,
+            ],
+            vec![Vec2::new(0.0, 0.0)],
+            vec![Vec3::new(0.0, 0.0, 1.0)],
+            vec![Face {
+                vertices: (0, 1, 2),
+                tex_coords: (0, 0, 0),
+                normals: (0, 0, 0),
+                material: 0,
+            }],
+            Texture::new(0, 0, vec![]),
+        )
+    }
+
+    #[test]
+    fn bake_transform() {
+        let mut object = triangle(Vec3::new(0.0, 0.0, 0.0));
+        object.transform(Mat4::identity().translate(Vec3::new(1.0, 2.0, 3.0)));
+        object.bake_transform();
+
+        assert_eq!(object.vertices[1], Vec3::new(2.0, 2.0, 3.0));
+        assert_eq!(object.normals[0], Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(object.transformation, Mat4::identity());
+    }
+
+    #[test]
+    fn vertex_normals() {
+        let mut object = triangle(Vec3::new(0.0, 0.0, 0.0));
+        Arc::make_mut(&mut object.vertices).push(Vec3::new(5.0, 5.0, 5.0));
+        Arc::make_mut(&mut object.normals).push(Vec3::new(1.0, 0.0, 0.0));
+        Arc::make_mut(&mut object.faces).push(Face {
+            vertices: (0, 1, 2),
+            tex_coords: (0, 0, 0),
+            normals: (1, 1, 0),
+            material: 0,
+        });
+
+        // Shared corners average their normals, and unused vertices have none
+        let normals = object.vertex_normals();
+        let diagonal = Vec3::new(1.0, 0.0, 1.0).normalise();
+        assert!((normals[0] - diagonal).length() < 1e-9);
+        assert_eq!(normals[2], Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(normals[3], Vec3::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn save_and_load_obj() {
+        let mut object = triangle(Vec3::new(0.0, 0.0, 1.0));
+        object.texture = Arc::new(Texture::new(1, 1, vec![0x336699]));
+        let name = std::env::temp_dir().join("megavertex-save-test");
+        let name = name.to_str().unwrap();
+        object.save_obj(name).unwrap();
+
+        let loaded = Object::from_obj(name).unwrap();
+        assert_eq!(loaded.vertices, object.vertices);
+        assert_eq!(loaded.faces[0].vertices, (0, 1, 2));
+        assert_eq!(loaded.texture.pixels, vec![0x336699]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn save_and_load_materials() {
+        let mut object = triangle(Vec3::new(0.0, 0.0, 0.0));
+        object.texture = Arc::new(Texture::solid(Colour::from_hex(0x336699)));
+        object.merge(&triangle(Vec3::new(1.0, 0.0, 0.0)));
+        Arc::make_mut(&mut object.faces)[1].material = 1;
+        object.materials = Arc::new(vec![Texture::solid(Colour::from_hex(0x996633))]);
+        let name = std::env::temp_dir().join("megavertex-materials-test");
+        let name = name.to_str().unwrap();
+        object.save_obj(name).unwrap();
+
+        // Each face is drawn with its own material's texture from the .mtl file
+        let loaded = Object::from_obj(name).unwrap();
+        assert_eq!(loaded.faces[0].material, 0);
+        assert_eq!(loaded.faces[1].material, 1);
+        assert_eq!(loaded.face_texture(&loaded.faces[0]).pixels, vec![0x336699]);
+        assert_eq!(loaded.face_texture(&loaded.faces[1]).pixels, vec![0x996633]);
+    }
+
+    #[test]
+    fn parse_face_tuples() {
+        assert_eq!(parse_face_tuple("3", 1).unwrap(), (2, None, None));
+        assert_eq!(parse_face_tuple("3//4", 1).unwrap(), (2, None, Some(3)));
+        assert_eq!(parse_face_tuple("3/2/4", 1).unwrap(), (2, Some(1), Some(3)));
+        assert!(matches!(
+            parse_face_tuple("0/1/1", 7),
+            Err(ModelLoadError::MalformedFace { line: 7 })
+        ));
+    }
+
+    #[test]
+    fn validate_out_of_range() {
+        let mut object = triangle(Vec3::new(0.0, 0.0, 0.0));
+        assert!(object.validate().is_ok());
+
+        Arc::make_mut(&mut object.faces)[0].vertices.2 = 3;
+        assert!(matches!(
+            object.validate(),
+            Err(ModelLoadError::IndexOutOfRange {
+                face: 0,
+                element: "vertex",
+                index: 3,
+                len: 3
+            })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn load_cube() {
+        let cube = Object::from_obj("./resources/cube").unwrap();
+        assert_eq!(cube.vertices.len(), 8);
+        assert_eq!(cube.faces.len(), 12);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn load_from_bytes() {
+        let object = Object::from_obj_bytes(
+            include_bytes!("../resources/cube.obj"),
+            include_bytes!("../resources/cube.png"),
+        )
+        .unwrap();
+        assert_eq!(object.faces.len(), 12);
+        assert_eq!(object.source, None);
+    }
+
+    #[test]
+    fn load_from_str() {
+        let object = Object::from_obj_str(
+            include_str!("../resources/cube.obj"),
+            Texture::new(1, 1, vec![0xffffff]),
+        )
+        .unwrap();
+        assert_eq!(object.vertices.len(), 8);
+        assert_eq!(object.faces.len(), 12);
+    }
+
+    #[test]
+    fn materials() {
+        let obj = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3\nusemtl brick\nf 1 3 4\nusemtl wood\nf 1 2 4\n\
+                   usemtl brick\nf 2 3 4";
+        let mut object =
+            Object::from_obj_str(obj, Texture::solid(Colour::from_hex(0xffffff))).unwrap();
+        let materials: Vec<_> = object.faces.iter().map(|face| face.material).collect();
+        assert_eq!(materials, vec![0, 1, 2, 1]);
+
+        // Materials without a texture are drawn with the object's own
+        object.materials = Arc::new(vec![Texture::solid(Colour::from_hex(0xff0000))]);
+        assert_eq!(object.face_texture(&object.faces[1]).pixels, vec![0xff0000]);
+        assert_eq!(object.face_texture(&object.faces[2]).pixels, vec![0xffffff]);
+
+        // Halves of a quad with different materials aren't put back together
+        assert!(object.quads().is_empty());
+    }
+
+    #[test]
+    fn quads() {
+        let cube = Object::from_obj_str(
+            include_str!("../resources/cube.obj"),
+            Texture::solid(Colour::from_hex(0xffffff)),
+        )
+        .unwrap();
+        let quads = cube.quads();
+        assert_eq!(quads.len(), 6);
+        assert_eq!(quads[0].faces, (0, 1));
+        assert_eq!(quads[0].vertices, [0, 1, 3, 2]);
+
+        // Quads in the file are split into a fan, which is put back together
+        let square =
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\nf 1/1 2/2 3/3 4/4";
+        let mut object =
+            Object::from_obj_str(square, Texture::solid(Colour::from_hex(0xffffff))).unwrap();
+        assert_eq!(object.faces[0].vertices, (0, 1, 2));
+        assert_eq!(object.faces[1].vertices, (0, 2, 3));
+        assert_eq!(object.quads()[0].vertices, [1, 2, 3, 0]);
+        assert_eq!(object.quads()[0].tex_coords, [1, 2, 3, 0]);
+
+        // Halves which bend along the cut, or whose texture coordinates don't line up, aren't quads
+        Arc::make_mut(&mut object.vertices)[3].z = 0.5;
+        assert!(object.quads().is_empty());
+        Arc::make_mut(&mut object.vertices)[3].z = 0.0;
+        Arc::make_mut(&mut object.tex_coords)[3] = Vec2::new(0.2, 1.0);
+        assert!(object.quads().is_empty());
+    }
+
+    #[test]
+    fn merge_objects() {
+        let mut object = triangle(Vec3::new(0.0, 0.0, 0.0));
+        let mut other = triangle(Vec3::new(0.0, 0.0, 0.0));
+        other.transform(Mat4::identity().translate(Vec3::new(1.0, 0.0, 0.0)));
+        object.merge(&other);
+
+        assert_eq!(object.vertices.len(), 6);
+        assert_eq!(object.faces.len(), 2);
+        assert_eq!(object.faces[1].vertices, (3, 4, 5));
+        assert_eq!(object.vertices[3], Vec3::new(1.0, 0.0, 0.0));
+
+        // Merged faces keep their own textures, and textures both objects use aren't copied
+        let mut red = triangle(Vec3::new(0.0, 0.0, 0.0));
+        red.texture = Arc::new(Texture::solid(Colour::from_hex(0xff0000)));
+        object.merge(&red);
+        object.merge(&red);
+        assert_eq!(object.materials.len(), 1);
+        let materials: Vec<_> = object.faces.iter().map(|face| face.material).collect();
+        assert_eq!(materials, vec![0, 0, 1, 1]);
+        assert_eq!(object.face_texture(&object.faces[3]).pixels, vec![0xff0000]);
+    }
+
+    #[test]
+    fn weld_vertices() {
+        let mut object = triangle(Vec3::new(0.0, 0.0, 0.0));
+        object.merge(&triangle(Vec3::new(1.0, 0.0, 0.0)));
+        let removed = object.weld(0.001);
+
+        assert_eq!(removed, 1);
+        assert_eq!(object.vertices.len(), 5);
+        assert_eq!(object.faces[1].vertices.0, 1);
     }
-    Ok(0)
 }
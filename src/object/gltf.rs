@@ -0,0 +1,239 @@
+use std::{collections::HashMap, fs, io, path::Path};
+
+use gltf::{buffer::Source, mesh::Mode, Gltf};
+
+use super::{Face, ModelLoadError, Object, Texture};
+use crate::{
+    skeleton::{Bone, Skeleton, Skin, VertexWeights},
+    Mat4, Quat, Transform, Vec2, Vec3,
+};
+
+impl Object {
+    ///
+    /// Loads in a 3D model, along with its skeleton and bone weights if it has them, from a `.glb` or `.gltf` file.
+    ///
+    /// As with `from_obj`, `name` is the path without an extension, and the texture is read from `name.png`.
+    /// Only the first mesh in the file is loaded - skinned meshes are preferred if there are several.
+    ///
+    pub fn from_gltf(name: &str) -> Result<Object, ModelLoadError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("load_model", name).entered();
+        let path = [".glb", ".gltf"]
+            .iter()
+            .map(|extension| String::from(name) + *extension)
+            .find(|path| Path::new(path).exists())
+            .ok_or_else(|| invalid_data(format!("no .glb or .gltf file found for {}", name)))?;
+        let gltf = Gltf::open(&path).map_err(invalid_data)?;
+        let texture = Texture::load_from(&(String::from(name) + ".png"))?;
+
+        // External buffers are referred to relative to the glTF file itself
+        let base = Path::new(&path).parent().unwrap_or(Path::new("."));
+        let mut buffers: Vec<Vec<u8>> = vec![];
+        for buffer in gltf.buffers() {
+            let data = match buffer.source() {
+                Source::Bin => gltf
+                    .blob
+                    .clone()
+                    .ok_or_else(|| invalid_data("missing binary chunk"))?,
+                Source::Uri(uri) if uri.starts_with("data:") => {
+                    return Err(invalid_data("embedded data URIs are not supported").into())
+                }
+                Source::Uri(uri) => fs::read(base.join(uri))?,
+            };
+            buffers.push(data);
+        }
+
+        let mut object = Object::parse_gltf(&gltf, &buffers, texture)?;
+        object.source = Some(String::from(name));
+        Ok(object)
+    }
+
+    ///
+    /// Loads in a 3D model from the contents of a `.glb` file and a PNG texture held in memory, as with `from_gltf`.
+    /// Everything has to be in the one file, as there's nowhere to read separate buffers from.
+    ///
+    pub fn from_glb_bytes(glb: &[u8], texture: &[u8]) -> Result<Object, ModelLoadError> {
+        let gltf = Gltf::from_slice(glb).map_err(invalid_data)?;
+        let texture = Texture::from_bytes(texture)?;
+        let mut buffers: Vec<Vec<u8>> = vec![];
+        for buffer in gltf.buffers() {
+            let data = match buffer.source() {
+                Source::Bin => gltf.blob.clone(),
+                Source::Uri(_) => None,
+            };
+            buffers.push(
+                data.ok_or_else(|| invalid_data("buffers outside the .glb file can't be loaded"))?,
+            );
+        }
+        Object::parse_gltf(&gltf, &buffers, texture)
+    }
+
+    ///
+    /// Reads the first mesh out of a parsed glTF file, whose buffers have already been loaded.
+    ///
+    fn parse_gltf(
+        gltf: &Gltf,
+        buffers: &[Vec<u8>],
+        texture: Texture,
+    ) -> Result<Object, ModelLoadError> {
+        let node = gltf
+            .nodes()
+            .find(|node| node.mesh().is_some() && node.skin().is_some())
+            .or_else(|| gltf.nodes().find(|node| node.mesh().is_some()))
+            .ok_or_else(|| invalid_data("file contains no meshes"))?;
+        let Some(mesh) = node.mesh() else {
+            return Err(invalid_data("file contains no meshes").into());
+        };
+
+        let mut vertices: Vec<Vec3> = vec![];
+        let mut tex_coords: Vec<Vec2> = vec![];
+        let mut normals: Vec<Vec3> = vec![];
+        let mut faces: Vec<Face> = vec![];
+        let mut weights: Vec<VertexWeights> = vec![];
+
+        for primitive in mesh.primitives() {
+            if primitive.mode() != Mode::Triangles {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(primitive = primitive.index(), mode = ?primitive.mode(), "dropped non-triangle primitive");
+                continue;
+            }
+            let reader =
+                primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| data.as_slice()));
+            let Some(positions) = reader.read_positions() else {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    primitive = primitive.index(),
+                    "dropped primitive without positions"
+                );
+                continue;
+            };
+
+            // Every attribute of a glTF vertex shares one index, so they're offset together
+            let offset = vertices.len();
+            vertices.extend(positions.map(|[x, y, z]| Vec3::new(x as f64, y as f64, z as f64)));
+            let count = vertices.len() - offset;
+
+            match reader.read_tex_coords(0) {
+                Some(coords) => tex_coords.extend(
+                    coords
+                        .into_f32()
+                        .map(|[u, v]| Vec2::new(u as f64, v as f64)),
+                ),
+                None => tex_coords.resize(vertices.len(), Vec2::new(0.0, 0.0)),
+            }
+            match reader.read_normals() {
+                Some(read) => {
+                    normals.extend(read.map(|[x, y, z]| Vec3::new(x as f64, y as f64, z as f64)))
+                }
+                None => normals.resize(vertices.len(), Vec3::new(0.0, 0.0, 1.0)),
+            }
+            match (reader.read_joints(0), reader.read_weights(0)) {
+                (Some(joints), Some(joint_weights)) => {
+                    weights.extend(joints.into_u16().zip(joint_weights.into_f32()).map(
+                        |(bones, bone_weights)| VertexWeights {
+                            bones: bones.map(|bone| bone as usize),
+                            weights: bone_weights.map(|weight| weight as f64),
+                        },
+                    ))
+                }
+                _ => weights.resize(vertices.len(), VertexWeights::default()),
+            }
+
+            let indices: Vec<usize> = match reader.read_indices() {
+                Some(indices) => indices
+                    .into_u32()
+                    .map(|index| index as usize + offset)
+                    .collect(),
+                None => (offset..offset + count).collect(),
+            };
+            #[cfg(feature = "tracing")]
+            if !indices.len().is_multiple_of(3) {
+                tracing::warn!(
+                    primitive = primitive.index(),
+                    "dropped {} indices left over after the last triangle",
+                    indices.len() % 3
+                );
+            }
+            for triangle in indices.chunks_exact(3) {
+                let indices = (triangle[0], triangle[1], triangle[2]);
+                faces.push(Face {
+                    vertices: indices,
+                    tex_coords: indices,
+                    normals: indices,
+                    material: 0,
+                });
+            }
+        }
+
+        let mut object = Object::new(vertices, tex_coords, normals, faces, texture);
+
+        if let Some(skin) = node.skin() {
+            // glTF nodes only list their children, so the parent of each node has to be worked out
+            let mut parents: HashMap<usize, usize> = HashMap::new();
+            for parent in gltf.nodes() {
+                for child in parent.children() {
+                    parents.insert(child.index(), parent.index());
+                }
+            }
+
+            let joints: Vec<_> = skin.joints().collect();
+            let bone_indices: HashMap<usize, usize> = joints
+                .iter()
+                .enumerate()
+                .map(|(bone, joint)| (joint.index(), bone))
+                .collect();
+            let inverse_binds: Vec<Mat4> = skin
+                .reader(|buffer| buffers.get(buffer.index()).map(|data| data.as_slice()))
+                .read_inverse_bind_matrices()
+                .map(|matrices| matrices.map(from_column_major).collect())
+                .unwrap_or_default();
+
+            let bones = joints
+                .iter()
+                .enumerate()
+                .map(|(i, joint)| {
+                    let ([tx, ty, tz], [rx, ry, rz, rw], [sx, sy, sz]) =
+                        joint.transform().decomposed();
+                    Bone {
+                        name: String::from(joint.name().unwrap_or_default()),
+                        parent: parents
+                            .get(&joint.index())
+                            .and_then(|parent| bone_indices.get(parent))
+                            .copied(),
+                        rest: Transform::new(
+                            Vec3::new(tx as f64, ty as f64, tz as f64),
+                            Quat::new(rx as f64, ry as f64, rz as f64, rw as f64),
+                            Vec3::new(sx as f64, sy as f64, sz as f64),
+                        ),
+                        inverse_bind: inverse_binds.get(i).copied().unwrap_or(Mat4::identity()),
+                    }
+                })
+                .collect();
+
+            object.skin = Some(Skin::new(Skeleton::new(bones), weights));
+        }
+
+        object.validate()?;
+        Ok(object)
+    }
+}
+
+///
+/// glTF stores matrices column by column, whereas `Mat4` is row by row
+///
+fn from_column_major(columns: [[f32; 4]; 4]) -> Mat4 {
+    let mut mat = Mat4::identity();
+    for (col, column) in columns.iter().enumerate() {
+        for (row, value) in column.iter().enumerate() {
+            mat.m[row][col] = *value as f64;
+        }
+    }
+    mat
+}
+
+fn invalid_data<E>(error: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
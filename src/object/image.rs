@@ -0,0 +1,56 @@
+use image::{DynamicImage, Rgb, RgbImage};
+
+use super::Texture;
+use crate::framebuffer::Colour;
+
+///
+/// Converts an image from the image crate into a texture, so textures can be generated or processed with the image
+/// crate before they're drawn. Textures have no alpha channel, so any transparency is dropped, as it is when loading
+/// PNGs.
+///
+impl From<DynamicImage> for Texture {
+    fn from(image: DynamicImage) -> Texture {
+        let image = image.into_rgb8();
+        let pixels = image
+            .pixels()
+            .map(|Rgb([r, g, b])| Colour::new(*r, *g, *b).to_0rgb())
+            .collect();
+        Texture::new(image.width() as usize, image.height() as usize, pixels)
+    }
+}
+
+impl Texture {
+    ///
+    /// Converts the texture into an RGB image from the image crate, for saving it in other formats or processing it
+    /// further.
+    ///
+    pub fn to_image(&self) -> DynamicImage {
+        let image = RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let colour = self.texel(x as usize, y as usize);
+            Rgb([colour.r, colour.g, colour.b])
+        });
+        DynamicImage::ImageRgb8(image)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use image::{Rgba, RgbaImage};
+
+    use super::*;
+
+    #[test]
+    fn images_round_trip() {
+        let mut image = RgbaImage::new(2, 1);
+        image.put_pixel(0, 0, Rgba([255, 128, 0, 255]));
+        // Transparency is dropped
+        image.put_pixel(1, 0, Rgba([1, 2, 3, 0]));
+
+        let texture = Texture::from(DynamicImage::ImageRgba8(image));
+        assert_eq!((texture.width, texture.height), (2, 1));
+        assert_eq!(texture.pixels, [0xff8000, 0x010203]);
+
+        let back = texture.to_image();
+        assert_eq!(Texture::from(back).pixels, texture.pixels);
+    }
+}
@@ -1,54 +1,330 @@
-use std::{fs::File, io};
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter},
+};
 
-use crate::vec::vec2::Vec2;
+use crate::{framebuffer::Colour, vec::vec2::Vec2};
+
+// Tiled textures are stored in square tiles of 2^TILE_SHIFT texels a side
+const TILE_SHIFT: usize = 3;
+const TILE_SIZE: usize = 1 << TILE_SHIFT;
+
+///
+/// How each texel of a texture is stored in its pixel buffer.
+///
+/// - `Rgb8` stores each texel in its own `u32`, as `0x00RRGGBB`. Textures are loaded in this format
+/// - `Rgb565` packs two texels into each `u32`, with 5 bits of red, 6 of green and 5 of blue, halving the memory a
+///   texture takes and how much of it has to be fetched while drawing, at the cost of some banding
+///
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum TextureFormat {
+    #[default]
+    Rgb8,
+    Rgb565,
+}
+
+///
+/// The order texels are stored in. `Linear` textures are stored a row at a time. `Tiled` textures are stored in 8x8
+/// tiles, a row of tiles at a time, so texels which are near each other on screen are near each other in memory even
+/// when a texture is drawn rotated or stretched.
+///
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum TextureLayout {
+    #[default]
+    Linear,
+    Tiled,
+}
 
 ///
-///Holds a pixel buffer, along with the dimensions of the image it represents
+/// Holds a pixel buffer, along with the dimensions of the image it represents
+///
+/// Textures are made with `Texture::new` from a row of `0x00RRGGBB` pixels at a time. `convert` stores them in a more
+/// compact `TextureFormat` or `TextureLayout` instead, after which `pixels` holds the packed texels - read them with
+/// `texel` rather than indexing `pixels` directly.
 ///
 #[derive(Clone)]
 pub struct Texture {
     pub width: usize,
     pub height: usize,
     pub pixels: Vec<u32>,
+    format: TextureFormat,
+    layout: TextureLayout,
 }
 
 impl Texture {
+    ///
+    /// Creates a texture from a row-by-row buffer of `0x00RRGGBB` pixels
+    ///
+    pub fn new(width: usize, height: usize, pixels: Vec<u32>) -> Texture {
+        Texture {
+            width,
+            height,
+            pixels,
+            format: TextureFormat::Rgb8,
+            layout: TextureLayout::Linear,
+        }
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    pub fn layout(&self) -> TextureLayout {
+        self.layout
+    }
+
     ///
     /// Sample a texture at `(x, y)`, where `x` and `y` are values between 0 and 1
     ///
-    pub fn sample(&self, coords: Vec2) -> u32 {
+    pub fn sample(&self, coords: Vec2) -> Colour {
         let x = coords.x * self.width as f64;
         let y = coords.y * self.height as f64;
         let mut i = self.width * y as usize + x as usize;
-        while i > self.pixels.len() - 1 {
-            i -= self.pixels.len();
+        if let (TextureFormat::Rgb8, TextureLayout::Linear) = (self.format, self.layout) {
+            while i > self.pixels.len() - 1 {
+                i -= self.pixels.len();
+            }
+            return Colour::from(self.pixels[i]);
+        }
+        let len = self.width * self.height;
+        while i > len - 1 {
+            i -= len;
+        }
+        self.texel(i % self.width, i / self.width)
+    }
+
+    ///
+    /// Reads the texel in column `x` and row `y`, whatever format and layout the texture is stored in
+    ///
+    pub fn texel(&self, x: usize, y: usize) -> Colour {
+        let address = self.address(x, y);
+        match self.format {
+            TextureFormat::Rgb8 => Colour::from(self.pixels[address]),
+            TextureFormat::Rgb565 => {
+                let packed = (self.pixels[address >> 1] >> ((address & 1) * 16)) as u16;
+                unpack_565(packed)
+            }
         }
-        self.pixels[i]
     }
 
+    ///
+    /// Stores the texture in another format and layout. Converting to `Rgb565` loses the low bits of each channel,
+    /// so converting back doesn't restore the original colours exactly.
+    ///
+    pub fn convert(&self, format: TextureFormat, layout: TextureLayout) -> Texture {
+        let mut texture = Texture {
+            width: self.width,
+            height: self.height,
+            pixels: vec![],
+            format,
+            layout,
+        };
+        let texels = match layout {
+            TextureLayout::Linear => self.width * self.height,
+            TextureLayout::Tiled => {
+                let tiles = |length: usize| length.div_ceil(TILE_SIZE);
+                tiles(self.width) * tiles(self.height) * TILE_SIZE * TILE_SIZE
+            }
+        };
+        texture.pixels = match format {
+            TextureFormat::Rgb8 => vec![0; texels],
+            TextureFormat::Rgb565 => vec![0; texels.div_ceil(2)],
+        };
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let colour = self.texel(x, y);
+                let address = texture.address(x, y);
+                match format {
+                    TextureFormat::Rgb8 => texture.pixels[address] = colour.to_0rgb(),
+                    TextureFormat::Rgb565 => {
+                        texture.pixels[address >> 1] |=
+                            (pack_565(colour) as u32) << ((address & 1) * 16)
+                    }
+                }
+            }
+        }
+        texture
+    }
+
+    ///
+    /// Returns how many bytes the texture's texels take up.
+    ///
+    pub fn size_in_bytes(&self) -> usize {
+        self.pixels.len() * core::mem::size_of::<u32>()
+    }
+
+    // Finds the index of a texel, counted in texels rather than in the words of the pixel buffer
+    fn address(&self, x: usize, y: usize) -> usize {
+        match self.layout {
+            TextureLayout::Linear => y * self.width + x,
+            TextureLayout::Tiled => {
+                let tiles_across = self.width.div_ceil(TILE_SIZE);
+                let tile = (y >> TILE_SHIFT) * tiles_across + (x >> TILE_SHIFT);
+                let within = ((y & (TILE_SIZE - 1)) << TILE_SHIFT) + (x & (TILE_SIZE - 1));
+                (tile << (2 * TILE_SHIFT)) + within
+            }
+        }
+    }
+
+    ///
+    /// A 1x1 texture of a single colour, for models which don't need an image
+    ///
+    pub fn solid(colour: Colour) -> Texture {
+        Texture::new(1, 1, vec![colour.to_0rgb()])
+    }
+}
+
+// Packs a colour into 16 bits, keeping the top 5 bits of red and blue and 6 of green
+fn pack_565(colour: Colour) -> u16 {
+    ((colour.r as u16 >> 3) << 11) | ((colour.g as u16 >> 2) << 5) | (colour.b as u16 >> 3)
+}
+
+// Unpacks a 16 bit colour, repeating the top bits of each channel into the bottom so white stays white
+fn unpack_565(packed: u16) -> Colour {
+    let (r, g, b) = (
+        (packed >> 11) as u8,
+        ((packed >> 5) & 0x3f) as u8,
+        (packed & 0x1f) as u8,
+    );
+    Colour::new(
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+    )
+}
+
+///
+/// Loading and saving PNGs needs the `std` feature. Without it, textures can be made from pixels with `Texture::new`.
+///
+#[cfg(feature = "std")]
+impl Texture {
     ///
     /// Loads a PNG texture from a given path into a u32 pixel buffer
     ///
     pub fn load_from(path: &str) -> Result<Texture, io::Error> {
-        let decoder = png::Decoder::new(File::open(path)?);
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("load_texture", path).entered();
+        Texture::from_bytes(&fs::read(path)?)
+    }
+
+    ///
+    /// Decodes a PNG texture held in memory into a u32 pixel buffer
+    ///
+    pub fn from_bytes(png_bytes: &[u8]) -> Result<Texture, io::Error> {
+        let mut decoder = png::Decoder::new(png_bytes);
+        // Expand palettes and low bit depths, so every pixel is made up of whole bytes
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
         let mut reader = decoder.read_info()?;
         let mut buf = vec![0; reader.output_buffer_size()];
 
-        let info = reader.next_frame(&mut buf).unwrap();
+        let info = reader.next_frame(&mut buf)?;
 
         let bytes = &buf[..info.buffer_size()];
+        let stride = info.color_type.samples();
         let mut pixels: Vec<u32> = vec![];
 
-        for i in (0..bytes.len() - 2).step_by(3) {
-            // Shift some bytes around to get an 32 bit colour value
-            let rgba = (bytes[i] as u32) << 16 | (bytes[i + 1] as u32) << 8 | bytes[i + 2] as u32;
-            pixels.push(rgba);
+        for pixel in bytes.chunks_exact(stride) {
+            // Greyscale images only have one colour channel, which is used for red, green and blue
+            let (r, g, b) = match info.color_type {
+                png::ColorType::Grayscale | png::ColorType::GrayscaleAlpha => {
+                    (pixel[0], pixel[0], pixel[0])
+                }
+                _ => (pixel[0], pixel[1], pixel[2]),
+            };
+            pixels.push(Colour::new(r, g, b).to_0rgb());
         }
 
-        Ok(Texture {
-            width: info.width as usize,
-            height: info.height as usize,
+        Ok(Texture::new(
+            info.width as usize,
+            info.height as usize,
             pixels,
-        })
+        ))
+    }
+
+    ///
+    /// Writes the texture to a given path as an RGB PNG
+    ///
+    pub fn save_png(&self, path: &str) -> Result<(), io::Error> {
+        let writer = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut bytes = Vec::with_capacity(self.width * self.height * 3);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let colour = self.texel(x, y);
+                bytes.extend_from_slice(&[colour.r, colour.g, colour.b]);
+            }
+        }
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&bytes)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // A texture which doesn't fill its last tiles, with a different colour in every texel
+    fn gradient() -> Texture {
+        let (width, height) = (11, 9);
+        let pixels = (0..width * height)
+            .map(|i| Colour::new((i * 2) as u8, (255 - i) as u8, (i * 7 % 256) as u8).to_0rgb())
+            .collect();
+        Texture::new(width, height, pixels)
+    }
+
+    #[test]
+    fn tiled_layout() {
+        let texture = gradient();
+        let tiled = texture.convert(TextureFormat::Rgb8, TextureLayout::Tiled);
+        assert_eq!(tiled.pixels.len(), 2 * 2 * 64);
+        for y in 0..texture.height {
+            for x in 0..texture.width {
+                assert_eq!(tiled.texel(x, y), texture.texel(x, y));
+            }
+        }
+
+        // Sampling wraps the same way whatever the layout
+        for coords in [
+            Vec2::new(0.5, 0.5),
+            Vec2::new(0.99, 0.1),
+            Vec2::new(1.2, 0.4),
+        ] {
+            assert_eq!(tiled.sample(coords), texture.sample(coords));
+        }
+        let back = tiled.convert(TextureFormat::Rgb8, TextureLayout::Linear);
+        assert_eq!(back.pixels, texture.pixels);
+    }
+
+    #[test]
+    fn rgb565() {
+        let texture = gradient();
+        let compact = texture.convert(TextureFormat::Rgb565, TextureLayout::Tiled);
+        assert_eq!(compact.size_in_bytes() * 2, 4 * 2 * 2 * 64);
+        assert_eq!(compact.format(), TextureFormat::Rgb565);
+
+        // Each channel keeps its top bits, and the extremes survive exactly
+        for y in 0..texture.height {
+            for x in 0..texture.width {
+                let (original, packed) = (texture.texel(x, y), compact.texel(x, y));
+                assert!(original.r.abs_diff(packed.r) <= 7);
+                assert!(original.g.abs_diff(packed.g) <= 3);
+                assert!(original.b.abs_diff(packed.b) <= 7);
+            }
+        }
+        let white = Texture::solid(Colour::new(255, 255, 255));
+        let black = Texture::solid(Colour::new(0, 0, 0));
+        for texture in [white, black] {
+            let packed = texture.convert(TextureFormat::Rgb565, TextureLayout::Linear);
+            assert_eq!(packed.pixels.len(), 1);
+            assert_eq!(packed.sample(Vec2::new(0.5, 0.5)), texture.texel(0, 0));
+        }
     }
 }
@@ -0,0 +1,255 @@
+use alloc::{vec, vec::Vec};
+use core::fmt::{self, Debug};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::float::Float;
+use crate::{framebuffer::Colour, object::Texture};
+
+// The most colours a palette can hold, so every pixel's entry fits in a byte as on indexed colour hardware
+const MAX_COLOURS: usize = 256;
+
+// How many bits of each channel pick a cell of the lookup table, which holds the nearest entry for each cell
+const LOOKUP_BITS: u32 = 5;
+
+// A 4x4 Bayer matrix, giving each pixel in a tile a different threshold so dithered gradients come out as even patterns
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+// How far ordered dithering pushes a channel either way, out of 255. Roughly the gap between neighbouring colours in
+// a small palette
+const DITHER_SPREAD: f64 = 32.0;
+
+///
+/// How colours between two palette entries are shown.
+/// - `None` snaps each pixel to the nearest entry, which gives flat bands across smooth gradients
+/// - `Ordered` nudges each pixel by a fixed pattern first, breaking bands up into a regular stipple that stays still as
+///   the camera moves
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Dither {
+    #[default]
+    None,
+    Ordered,
+}
+
+///
+/// A run of palette entries which rotate over time, like water or lava on old indexed colour hardware. Entries from
+/// `start` up to but not including `end` move along by one place `rate` times per second, and backwards when
+/// `rate` is negative, so anything drawn in them appears to flow without being redrawn.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PaletteCycle {
+    pub start: usize,
+    pub end: usize,
+    pub rate: f64,
+}
+
+///
+/// A fixed set of colours that finished frames are reduced to, for a retro look. Pixels are matched to the nearest
+/// colour by their top 5 bits in each channel, so entries closer together than that can't always be told apart. Only
+/// the first 256 colours are kept.
+///
+#[derive(Clone, PartialEq)]
+pub struct Palette {
+    colours: Vec<Colour>,
+    pub dither: Dither,
+    pub cycles: Vec<PaletteCycle>,
+    // The index of the nearest colour for each cell of RGB space
+    lookup: Vec<u8>,
+}
+
+impl Palette {
+    pub fn new(mut colours: Vec<Colour>) -> Palette {
+        colours.truncate(MAX_COLOURS);
+
+        let cells = 1 << LOOKUP_BITS;
+        let cell_size = 256 / cells;
+        let mut lookup = vec![0; cells * cells * cells];
+        if !colours.is_empty() {
+            for (cell, nearest) in lookup.iter_mut().enumerate() {
+                // Each cell is matched from its centre
+                let channel =
+                    |shift: u32| ((cell >> shift) % cells * cell_size + cell_size / 2) as u8;
+                let centre =
+                    Colour::new(channel(2 * LOOKUP_BITS), channel(LOOKUP_BITS), channel(0));
+                *nearest = nearest_colour(&colours, centre) as u8;
+            }
+        }
+        Palette {
+            colours,
+            dither: Dither::None,
+            cycles: vec![],
+            lookup,
+        }
+    }
+
+    ///
+    /// Creates a palette from every pixel of a texture in order, left to right then top to bottom, like the strips
+    /// palettes are often shared as.
+    ///
+    pub fn from_texture(texture: &Texture) -> Palette {
+        Palette::new(
+            (0..texture.height)
+                .flat_map(|y| (0..texture.width).map(move |x| texture.texel(x, y)))
+                .collect(),
+        )
+    }
+
+    pub fn with_dither(mut self, dither: Dither) -> Palette {
+        self.dither = dither;
+        self
+    }
+
+    pub fn with_cycle(mut self, cycle: PaletteCycle) -> Palette {
+        self.cycles.push(cycle);
+        self
+    }
+
+    pub fn colours(&self) -> &[Colour] {
+        &self.colours
+    }
+
+    ///
+    /// Returns the palette's colours as they're shown at a given time, with every cycle rotated into place.
+    ///
+    pub fn colours_at(&self, time: f64) -> Vec<Colour> {
+        let mut colours = self.colours.clone();
+        for cycle in &self.cycles {
+            let end = cycle.end.min(colours.len());
+            if cycle.start >= end {
+                continue;
+            }
+            let len = (end - cycle.start) as i64;
+            let steps = (time * cycle.rate).floor() as i64;
+            colours[cycle.start..end].rotate_right(steps.rem_euclid(len) as usize);
+        }
+        colours
+    }
+
+    ///
+    /// Returns the index of the entry a colour is shown as, before any cycling.
+    ///
+    pub fn index_of(&self, colour: Colour) -> usize {
+        let cell = |channel: u8| (channel >> (8 - LOOKUP_BITS)) as usize;
+        let i =
+            cell(colour.r) << (2 * LOOKUP_BITS) | cell(colour.g) << LOOKUP_BITS | cell(colour.b);
+        self.lookup[i] as usize
+    }
+
+    ///
+    /// Reduces a frame of 0RGB pixels, `width` pixels wide, to the palette's colours as they're shown at `time`.
+    /// Nothing is changed if the palette is empty.
+    ///
+    pub fn apply(&self, pixels: &mut [u32], width: usize, time: f64) {
+        if self.colours.is_empty() || width == 0 {
+            return;
+        }
+        let shown: Vec<u32> = self
+            .colours_at(time)
+            .iter()
+            .map(|colour| colour.to_0rgb())
+            .collect();
+        for (i, pixel) in pixels.iter_mut().enumerate() {
+            let mut colour = Colour::from(*pixel);
+            if self.dither == Dither::Ordered {
+                let offset = bayer_offset(i % width, i / width) * DITHER_SPREAD;
+                let nudge = |channel: u8| (channel as f64 + offset).clamp(0.0, 255.0) as u8;
+                colour = Colour::new(nudge(colour.r), nudge(colour.g), nudge(colour.b));
+            }
+            *pixel = shown[self.index_of(colour)];
+        }
+    }
+}
+
+impl Debug for Palette {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Palette")
+            .field("colours", &self.colours)
+            .field("dither", &self.dither)
+            .field("cycles", &self.cycles)
+            .finish()
+    }
+}
+
+///
+/// Returns how far ordered dithering nudges a pixel, between -0.5 and 0.5, from its place in a 4x4 Bayer matrix.
+///
+pub(crate) fn bayer_offset(x: usize, y: usize) -> f64 {
+    (BAYER[y % 4][x % 4] as f64 + 0.5) / 16.0 - 0.5
+}
+
+// The index of the colour closest to another, by the distance between them in RGB space
+fn nearest_colour(colours: &[Colour], to: Colour) -> usize {
+    let distance = |colour: &Colour| {
+        let channel = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        channel(colour.r, to.r) + channel(colour.g, to.g) + channel(colour.b, to.b)
+    };
+    (0..colours.len())
+        .min_by_key(|&i| distance(&colours[i]))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn greys() -> Palette {
+        Palette::new(vec![
+            Colour::from_hex(0x000000),
+            Colour::from_hex(0x808080),
+            Colour::from_hex(0xffffff),
+            Colour::from_hex(0xff0000),
+        ])
+    }
+
+    #[test]
+    fn quantises_to_nearest() {
+        let palette = greys();
+        let mut pixels = vec![0x101010, 0x7a8a80, 0xf0f0f0, 0xd02010];
+        palette.apply(&mut pixels, 4, 0.0);
+        assert_eq!(pixels, [0x000000, 0x808080, 0xffffff, 0xff0000]);
+
+        // An empty palette leaves the frame alone
+        let mut pixels = vec![0x123456];
+        Palette::new(vec![]).apply(&mut pixels, 1, 0.0);
+        assert_eq!(pixels, [0x123456]);
+    }
+
+    #[test]
+    fn dithering() {
+        // A flat grey halfway between black and the middle entry breaks up into a mix of both
+        let palette = greys().with_dither(Dither::Ordered);
+        let mut pixels = vec![0x404040; 16];
+        palette.apply(&mut pixels, 4, 0.0);
+        let dark = pixels.iter().filter(|pixel| **pixel == 0x000000).count();
+        assert!(dark > 0 && dark < 16, "{} of 16 dark", dark);
+        assert!(pixels
+            .iter()
+            .all(|pixel| [0x000000, 0x808080].contains(pixel)));
+    }
+
+    #[test]
+    fn cycling() {
+        let palette = greys().with_cycle(PaletteCycle {
+            start: 1,
+            end: 4,
+            rate: 2.0,
+        });
+        let at = |time| {
+            palette
+                .colours_at(time)
+                .iter()
+                .map(|colour| colour.to_0rgb())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(at(0.0), [0x000000, 0x808080, 0xffffff, 0xff0000]);
+        assert_eq!(at(0.5), [0x000000, 0xff0000, 0x808080, 0xffffff]);
+        // Three steps brings the cycle back round
+        assert_eq!(at(1.5), at(0.0));
+
+        // Pixels keep their index, and take on whatever colour it has at the time
+        let mut pixels = vec![0x808080];
+        palette.apply(&mut pixels, 1, 0.5);
+        assert_eq!(pixels, [0xff0000]);
+    }
+}
@@ -0,0 +1,314 @@
+use crate::{geometry::aabb::Aabb, vec::vec3::Vec3};
+
+///
+/// Settings for the simulation run by `World::step_physics`.
+/// - `gravity` is the acceleration applied to every rigidbody, pointing down the Y axis by default
+/// - `timestep` is how much time each step covers when the simulation is run by `World::advance_physics`, which is
+///   120 steps a second by default
+/// - `max_steps` is how many steps `World::advance_physics` takes at most in one go. If a frame takes longer than
+///   that, the rest of its time is dropped and the simulation slows down, rather than falling further and further
+///   behind
+/// - `contact_iterations` is how many times the impulses between touching bodies are worked out each step. More
+///   iterations let stacks of bodies settle faster
+/// - `joint_iterations` is how many times the joints are solved each step. More iterations keep long chains of
+///   joints tighter
+/// - `sleep_speed` and `sleep_time` control when resting bodies stop being simulated. Once every body in a group of
+///   touching or jointed bodies has been moving and spinning slower than `sleep_speed` for `sleep_time`, the whole
+///   group falls asleep until something disturbs it. An infinite `sleep_time` keeps every body awake
+/// - `broadphase` is how the pairs of bodies which might be touching are found each step
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Physics {
+    pub gravity: Vec3,
+    pub timestep: f64,
+    pub max_steps: u32,
+    pub contact_iterations: u32,
+    pub joint_iterations: u32,
+    pub sleep_speed: f64,
+    pub sleep_time: f64,
+    pub broadphase: Broadphase,
+    accumulator: f64,
+}
+
+///
+/// How the simulation finds the pairs of bodies which might be touching, before testing their colliders properly.
+/// - `SweepAndPrune` sorts bodies along the X axis, which copes well with bodies of any size, and is the default
+/// - `SpatialHash` sorts bodies into cubes `cell_size` across, and only compares bodies sharing a cube. It's faster for
+///   crowds of small bodies, like particles and debris, especially when many of them line up along the X axis. Bodies
+///   much larger than a cell are listed in a lot of cells, so the cell size should be a little bigger than a typical
+///   body. Bodies spanning so many cells that listing them would be slower than testing them against everything,
+///   like huge floors, are paired with everything instead
+///
+/// Which works best depends on the scene - the broadphase benchmark (`cargo bench`) compares them.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Broadphase {
+    SweepAndPrune,
+    SpatialHash { cell_size: f64 },
+}
+
+impl Broadphase {
+    ///
+    /// Finds the pairs of boxes which overlap, as indices into `bounds` with the smaller index first. Colliders without
+    /// bounds, like planes, are paired with everything.
+    ///
+    pub fn pairs(&self, bounds: &[Option<Aabb>]) -> Vec<(usize, usize)> {
+        match *self {
+            Broadphase::SweepAndPrune => sweep_and_prune(bounds),
+            Broadphase::SpatialHash { cell_size } => spatial_hash(bounds, cell_size),
+        }
+    }
+}
+
+impl Default for Physics {
+    fn default() -> Self {
+        Physics {
+            gravity: Vec3::new(0.0, -9.81, 0.0),
+            timestep: 1.0 / 120.0,
+            max_steps: 8,
+            contact_iterations: 8,
+            joint_iterations: 8,
+            sleep_speed: 0.1,
+            sleep_time: 0.5,
+            broadphase: Broadphase::SweepAndPrune,
+            accumulator: 0.0,
+        }
+    }
+}
+
+impl Physics {
+    ///
+    /// Adds `elapsed` to the time waiting to be simulated, and returns how many whole steps should be taken to catch
+    /// up. Whatever is left over is kept for next time.
+    ///
+    pub fn take_steps(&mut self, elapsed: f64) -> u32 {
+        if self.timestep <= 0.0 {
+            return 0;
+        }
+        self.accumulator += elapsed.max(0.0);
+        let mut steps = 0;
+        while self.accumulator >= self.timestep && steps < self.max_steps {
+            self.accumulator -= self.timestep;
+            steps += 1;
+        }
+        if self.accumulator >= self.timestep {
+            self.accumulator = 0.0;
+        }
+        steps
+    }
+
+    ///
+    /// Returns how far between the last two steps the time waiting to be simulated reaches, from 0 to 1. Rendering
+    /// bodies this far between where they were and where they are now keeps their motion smooth, however the frame
+    /// rate lines up with the timestep.
+    ///
+    pub fn alpha(&self) -> f64 {
+        if self.timestep <= 0.0 {
+            return 1.0;
+        }
+        (self.accumulator / self.timestep).clamp(0.0, 1.0)
+    }
+}
+
+// How far bodies can overlap before being pushed apart, and how much of the rest of the overlap is fixed each step.
+// Leaving a little overlap keeps resting contacts touching, and fixing it gradually stops bodies popping apart.
+const PENETRATION_SLOP: f64 = 0.01;
+const CORRECTION_RATE: f64 = 0.8;
+
+// The most cells a box is listed in by the spatial hash before it's paired with everything instead
+const MAX_HASHED_CELLS: f64 = 512.0;
+
+///
+/// Returns how far two bodies overlapping by `depth` should be pushed apart this step.
+///
+pub fn correction(depth: f64) -> f64 {
+    (depth - PENETRATION_SLOP).max(0.0) * CORRECTION_RATE
+}
+
+///
+/// Finds the pairs of colliders which might be touching, so only those need testing properly. Boxes are sorted along
+/// the X axis, and each is only compared with the boxes which start before it ends. Colliders without bounds, like
+/// planes, are paired with everything. Pairs are returned as indices into `bounds`, with the smaller index first.
+///
+pub fn sweep_and_prune(bounds: &[Option<Aabb>]) -> Vec<(usize, usize)> {
+    let mut pairs = vec![];
+    let mut sorted = split_unbounded(bounds, &mut pairs);
+    sorted.sort_by(|a, b| a.1.min.x.total_cmp(&b.1.min.x));
+
+    for (i, (a, a_bounds)) in sorted.iter().enumerate() {
+        for (b, b_bounds) in &sorted[i + 1..] {
+            if b_bounds.min.x > a_bounds.max.x {
+                break;
+            }
+            if a_bounds.intersects(b_bounds) {
+                pairs.push((*a.min(b), *a.max(b)));
+            }
+        }
+    }
+
+    // Two unbounded colliders would otherwise be paired twice
+    pairs.sort_unstable();
+    pairs.dedup();
+    pairs
+}
+
+///
+/// Finds the pairs of colliders which might be touching by sorting their boxes into a grid of cubes `cell_size` across,
+/// and comparing the boxes listed in each cube. Pairs are returned in the same way as `sweep_and_prune`, which is used
+/// instead if the cell size isn't positive.
+///
+/// Boxes which would be listed in more than `MAX_HASHED_CELLS` cells, including those reaching infinitely far or with
+/// bounds that aren't numbers, are paired with everything like colliders without bounds.
+///
+pub fn spatial_hash(bounds: &[Option<Aabb>], cell_size: f64) -> Vec<(usize, usize)> {
+    if !(cell_size > 0.0 && cell_size.is_finite()) {
+        return sweep_and_prune(bounds);
+    }
+    let cells = |aabb: &Aabb| {
+        let span = |min: f64, max: f64| (max / cell_size).floor() - (min / cell_size).floor() + 1.0;
+        let (min, max) = (aabb.min, aabb.max);
+        span(min.x, max.x) * span(min.y, max.y) * span(min.z, max.z)
+    };
+    let bounds: Vec<Option<Aabb>> = bounds
+        .iter()
+        .map(|aabb| aabb.filter(|aabb| cells(aabb) <= MAX_HASHED_CELLS))
+        .collect();
+    let mut pairs = vec![];
+    let boxes = split_unbounded(&bounds, &mut pairs);
+
+    // Each box is listed once for every cell it touches, and sorting the list brings the boxes in each cell together
+    let cell = |value: f64| (value / cell_size).floor() as i64;
+    let mut listed: Vec<((i64, i64, i64), usize)> = vec![];
+    for (slot, (_, aabb)) in boxes.iter().enumerate() {
+        let (min, max) = (aabb.min, aabb.max);
+        for x in cell(min.x)..=cell(max.x) {
+            for y in cell(min.y)..=cell(max.y) {
+                for z in cell(min.z)..=cell(max.z) {
+                    listed.push(((x, y, z), slot));
+                }
+            }
+        }
+    }
+    listed.sort_unstable();
+
+    for group in listed.chunk_by(|a, b| a.0 == b.0) {
+        for (i, (_, a)) in group.iter().enumerate() {
+            for (_, b) in &group[i + 1..] {
+                let ((a, a_bounds), (b, b_bounds)) = (boxes[*a], boxes[*b]);
+                if a_bounds.intersects(&b_bounds) {
+                    pairs.push((a.min(b), a.max(b)));
+                }
+            }
+        }
+    }
+
+    // Boxes sharing more than one cell are paired once for each
+    pairs.sort_unstable();
+    pairs.dedup();
+    pairs
+}
+
+///
+/// Pairs every collider without bounds with every other collider, and returns the boxes of the rest.
+///
+fn split_unbounded(bounds: &[Option<Aabb>], pairs: &mut Vec<(usize, usize)>) -> Vec<(usize, Aabb)> {
+    let mut boxes = vec![];
+    for (index, aabb) in bounds.iter().enumerate() {
+        match aabb {
+            Some(aabb) => boxes.push((index, *aabb)),
+            None => {
+                let others = (0..bounds.len()).filter(|other| *other != index);
+                pairs.extend(others.map(|other| (index.min(other), index.max(other))));
+            }
+        }
+    }
+    boxes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fixed_timesteps() {
+        let mut physics = Physics {
+            timestep: 0.1,
+            max_steps: 4,
+            ..Physics::default()
+        };
+        assert_eq!(physics.take_steps(0.25), 2);
+        assert!((physics.alpha() - 0.5).abs() < 1e-9);
+        assert_eq!(physics.take_steps(0.06), 1);
+        assert!((physics.alpha() - 0.1).abs() < 1e-9);
+
+        // Long frames are cut short
+        assert_eq!(physics.take_steps(10.0), 4);
+        assert_eq!(physics.alpha(), 0.0);
+    }
+
+    #[test]
+    fn broadphase_pairs() {
+        let cube = |x: f64| {
+            Some(Aabb::new(
+                Vec3::new(x, 0.0, 0.0),
+                Vec3::new(x + 1.0, 1.0, 1.0),
+            ))
+        };
+        let bounds = [cube(0.0), cube(5.0), cube(0.5), None, cube(5.5)];
+        let expected = vec![(0, 2), (0, 3), (1, 3), (1, 4), (2, 3), (3, 4)];
+        assert_eq!(sweep_and_prune(&bounds), expected);
+        for cell_size in [0.3, 1.0, 4.0, 0.0] {
+            assert_eq!(spatial_hash(&bounds, cell_size), expected);
+        }
+    }
+
+    #[test]
+    fn huge_boxes_in_spatial_hashes() {
+        let cube = |x: f64| {
+            Some(Aabb::new(
+                Vec3::new(x, 0.0, 0.0),
+                Vec3::new(x + 1.0, 1.0, 1.0),
+            ))
+        };
+        let huge = Aabb::new(Vec3::new(-1e12, -1.0, -1e12), Vec3::new(1e12, 0.5, 1e12));
+        let infinite = Aabb::new(
+            Vec3::new(f64::NEG_INFINITY, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 1.0),
+        );
+        let broken = Aabb::new(Vec3::new(f64::NAN, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+
+        // They're paired with everything rather than listed in every cell they cover
+        let bounds = [
+            cube(0.0),
+            Some(huge),
+            cube(5.0),
+            Some(infinite),
+            Some(broken),
+        ];
+        let pairs = spatial_hash(&bounds, 1.0);
+        for other in [0, 2, 3, 4] {
+            assert!(pairs.contains(&(1.min(other), 1.max(other))));
+        }
+        assert!(pairs.contains(&(0, 3)) && pairs.contains(&(3, 4)));
+        assert!(!pairs.contains(&(0, 2)));
+    }
+
+    #[test]
+    fn broadphases_agree() {
+        // A tangle of boxes of different sizes, many of them straddling cells and lined up along X
+        let bounds: Vec<Option<Aabb>> = (0..200)
+            .map(|i| {
+                let f = i as f64;
+                let min = Vec3::new((f * 0.37) % 3.0, (f * 1.91) % 7.0 - 3.5, (f * 0.53) % 5.0);
+                let size = 0.2 + (f * 0.71) % 1.3;
+                Some(Aabb::new(min, min + Vec3::new(size, size * 0.5, size)))
+            })
+            .collect();
+        let expected = sweep_and_prune(&bounds);
+        assert!(!expected.is_empty());
+        for cell_size in [0.25, 1.0, 2.5] {
+            let hashed = Broadphase::SpatialHash { cell_size }.pairs(&bounds);
+            assert_eq!(hashed, expected);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::rc::Rc;
+
+use crate::{
+    bvh::Bvh,
+    geometry::aabb::Aabb,
+    mat4::Mat4,
+    object::Object,
+    world::{EntityId, Layer, World},
+};
+
+type Attach = Rc<dyn Fn(&mut World, EntityId)>;
+
+///
+/// A template for entities which are spawned many times, like props or projectiles.
+///
+/// The object's bounds and face hierarchy are worked out once when the prefab is made, rather than every time it's
+/// spawned. `offset` is applied to the object before it's moved to where it's spawned, e.g. to lift a model so its
+/// base sits on the spawn point.
+///
+#[derive(Clone)]
+pub struct Prefab {
+    object: Object,
+    bounds: Option<Aabb>,
+    faces: Bvh<usize>,
+    pub offset: Mat4,
+    pub name: Option<String>,
+    pub tags: Vec<String>,
+    pub layer: Layer,
+    pub is_static: bool,
+    components: Vec<Attach>,
+}
+
+impl Prefab {
+    pub fn new(object: Object) -> Prefab {
+        Prefab {
+            bounds: object.local_bounds(),
+            faces: object.face_bvh(),
+            object,
+            offset: Mat4::identity(),
+            name: None,
+            tags: vec![],
+            layer: Layer::default(),
+            is_static: false,
+            components: vec![],
+        }
+    }
+
+    pub fn with_offset(mut self, offset: Mat4) -> Prefab {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_name(mut self, name: &str) -> Prefab {
+        self.name = Some(String::from(name));
+        self
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Prefab {
+        self.tags.push(String::from(tag));
+        self
+    }
+
+    pub fn with_layer(mut self, layer: Layer) -> Prefab {
+        self.layer = layer;
+        self
+    }
+
+    pub fn with_static(mut self, is_static: bool) -> Prefab {
+        self.is_static = is_static;
+        self
+    }
+
+    ///
+    /// Adds a component which every spawned entity gets its own copy of.
+    ///
+    pub fn with_component<T: Clone + Send + Sync + 'static>(mut self, component: T) -> Prefab {
+        self.components.push(Rc::new(move |world, id| {
+            world.insert_component(id, component.clone());
+        }));
+        self
+    }
+
+    pub fn object(&self) -> &Object {
+        &self.object
+    }
+
+    ///
+    /// The object's bounds in its own local space.
+    ///
+    pub fn bounds(&self) -> Option<Aabb> {
+        self.bounds
+    }
+
+    ///
+    /// The hierarchy over the object's faces in its own local space.
+    ///
+    pub fn faces(&self) -> &Bvh<usize> {
+        &self.faces
+    }
+
+    ///
+    /// Attaches the prefab's components to an entity.
+    ///
+    pub fn attach_components(&self, world: &mut World, id: EntityId) {
+        for attach in &self.components {
+            attach(world, id);
+        }
+    }
+}
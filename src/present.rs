@@ -0,0 +1,78 @@
+use crate::{error::Error, object::Texture, renderer::Renderer};
+
+///
+/// Somewhere finished frames are shown or kept - a window, a terminal, a canvas or a series of PNG files. Frames are
+/// rows of 0RGB pixels from the top left, as drawn into `Renderer::buffer`, so the loop drawing them can be written
+/// once whatever they end up in.
+///
+pub trait PresentTarget {
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize) -> Result<(), Error>;
+
+    ///
+    /// Presents the renderer's last frame.
+    ///
+    fn present_frame(&mut self, renderer: &Renderer) -> Result<(), Error> {
+        self.present(&renderer.buffer, renderer.width(), renderer.height())
+    }
+}
+
+///
+/// Saves every frame presented to it as a PNG, for recording a run to turn into a video or compare against later.
+/// `path` is called with each frame's index, counting from 0, to name its file.
+///
+pub struct PngRecorder {
+    path: Box<dyn Fn(usize) -> String>,
+    frames: usize,
+}
+
+impl PngRecorder {
+    pub fn new(path: impl Fn(usize) -> String + 'static) -> PngRecorder {
+        PngRecorder {
+            path: Box::new(path),
+            frames: 0,
+        }
+    }
+
+    ///
+    /// Returns how many frames have been saved.
+    ///
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+}
+
+impl PresentTarget for PngRecorder {
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize) -> Result<(), Error> {
+        let image = Texture::new(width, height, buffer.to_vec());
+        image
+            .save_png(&(self.path)(self.frames))
+            .map_err(Error::TextureError)?;
+        self.frames += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::headless::Headless;
+
+    #[test]
+    fn png_recorder() {
+        let directory = std::env::temp_dir();
+        let path = move |index: usize| {
+            let path = directory.join(format!("megavertex-recorded-{}.png", index));
+            path.to_str().unwrap().to_string()
+        };
+        let mut recorder = PngRecorder::new(path.clone());
+        let mut headless = Headless::new(30, 20);
+        headless.present_frames(2, &mut recorder).unwrap();
+        assert_eq!(recorder.frames(), 2);
+
+        for index in 0..2 {
+            let saved = Texture::load_from(&path(index)).unwrap();
+            assert_eq!((saved.width, saved.height), (30, 20));
+            let _ = std::fs::remove_file(path(index));
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{collider::Collider, object::Object, rigidbody::Rigidbody, vec::vec3::Vec3};
+
+///
+/// How to launch a projectile, like a thrown ball or an arrow, spawned with `World::spawn_projectile`.
+/// - `velocity` is how fast, and in what direction, it's launched
+/// - `gravity_scale` multiplies the world's gravity for the projectile, so 0 makes it fly straight, like a laser bolt
+/// - `lifetime` is how long the projectile lasts before it's removed from the world, in seconds. Projectiles without
+///   one last until they're removed by hand
+/// - `radius` is the size of the sphere it collides with
+/// - `mass` is how heavy it is, which decides how hard it knocks other bodies around
+///
+/// The path a projectile will take can be worked out before it's launched with `trajectory`, for drawing an aiming
+/// arc.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Projectile {
+    pub velocity: Vec3,
+    pub gravity_scale: f64,
+    pub lifetime: Option<f64>,
+    pub radius: f64,
+    pub mass: f64,
+}
+
+impl Projectile {
+    pub fn new(velocity: Vec3) -> Projectile {
+        Projectile {
+            velocity,
+            gravity_scale: 1.0,
+            lifetime: None,
+            radius: 0.1,
+            mass: 1.0,
+        }
+    }
+
+    pub fn with_gravity_scale(mut self, gravity_scale: f64) -> Projectile {
+        self.gravity_scale = gravity_scale;
+        self
+    }
+
+    pub fn with_lifetime(mut self, lifetime: f64) -> Projectile {
+        self.lifetime = Some(lifetime);
+        self
+    }
+
+    pub fn with_radius(mut self, radius: f64) -> Projectile {
+        self.radius = radius;
+        self
+    }
+
+    pub fn with_mass(mut self, mass: f64) -> Projectile {
+        self.mass = mass;
+        self
+    }
+
+    ///
+    /// Builds the rigidbody for the projectile, moving at its launch velocity with a sphere collider around the
+    /// object's origin.
+    ///
+    pub fn body(&self, object: Object) -> Rigidbody {
+        let mut body = Rigidbody::new(object)
+            .with_mass(self.mass)
+            .with_gravity_scale(self.gravity_scale)
+            .with_collider(Collider::Sphere {
+                centre: Vec3::default(),
+                radius: self.radius,
+            });
+        body.velocity = self.velocity;
+        body
+    }
+
+    ///
+    /// Returns where the projectile will be `time` after being launched from `start`, if it doesn't hit anything.
+    ///
+    pub fn position_at(&self, start: Vec3, gravity: Vec3, time: f64) -> Vec3 {
+        let acceleration = gravity * self.gravity_scale;
+        start + self.velocity * time + acceleration * (0.5 * time * time)
+    }
+
+    ///
+    /// Samples the path the projectile will take when launched from `start`, returning `samples` points evenly spaced
+    /// in time over `duration`, from the start onwards. The path is cut short at the end of the projectile's lifetime,
+    /// and doesn't account for anything it might hit along the way. Joining the points up with debug lines draws an
+    /// aiming arc.
+    ///
+    pub fn trajectory(
+        &self,
+        start: Vec3,
+        gravity: Vec3,
+        duration: f64,
+        samples: usize,
+    ) -> Vec<Vec3> {
+        let duration = match self.lifetime {
+            Some(lifetime) => duration.min(lifetime),
+            None => duration,
+        };
+        if samples < 2 {
+            return vec![start; samples];
+        }
+        (0..samples)
+            .map(|i| {
+                let time = duration * i as f64 / (samples - 1) as f64;
+                self.position_at(start, gravity, time)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::vec::vec3::{X_AXIS, Y_AXIS};
+
+    #[test]
+    fn arcs() {
+        let gravity = Y_AXIS * -10.0;
+        let throw = Projectile::new(Vec3::new(10.0, 10.0, 0.0));
+
+        // Thrown at 45 degrees, it lands level with where it started after two seconds, twenty units away
+        let arc = throw.trajectory(Vec3::default(), gravity, 2.0, 5);
+        assert_eq!(arc.len(), 5);
+        assert_eq!(arc[0], Vec3::default());
+        assert_eq!(arc[2], Vec3::new(10.0, 5.0, 0.0));
+        assert_eq!(arc[4], Vec3::new(20.0, 0.0, 0.0));
+
+        // Without gravity it flies straight, and its lifetime ends the path early
+        let bolt = Projectile::new(X_AXIS * 10.0)
+            .with_gravity_scale(0.0)
+            .with_lifetime(1.0);
+        let path = bolt.trajectory(Vec3::default(), gravity, 2.0, 3);
+        assert_eq!(path, vec![Vec3::default(), X_AXIS * 5.0, X_AXIS * 10.0]);
+    }
+}
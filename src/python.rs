@@ -0,0 +1,290 @@
+//! Python bindings for quick scripted visualisation and rendering datasets, built into a `megavertex` module by
+//! `maturin develop`, which turns this feature on through `pyproject.toml`. They cover building a world out of objects
+//! and lights, pointing a camera at it and rendering frames into numpy arrays - there's an example in the README.
+
+use numpy::{PyArray1, PyArray3, PyArrayMethods};
+use pyo3::{exceptions::PyIOError, prelude::*};
+
+use crate::{
+    camera::Camera, config::RendererConfig, framebuffer::Colour, light::Light, mat4::Mat4,
+    object::Object, renderer::Renderer, vec::vec3::Vec3, world::EntityId, world::World,
+};
+
+type Vector = (f64, f64, f64);
+
+fn vec3((x, y, z): Vector) -> Vec3 {
+    Vec3::new(x, y, z)
+}
+
+///
+/// A model and its texture, which can be moved about before it's added to a world.
+///
+#[pyclass(name = "Object", unsendable)]
+#[derive(Clone)]
+struct PyModel {
+    object: Object,
+}
+
+#[pymethods]
+impl PyModel {
+    ///
+    /// Loads a model from `name.obj` and its texture from `name.png`.
+    ///
+    #[staticmethod]
+    fn from_obj(name: &str) -> PyResult<PyModel> {
+        let object =
+            Object::from_obj(name).map_err(|error| PyIOError::new_err(error.to_string()))?;
+        Ok(PyModel { object })
+    }
+
+    fn translate(&mut self, offset: Vector) {
+        self.object
+            .transform(Mat4::identity().translate(vec3(offset)));
+    }
+
+    ///
+    /// Rotates the model by `angle` radians around `axis`, which passes through its origin.
+    ///
+    fn rotate(&mut self, axis: Vector, angle: f64) {
+        self.object
+            .transform(Mat4::identity().rotate(vec3(axis), angle));
+    }
+
+    fn scale(&mut self, factors: Vector) {
+        self.object.transform(Mat4::identity().scale(vec3(factors)));
+    }
+
+    #[getter]
+    fn vertex_count(&self) -> usize {
+        self.object.vertices.len()
+    }
+
+    #[getter]
+    fn face_count(&self) -> usize {
+        self.object.faces.len()
+    }
+}
+
+///
+/// A handle to an entity in a world, from `World.add_object`.
+///
+#[pyclass(name = "Entity", frozen, eq, hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct PyEntity {
+    id: EntityId,
+}
+
+///
+/// The objects and lights that make up a scene, as with `World`. Entities are placed relative to where they were
+/// added, so `set_position` and `rotate` move them as a whole.
+///
+#[pyclass(name = "World", unsendable)]
+struct PyWorld {
+    world: World,
+}
+
+#[pymethods]
+impl PyWorld {
+    #[new]
+    fn new() -> PyWorld {
+        PyWorld {
+            world: World::new(),
+        }
+    }
+
+    fn add_object(&mut self, object: &PyModel, position: Vector) -> PyEntity {
+        PyEntity {
+            id: self.world.add_object(object.object.clone(), vec3(position)),
+        }
+    }
+
+    ///
+    /// Removes an entity, returning whether it was still in the world.
+    ///
+    fn remove(&mut self, entity: PyEntity) -> bool {
+        self.world.remove(entity.id).is_some()
+    }
+
+    fn position(&self, entity: PyEntity) -> Option<Vector> {
+        let position = self
+            .world
+            .base_transform(entity.id)?
+            .transform(Vec3::default());
+        Some((position.x, position.y, position.z))
+    }
+
+    fn set_position(&mut self, entity: PyEntity, position: Vector) {
+        if let Some(base) = self.world.base_transform(entity.id) {
+            let offset = vec3(position) - base.transform(Vec3::default());
+            self.world
+                .set_base_transform(entity.id, base.translate(offset));
+        }
+    }
+
+    ///
+    /// Rotates an entity by `angle` radians around `axis`, about where it is rather than the world's origin.
+    ///
+    fn rotate(&mut self, entity: PyEntity, axis: Vector, angle: f64) {
+        if let Some(base) = self.world.base_transform(entity.id) {
+            let position = base.transform(Vec3::default());
+            let rotated = base
+                .translate(Vec3::default() - position)
+                .rotate(vec3(axis), angle)
+                .translate(position);
+            self.world.set_base_transform(entity.id, rotated);
+        }
+    }
+
+    ///
+    /// Adds a light shining in `direction` from infinitely far away, like the sun.
+    ///
+    #[pyo3(signature = (direction, colour = (1.0, 1.0, 1.0), intensity = 1.0))]
+    fn add_directional_light(&mut self, direction: Vector, colour: Vector, intensity: f64) {
+        self.world.lights.push(Light::Directional {
+            direction: vec3(direction),
+            colour: vec3(colour),
+            intensity,
+        });
+    }
+
+    ///
+    /// Adds a light at `position` which fades out to nothing at `range`.
+    ///
+    #[pyo3(signature = (position, range, colour = (1.0, 1.0, 1.0), intensity = 1.0))]
+    fn add_point_light(&mut self, position: Vector, range: f64, colour: Vector, intensity: f64) {
+        self.world.lights.push(Light::Point {
+            position: vec3(position),
+            colour: vec3(colour),
+            intensity,
+            range,
+        });
+    }
+
+    ///
+    /// Moves the world on by `elapsed` seconds, advancing its physics and animations as `Headless` does each frame.
+    ///
+    fn advance(&mut self, elapsed: f64) {
+        self.world.advance_physics(elapsed);
+        self.world.update(elapsed);
+    }
+}
+
+///
+/// Where a scene is seen from, looking down the negative Z axis until it's turned.
+///
+#[pyclass(name = "Camera", unsendable)]
+struct PyCamera {
+    camera: Camera,
+}
+
+#[pymethods]
+impl PyCamera {
+    #[new]
+    #[pyo3(signature = (position = (0.0, 0.0, 0.0)))]
+    fn new(position: Vector) -> PyCamera {
+        PyCamera {
+            camera: Camera::new(vec3(position)),
+        }
+    }
+
+    #[getter]
+    fn position(&self) -> Vector {
+        let position = self.camera.position();
+        (position.x, position.y, position.z)
+    }
+
+    #[setter]
+    fn set_position(&mut self, position: Vector) {
+        self.camera.set_position(vec3(position));
+    }
+
+    ///
+    /// Turns the camera to the given pitch and yaw, in radians.
+    ///
+    fn set_rotation(&mut self, pitch: f64, yaw: f64) {
+        self.camera.set_rotation(Vec3::new(pitch, yaw, 0.0));
+    }
+
+    ///
+    /// The horizontal field of view, in radians.
+    ///
+    #[getter]
+    fn fov(&self) -> f64 {
+        self.camera.fov
+    }
+
+    #[setter]
+    fn set_fov(&mut self, fov: f64) {
+        self.camera.fov = fov;
+    }
+}
+
+///
+/// Draws worlds into images of a fixed size.
+///
+#[pyclass(name = "Renderer", unsendable)]
+struct PyRenderer {
+    renderer: Renderer,
+}
+
+#[pymethods]
+impl PyRenderer {
+    #[new]
+    fn new(width: usize, height: usize) -> PyRenderer {
+        PyRenderer {
+            renderer: Renderer::new(RendererConfig::new(width, height)),
+        }
+    }
+
+    #[getter]
+    fn width(&self) -> usize {
+        self.renderer.width()
+    }
+
+    #[getter]
+    fn height(&self) -> usize {
+        self.renderer.height()
+    }
+
+    ///
+    /// Draws the world from the camera, returning the image as a `height` by `width` by 3 array of RGB bytes.
+    ///
+    fn render_to_numpy<'py>(
+        &mut self,
+        py: Python<'py>,
+        world: &PyWorld,
+        camera: &PyCamera,
+    ) -> PyResult<Bound<'py, PyArray3<u8>>> {
+        self.renderer.clear();
+        camera.camera.render_world(&mut self.renderer, &world.world);
+        let pixels = self
+            .renderer
+            .buffer
+            .iter()
+            .flat_map(|pixel| {
+                let colour = Colour::from(*pixel);
+                [colour.r, colour.g, colour.b]
+            })
+            .collect();
+        PyArray1::from_vec(py, pixels).reshape([self.renderer.height(), self.renderer.width(), 3])
+    }
+
+    ///
+    /// Saves the last image drawn as a PNG.
+    ///
+    fn save_png(&self, path: &str) -> PyResult<()> {
+        self.renderer
+            .save_png(path)
+            .map_err(|error| PyIOError::new_err(error.to_string()))
+    }
+}
+
+#[pymodule]
+fn megavertex(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyModel>()?;
+    module.add_class::<PyEntity>()?;
+    module.add_class::<PyWorld>()?;
+    module.add_class::<PyCamera>()?;
+    module.add_class::<PyRenderer>()?;
+    Ok(())
+}
@@ -0,0 +1,291 @@
+use core::ops::Mul;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::float::Float;
+use crate::{mat4::Mat4, vec::vec3::Vec3};
+
+///
+/// A unit quaternion representing a rotation. Unlike an axis and angle, quaternions can be smoothly interpolated, which
+/// makes them the natural choice for bone and keyframe rotations.
+///
+#[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub struct Quat {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub w: f64,
+}
+
+impl Quat {
+    pub fn new(x: f64, y: f64, z: f64, w: f64) -> Quat {
+        Quat { x, y, z, w }
+    }
+
+    ///
+    /// A quaternion representing no rotation.
+    ///
+    pub fn identity() -> Quat {
+        Quat::new(0.0, 0.0, 0.0, 1.0)
+    }
+
+    ///
+    /// Creates a rotation of `theta` radians about `axis`.
+    ///
+    pub fn from_axis_angle(axis: Vec3, theta: f64) -> Quat {
+        let axis = axis.normalise();
+        let s = (theta / 2.0).sin();
+        Quat::new(axis.x * s, axis.y * s, axis.z * s, (theta / 2.0).cos())
+    }
+
+    ///
+    /// Creates a rotation from Euler angles in radians, applied about the X, then Y, then Z axes.
+    ///
+    pub fn from_euler(angles: Vec3) -> Quat {
+        let (sx, cx) = (angles.x / 2.0).sin_cos();
+        let (sy, cy) = (angles.y / 2.0).sin_cos();
+        let (sz, cz) = (angles.z / 2.0).sin_cos();
+        Quat::new(
+            sx * cy * cz - cx * sy * sz,
+            cx * sy * cz + sx * cy * sz,
+            cx * cy * sz - sx * sy * cz,
+            cx * cy * cz + sx * sy * sz,
+        )
+    }
+
+    ///
+    /// Converts this rotation to Euler angles in radians, in the same order as `from_euler`.
+    ///
+    pub fn to_euler(self) -> Vec3 {
+        let Quat { x, y, z, w } = self;
+        let x_angle = (2.0 * (w * x + y * z)).atan2(1.0 - 2.0 * (x * x + y * y));
+        let y_angle = (2.0 * (w * y - z * x)).clamp(-1.0, 1.0).asin();
+        let z_angle = (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z));
+        Vec3::new(x_angle, y_angle, z_angle)
+    }
+
+    ///
+    /// Extracts the rotation from a pure rotation matrix.
+    ///
+    pub fn from_mat4(mat: Mat4) -> Quat {
+        let m = mat.m;
+        let trace = m[0][0] + m[1][1] + m[2][2];
+
+        // Pick the largest diagonal term to divide by, to avoid dividing by ~0
+        let q = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Quat::new(
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+                s / 4.0,
+            )
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+            Quat::new(
+                s / 4.0,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[2][1] - m[1][2]) / s,
+            )
+        } else if m[1][1] > m[2][2] {
+            let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+            Quat::new(
+                (m[0][1] + m[1][0]) / s,
+                s / 4.0,
+                (m[1][2] + m[2][1]) / s,
+                (m[0][2] - m[2][0]) / s,
+            )
+        } else {
+            let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+            Quat::new(
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                s / 4.0,
+                (m[1][0] - m[0][1]) / s,
+            )
+        };
+        q.normalise()
+    }
+
+    pub fn dot(self, q: Quat) -> f64 {
+        self.x * q.x + self.y * q.y + self.z * q.z + self.w * q.w
+    }
+
+    pub fn normalise(self) -> Quat {
+        let length = self.dot(self).sqrt();
+        Quat::new(
+            self.x / length,
+            self.y / length,
+            self.z / length,
+            self.w / length,
+        )
+    }
+
+    ///
+    /// Returns the inverse rotation. Assumes the quaternion is normalised.
+    ///
+    pub fn conjugate(self) -> Quat {
+        Quat::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    ///
+    /// Spherically interpolates between two rotations, taking the shortest path.
+    ///
+    pub fn slerp(self, mut to: Quat, t: f64) -> Quat {
+        let mut cos_theta = self.dot(to);
+        if cos_theta < 0.0 {
+            to = Quat::new(-to.x, -to.y, -to.z, -to.w);
+            cos_theta = -cos_theta;
+        }
+
+        // Fall back to linear interpolation for nearly identical rotations, where slerp divides by ~0
+        let (a, b) = if cos_theta > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let theta = cos_theta.acos();
+            let sin_theta = theta.sin();
+            (
+                ((1.0 - t) * theta).sin() / sin_theta,
+                (t * theta).sin() / sin_theta,
+            )
+        };
+
+        Quat::new(
+            self.x * a + to.x * b,
+            self.y * a + to.y * b,
+            self.z * a + to.z * b,
+            self.w * a + to.w * b,
+        )
+        .normalise()
+    }
+
+    ///
+    /// Rotates a vector by this quaternion.
+    ///
+    pub fn rotate(self, vec: Vec3) -> Vec3 {
+        let q = Vec3::new(self.x, self.y, self.z);
+        let t = q.cross_product(vec) * 2.0;
+        vec + t * self.w + q.cross_product(t)
+    }
+
+    ///
+    /// Converts this quaternion to a rotation matrix.
+    ///
+    pub fn to_mat4(self) -> Mat4 {
+        let Quat { x, y, z, w } = self;
+        Mat4 {
+            m: [
+                [
+                    1.0 - 2.0 * (y * y + z * z),
+                    2.0 * (x * y - z * w),
+                    2.0 * (x * z + y * w),
+                    0.0,
+                ],
+                [
+                    2.0 * (x * y + z * w),
+                    1.0 - 2.0 * (x * x + z * z),
+                    2.0 * (y * z - x * w),
+                    0.0,
+                ],
+                [
+                    2.0 * (x * z - y * w),
+                    2.0 * (y * z + x * w),
+                    1.0 - 2.0 * (x * x + y * y),
+                    0.0,
+                ],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+}
+
+impl Mul for Quat {
+    type Output = Self;
+
+    ///
+    /// Combines two rotations - `a * b` applies `b` first, then `a`.
+    ///
+    fn mul(self, q: Self) -> Self::Output {
+        Quat::new(
+            self.w * q.x + self.x * q.w + self.y * q.z - self.z * q.y,
+            self.w * q.y - self.x * q.z + self.y * q.w + self.z * q.x,
+            self.w * q.z + self.x * q.y - self.y * q.x + self.z * q.w,
+            self.w * q.w - self.x * q.x - self.y * q.y - self.z * q.z,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::f64::consts::PI;
+
+    use super::*;
+    use crate::vec::vec3::{X_AXIS, Y_AXIS};
+
+    fn assert_close(expected: Vec3, result: Vec3) {
+        assert!(
+            (expected - result).length() < 1e-9,
+            "{} != {}",
+            expected,
+            result
+        );
+    }
+
+    #[test]
+    fn rotate_vector() {
+        let q = Quat::from_axis_angle(Y_AXIS, PI / 2.0);
+        assert_close(Vec3::new(0.0, 0.0, -1.0), q.rotate(X_AXIS));
+    }
+
+    #[test]
+    fn matrix_matches_rotate() {
+        let q = Quat::from_axis_angle(Vec3::new(1.0, 2.0, 3.0), 0.7);
+        let point = Vec3::new(3.0, -1.0, 2.0);
+        let expected = Mat4::identity()
+            .rotate(Vec3::new(1.0, 2.0, 3.0), 0.7)
+            .transform(point);
+
+        assert_close(expected, q.to_mat4().transform(point));
+        assert_close(expected, q.rotate(point));
+    }
+
+    #[test]
+    fn slerp_halfway() {
+        let a = Quat::identity();
+        let b = Quat::from_axis_angle(Y_AXIS, PI / 2.0);
+        let halfway = a.slerp(b, 0.5);
+        let expected = Quat::from_axis_angle(Y_AXIS, PI / 4.0);
+
+        assert!((halfway.dot(expected) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn euler_round_trip() {
+        let angles = Vec3::new(0.3, -0.7, 1.2);
+        let q = Quat::from_euler(angles);
+        assert_close(angles, q.to_euler());
+
+        let expected = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), 1.2)
+            * Quat::from_axis_angle(Y_AXIS, -0.7)
+            * Quat::from_axis_angle(X_AXIS, 0.3);
+        assert!((q.dot(expected).abs() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn matrix_round_trip() {
+        let q = Quat::from_axis_angle(Vec3::new(-1.0, 2.0, 0.5), 2.5);
+        let result = Quat::from_mat4(q.to_mat4());
+        assert!((q.dot(result).abs() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn combine_rotations() {
+        let a = Quat::from_axis_angle(Y_AXIS, PI / 2.0);
+        let b = Quat::from_axis_angle(X_AXIS, PI / 2.0);
+        let point = Vec3::new(0.0, 1.0, 0.0);
+
+        assert_close(a.rotate(b.rotate(point)), (a * b).rotate(point));
+    }
+}
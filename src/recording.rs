@@ -0,0 +1,163 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    fs, io,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::input::InputFrame;
+
+///
+/// Whether `run` records the input it reads to a file, or replays a recording instead of reading input at all.
+/// - `Record` saves every frame's input, along with how much time passed in it, to the given path when the app exits
+/// - `Replay` plays back a recording from the given path, feeding the app the same input and frame times so it does
+///   exactly what it did while recording, then exits when the recording runs out
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum RecordMode {
+    #[default]
+    Off,
+    Record(String),
+    Replay(String),
+}
+
+///
+/// One frame of a recording.
+/// - `elapsed` is how much time passed in the frame in seconds, after it was paused, slowed down or sped up
+/// - `step` is whether a single update was stepped through while paused
+/// - `input` is what the window reported about the keyboard and mouse
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    pub elapsed: f64,
+    #[serde(default)]
+    pub step: bool,
+    pub input: InputFrame,
+}
+
+///
+/// The input an app was run with, frame by frame, so a run can be played back exactly - for reproducing bugs, or
+/// capturing a flythrough as data rather than video. Recordings are saved as RON.
+///
+/// Anything random should be seeded with `seed`, which `run` hands the app through `App::set_seed` before it's set
+/// up. Replays are only exact if the app doesn't depend on anything else that changes between runs, like the time of
+/// day or the files on disk.
+///
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Recording {
+    pub seed: u64,
+    pub frames: Vec<RecordedFrame>,
+}
+
+#[derive(Debug)]
+pub enum RecordingError {
+    IoError(io::Error),
+    ParseError(ron::error::SpannedError),
+    SerializeError(ron::Error),
+}
+
+impl From<io::Error> for RecordingError {
+    fn from(error: io::Error) -> Self {
+        RecordingError::IoError(error)
+    }
+}
+impl From<ron::error::SpannedError> for RecordingError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        RecordingError::ParseError(error)
+    }
+}
+impl From<ron::Error> for RecordingError {
+    fn from(error: ron::Error) -> Self {
+        RecordingError::SerializeError(error)
+    }
+}
+
+impl Display for RecordingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordingError::IoError(error) => write!(f, "failed to access recording: {}", error),
+            RecordingError::ParseError(error) => write!(f, "failed to parse recording: {}", error),
+            RecordingError::SerializeError(error) => {
+                write!(f, "failed to write recording: {}", error)
+            }
+        }
+    }
+}
+
+impl Error for RecordingError {}
+
+impl Recording {
+    ///
+    /// Starts an empty recording. Without a seed, one is picked from the clock.
+    ///
+    pub fn new(seed: Option<u64>) -> Recording {
+        let seed = seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_nanos() as u64)
+        });
+        Recording {
+            seed,
+            frames: vec![],
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Recording, RecordingError> {
+        Recording::from_ron(&fs::read_to_string(path)?)
+    }
+
+    pub fn from_ron(ron: &str) -> Result<Recording, RecordingError> {
+        Ok(ron::from_str(ron)?)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), RecordingError> {
+        fs::write(path, self.to_ron()?)?;
+        Ok(())
+    }
+
+    pub fn to_ron(&self) -> Result<String, RecordingError> {
+        Ok(ron::to_string(self)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::vec::vec2::Vec2;
+    use minifb::{Key, MouseButton};
+
+    #[test]
+    fn recordings_round_trip() {
+        let mut recording = Recording::new(Some(42));
+        recording.frames.push(RecordedFrame {
+            // Frame times are kept exactly, so replays see the same times
+            elapsed: 1.0 / 60.0,
+            step: false,
+            input: InputFrame {
+                keys_down: vec![Key::W, Key::LeftShift],
+                keys_pressed: vec![Key::LeftShift],
+                mouse: Some(Vec2::new(10.5, 20.0)),
+                size: (600, 400),
+                buttons: vec![MouseButton::Right],
+                ..Default::default()
+            },
+        });
+        recording.frames.push(RecordedFrame {
+            elapsed: 0.1 + 0.2,
+            step: true,
+            input: InputFrame::default(),
+        });
+
+        let ron = recording.to_ron().unwrap();
+        assert!(ron.contains("\"LeftShift\""));
+        assert_eq!(Recording::from_ron(&ron).unwrap(), recording);
+        assert!(matches!(
+            Recording::from_ron(
+                "(seed: 0, frames: [(elapsed: 0.0, input: (keys_down: [\"Nope\"]))])"
+            ),
+            Err(RecordingError::ParseError(_))
+        ));
+    }
+}
@@ -1,56 +1,249 @@
-use std::{
+use alloc::{vec, vec::Vec};
+use core::{
     cmp::{max, min},
-    vec,
+    f64::consts::PI,
+    time::Duration,
 };
+#[cfg(feature = "std")]
+use std::{io, time::Instant};
 
 use fontdue::Font;
+use serde::{Deserialize, Serialize};
 
-use crate::{object::Texture, vec::vec2::Vec2, vec::vec3::Vec3};
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::float::Float;
+use crate::{
+    config::{FillMode, Interlace, RendererConfig, Upscale},
+    decal::{decal_coords, DecalMode},
+    environment::Fog,
+    error::Error,
+    framebuffer::Colour,
+    hdr,
+    mat4::Mat4,
+    object::Texture,
+    palette::Palette,
+    vec::vec2::Vec2,
+    vec::vec3::Vec3,
+};
 
-const _BLACK: u32 = 0x000000;
-const _WHITE: u32 = 0xffffff;
-const _BLUE: u32 = 0x0000aa;
+// Wireframes are drawn in a dark blue, and text in a bright one
+const WIREFRAME_COLOUR: Colour = Colour::from_hex(0x0000aa);
+const TEXT_COLOUR: Colour = Colour::from_hex(0x0000ff);
 
 const MAX_Z: f64 = 1000.0;
 
-const WIREFRAME: bool = false;
+// How far a pixel's depth can be from a decal's triangle, relative to the depth, and still be treated as part of it
+const DECAL_DEPTH_TOLERANCE: f64 = 1e-9;
+
+// How far outside a quad, as a fraction of its size, a point can be and still be mapped onto it
+const QUAD_SLACK: f64 = 0.01;
+
+// The font text is written in unless another is given to `Renderer::with_font`
+const FONT: &[u8] = include_bytes!("../resources/liberation-mono.ttf");
+
+///
+/// How a triangle is combined with what's already on screen.
+/// - `opacity` mixes the triangle's colour with the pixels behind it. Triangles which aren't fully opaque don't write to
+///   the depth buffer, so they should be drawn after everything behind them
+/// - `reflection` makes the surface mirror-like, reflecting whatever has already been drawn. It holds the surface's
+///   normal in camera space, and how strongly it reflects between 0 and 1
+/// - `ambient` multiplies the triangle's colour, as an RGB value between 0 and 1. When drawing in HDR it can go above 1
+///   to brighten the triangle past white
+/// - `fog` fades the triangle into the fog's colour with its depth
+/// - `texture_mapping` picks how the triangle's texture is mapped, in place of the renderer's setting
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Blend {
+    pub opacity: f64,
+    pub reflection: Option<(Vec3, f64)>,
+    pub ambient: Vec3,
+    pub fog: Option<Fog>,
+    pub texture_mapping: Option<TextureMapping>,
+}
+
+///
+/// How texture coordinates are interpolated across a triangle.
+/// - `Perspective` corrects them for depth, so textures stay straight on triangles seen at an angle
+/// - `Affine` interpolates them across the screen as they are, which is a little cheaper but warps textures on
+///   triangles seen at an angle, as on old consoles
+///
+/// Triangles follow `Retro::affine_textures` unless their `Blend` picks one.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextureMapping {
+    Perspective,
+    Affine,
+}
+
+impl Blend {
+    pub const OPAQUE: Blend = Blend {
+        opacity: 1.0,
+        reflection: None,
+        ambient: Vec3 {
+            x: 1.0,
+            y: 1.0,
+            z: 1.0,
+        },
+        fog: None,
+        texture_mapping: None,
+    };
+}
+
+impl Default for Blend {
+    fn default() -> Self {
+        Blend::OPAQUE
+    }
+}
+
+// How many steps a reflected ray takes across the screen before giving up, and how far each one goes in camera space
+const REFLECTION_STEPS: usize = 48;
+const REFLECTION_STEP: f64 = 0.5;
+
+///
+/// Counts of what was drawn since the renderer was last cleared, for measuring performance.
+/// - `triangles` is how many triangles were passed in to be drawn
+/// - `rasterised` is how many of those were in front of the camera and on screen, and so were filled in
+/// - `pixels` is how many pixels were written, including ones which were later drawn over
+/// - `raster_time` is how long was spent filling in triangles. Timing every triangle slows drawing down, so it's only
+///   measured while profiling is turned on with `Renderer::set_profiling`
+///
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct RenderStats {
+    pub triangles: usize,
+    pub rasterised: usize,
+    pub pixels: usize,
+    pub raster_time: Duration,
+}
 
 pub struct Renderer {
-    // Screen dimensions
+    config: RendererConfig,
+
+    // Screen dimensions, after the render scale is applied until the frame is resolved
     width: usize,
     height: usize,
     centre: Vec3,
+    // How many pixels a point one unit off centre at a depth of one is moved, set by the field of view
+    scale: f64,
 
     // Pixel and depth buffer
     pub buffer: Vec<u32>,
     depth_buffer: Vec<Vec<f64>>,
+    // Unclamped colours, which `resolve` tonemaps into the pixel buffer. Empty unless drawing in HDR
+    hdr_buffer: Vec<[f32; 3]>,
+    // The colours finished frames are reduced to, if any
+    palette: Option<Palette>,
+    // The last frame's finished world, before it was reduced to the palette or scaled up, which interlaced frames are
+    // drawn over. Empty unless interlacing
+    previous: Vec<u32>,
+    // Which half of the pixels is being drawn this frame when interlacing, 0 or 1
+    field: usize,
 
     // Font rendering
     font: Font,
+
+    // Counts of what's been drawn this frame, and whether triangles are being timed
+    stats: RenderStats,
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    profiling: bool,
 }
 
 impl Renderer {
-    pub fn new(width: usize, height: usize) -> Self {
-        // Read the font data and parse it into the font type
-        let font_bytes = include_bytes!("../resources/liberation-mono.ttf") as &[u8];
-        let font = fontdue::Font::from_bytes(font_bytes, fontdue::FontSettings::default()).unwrap();
+    ///
+    /// Creates a renderer drawing images of the size given in `config`, scaled by its render scale.
+    ///
+    pub fn new(config: RendererConfig) -> Self {
+        Renderer::with_font(config, FONT).expect("the bundled font is valid")
+    }
+
+    ///
+    /// Creates a renderer as with `new`, which writes text in the TrueType or OpenType font given rather than the
+    /// bundled monospaced one.
+    ///
+    pub fn with_font(config: RendererConfig, font_bytes: &[u8]) -> Result<Self, Error> {
+        let font = Font::from_bytes(font_bytes, fontdue::FontSettings::default())
+            .map_err(Error::FontError)?;
 
         // Precalculate the centre of the screen
+        let (width, height) = config.render_size();
         let centre = Vec3::new((width as f64) / 2.0, (height as f64) / 2.0, 0.0);
 
         let mut renderer = Renderer {
+            config,
             width,
             height,
             centre,
+            scale: width as f64,
             buffer: vec![],
             depth_buffer: vec![],
+            hdr_buffer: vec![],
+            palette: None,
+            previous: vec![],
+            field: 0,
             font,
+            stats: RenderStats::default(),
+            profiling: false,
         };
         renderer.clear();
-        return renderer;
+        Ok(renderer)
+    }
+
+    pub fn config(&self) -> &RendererConfig {
+        &self.config
+    }
+
+    ///
+    /// Changes how much of the configured size the world is drawn at, from the next time the renderer is cleared.
+    ///
+    pub fn set_render_scale(&mut self, render_scale: f64) {
+        self.config.render_scale = render_scale;
+    }
+
+    ///
+    /// Returns the width of the image being drawn in pixels. Until the frame is finished by `resolve`, this is smaller
+    /// than the configured width when the render scale is below 1, and after it's the configured width. The same goes
+    /// for `height`.
+    ///
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    ///
+    /// Returns what's been drawn since the renderer was last cleared.
+    ///
+    pub fn stats(&self) -> RenderStats {
+        self.stats
+    }
+
+    ///
+    /// Turns timing how long triangles take to fill in on or off, which is reported in `stats`. Timing needs a clock,
+    /// so nothing is measured without the `std` feature.
+    ///
+    pub fn set_profiling(&mut self, profiling: bool) {
+        self.profiling = profiling;
+    }
+
+    ///
+    /// Sets the horizontal field of view in radians, which `Camera::render_world` does before drawing. Triangles and
+    /// lines are passed in divided by their depth, and are scaled up to fill this angle across the screen.
+    ///
+    pub fn set_fov(&mut self, fov: f64) {
+        self.scale = self.width as f64 / (2.0 * (fov / 2.0).tan());
     }
 
     pub fn write_text(&mut self, text: &str, pos: Vec2, size: f32) {
+        self.write_text_coloured(text, pos, size, TEXT_COLOUR);
+    }
+
+    ///
+    /// Writes text as with `write_text`, in the given colour. Each glyph is drawn over a black box, faded towards the
+    /// colour by how much of each pixel it covers.
+    ///
+    pub fn write_text_coloured(&mut self, text: &str, pos: Vec2, size: f32, colour: Colour) {
         let mut x_offset = pos.x;
 
         if let Some(line_metrics) = self.font.horizontal_line_metrics(size) {
@@ -61,35 +254,190 @@ impl Renderer {
 
                 for y in 0..metrics.height {
                     for x in 0..metrics.width {
-                        let char_s = bitmap[x + y * metrics.width];
+                        let coverage = bitmap[x + y * metrics.width] as f64 / u8::MAX as f64;
                         self.draw_pixel(
                             Vec3::new(x as f64 + x_offset, y as f64 + pos.y + top_offset, 0.0),
-                            char_s as u32,
+                            Colour::BLACK.lerp(colour, coverage),
                         );
                     }
                 }
-                x_offset = x_offset + metrics.advance_width as f64;
+                x_offset += metrics.advance_width as f64;
             }
         }
     }
+    ///
+    /// Returns how wide `text` is when written with `write_text` at the given size, in pixels.
+    ///
+    pub fn text_width(&self, text: &str, size: f32) -> f64 {
+        text.chars()
+            .map(|char| self.font.metrics(char, size).advance_width as f64)
+            .sum()
+    }
+
+    ///
+    /// Fills a rectangle over whatever has been drawn, for 2D overlays. `pos` is its top left corner in pixels, and any
+    /// of it off screen is left out.
+    ///
+    pub fn fill_rect(&mut self, pos: Vec2, size: Vec2, col: Colour) {
+        let clamp = |value: f64, max: usize| (value.round().max(0.0) as usize).min(max);
+        let (left, right) = (clamp(pos.x, self.width), clamp(pos.x + size.x, self.width));
+        let (top, bottom) = (
+            clamp(pos.y, self.height),
+            clamp(pos.y + size.y, self.height),
+        );
+        for y in top..bottom {
+            self.buffer[y * self.width + left..y * self.width + right].fill(col.to_0rgb());
+        }
+    }
+
+    ///
+    /// Draws the outline of a rectangle a pixel wide, as with `fill_rect`.
+    ///
+    pub fn draw_rect(&mut self, pos: Vec2, size: Vec2, col: Colour) {
+        self.fill_rect(pos, Vec2::new(size.x, 1.0), col);
+        self.fill_rect(
+            Vec2::new(pos.x, pos.y + size.y - 1.0),
+            Vec2::new(size.x, 1.0),
+            col,
+        );
+        self.fill_rect(pos, Vec2::new(1.0, size.y), col);
+        self.fill_rect(
+            Vec2::new(pos.x + size.x - 1.0, pos.y),
+            Vec2::new(1.0, size.y),
+            col,
+        );
+    }
+
     // Draws a triangle from an array of 3 points.
-    pub fn draw_triangle(&mut self, vertices: Vec<Vec3>, texture: &Texture, tex_coords: Vec<Vec2>) {
-        // TODO - potentially faster to use arrays, but need to investigate closures
-        // Contains the rasterized points to be drawn
-        let mut raster_points: Vec<Vec3> = vec![];
-
-        // Scale the points up to raster space. Z is left alone, as it is only used by the depth buffer
-        for vec in vertices {
-            if vec.z >= 0.0 {
-                return;
-            }
-            let scaled = vec * self.width as f64;
-            let centred = scaled + self.centre;
+    pub fn draw_triangle(&mut self, vertices: [Vec3; 3], texture: &Texture, tex_coords: [Vec2; 3]) {
+        self.draw_triangle_blended(vertices, texture, tex_coords, Blend::OPAQUE);
+    }
+
+    ///
+    /// Draws a triangle as with `draw_triangle`, combining it with what's already on screen according to `blend`.
+    ///
+    pub fn draw_triangle_blended(
+        &mut self,
+        vertices: [Vec3; 3],
+        texture: &Texture,
+        tex_coords: [Vec2; 3],
+        blend: Blend,
+    ) {
+        self.stats.triangles += 1;
+        #[cfg(feature = "std")]
+        if self.profiling {
+            let start = Instant::now();
+            self.fill_triangle(vertices, texture, tex_coords, blend, None);
+            self.stats.raster_time += start.elapsed();
+            return;
+        }
+        self.fill_triangle(vertices, texture, tex_coords, blend, None);
+    }
+
+    // Draws a quad from an array of 4 points, in order around its edge.
+    pub fn draw_quad(&mut self, vertices: [Vec3; 4], texture: &Texture, tex_coords: [Vec2; 4]) {
+        self.draw_quad_blended(vertices, texture, tex_coords, Blend::OPAQUE);
+    }
 
-            raster_points.push(Vec3::new(centred.x, centred.y, vec.z));
+    ///
+    /// Draws a quad as with `draw_quad`, combining it with what's already on screen according to `blend`. The quad is
+    /// filled in as two triangles, but when its texture is mapped affinely the texture coordinates are interpolated
+    /// across the whole quad rather than each triangle, so there's no seam where it's cut in two.
+    ///
+    pub fn draw_quad_blended(
+        &mut self,
+        vertices: [Vec3; 4],
+        texture: &Texture,
+        tex_coords: [Vec2; 4],
+        blend: Blend,
+    ) {
+        self.stats.triangles += 2;
+        #[cfg(feature = "std")]
+        let start = self.profiling.then(Instant::now);
+        for (a, b, c) in [(0, 1, 2), (0, 2, 3)] {
+            self.fill_triangle(
+                [vertices[a], vertices[b], vertices[c]],
+                texture,
+                [tex_coords[a], tex_coords[b], tex_coords[c]],
+                blend,
+                Some((vertices, tex_coords)),
+            );
+        }
+        #[cfg(feature = "std")]
+        if let Some(start) = start {
+            self.stats.raster_time += start.elapsed();
+        }
+    }
+
+    ///
+    /// Moves a point divided by its depth to raster space. Z is left alone, as it is only used by the depth buffer and
+    /// to correct texture coordinates for perspective.
+    ///
+    fn to_raster(&self, vec: Vec3) -> Vec3 {
+        let retro = self.config.retro;
+        let vec = retro.map_or(vec, |retro| retro.wobble(vec));
+        let scaled = vec * self.scale;
+        let centred = scaled + self.centre;
+        let point = Vec3::new(centred.x, centred.y, vec.z);
+        retro.map_or(point, |retro| retro.snap(point))
+    }
+
+    ///
+    /// Fills in a triangle, which may be cut from a `quad` with the texture coordinates at each of its corners.
+    ///
+    fn fill_triangle(
+        &mut self,
+        vertices: [Vec3; 3],
+        texture: &Texture,
+        tex_coords: [Vec2; 3],
+        blend: Blend,
+        quad: Option<([Vec3; 4], [Vec2; 4])>,
+    ) {
+        if vertices.iter().any(|vec| vec.z >= 0.0) {
+            return;
+        }
+
+        // Scale the points up to raster space
+        let raster_points = vertices.map(|vec| self.to_raster(vec));
+        let tex_coords = TexCoords {
+            corners: tex_coords,
+            // A quad with a corner behind the camera can't be projected whole, so its halves are mapped on their own
+            quad: quad
+                .filter(|(vertices, _)| vertices.iter().all(|vec| vec.z < 0.0))
+                .map(|(vertices, tex_coords)| {
+                    (vertices.map(|vec| self.to_raster(vec).into()), tex_coords)
+                }),
+        };
+
+        let Some(((x_min, x_max), (y_min, y_max))) = self.raster_bounds(&raster_points) else {
+            return;
+        };
+        self.stats.rasterised += 1;
+
+        if self.config.fill_mode != FillMode::Wireframe {
+            self.fill_pixels(
+                &raster_points,
+                texture,
+                &tex_coords,
+                blend,
+                (x_min, x_max),
+                (y_min, y_max),
+            );
+        }
+        if self.config.fill_mode != FillMode::Solid {
+            // Bresenham's line algorithm - info here:
+            // https://en.wikipedia.org/wiki/Bresenham%27s_line_algorithm#Algorithm_for_integer_arithmetic
+            self.draw_line(raster_points[0], raster_points[1]);
+            self.draw_line(raster_points[1], raster_points[2]);
+            self.draw_line(raster_points[2], raster_points[0]);
         }
+    }
 
-        // Compute the triangle's rectangular boundaries on the screen, clamped to be within the screen's size
+    ///
+    /// Computes a triangle's rectangular boundaries on the screen, clamped to be within the screen's size, or `None` if
+    /// they're entirely off the screen.
+    ///
+    fn raster_bounds(&self, raster_points: &[Vec3; 3]) -> Option<((isize, isize), (isize, isize))> {
         let x_min = max(
             0,
             min3(raster_points[0].x, raster_points[1].x, raster_points[2].x),
@@ -108,17 +456,140 @@ impl Renderer {
             max3(raster_points[0].y, raster_points[1].y, raster_points[2].y),
         );
 
-        // Stop here if the bounding box is entirely off the screen
-        if x_max < x_min || y_max < y_min {
+        (x_max >= x_min && y_max >= y_min).then_some(((x_min, x_max), (y_min, y_max)))
+    }
+
+    ///
+    /// Draws a decal over triangles which have already been drawn. The triangles are filled in again, and wherever
+    /// they're still what's on screen, the point on them is moved from camera space into the decal's box by
+    /// `to_decal`. Points inside the box have the decal's texture combined with them by `mode`, lit and fogged by
+    /// `blend` and mixed in by its opacity. Pixels on the edges between triangles are only covered once, and decals
+    /// don't write to the depth buffer.
+    ///
+    pub fn draw_decal(
+        &mut self,
+        triangles: &[[Vec3; 3]],
+        texture: &Texture,
+        to_decal: Mat4,
+        mode: DecalMode,
+        blend: Blend,
+    ) {
+        if self.config.fill_mode == FillMode::Wireframe {
             return;
         }
+        // Every pixel is found first, so ones shared by neighbouring triangles can be drawn over only once
+        let mut covered = vec![];
+        for vertices in triangles {
+            if vertices.iter().any(|vec| vec.z >= 0.0) {
+                continue;
+            }
+            let raster_points = vertices.map(|vec| self.to_raster(vec));
+            let Some(((x_min, x_max), (y_min, y_max))) = self.raster_bounds(&raster_points) else {
+                continue;
+            };
+            let (a, b, c) = (
+                raster_points[0].into(),
+                raster_points[1].into(),
+                raster_points[2].into(),
+            );
+            for x in x_min..x_max {
+                for y in y_min..y_max {
+                    if !self.in_field(x as usize, y as usize) {
+                        continue;
+                    }
+                    let bary = get_barycentric(a, b, c, Vec2::new(x as f64, y as f64));
+                    if bary.u < 0.0 || bary.v < 0.0 || bary.w < 0.0 {
+                        continue;
+                    }
+                    // Only pixels where the triangle was drawn and hasn't been covered since have the same depth
+                    let z = raster_points[0].z * bary.u
+                        + raster_points[1].z * bary.v
+                        + raster_points[2].z * bary.w;
+                    if (z - self.depth_buffer[y as usize][x as usize]).abs()
+                        <= DECAL_DEPTH_TOLERANCE * z.abs()
+                    {
+                        covered.push((self.width * y as usize + x as usize, z));
+                    }
+                }
+            }
+        }
+        covered.sort_unstable_by_key(|(i, _)| *i);
+        covered.dedup_by_key(|(i, _)| *i);
+
+        for (i, z) in covered {
+            let (x, y) = ((i % self.width) as isize, (i / self.width) as isize);
+            let position = self.camera_position(x, y, z);
+            let Some(coords) = decal_coords(to_decal.transform(position)) else {
+                continue;
+            };
+
+            let texel = texture.sample(coords);
+            let fogged = blend.fog.map_or(0.0, |fog| fog.amount(position.length()));
+            self.stats.pixels += 1;
+            if !self.hdr_buffer.is_empty() {
+                let lerp = |from: Vec3, to: Vec3, t: f64| from + (to - from) * t;
+                let base = self.hdr_pixel(i);
+                let texel = hdr::linear(texel);
+                let col = match mode {
+                    DecalMode::Mix => {
+                        let lit = Vec3::new(
+                            texel.x * blend.ambient.x.max(0.0),
+                            texel.y * blend.ambient.y.max(0.0),
+                            texel.z * blend.ambient.z.max(0.0),
+                        );
+                        lerp(lit, blend.fog.map_or(lit, |fog| fog.colour), fogged)
+                    }
+                    // Fog hides the decal along with the surface under it, so it fades the shading out
+                    DecalMode::Multiply => {
+                        let shade = lerp(texel, Vec3::new(1.0, 1.0, 1.0), fogged);
+                        Vec3::new(base.x * shade.x, base.y * shade.y, base.z * shade.z)
+                    }
+                };
+                self.hdr_buffer[i] = to_f32(lerp(base, col, blend.opacity));
+                continue;
+            }
+            let base = Colour::from(self.buffer[i]);
+            let col = match mode {
+                DecalMode::Mix => {
+                    let lit = texel.multiply(blend.ambient);
+                    blend
+                        .fog
+                        .map_or(lit, |fog| lit.lerp(fog.colour.into(), fogged))
+                }
+                DecalMode::Multiply => {
+                    base.multiply(hdr::linear(texel.lerp(Colour::from_hex(0xffffff), fogged)))
+                }
+            };
+            self.buffer[i] = base.lerp(col, blend.opacity).to_0rgb();
+        }
+    }
 
+    ///
+    /// Fills in the pixels of a triangle in raster space within its bounding box on screen.
+    ///
+    fn fill_pixels(
+        &mut self,
+        raster_points: &[Vec3; 3],
+        texture: &Texture,
+        tex_coords: &TexCoords,
+        blend: Blend,
+        (x_min, x_max): (isize, isize),
+        (y_min, y_max): (isize, isize),
+    ) {
+        let a: Vec2 = raster_points[0].into();
+        let b: Vec2 = raster_points[1].into();
+        let c: Vec2 = raster_points[2].into();
+        // Texture coordinates are interpolated divided by depth, then divided by the interpolated reciprocal of depth, so
+        // they're correct for perspective. Affine mapping interpolates them as they are
+        let affine = self.maps_affinely(blend);
+        let inverse_z = raster_points.map(|point| 1.0 / point.z);
+        let (quad, tex_coords) = (tex_coords.quad.as_ref(), &tex_coords.corners);
         for x in x_min..x_max {
             for y in y_min..y_max {
+                if !self.in_field(x as usize, y as usize) {
+                    continue;
+                }
                 let point = Vec2::new(x as f64, y as f64);
-                let a: Vec2 = raster_points[0].into();
-                let b: Vec2 = raster_points[1].into();
-                let c: Vec2 = raster_points[2].into();
 
                 let bary = get_barycentric(a, b, c, point);
 
@@ -131,27 +602,128 @@ impl Renderer {
                         continue;
                     }
 
-                    let tex_xy = tex_coords[0]
-                        + tex_coords[0] * bary.u
-                        + tex_coords[1] * bary.v
-                        + tex_coords[2] * bary.w;
+                    // Affine mapping across a whole quad is bilinear, so it's the same on both sides of the cut
+                    let bilinear = quad
+                        .filter(|_| affine)
+                        .and_then(|(corners, coords)| bilinear_coords(corners, coords, point));
+                    let tex_xy = if let Some(tex_xy) = bilinear {
+                        tex_xy
+                    } else if affine {
+                        tex_coords[0] * bary.u + tex_coords[1] * bary.v + tex_coords[2] * bary.w
+                    } else {
+                        let weights = [
+                            bary.u * inverse_z[0],
+                            bary.v * inverse_z[1],
+                            bary.w * inverse_z[2],
+                        ];
+                        (tex_coords[0] * weights[0]
+                            + tex_coords[1] * weights[1]
+                            + tex_coords[2] * weights[2])
+                            / (weights[0] + weights[1] + weights[2])
+                    };
+
+                    let texel = texture.sample(tex_xy);
+                    let reflected = blend.reflection.and_then(|(normal, strength)| {
+                        let position = self.camera_position(x, y, point_exact.z);
+                        Some((self.trace_reflection(position, normal)?, strength))
+                    });
+                    let fogged = blend.fog.map(|fog| {
+                        let distance = self.camera_position(x, y, point_exact.z).length();
+                        (fog.colour, fog.amount(distance))
+                    });
 
-                    let col = texture.sample(tex_xy);
-                    self.draw_pixel(Vec3::new(x as f64, y as f64, point_exact.z), col as u32);
+                    self.stats.pixels += 1;
+                    let i = self.width * y as usize + x as usize;
+                    if !self.hdr_buffer.is_empty() {
+                        self.shade_hdr(i, point_exact.z, texel, reflected, fogged, blend);
+                        continue;
+                    }
+                    let mut col = texel.multiply(blend.ambient);
+                    if let Some((reflected, strength)) = reflected {
+                        col = col.lerp(Colour::from(self.buffer[reflected]), strength);
+                    }
+                    if let Some((colour, amount)) = fogged {
+                        col = col.lerp(colour.into(), amount);
+                    }
+                    if blend.opacity >= 1.0 {
+                        // The bounding box has already been clamped to the screen
+                        self.draw_pixel_unchecked(
+                            Vec3::new(x as f64, y as f64, point_exact.z),
+                            col,
+                        );
+                    } else {
+                        self.buffer[i] = Colour::from(self.buffer[i])
+                            .lerp(col, blend.opacity)
+                            .to_0rgb();
+                    }
                 }
             }
         }
+    }
 
-        if WIREFRAME {
-            // Bresenham's line algorithm - info here:
-            // https://en.wikipedia.org/wiki/Bresenham%27s_line_algorithm#Algorithm_for_integer_arithmetic
-            self.draw_line(raster_points[0], raster_points[1]);
-            self.draw_line(raster_points[1], raster_points[2]);
-            self.draw_line(raster_points[2], raster_points[0]);
+    ///
+    /// Works out the colour of a pixel of a triangle as `fill_pixels` does, without clamping it, and writes it to the
+    /// HDR buffer. `reflected` is the pixel a reflection landed on, and `fogged` the fog's colour and how much of it
+    /// covers the pixel.
+    ///
+    fn shade_hdr(
+        &mut self,
+        i: usize,
+        depth: f64,
+        texel: Colour,
+        reflected: Option<(usize, f64)>,
+        fogged: Option<(Vec3, f64)>,
+        blend: Blend,
+    ) {
+        let lerp = |from: Vec3, to: Vec3, t: f64| from + (to - from) * t;
+        let texel = hdr::linear(texel);
+        let ambient = |channel: f64| channel.max(0.0);
+        let mut col = Vec3::new(
+            texel.x * ambient(blend.ambient.x),
+            texel.y * ambient(blend.ambient.y),
+            texel.z * ambient(blend.ambient.z),
+        );
+        if let Some((reflected, strength)) = reflected {
+            col = lerp(col, self.hdr_pixel(reflected), strength);
+        }
+        if let Some((colour, amount)) = fogged {
+            col = lerp(col, colour, amount);
+        }
+        if blend.opacity >= 1.0 {
+            self.depth_buffer[i / self.width][i % self.width] = depth;
+        } else {
+            col = lerp(self.hdr_pixel(i), col, blend.opacity);
         }
+        self.hdr_buffer[i] = to_f32(col);
     }
 
+    fn hdr_pixel(&self, i: usize) -> Vec3 {
+        let [r, g, b] = self.hdr_buffer[i];
+        Vec3::new(r as f64, g as f64, b as f64)
+    }
+
+    ///
+    /// Writes a pixel of the scene, into the HDR buffer when drawing in HDR so it's tonemapped with everything else.
+    ///
+    fn put_pixel(&mut self, i: usize, col: Colour) {
+        match self.hdr_buffer.get_mut(i) {
+            Some(pixel) => *pixel = to_f32(hdr::linear(col)),
+            None => self.buffer[i] = col.to_0rgb(),
+        }
+    }
+
+    ///
+    /// Draws the edge of a triangle between two points in raster space, over everything else.
+    ///
     fn draw_line(&mut self, a: Vec3, b: Vec3) {
+        // Vertices close to the camera can land far off screen, so only the part of the line on screen is stepped along
+        let Some((a, b)) = self.clip_to_screen(a, b) else {
+            return;
+        };
+        // The line steps a whole pixel at a time, so it has to start and end on one to finish
+        let a = Vec3::new(a.x.round(), a.y.round(), a.z);
+        let b = Vec3::new(b.x.round(), b.y.round(), b.z);
+
         let dx = (b.x - a.x).abs();
         let dy = -(b.y - a.y).abs();
 
@@ -175,9 +747,9 @@ impl Renderer {
         let mut y = a.y;
 
         loop {
-            if x > 0.0 && y > 0.0 {
+            if x >= 0.0 && y >= 0.0 && x < self.width as f64 && y < self.height as f64 {
                 // Wireframes ignore the depth buffer
-                self.draw_pixel(Vec3::new(x, y, -100.0), _BLUE);
+                self.put_pixel(self.width * y as usize + x as usize, WIREFRAME_COLOUR);
             }
 
             if x == b.x && y == b.y {
@@ -188,35 +760,416 @@ impl Renderer {
                 if x == b.x {
                     break;
                 }
-                err = err + dy;
-                x = x + sx;
+                err += dy;
+                x += sx;
             }
             if err2 <= dx {
                 if y == b.y {
                     break;
                 }
-                err = err + dx;
-                y = y + sy;
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    ///
+    /// Cuts a line in raster space down to the part of it on screen, or returns `None` if none of it is.
+    ///
+    fn clip_to_screen(&self, a: Vec3, b: Vec3) -> Option<(Vec3, Vec3)> {
+        let (mut from, mut to) = (0.0, 1.0);
+        let delta = b - a;
+        let max = Vec2::new(self.width as f64 - 1.0, self.height as f64 - 1.0);
+        for (start, step, limit) in [(a.x, delta.x, max.x), (a.y, delta.y, max.y)] {
+            // Each axis keeps the line between 0 and the last pixel, which narrows the part of it that's left
+            for (p, q) in [(-step, start), (step, limit - start)] {
+                if p == 0.0 {
+                    if q < 0.0 {
+                        return None;
+                    }
+                    continue;
+                }
+                let t = q / p;
+                if p < 0.0 {
+                    from = f64::max(from, t);
+                } else {
+                    to = f64::min(to, t);
+                }
             }
         }
+        (from <= to).then(|| (a + delta * from, a + delta * to))
     }
 
-    pub fn draw_pixel(&mut self, pixel: Vec3, col: u32) {
+    ///
+    /// Draws a line between two points projected in the same way as the vertices passed to `draw_triangle`. The line is
+    /// depth tested against what's already been drawn, but doesn't write to the depth buffer itself.
+    ///
+    pub fn draw_line_depth(&mut self, a: Vec3, b: Vec3, col: Colour) {
+        if a.z >= 0.0 || b.z >= 0.0 {
+            return;
+        }
+        // As with triangles, only X and Y are scaled up to raster space
+        let raster = |vec: Vec3| {
+            let centred = vec * self.scale + self.centre;
+            Vec3::new(centred.x, centred.y, vec.z)
+        };
+        let (a, b) = (raster(a), raster(b));
+
+        // Step one pixel at a time along the longest axis
+        let steps = (b.x - a.x).abs().max((b.y - a.y).abs()).ceil().max(1.0);
+        for step in 0..=steps as usize {
+            let point = a + (b - a) * (step as f64 / steps);
+            if point.x < 0.0
+                || point.y < 0.0
+                || point.x >= self.width as f64
+                || point.y >= self.height as f64
+            {
+                continue;
+            }
+            let (x, y) = (point.x as usize, point.y as usize);
+            if point.z >= self.depth_buffer[y][x] {
+                self.put_pixel(self.width * y + x, col);
+            }
+        }
+    }
+
+    ///
+    /// Works out the camera space position of a pixel from its depth, undoing the projection in `draw_triangle`.
+    ///
+    fn camera_position(&self, x: isize, y: isize, z: f64) -> Vec3 {
+        let scale = z / self.scale;
+        Vec3::new(
+            (x as f64 - self.centre.x) * scale,
+            (y as f64 - self.centre.y) * scale,
+            z,
+        )
+    }
+
+    ///
+    /// Marches a ray reflected off a surface across the screen, returning the index of the first pixel it passes behind.
+    /// Only things already drawn can be reflected, and the ray gives up if it leaves the screen.
+    ///
+    fn trace_reflection(&self, position: Vec3, normal: Vec3) -> Option<usize> {
+        let view = position.normalise();
+        let direction = view - normal * (2.0 * view.dot(normal));
+
+        for step in 1..=REFLECTION_STEPS {
+            let point = position + direction * (step as f64 * REFLECTION_STEP);
+            if point.z >= 0.0 {
+                return None;
+            }
+
+            let screen = point / point.z * self.scale + self.centre;
+            if screen.x < 0.0
+                || screen.y < 0.0
+                || screen.x >= self.width as f64
+                || screen.y >= self.height as f64
+            {
+                return None;
+            }
+
+            // Greater depths are closer to the camera, so the ray has passed behind whatever is drawn here
+            let (sx, sy) = (screen.x as usize, screen.y as usize);
+            let depth = self.depth_buffer[sy][sx];
+            if depth > -MAX_Z && depth > point.z {
+                return Some(self.width * sy + sx);
+            }
+        }
+        None
+    }
+
+    ///
+    /// Fills the screen with an equirectangular sky texture, without touching the depth buffer. `camera_to_world` turns
+    /// directions in camera space back into world space, so the sky turns with the camera.
+    ///
+    pub fn draw_skybox(&mut self, texture: &Texture, camera_to_world: Mat4) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if !self.in_field(x, y) {
+                    continue;
+                }
+                let view = self.camera_position(x as isize, y as isize, -1.0);
+                let direction = camera_to_world.transform_direction(view).normalise();
+
+                let u = direction.z.atan2(direction.x) / (2.0 * PI) + 0.5;
+                let v = 0.5 - direction.y.clamp(-1.0, 1.0).asin() / PI;
+                // Keep the bottom row of the sky from wrapping back round to the top of the texture
+                let col = texture.sample(Vec2::new(u.min(0.9999), v.min(0.9999)));
+                self.put_pixel(self.width * y + x, col);
+            }
+        }
+    }
+
+    ///
+    /// Sets every pixel being drawn this frame to a colour, leaving the depth buffer alone.
+    ///
+    pub fn fill(&mut self, col: Colour) {
+        if self.config.interlace == Interlace::Off {
+            self.buffer.fill(col.to_0rgb());
+            self.hdr_buffer.fill(to_f32(hdr::linear(col)));
+            return;
+        }
+        for i in 0..self.buffer.len() {
+            if self.in_field(i % self.width, i / self.width) {
+                self.buffer[i] = col.to_0rgb();
+            }
+        }
+        for i in 0..self.hdr_buffer.len() {
+            if self.in_field(i % self.width, i / self.width) {
+                self.hdr_buffer[i] = to_f32(hdr::linear(col));
+            }
+        }
+    }
+
+    ///
+    /// Returns whether triangles drawn with `blend` have their textures mapped affinely, which is when their blend asks
+    /// for it, or when they leave it to `Retro::affine_textures`.
+    ///
+    pub fn maps_affinely(&self, blend: Blend) -> bool {
+        match blend.texture_mapping {
+            Some(mapping) => mapping == TextureMapping::Affine,
+            None => self.config.retro.is_some_and(|retro| retro.affine_textures),
+        }
+    }
+
+    ///
+    /// Returns whether a pixel is drawn this frame, which is every pixel unless interlacing.
+    ///
+    pub fn in_field(&self, x: usize, y: usize) -> bool {
+        match self.config.interlace {
+            Interlace::Off => true,
+            Interlace::Scanlines => (y + self.field).is_multiple_of(2),
+            Interlace::Checkerboard => (x + y + self.field).is_multiple_of(2),
+        }
+    }
+
+    ///
+    /// Sets a pixel to a colour and its depth to `pixel.z`, if it's on screen, returning whether it was drawn. The pixel
+    /// isn't depth tested.
+    ///
+    pub fn draw_pixel(&mut self, pixel: Vec3, col: Colour) -> bool {
+        // Written so that NaN positions are left out too
+        let on_screen = pixel.x >= 0.0
+            && pixel.y >= 0.0
+            && pixel.x < self.width as f64
+            && pixel.y < self.height as f64;
+        if on_screen {
+            self.draw_pixel_unchecked(pixel, col);
+        }
+        on_screen
+    }
+
+    ///
+    /// Draws a pixel as with `draw_pixel`, without checking it's on screen first, for when the caller already knows it
+    /// is. Pixels off the right edge land on the next row, and ones below the bottom panic.
+    ///
+    pub fn draw_pixel_unchecked(&mut self, pixel: Vec3, col: Colour) {
         let ix = pixel.x as usize;
         let iy = pixel.y as usize;
 
         let i = (self.width * iy) + ix;
-        self.buffer[i] = col;
+        self.buffer[i] = col.to_0rgb();
         self.depth_buffer[iy][ix] = pixel.z;
     }
 
+    ///
+    /// Writes what's been drawn to a given path as an RGB PNG, for screenshots and rendering without a window.
+    ///
+    #[cfg(feature = "std")]
+    pub fn save_png(&self, path: &str) -> Result<(), io::Error> {
+        let image = Texture::new(self.width, self.height, self.buffer.clone());
+        image.save_png(path)
+    }
+
+    ///
+    /// Resets the screen to the clear colour, and the depth buffer and stats for a new frame. When interlacing, only the
+    /// half of the screen drawn in the new frame is cleared, and the rest keeps the last frame's world.
+    ///
     pub fn clear(&mut self) {
-        self.buffer = vec![_BLACK; self.width * self.height];
+        // The last frame may have been scaled up to full size
+        let (width, height) = self.config.render_size();
+        if (width, height) != (self.width, self.height) {
+            self.resize(width, height);
+        }
+        self.stats = RenderStats::default();
+        self.field ^= 1;
+        let clear = self.config.clear_colour;
+        self.hdr_buffer = match self.config.hdr {
+            Some(_) => vec![to_f32(hdr::linear(clear)); width * height],
+            None => vec![],
+        };
+        if self.config.interlace != Interlace::Off && self.previous.len() == width * height {
+            self.buffer.clone_from(&self.previous);
+            self.fill(clear);
+        } else {
+            self.buffer = vec![clear.to_0rgb(); width * height];
+        }
+        self.clear_depth();
+    }
+
+    ///
+    /// Sets the palette finished frames are reduced to by `resolve`, or goes back to full colour with `None`.
+    ///
+    pub fn set_palette(&mut self, palette: Option<Palette>) {
+        self.palette = palette;
+    }
+
+    pub fn palette(&self) -> Option<&Palette> {
+        self.palette.as_ref()
+    }
+
+    ///
+    /// Finishes the frame in `buffer`, ready to be shown - what's been drawn in HDR is tonemapped into it, it's reduced
+    /// to the retro colour depth, it's kept to be drawn over when interlacing, it's reduced to the palette, with its
+    /// cycles rotated to where they are at `time`, and then it's scaled up to full size if the render scale isn't 1.
+    /// `Camera::render_world` does this with the world's time once the world has been drawn, so anything drawn over it
+    /// afterwards, like text, goes straight into `buffer` at full resolution until the renderer is next cleared.
+    /// Without HDR, retro colours, a palette or a render scale, this does nothing.
+    ///
+    pub fn resolve(&mut self, time: f64) {
+        if let Some(hdr) = self.config.hdr {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("tonemap").entered();
+            // When interlacing, the pixels not drawn this frame were tonemapped in the last one
+            for (i, colour) in self.hdr_buffer.iter().enumerate() {
+                if self.in_field(i % self.width, i / self.width) {
+                    self.buffer[i] = hdr.tonemap(*colour).to_0rgb();
+                }
+            }
+        }
+        if let Some(retro) = self.config.retro {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("reduce colours").entered();
+            retro.reduce_colours(&mut self.buffer, self.width);
+        }
+        if self.config.interlace != Interlace::Off {
+            self.previous.clone_from(&self.buffer);
+        }
+        if let Some(palette) = &self.palette {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("palette").entered();
+            palette.apply(&mut self.buffer, self.width, time);
+        }
+
+        let (width, height) = (self.config.width, self.config.height);
+        if (width, height) != (self.width, self.height) && width > 0 && height > 0 {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("upscale").entered();
+            self.buffer = scale_pixels(
+                &self.buffer,
+                (self.width, self.height),
+                (width, height),
+                self.config.upscale,
+            );
+            // Anything drawn from here on is drawn as it's shown, without HDR
+            self.hdr_buffer = vec![];
+            self.resize(width, height);
+        }
+    }
+
+    ///
+    /// Blurs what's been drawn so far where it's out of focus, by the config's depth of field, using how far in front
+    /// of the camera each pixel is in the depth buffer. `Camera::render_world` does this once the world layer is
+    /// drawn, before the layers in front of it clear the depth buffer, so they stay sharp. Without depth of field, this
+    /// does nothing.
+    ///
+    pub fn apply_depth_of_field(&mut self) {
+        let Some(depth_of_field) = self.config.depth_of_field else {
+            return;
+        };
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("depth of field").entered();
+        let distances: Vec<f64> = self.depth_buffer.iter().flatten().map(|z| -z).collect();
+        let hdr = !self.hdr_buffer.is_empty();
+        let colours: Vec<Vec3> = (0..self.width * self.height)
+            .map(|i| match hdr {
+                true => self.hdr_pixel(i),
+                false => {
+                    let colour = Colour::from(self.buffer[i]);
+                    Vec3::new(colour.r as f64, colour.g as f64, colour.b as f64)
+                }
+            })
+            .collect();
+
+        let blurred = depth_of_field.blur(&colours, &distances, self.width);
+        // When interlacing, the pixels not drawn this frame were blurred in the last one
+        for (i, colour) in blurred.into_iter().enumerate() {
+            if !self.in_field(i % self.width, i / self.width) {
+                continue;
+            }
+            if hdr {
+                self.hdr_buffer[i] = to_f32(colour);
+            } else {
+                let channel = |value: f64| value.round().clamp(0.0, 255.0) as u8;
+                self.buffer[i] =
+                    Colour::new(channel(colour.x), channel(colour.y), channel(colour.z)).to_0rgb();
+            }
+        }
+    }
+
+    // Changes the size of the image being drawn, keeping the field of view, without touching the pixel buffers
+    fn resize(&mut self, width: usize, height: usize) {
+        self.scale *= width as f64 / self.width as f64;
+        self.width = width;
+        self.height = height;
+        self.centre = Vec3::new((width as f64) / 2.0, (height as f64) / 2.0, 0.0);
+        self.clear_depth();
+    }
+
+    ///
+    /// Resets the depth buffer but keeps the pixels, so anything drawn afterwards appears in front of what's already on
+    /// screen.
+    ///
+    pub fn clear_depth(&mut self) {
         self.depth_buffer = vec![vec![-MAX_Z; self.width]; self.height];
     }
 }
 
+// Narrows a colour to the precision it's kept at in the HDR buffer
+fn to_f32(col: Vec3) -> [f32; 3] {
+    [col.x as f32, col.y as f32, col.z as f32]
+}
+
 // Note that these functions discard the decimal components of the passed on floats
+// Scales an image of 0RGB pixels from one size to another, sampling each output pixel at its centre
+fn scale_pixels(
+    pixels: &[u32],
+    (width, height): (usize, usize),
+    (out_width, out_height): (usize, usize),
+    filter: Upscale,
+) -> Vec<u32> {
+    let (x_step, y_step) = (
+        width as f64 / out_width as f64,
+        height as f64 / out_height as f64,
+    );
+    let mut out = Vec::with_capacity(out_width * out_height);
+    for y in 0..out_height {
+        let sy = (y as f64 + 0.5) * y_step;
+        for x in 0..out_width {
+            let sx = (x as f64 + 0.5) * x_step;
+            let pixel = match filter {
+                Upscale::Nearest => {
+                    let (ix, iy) = ((sx as usize).min(width - 1), (sy as usize).min(height - 1));
+                    pixels[iy * width + ix]
+                }
+                Upscale::Bilinear => {
+                    // Blends between the centres of the four pixels around the sample, clamped at the edges
+                    let (fx, fy) = ((sx - 0.5).max(0.0), (sy - 0.5).max(0.0));
+                    let (x0, y0) = ((fx as usize).min(width - 1), (fy as usize).min(height - 1));
+                    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+                    let (tx, ty) = (fx - x0 as f64, fy - y0 as f64);
+                    let at = |x: usize, y: usize| Colour::from(pixels[y * width + x]);
+                    let top = at(x0, y0).lerp(at(x1, y0), tx);
+                    let bottom = at(x0, y1).lerp(at(x1, y1), tx);
+                    top.lerp(bottom, ty).to_0rgb()
+                }
+            };
+            out.push(pixel);
+        }
+    }
+    out
+}
+
 fn min3(a: f64, b: f64, c: f64) -> isize {
     min(a as isize, min(b as isize, c as isize))
 }
@@ -225,6 +1178,68 @@ fn max3(a: f64, b: f64, c: f64) -> isize {
     max(a as isize, max(b as isize, c as isize))
 }
 
+// The texture coordinates at each corner of a triangle being filled in. Triangles cut from a quad also carry the quad's
+// corners in raster space along with their texture coordinates
+struct TexCoords {
+    corners: [Vec2; 3],
+    quad: Option<([Vec2; 4], [Vec2; 4])>,
+}
+
+///
+/// Finds where a point is within a quad, as the fractions `u` and `v` of the way along its first edge and its last
+/// edge, and interpolates the quad's texture coordinates there. Returns `None` if the point can't be placed in the
+/// quad, e.g. if the quad is bent back on itself.
+///
+/// This inverts the bilinear mapping `a + (b - a)u + (d - a)v + (a - b + c - d)uv`, following
+/// https://iquilezles.org/articles/ibilinear/.
+///
+fn bilinear_coords(corners: &[Vec2; 4], coords: &[Vec2; 4], p: Vec2) -> Option<Vec2> {
+    let [a, b, c, d] = *corners;
+    let cross = |a: Vec2, b: Vec2| a.x * b.y - a.y * b.x;
+    let (e, f, g, h) = (b - a, d - a, a - b + c - d, p - a);
+    let k2 = cross(g, f);
+    let k1 = cross(e, f) + cross(h, g);
+    let k0 = cross(h, e);
+
+    // The fraction along the first edge, given the fraction along the last, from whichever axis is better conditioned
+    let u_at = |v: f64| {
+        let (x, y) = (e.x + g.x * v, e.y + g.y * v);
+        if x.abs() > y.abs() {
+            (h.x - f.x * v) / x
+        } else {
+            (h.y - f.y * v) / y
+        }
+    };
+    // Points on the edges can land a hair outside the quad, so a little slack is allowed
+    let inside = |value: f64| (-QUAD_SLACK..=1.0 + QUAD_SLACK).contains(&value);
+
+    let (u, v) = if k2.abs() < f64::EPSILON * k1.abs().max(1.0) {
+        // Opposite edges are parallel, so the equation is linear
+        let v = -k0 / k1;
+        (u_at(v), v)
+    } else {
+        let discriminant = k1 * k1 - 4.0 * k0 * k2;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        [(-k1 - root) / (2.0 * k2), (-k1 + root) / (2.0 * k2)]
+            .into_iter()
+            .map(|v| (u_at(v), v))
+            .find(|&(u, v)| inside(u) && inside(v))?
+    };
+    if !(u.is_finite() && v.is_finite() && inside(u) && inside(v)) {
+        return None;
+    }
+    let (u, v) = (u.clamp(0.0, 1.0), v.clamp(0.0, 1.0));
+    Some(
+        coords[0] * ((1.0 - u) * (1.0 - v))
+            + coords[1] * (u * (1.0 - v))
+            + coords[2] * (u * v)
+            + coords[3] * ((1.0 - u) * v),
+    )
+}
+
 /// Analogous to a `Vec3`, but easier to understand this way.
 ///
 /// Also, there's no need to include all of `Vec3`'s implementation.
@@ -259,6 +1274,7 @@ fn get_barycentric(a: Vec2, b: Vec2, c: Vec2, p: Vec2) -> Barycentric {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{hdr::Hdr, retro::Retro};
 
     #[test]
     fn min3_is_accurate() {
@@ -267,12 +1283,411 @@ mod test {
         assert_eq!(expected, result);
     }
 
+    fn solid(colour: u32) -> Texture {
+        Texture::new(1, 1, vec![colour])
+    }
+
+    #[test]
+    fn transparent_triangles_blend() {
+        let mut renderer = Renderer::new(RendererConfig::new(10, 10));
+        // Big enough to cover the whole screen once projected
+        let covering = |z: f64| {
+            [
+                Vec3::new(-2.0, -2.0, z),
+                Vec3::new(2.0, -2.0, z),
+                Vec3::new(0.0, 4.0, z),
+            ]
+        };
+        let coords = [Vec2::new(0.0, 0.0); 3];
+
+        renderer.draw_triangle(covering(-5.0), &solid(0xff0000), coords);
+        let blend = Blend {
+            opacity: 0.5,
+            ..Blend::OPAQUE
+        };
+        renderer.draw_triangle_blended(covering(-1.0), &solid(0x0000ff), coords, blend);
+        assert_eq!(renderer.buffer[55], 0x800080);
+
+        // Transparent triangles don't write depth, so an opaque one behind them still draws
+        renderer.draw_triangle(covering(-3.0), &solid(0x00ff00), coords);
+        assert_eq!(renderer.buffer[55], 0x00ff00);
+    }
+
+    #[test]
+    fn texture_mapping() {
+        // A triangle leaning away to the right, with a texture that's red on its left half and blue on its right
+        let leaning = [
+            Vec3::new(-0.4, -0.4, -1.0),
+            Vec3::new(0.4, -0.4, -10.0),
+            Vec3::new(-0.4, 0.4, -1.0),
+        ];
+        let coords = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 0.0),
+        ];
+        let texture = Texture::new(2, 1, vec![0xff0000, 0x0000ff]);
+        let drawn = |config: RendererConfig, texture_mapping| {
+            let mut renderer = Renderer::new(config);
+            let blend = Blend {
+                texture_mapping,
+                ..Blend::OPAQUE
+            };
+            renderer.draw_triangle_blended(leaning, &texture, coords, blend);
+            renderer.buffer[10 * 2 + 6]
+        };
+
+        // Most of the screen is taken up by the near end of the texture, unless the mapping is affine
+        let config = RendererConfig::new(10, 10);
+        assert_eq!(drawn(config, None), 0xff0000);
+        assert_eq!(drawn(config, Some(TextureMapping::Affine)), 0x0000ff);
+
+        // Triangles can pick their mapping whatever the renderer's retro settings are
+        let retro = RendererConfig {
+            retro: Some(Retro {
+                colour_depth: 8,
+                ..Retro::default()
+            }),
+            ..config
+        };
+        assert_eq!(drawn(retro, None), 0x0000ff);
+        assert_eq!(drawn(retro, Some(TextureMapping::Perspective)), 0xff0000);
+    }
+
+    #[test]
+    fn quads() {
+        // A quad narrower at the top than the bottom, with a texture that's red on its left half and blue on its right
+        let vertices = [
+            Vec3::new(-0.4, -0.2, -1.0),
+            Vec3::new(0.4, -0.2, -1.0),
+            Vec3::new(0.2, 0.2, -1.0),
+            Vec3::new(-0.2, 0.2, -1.0),
+        ];
+        let coords = [
+            Vec2::new(0.01, 0.0),
+            Vec2::new(0.99, 0.0),
+            Vec2::new(0.99, 0.0),
+            Vec2::new(0.01, 0.0),
+        ];
+        let texture = Texture::new(2, 1, vec![0xff0000, 0x0000ff]);
+        let affine = Blend {
+            texture_mapping: Some(TextureMapping::Affine),
+            ..Blend::OPAQUE
+        };
+        // Whether every row is red left of the middle and blue right of it, with either on the middle column
+        let split_down_middle = |renderer: &Renderer| {
+            (0..20).all(|y| {
+                (0..20).all(|x| match renderer.buffer[y * 20 + x] {
+                    0xff0000 => x <= 10,
+                    0x0000ff => x >= 10,
+                    _ => true,
+                })
+            })
+        };
+
+        let mut renderer = Renderer::new(RendererConfig::new(20, 20));
+        renderer.draw_quad_blended(vertices, &texture, coords, affine);
+        assert!(renderer.buffer.contains(&0xff0000) && renderer.buffer.contains(&0x0000ff));
+        assert!(split_down_middle(&renderer));
+        assert_eq!(renderer.stats().triangles, 2);
+
+        // Drawn as two separate triangles, the halves of the texture bend apart along the cut
+        let mut renderer = Renderer::new(RendererConfig::new(20, 20));
+        for (a, b, c) in [(0, 1, 2), (0, 2, 3)] {
+            renderer.draw_triangle_blended(
+                [vertices[a], vertices[b], vertices[c]],
+                &texture,
+                [coords[a], coords[b], coords[c]],
+                affine,
+            );
+        }
+        assert!(!split_down_middle(&renderer));
+
+        // Points are placed in a quad by inverting its bilinear mapping
+        let corners = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(4.0, 0.0),
+            Vec2::new(3.0, 2.0),
+            Vec2::new(1.0, 2.0),
+        ];
+        let unit = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(0.0, 1.0),
+        ];
+        let mapped = bilinear_coords(&corners, &unit, Vec2::new(1.5, 1.0)).unwrap();
+        assert!((mapped.x - 1.0 / 3.0).abs() < 1e-9 && (mapped.y - 0.5).abs() < 1e-9);
+        assert_eq!(bilinear_coords(&corners, &unit, corners[2]), Some(unit[2]));
+        assert_eq!(bilinear_coords(&corners, &unit, Vec2::new(5.0, 1.0)), None);
+    }
+
+    #[test]
+    fn ambient_and_fog() {
+        let mut renderer = Renderer::new(RendererConfig::new(10, 10));
+        let covering = [
+            Vec3::new(-2.0, -2.0, -5.0),
+            Vec3::new(2.0, -2.0, -5.0),
+            Vec3::new(0.0, 4.0, -5.0),
+        ];
+        let coords = [Vec2::new(0.0, 0.0); 3];
+        let blend = Blend {
+            ambient: Vec3::new(0.5, 1.0, 1.0),
+            ..Blend::OPAQUE
+        };
+        renderer.draw_triangle_blended(covering, &solid(0xffffff), coords, blend);
+        assert_eq!(renderer.buffer[55], 0x80ffff);
+
+        // Far enough away to be completely covered
+        let blend = Blend {
+            fog: Some(Fog {
+                colour: Vec3::new(0.0, 0.0, 1.0),
+                start: 1.0,
+                end: 2.0,
+            }),
+            ..Blend::OPAQUE
+        };
+        renderer.clear();
+        renderer.draw_triangle_blended(covering, &solid(0xffffff), coords, blend);
+        assert_eq!(renderer.buffer[55], 0x0000ff);
+    }
+
+    #[test]
+    fn hdr_keeps_bright_colours() {
+        let covering = [
+            Vec3::new(-2.0, -2.0, -5.0),
+            Vec3::new(2.0, -2.0, -5.0),
+            Vec3::new(0.0, 4.0, -5.0),
+        ];
+        let coords = [Vec2::new(0.0, 0.0); 3];
+        let bright = Blend {
+            ambient: Vec3::new(3.0, 1.0, 0.0),
+            ..Blend::OPAQUE
+        };
+
+        // Without HDR, anything brighter than white is clamped as it's drawn
+        let mut renderer = Renderer::new(RendererConfig::new(10, 10));
+        renderer.draw_triangle_blended(covering, &solid(0xffffff), coords, bright);
+        renderer.resolve(0.0);
+        assert_eq!(renderer.buffer[55], 0xffff00);
+
+        let config = RendererConfig {
+            hdr: Some(Hdr::default()),
+            ..RendererConfig::new(10, 10)
+        };
+        let mut renderer = Renderer::new(config);
+        renderer.draw_triangle_blended(covering, &solid(0xffffff), coords, bright);
+        // Nothing is shown until the frame is tonemapped
+        assert_eq!(renderer.buffer[55], 0x000000);
+        renderer.resolve(0.0);
+        assert_eq!(renderer.buffer[55], 0xbf8000);
+
+        // Blending mixes the unclamped colours
+        let blend = Blend {
+            opacity: 0.5,
+            ..Blend::OPAQUE
+        };
+        renderer.draw_triangle_blended(covering, &solid(0x000000), coords, blend);
+        renderer.resolve(0.0);
+        assert_eq!(renderer.buffer[55], 0x995500);
+    }
+
+    #[test]
+    fn fill_modes_and_render_scale() {
+        let config = RendererConfig {
+            render_scale: 0.5,
+            fill_mode: FillMode::Wireframe,
+            clear_colour: Colour::WHITE,
+            ..RendererConfig::new(40, 40)
+        };
+        let mut renderer = Renderer::new(config);
+        assert_eq!((renderer.width(), renderer.height()), (20, 20));
+        assert!(renderer.buffer.iter().all(|pixel| *pixel == 0xffffff));
+
+        // Wireframes draw the edges but leave the inside alone, even when the corners are far off screen
+        let triangle = [
+            Vec3::new(-0.2, -0.2, -1.0),
+            Vec3::new(0.2, -0.2, -1.0),
+            Vec3::new(0.0, 1000.0, -1.0),
+        ];
+        let coords = [Vec2::new(0.0, 0.0); 3];
+        renderer.draw_triangle(triangle, &solid(0xff0000), coords);
+        assert_eq!(renderer.buffer[20 * 6 + 6], WIREFRAME_COLOUR.to_0rgb());
+        assert_eq!(renderer.buffer[20 * 12 + 10], 0xffffff);
+
+        let mut renderer = Renderer::new(RendererConfig {
+            fill_mode: FillMode::Outlined,
+            ..config
+        });
+        renderer.draw_triangle(triangle, &solid(0xff0000), coords);
+        assert_eq!(renderer.buffer[20 * 6 + 6], WIREFRAME_COLOUR.to_0rgb());
+        assert_eq!(renderer.buffer[20 * 12 + 10], 0xff0000);
+    }
+
+    #[test]
+    fn upscaling() {
+        let config = RendererConfig {
+            render_scale: 0.5,
+            ..RendererConfig::new(4, 2)
+        };
+        let mut renderer = Renderer::new(config);
+        renderer.buffer.copy_from_slice(&[0x000000, 0xffffff]);
+        renderer.resolve(0.0);
+        // The frame is scaled up to full size, and stays there for anything drawn over it
+        assert_eq!((renderer.width(), renderer.height()), (4, 2));
+        assert_eq!(
+            renderer.buffer,
+            [0x000000, 0x000000, 0xffffff, 0xffffff].repeat(2)
+        );
+        assert!(renderer.draw_pixel(Vec3::new(3.0, 1.0, 0.0), Colour::from_hex(0xff0000)));
+        renderer.clear();
+        assert_eq!((renderer.width(), renderer.height()), (2, 1));
+
+        let mut renderer = Renderer::new(RendererConfig {
+            upscale: Upscale::Bilinear,
+            ..config
+        });
+        renderer.buffer.copy_from_slice(&[0x000000, 0xffffff]);
+        renderer.resolve(0.0);
+        // Pixels between the centres of the small image's pixels are blended, and ones past them take the edge's colour
+        assert_eq!(
+            renderer.buffer[..4],
+            [0x000000, 0x404040, 0xbfbfbf, 0xffffff]
+        );
+    }
+
+    #[test]
+    fn interlacing() {
+        // A triangle covering the whole screen
+        let covering = [
+            Vec3::new(-1.0, -1.0, -1.0),
+            Vec3::new(3.0, -1.0, -1.0),
+            Vec3::new(-1.0, 3.0, -1.0),
+        ];
+        let coords = [Vec2::new(0.0, 0.0); 3];
+        let frames = |interlace| {
+            let mut renderer = Renderer::new(RendererConfig {
+                interlace,
+                clear_colour: Colour::WHITE,
+                ..RendererConfig::new(2, 2)
+            });
+            renderer.draw_triangle(covering, &solid(0xff0000), coords);
+            renderer.resolve(0.0);
+            let first = renderer.buffer.clone();
+            renderer.clear();
+            renderer.draw_triangle(covering, &solid(0x0000ff), coords);
+            renderer.resolve(0.0);
+            (first, renderer.buffer.clone())
+        };
+
+        let (red, blue, white) = (0xff0000, 0x0000ff, 0xffffff);
+        assert_eq!(frames(Interlace::Off), (vec![red; 4], vec![blue; 4]));
+        // Half the pixels are drawn each frame, and the first frame's other half is left clear
+        assert_eq!(
+            frames(Interlace::Scanlines),
+            (vec![white, white, red, red], vec![blue, blue, red, red])
+        );
+        assert_eq!(
+            frames(Interlace::Checkerboard),
+            (vec![white, red, red, white], vec![blue, red, red, blue])
+        );
+    }
+
+    #[test]
+    fn rects() {
+        let mut renderer = Renderer::new(RendererConfig::new(10, 10));
+        renderer.clear();
+        // Rectangles hanging off the screen are cut down to fit
+        renderer.fill_rect(Vec2::new(-5.0, 8.0), Vec2::new(8.0, 5.0), Colour::WHITE);
+        assert_eq!(
+            renderer
+                .buffer
+                .iter()
+                .filter(|pixel| **pixel == 0xffffff)
+                .count(),
+            6
+        );
+
+        renderer.clear();
+        renderer.draw_rect(Vec2::new(2.0, 2.0), Vec2::new(4.0, 3.0), Colour::WHITE);
+        assert_eq!(
+            renderer
+                .buffer
+                .iter()
+                .filter(|pixel| **pixel == 0xffffff)
+                .count(),
+            10
+        );
+        assert_eq!(renderer.buffer[3 * 10 + 3], 0x000000);
+
+        assert_eq!(renderer.text_width("", 14.0), 0.0);
+        assert!(renderer.text_width("ab", 14.0) > renderer.text_width("a", 14.0));
+    }
+
+    #[test]
+    fn pixels_are_clipped() {
+        let mut renderer = Renderer::new(RendererConfig::new(10, 10));
+        assert!(renderer.draw_pixel(Vec3::new(9.5, 0.0, -1.0), Colour::WHITE));
+        assert_eq!(renderer.buffer[9], 0xffffff);
+
+        // Pixels off the right edge used to wrap onto the next row
+        for pixel in [
+            Vec3::new(10.0, 0.0, -1.0),
+            Vec3::new(-1.0, 5.0, -1.0),
+            Vec3::new(5.0, 10.0, -1.0),
+            Vec3::new(f64::NAN, 0.0, -1.0),
+        ] {
+            assert!(!renderer.draw_pixel(pixel, Colour::WHITE));
+        }
+        assert_eq!(
+            renderer.buffer.iter().filter(|pixel| **pixel != 0).count(),
+            1
+        );
+
+        // Text hanging off any edge is cut off rather than panicking
+        renderer.write_text("hello", Vec2::new(-6.0, -8.0), 14.0);
+        renderer.write_text("hello", Vec2::new(4.0, 5.0), 14.0);
+    }
+
+    #[test]
+    fn fonts() {
+        let config = RendererConfig::new(8, 8);
+        assert!(Renderer::with_font(config, FONT).is_ok());
+        assert!(matches!(
+            Renderer::with_font(config, &[0; 16]),
+            Err(Error::FontError(_))
+        ));
+    }
+
+    #[test]
+    fn textures_are_interpolated_across_triangles() {
+        let mut renderer = Renderer::new(RendererConfig::new(20, 20));
+        let texture = Texture::new(4, 1, vec![0xff0000, 0x00ff00, 0x0000ff, 0xffffff]);
+        let vertices = [
+            Vec3::new(0.4, -0.45, -1.0),
+            Vec3::new(-0.45, -0.45, -1.0),
+            Vec3::new(0.0, 0.45, -1.0),
+        ];
+        let coords = [
+            Vec2::new(0.625, 0.0),
+            Vec2::new(0.125, 0.0),
+            Vec2::new(0.375, 0.0),
+        ];
+        renderer.draw_triangle(vertices, &texture, coords);
+
+        // Each corner of the triangle shows the texel under its own texture coordinate
+        let near_first = renderer.buffer[2 * 20 + 16];
+        let near_second = renderer.buffer[2 * 20 + 3];
+        assert_eq!(near_first, 0x0000ff);
+        assert_eq!(near_second, 0xff0000);
+    }
+
     #[test]
     fn barycentric() {
         let expected = Barycentric {
             u: 0.5,
-            v: 0.5,
-            w: 0.0,
+            v: 0.25,
+            w: 0.25,
         };
         let a = Vec2::new(0.0, 0.0);
         let b = Vec2::new(1.0, 1.0);
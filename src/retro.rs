@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::float::Float;
+use crate::{framebuffer::Colour, palette, vec::vec3::Vec3};
+
+///
+/// Settings for drawing like a mid-90s console, each of which can be turned off on its own. The defaults are a preset
+/// close to the original PlayStation.
+/// - `vertex_snap` rounds the corners of each triangle to a grid this many pixels across, so edges jitter from one
+///   pixel to the next as things move rather than sliding smoothly. 0 leaves them where they land
+/// - `affine_textures` interpolates texture coordinates across the screen without correcting for depth, which warps
+///   and swims textures on triangles seen at an angle. Entities can pick their own with `World::set_texture_mapping`
+/// - `colour_depth` is how many bits are kept of each colour channel, up to 8
+/// - `dither` breaks up the bands left by a low colour depth with an ordered pattern
+/// - `depth_precision` rounds the depth each vertex is divided by to steps of this size in camera space, as low
+///   precision hardware did. Steps are large next to the camera, so triangles close to it wobble as it moves. 0 divides
+///   by the exact depth
+///
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Retro {
+    pub vertex_snap: f64,
+    pub affine_textures: bool,
+    pub colour_depth: u8,
+    pub dither: bool,
+    pub depth_precision: f64,
+}
+
+impl Retro {
+    ///
+    /// Moves a vertex divided by its depth, as the renderer is given it, to where it lands when divided by its depth
+    /// rounded to `depth_precision`.
+    ///
+    pub(crate) fn wobble(&self, vertex: Vec3) -> Vec3 {
+        if self.depth_precision <= 0.0 {
+            return vertex;
+        }
+        // Points are never rounded onto the camera itself, which they couldn't be divided by
+        let rounded = ((vertex.z / self.depth_precision).round() * self.depth_precision)
+            .min(-self.depth_precision);
+        let ratio = vertex.z / rounded;
+        Vec3::new(vertex.x * ratio, vertex.y * ratio, vertex.z)
+    }
+
+    ///
+    /// Rounds a vertex in raster space to the grid set by `vertex_snap`, leaving its depth alone.
+    ///
+    pub(crate) fn snap(&self, vertex: Vec3) -> Vec3 {
+        if self.vertex_snap <= 0.0 {
+            return vertex;
+        }
+        let snap = |value: f64| (value / self.vertex_snap).round() * self.vertex_snap;
+        Vec3::new(snap(vertex.x), snap(vertex.y), vertex.z)
+    }
+
+    ///
+    /// Reduces a frame of 0RGB pixels, `width` pixels wide, to `colour_depth` bits a channel, dithering it if `dither`
+    /// is set.
+    ///
+    pub(crate) fn reduce_colours(&self, pixels: &mut [u32], width: usize) {
+        let bits = self.colour_depth.clamp(1, 8);
+        if bits == 8 || width == 0 {
+            return;
+        }
+        let levels = ((1 << bits) - 1) as f64;
+        let step = u8::MAX as f64 / levels;
+        for (i, pixel) in pixels.iter_mut().enumerate() {
+            let offset = if self.dither {
+                palette::bayer_offset(i % width, i / width) * step
+            } else {
+                0.0
+            };
+            let reduce = |channel: u8| {
+                let level = ((channel as f64 + offset) / step)
+                    .round()
+                    .clamp(0.0, levels);
+                (level * step).round() as u8
+            };
+            let colour = Colour::from(*pixel);
+            *pixel = Colour::new(reduce(colour.r), reduce(colour.g), reduce(colour.b)).to_0rgb();
+        }
+    }
+}
+
+impl Default for Retro {
+    fn default() -> Self {
+        Retro {
+            vertex_snap: 1.0,
+            affine_textures: true,
+            colour_depth: 5,
+            dither: true,
+            depth_precision: 0.0625,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn vertices() {
+        let retro = Retro::default();
+        assert_eq!(
+            retro.snap(Vec3::new(10.4, 3.6, -2.0)),
+            Vec3::new(10.0, 4.0, -2.0)
+        );
+
+        // Rounding the depth moves points near the camera much further than distant ones
+        let near = retro.wobble(Vec3::new(1.0, 1.0, -0.1));
+        let far = retro.wobble(Vec3::new(1.0, 1.0, -10.02));
+        assert!((near.x - 1.0).abs() > 0.1);
+        assert!((far.x - 1.0).abs() < 0.01);
+        assert_eq!((near.z, far.z), (-0.1, -10.02));
+
+        // Points are never divided by a depth of zero
+        assert!(retro.wobble(Vec3::new(1.0, 1.0, -0.01)).x.is_finite());
+
+        let off = Retro {
+            vertex_snap: 0.0,
+            depth_precision: 0.0,
+            ..retro
+        };
+        let vertex = Vec3::new(10.4, 3.6, -0.1);
+        assert_eq!(off.snap(off.wobble(vertex)), vertex);
+    }
+
+    #[test]
+    fn colours() {
+        // Five bits a channel leaves steps of a little over 8
+        let flat = Retro {
+            dither: false,
+            ..Retro::default()
+        };
+        let mut pixels = vec![0x000000, 0xffffff, 0x0a0a0a];
+        flat.reduce_colours(&mut pixels, 3);
+        assert_eq!(pixels, [0x000000, 0xffffff, 0x080808]);
+
+        // Dithering mixes the two nearest levels
+        let mut pixels = vec![0x0c0c0c; 16];
+        Retro::default().reduce_colours(&mut pixels, 4);
+        assert!(pixels.contains(&0x080808) && pixels.contains(&0x101010));
+        assert!(pixels
+            .iter()
+            .all(|pixel| [0x080808, 0x101010].contains(pixel)));
+
+        // Full colour depth is left alone
+        let full = Retro {
+            colour_depth: 8,
+            ..Retro::default()
+        };
+        let mut pixels = vec![0x123456];
+        full.reduce_colours(&mut pixels, 1);
+        assert_eq!(pixels, [0x123456]);
+    }
+}
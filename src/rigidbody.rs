@@ -1,14 +1,470 @@
-use crate::{Object, Vec3};
+use crate::{collider::Collider, mass::MassProperties, mat4::Mat4, quat::Quat, Object, Vec3};
 
+///
+/// A physically simulated body, attached to an entity as a component and moved by `World::step_physics`.
+/// - `mass` is the body's mass. Bodies with a mass of zero or less, or an infinite mass, are never moved or spun
+/// - `velocity` is how far the body moves per second
+/// - `collider` is the shape the body collides with. Bodies without one pass through everything
+/// - `restitution` is how bouncy the body is, from 0 for no bounce to 1 for bouncing back at full speed. When two
+///   bodies hit each other, the bouncier of the two is used
+/// - `static_friction` is how hard the body grips surfaces it's resting on, and `dynamic_friction` how much it's
+///   slowed while sliding across them. Static friction should be at least as high as dynamic friction
+///
+/// - `angular_velocity` is the axis the body is spinning around in world space, with a length of how fast it spins
+///   in radians per second
+/// - `gravity_scale` multiplies the world's gravity for this body, so 0 makes it float and 2 makes it fall twice as
+///   fast
+/// - `trigger` makes the body's collider a trigger volume, which other bodies pass straight through. Bodies entering
+///   and leaving it are reported as collision events instead, for checkpoints and damage zones. Triggers still fall
+///   under gravity unless they're given a mass of zero, or their entity is static
+/// - `linear_damping` and `angular_damping` are how quickly the body slows down moving and spinning, like air
+///   resistance. Each second, its speed falls by a factor of e raised to the damping
+///
+/// Forces applied with `apply_force` and torques applied with `apply_torque` are accumulated until the next step, and
+/// then cleared.
+///
+/// The body turns around its centre of mass, which is the centre of the solid enclosed by the object's mesh (see
+/// `MassProperties`). Objects which don't enclose anything, like flat quads, use the average of their vertices instead.
+///
+/// How hard the body is to spin comes from its inertia tensor, which is worked out from the collider's shape, or from
+/// the object's mesh if it has no box, sphere or hull collider. Axis-aligned box colliders stay lined up with the world
+/// axes as the body turns, so bodies which should tumble are better given an oriented box.
+///
 pub struct Rigidbody {
     object: Object,
     centre: Vec3,
+    pub mass: f64,
+    pub velocity: Vec3,
+    pub collider: Option<Collider>,
+    pub restitution: f64,
+    pub static_friction: f64,
+    pub dynamic_friction: f64,
+    pub angular_velocity: Vec3,
+    pub gravity_scale: f64,
+    pub trigger: bool,
+    pub linear_damping: f64,
+    pub angular_damping: f64,
+    orientation: Quat,
+    vertex_inertia: Mat4,
+    acceleration: Vec3,
+    force: Vec3,
+    torque: Vec3,
 }
 
 impl Rigidbody {
     pub fn new(object: Object) -> Rigidbody {
-        let centre: Vec3 =
-            object.vertices.iter().copied().sum::<Vec3>() / object.vertices.len() as f64;
-        Rigidbody { object, centre }
+        let (centre, vertex_inertia) = match MassProperties::from_object(&object) {
+            Some(mass) => (mass.centre, mass.inertia),
+            None => {
+                let centre =
+                    object.vertices.iter().copied().sum::<Vec3>() / object.vertices.len() as f64;
+                (centre, point_inertia(&object.vertices, centre))
+            }
+        };
+        Rigidbody {
+            object,
+            centre,
+            mass: 1.0,
+            velocity: Vec3::default(),
+            collider: None,
+            restitution: 0.5,
+            static_friction: 0.6,
+            dynamic_friction: 0.4,
+            angular_velocity: Vec3::default(),
+            gravity_scale: 1.0,
+            trigger: false,
+            linear_damping: 0.0,
+            angular_damping: 0.0,
+            orientation: Quat::identity(),
+            vertex_inertia,
+            acceleration: Vec3::default(),
+            force: Vec3::default(),
+            torque: Vec3::default(),
+        }
+    }
+
+    pub fn with_mass(mut self, mass: f64) -> Rigidbody {
+        self.mass = mass;
+        self
+    }
+
+    pub fn with_collider(mut self, collider: Collider) -> Rigidbody {
+        self.collider = Some(collider);
+        self
+    }
+
+    pub fn with_restitution(mut self, restitution: f64) -> Rigidbody {
+        self.restitution = restitution;
+        self
+    }
+
+    pub fn with_friction(mut self, static_friction: f64, dynamic_friction: f64) -> Rigidbody {
+        self.static_friction = static_friction;
+        self.dynamic_friction = dynamic_friction;
+        self
+    }
+
+    pub fn with_gravity_scale(mut self, gravity_scale: f64) -> Rigidbody {
+        self.gravity_scale = gravity_scale;
+        self
+    }
+
+    pub fn with_trigger(mut self, trigger: bool) -> Rigidbody {
+        self.trigger = trigger;
+        self
+    }
+
+    pub fn with_damping(mut self, linear_damping: f64, angular_damping: f64) -> Rigidbody {
+        self.linear_damping = linear_damping;
+        self.angular_damping = angular_damping;
+        self
+    }
+
+    pub fn object(&self) -> &Object {
+        &self.object
+    }
+
+    pub fn centre(&self) -> Vec3 {
+        self.centre
+    }
+
+    ///
+    /// Returns which way the body is turned in world space. Bodies attached to an entity follow the rotation of its
+    /// transformation, which the world copies across at the start of every step.
+    ///
+    pub fn orientation(&self) -> Quat {
+        self.orientation
+    }
+
+    pub(crate) fn set_orientation(&mut self, orientation: Quat) {
+        self.orientation = orientation;
+    }
+
+    ///
+    /// Returns one over the body's mass, which is zero for bodies which can't be moved.
+    ///
+    pub fn inverse_mass(&self) -> f64 {
+        if self.mass > 0.0 && self.mass.is_finite() {
+            1.0 / self.mass
+        } else {
+            0.0
+        }
+    }
+
+    ///
+    /// Returns the acceleration the body had during the last step, including gravity.
+    ///
+    pub fn acceleration(&self) -> Vec3 {
+        self.acceleration
+    }
+
+    ///
+    /// Returns the total force applied since the last step.
+    ///
+    pub fn force(&self) -> Vec3 {
+        self.force
+    }
+
+    ///
+    /// Returns the total torque applied since the last step.
+    ///
+    pub fn torque(&self) -> Vec3 {
+        self.torque
+    }
+
+    ///
+    /// Pushes the body with a force through its centre of mass, which lasts until the next step.
+    ///
+    pub fn apply_force(&mut self, force: Vec3) {
+        self.force = self.force + force;
+    }
+
+    ///
+    /// Pushes the body with a force at a point, given as an offset from its centre of mass in world space (see
+    /// `World::centre_of_mass`). As well as pushing the body along, forces away from the centre twist it.
+    ///
+    pub fn apply_force_at_point(&mut self, force: Vec3, offset: Vec3) {
+        self.apply_force(force);
+        self.apply_torque(offset.cross_product(force));
+    }
+
+    ///
+    /// Changes the body's velocity straight away with a sudden push through its centre of mass, like a hit or an
+    /// explosion, rather than over the course of a step like a force.
+    ///
+    pub fn apply_impulse(&mut self, impulse: Vec3) {
+        self.velocity = self.velocity + impulse * self.inverse_mass();
+    }
+
+    ///
+    /// Twists the body around an axis in world space, which lasts until the next step.
+    ///
+    pub fn apply_torque(&mut self, torque: Vec3) {
+        self.torque = self.torque + torque;
+    }
+
+    ///
+    /// Returns the body's inertia tensor in its own space, around its centre of mass.
+    ///
+    pub fn inertia(&self) -> Mat4 {
+        let unit = match self.collider {
+            Some(Collider::Sphere { radius, .. }) => {
+                diagonal(Vec3::new(1.0, 1.0, 1.0) * (0.4 * radius * radius))
+            }
+            Some(Collider::Box(aabb)) => box_inertia(aabb.size()),
+            Some(Collider::Obb(obb)) => {
+                let [x, y, z] = obb.axes;
+                let axes = Mat4 {
+                    m: [
+                        [x.x, y.x, z.x, 0.0],
+                        [x.y, y.y, z.y, 0.0],
+                        [x.z, y.z, z.z, 0.0],
+                        [0.0, 0.0, 0.0, 1.0],
+                    ],
+                };
+                axes.mult(box_inertia(obb.half_extents * 2.0))
+                    .mult(axes.transpose())
+            }
+            Some(Collider::Hull(ref hull)) => match hull.mass_properties() {
+                Some(mass) => mass.inertia_about(self.centre),
+                None => point_inertia(hull.points(), self.centre),
+            },
+            Some(Collider::Plane(_)) | None => self.vertex_inertia,
+        };
+        let mut inertia = unit;
+        for row in inertia.m.iter_mut().take(3) {
+            for value in row.iter_mut().take(3) {
+                *value *= self.mass;
+            }
+        }
+        inertia
+    }
+
+    ///
+    /// Returns the inverse of the inertia tensor turned to match the body's orientation, which converts a torque in
+    /// world space into a change in angular velocity. It's zero for bodies which can't be moved or spun.
+    ///
+    pub fn inverse_inertia(&self) -> Mat4 {
+        let zero = diagonal(Vec3::default());
+        if self.inverse_mass() == 0.0 {
+            return zero;
+        }
+        let Some(inverse) = self.inertia().inverse() else {
+            return zero;
+        };
+        let rotation = self.orientation.to_mat4();
+        rotation.mult(inverse).mult(rotation.transpose())
+    }
+
+    ///
+    /// Advances the body's velocity by `dt` using semi-implicit Euler integration, and returns how far it moves and
+    /// how much it turns around its centre of mass. The velocity is updated first and then used for the movement,
+    /// which keeps orbits and springs stable.
+    ///
+    pub fn integrate(&mut self, gravity: Vec3, dt: f64) -> (Vec3, Quat) {
+        let inverse_mass = self.inverse_mass();
+        let force = std::mem::take(&mut self.force);
+        let torque = std::mem::take(&mut self.torque);
+        if inverse_mass == 0.0 {
+            self.acceleration = Vec3::default();
+            return (Vec3::default(), Quat::identity());
+        }
+
+        self.acceleration = gravity * self.gravity_scale + force * inverse_mass;
+        self.velocity =
+            (self.velocity + self.acceleration * dt) * (-self.linear_damping * dt).exp();
+
+        let inverse_inertia = self.inverse_inertia();
+        self.angular_velocity = (self.angular_velocity
+            + inverse_inertia.transform_direction(torque) * dt)
+            * (-self.angular_damping * dt).exp();
+        let angle = self.angular_velocity.length() * dt;
+        let rotation = if angle > f64::EPSILON {
+            Quat::from_axis_angle(self.angular_velocity, angle)
+        } else {
+            Quat::identity()
+        };
+        self.orientation = (rotation * self.orientation).normalise();
+
+        (self.velocity * dt, rotation)
+    }
+}
+
+fn diagonal(values: Vec3) -> Mat4 {
+    Mat4 {
+        m: [
+            [values.x, 0.0, 0.0, 0.0],
+            [0.0, values.y, 0.0, 0.0],
+            [0.0, 0.0, values.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    }
+}
+
+///
+/// The inertia tensor of a solid box with a mass of one.
+///
+fn box_inertia(size: Vec3) -> Mat4 {
+    let (x, y, z) = (size.x * size.x, size.y * size.y, size.z * size.z);
+    diagonal(Vec3::new(y + z, x + z, x + y) / 12.0)
+}
+
+///
+/// The inertia tensor of a mass of one spread evenly between a set of points.
+///
+fn point_inertia(points: &[Vec3], centre: Vec3) -> Mat4 {
+    let mut inertia = diagonal(Vec3::default());
+    if points.is_empty() {
+        return inertia;
+    }
+    let share = 1.0 / points.len() as f64;
+    for point in points {
+        let r = *point - centre;
+        let r = [r.x, r.y, r.z];
+        let squared = r.iter().map(|v| v * v).sum::<f64>();
+        for i in 0..3 {
+            for j in 0..3 {
+                let identity = if i == j { squared } else { 0.0 };
+                inertia.m[i][j] += share * (identity - r[i] * r[j]);
+            }
+        }
+    }
+    inertia
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        framebuffer::Colour,
+        geometry::{aabb::Aabb, hull::ConvexHull, obb::Obb},
+        object::Texture,
+        vec::vec3::{X_AXIS, Y_AXIS, Z_AXIS},
+    };
+    use std::f64::consts::FRAC_PI_2;
+
+    fn body() -> Rigidbody {
+        let texture = Texture::new(0, 0, vec![]);
+        Rigidbody::new(Object::new(
+            vec![Vec3::new(1.0, 1.0, 1.0)],
+            vec![],
+            vec![],
+            vec![],
+            texture,
+        ))
+    }
+
+    #[test]
+    fn integrate_forces() {
+        let mut body = body().with_mass(2.0);
+        body.apply_force(Vec3::new(4.0, 0.0, 0.0));
+
+        let (moved, _) = body.integrate(Vec3::new(0.0, -10.0, 0.0), 0.5);
+        assert_eq!(body.acceleration(), Vec3::new(2.0, -10.0, 0.0));
+        assert_eq!(body.velocity, Vec3::new(1.0, -5.0, 0.0));
+        assert_eq!(moved, Vec3::new(0.5, -2.5, 0.0));
+
+        // Forces only last for one step
+        assert_eq!(body.force(), Vec3::default());
+        body.integrate(Vec3::default(), 0.5);
+        assert_eq!(body.velocity, Vec3::new(1.0, -5.0, 0.0));
+    }
+
+    #[test]
+    fn immovable_bodies() {
+        let mut body = body().with_mass(0.0);
+        body.velocity = Vec3::new(1.0, 0.0, 0.0);
+        body.apply_torque(Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(
+            body.integrate(Vec3::new(0.0, -10.0, 0.0), 1.0),
+            (Vec3::default(), Quat::identity())
+        );
+        assert_eq!(body.angular_velocity, Vec3::default());
+    }
+
+    #[test]
+    fn impulses_and_offset_forces() {
+        let mut body = body().with_mass(2.0).with_collider(Collider::Sphere {
+            centre: Vec3::default(),
+            radius: 1.0,
+        });
+        body.apply_impulse(Vec3::new(4.0, 0.0, 0.0));
+        assert_eq!(body.velocity, Vec3::new(2.0, 0.0, 0.0));
+
+        // Pushing the top of the body sideways moves it and tips it over
+        body.apply_force_at_point(Vec3::new(1.0, 0.0, 0.0), Y_AXIS);
+        assert_eq!(body.force(), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(body.torque(), Vec3::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn gravity_scale_and_damping() {
+        let mut floating = body().with_gravity_scale(0.0);
+        floating.integrate(Vec3::new(0.0, -10.0, 0.0), 1.0);
+        assert_eq!(floating.velocity, Vec3::default());
+
+        let mut damped = body().with_damping(2.0, 1.0);
+        damped.velocity = Vec3::new(1.0, 0.0, 0.0);
+        damped.angular_velocity = Vec3::new(0.0, 1.0, 0.0);
+        damped.integrate(Vec3::default(), 0.5);
+        assert!((damped.velocity.x - (-1.0f64).exp()).abs() < 1e-9);
+        assert!((damped.angular_velocity.y - (-0.5f64).exp()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn inertia_from_colliders() {
+        let sphere = body().with_mass(5.0).with_collider(Collider::Sphere {
+            centre: Vec3::default(),
+            radius: 2.0,
+        });
+        assert_eq!(sphere.inertia().transform_direction(X_AXIS), X_AXIS * 8.0);
+
+        // A long box is easier to spin around its length than across it
+        let aabb = Aabb::new(Vec3::new(-3.0, -0.5, -0.5), Vec3::new(3.0, 0.5, 0.5));
+        let long = body().with_mass(12.0).with_collider(Collider::Box(aabb));
+        let inertia = long.inertia();
+        assert_eq!(inertia.transform_direction(X_AXIS), X_AXIS * 2.0);
+        assert_eq!(inertia.transform_direction(Y_AXIS), Y_AXIS * 37.0);
+
+        // Turning the box around turns its inertia with it
+        let turned = Obb::from_aabb(aabb).transform(Mat4::identity().rotate(Z_AXIS, FRAC_PI_2));
+        let turned = body().with_mass(12.0).with_collider(Collider::Obb(turned));
+        let spin = turned.inertia().transform_direction(Y_AXIS);
+        assert!((spin - Y_AXIS * 2.0).length() < 1e-9, "{}", spin);
+    }
+
+    #[test]
+    fn centre_of_mass_from_mesh() {
+        // Most of a pyramid's vertices are around its base, but its centre is a quarter of the way up
+        let pyramid = ConvexHull::from_points(&[
+            Vec3::new(-1.0, 0.0, -1.0),
+            Vec3::new(1.0, 0.0, -1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(-1.0, 0.0, 1.0),
+            Vec3::new(0.0, 4.0, 0.0),
+        ])
+        .unwrap();
+        let centre = Rigidbody::new(pyramid.mesh(Colour::WHITE)).centre();
+        assert!((centre - Y_AXIS).length() < 1e-9, "{}", centre);
+
+        // Objects which don't enclose anything fall back to the average of their vertices
+        assert_eq!(body().centre(), Vec3::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn torques_spin_bodies() {
+        let mut body = body().with_collider(Collider::Sphere {
+            centre: Vec3::default(),
+            radius: 1.0,
+        });
+        body.apply_torque(Z_AXIS * 0.4);
+
+        // With an inertia of 0.4, the body speeds up by one radian per second every second
+        let (_, rotation) = body.integrate(Vec3::default(), 0.5);
+        assert_eq!(body.angular_velocity, Z_AXIS * 0.5);
+        assert_eq!(body.torque(), Vec3::default());
+        let turned = rotation.rotate(X_AXIS);
+        assert!((turned - Vec3::new(0.25f64.cos(), 0.25f64.sin(), 0.0)).length() < 1e-9);
+        assert!((body.orientation().dot(rotation) - 1.0).abs() < 1e-9);
     }
 }
@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+
+use crate::{
+    geometry::{aabb::Aabb, frustum::Frustum},
+    mat4::Mat4,
+    object::Object,
+    sprite::Sprite,
+    terrain::Terrain,
+    vec::vec3::{Vec3, Y_AXIS},
+};
+
+///
+/// What each instance in a `Scatter` is drawn as. `Billboard`s always face the camera, centred on their instance, and
+/// are the cheapest to draw. `Mesh`es are turned by their instance's yaw, and should be low-poly, as every vertex is
+/// moved for every instance on screen.
+///
+#[derive(Clone)]
+pub enum ScatterModel {
+    Billboard(Sprite),
+    Mesh(Box<Object>),
+}
+
+impl ScatterModel {
+    ///
+    /// Returns how far the model reaches from an instance's position at a scale of 1.
+    ///
+    fn radius(&self) -> f64 {
+        match self {
+            ScatterModel::Billboard(sprite) => sprite.size.x.hypot(sprite.size.y) / 2.0,
+            ScatterModel::Mesh(object) => object
+                .vertices
+                .iter()
+                .map(|vertex| object.transformation.transform(*vertex).length())
+                .fold(0.0, f64::max),
+        }
+    }
+}
+
+///
+/// A single copy of a scatter's model, stored in single precision to keep large scatters small.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ScatterInstance {
+    position: [f32; 3],
+    yaw: f32,
+    scale: f32,
+}
+
+impl ScatterInstance {
+    pub fn new(position: Vec3, yaw: f64, scale: f64) -> ScatterInstance {
+        ScatterInstance {
+            position: [position.x as f32, position.y as f32, position.z as f32],
+            yaw: yaw as f32,
+            scale: scale as f32,
+        }
+    }
+
+    pub fn position(&self) -> Vec3 {
+        let [x, y, z] = self.position;
+        Vec3::new(x as f64, y as f64, z as f64)
+    }
+
+    ///
+    /// Returns the instance's rotation around the Y axis, in radians.
+    ///
+    pub fn yaw(&self) -> f64 {
+        self.yaw as f64
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale as f64
+    }
+
+    ///
+    /// Computes the matrix placing a mesh at the instance - scaled, turned around the Y axis, then moved into place.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        let scale = self.scale();
+        Mat4::identity()
+            .scale(Vec3::new(scale, scale, scale))
+            .rotate(Y_AXIS, self.yaw())
+            .translate(self.position())
+    }
+}
+
+// The instances in one square of the ground, and the box containing all of them
+struct Chunk {
+    bounds: Aabb,
+    instances: Vec<ScatterInstance>,
+}
+
+///
+/// Thousands of copies of a single lightweight model, like grass tufts or rocks, which are drawn with the world
+/// without each needing to be an entity. Instances are grouped into square chunks `chunk_size` wide on the XZ plane,
+/// and only chunks in view and within `draw_distance` of the camera are drawn.
+///
+/// Add scatters to `World::scatters` to draw them. Instances are only drawn - they don't collide with anything.
+///
+pub struct Scatter {
+    pub model: ScatterModel,
+    pub draw_distance: f64,
+    chunk_size: f64,
+    radius: f64,
+    chunks: Vec<Chunk>,
+    cells: HashMap<(i64, i64), usize>,
+    len: usize,
+}
+
+impl Scatter {
+    pub fn new(model: ScatterModel, chunk_size: f64, draw_distance: f64) -> Scatter {
+        Scatter {
+            radius: model.radius(),
+            model,
+            draw_distance,
+            chunk_size,
+            chunks: vec![],
+            cells: HashMap::new(),
+            len: 0,
+        }
+    }
+
+    pub fn chunk_size(&self) -> f64 {
+        self.chunk_size
+    }
+
+    ///
+    /// Returns the number of instances in the scatter.
+    ///
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn add(&mut self, instance: ScatterInstance) {
+        let position = instance.position();
+        let cell = (
+            (position.x / self.chunk_size).floor() as i64,
+            (position.z / self.chunk_size).floor() as i64,
+        );
+        let reach = self.radius * instance.scale().abs();
+        let bounds = Aabb::new(position, position).inflate(reach);
+
+        let chunks = &mut self.chunks;
+        let index = *self.cells.entry(cell).or_insert_with(|| {
+            chunks.push(Chunk {
+                bounds,
+                instances: vec![],
+            });
+            chunks.len() - 1
+        });
+        let chunk = &mut self.chunks[index];
+        chunk.bounds = chunk.bounds.union(bounds);
+        chunk.instances.push(instance);
+        self.len += 1;
+    }
+
+    ///
+    /// Scatters `count` instances over a terrain, each at a random position and yaw, with a random scale between
+    /// `min_scale` and `max_scale`. Billboards are raised by half their height so they stand on the ground. The same
+    /// seed always places them the same way.
+    ///
+    pub fn scatter_over(
+        &mut self,
+        terrain: &Terrain,
+        count: usize,
+        (min_scale, max_scale): (f64, f64),
+        seed: u64,
+    ) {
+        let (chunks_x, chunks_z) = terrain.chunk_count();
+        let extent = terrain.chunk_size() as f64 * terrain.spacing();
+        let (width, depth) = (chunks_x as f64 * extent, chunks_z as f64 * extent);
+        let lift = match &self.model {
+            ScatterModel::Billboard(sprite) => sprite.size.y / 2.0,
+            ScatterModel::Mesh(_) => 0.0,
+        };
+
+        let mut random = Random(seed);
+        for _ in 0..count {
+            let x = terrain.origin.x + random.next() * width;
+            let z = terrain.origin.z + random.next() * depth;
+            let Some(height) = terrain.height(x, z) else {
+                continue;
+            };
+            let yaw = random.next() * std::f64::consts::TAU;
+            let scale = min_scale + random.next() * (max_scale - min_scale);
+            self.add(ScatterInstance::new(
+                Vec3::new(x, height + lift * scale, z),
+                yaw,
+                scale,
+            ));
+        }
+    }
+
+    ///
+    /// Returns the instances within `draw_distance` of the viewer, in chunks at least partly inside a frustum.
+    ///
+    pub fn visible<'a>(
+        &'a self,
+        frustum: &'a Frustum,
+        viewer: Vec3,
+    ) -> impl Iterator<Item = &'a ScatterInstance> + 'a {
+        let reach = self.draw_distance;
+        self.chunks
+            .iter()
+            .filter(move |chunk| chunk.bounds.distance(viewer) <= reach)
+            .filter(|chunk| frustum.intersects_aabb(&chunk.bounds))
+            .flat_map(|chunk| &chunk.instances)
+            .filter(move |instance| (instance.position() - viewer).length() <= reach)
+    }
+}
+
+// Splitmix64, which is enough for spreading instances around
+struct Random(u64);
+
+impl Random {
+    // Returns a number between 0 and 1
+    fn next(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::{
+        framebuffer::Colour,
+        geometry::frustum::Plane,
+        object::Texture,
+        vec::{vec2::Vec2, vec3::ORIGIN},
+    };
+
+    fn grass() -> ScatterModel {
+        let texture = Texture::solid(Colour::from_hex(0x00ff00));
+        ScatterModel::Billboard(Sprite::new(texture, Vec2::new(0.5, 1.0)))
+    }
+
+    #[test]
+    fn instances_are_compact() {
+        assert_eq!(std::mem::size_of::<ScatterInstance>(), 20);
+        let instance = ScatterInstance::new(Vec3::new(1.5, 2.0, -3.25), 0.5, 2.0);
+        assert_eq!(instance.position(), Vec3::new(1.5, 2.0, -3.25));
+        assert_eq!(
+            instance.transformation().transform(ORIGIN),
+            instance.position()
+        );
+    }
+
+    #[test]
+    fn chunks_and_culling() {
+        let mut scatter = Scatter::new(grass(), 10.0, 50.0);
+        for x in 0..40 {
+            scatter.add(ScatterInstance::new(
+                Vec3::new(x as f64, 0.0, 5.0),
+                0.0,
+                1.0,
+            ));
+        }
+        assert_eq!(scatter.len(), 40);
+        assert_eq!(scatter.chunk_count(), 4);
+
+        // Only the chunks reaching past the plane are kept, including the parts of them behind it
+        let frustum = Frustum::new(vec![Plane::new(Vec3::new(1.0, 0.0, 0.0), -25.0)]);
+        let visible: Vec<_> = scatter.visible(&frustum, ORIGIN).collect();
+        assert_eq!(visible.len(), 20);
+
+        // Instances out of reach of the viewer are dropped, even in a chunk that's partly in reach
+        scatter.draw_distance = 32.0;
+        let visible = scatter.visible(&frustum, Vec3::new(0.0, 0.0, 5.0)).count();
+        assert_eq!(visible, 13);
+    }
+
+    #[test]
+    fn scatter_over_terrain() {
+        let texture = Texture::new(0, 0, vec![]);
+        let terrain = Terrain::from_fn(2, 2, 4, 1.0, texture, |x, _| x);
+        let mut scatter = Scatter::new(grass(), 4.0, 100.0);
+        scatter.scatter_over(&terrain, 100, (1.0, 2.0), 7);
+        assert_eq!(scatter.len(), 100);
+
+        // Every billboard stands on the ground, and the same seed places them identically
+        let everywhere = Frustum::new(vec![]);
+        for instance in scatter.visible(&everywhere, ORIGIN) {
+            let position = instance.position();
+            let ground = terrain.height(position.x, position.z).unwrap();
+            assert!((position.y - ground - instance.scale() / 2.0).abs() < 1e-4);
+            assert!((1.0..=2.0).contains(&instance.scale()));
+        }
+        let mut again = Scatter::new(grass(), 4.0, 100.0);
+        again.scatter_over(&terrain, 100, (1.0, 2.0), 7);
+        assert!(again
+            .visible(&everywhere, ORIGIN)
+            .eq(scatter.visible(&everywhere, ORIGIN)));
+    }
+}
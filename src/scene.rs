@@ -0,0 +1,493 @@
+use std::{collections::BTreeMap, error::Error, fmt::Display, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    animation::Animation,
+    assets::AssetManager,
+    camera::Camera,
+    environment::{Environment, Fog},
+    geometry::aabb::Aabb,
+    light::Light,
+    mat4::Mat4,
+    quat::Quat,
+    renderer::TextureMapping,
+    transform::Transform,
+    vec::vec3::Vec3,
+    world::{EntityId, Layer, World},
+};
+
+#[derive(Debug)]
+pub enum SceneError {
+    IoError(io::Error),
+    ParseError(ron::error::SpannedError),
+    SerializeError(ron::Error),
+}
+
+impl From<io::Error> for SceneError {
+    fn from(error: io::Error) -> Self {
+        SceneError::IoError(error)
+    }
+}
+impl From<ron::error::SpannedError> for SceneError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        SceneError::ParseError(error)
+    }
+}
+impl From<ron::Error> for SceneError {
+    fn from(error: ron::Error) -> Self {
+        SceneError::SerializeError(error)
+    }
+}
+
+impl Display for SceneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SceneError::IoError(error) => write!(f, "failed to access scene file: {}", error),
+            SceneError::ParseError(error) => write!(f, "failed to parse scene: {}", error),
+            SceneError::SerializeError(error) => write!(f, "failed to write scene: {}", error),
+        }
+    }
+}
+
+impl Error for SceneError {}
+
+///
+/// Where the camera starts in a scene. Angles are in degrees.
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraSpawn {
+    pub position: Vec3,
+    #[serde(default)]
+    pub pitch: f64,
+    #[serde(default = "default_yaw")]
+    pub yaw: f64,
+}
+
+impl Default for CameraSpawn {
+    fn default() -> Self {
+        CameraSpawn {
+            position: Vec3::new(0.0, 0.0, 0.0),
+            pitch: 0.0,
+            yaw: default_yaw(),
+        }
+    }
+}
+
+// Matches the default direction of `Camera::new`, looking down the negative Z axis
+fn default_yaw() -> f64 {
+    -90.0
+}
+
+fn default_scale() -> Vec3 {
+    Vec3::new(1.0, 1.0, 1.0)
+}
+
+///
+/// An object placed in a scene.
+/// - `asset` is the name of the model, as passed to `Object::from_obj`
+/// - `rotation` is a set of Euler angles in degrees, applied about the X, then Y, then Z axes
+/// - `static` marks objects which never move, as with `World::set_static`
+/// - `name` and `tags` are given to the entity, for finding it with `World::find_by_name` and `World::iter_tagged`
+/// - `layer` and `draw_order` control when the object is drawn, as with `World::set_layer` and `World::set_draw_order`
+/// - `visible` can be set to false to start the object hidden
+/// - `texture_mapping` picks how the object's texture is mapped, as with `World::set_texture_mapping`
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneObject {
+    pub asset: String,
+    #[serde(default)]
+    pub position: Vec3,
+    #[serde(default)]
+    pub rotation: Vec3,
+    #[serde(default = "default_scale")]
+    pub scale: Vec3,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub animation: Option<Animation>,
+    #[serde(default, rename = "static", skip_serializing_if = "is_default")]
+    pub is_static: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub layer: Layer,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub draw_order: i32,
+    #[serde(default = "default_visible", skip_serializing_if = "is_visible")]
+    pub visible: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub texture_mapping: Option<TextureMapping>,
+}
+
+fn default_visible() -> bool {
+    true
+}
+
+fn is_visible(visible: &bool) -> bool {
+    *visible
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+impl SceneObject {
+    pub fn transform(&self) -> Transform {
+        let radians = Vec3::new(
+            self.rotation.x.to_radians(),
+            self.rotation.y.to_radians(),
+            self.rotation.z.to_radians(),
+        );
+        Transform::new(self.position, Quat::from_euler(radians), self.scale)
+    }
+
+    ///
+    /// Adds the object to a world, loading its model in the background.
+    ///
+    pub fn spawn(&self, world: &mut World, assets: &mut AssetManager) -> EntityId {
+        let handle = assets.load_async(&self.asset);
+        let id = world.place_object_async(handle, self.transform().to_mat4());
+        if let Some(animation) = &self.animation {
+            world.set_animation(id, animation.clone());
+        }
+        world.set_static(id, self.is_static);
+        if let Some(name) = &self.name {
+            world.set_name(id, name);
+        }
+        for tag in &self.tags {
+            world.add_tag(id, tag);
+        }
+        world.set_layer(id, self.layer);
+        world.set_draw_order(id, self.draw_order);
+        world.set_visible(id, self.visible);
+        world.set_texture_mapping(id, self.texture_mapping);
+        id
+    }
+}
+
+///
+/// The skybox, ambient light and fog a scene is drawn with, as in `Environment`. `skybox` is the path of a PNG.
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneEnvironment {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skybox: Option<String>,
+    #[serde(default = "default_scale")]
+    pub ambient: Vec3,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fog: Option<Fog>,
+}
+
+impl Default for SceneEnvironment {
+    fn default() -> Self {
+        SceneEnvironment {
+            skybox: None,
+            ambient: Vec3::new(1.0, 1.0, 1.0),
+            fog: None,
+        }
+    }
+}
+
+impl SceneEnvironment {
+    ///
+    /// Builds the environment, loading the skybox if there is one.
+    ///
+    pub fn load(&self) -> Result<Environment, SceneError> {
+        let mut environment = Environment {
+            ambient: self.ambient,
+            fog: self.fog,
+            ..Default::default()
+        };
+        if let Some(path) = &self.skybox {
+            environment.load_skybox(path)?;
+        }
+        Ok(environment)
+    }
+}
+
+///
+/// A part of a scene stored in its own file, which can be streamed in and out of the world with a `RegionStreamer`.
+/// - `path` is the file holding the region's objects, as a RON list of `SceneObject`s
+/// - `bounds` contains the positions of the region's objects, and is used to decide when to load it
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneRegion {
+    pub path: String,
+    pub bounds: Aabb,
+}
+
+impl SceneRegion {
+    ///
+    /// Reads the region's objects from its file.
+    ///
+    pub fn load(&self) -> Result<Vec<SceneObject>, SceneError> {
+        Ok(ron::from_str(&fs::read_to_string(&self.path)?)?)
+    }
+}
+
+///
+/// A description of a world, which can be written by hand in a RON file rather than built up in code.
+///
+/// A minimal scene looks like:
+///
+/// ```text
+/// (
+///     camera: (position: (x: 0.0, y: -1.0, z: 20.0)),
+///     objects: [
+///         (asset: "./resources/cube", position: (x: 2.0, y: 0.0, z: 0.0)),
+///     ],
+/// )
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Scene {
+    #[serde(default)]
+    pub camera: CameraSpawn,
+    #[serde(default)]
+    pub lights: Vec<Light>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub environment: SceneEnvironment,
+    #[serde(default)]
+    pub objects: Vec<SceneObject>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub regions: Vec<SceneRegion>,
+}
+
+impl Scene {
+    ///
+    /// Reads a scene from a RON file.
+    ///
+    pub fn load(path: &str) -> Result<Scene, SceneError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("load_scene", path).entered();
+        Scene::from_ron(&fs::read_to_string(path)?)
+    }
+
+    pub fn from_ron(ron_str: &str) -> Result<Scene, SceneError> {
+        Ok(ron::from_str(ron_str)?)
+    }
+
+    ///
+    /// Writes the scene to a RON file.
+    ///
+    pub fn save(&self, path: &str) -> Result<(), SceneError> {
+        fs::write(path, self.to_ron()?)?;
+        Ok(())
+    }
+
+    pub fn to_ron(&self) -> Result<String, SceneError> {
+        Ok(ron::ser::to_string_pretty(
+            self,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+
+    ///
+    /// Moves the scene's objects out into region files, one for each `region_size` square on the XZ plane, which are
+    /// written to `directory`. The objects are replaced by a list of the regions, so the scene can be saved without
+    /// them and the regions streamed in with a `RegionStreamer`.
+    ///
+    pub fn split_into_regions(
+        &mut self,
+        region_size: f64,
+        directory: &str,
+    ) -> Result<(), SceneError> {
+        let mut cells: BTreeMap<(i64, i64), Vec<SceneObject>> = BTreeMap::new();
+        for object in self.objects.drain(..) {
+            let cell = (
+                (object.position.x / region_size).floor() as i64,
+                (object.position.z / region_size).floor() as i64,
+            );
+            cells.entry(cell).or_default().push(object);
+        }
+
+        fs::create_dir_all(directory)?;
+        for ((x, z), objects) in cells {
+            let path = Path::new(directory).join(format!("region_{}_{}.ron", x, z));
+            let ron = ron::ser::to_string_pretty(&objects, ron::ser::PrettyConfig::default())?;
+            fs::write(&path, ron)?;
+
+            let bounds = Aabb::from_points(objects.iter().map(|object| object.position))
+                .expect("cells always have at least one object");
+            self.regions.push(SceneRegion {
+                path: path.to_string_lossy().into_owned(),
+                bounds,
+            });
+        }
+        Ok(())
+    }
+
+    ///
+    /// Adds the scene's lights and objects to a world and sets its environment, returning a camera at the scene's spawn
+    /// point. Regions aren't loaded - they're streamed in separately with a `RegionStreamer`.
+    ///
+    /// Objects are loaded in the background - call `World::poll_assets` each frame to add them as they finish, and to
+    /// find out about any that failed. Only the skybox is loaded straight away, and fails the whole spawn if it's
+    /// missing.
+    ///
+    pub fn spawn(
+        &self,
+        world: &mut World,
+        assets: &mut AssetManager,
+    ) -> Result<Camera, SceneError> {
+        world.environment = self.environment.load()?;
+        world.lights.extend(self.lights.iter().copied());
+
+        for scene_object in &self.objects {
+            scene_object.spawn(world, assets);
+        }
+
+        let mut camera = Camera::new(self.camera.position);
+        camera.set_rotation(Vec3::new(
+            self.camera.pitch.to_radians(),
+            self.camera.yaw.to_radians(),
+            0.0,
+        ));
+        Ok(camera)
+    }
+
+    ///
+    /// Describes an existing world and camera as a scene. Only objects loaded from files can be saved.
+    ///
+    pub fn from_world(world: &World, camera: &Camera) -> Scene {
+        let objects = world
+            .iter()
+            .filter_map(|(id, obj)| {
+                let asset = obj.source.clone()?;
+                let transform =
+                    Transform::from_mat4(world.base_transform(id).unwrap_or(Mat4::identity()));
+                let radians = transform.rotation.to_euler();
+
+                Some(SceneObject {
+                    asset,
+                    position: transform.translation,
+                    rotation: Vec3::new(
+                        radians.x.to_degrees(),
+                        radians.y.to_degrees(),
+                        radians.z.to_degrees(),
+                    ),
+                    scale: transform.scale,
+                    animation: world.animation(id).cloned(),
+                    is_static: world.is_static(id),
+                    name: world.name(id).map(String::from),
+                    tags: world.tags(id).map(String::from).collect(),
+                    layer: world.layer(id).unwrap_or_default(),
+                    draw_order: world.draw_order(id).unwrap_or_default(),
+                    visible: world.is_visible(id),
+                    texture_mapping: world.texture_mapping(id),
+                })
+            })
+            .collect();
+
+        let rotation = camera.rotation();
+        Scene {
+            camera: CameraSpawn {
+                position: camera.position(),
+                pitch: rotation.x.to_degrees(),
+                yaw: rotation.y.to_degrees(),
+            },
+            lights: world.lights.clone(),
+            environment: SceneEnvironment {
+                skybox: world.environment.skybox_source.clone(),
+                ambient: world.environment.ambient,
+                fog: world.environment.fog,
+            },
+            objects,
+            regions: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_minimal_scene() {
+        let scene = Scene::from_ron(
+            r#"(
+                camera: (position: (x: 0.0, y: -1.0, z: 20.0)),
+                objects: [(asset: "./resources/cube", position: (x: 2.0, y: 0.0, z: 0.0))],
+            )"#,
+        )
+        .unwrap();
+
+        assert_eq!(scene.camera.yaw, -90.0);
+        assert_eq!(scene.objects[0].scale, Vec3::new(1.0, 1.0, 1.0));
+        assert!(scene.lights.is_empty());
+    }
+
+    #[test]
+    fn ron_round_trip() {
+        let scene = Scene {
+            camera: CameraSpawn::default(),
+            lights: vec![Light::Directional {
+                direction: Vec3::new(0.0, -1.0, 0.0),
+                colour: Vec3::new(1.0, 1.0, 1.0),
+                intensity: 1.0,
+            }],
+            environment: SceneEnvironment {
+                skybox: Some(String::from("./resources/sky.png")),
+                ambient: Vec3::new(0.5, 0.5, 0.6),
+                fog: Some(Fog {
+                    colour: Vec3::new(0.7, 0.7, 0.8),
+                    start: 20.0,
+                    end: 100.0,
+                }),
+            },
+            objects: vec![SceneObject {
+                asset: String::from("./resources/cube"),
+                position: Vec3::new(1.0, 2.0, 3.0),
+                rotation: Vec3::new(0.0, 45.0, 0.0),
+                scale: default_scale(),
+                animation: None,
+                is_static: false,
+                name: None,
+                tags: vec![],
+                layer: Layer::World,
+                draw_order: 0,
+                visible: false,
+                texture_mapping: Some(TextureMapping::Affine),
+            }],
+            regions: vec![],
+        };
+
+        let result = Scene::from_ron(&scene.to_ron().unwrap()).unwrap();
+        assert_eq!(scene, result);
+    }
+
+    #[test]
+    fn spawn_and_describe_world() {
+        let scene = Scene {
+            objects: vec![SceneObject {
+                asset: String::from("./resources/cube"),
+                position: Vec3::new(1.0, 2.0, 3.0),
+                rotation: Vec3::new(0.0, 45.0, 0.0),
+                scale: default_scale(),
+                animation: None,
+                is_static: false,
+                name: None,
+                tags: vec![],
+                layer: Layer::World,
+                draw_order: 0,
+                visible: true,
+                texture_mapping: None,
+            }],
+            ..Default::default()
+        };
+
+        let mut world = World::new();
+        let mut assets = AssetManager::new();
+        let camera = scene.spawn(&mut world, &mut assets).unwrap();
+        while world.pending_count() > 0 {
+            assert!(world.poll_assets().is_empty());
+        }
+
+        let described = Scene::from_world(&world, &camera);
+        let object = &described.objects[0];
+        assert!((object.position - Vec3::new(1.0, 2.0, 3.0)).length() < 1e-9);
+        assert!((object.rotation - Vec3::new(0.0, 45.0, 0.0)).length() < 1e-9);
+        assert!((described.camera.yaw - -90.0).abs() < 1e-9);
+    }
+}
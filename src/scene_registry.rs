@@ -0,0 +1,269 @@
+use std::error::Error;
+
+use minifb::Key;
+
+use crate::{
+    app::App,
+    camera::Camera,
+    input::Input,
+    renderer::Renderer,
+    timing::FrameStats,
+    vec::{vec2::Vec2, vec3::Vec3},
+    world::World,
+};
+
+// The size of the scene's name, and how far it's kept from the bottom right corner of the window
+const NAME_SIZE: f32 = 14.0;
+const NAME_MARGIN: f64 = 5.0;
+
+// What each scene is made by. A fresh app is made each time the scene is switched to, so it starts over
+type SceneBuilder = Box<dyn Fn() -> Box<dyn App>>;
+
+///
+/// A set of apps registered by name, which can be switched between while running, for demos that show off different
+/// parts of the engine in one program. The registry is itself an app, so it's handed to `run` in place of the scenes.
+///
+/// Switching scenes makes a new app for the scene, and sets it up in a new world with a new camera, so nothing is left
+/// over from the last scene. If setting it up fails, the last scene carries on and the error is shown in the window.
+/// The input's bindings and mouse sensitivity are shared by every scene, so actions used by any of them should be bound
+/// before running.
+///
+/// - `next_key` switches to the next scene, going back to the first after the last. With no key, the scene can only be
+///   picked before running with `select`
+///
+pub struct SceneRegistry {
+    pub next_key: Option<Key>,
+    scenes: Vec<(String, SceneBuilder)>,
+    current: usize,
+    // The scene last switched to, which is ahead of `current` if it failed to start, so a broken scene is skipped
+    selected: usize,
+    app: Option<Box<dyn App>>,
+    seed: u64,
+    // Why the last switch failed, until the next one
+    error: Option<String>,
+}
+
+impl Default for SceneRegistry {
+    fn default() -> Self {
+        SceneRegistry::new()
+    }
+}
+
+impl SceneRegistry {
+    pub fn new() -> SceneRegistry {
+        SceneRegistry {
+            next_key: Some(Key::F2),
+            scenes: vec![],
+            current: 0,
+            selected: 0,
+            app: None,
+            seed: 0,
+            error: None,
+        }
+    }
+
+    ///
+    /// Registers a scene, made by `build`. The first scene registered is the one that's started with, unless another
+    /// is picked with `select`. Registering a name again replaces the scene.
+    ///
+    pub fn with_scene<A: App + 'static>(
+        mut self,
+        name: &str,
+        build: impl Fn() -> A + 'static,
+    ) -> SceneRegistry {
+        let build: SceneBuilder = Box::new(move || Box::new(build()));
+        match self.scenes.iter_mut().find(|(scene, _)| scene == name) {
+            Some(scene) => scene.1 = build,
+            None => self.scenes.push((name.to_string(), build)),
+        }
+        self
+    }
+
+    ///
+    /// Picks the scene to start with, such as from the command line. Returns false if there's no scene by that name.
+    ///
+    pub fn select(&mut self, name: &str) -> bool {
+        match self.scenes.iter().position(|(scene, _)| scene == name) {
+            Some(index) => {
+                self.current = index;
+                self.selected = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    ///
+    /// Returns the names of the scenes, in the order they're switched between.
+    ///
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.scenes.iter().map(|(name, _)| name.as_str())
+    }
+
+    ///
+    /// Returns the name of the scene that's running, or that will be started with.
+    ///
+    pub fn current(&self) -> Option<&str> {
+        self.scenes.get(self.current).map(|(name, _)| name.as_str())
+    }
+
+    ///
+    /// Makes the scene at `index` and sets it up in a new world with a new camera, which replace the old ones only if
+    /// it's set up successfully.
+    ///
+    fn start(
+        &mut self,
+        index: usize,
+        world: &mut World,
+        camera: &mut Camera,
+    ) -> Result<(), Box<dyn Error>> {
+        let (_, build) = self
+            .scenes
+            .get(index)
+            .ok_or("no scenes have been registered")?;
+        let mut app = build();
+        let mut new_world = World::new();
+        let mut new_camera = Camera::new(Vec3::default());
+        app.set_seed(self.seed);
+        app.setup(&mut new_world, &mut new_camera)?;
+
+        *world = new_world;
+        *camera = new_camera;
+        self.app = Some(app);
+        self.current = index;
+        Ok(())
+    }
+}
+
+impl App for SceneRegistry {
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    fn setup(&mut self, world: &mut World, camera: &mut Camera) -> Result<(), Box<dyn Error>> {
+        self.start(self.current, world, camera)
+    }
+
+    fn update(&mut self, world: &mut World, camera: &mut Camera, input: &mut Input, delta: f64) {
+        if self.next_key.is_some_and(|key| input.is_key_pressed(key)) && !self.scenes.is_empty() {
+            let next = (self.selected + 1) % self.scenes.len();
+            self.selected = next;
+            self.error = match self.start(next, world, camera) {
+                Ok(()) => {
+                    // The new scene captures the mouse itself if it wants to
+                    input.capture_mouse = false;
+                    None
+                }
+                Err(error) => Some(format!(
+                    "failed to start {}: {}",
+                    self.scenes[next].0, error
+                )),
+            };
+        }
+        if let Some(app) = &mut self.app {
+            app.update(world, camera, input, delta);
+        }
+    }
+
+    fn draw_overlay(&mut self, renderer: &mut Renderer, stats: &FrameStats) {
+        if let Some(app) = &mut self.app {
+            app.draw_overlay(renderer, stats);
+        }
+
+        let mut lines = vec![];
+        if let Some(name) = self.current() {
+            lines.push(match self.next_key {
+                Some(key) => format!("{} ({:?} for next)", name, key),
+                None => name.to_string(),
+            });
+        }
+        lines.extend(self.error.clone());
+        let mut y = renderer.height() as f64 - NAME_MARGIN;
+        for line in lines {
+            y -= NAME_SIZE as f64 + NAME_MARGIN;
+            let x = renderer.width() as f64 - renderer.text_width(&line, NAME_SIZE) - NAME_MARGIN;
+            renderer.write_text(&line, Vec2::new(x, y), NAME_SIZE);
+        }
+    }
+
+    fn interpolate(&mut self, world: &mut World, alpha: f64) {
+        if let Some(app) = &mut self.app {
+            app.interpolate(world, alpha);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        input::InputFrame,
+        object::{Object, Texture},
+    };
+
+    ///
+    /// A scene that adds `objects` empty objects, or fails to set up with none.
+    ///
+    struct Counted {
+        objects: usize,
+    }
+
+    impl App for Counted {
+        fn setup(&mut self, world: &mut World, camera: &mut Camera) -> Result<(), Box<dyn Error>> {
+            if self.objects == 0 {
+                return Err("nothing to show".into());
+            }
+            for _ in 0..self.objects {
+                let texture = Texture::new(0, 0, vec![]);
+                let object = Object::new(vec![], vec![], vec![], vec![], texture);
+                world.add_object(object, Vec3::default());
+            }
+            camera.set_position(Vec3::new(self.objects as f64, 0.0, 0.0));
+            Ok(())
+        }
+
+        fn update(&mut self, _: &mut World, _: &mut Camera, _: &mut Input, _: f64) {}
+    }
+
+    fn press_next(registry: &mut SceneRegistry, world: &mut World, camera: &mut Camera) {
+        let mut input = Input::default();
+        input.capture_mouse = true;
+        input.update(InputFrame {
+            keys_down: vec![Key::F2],
+            keys_pressed: vec![Key::F2],
+            ..Default::default()
+        });
+        registry.update(world, camera, &mut input, 1.0);
+    }
+
+    #[test]
+    fn switching_scenes() {
+        let mut registry = SceneRegistry::new()
+            .with_scene("one", || Counted { objects: 1 })
+            .with_scene("two", || Counted { objects: 2 })
+            .with_scene("broken", || Counted { objects: 0 });
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            ["one", "two", "broken"]
+        );
+        assert!(!registry.select("three"));
+        assert!(registry.select("two"));
+
+        let mut world = World::new();
+        let mut camera = Camera::new(Vec3::default());
+        registry.setup(&mut world, &mut camera).unwrap();
+        assert_eq!(world.len(), 2);
+
+        // A scene that fails to start leaves the last one running
+        press_next(&mut registry, &mut world, &mut camera);
+        assert_eq!(registry.current(), Some("two"));
+        assert_eq!(world.len(), 2);
+        assert!(registry.error.as_ref().unwrap().contains("nothing to show"));
+
+        press_next(&mut registry, &mut world, &mut camera);
+        assert_eq!(registry.current(), Some("one"));
+        assert_eq!(world.len(), 1);
+        assert_eq!(camera.position(), Vec3::new(1.0, 0.0, 0.0));
+        assert!(registry.error.is_none());
+    }
+}
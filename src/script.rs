@@ -0,0 +1,362 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt::Display,
+    fs, io,
+    rc::Rc,
+    time::SystemTime,
+};
+
+use rhai::{Array, Dynamic, Engine, EvalAltResult, ParseError, Scope, AST};
+
+use crate::{
+    mat4::Mat4,
+    prefab::Prefab,
+    vec::vec3::{Vec3, ORIGIN},
+    world::{EntityId, World},
+};
+
+#[derive(Debug)]
+pub enum ScriptError {
+    IoError(io::Error),
+    ParseError(ParseError),
+    RuntimeError(Box<EvalAltResult>),
+}
+
+impl From<io::Error> for ScriptError {
+    fn from(error: io::Error) -> Self {
+        ScriptError::IoError(error)
+    }
+}
+impl From<ParseError> for ScriptError {
+    fn from(error: ParseError) -> Self {
+        ScriptError::ParseError(error)
+    }
+}
+impl From<Box<EvalAltResult>> for ScriptError {
+    fn from(error: Box<EvalAltResult>) -> Self {
+        ScriptError::RuntimeError(error)
+    }
+}
+
+impl Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScriptError::IoError(error) => write!(f, "failed to read script: {}", error),
+            ScriptError::ParseError(error) => write!(f, "failed to parse script: {}", error),
+            ScriptError::RuntimeError(error) => write!(f, "script failed: {}", error),
+        }
+    }
+}
+
+impl Error for ScriptError {}
+
+///
+/// The state scripts can see. The world is moved in here while scripts run, since the functions registered with the
+/// engine can't borrow it.
+///
+#[derive(Default)]
+struct Shared {
+    world: World,
+    keys: HashSet<String>,
+    prefabs: HashMap<String, Prefab>,
+}
+
+///
+/// A loaded script, and when its file was last changed.
+///
+struct Script {
+    path: String,
+    modified: Option<SystemTime>,
+    ast: AST,
+    scope: Scope<'static>,
+    started: bool,
+}
+
+///
+/// Runs rhai scripts against a world. Each script can define an `init()` function, called once when the script is
+/// loaded, and an `update(delta)` function, called every frame. Scripts are reloaded whenever their file changes, so
+/// behaviour can be changed while the program is running.
+///
+/// Scripts have access to the following functions:
+/// - `vec3(x, y, z)` makes a vector, which has `x`, `y` and `z` properties and supports `+`, `-` and `*` by a number
+/// - `find(name)` returns the entity with a name, and `tagged(tag)` returns an array of entities with a tag
+/// - `exists(entity)`, `has_tag(entity, tag)` and `remove(entity)`
+/// - `position(entity)`, `set_position(entity, vec)`, `translate(entity, vec)`, and
+///   `rotate(entity, axis, degrees)` which turns an entity in place
+/// - `spawn_prefab(name, vec)` spawns a prefab registered with `add_prefab`, returning the new entity
+/// - `key_down(name)` checks the keys passed to `set_keys`, e.g. `key_down("Space")`
+/// - `time()` returns the world's time
+///
+/// Functions which find an entity return `()` when there isn't one.
+///
+pub struct ScriptHost {
+    engine: Engine,
+    shared: Rc<RefCell<Shared>>,
+    scripts: Vec<Script>,
+}
+
+impl ScriptHost {
+    pub fn new() -> ScriptHost {
+        let shared = Rc::new(RefCell::new(Shared::default()));
+        let mut engine = Engine::new();
+        register_vec3(&mut engine);
+        register_world(&mut engine, &shared);
+
+        ScriptHost {
+            engine,
+            shared,
+            scripts: vec![],
+        }
+    }
+
+    ///
+    /// Loads a script from a file. Its `init` function is called on the next `update`.
+    ///
+    pub fn load(&mut self, path: &str) -> Result<(), ScriptError> {
+        let source = fs::read_to_string(path)?;
+        let ast = self.engine.compile(source)?;
+        self.scripts.push(Script {
+            path: String::from(path),
+            modified: last_modified(path),
+            ast,
+            scope: Scope::new(),
+            started: false,
+        });
+        Ok(())
+    }
+
+    ///
+    /// Makes a prefab available to scripts' `spawn_prefab` function under a name.
+    ///
+    pub fn add_prefab(&mut self, name: &str, prefab: Prefab) {
+        self.shared
+            .borrow_mut()
+            .prefabs
+            .insert(String::from(name), prefab);
+    }
+
+    ///
+    /// Sets which keys `key_down` reports as held.
+    ///
+    pub fn set_keys<I, S>(&mut self, keys: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.shared.borrow_mut().keys = keys.into_iter().map(Into::into).collect();
+    }
+
+    ///
+    /// Reloads any scripts whose files have changed, then runs every script against the world. Scripts which fail
+    /// are skipped for this frame, and the errors are returned along with the path of the script.
+    ///
+    pub fn update(&mut self, world: &mut World, delta: f64) -> Vec<(String, ScriptError)> {
+        let mut errors = vec![];
+        std::mem::swap(world, &mut self.shared.borrow_mut().world);
+
+        for script in &mut self.scripts {
+            if let Err(error) = reload_if_changed(&self.engine, script) {
+                errors.push((script.path.clone(), error));
+            }
+            if let Err(error) = run(&self.engine, script, delta) {
+                errors.push((script.path.clone(), error));
+            }
+        }
+
+        std::mem::swap(world, &mut self.shared.borrow_mut().world);
+        errors
+    }
+}
+
+impl Default for ScriptHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn reload_if_changed(engine: &Engine, script: &mut Script) -> Result<(), ScriptError> {
+    let modified = last_modified(&script.path);
+    if modified == script.modified {
+        return Ok(());
+    }
+    // Only try again once the file changes again, rather than failing every frame
+    script.modified = modified;
+    script.ast = engine.compile(fs::read_to_string(&script.path)?)?;
+    script.scope = Scope::new();
+    script.started = false;
+    Ok(())
+}
+
+fn run(engine: &Engine, script: &mut Script, delta: f64) -> Result<(), ScriptError> {
+    let defines = |name: &str| script.ast.iter_functions().any(|f| f.name == name);
+    let (has_init, has_update) = (defines("init"), defines("update"));
+
+    // Whatever the functions return is ignored
+    if !script.started {
+        script.started = true;
+        engine.run_ast_with_scope(&mut script.scope, &script.ast)?;
+        if has_init {
+            let _ = engine.call_fn::<Dynamic>(&mut script.scope, &script.ast, "init", ())?;
+        }
+    }
+    if has_update {
+        let _ = engine.call_fn::<Dynamic>(&mut script.scope, &script.ast, "update", (delta,))?;
+    }
+    Ok(())
+}
+
+fn last_modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+fn register_vec3(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<Vec3>("Vec3")
+        .register_fn("vec3", Vec3::new)
+        .register_get_set("x", |v: &mut Vec3| v.x, |v: &mut Vec3, x: f64| v.x = x)
+        .register_get_set("y", |v: &mut Vec3| v.y, |v: &mut Vec3, y: f64| v.y = y)
+        .register_get_set("z", |v: &mut Vec3| v.z, |v: &mut Vec3, z: f64| v.z = z)
+        .register_fn("+", |a: Vec3, b: Vec3| a + b)
+        .register_fn("-", |a: Vec3, b: Vec3| a - b)
+        .register_fn("*", |a: Vec3, scale: f64| a * scale)
+        .register_fn("length", |v: &mut Vec3| v.length())
+        .register_fn("normalise", |v: &mut Vec3| v.normalise())
+        .register_fn("to_string", |v: &mut Vec3| v.to_string());
+}
+
+fn register_world(engine: &mut Engine, shared: &Rc<RefCell<Shared>>) {
+    engine.register_type_with_name::<EntityId>("Entity");
+
+    // Entities which don't exist are returned to scripts as ()
+    let entity = |id: Option<EntityId>| id.map_or(Dynamic::UNIT, Dynamic::from);
+
+    let state = shared.clone();
+    engine.register_fn("find", move |name: &str| {
+        entity(state.borrow().world.find_by_name(name))
+    });
+    let state = shared.clone();
+    engine.register_fn("tagged", move |tag: &str| -> Array {
+        let state = state.borrow();
+        let tagged = state.world.iter_tagged(tag);
+        tagged.map(|(id, _)| Dynamic::from(id)).collect()
+    });
+    let state = shared.clone();
+    engine.register_fn("exists", move |id: EntityId| {
+        state.borrow().world.contains(id)
+    });
+    let state = shared.clone();
+    engine.register_fn("has_tag", move |id: EntityId, tag: &str| {
+        state.borrow().world.has_tag(id, tag)
+    });
+    let state = shared.clone();
+    engine.register_fn("remove", move |id: EntityId| {
+        state.borrow_mut().world.remove(id);
+    });
+
+    let state = shared.clone();
+    engine.register_fn("position", move |id: EntityId| {
+        let state = state.borrow();
+        state.world.get(id).map_or(Dynamic::UNIT, |obj| {
+            Dynamic::from(obj.transformation.transform(ORIGIN))
+        })
+    });
+    let state = shared.clone();
+    engine.register_fn("set_position", move |id: EntityId, position: Vec3| {
+        move_entity(&mut state.borrow_mut().world, id, |base| {
+            base.translate(position - base.transform(ORIGIN))
+        });
+    });
+    let state = shared.clone();
+    engine.register_fn("translate", move |id: EntityId, offset: Vec3| {
+        move_entity(&mut state.borrow_mut().world, id, |base| {
+            base.translate(offset)
+        });
+    });
+    let state = shared.clone();
+    engine.register_fn("rotate", move |id: EntityId, axis: Vec3, degrees: f64| {
+        move_entity(&mut state.borrow_mut().world, id, |base| {
+            let position = base.transform(ORIGIN);
+            base.translate(ORIGIN - position)
+                .rotate(axis, degrees.to_radians())
+                .translate(position)
+        });
+    });
+
+    let state = shared.clone();
+    engine.register_fn("spawn_prefab", move |name: &str, position: Vec3| {
+        let state = &mut *state.borrow_mut();
+        entity(
+            state
+                .prefabs
+                .get(name)
+                .map(|prefab| state.world.spawn(prefab, position)),
+        )
+    });
+
+    let state = shared.clone();
+    engine.register_fn("key_down", move |key: &str| {
+        state.borrow().keys.contains(key)
+    });
+    let state = shared.clone();
+    engine.register_fn("time", move || state.borrow().world.time);
+}
+
+///
+/// Changes the transformation an entity's animations are applied on top of, so scripts can move animated entities.
+///
+fn move_entity<F>(world: &mut World, id: EntityId, change: F)
+where
+    F: FnOnce(Mat4) -> Mat4,
+{
+    if let Some(base) = world.base_transform(id) {
+        world.set_base_transform(id, change(base));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::object::{Object, Texture};
+
+    fn empty_object() -> Object {
+        Object::new(vec![], vec![], vec![], vec![], Texture::new(0, 0, vec![]))
+    }
+
+    #[test]
+    fn scripts_move_and_spawn() {
+        let path = std::env::temp_dir().join("megavertex-script-test.rhai");
+        fs::write(
+            &path,
+            r#"
+                fn init() {
+                    spawn_prefab("crate", vec3(0.0, 5.0, 0.0));
+                }
+                fn update(delta) {
+                    if key_down("Space") {
+                        translate(find("player"), vec3(delta, 0.0, 0.0));
+                    }
+                }
+            "#,
+        )
+        .unwrap();
+
+        let mut world = World::new();
+        let player = world.add_named_object(empty_object(), ORIGIN, Some("player"), &[]);
+
+        let mut scripts = ScriptHost::new();
+        scripts.add_prefab("crate", Prefab::new(empty_object()).with_tag("crate"));
+        scripts.load(path.to_str().unwrap()).unwrap();
+        scripts.set_keys(["Space"]);
+
+        let errors = scripts.update(&mut world, 2.0);
+        assert!(errors.is_empty(), "{}", errors[0].1);
+        assert!(scripts.update(&mut world, 1.0).is_empty());
+        fs::remove_file(path).unwrap();
+
+        let position = world.get(player).unwrap().transformation.transform(ORIGIN);
+        assert_eq!(position, Vec3::new(3.0, 0.0, 0.0));
+        assert_eq!(world.iter_tagged("crate").count(), 1);
+    }
+}
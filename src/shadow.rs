@@ -0,0 +1,148 @@
+use crate::{
+    decal::{surface_volume, Decal, DecalMode},
+    geometry::aabb::Aabb,
+    object::Texture,
+    vec::{
+        vec2::Vec2,
+        vec3::{Vec3, Y_AXIS},
+    },
+};
+
+// The width and height of the texture blob shadows are drawn with
+const BLOB_RESOLUTION: usize = 32;
+// How far a blob shadow's box reaches above and below the ground, relative to its widest side, so it still covers
+// bumpy ground without climbing up onto the entity casting it
+const BLOB_DEPTH: f64 = 0.25;
+
+///
+/// A cheap shadow for an entity: a soft, dark ellipse on the ground beneath it, the size of the entity's footprint.
+/// The shadow fades as the entity rises, and is gone once it's `max_height` above the ground.
+///
+/// Give an entity a blob shadow with `World::set_blob_shadow`. Shadows are drawn as decals, so they land on whatever
+/// opaque world geometry is under the entity, terrain included, without fighting it for depth.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BlobShadow {
+    pub darkness: f64,
+    pub scale: f64,
+    pub max_height: f64,
+}
+
+impl Default for BlobShadow {
+    fn default() -> Self {
+        BlobShadow {
+            darkness: 0.6,
+            scale: 1.0,
+            max_height: 8.0,
+        }
+    }
+}
+
+impl BlobShadow {
+    pub fn with_darkness(mut self, darkness: f64) -> BlobShadow {
+        self.darkness = darkness;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: f64) -> BlobShadow {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_max_height(mut self, max_height: f64) -> BlobShadow {
+        self.max_height = max_height;
+        self
+    }
+
+    ///
+    /// Creates the decal the shadow is drawn with, which is placed with `place`.
+    ///
+    pub(crate) fn decal(&self) -> Decal {
+        let volume = surface_volume(Vec3::default(), Y_AXIS, Vec2::default(), 0.0);
+        Decal::new(blob_texture(), volume)
+            .with_mode(DecalMode::Multiply)
+            .with_opacity(0.0)
+    }
+
+    ///
+    /// Moves a shadow's decal onto the ground under an entity with the given bounds, where the ground was found at
+    /// `point` with the given normal. Returns false if the entity is too high to cast a shadow.
+    ///
+    pub(crate) fn place(&self, decal: &mut Decal, bounds: Aabb, point: Vec3, normal: Vec3) -> bool {
+        let height = (bounds.min.y - point.y).max(0.0);
+        let fade = 1.0 - height / self.max_height;
+        if self.max_height <= 0.0 || fade <= 0.0 {
+            return false;
+        }
+        // Ground is hit from either side, so make sure the shadow is projected down onto its top
+        let normal = match normal.y < 0.0 {
+            true => -normal,
+            false => normal,
+        };
+        let size = bounds.size();
+        let size = Vec2::new(size.x, size.z) * self.scale;
+        let depth = size.x.max(size.y) * BLOB_DEPTH;
+        decal.volume = surface_volume(point, normal, size, depth);
+        decal.opacity = self.darkness.clamp(0.0, 1.0) * fade;
+        true
+    }
+}
+
+///
+/// Generates a black disc which fades smoothly to white at its edge, leaving the corners white.
+///
+fn blob_texture() -> Texture {
+    let centre = (BLOB_RESOLUTION as f64 - 1.0) / 2.0;
+    let pixels = (0..BLOB_RESOLUTION * BLOB_RESOLUTION)
+        .map(|i| {
+            let x = (i % BLOB_RESOLUTION) as f64 - centre;
+            let y = (i / BLOB_RESOLUTION) as f64 - centre;
+            let t = (x.hypot(y) / (centre + 0.5)).min(1.0);
+            let light = t * t * (3.0 - 2.0 * t);
+            let channel = (light * 255.0).round() as u32;
+            channel << 16 | channel << 8 | channel
+        })
+        .collect();
+    Texture::new(BLOB_RESOLUTION, BLOB_RESOLUTION, pixels)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::decal::decal_coords;
+
+    #[test]
+    fn blob_shadows_sit_under_their_entity() {
+        let shadow = BlobShadow::default().with_max_height(4.0);
+        let mut decal = shadow.decal();
+        let bounds = Aabb::new(Vec3::new(-1.0, 1.0, -0.5), Vec3::new(1.0, 3.0, 0.5));
+
+        // The shadow is the entity's footprint, flat on the ground, and half as dark when halfway to its max height
+        assert!(shadow.place(
+            &mut decal,
+            bounds,
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0)
+        ));
+        assert_eq!(decal.mode, DecalMode::Multiply);
+        assert!((decal.opacity - 0.3).abs() < 1e-9);
+        assert_eq!(decal.volume.axes[2], Y_AXIS);
+        let to_local = decal.to_local().unwrap();
+        let coords = |point: Vec3| decal_coords(to_local.transform(point));
+        assert!(coords(Vec3::new(0.99, -1.0, 0.49)).is_some());
+        assert!(coords(Vec3::new(1.01, -1.0, 0.0)).is_none());
+        assert!(coords(Vec3::new(0.0, -1.0, 0.51)).is_none());
+        // It doesn't reach up to the entity
+        assert!(coords(Vec3::new(0.0, 1.0, 0.0)).is_none());
+
+        // Dark in the middle and untouched at the corners
+        let middle = decal.texture.sample(Vec2::new(0.5, 0.5));
+        assert!(middle.r < 8 && middle.g < 8 && middle.b < 8);
+        assert_eq!(u32::from(decal.texture.texel(0, 0)) & 0xffffff, 0xffffff);
+
+        // Entities too high up cast no shadow
+        let high = Aabb::new(Vec3::new(-1.0, 3.0, -0.5), Vec3::new(1.0, 5.0, 0.5));
+        assert!(!shadow.place(&mut decal, high, Vec3::new(0.0, -1.0, 0.0), Y_AXIS));
+    }
+}
@@ -0,0 +1,247 @@
+use crate::{mat4::Mat4, transform::Transform, vec::vec3::Vec3};
+use alloc::{string::String, vec, vec::Vec};
+
+///
+/// A single joint in a skeleton.
+/// - `parent` is the index of the parent bone, or `None` for a root bone
+/// - `rest` is the bone's transform relative to its parent when no animation is applied
+/// - `inverse_bind` transforms a vertex from model space into the bone's space in the bind pose
+///
+#[derive(Clone, Debug)]
+pub struct Bone {
+    pub name: String,
+    pub parent: Option<usize>,
+    pub rest: Transform,
+    pub inverse_bind: Mat4,
+}
+
+///
+/// A hierarchy of bones. Bones refer to their parents by index, so they can be listed in any order. When the skeleton
+/// is posed, a chain of parents is cut where it leaves the list of bones or loops back on itself.
+///
+#[derive(Clone, Debug, Default)]
+pub struct Skeleton {
+    pub bones: Vec<Bone>,
+}
+
+///
+/// The local transform of every bone in a skeleton at a point in time.
+///
+#[derive(Clone, Debug)]
+pub struct Pose {
+    pub local: Vec<Transform>,
+}
+
+impl Skeleton {
+    pub fn new(bones: Vec<Bone>) -> Skeleton {
+        Skeleton { bones }
+    }
+
+    ///
+    /// Returns the index of the bone with the given name.
+    ///
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.bones.iter().position(|bone| bone.name == name)
+    }
+
+    ///
+    /// Returns a pose with every bone at its rest transform.
+    ///
+    pub fn rest_pose(&self) -> Pose {
+        Pose {
+            local: self.bones.iter().map(|bone| bone.rest).collect(),
+        }
+    }
+
+    ///
+    /// Evaluates a pose, returning the matrix which moves a vertex from the bind pose to its posed position for
+    /// each bone.
+    ///
+    pub fn skinning_matrices(&self, pose: &Pose) -> Vec<Mat4> {
+        let mut globals: Vec<Option<Mat4>> = vec![None; self.bones.len()];
+        for i in 0..self.bones.len() {
+            self.global_transform(i, pose, &mut globals);
+        }
+
+        globals
+            .iter()
+            .zip(&self.bones)
+            .map(|(global, bone)| global.unwrap_or(Mat4::identity()).mult(bone.inverse_bind))
+            .collect()
+    }
+
+    ///
+    /// Computes a bone's model space transform by walking up to the nearest ancestor already worked out, caching the
+    /// result for each bone on the way back down. Parents which are out of range, or which lead back to a bone already
+    /// on the path, are treated as missing, so a broken hierarchy can't loop forever.
+    ///
+    fn global_transform(&self, i: usize, pose: &Pose, globals: &mut [Option<Mat4>]) -> Mat4 {
+        let mut chain = vec![];
+        let mut global = Mat4::identity();
+        let mut next = Some(i);
+        while let Some(bone) = next.filter(|bone| *bone < self.bones.len() && !chain.contains(bone))
+        {
+            if let Some(cached) = globals[bone] {
+                global = cached;
+                break;
+            }
+            chain.push(bone);
+            next = self.bones[bone].parent;
+        }
+
+        for bone in chain.into_iter().rev() {
+            let local = pose
+                .local
+                .get(bone)
+                .copied()
+                .unwrap_or(self.bones[bone].rest)
+                .to_mat4();
+            global = global.mult(local);
+            globals[bone] = Some(global);
+        }
+        global
+    }
+}
+
+///
+/// The bones influencing a single vertex, and how strongly. Up to four bones can affect each vertex.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct VertexWeights {
+    pub bones: [usize; 4],
+    pub weights: [f64; 4],
+}
+
+///
+/// Binds an object's vertices to a skeleton, along with the pose the skeleton is currently in.
+///
+/// `weights` holds one entry per vertex of the object the skin belongs to.
+///
+#[derive(Clone, Debug)]
+pub struct Skin {
+    pub skeleton: Skeleton,
+    pub weights: Vec<VertexWeights>,
+    pub pose: Pose,
+}
+
+impl Skin {
+    ///
+    /// Creates a skin with its skeleton in the rest pose.
+    ///
+    pub fn new(skeleton: Skeleton, weights: Vec<VertexWeights>) -> Skin {
+        let pose = skeleton.rest_pose();
+        Skin {
+            skeleton,
+            weights,
+            pose,
+        }
+    }
+
+    ///
+    /// Moves each vertex by the weighted sum of its bones' transformations in the current pose.
+    /// Vertices without any weights are left where they are.
+    ///
+    pub fn apply(&self, vertices: &[Vec3]) -> Vec<Vec3> {
+        let matrices = self.skeleton.skinning_matrices(&self.pose);
+
+        vertices
+            .iter()
+            .enumerate()
+            .map(|(i, vertex)| {
+                let Some(influence) = self.weights.get(i) else {
+                    return *vertex;
+                };
+                let total: f64 = influence.weights.iter().sum();
+                if total <= 0.0 {
+                    return *vertex;
+                }
+
+                influence
+                    .bones
+                    .iter()
+                    .zip(influence.weights)
+                    .filter(|(bone, weight)| *weight > 0.0 && **bone < matrices.len())
+                    .map(|(bone, weight)| matrices[*bone].transform(*vertex) * (weight / total))
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::f64::consts::PI;
+
+    use super::*;
+    use crate::quat::Quat;
+
+    fn arm() -> Skeleton {
+        // A root bone at the origin, with a child bone one unit along X
+        Skeleton::new(vec![
+            Bone {
+                name: String::from("shoulder"),
+                parent: None,
+                rest: Transform::identity(),
+                inverse_bind: Mat4::identity(),
+            },
+            Bone {
+                name: String::from("elbow"),
+                parent: Some(0),
+                rest: Transform::from_translation(Vec3::new(1.0, 0.0, 0.0)),
+                inverse_bind: Mat4::identity().translate(Vec3::new(-1.0, 0.0, 0.0)),
+            },
+        ])
+    }
+
+    #[test]
+    fn rest_pose_is_identity() {
+        let skeleton = arm();
+        for matrix in skeleton.skinning_matrices(&skeleton.rest_pose()) {
+            let point = Vec3::new(2.0, 1.0, 0.0);
+            assert!((matrix.transform(point) - point).length() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn child_follows_parent() {
+        let weights = VertexWeights {
+            bones: [1, 0, 0, 0],
+            weights: [1.0, 0.0, 0.0, 0.0],
+        };
+        let mut skin = Skin::new(arm(), vec![weights]);
+        skin.pose.local[0].rotation = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), PI / 2.0);
+
+        let result = skin.apply(&[Vec3::new(2.0, 0.0, 0.0)]);
+        assert!((result[0] - Vec3::new(0.0, 2.0, 0.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn unweighted_vertices_are_unchanged() {
+        let skin = Skin::new(arm(), vec![VertexWeights::default()]);
+        let vertex = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!(skin.apply(&[vertex]), vec![vertex]);
+    }
+
+    #[test]
+    fn broken_hierarchies_are_cut() {
+        // The elbow and wrist are each other's parents, and the hand's parent doesn't exist
+        let mut skeleton = arm();
+        skeleton.bones[1].parent = Some(2);
+        for (name, parent) in [("wrist", 1), ("hand", 7)] {
+            skeleton.bones.push(Bone {
+                name: String::from(name),
+                parent: Some(parent),
+                rest: Transform::from_translation(Vec3::new(1.0, 0.0, 0.0)),
+                inverse_bind: Mat4::identity(),
+            });
+        }
+
+        // The cycle is broken where it's first found, so the wrist becomes a root with the elbow under it
+        let origin = Vec3::new(0.0, 0.0, 0.0);
+        let matrices = skeleton.skinning_matrices(&skeleton.rest_pose());
+        assert_eq!(matrices.len(), 4);
+        assert!((matrices[2].transform(origin) - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!((matrices[1].transform(origin) - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+        assert!((matrices[3].transform(origin) - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-9);
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{
+    object::{Face, Object, Texture},
+    vec::{vec2::Vec2, vec3::Vec3},
+};
+
+///
+/// Plays the frames of a sprite sheet in order. Frames are laid out left to right, then top to bottom, in a grid of
+/// `columns` by `rows`, and `frame_count` of them are played at `frame_rate` frames a second.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SpriteAnimation {
+    pub columns: usize,
+    pub rows: usize,
+    pub frame_count: usize,
+    pub frame_rate: f64,
+}
+
+///
+/// A flat image which always faces the camera, like a particle or a tree in the distance. `size` is the sprite's
+/// width and height in world units.
+///
+#[derive(Clone)]
+pub struct Sprite {
+    pub texture: Texture,
+    pub size: Vec2,
+    pub animation: Option<SpriteAnimation>,
+}
+
+impl Sprite {
+    pub fn new(texture: Texture, size: Vec2) -> Sprite {
+        Sprite {
+            texture,
+            size,
+            animation: None,
+        }
+    }
+
+    pub fn with_animation(mut self, animation: SpriteAnimation) -> Sprite {
+        self.animation = Some(animation);
+        self
+    }
+
+    ///
+    /// Returns the frame shown at a given time, which is always 0 for sprites without an animation.
+    ///
+    pub fn frame(&self, time: f64) -> usize {
+        match self.animation {
+            Some(animation) if animation.frame_count > 0 => {
+                (time * animation.frame_rate).max(0.0) as usize % animation.frame_count
+            }
+            _ => 0,
+        }
+    }
+
+    ///
+    /// Returns the top left and bottom right texture coordinates of the frame shown at a given time.
+    ///
+    pub fn frame_coords(&self, time: f64) -> (Vec2, Vec2) {
+        let Some(animation) = self.animation else {
+            return (Vec2::new(0.0, 0.0), Vec2::new(1.0, 1.0));
+        };
+        let (columns, rows) = (animation.columns.max(1), animation.rows.max(1));
+        let frame = self.frame(time);
+        let (column, row) = (frame % columns, frame / columns % rows);
+
+        let cell = Vec2::new(1.0 / columns as f64, 1.0 / rows as f64);
+        let min = Vec2::new(column as f64 * cell.x, row as f64 * cell.y);
+        (min, min + cell)
+    }
+
+    ///
+    /// Builds a quad the size of the sprite, facing along Z. The camera draws sprites itself, so this is only used for
+    /// the sprite's bounds and raycasts.
+    ///
+    pub fn mesh(&self) -> Object {
+        let (x, y) = (self.size.x / 2.0, self.size.y / 2.0);
+        let vertices = vec![
+            Vec3::new(-x, y, 0.0),
+            Vec3::new(x, y, 0.0),
+            Vec3::new(x, -y, 0.0),
+            Vec3::new(-x, -y, 0.0),
+        ];
+        let tex_coords = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(0.0, 1.0),
+        ];
+        let normals = vec![Vec3::new(0.0, 0.0, 1.0)];
+        let faces = [(0, 1, 2), (0, 2, 3)]
+            .into_iter()
+            .map(|vertices| Face {
+                vertices,
+                tex_coords: vertices,
+                normals: (0, 0, 0),
+                material: 0,
+            })
+            .collect();
+        Object::new(vertices, tex_coords, normals, faces, self.texture.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn animated_frames() {
+        let texture = Texture::new(1, 1, vec![0]);
+        let sprite = Sprite::new(texture, Vec2::new(1.0, 1.0)).with_animation(SpriteAnimation {
+            columns: 4,
+            rows: 2,
+            frame_count: 6,
+            frame_rate: 2.0,
+        });
+
+        assert_eq!(sprite.frame(0.0), 0);
+        assert_eq!(sprite.frame(2.5), 5);
+        // Loops back round after the last frame
+        assert_eq!(sprite.frame(3.0), 0);
+
+        let (min, max) = sprite.frame_coords(2.5);
+        assert_eq!(min, Vec2::new(0.25, 0.5));
+        assert_eq!(max, Vec2::new(0.5, 1.0));
+    }
+}
@@ -0,0 +1,187 @@
+use std::{
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+use crate::{
+    assets::AssetManager,
+    scene::{SceneError, SceneObject, SceneRegion},
+    vec::vec3::Vec3,
+    world::{EntityId, World},
+};
+
+enum RegionState {
+    Unloaded,
+    Loading(Receiver<Result<Vec<SceneObject>, SceneError>>),
+    Loaded(Vec<EntityId>),
+    Failed,
+}
+
+///
+/// Loads the regions of a scene into a world as the viewer gets near them, and removes them again once it moves away.
+///
+/// Region files are parsed on worker threads, with at most `max_loading` at once - the nearest regions are started
+/// first. Regions are loaded within `load_distance` of the viewer and unloaded beyond `unload_distance`, which should
+/// be a bit further so regions on the edge don't load and unload over and over.
+///
+pub struct RegionStreamer {
+    regions: Vec<(SceneRegion, RegionState)>,
+    pub load_distance: f64,
+    pub unload_distance: f64,
+    pub max_loading: usize,
+}
+
+impl RegionStreamer {
+    pub fn new(regions: Vec<SceneRegion>, load_distance: f64) -> RegionStreamer {
+        RegionStreamer {
+            regions: regions
+                .into_iter()
+                .map(|region| (region, RegionState::Unloaded))
+                .collect(),
+            load_distance,
+            unload_distance: load_distance * 1.25,
+            max_loading: 2,
+        }
+    }
+
+    pub fn regions(&self) -> impl Iterator<Item = &SceneRegion> {
+        self.regions.iter().map(|(region, _)| region)
+    }
+
+    ///
+    /// Returns true if the region at `index` has been added to the world. Its objects may still be loading.
+    ///
+    pub fn is_loaded(&self, index: usize) -> bool {
+        matches!(self.regions.get(index), Some((_, RegionState::Loaded(_))))
+    }
+
+    ///
+    /// Returns the number of region files still being parsed.
+    ///
+    pub fn loading_count(&self) -> usize {
+        self.regions
+            .iter()
+            .filter(|(_, state)| matches!(state, RegionState::Loading(_)))
+            .count()
+    }
+
+    ///
+    /// Unloads regions the viewer has moved away from, adds any which have finished parsing, and starts parsing the
+    /// nearest regions in range. Regions which fail to load are returned along with their path, and aren't tried again.
+    ///
+    pub fn update(
+        &mut self,
+        world: &mut World,
+        assets: &mut AssetManager,
+        viewer: Vec3,
+    ) -> Vec<(String, SceneError)> {
+        let mut errors = vec![];
+
+        for (region, state) in &mut self.regions {
+            let distance = region.bounds.distance(viewer);
+
+            if let RegionState::Loading(receiver) = state {
+                match receiver.try_recv() {
+                    Ok(Ok(objects)) => {
+                        let ids = objects
+                            .iter()
+                            .map(|object| object.spawn(world, assets))
+                            .collect();
+                        *state = RegionState::Loaded(ids);
+                    }
+                    Ok(Err(error)) => {
+                        errors.push((region.path.clone(), error));
+                        *state = RegionState::Failed;
+                    }
+                    Err(TryRecvError::Empty) => (),
+                    Err(TryRecvError::Disconnected) => *state = RegionState::Unloaded,
+                }
+            }
+
+            if distance > self.unload_distance {
+                if let RegionState::Loaded(ids) = state {
+                    for id in ids.drain(..) {
+                        world.remove(id);
+                    }
+                }
+                // Regions still being parsed are abandoned, and their results dropped when they finish
+                if matches!(state, RegionState::Loaded(_) | RegionState::Loading(_)) {
+                    *state = RegionState::Unloaded;
+                }
+            }
+        }
+
+        let mut wanted: Vec<(usize, f64)> = self
+            .regions
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, state))| matches!(state, RegionState::Unloaded))
+            .map(|(index, (region, _))| (index, region.bounds.distance(viewer)))
+            .filter(|(_, distance)| *distance <= self.load_distance)
+            .collect();
+        wanted.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let free = self.max_loading.saturating_sub(self.loading_count());
+        for (index, _) in wanted.into_iter().take(free) {
+            let (region, state) = &mut self.regions[index];
+            let (sender, receiver) = mpsc::channel();
+            let region = region.clone();
+            thread::spawn(move || sender.send(region.load()));
+            *state = RegionState::Loading(receiver);
+        }
+
+        errors
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::scene::Scene;
+
+    fn object_at(x: f64) -> SceneObject {
+        let ron = format!(
+            "(asset: \"./resources/cube\", position: (x: {:?}, y: 0.0, z: 0.0))",
+            x
+        );
+        ron::from_str(&ron).unwrap()
+    }
+
+    #[test]
+    fn stream_regions() {
+        let directory = std::env::temp_dir().join("megavertex-streaming-test");
+        let mut scene = Scene {
+            objects: vec![object_at(1.0), object_at(2.0), object_at(50.0)],
+            ..Default::default()
+        };
+        scene
+            .split_into_regions(10.0, directory.to_str().unwrap())
+            .unwrap();
+        assert!(scene.objects.is_empty());
+        assert_eq!(scene.regions.len(), 2);
+
+        let mut world = World::new();
+        let mut assets = AssetManager::new();
+        let mut streamer = RegionStreamer::new(scene.regions, 20.0);
+
+        // Only the region near the origin is in range
+        let viewer = Vec3::new(0.0, 0.0, 0.0);
+        while !streamer.is_loaded(0) {
+            assert!(streamer.update(&mut world, &mut assets, viewer).is_empty());
+            thread::yield_now();
+        }
+        assert_eq!(world.len(), 2);
+        assert!(!streamer.is_loaded(1));
+
+        // Moving away unloads it, and brings the other region in
+        let viewer = Vec3::new(60.0, 0.0, 0.0);
+        while !streamer.is_loaded(1) {
+            assert!(streamer.update(&mut world, &mut assets, viewer).is_empty());
+            thread::yield_now();
+        }
+        assert!(!streamer.is_loaded(0));
+        assert_eq!(world.len(), 1);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
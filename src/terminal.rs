@@ -0,0 +1,134 @@
+use std::{
+    fmt::Write as _,
+    io::{self, Stdout, Write},
+};
+
+use crate::{error::Error, present::PresentTarget};
+
+// Moves the cursor to the top left, so each frame is drawn over the last
+const HOME: &str = "\x1b[H";
+// Clears the screen, before the first frame
+const CLEAR: &str = "\x1b[2J";
+// Puts the terminal's colours back to normal
+const RESET: &str = "\x1b[0m";
+// The top half of a character cell, coloured with the top pixel, leaving the bottom half for the background colour
+const HALF_BLOCK: char = '▀';
+
+///
+/// Shows the renderer's frames in a terminal, for watching a scene over SSH or anywhere else a window can't be opened.
+///
+/// Each character shows two pixels stacked on top of each other, as an upper half block coloured with the top pixel on
+/// a background of the bottom pixel, so a frame takes up `width` columns and half of `height` rows. The terminal needs
+/// to support 24-bit colour, which most do. Frames are drawn over the top of each other rather than scrolling, and
+/// only the colours that change between characters are written, which keeps frames small enough to send over a slow
+/// connection.
+///
+pub struct Terminal<W: Write> {
+    out: W,
+    cleared: bool,
+    // The escape codes for the last frame, kept between frames to save reallocating them
+    text: String,
+}
+
+impl Terminal<Stdout> {
+    pub fn stdout() -> Terminal<Stdout> {
+        Terminal::new(io::stdout())
+    }
+}
+
+impl<W: Write> Terminal<W> {
+    ///
+    /// Creates a terminal that writes frames to `out`.
+    ///
+    pub fn new(out: W) -> Terminal<W> {
+        Terminal {
+            out,
+            cleared: false,
+            text: String::new(),
+        }
+    }
+}
+
+impl<W: Write> PresentTarget for Terminal<W> {
+    ///
+    /// Draws a frame, clearing the screen first if it's the first frame drawn.
+    ///
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize) -> Result<(), Error> {
+        self.text.clear();
+        if !self.cleared {
+            self.text.push_str(CLEAR);
+            self.cleared = true;
+        }
+        self.text.push_str(HOME);
+        write_frame(buffer, width, height, &mut self.text);
+        self.out
+            .write_all(self.text.as_bytes())
+            .map_err(Error::render)?;
+        self.out.flush().map_err(Error::render)
+    }
+}
+
+///
+/// Writes a frame of 0RGB pixels as rows of coloured half blocks, two rows of pixels to a row of text. An odd last row
+/// of pixels is drawn over the terminal's own background.
+///
+fn write_frame(buffer: &[u32], width: usize, height: usize, text: &mut String) {
+    for y in (0..height).step_by(2) {
+        let (mut foreground, mut background) = (None, None);
+        for x in 0..width {
+            let top = buffer[y * width + x];
+            let bottom = (y + 1 < height).then(|| buffer[(y + 1) * width + x]);
+
+            if foreground != Some(top) {
+                let [_, r, g, b] = top.to_be_bytes();
+                let _ = write!(text, "\x1b[38;2;{};{};{}m", r, g, b);
+                foreground = Some(top);
+            }
+            if background != Some(bottom) {
+                match bottom {
+                    Some(bottom) => {
+                        let [_, r, g, b] = bottom.to_be_bytes();
+                        let _ = write!(text, "\x1b[48;2;{};{};{}m", r, g, b);
+                    }
+                    None => text.push_str("\x1b[49m"),
+                }
+                background = Some(bottom);
+            }
+            text.push(HALF_BLOCK);
+        }
+        text.push_str(RESET);
+        text.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{config::RendererConfig, renderer::Renderer};
+
+    #[test]
+    fn frames_as_half_blocks() {
+        let mut text = String::new();
+        write_frame(
+            &[0xff0000, 0xff0000, 0x00ff00, 0x0000ff, 0x000000, 0x000000],
+            2,
+            3,
+            &mut text,
+        );
+        assert_eq!(
+            text,
+            "\x1b[38;2;255;0;0m\x1b[48;2;0;255;0m▀\x1b[48;2;0;0;255m▀\x1b[0m\n\
+             \x1b[38;2;0;0;0m\x1b[49m▀▀\x1b[0m\n"
+        );
+
+        let renderer = Renderer::new(RendererConfig::new(4, 4));
+        let mut terminal = Terminal::new(vec![]);
+        terminal.present_frame(&renderer).unwrap();
+        terminal.present_frame(&renderer).unwrap();
+        let output = String::from_utf8(terminal.out).unwrap();
+        // The screen is only cleared before the first frame
+        assert_eq!(output.matches(CLEAR).count(), 1);
+        assert_eq!(output.matches(HOME).count(), 2);
+        assert_eq!(output.matches(HALF_BLOCK).count(), 16);
+    }
+}
@@ -0,0 +1,397 @@
+use crate::{
+    geometry::aabb::Aabb,
+    object::{Face, Object, Texture},
+    vec::{vec2::Vec2, vec3::Vec3},
+};
+
+///
+/// A heightfield split into square chunks, each of which is turned into its own mesh.
+///
+/// - `chunk_size` is the number of quads along each edge of a chunk, which must be a power of two
+/// - `spacing` is the distance between neighbouring height samples
+/// - `origin` is the position of the terrain's corner with the lowest X and Z, and heights are relative to its Y
+/// - `lod_distances` are the distances from the viewer at which chunks drop to each lower level of detail. Each level
+///   halves the number of quads along a chunk's edges
+/// - `skirt_depth` is how far the skirt around each chunk hangs below its edges, which hides the cracks that appear
+///   between neighbouring chunks at different levels of detail
+///
+/// Each chunk stores its own copy of the samples along its edges, so chunks can be meshed independently.
+///
+#[derive(Clone)]
+pub struct Terrain {
+    chunk_size: usize,
+    chunks_x: usize,
+    chunks_z: usize,
+    spacing: f64,
+    chunks: Vec<Vec<f64>>,
+    pub origin: Vec3,
+    pub texture: Texture,
+    pub lod_distances: Vec<f64>,
+    pub skirt_depth: f64,
+}
+
+impl Terrain {
+    ///
+    /// Creates a flat terrain made up of `chunks_x` by `chunks_z` chunks.
+    ///
+    pub fn new(
+        chunks_x: usize,
+        chunks_z: usize,
+        chunk_size: usize,
+        spacing: f64,
+        texture: Texture,
+    ) -> Terrain {
+        assert!(
+            chunk_size.is_power_of_two(),
+            "terrain chunk size must be a power of two"
+        );
+        let samples = (chunk_size + 1) * (chunk_size + 1);
+        Terrain {
+            chunk_size,
+            chunks_x,
+            chunks_z,
+            spacing,
+            chunks: vec![vec![0.0; samples]; chunks_x * chunks_z],
+            origin: Vec3::new(0.0, 0.0, 0.0),
+            texture,
+            lod_distances: vec![],
+            skirt_depth: spacing,
+        }
+    }
+
+    ///
+    /// Creates a terrain whose height at each sample is given by a function of the sample's X and Z position, relative
+    /// to the terrain's origin.
+    ///
+    pub fn from_fn<F>(
+        chunks_x: usize,
+        chunks_z: usize,
+        chunk_size: usize,
+        spacing: f64,
+        texture: Texture,
+        height: F,
+    ) -> Terrain
+    where
+        F: Fn(f64, f64) -> f64,
+    {
+        let mut terrain = Terrain::new(chunks_x, chunks_z, chunk_size, spacing, texture);
+        for iz in 0..terrain.samples_z() {
+            for ix in 0..terrain.samples_x() {
+                terrain.set_sample(ix, iz, height(ix as f64 * spacing, iz as f64 * spacing));
+            }
+        }
+        terrain
+    }
+
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    pub fn spacing(&self) -> f64 {
+        self.spacing
+    }
+
+    ///
+    /// Returns the number of chunks along the X and Z axes.
+    ///
+    pub fn chunk_count(&self) -> (usize, usize) {
+        (self.chunks_x, self.chunks_z)
+    }
+
+    pub fn samples_x(&self) -> usize {
+        self.chunks_x * self.chunk_size + 1
+    }
+
+    pub fn samples_z(&self) -> usize {
+        self.chunks_z * self.chunk_size + 1
+    }
+
+    ///
+    /// The coarsest level of detail, at which each chunk is a single quad.
+    ///
+    pub fn max_lod(&self) -> usize {
+        self.chunk_size.trailing_zeros() as usize
+    }
+
+    ///
+    /// Returns the height of a sample, where `ix` and `iz` count samples from the origin.
+    ///
+    pub fn sample(&self, ix: usize, iz: usize) -> f64 {
+        // Samples on an edge belong to the chunk before it, unless it's the last edge of the terrain
+        let cx = (ix / self.chunk_size).min(self.chunks_x - 1);
+        let cz = (iz / self.chunk_size).min(self.chunks_z - 1);
+        self.chunk_sample(cx, cz, ix - cx * self.chunk_size, iz - cz * self.chunk_size)
+    }
+
+    ///
+    /// Sets the height of a sample, updating every chunk that shares it.
+    ///
+    pub fn set_sample(&mut self, ix: usize, iz: usize, height: f64) {
+        let size = self.chunk_size;
+        let chunks_of = |i: usize, count: usize| {
+            let chunk = i / size;
+            let mut chunks = vec![];
+            if chunk < count {
+                chunks.push(chunk);
+            }
+            // Samples on an edge between two chunks are shared by both
+            if i.is_multiple_of(size) && chunk > 0 {
+                chunks.push(chunk - 1);
+            }
+            chunks
+        };
+
+        for cz in chunks_of(iz, self.chunks_z) {
+            for cx in chunks_of(ix, self.chunks_x) {
+                let (lx, lz) = (ix - cx * size, iz - cz * size);
+                self.chunks[cz * self.chunks_x + cx][lz * (size + 1) + lx] = height;
+            }
+        }
+    }
+
+    fn chunk_sample(&self, cx: usize, cz: usize, lx: usize, lz: usize) -> f64 {
+        self.chunks[cz * self.chunks_x + cx][lz * (self.chunk_size + 1) + lx]
+    }
+
+    ///
+    /// Returns the height of the terrain's surface at a world position, or `None` if the position is outside the
+    /// terrain. This matches the most detailed mesh exactly.
+    ///
+    pub fn height(&self, x: f64, z: f64) -> Option<f64> {
+        let fx = (x - self.origin.x) / self.spacing;
+        let fz = (z - self.origin.z) / self.spacing;
+        let (max_x, max_z) = ((self.samples_x() - 1) as f64, (self.samples_z() - 1) as f64);
+        if !(0.0..=max_x).contains(&fx) || !(0.0..=max_z).contains(&fz) {
+            return None;
+        }
+
+        // Find the quad the position is in, staying inside the terrain on its far edges
+        let ix = (fx.floor() as usize).min(self.samples_x() - 2);
+        let iz = (fz.floor() as usize).min(self.samples_z() - 2);
+        let (tx, tz) = (fx - ix as f64, fz - iz as f64);
+
+        // Quads are split along the diagonal from their first corner to their opposite corner, as in `chunk_mesh`
+        let a = self.sample(ix, iz);
+        let b = self.sample(ix, iz + 1);
+        let c = self.sample(ix + 1, iz + 1);
+        let d = self.sample(ix + 1, iz);
+        let height = if tz >= tx {
+            a + (c - b) * tx + (b - a) * tz
+        } else {
+            a + (d - a) * tx + (c - d) * tz
+        };
+        Some(self.origin.y + height)
+    }
+
+    ///
+    /// Returns the terrain's surface normal at a world position, or `None` if the position is outside the terrain. The
+    /// slope is measured half a sample either side, so it changes smoothly between quads.
+    ///
+    pub fn normal(&self, x: f64, z: f64) -> Option<Vec3> {
+        let centre = self.height(x, z)?;
+        let step = self.spacing / 2.0;
+        // On the edges of the terrain, only the side inside it is used
+        let slope = |ahead: Option<f64>, behind: Option<f64>| match (ahead, behind) {
+            (Some(ahead), Some(behind)) => (ahead - behind) / (2.0 * step),
+            (Some(ahead), None) => (ahead - centre) / step,
+            (None, Some(behind)) => (centre - behind) / step,
+            (None, None) => 0.0,
+        };
+        let dx = slope(self.height(x + step, z), self.height(x - step, z));
+        let dz = slope(self.height(x, z + step), self.height(x, z - step));
+        Some(Vec3::new(-dx, 1.0, -dz).normalise())
+    }
+
+    ///
+    /// Estimates the surface normal at a sample from the heights of its neighbours.
+    ///
+    fn sample_normal(&self, ix: usize, iz: usize) -> Vec3 {
+        let (x0, x1) = (ix.saturating_sub(1), (ix + 1).min(self.samples_x() - 1));
+        let (z0, z1) = (iz.saturating_sub(1), (iz + 1).min(self.samples_z() - 1));
+        let dx = (self.sample(x1, iz) - self.sample(x0, iz)) / ((x1 - x0) as f64 * self.spacing);
+        let dz = (self.sample(ix, z1) - self.sample(ix, z0)) / ((z1 - z0) as f64 * self.spacing);
+        Vec3::new(-dx, 1.0, -dz).normalise()
+    }
+
+    ///
+    /// Returns the world space bounds of a chunk, including its skirt.
+    ///
+    pub fn chunk_bounds(&self, cx: usize, cz: usize) -> Aabb {
+        let heights = &self.chunks[cz * self.chunks_x + cx];
+        let min = heights.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = heights.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let extent = self.chunk_size as f64 * self.spacing;
+        let corner = self.origin + Vec3::new(cx as f64 * extent, 0.0, cz as f64 * extent);
+
+        Aabb::new(
+            corner + Vec3::new(0.0, min - self.skirt_depth, 0.0),
+            corner + Vec3::new(extent, max, extent),
+        )
+    }
+
+    ///
+    /// Picks the level of detail for a chunk from its horizontal distance to the viewer.
+    ///
+    pub fn lod_for(&self, cx: usize, cz: usize, viewer: Vec3) -> usize {
+        let centre = self.chunk_bounds(cx, cz).centre();
+        let distance = Vec3::new(centre.x - viewer.x, 0.0, centre.z - viewer.z).length();
+        let lod = self
+            .lod_distances
+            .iter()
+            .filter(|threshold| distance > **threshold)
+            .count();
+        lod.min(self.max_lod())
+    }
+
+    ///
+    /// Generates the mesh for a chunk, in world space. At level of detail `lod`, only every `2^lod`th sample is used.
+    /// The texture is stretched once across each chunk.
+    ///
+    pub fn chunk_mesh(&self, cx: usize, cz: usize, lod: usize) -> Object {
+        let step = 1 << lod.min(self.max_lod());
+        let count = self.chunk_size / step + 1;
+        let (base_x, base_z) = (cx * self.chunk_size, cz * self.chunk_size);
+
+        let mut vertices = vec![];
+        let mut tex_coords = vec![];
+        let mut normals = vec![];
+        for row in 0..count {
+            for col in 0..count {
+                let (lx, lz) = (col * step, row * step);
+                vertices.push(
+                    self.origin
+                        + Vec3::new(
+                            (base_x + lx) as f64 * self.spacing,
+                            self.chunk_sample(cx, cz, lx, lz),
+                            (base_z + lz) as f64 * self.spacing,
+                        ),
+                );
+                tex_coords.push(Vec2::new(
+                    lx as f64 / self.chunk_size as f64,
+                    lz as f64 / self.chunk_size as f64,
+                ));
+                normals.push(self.sample_normal(base_x + lx, base_z + lz));
+            }
+        }
+
+        let mut faces = vec![];
+        let mut triangle = |a: usize, b: usize, c: usize| {
+            faces.push(Face {
+                vertices: (a, b, c),
+                tex_coords: (a, b, c),
+                normals: (a, b, c),
+                material: 0,
+            })
+        };
+
+        let index = |col: usize, row: usize| row * count + col;
+        for row in 0..count - 1 {
+            for col in 0..count - 1 {
+                let a = index(col, row);
+                let b = index(col, row + 1);
+                let c = index(col + 1, row + 1);
+                let d = index(col + 1, row);
+                triangle(a, b, c);
+                triangle(a, c, d);
+            }
+        }
+
+        // Walk round the edge of the chunk, hanging a strip of faces below each edge
+        let last = count - 1;
+        let perimeter: Vec<usize> = (0..last)
+            .map(|col| index(col, 0))
+            .chain((0..last).map(|row| index(last, row)))
+            .chain((1..=last).rev().map(|col| index(col, last)))
+            .chain((1..=last).rev().map(|row| index(0, row)))
+            .collect();
+
+        let skirt_start = vertices.len();
+        for &edge in &perimeter {
+            vertices.push(vertices[edge] - Vec3::new(0.0, self.skirt_depth, 0.0));
+            tex_coords.push(tex_coords[edge]);
+            normals.push(normals[edge]);
+        }
+        for i in 0..perimeter.len() {
+            let next = (i + 1) % perimeter.len();
+            let (p, q) = (perimeter[i], perimeter[next]);
+            let (p_skirt, q_skirt) = (skirt_start + i, skirt_start + next);
+            triangle(p, q_skirt, p_skirt);
+            triangle(p, q, q_skirt);
+        }
+
+        Object::new(vertices, tex_coords, normals, faces, self.texture.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn texture() -> Texture {
+        Texture::new(1, 1, vec![0])
+    }
+
+    fn slope() -> Terrain {
+        Terrain::from_fn(2, 2, 4, 1.0, texture(), |x, z| x + z * 2.0)
+    }
+
+    #[test]
+    fn height_between_samples() {
+        let mut terrain = slope();
+        terrain.origin = Vec3::new(-4.0, 10.0, -4.0);
+
+        // A plane is reproduced exactly by the triangles
+        assert_eq!(terrain.height(-4.0, -4.0), Some(10.0));
+        assert_eq!(terrain.height(-2.5, -3.0), Some(10.0 + 1.5 + 2.0));
+        assert_eq!(terrain.height(4.0, 4.0), Some(10.0 + 8.0 + 16.0));
+        assert_eq!(terrain.height(4.5, 0.0), None);
+
+        // The slope rises 1 along X and 2 along Z for every unit, so the normal leans back against it
+        let expected = Vec3::new(-1.0, 1.0, -2.0).normalise();
+        let normal = terrain.normal(-1.3, 0.6).unwrap();
+        assert!((normal - expected).length() < 1e-9);
+        assert!((terrain.normal(4.0, 4.0).unwrap() - expected).length() < 1e-9);
+        assert_eq!(terrain.normal(4.5, 0.0), None);
+    }
+
+    #[test]
+    fn edge_samples_are_shared() {
+        let mut terrain = slope();
+        terrain.set_sample(4, 4, 100.0);
+
+        // The sample at the centre is the corner of all four chunks
+        for (cx, cz) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let lx = if cx == 0 { 4 } else { 0 };
+            let lz = if cz == 0 { 4 } else { 0 };
+            assert_eq!(terrain.chunk_sample(cx, cz, lx, lz), 100.0);
+        }
+        assert_eq!(terrain.height(4.0, 4.0), Some(100.0));
+    }
+
+    #[test]
+    fn mesh_levels_of_detail() {
+        let terrain = slope();
+
+        // 5x5 grid of vertices plus a skirt vertex for each of the 16 on the edge
+        let detailed = terrain.chunk_mesh(1, 0, 0);
+        assert_eq!(detailed.vertices.len(), 25 + 16);
+        assert_eq!(detailed.faces.len(), 32 + 32);
+        assert!(detailed.validate().is_ok());
+
+        let coarse = terrain.chunk_mesh(1, 0, terrain.max_lod());
+        assert_eq!(coarse.vertices.len(), 4 + 4);
+        assert_eq!(coarse.faces.len(), 2 + 8);
+        assert_eq!(coarse.vertices[0], Vec3::new(4.0, 4.0, 0.0));
+    }
+
+    #[test]
+    fn lod_by_distance() {
+        let mut terrain = slope();
+        terrain.lod_distances = vec![10.0, 20.0, 30.0, 40.0];
+
+        assert_eq!(terrain.lod_for(0, 0, Vec3::new(0.0, 100.0, 0.0)), 0);
+        assert_eq!(terrain.lod_for(0, 0, Vec3::new(25.0, 0.0, 2.0)), 2);
+        // Chunks of 4 quads can't go below 1 quad
+        assert_eq!(terrain.lod_for(0, 0, Vec3::new(100.0, 0.0, 2.0)), 2);
+    }
+}
@@ -0,0 +1,408 @@
+use std::{
+    collections::VecDeque,
+    thread,
+    time::{Duration, Instant},
+};
+
+// How many recent frames `FrameStats` averages over
+const FRAME_HISTORY: usize = 60;
+
+// How long before a frame is due `FramePacing::Hybrid` stops sleeping and starts spinning, as sleeps can overshoot
+const SPIN_MARGIN: Duration = Duration::from_millis(2);
+
+// The speeds `TimeControl::slower` and `TimeControl::faster` step between, which are also its limits
+const TIME_SCALES: [f64; 6] = [0.1, 0.25, 0.5, 1.0, 1.5, 2.0];
+
+// How much each new frame moves `DynamicResolution`'s average frame time, so one slow frame doesn't change the scale
+const FRAME_SMOOTHING: f64 = 0.1;
+
+// How far `DynamicResolution` moves the render scale at a time. It drops faster than it rises, so a slow scene recovers
+// quickly but the scale creeps back up
+const SCALE_DOWN_STEP: f64 = 0.1;
+const SCALE_UP_STEP: f64 = 0.05;
+
+// How many frames `DynamicResolution` waits after changing the scale, so the average catches up before it's changed again
+const SCALE_COOLDOWN: u32 = 20;
+
+///
+/// Slows down, speeds up or pauses the passing of time, for debugging animation and physics. Time is scaled between
+/// 0.1x and 2x before the physics simulation and updates see it, so updates come less often in slow motion but each one
+/// is the same length. While paused no time passes at all, and `step` runs exactly one update at a time.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TimeControl {
+    scale: f64,
+    paused: bool,
+    step: bool,
+}
+
+impl Default for TimeControl {
+    fn default() -> Self {
+        TimeControl::new()
+    }
+}
+
+impl TimeControl {
+    pub fn new() -> TimeControl {
+        TimeControl {
+            scale: 1.0,
+            paused: false,
+            step: false,
+        }
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    ///
+    /// Sets how fast time passes, where 1 is normal speed. The scale is kept between 0.1 and 2.
+    ///
+    pub fn set_scale(&mut self, scale: f64) {
+        let (min, max) = (TIME_SCALES[0], TIME_SCALES[TIME_SCALES.len() - 1]);
+        if !scale.is_nan() {
+            self.scale = scale.clamp(min, max);
+        }
+    }
+
+    ///
+    /// Slows time down to the next speed in 0.1x, 0.25x, 0.5x, 1x, 1.5x and 2x.
+    ///
+    pub fn slower(&mut self) {
+        let slower = TIME_SCALES.iter().rev().find(|scale| **scale < self.scale);
+        self.set_scale(slower.copied().unwrap_or(TIME_SCALES[0]));
+    }
+
+    ///
+    /// Speeds time up to the next speed, as with `slower`.
+    ///
+    pub fn faster(&mut self) {
+        let faster = TIME_SCALES.iter().find(|scale| **scale > self.scale);
+        self.set_scale(
+            faster
+                .copied()
+                .unwrap_or(TIME_SCALES[TIME_SCALES.len() - 1]),
+        );
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        self.step = false;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.set_paused(!self.paused);
+    }
+
+    ///
+    /// Pauses time if it isn't already, and lets exactly one more update run.
+    ///
+    pub fn step(&mut self) {
+        self.paused = true;
+        self.step = true;
+    }
+
+    ///
+    /// Returns whether a step is waiting to be run, and forgets it.
+    ///
+    pub fn take_step(&mut self) -> bool {
+        std::mem::take(&mut self.step)
+    }
+
+    ///
+    /// Returns how much time passes in the world over `elapsed` seconds of real time.
+    ///
+    pub fn scaled(&self, elapsed: f64) -> f64 {
+        if self.paused {
+            0.0
+        } else {
+            elapsed * self.scale
+        }
+    }
+}
+
+///
+/// How `run` waits between frames to hold the target frame rate.
+/// - `Sleep` hands the time back to the system, which keeps the CPU idle but can overshoot by a millisecond or more
+/// - `Spin` busy waits, which is precise but keeps a core busy the whole time
+/// - `Hybrid` sleeps for most of the wait and spins for the last moment, which is nearly as precise as spinning
+///   while using little more CPU than sleeping
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum FramePacing {
+    Sleep,
+    Spin,
+    #[default]
+    Hybrid,
+}
+
+impl FramePacing {
+    ///
+    /// Waits until `deadline`, returning straight away if it has already passed.
+    ///
+    pub fn wait_until(self, deadline: Instant) {
+        let sleep_until = match self {
+            FramePacing::Sleep => deadline,
+            FramePacing::Spin => Instant::now(),
+            FramePacing::Hybrid => deadline.checked_sub(SPIN_MARGIN).unwrap_or(deadline),
+        };
+        let now = Instant::now();
+        if sleep_until > now {
+            thread::sleep(sleep_until - now);
+        }
+        while Instant::now() < deadline {
+            std::hint::spin_loop();
+        }
+    }
+
+    ///
+    /// Returns when the frame after one due at `previous` should be shown, `frame_time` later. If the frame ran late
+    /// and `now` is already past that, the schedule starts again from now rather than rushing to catch up.
+    ///
+    pub fn next_frame(previous: Instant, now: Instant, frame_time: Duration) -> Instant {
+        let next = previous + frame_time;
+        if next < now {
+            now
+        } else {
+            next
+        }
+    }
+}
+
+///
+/// How long recent frames have taken, measured from the start of one frame to the start of the next, so waiting to
+/// hold the frame rate counts. Only the last 60 frames are kept.
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrameStats {
+    frames: VecDeque<Duration>,
+}
+
+impl FrameStats {
+    pub fn new() -> FrameStats {
+        FrameStats {
+            frames: VecDeque::with_capacity(FRAME_HISTORY),
+        }
+    }
+
+    ///
+    /// Adds a frame's time, forgetting the oldest once the history is full.
+    ///
+    pub fn record(&mut self, frame_time: Duration) {
+        if self.frames.len() == FRAME_HISTORY {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame_time);
+    }
+
+    ///
+    /// Returns how long the last frame took, or zero before any have been recorded.
+    ///
+    pub fn last(&self) -> Duration {
+        self.frames.back().copied().unwrap_or_default()
+    }
+
+    pub fn average(&self) -> Duration {
+        if self.frames.is_empty() {
+            return Duration::ZERO;
+        }
+        self.frames.iter().sum::<Duration>() / self.frames.len() as u32
+    }
+
+    ///
+    /// Returns the slowest recent frame, which shows stutters that the average smooths over.
+    ///
+    pub fn worst(&self) -> Duration {
+        self.frames.iter().max().copied().unwrap_or_default()
+    }
+
+    ///
+    /// Returns the average number of frames shown a second.
+    ///
+    pub fn fps(&self) -> f64 {
+        let average = self.average().as_secs_f64();
+        if average > 0.0 {
+            1.0 / average
+        } else {
+            0.0
+        }
+    }
+}
+
+///
+/// Adjusts the render scale to hold a frame rate, drawing fewer pixels when frames take too long and more when there's
+/// time to spare. Frames are measured by the time spent working on them, not counting any wait to hold the frame rate,
+/// and averaged over the last few.
+/// - `target_fps` is the frame rate to hold
+/// - `min_scale` and `max_scale` are how far the render scale can go
+/// - `headroom` is how far under the time for a frame the average has to be before the scale goes up, as a fraction of
+///   it. The scale goes down as soon as frames run over, so a gap between the two stops it flickering back and forth
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DynamicResolution {
+    pub target_fps: f64,
+    pub min_scale: f64,
+    pub max_scale: f64,
+    pub headroom: f64,
+    scale: f64,
+    // The smoothed time spent on each frame, in seconds, once one has been recorded
+    average: Option<f64>,
+    // Frames left to wait before the scale can change again
+    cooldown: u32,
+}
+
+impl DynamicResolution {
+    pub fn new(target_fps: f64) -> DynamicResolution {
+        DynamicResolution {
+            target_fps,
+            min_scale: 0.25,
+            max_scale: 1.0,
+            headroom: 0.25,
+            scale: 1.0,
+            average: None,
+            cooldown: 0,
+        }
+    }
+
+    ///
+    /// Returns the render scale frames should be drawn at.
+    ///
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    ///
+    /// Returns the average time spent on recent frames, or zero before any have been recorded.
+    ///
+    pub fn average(&self) -> Duration {
+        Duration::from_secs_f64(self.average.unwrap_or_default())
+    }
+
+    ///
+    /// Adds how long the last frame took to work on, and returns the render scale to draw the next one at.
+    ///
+    pub fn record(&mut self, work_time: Duration) -> f64 {
+        let work_time = work_time.as_secs_f64();
+        let average = match self.average {
+            Some(average) => average + (work_time - average) * FRAME_SMOOTHING,
+            None => work_time,
+        };
+        self.average = Some(average);
+
+        let budget = 1.0 / self.target_fps;
+        if self.cooldown > 0 {
+            self.cooldown -= 1;
+        } else if average > budget && self.scale > self.min_scale {
+            self.set_scale(self.scale - SCALE_DOWN_STEP);
+        } else if average < budget * (1.0 - self.headroom) && self.scale < self.max_scale {
+            self.set_scale(self.scale + SCALE_UP_STEP);
+        }
+        self.scale
+    }
+
+    fn set_scale(&mut self, scale: f64) {
+        self.scale = scale.clamp(self.min_scale, self.max_scale);
+        self.cooldown = SCALE_COOLDOWN;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn frame_stats() {
+        let mut stats = FrameStats::new();
+        assert_eq!(stats.fps(), 0.0);
+
+        stats.record(Duration::from_millis(10));
+        stats.record(Duration::from_millis(30));
+        assert_eq!(stats.last(), Duration::from_millis(30));
+        assert_eq!(stats.average(), Duration::from_millis(20));
+        assert_eq!(stats.worst(), Duration::from_millis(30));
+        assert!((stats.fps() - 50.0).abs() < 1e-9);
+
+        // Old frames drop out of the history
+        for _ in 0..FRAME_HISTORY {
+            stats.record(Duration::from_millis(5));
+        }
+        assert_eq!(stats.worst(), Duration::from_millis(5));
+    }
+
+    #[test]
+    fn time_control() {
+        let mut time = TimeControl::new();
+        assert_eq!(time.scaled(0.5), 0.5);
+
+        // Speeds step between the presets, and stop at the ends
+        time.slower();
+        time.slower();
+        assert_eq!(time.scale(), 0.25);
+        assert_eq!(time.scaled(0.4), 0.1);
+        for _ in 0..10 {
+            time.faster();
+        }
+        assert_eq!(time.scale(), 2.0);
+        time.set_scale(0.0);
+        assert_eq!(time.scale(), 0.1);
+        time.set_scale(0.7);
+        time.faster();
+        assert_eq!(time.scale(), 1.0);
+
+        // Stepping pauses, and only lets one update through
+        time.step();
+        assert!(time.is_paused());
+        assert_eq!(time.scaled(0.5), 0.0);
+        assert!(time.take_step());
+        assert!(!time.take_step());
+        time.toggle_pause();
+        assert!(!time.is_paused());
+    }
+
+    #[test]
+    fn frame_schedule() {
+        let start = Instant::now();
+        let frame = Duration::from_millis(16);
+
+        // Frames on time keep to the schedule, even if they're shown a little late
+        let late = start + Duration::from_millis(5);
+        assert_eq!(FramePacing::next_frame(start, late, frame), start + frame);
+
+        // Frames which fall behind start again from now
+        let behind = start + Duration::from_millis(40);
+        assert_eq!(FramePacing::next_frame(start, behind, frame), behind);
+
+        // Every strategy waits until the deadline
+        for pacing in [FramePacing::Sleep, FramePacing::Spin, FramePacing::Hybrid] {
+            let deadline = Instant::now() + Duration::from_millis(3);
+            pacing.wait_until(deadline);
+            assert!(Instant::now() >= deadline);
+        }
+    }
+
+    #[test]
+    fn dynamic_resolution() {
+        let mut resolution = DynamicResolution::new(50.0);
+        let frames = |resolution: &mut DynamicResolution, ms: u64, count: u32| {
+            for _ in 0..count {
+                resolution.record(Duration::from_millis(ms));
+            }
+            resolution.scale()
+        };
+
+        // Slow frames bring the scale down a step at a time, waiting between steps, until it reaches its limit
+        assert_eq!(frames(&mut resolution, 30, 1), 0.9);
+        assert_eq!(frames(&mut resolution, 30, SCALE_COOLDOWN), 0.9);
+        assert!((frames(&mut resolution, 30, 1) - 0.8).abs() < 1e-9);
+        assert_eq!(frames(&mut resolution, 30, 1000), 0.25);
+
+        // Frames just under the time for a frame leave it where it is, and ones well under bring it back up
+        assert_eq!(frames(&mut resolution, 18, 1000), 0.25);
+        assert_eq!(frames(&mut resolution, 5, 1000), 1.0);
+        assert!(resolution.average() < Duration::from_millis(6));
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{framebuffer::Colour, light::Light, vec::vec3::Vec3};
+
+///
+/// Cel shading for an entity, which lights each face by the world's lights in a few flat bands of brightness rather
+/// than a smooth gradient, and can draw a solid outline around the entity's silhouette.
+/// - `bands` is how many levels of brightness there are, from `shadow` for faces turned away from every light up to
+///   full brightness for faces turned squarely towards one
+/// - `outline_width` is how far the outline reaches past the entity's surface in world units, or 0 for no outline
+///
+/// Give an entity toon shading with `World::set_toon`. Entities without it stay unlit. Outlines are drawn as the back
+/// faces of a copy of the mesh pushed out along its normals, so they need meshes with smooth, shared normals to stay
+/// unbroken at corners.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Toon {
+    pub bands: usize,
+    pub shadow: f64,
+    pub outline: Colour,
+    pub outline_width: f64,
+}
+
+impl Default for Toon {
+    fn default() -> Self {
+        Toon {
+            bands: 3,
+            shadow: 0.35,
+            outline: Colour::from_hex(0x000000),
+            outline_width: 0.03,
+        }
+    }
+}
+
+impl Toon {
+    pub fn with_bands(mut self, bands: usize) -> Toon {
+        self.bands = bands;
+        self
+    }
+
+    pub fn with_shadow(mut self, shadow: f64) -> Toon {
+        self.shadow = shadow;
+        self
+    }
+
+    pub fn with_outline(mut self, colour: Colour, width: f64) -> Toon {
+        self.outline = colour;
+        self.outline_width = width;
+        self
+    }
+
+    ///
+    /// Rounds how much light reaches a face, between 0 and 1, down to the band it falls in.
+    ///
+    pub fn band(&self, light: f64) -> f64 {
+        let steps = self.bands.max(2) - 1;
+        let band = (light.clamp(0.0, 1.0) * steps as f64 + 1e-9).floor();
+        band / steps as f64
+    }
+
+    ///
+    /// Works out the colour a face at `centre` with the given normal is multiplied by, in world space. Each light's
+    /// share is banded separately, then added up and raised so the darkest band is `shadow`.
+    ///
+    pub fn shade(&self, lights: &[Light], centre: Vec3, normal: Vec3) -> Vec3 {
+        // Faces without any area have no normal, so no light reaches them
+        let normal = match normal.length() > 0.0 {
+            true => normal.normalise(),
+            false => normal,
+        };
+        let mut lit = Vec3::new(0.0, 0.0, 0.0);
+        for light in lights {
+            let (colour, amount) = match *light {
+                Light::Directional {
+                    direction,
+                    colour,
+                    intensity,
+                } => (colour, -normal.dot(direction.normalise()) * intensity),
+                Light::Point {
+                    position,
+                    colour,
+                    intensity,
+                    range,
+                } => {
+                    let to_light = position - centre;
+                    let distance = to_light.length();
+                    let falloff = (1.0 - distance / range).max(0.0);
+                    let facing = normal.dot(to_light / distance.max(f64::EPSILON));
+                    (colour, facing * falloff * intensity)
+                }
+            };
+            lit = lit + colour * self.band(amount);
+        }
+        let shadow = self.shadow.clamp(0.0, 1.0);
+        let raise = |channel: f64| shadow + (1.0 - shadow) * channel.min(1.0);
+        Vec3::new(raise(lit.x), raise(lit.y), raise(lit.z))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::vec::vec3::{ORIGIN, Y_AXIS};
+
+    #[test]
+    fn bands() {
+        let toon = Toon::default();
+        assert_eq!(toon.band(0.0), 0.0);
+        assert_eq!(toon.band(0.3), 0.0);
+        assert_eq!(toon.band(0.5), 0.5);
+        assert_eq!(toon.band(0.9), 0.5);
+        assert_eq!(toon.band(1.0), 1.0);
+        assert_eq!(toon.band(-1.0), 0.0);
+        assert_eq!(toon.with_bands(1).band(0.7), 0.0);
+    }
+
+    #[test]
+    fn shading() {
+        let toon = Toon::default().with_shadow(0.2);
+        let sun = Light::Directional {
+            direction: -Y_AXIS,
+            colour: Vec3::new(1.0, 1.0, 1.0),
+            intensity: 1.0,
+        };
+
+        // Faces turned to the light are fully lit, side-on ones in the middle band and turned away ones in shadow
+        let close = |a: Vec3, b: Vec3| (a - b).length() < 1e-9;
+        let shade = |normal: Vec3| toon.shade(&[sun], ORIGIN, normal);
+        assert!(close(shade(Y_AXIS), Vec3::new(1.0, 1.0, 1.0)));
+        assert!(close(
+            shade(Vec3::new(1.0, 1.0, 0.0)),
+            Vec3::new(0.6, 0.6, 0.6)
+        ));
+        assert!(close(shade(-Y_AXIS), Vec3::new(0.2, 0.2, 0.2)));
+        assert!(close(
+            toon.shade(&[], ORIGIN, Y_AXIS),
+            Vec3::new(0.2, 0.2, 0.2)
+        ));
+
+        // Point lights fade out by their range, and colour what they reach
+        let lamp = Light::Point {
+            position: Vec3::new(0.0, 2.0, 0.0),
+            colour: Vec3::new(1.0, 0.0, 0.0),
+            intensity: 1.0,
+            range: 10.0,
+        };
+        let near = toon.shade(&[lamp], ORIGIN, Y_AXIS);
+        assert!(close(near, Vec3::new(0.6, 0.2, 0.2)));
+        let far = toon.shade(&[lamp], Vec3::new(0.0, -20.0, 0.0), Y_AXIS);
+        assert!(close(far, Vec3::new(0.2, 0.2, 0.2)));
+    }
+}
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{mat4::Mat4, quat::Quat, vec::vec3::Vec3};
+
+///
+/// A transformation split into its translation, rotation and scale components. Unlike a `Mat4`, these can be
+/// interpolated independently, which is what bone poses and keyframes need.
+///
+/// The components are applied in the order scale, rotation, translation.
+///
+#[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Transform {
+    pub fn new(translation: Vec3, rotation: Quat, scale: Vec3) -> Transform {
+        Transform {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    ///
+    /// A transform which leaves vectors unchanged.
+    ///
+    pub fn identity() -> Transform {
+        Transform::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Quat::identity(),
+            Vec3::new(1.0, 1.0, 1.0),
+        )
+    }
+
+    pub fn from_translation(translation: Vec3) -> Transform {
+        Transform {
+            translation,
+            ..Transform::identity()
+        }
+    }
+
+    ///
+    /// Splits a matrix made up of a scale, rotation and translation back into its components.
+    /// Matrices with shearing can't be represented exactly, so the result is an approximation.
+    ///
+    pub fn from_mat4(mat: Mat4) -> Transform {
+        let m = mat.m;
+        let translation = Vec3::new(m[0][3], m[1][3], m[2][3]);
+        let column = |i: usize| Vec3::new(m[0][i], m[1][i], m[2][i]);
+        let scale = Vec3::new(column(0).length(), column(1).length(), column(2).length());
+
+        let mut rotation = Mat4::identity();
+        for (row, source) in rotation.m.iter_mut().zip(m).take(3) {
+            row[0] = source[0] / scale.x;
+            row[1] = source[1] / scale.y;
+            row[2] = source[2] / scale.z;
+        }
+
+        Transform::new(translation, Quat::from_mat4(rotation), scale)
+    }
+
+    ///
+    /// Interpolates each component between two transforms, where `t` is between 0 and 1.
+    ///
+    pub fn lerp(self, to: Transform, t: f64) -> Transform {
+        Transform {
+            translation: self.translation + (to.translation - self.translation) * t,
+            rotation: self.rotation.slerp(to.rotation, t),
+            scale: self.scale + (to.scale - self.scale) * t,
+        }
+    }
+
+    ///
+    /// Computes the matrix equivalent of this transform.
+    ///
+    pub fn to_mat4(self) -> Mat4 {
+        self.rotation
+            .to_mat4()
+            .mult(Mat4::identity().scale(self.scale))
+            .translate(self.translation)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::f64::consts::PI;
+
+    use super::*;
+
+    #[test]
+    fn transform_to_matrix() {
+        let transform = Transform::new(
+            Vec3::new(1.0, 0.0, 0.0),
+            Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), PI / 2.0),
+            Vec3::new(2.0, 2.0, 2.0),
+        );
+        let result = transform.to_mat4().transform(Vec3::new(1.0, 0.0, 0.0));
+
+        assert!((result - Vec3::new(1.0, 2.0, 0.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn matrix_round_trip() {
+        let transform = Transform::new(
+            Vec3::new(1.0, -2.0, 3.0),
+            Quat::from_axis_angle(Vec3::new(1.0, 1.0, 0.0), 0.8),
+            Vec3::new(2.0, 1.0, 0.5),
+        );
+        let result = Transform::from_mat4(transform.to_mat4());
+
+        assert!((result.translation - transform.translation).length() < 1e-9);
+        assert!((result.scale - transform.scale).length() < 1e-9);
+        assert!((result.rotation.dot(transform.rotation).abs() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn lerp_translation() {
+        let a = Transform::identity();
+        let b = Transform::from_translation(Vec3::new(2.0, 0.0, 0.0));
+
+        assert_eq!(a.lerp(b, 0.5).translation, Vec3::new(1.0, 0.0, 0.0));
+    }
+}
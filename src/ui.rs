@@ -0,0 +1,333 @@
+use std::ops::RangeInclusive;
+
+use minifb::MouseButton;
+
+use crate::{framebuffer::Colour, input::Input, renderer::Renderer, vec::vec2::Vec2};
+
+// Colours of the panel and its widgets
+const BACKGROUND: Colour = Colour::from_hex(0x101010);
+const OUTLINE: Colour = Colour::from_hex(0x505050);
+const HOVERED: Colour = Colour::from_hex(0xa0a0a0);
+const ACCENT: Colour = Colour::from_hex(0x3080e0);
+
+// Space around the panel's edge and between widgets, in pixels
+const PADDING: f64 = 4.0;
+
+///
+/// A small immediate mode UI of labels, buttons, checkboxes and sliders, for tweaking settings while an app runs.
+///
+/// Widgets aren't kept anywhere - they're laid out top to bottom in a panel each frame by `Ui::panel`, and the ones
+/// that have been used say so when they're drawn, so the UI always shows the values it's given. The mouse is read from
+/// `Input` with `read_input` in `App::update`, and the panel is drawn in `App::draw_overlay`:
+///
+/// ```text
+/// fn update(&mut self, world: &mut World, camera: &mut Camera, input: &mut Input, delta: f64) {
+///     self.ui.read_input(input);
+///     world.environment.fog = self.fog.then_some(self.fog_settings);
+/// }
+///
+/// fn draw_overlay(&mut self, renderer: &mut Renderer, stats: &FrameStats) {
+///     self.ui.panel(renderer, |panel| {
+///         panel.checkbox("fog", &mut self.fog);
+///         panel.slider("fog end", &mut self.fog_settings.end, 10.0..=100.0);
+///     });
+/// }
+/// ```
+///
+/// - `position` is the top left corner of the panel, in pixels
+/// - `width` is how wide the panel is, with every widget stretched across it
+/// - `text_size` is the size of the text, which sets how tall each widget is
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ui {
+    pub position: Vec2,
+    pub width: f64,
+    pub text_size: f32,
+    // The mouse, as of the last update
+    mouse: Option<Vec2>,
+    down: bool,
+    // Set by a click in any update since the panel was last drawn, so clicks aren't handled twice or missed
+    clicked: bool,
+    // The slider being dragged, counting widgets from the top of the panel
+    dragging: Option<usize>,
+    // How tall the panel was last frame, for drawing its background before its widgets
+    height: f64,
+}
+
+impl Ui {
+    pub fn new(position: Vec2, width: f64) -> Ui {
+        Ui {
+            position,
+            width,
+            text_size: 14.0,
+            mouse: None,
+            down: false,
+            clicked: false,
+            dragging: None,
+            height: 0.0,
+        }
+    }
+
+    ///
+    /// Reads the mouse for the next time the panel is drawn. This should be called from `App::update` before anything
+    /// else uses the mouse, so clicks on the panel can be ignored with `wants_mouse`.
+    ///
+    pub fn read_input(&mut self, input: &Input) {
+        self.mouse = input.mouse_position();
+        self.down = input.is_mouse_down(MouseButton::Left);
+        self.clicked |= input.is_mouse_pressed(MouseButton::Left);
+    }
+
+    ///
+    /// Returns whether the mouse is over the panel or dragging one of its sliders, so clicks there shouldn't also be
+    /// handled by the app.
+    ///
+    pub fn wants_mouse(&self) -> bool {
+        let over = self.mouse.is_some_and(|mouse| {
+            contains(self.position, Vec2::new(self.width, self.height), mouse)
+        });
+        over || self.dragging.is_some()
+    }
+
+    ///
+    /// Draws the panel, with the widgets added by `widgets` from the top down.
+    ///
+    pub fn panel(&mut self, renderer: &mut Renderer, widgets: impl FnOnce(&mut Panel)) {
+        renderer.fill_rect(
+            self.position,
+            Vec2::new(self.width, self.height),
+            BACKGROUND,
+        );
+        if !self.down {
+            self.dragging = None;
+        }
+
+        let mut panel = Panel {
+            renderer,
+            cursor: self.position + Vec2::new(PADDING, PADDING),
+            width: self.width - PADDING * 2.0,
+            index: 0,
+            ui: self,
+        };
+        widgets(&mut panel);
+        let bottom = panel.cursor.y;
+
+        self.height = bottom - self.position.y;
+        self.clicked = false;
+    }
+}
+
+///
+/// The panel being drawn by `Ui::panel`, which widgets are added to.
+///
+pub struct Panel<'a> {
+    ui: &'a mut Ui,
+    renderer: &'a mut Renderer,
+    // The top left of the next widget
+    cursor: Vec2,
+    width: f64,
+    index: usize,
+}
+
+impl Panel<'_> {
+    pub fn label(&mut self, text: &str) {
+        let size = self.row();
+        self.write(text, self.cursor);
+        self.next(size);
+    }
+
+    ///
+    /// Draws a button, returning true if it was clicked.
+    ///
+    pub fn button(&mut self, text: &str) -> bool {
+        let size = self.row();
+        let hovered = self.hovered(self.cursor, size);
+        let colour = if hovered { HOVERED } else { OUTLINE };
+        self.renderer.draw_rect(self.cursor, size, colour);
+        let text_x = (size.x - self.renderer.text_width(text, self.ui.text_size)) / 2.0;
+        self.write(
+            text,
+            self.cursor + Vec2::new(text_x.max(PADDING), PADDING / 2.0),
+        );
+        self.next(size);
+        hovered && self.ui.clicked
+    }
+
+    ///
+    /// Draws a box that's ticked while `value` is true, and flips it when clicked. Returns true if it was flipped.
+    ///
+    pub fn checkbox(&mut self, text: &str, value: &mut bool) -> bool {
+        let size = self.row();
+        let clicked = self.hovered(self.cursor, size) && self.ui.clicked;
+        if clicked {
+            *value = !*value;
+        }
+
+        let side = size.y;
+        let colour = if self.hovered(self.cursor, size) {
+            HOVERED
+        } else {
+            OUTLINE
+        };
+        self.renderer
+            .draw_rect(self.cursor, Vec2::new(side, side), colour);
+        if *value {
+            let inset = Vec2::new(3.0, 3.0);
+            self.renderer.fill_rect(
+                self.cursor + inset,
+                Vec2::new(side, side) - inset * 2.0,
+                ACCENT,
+            );
+        }
+        self.write(text, self.cursor + Vec2::new(side + PADDING, PADDING / 2.0));
+        self.next(size);
+        clicked
+    }
+
+    ///
+    /// Draws a slider for picking a value in `range`, labelled with its name and current value. Clicking on it or
+    /// dragging along it sets the value, and returns true while it's changing.
+    ///
+    pub fn slider(&mut self, text: &str, value: &mut f64, range: RangeInclusive<f64>) -> bool {
+        let index = self.index;
+        self.label(&format!("{}: {:.2}", text, value));
+
+        let size = self.row();
+        let (min, max) = (*range.start(), *range.end());
+        if self.hovered(self.cursor, size) && self.ui.clicked {
+            self.ui.dragging = Some(index);
+        }
+        let mut changed = false;
+        if let (Some(mouse), Some(dragged)) = (self.ui.mouse, self.ui.dragging) {
+            if dragged == index {
+                let t = ((mouse.x - self.cursor.x) / size.x).clamp(0.0, 1.0);
+                let dragged_to = min + (max - min) * t;
+                changed = dragged_to != *value;
+                *value = dragged_to;
+            }
+        }
+
+        let middle = self.cursor.y + size.y / 2.0;
+        let track = Vec2::new(self.cursor.x, middle - 1.0);
+        self.renderer
+            .fill_rect(track, Vec2::new(size.x, 2.0), OUTLINE);
+        let t = if max > min {
+            ((*value - min) / (max - min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let handle = Vec2::new(self.cursor.x + (size.x - PADDING) * t, self.cursor.y);
+        let colour = if self.ui.dragging == Some(index) {
+            ACCENT
+        } else {
+            HOVERED
+        };
+        self.renderer
+            .fill_rect(handle, Vec2::new(PADDING, size.y), colour);
+        self.next(size);
+        changed
+    }
+
+    ///
+    /// Returns the size of the next widget, which is as wide as the panel and tall enough for a line of text.
+    ///
+    fn row(&self) -> Vec2 {
+        Vec2::new(self.width, self.ui.text_size as f64 + PADDING)
+    }
+
+    fn hovered(&self, pos: Vec2, size: Vec2) -> bool {
+        self.ui
+            .mouse
+            .is_some_and(|mouse| contains(pos, size, mouse))
+    }
+
+    fn write(&mut self, text: &str, pos: Vec2) {
+        self.renderer.write_text(text, pos, self.ui.text_size);
+    }
+
+    ///
+    /// Moves down past a widget to where the next one goes.
+    ///
+    fn next(&mut self, size: Vec2) {
+        self.cursor.y += size.y + PADDING;
+        self.index += 1;
+    }
+}
+
+fn contains(pos: Vec2, size: Vec2, point: Vec2) -> bool {
+    point.x >= pos.x && point.x < pos.x + size.x && point.y >= pos.y && point.y < pos.y + size.y
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{config::RendererConfig, input::InputFrame};
+
+    fn click(input: &mut Input, position: Vec2, down: bool) {
+        input.update(InputFrame {
+            mouse: Some(position),
+            buttons: if down {
+                vec![MouseButton::Left]
+            } else {
+                vec![]
+            },
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn widgets() {
+        let mut renderer = Renderer::new(RendererConfig::new(200, 200));
+        let mut input = Input::default();
+        let mut ui = Ui::new(Vec2::new(10.0, 10.0), 120.0);
+        let (mut enabled, mut value, mut pressed) = (false, 0.0, false);
+        let mut draw = |ui: &mut Ui, enabled: &mut bool, value: &mut f64| {
+            ui.panel(&mut renderer, |panel| {
+                panel.label("settings");
+                pressed = panel.button("reset");
+                panel.checkbox("enabled", enabled);
+                panel.slider("value", value, 0.0..=10.0);
+            });
+            pressed
+        };
+
+        // Each widget is a row of text, and the slider has its label above it, so the panel is laid out as
+        // label, button, checkbox, slider label and slider, each 22 pixels apart
+        let row = |index: f64| Vec2::new(20.0, 16.0 + 22.0 * index);
+        assert!(!draw(&mut ui, &mut enabled, &mut value));
+        assert!(!ui.wants_mouse());
+
+        click(&mut input, row(1.0), true);
+        ui.read_input(&input);
+        assert!(ui.wants_mouse());
+        assert!(draw(&mut ui, &mut enabled, &mut value));
+        // The click is only handled by the frame that was drawn after it
+        assert!(!draw(&mut ui, &mut enabled, &mut value));
+        input.clear_edges();
+
+        click(&mut input, row(2.0), false);
+        click(&mut input, row(2.0), true);
+        ui.read_input(&input);
+        draw(&mut ui, &mut enabled, &mut value);
+        assert!(enabled);
+        input.clear_edges();
+
+        // Sliders follow the mouse while it's held down, even once it's left them
+        let track = row(4.0);
+        click(&mut input, track, false);
+        click(&mut input, track, true);
+        ui.read_input(&input);
+        draw(&mut ui, &mut enabled, &mut value);
+        input.clear_edges();
+        click(&mut input, Vec2::new(500.0, 300.0), true);
+        ui.read_input(&input);
+        draw(&mut ui, &mut enabled, &mut value);
+        assert_eq!(value, 10.0);
+        assert!(ui.wants_mouse());
+
+        click(&mut input, Vec2::new(500.0, 300.0), false);
+        ui.read_input(&input);
+        draw(&mut ui, &mut enabled, &mut value);
+        assert!(!ui.wants_mouse());
+    }
+}
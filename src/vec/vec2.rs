@@ -1,8 +1,9 @@
-use std::ops::{Add, Div, Mul, Sub};
+use core::ops::{Add, Div, Mul, Sub};
+use serde::{Deserialize, Serialize};
 
 use crate::vec::vec3::Vec3;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Default, PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub struct Vec2 {
     pub x: f64,
     pub y: f64,
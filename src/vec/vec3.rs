@@ -1,9 +1,16 @@
-use std::{
+use core::{
     fmt::Display,
     iter::Sum,
-    ops::{Add, Div, Mul},
+    ops::{Add, Div, Mul, Neg, Sub},
 };
+use serde::{Deserialize, Serialize};
 
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use crate::float::Float;
+
+// Only used by the parts of the crate which need `std`
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
 pub const ORIGIN: Vec3 = Vec3 {
     x: 0.0,
     y: 0.0,
@@ -19,8 +26,13 @@ pub const Y_AXIS: Vec3 = Vec3 {
     y: 1.0,
     z: 0.0,
 };
+pub const Z_AXIS: Vec3 = Vec3 {
+    x: 0.0,
+    y: 0.0,
+    z: 1.0,
+};
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, Default, PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub struct Vec3 {
     pub x: f64,
     pub y: f64,
@@ -47,6 +59,10 @@ impl Vec3 {
     pub fn length(&self) -> f64 {
         (self.x.powf(2.0) + self.y.powf(2.0) + self.z.powf(2.0)).sqrt()
     }
+
+    pub fn dot(self, vec: Vec3) -> f64 {
+        self.x * vec.x + self.y * vec.y + self.z * vec.z
+    }
 }
 
 impl Add for Vec3 {
@@ -61,6 +77,30 @@ impl Add for Vec3 {
     }
 }
 
+impl Sub for Vec3 {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self::Output {
+        Self {
+            x: self.x - other.x,
+            y: self.y - other.y,
+            z: self.z - other.z,
+        }
+    }
+}
+
+impl Neg for Vec3 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
 impl Mul<f64> for Vec3 {
     type Output = Self;
 
@@ -103,7 +143,7 @@ impl Sum for Vec3 {
 }
 
 impl Display for Vec3 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "[{:.2}, {:.2}, {:.2}]", self.x, self.y, self.z)
     }
 }
@@ -120,6 +160,19 @@ mod test {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn subtract_vector() {
+        let expected = Vec3::new(1.0, 2.0, 3.0);
+        let result = Vec3::new(2.0, 4.0, 6.0) - Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn dot_product() {
+        let result = Vec3::new(1.0, 2.0, 3.0).dot(Vec3::new(4.0, 5.0, 6.0));
+        assert_eq!(32.0, result);
+    }
+
     #[test]
     fn cross_product() {
         let expected = Vec3::new(-3.0, 6.0, -3.0);
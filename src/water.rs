@@ -0,0 +1,334 @@
+use serde::{Deserialize, Serialize};
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    collider::Collider,
+    framebuffer::Colour,
+    mat4::Mat4,
+    object::{Face, Object, Texture},
+    vec::{vec2::Vec2, vec3::Vec3},
+};
+
+// How many points along each side of a collider's bounds are tested against the surface to work out how much of it is
+// underwater
+const SUBMERGED_SAMPLES: usize = 6;
+
+///
+/// A single sine wave travelling across a water surface.
+/// - `direction` is the direction the wave travels in, on the XZ plane
+/// - `amplitude` is the height of the wave's peaks above the rest level
+/// - `wavelength` is the distance between peaks
+/// - `speed` is how far the peaks move per second
+///
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Wave {
+    pub direction: Vec2,
+    pub amplitude: f64,
+    pub wavelength: f64,
+    pub speed: f64,
+}
+
+impl Wave {
+    ///
+    /// Computes the wave's phase at a point, and how quickly it changes along X and Z.
+    ///
+    fn phase(&self, x: f64, z: f64, time: f64) -> (f64, f64, f64) {
+        let length = (self.direction.x.powi(2) + self.direction.y.powi(2)).sqrt();
+        let (dx, dz) = if length > 0.0 {
+            (self.direction.x / length, self.direction.y / length)
+        } else {
+            (1.0, 0.0)
+        };
+        let k = 2.0 * PI / self.wavelength;
+        (k * (dx * x + dz * z - self.speed * time), k * dx, k * dz)
+    }
+}
+
+///
+/// A flat, square grid of water, whose surface is moved by a sum of sine waves.
+/// - `size` is the length of each side, centred on the water's position
+/// - `resolution` is the number of quads along each side - more quads give smoother waves
+/// - `colour` is the water's colour, mixed with whatever is behind it according to `opacity`
+/// - `reflectivity` is how strongly the water reflects the scene above it, between 0 and 1
+///
+/// Water is also a volume of fluid, which holds up rigidbodies floating in it:
+/// - `depth` is how far below the rest level the water reaches
+/// - `density` is the mass of each unit of volume. Bodies lighter than the water they'd fill float, and heavier ones
+///   sink
+/// - `linear_drag` and `angular_drag` slow down bodies moving and spinning underwater, in the same way as a body's
+///   own damping, scaled by how much of the body is submerged
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Water {
+    pub size: f64,
+    pub resolution: usize,
+    pub waves: Vec<Wave>,
+    pub colour: Colour,
+    pub opacity: f64,
+    pub reflectivity: f64,
+    #[serde(default = "default_depth")]
+    pub depth: f64,
+    #[serde(default = "default_density")]
+    pub density: f64,
+    #[serde(default = "default_linear_drag")]
+    pub linear_drag: f64,
+    #[serde(default = "default_angular_drag")]
+    pub angular_drag: f64,
+}
+
+fn default_depth() -> f64 {
+    10.0
+}
+
+fn default_density() -> f64 {
+    1.0
+}
+
+fn default_linear_drag() -> f64 {
+    2.0
+}
+
+fn default_angular_drag() -> f64 {
+    2.0
+}
+
+impl Water {
+    pub fn new(size: f64, resolution: usize) -> Water {
+        Water {
+            size,
+            resolution: resolution.max(1),
+            waves: vec![],
+            colour: Colour::from_hex(0x1d5a8a),
+            opacity: 0.7,
+            reflectivity: 0.3,
+            depth: default_depth(),
+            density: default_density(),
+            linear_drag: default_linear_drag(),
+            angular_drag: default_angular_drag(),
+        }
+    }
+
+    ///
+    /// Returns the height of the surface above its rest level at a point in the water's local space.
+    ///
+    pub fn height(&self, x: f64, z: f64, time: f64) -> f64 {
+        self.waves
+            .iter()
+            .map(|wave| wave.phase(x, z, time).0.sin() * wave.amplitude)
+            .sum()
+    }
+
+    ///
+    /// Returns the surface normal at a point in the water's local space.
+    ///
+    pub fn normal(&self, x: f64, z: f64, time: f64) -> Vec3 {
+        let (slope_x, slope_z) = self.waves.iter().fold((0.0, 0.0), |(sx, sz), wave| {
+            let (phase, kx, kz) = wave.phase(x, z, time);
+            let slope = phase.cos() * wave.amplitude;
+            (sx + slope * kx, sz + slope * kz)
+        });
+        Vec3::new(-slope_x, 1.0, -slope_z).normalise()
+    }
+
+    ///
+    /// The furthest the surface can move from its rest level.
+    ///
+    pub fn max_height(&self) -> f64 {
+        self.waves.iter().map(|wave| wave.amplitude.abs()).sum()
+    }
+
+    ///
+    /// Works out how much of a world space collider is underwater, for water placed by `transformation` at a given
+    /// time. Returns the submerged volume and the centre of the submerged part, which is where the water pushes up,
+    /// or `None` if none of it is underwater. Planes go on forever, so they're never counted as submerged.
+    ///
+    /// The collider's bounds are split into a grid of small cells, and each cell inside the collider counts as
+    /// submerged by however much of its height is below the surface above it. This is only approximate, but it
+    /// changes smoothly as a body bobs up and down, and follows the waves.
+    ///
+    pub fn submerged(
+        &self,
+        collider: &Collider,
+        transformation: Mat4,
+        time: f64,
+    ) -> Option<(f64, Vec3)> {
+        let bounds = collider.bounds()?;
+        let volume = collider.volume()?;
+        let inverse = transformation.inverse()?;
+        let cell = bounds.size() / SUBMERGED_SAMPLES as f64;
+        let half = self.size / 2.0;
+
+        // Most bodies are nowhere near the water, so there's no need to test them any further
+        let local = bounds.transform(inverse);
+        let outside =
+            local.min.x > half || local.max.x < -half || local.min.z > half || local.max.z < -half;
+        if outside || local.min.y > self.max_height() || local.max.y < -self.depth {
+            return None;
+        }
+
+        let (mut inside, mut submerged) = (0.0, 0.0);
+        let mut centre = Vec3::default();
+        for i in 0..SUBMERGED_SAMPLES.pow(3) {
+            let steps = [
+                i % SUBMERGED_SAMPLES,
+                i / SUBMERGED_SAMPLES % SUBMERGED_SAMPLES,
+                i / SUBMERGED_SAMPLES / SUBMERGED_SAMPLES,
+            ]
+            .map(|step| step as f64 + 0.5);
+            let point =
+                bounds.min + Vec3::new(cell.x * steps[0], cell.y * steps[1], cell.z * steps[2]);
+            if !collider.contains(point) {
+                continue;
+            }
+            inside += 1.0;
+
+            let local = inverse.transform(point);
+            if local.x.abs() > half || local.z.abs() > half || local.y < -self.depth {
+                continue;
+            }
+            let surface = self.height(local.x, local.z, time);
+            let amount = ((surface - local.y) / cell.y.max(f64::EPSILON) + 0.5).clamp(0.0, 1.0);
+            submerged += amount;
+            centre = centre + point * amount;
+        }
+
+        if submerged <= 0.0 {
+            return None;
+        }
+        Some((volume * submerged / inside, centre / submerged))
+    }
+
+    ///
+    /// Generates the surface at rest, as a flat grid in the water's local space.
+    ///
+    pub fn mesh(&self) -> Object {
+        let count = self.resolution + 1;
+        let half = self.size / 2.0;
+
+        let mut vertices = vec![];
+        let mut tex_coords = vec![];
+        for row in 0..count {
+            for col in 0..count {
+                let (u, v) = (
+                    col as f64 / self.resolution as f64,
+                    row as f64 / self.resolution as f64,
+                );
+                vertices.push(Vec3::new(u * self.size - half, 0.0, v * self.size - half));
+                tex_coords.push(Vec2::new(u, v));
+            }
+        }
+        let normals = vec![Vec3::new(0.0, 1.0, 0.0); vertices.len()];
+
+        let mut faces = vec![];
+        let index = |col: usize, row: usize| row * count + col;
+        for row in 0..self.resolution {
+            for col in 0..self.resolution {
+                let (a, b, c, d) = (
+                    index(col, row),
+                    index(col, row + 1),
+                    index(col + 1, row + 1),
+                    index(col + 1, row),
+                );
+                for vertices in [(a, b, c), (a, c, d)] {
+                    faces.push(Face {
+                        vertices,
+                        tex_coords: vertices,
+                        normals: vertices,
+                        material: 0,
+                    });
+                }
+            }
+        }
+
+        let texture = Texture::solid(self.colour);
+        Object::new(vertices, tex_coords, normals, faces, texture)
+    }
+
+    ///
+    /// Moves the vertices of a mesh made by `mesh` to the wave heights at a given time, and updates its normals.
+    ///
+    pub fn displace(&self, obj: &mut Object, time: f64) {
+        let vertices = Arc::make_mut(&mut obj.vertices);
+        let normals = Arc::make_mut(&mut obj.normals);
+        for (vertex, normal) in vertices.iter_mut().zip(normals.iter_mut()) {
+            vertex.y = self.height(vertex.x, vertex.z, time);
+            *normal = self.normal(vertex.x, vertex.z, time);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::geometry::aabb::Aabb;
+
+    fn swell() -> Water {
+        let mut water = Water::new(10.0, 4);
+        water.waves.push(Wave {
+            direction: Vec2::new(1.0, 0.0),
+            amplitude: 0.5,
+            wavelength: 4.0,
+            speed: 1.0,
+        });
+        water
+    }
+
+    #[test]
+    fn waves_travel() {
+        let water = swell();
+        // A quarter of a wavelength along is the first peak, which moves along at the wave's speed
+        assert!((water.height(1.0, 0.0, 0.0) - 0.5).abs() < 1e-9);
+        assert!((water.height(3.0, 7.0, 2.0) - 0.5).abs() < 1e-9);
+        assert!((water.normal(1.0, 0.0, 0.0) - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn submerged_volume() {
+        let water = Water::new(10.0, 4);
+        let at_rest = Mat4::identity();
+        let cube = |height: f64| {
+            Collider::Box(Aabb::new(
+                Vec3::new(-1.0, height - 1.0, -1.0),
+                Vec3::new(1.0, height + 1.0, 1.0),
+            ))
+        };
+
+        // Half under the surface, the bottom half is submerged
+        let (volume, centre) = water.submerged(&cube(0.0), at_rest, 0.0).unwrap();
+        assert!((volume - 4.0).abs() < 1e-9, "{}", volume);
+        assert!(
+            (centre - Vec3::new(0.0, -0.5, 0.0)).length() < 1e-9,
+            "{}",
+            centre
+        );
+
+        // All the way under, or out of the water entirely
+        let (volume, _) = water.submerged(&cube(-3.0), at_rest, 0.0).unwrap();
+        assert!((volume - 8.0).abs() < 1e-9);
+        assert_eq!(water.submerged(&cube(3.0), at_rest, 0.0), None);
+        assert_eq!(water.submerged(&cube(-12.0), at_rest, 0.0), None);
+
+        // The surface is wherever the water is placed, and waves lift it up
+        let lowered = Mat4::identity().translate(Vec3::new(0.0, -1.0, 0.0));
+        let volume = water
+            .submerged(&cube(0.0), lowered, 0.0)
+            .map_or(0.0, |(volume, _)| volume);
+        assert!(volume < 0.01, "{}", volume);
+        let (volume, _) = swell().submerged(&cube(0.0), lowered, 0.0).unwrap();
+        assert!(volume > 0.1, "{}", volume);
+    }
+
+    #[test]
+    fn displace_mesh() {
+        let water = swell();
+        let mut mesh = water.mesh();
+        assert_eq!(mesh.vertices.len(), 25);
+        assert_eq!(mesh.faces.len(), 32);
+        assert!(mesh.validate().is_ok());
+
+        water.displace(&mut mesh, 0.0);
+        for vertex in mesh.vertices.iter() {
+            assert_eq!(vertex.y, water.height(vertex.x, vertex.z, 0.0));
+        }
+    }
+}
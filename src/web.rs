@@ -0,0 +1,123 @@
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen::{closure::Closure, Clamped, JsCast, JsValue};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData};
+
+use crate::{error::Error, framebuffer::Colour, present::PresentTarget};
+
+type FrameCallback = Closure<dyn FnMut(f64)>;
+
+///
+/// An HTML canvas that frames are shown in when running in the browser, in place of the window opened by `run`.
+///
+/// The canvas is resized to the renderer's image, so it should be scaled up with CSS, using `image-rendering:
+/// pixelated` to keep pixels sharp. Browsers have no files or threads, so models should be loaded from bytes (e.g.
+/// with `Object::from_obj_bytes` and `include_bytes!`). `std::time::Instant` panics on the web as well, which rules out
+/// `Headless::profile_frame` and `FramePacing` - `animate` passes each frame the browser's own timestamp instead.
+///
+pub struct Canvas {
+    canvas: HtmlCanvasElement,
+    context: CanvasRenderingContext2d,
+    // The last frame as RGBA bytes, kept between frames to save reallocating it
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(canvas: HtmlCanvasElement) -> Result<Canvas, JsValue> {
+        let context = canvas
+            .get_context("2d")?
+            .ok_or("the canvas has no 2D context")?
+            .dyn_into::<CanvasRenderingContext2d>()?;
+        Ok(Canvas {
+            canvas,
+            context,
+            pixels: vec![],
+        })
+    }
+
+    ///
+    /// Finds the canvas on the page with the given `id`.
+    ///
+    pub fn from_id(id: &str) -> Result<Canvas, JsValue> {
+        let canvas = web_sys::window()
+            .and_then(|window| window.document())
+            .ok_or("there is no document to find the canvas in")?
+            .get_element_by_id(id)
+            .ok_or_else(|| format!("there is no element with id {}", id))?
+            .dyn_into::<HtmlCanvasElement>()?;
+        Canvas::new(canvas)
+    }
+}
+
+impl PresentTarget for Canvas {
+    ///
+    /// Copies a frame onto the canvas, resizing the canvas to fit it.
+    ///
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize) -> Result<(), Error> {
+        let (width, height) = (width as u32, height as u32);
+        if self.canvas.width() != width || self.canvas.height() != height {
+            self.canvas.set_width(width);
+            self.canvas.set_height(height);
+        }
+        to_rgba(buffer, &mut self.pixels);
+        let image =
+            ImageData::new_with_u8_clamped_array_and_sh(Clamped(&self.pixels), width, height)
+                .map_err(js_error)?;
+        self.context
+            .put_image_data(&image, 0.0, 0.0)
+            .map_err(js_error)?;
+        Ok(())
+    }
+}
+
+///
+/// Calls `frame` every time the browser draws the page, with the time in milliseconds since the page loaded, until it
+/// returns false. This is how frames are paced on the web, as the browser can't be blocked waiting for the next one.
+///
+pub fn animate(mut frame: impl FnMut(f64) -> bool + 'static) {
+    // The callback has to request itself for the next frame, so it keeps a handle to itself
+    let callback: Rc<RefCell<Option<FrameCallback>>> = Rc::new(RefCell::new(None));
+    let next = callback.clone();
+    *callback.borrow_mut() = Some(Closure::new(move |time: f64| {
+        if frame(time) {
+            request_frame(next.borrow().as_ref());
+        } else {
+            next.borrow_mut().take();
+        }
+    }));
+    request_frame(callback.borrow().as_ref());
+}
+
+fn request_frame(callback: Option<&FrameCallback>) {
+    if let (Some(window), Some(callback)) = (web_sys::window(), callback) {
+        let _ = window.request_animation_frame(callback.as_ref().unchecked_ref());
+    }
+}
+
+fn js_error(error: JsValue) -> Error {
+    Error::render(format!("{:?}", error))
+}
+
+///
+/// Converts the renderer's 0RGB pixels into the RGBA bytes a canvas expects.
+///
+fn to_rgba(buffer: &[u32], pixels: &mut Vec<u8>) {
+    pixels.clear();
+    pixels.extend(
+        buffer
+            .iter()
+            .flat_map(|pixel| Colour::from(*pixel).to_rgba8()),
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pixels_to_rgba() {
+        let mut pixels = vec![1, 2, 3];
+        to_rgba(&[0x00112233, 0x00ffffff], &mut pixels);
+        assert_eq!(pixels, [0x11, 0x22, 0x33, 0xff, 0xff, 0xff, 0xff, 0xff]);
+    }
+}
@@ -1,53 +1,4222 @@
-use crate::{mat4::Mat4, object::Object, vec::vec3::Vec3};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::{Deref, DerefMut},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    animation::{Animation, AnimationClip, ClipPlayer},
+    assets::AssetHandle,
+    behaviour::Behaviour,
+    bvh::Bvh,
+    cloth::Cloth,
+    collider::{Collider, Contact},
+    component::Components,
+    debug::{DebugDraw, DebugLine},
+    decal::Decal,
+    deform::VertexModifier,
+    environment::Environment,
+    framebuffer::Colour,
+    geometry::{
+        aabb::Aabb,
+        frustum::{Frustum, Plane},
+        ray::Ray,
+    },
+    grid::Grid,
+    joint::{Joint, JointKind},
+    light::Light,
+    lod::Lod,
+    mat4::Mat4,
+    object::{ModelLoadError, Object, Texture},
+    physics::{self, Physics},
+    prefab::Prefab,
+    projectile::Projectile,
+    quat::Quat,
+    renderer::TextureMapping,
+    rigidbody::Rigidbody,
+    scatter::Scatter,
+    shadow::BlobShadow,
+    sprite::Sprite,
+    terrain::Terrain,
+    toon::Toon,
+    transform::Transform,
+    vec::vec3::{Vec3, X_AXIS, Y_AXIS, Z_AXIS},
+    water::Water,
+};
+
+/**
+The size of the cells static entities are sorted into, which works best at around the size of a typical object.
+*/
+const STATIC_CELL_SIZE: f64 = 16.0;
+
+// How long normals are drawn by `debug_normals`, and in what colour
+const DEBUG_NORMAL_LENGTH: f64 = 0.25;
+const DEBUG_NORMAL_COLOUR: Colour = Colour::from_hex(0xffff00);
+
+// The colours `debug_physics` draws colliders in, depending on what the body is doing
+const DEBUG_AWAKE_COLOUR: Colour = Colour::from_hex(0x00ff00);
+const DEBUG_ASLEEP_COLOUR: Colour = Colour::from_hex(0x4060ff);
+const DEBUG_FIXED_COLOUR: Colour = Colour::from_hex(0x808080);
+const DEBUG_TRIGGER_COLOUR: Colour = Colour::from_hex(0xff8000);
+
+// How `debug_physics` draws contacts, and how long it draws velocities for each unit of speed
+const DEBUG_CONTACT_SIZE: f64 = 0.1;
+const DEBUG_CONTACT_COLOUR: Colour = Colour::from_hex(0xff0000);
+const DEBUG_CONTACT_NORMAL_COLOUR: Colour = Colour::from_hex(0xff00ff);
+const DEBUG_VELOCITY_SCALE: f64 = 0.1;
+const DEBUG_VELOCITY_COLOUR: Colour = Colour::from_hex(0x00ffff);
+
+/**
+A stable handle to an entity in a `World`.
+Handles stay valid until the entity is removed - after that, lookups with the old handle return `None`,
+even if its slot is reused by a new entity.
+*/
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct EntityId {
+    index: usize,
+    generation: u32,
+}
+
+#[cfg(feature = "ffi")]
+impl EntityId {
+    /**
+    Splits the handle into its slot and how many times that slot had been reused, for passing it outside of Rust.
+    */
+    pub(crate) fn to_parts(self) -> (usize, u32) {
+        (self.index, self.generation)
+    }
+
+    pub(crate) fn from_parts(index: usize, generation: u32) -> EntityId {
+        EntityId { index, generation }
+    }
+}
+
+/**
+Storage for a single entity. `object` is `None` while the slot is free, or while its object is still loading.
+`bounds` and `faces` cache the object's local space bounds and a hierarchy over its faces, so they don't need
+recalculating every time it moves. Static entities are kept in the world's grid rather than its bounding volume
+hierarchy.
+*/
+struct Slot {
+    generation: u32,
+    object: Option<Object>,
+    bounds: Option<Aabb>,
+    faces: Bvh<usize>,
+    alive: bool,
+    is_static: bool,
+    name: Option<String>,
+    tags: Vec<String>,
+    layer: Layer,
+    draw_order: i32,
+    visible: bool,
+    opacity: f64,
+    texture_mapping: Option<TextureMapping>,
+}
+
+impl Slot {
+    fn set_object(&mut self, obj: Object) -> Option<Object> {
+        self.bounds = obj.local_bounds();
+        self.faces = obj.face_bvh();
+        self.object.replace(obj)
+    }
+
+    /**
+    Sets the object along with caches worked out ahead of time, e.g. by a `Prefab`.
+    */
+    fn set_object_cached(&mut self, obj: Object, bounds: Option<Aabb>, faces: Bvh<usize>) {
+        self.bounds = bounds;
+        self.faces = faces;
+        self.object = Some(obj);
+    }
+
+    fn world_bounds(&self) -> Option<Aabb> {
+        let obj = self.object.as_ref()?;
+        Some(self.bounds?.transform(obj.transformation))
+    }
+}
+
+/**
+The behaviours attached to an entity, run in the order they were added.
+*/
+#[derive(Default)]
+struct Behaviours(Vec<Box<dyn Behaviour>>);
+
+/**
+The vertex modifier drawn with an entity, and how far it can move a vertex, which its bounds have been grown by.
+*/
+struct Modifier {
+    modifier: Box<dyn VertexModifier>,
+    reach: f64,
+}
+
+/**
+A mutable borrow of an entity's object, returned by `World::get_mut`. When it's dropped, the entity's cached bounds
+and face hierarchy are rebuilt from the object, so edits to its mesh are seen by culling, collisions and raycasts.
+*/
+pub struct ObjectMut<'a> {
+    world: &'a mut World,
+    id: EntityId,
+}
+
+impl Deref for ObjectMut<'_> {
+    type Target = Object;
+
+    fn deref(&self) -> &Object {
+        // Guards are only handed out for entities with an object, and hold the world until they're dropped
+        self.world.slots[self.id.index].object.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for ObjectMut<'_> {
+    fn deref_mut(&mut self) -> &mut Object {
+        self.world.slots[self.id.index].object.as_mut().unwrap()
+    }
+}
+
+impl Drop for ObjectMut<'_> {
+    fn drop(&mut self) {
+        self.world.refresh_object(self.id);
+    }
+}
+
+/**
+An entity's blob shadow, and the decal it's drawn with, which is moved under the entity every update. Shadows with
+nothing under them, or too far above it, aren't drawn.
+*/
+struct Shadow {
+    settings: BlobShadow,
+    decal: Decal,
+    placed: bool,
+}
+
+/**
+An entity's toon shading, and the single colour texture its outline is drawn with.
+*/
+struct ToonShading {
+    toon: Toon,
+    outline: Texture,
+}
+
+/**
+Marks an entity whose triangles rigidbodies collide with.
+*/
+struct CollisionMesh;
+
+/**
+Where a rigidbody's object was before the last fixed step, for blending towards where it is now when rendering.
+*/
+struct PreviousTransformation(Mat4);
+
+/**
+Where an entity's object was at the end of the update before the last, for blending towards where it is now by the
+world's update alpha when rendering.
+*/
+struct PreviousUpdate(Mat4);
+
+/**
+How long a rigidbody has been almost still for, and whether it's been put to sleep.
+*/
+#[derive(Default)]
+struct Sleep {
+    still_time: f64,
+    asleep: bool,
+}
+
+/**
+How much longer a projectile has before it's removed from the world.
+*/
+struct Lifetime(f64);
+
+/**
+The joints attached to an entity's rigidbody.
+*/
+struct Joints(Vec<Joint>);
+
+/**
+What `resolve_contact` needs to know about each body in a contact.
+*/
+#[derive(Copy, Clone)]
+struct ContactBody {
+    inverse_mass: f64,
+    inverse_inertia: Mat4,
+    centre: Vec3,
+    velocity: Vec3,
+    angular_velocity: Vec3,
+    restitution: f64,
+    static_friction: f64,
+    dynamic_friction: f64,
+}
+
+impl ContactBody {
+    /**
+    A body which can't be moved or spun at all, like the world itself.
+    */
+    fn fixed(centre: Vec3) -> ContactBody {
+        ContactBody {
+            inverse_mass: 0.0,
+            inverse_inertia: Mat4::identity().scale(Vec3::default()),
+            centre,
+            velocity: Vec3::default(),
+            angular_velocity: Vec3::default(),
+            restitution: 0.0,
+            static_friction: 0.0,
+            dynamic_friction: 0.0,
+        }
+    }
+
+    /**
+    Returns how fast a point on the body is moving, including its spin.
+    */
+    fn point_velocity(&self, point: Vec3) -> Vec3 {
+        self.velocity + self.angular_velocity.cross_product(point - self.centre)
+    }
+
+    /**
+    Returns how much a point on the body speeds up along a direction when pushed along it, which is higher the
+    easier the body is to move and to spin around that point.
+    */
+    fn inverse_mass_along(&self, point: Vec3, direction: Vec3) -> f64 {
+        let arm = point - self.centre;
+        let spin = self
+            .inverse_inertia
+            .transform_direction(arm.cross_product(direction));
+        self.inverse_mass + spin.cross_product(arm).dot(direction)
+    }
+
+    /**
+    Applies an impulse to the body at a point, changing both how fast it moves and how fast it spins.
+    */
+    fn push(&mut self, point: Vec3, impulse: Vec3) {
+        let arm = point - self.centre;
+        self.velocity = self.velocity + impulse * self.inverse_mass;
+        self.angular_velocity = self.angular_velocity
+            + self
+                .inverse_inertia
+                .transform_direction(arm.cross_product(impulse));
+    }
+
+    /**
+    Returns how much the body's spin around an axis speeds up when twisted around it.
+    */
+    fn inverse_inertia_along(&self, axis: Vec3) -> f64 {
+        self.inverse_inertia.transform_direction(axis).dot(axis)
+    }
+
+    /**
+    Applies an angular impulse to the body, changing only how fast it spins.
+    */
+    fn twist(&mut self, impulse: Vec3) {
+        self.angular_velocity =
+            self.angular_velocity + self.inverse_inertia.transform_direction(impulse);
+    }
+}
+
+/**
+The animations attached to an entity. `base` is the object's transformation before any animation was applied,
+captured the first time the animations are evaluated.
+The clip's transform is applied on top of the channels.
+*/
+#[derive(Default)]
+struct Animated {
+    animation: Option<Animation>,
+    clip: Option<ClipPlayer>,
+    base: Option<Mat4>,
+}
+
+/**
+Which pass an entity is drawn in. Layers are drawn in the order they're declared, and each layer other than
+`Background` clears the depth buffer first, so it always appears in front of the layers before it.
+- `Background` is for skyboxes and distant scenery, which everything else should cover
+- `World` is the default, for everything in the scene itself
+- `Foreground` is for things that mustn't clip into the world, like a first-person weapon
+- `Overlay` is for markers and gizmos drawn over everything
+*/
+#[derive(
+    Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Layer {
+    Background,
+    #[default]
+    World,
+    Foreground,
+    Overlay,
+}
+
+impl Layer {
+    pub fn clears_depth(self) -> bool {
+        self != Layer::Background
+    }
+}
+
+/**
+Where a ray cast with `World::raycast` hit an object.
+- `face` is the index of the face that was hit in the object's `faces`
+- `normal` is the face's normal in world space, pointing out of the side its vertices wind anticlockwise around
+- `distance` is how far along the ray the hit was
+*/
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RayHit {
+    pub entity: EntityId,
+    pub face: usize,
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub distance: f64,
+}
+
+/**
+Where a ray or a swept sphere hits a rigidbody's collider.
+- `point` is where the collider was hit, in world space
+- `normal` is the collider's surface normal there
+- `distance` is how far along the ray the hit was. For sphere casts, it's how far the sphere's centre travelled
+*/
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BodyHit {
+    pub entity: EntityId,
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub distance: f64,
+}
+
+/**
+Something that happened between two rigidbodies during a physics step. `Begin` is sent on the first step two
+entities touch, with the first contact found between them, and `End` on the first step they've stopped touching.
+Bodies landing on collision meshes are reported with the mesh's entity as `b`. Bodies going into and out of a
+trigger volume send `Enter` and `Exit` instead.
+*/
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CollisionEvent {
+    Begin {
+        a: EntityId,
+        b: EntityId,
+        contact: Contact,
+    },
+    End {
+        a: EntityId,
+        b: EntityId,
+    },
+    Enter {
+        trigger: EntityId,
+        other: EntityId,
+    },
+    Exit {
+        trigger: EntityId,
+        other: EntityId,
+    },
+}
+
+type CollisionCallback = Box<dyn FnMut(&mut World, &CollisionEvent) + Send>;
+
+/**
+A world's terrain, along with the entity drawing each of its chunks and the level of detail it was meshed at.
+Chunks are listed row by row along the X axis.
+*/
+struct TerrainChunks {
+    terrain: Terrain,
+    chunks: Vec<(EntityId, usize)>,
+}
 
 pub struct World {
-    pub objects: Vec<Object>,
+    slots: Vec<Slot>,
+    free: Vec<usize>,
     pub time: f64,
+    update_alpha: f64,
+    pub lights: Vec<Light>,
+    pub scatters: Vec<Scatter>,
+    pub decals: Vec<Decal>,
+    pub environment: Environment,
+    pub physics: Physics,
+    touching: HashSet<(EntityId, EntityId)>,
+    overlapping: HashSet<(EntityId, EntityId)>,
+    collision_events: Vec<CollisionEvent>,
+    collision_callbacks: Vec<CollisionCallback>,
+    last_contacts: Vec<Contact>,
+    pending: Vec<(AssetHandle, Mat4, EntityId)>,
+    bvh: Bvh<EntityId>,
+    bvh_stale: bool,
+    statics: Grid<EntityId>,
+    terrain: Option<TerrainChunks>,
+    debug: DebugDraw,
+    components: Components,
 }
 
 impl World {
     pub fn new() -> World {
         World {
-            objects: vec![],
+            slots: vec![],
+            free: vec![],
             time: 0.0,
+            update_alpha: 1.0,
+            lights: vec![],
+            scatters: vec![],
+            decals: vec![],
+            environment: Environment::default(),
+            physics: Physics::default(),
+            touching: HashSet::new(),
+            overlapping: HashSet::new(),
+            collision_events: vec![],
+            collision_callbacks: vec![],
+            last_contacts: vec![],
+            pending: vec![],
+            bvh: Bvh::default(),
+            bvh_stale: false,
+            statics: Grid::new(STATIC_CELL_SIZE),
+            terrain: None,
+            debug: DebugDraw::new(),
+            components: Components::new(),
         }
     }
 
     /**
-    Adds an object to the world at a given location
+    Adds an object to the world at a given location, returning a handle to it
     */
-    pub fn add_object(&mut self, mut obj: Object, pos: Vec3) {
-        obj.transform(Mat4::identity().translate(pos));
-        self.objects.push(obj);
+    pub fn add_object(&mut self, obj: Object, pos: Vec3) -> EntityId {
+        self.place_object(obj, Mat4::identity().translate(pos))
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    /**
+    As with `add_object`, but also gives the entity a name and a set of tags, for finding it again with
+    `find_by_name` and `iter_tagged`.
+    */
+    pub fn add_named_object(
+        &mut self,
+        obj: Object,
+        pos: Vec3,
+        name: Option<&str>,
+        tags: &[&str],
+    ) -> EntityId {
+        let id = self.add_object(obj, pos);
+        if let Some(name) = name {
+            self.set_name(id, name);
+        }
+        for tag in tags {
+            self.add_tag(id, tag);
+        }
+        id
+    }
 
-    use crate::{
-        mat4::Mat4,
-        object::{Object, Texture},
-        vec::vec3::ORIGIN,
-    };
+    /**
+    Adds an object to the world with a given transformation on top of its own, returning a handle to it
+    */
+    pub fn place_object(&mut self, mut obj: Object, transformation: Mat4) -> EntityId {
+        obj.transformation = transformation.mult(obj.transformation);
+        let id = self.allocate();
+        self.slots[id.index].set_object(obj);
+        self.bvh_stale = true;
+        id
+    }
 
-    #[test]
-    fn add_object() {
-        let mut world = World::new();
-        let object = Object {
-            vertices: vec![],
-            tex_coords: vec![],
-            normals: vec![],
-            faces: vec![],
-            texture: Texture {
-                width: 0,
-                height: 0,
-                pixels: vec![],
-            },
-            transformation: Mat4::identity(),
+    /**
+    Spawns a new entity from a prefab at a given location, returning a handle to it.
+    */
+    pub fn spawn(&mut self, prefab: &Prefab, pos: Vec3) -> EntityId {
+        self.spawn_placed(prefab, Mat4::identity().translate(pos))
+    }
+
+    /**
+    As with `spawn`, but places the entity with a given transformation, e.g. to point a projectile the way it was
+    fired. The prefab's offset is applied first. Spawned entities share the prefab's mesh and texture until one of
+    them is edited.
+    */
+    pub fn spawn_placed(&mut self, prefab: &Prefab, transformation: Mat4) -> EntityId {
+        let mut obj = prefab.object().clone();
+        obj.transformation = transformation.mult(prefab.offset).mult(obj.transformation);
+
+        let id = self.allocate();
+        self.slots[id.index].set_object_cached(obj, prefab.bounds(), prefab.faces().clone());
+        self.bvh_stale = true;
+
+        if let Some(name) = &prefab.name {
+            self.set_name(id, name);
+        }
+        for tag in &prefab.tags {
+            self.add_tag(id, tag);
+        }
+        self.set_layer(id, prefab.layer);
+        self.set_static(id, prefab.is_static);
+        prefab.attach_components(self, id);
+        id
+    }
+
+    /**
+    Reserves a slot for a new entity, reusing a removed entity's slot if possible
+    */
+    fn allocate(&mut self) -> EntityId {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    object: None,
+                    bounds: None,
+                    faces: Bvh::default(),
+                    alive: false,
+                    is_static: false,
+                    name: None,
+                    tags: vec![],
+                    layer: Layer::World,
+                    draw_order: 0,
+                    visible: true,
+                    opacity: 1.0,
+                    texture_mapping: None,
+                });
+                self.slots.len() - 1
+            }
         };
-        world.add_object(object, ORIGIN);
-        assert_eq!(world.objects.len(), 1);
+
+        let slot = &mut self.slots[index];
+        slot.alive = true;
+        EntityId {
+            index,
+            generation: slot.generation,
+        }
+    }
+
+    fn slot(&self, id: EntityId) -> Option<&Slot> {
+        self.slots
+            .get(id.index)
+            .filter(|slot| slot.alive && slot.generation == id.generation)
+    }
+
+    fn slot_mut(&mut self, id: EntityId) -> Option<&mut Slot> {
+        self.slots
+            .get_mut(id.index)
+            .filter(|slot| slot.alive && slot.generation == id.generation)
+    }
+
+    /**
+    Removes an entity from the world, returning its object if it had finished loading
+    */
+    pub fn remove(&mut self, id: EntityId) -> Option<Object> {
+        let slot = self.slot_mut(id)?;
+        let obj = slot.object.take();
+        slot.bounds = None;
+        slot.faces = Bvh::default();
+        slot.is_static = false;
+        slot.name = None;
+        slot.tags.clear();
+        slot.layer = Layer::World;
+        slot.draw_order = 0;
+        slot.visible = true;
+        slot.opacity = 1.0;
+        slot.texture_mapping = None;
+        slot.alive = false;
+        slot.generation = slot.generation.wrapping_add(1);
+
+        self.components.clear(id.index);
+        self.free.push(id.index);
+        self.pending.retain(|(_, _, pending_id)| *pending_id != id);
+        self.statics.remove(id);
+        self.bvh_stale = true;
+        obj
+    }
+
+    /**
+    Swaps an entity's object for another, returning the previous one.
+    The new object's transformation is used as is.
+    */
+    pub fn replace(&mut self, id: EntityId, obj: Object) -> Option<Object> {
+        let previous = self.slot_mut(id)?.set_object(obj);
+        self.reindex_static(id);
+        self.bvh_stale = true;
+        previous
+    }
+
+    /**
+    Returns true if the entity hasn't been removed
+    */
+    pub fn contains(&self, id: EntityId) -> bool {
+        self.slot(id).is_some()
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&Object> {
+        self.slot(id)?.object.as_ref()
+    }
+
+    /**
+    Borrows an entity's object to edit it. Its cached bounds and face hierarchy are rebuilt when the returned guard is
+    dropped, which costs about as much as adding the object again, so use `set_base_transform` to simply move it.
+    */
+    pub fn get_mut(&mut self, id: EntityId) -> Option<ObjectMut<'_>> {
+        self.get(id)?;
+        Some(ObjectMut { world: self, id })
+    }
+
+    /**
+    Borrows an entity's object without refreshing its caches, for changes which only move it.
+    */
+    fn object_mut(&mut self, id: EntityId) -> Option<&mut Object> {
+        self.slot_mut(id)?.object.as_mut()
+    }
+
+    /**
+    Rebuilds an entity's cached bounds and face hierarchy after its object has been edited. The bounds are grown to
+    fit anything which moves its vertices after they're cached, like waves or a vertex modifier.
+    */
+    fn refresh_object(&mut self, id: EntityId) {
+        let Some(obj) = self.get(id) else {
+            return;
+        };
+        let (bounds, faces) = (obj.local_bounds(), obj.face_bvh());
+        let bounds = if let Some(sprite) = self.component::<Sprite>(id) {
+            let radius = sprite.size.dot(sprite.size).sqrt() / 2.0;
+            let corner = Vec3::new(radius, radius, radius);
+            Some(Aabb::new(-corner, corner))
+        } else {
+            let waves = self.component::<Water>(id).map_or(0.0, Water::max_height);
+            let reach = self
+                .component::<Modifier>(id)
+                .map_or(0.0, |modifier| modifier.reach);
+            bounds.map(|bounds| bounds.inflate(waves + reach))
+        };
+
+        if let Some(slot) = self.slot_mut(id) {
+            slot.bounds = bounds;
+            slot.faces = faces;
+        }
+        self.reindex_static(id);
+        self.bvh_stale = true;
+    }
+
+    /**
+    Iterates over every entity which has an object, along with its handle
+    */
+    pub fn iter(&self) -> impl Iterator<Item = (EntityId, &Object)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let obj = slot.object.as_ref().filter(|_| slot.alive)?;
+            let id = EntityId {
+                index,
+                generation: slot.generation,
+            };
+            Some((id, obj))
+        })
+    }
+
+    /**
+    Iterates over every entity's object mutably. Unlike `get_mut`, this doesn't rebuild the entities' cached bounds,
+    so it's for changes which don't touch their meshes.
+    */
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (EntityId, &mut Object)> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                let id = EntityId {
+                    index,
+                    generation: slot.generation,
+                };
+                let obj = slot.object.as_mut().filter(|_| slot.alive)?;
+                Some((id, obj))
+            })
+    }
+
+    /**
+    Iterates over every object in the world
+    */
+    pub fn objects(&self) -> impl Iterator<Item = &Object> {
+        self.iter().map(|(_, obj)| obj)
+    }
+
+    /**
+    Returns the number of entities in the world, including any still loading
+    */
+    pub fn len(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.alive).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /**
+    Adds an object that is still loading to the world at a given location. The returned handle can be used straight
+    away, but `get` returns `None` and the entity is simply not drawn until the object resolves.
+    */
+    pub fn add_object_async(&mut self, handle: AssetHandle, pos: Vec3) -> EntityId {
+        self.place_object_async(handle, Mat4::identity().translate(pos))
+    }
+
+    /**
+    As with `add_object_async`, but places the object with a given transformation.
+    */
+    pub fn place_object_async(&mut self, handle: AssetHandle, transformation: Mat4) -> EntityId {
+        let id = self.allocate();
+        self.pending.push((handle, transformation, id));
+        id
+    }
+
+    /**
+    Fills in any entities whose objects have finished loading.
+    Entities whose loads failed are removed, and the errors are returned along with the name of the asset.
+    */
+    pub fn poll_assets(&mut self) -> Vec<(String, ModelLoadError)> {
+        let mut errors = vec![];
+        let mut still_pending = vec![];
+
+        for (handle, transformation, id) in std::mem::take(&mut self.pending) {
+            match handle.take() {
+                Some(Ok(mut obj)) => {
+                    obj.transformation = transformation.mult(obj.transformation);
+                    if let Some(slot) = self.slot_mut(id) {
+                        slot.set_object(obj);
+                        self.reindex_static(id);
+                        self.bvh_stale = true;
+                    }
+                }
+                Some(Err(error)) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(asset = handle.name(), %error, "failed to load asset");
+                    self.remove(id);
+                    errors.push((String::from(handle.name()), error));
+                }
+                None => still_pending.push((handle, transformation, id)),
+            }
+        }
+
+        self.pending = still_pending;
+        errors
+    }
+
+    /**
+    Swaps the geometry and texture of every object loaded from `reloaded.source` for the reloaded version,
+    keeping each object's transformation.
+    */
+    pub fn reload(&mut self, reloaded: &Object) {
+        let ids: Vec<EntityId> = self
+            .iter()
+            .filter(|(_, obj)| obj.source.is_some() && obj.source == reloaded.source)
+            .map(|(id, _)| id)
+            .collect();
+
+        for id in ids {
+            if let Some(slot) = self.slot_mut(id) {
+                let mut obj = reloaded.clone();
+                if let Some(old) = &slot.object {
+                    obj.transformation = old.transformation;
+                }
+                slot.set_object(obj);
+            }
+            self.reindex_static(id);
+            self.bvh_stale = true;
+        }
+    }
+
+    /**
+    Advances the world's time by `delta` seconds, runs every entity's behaviours, evaluates the animation of every
+    animated entity, moves the waves of any water, and brings the bounding volume hierarchy up to date. Debug lines
+    from the previous frame are cleared. Where each entity was beforehand is kept, so `render_transformation` can
+    blend between the last two updates.
+    */
+    pub fn update(&mut self, delta: f64) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("update").entered();
+        let placed: Vec<(EntityId, Mat4)> = self
+            .iter_slots()
+            .filter_map(|(id, slot)| Some((id, slot.object.as_ref()?.transformation)))
+            .collect();
+        for (id, transformation) in placed {
+            self.insert_component(id, PreviousUpdate(transformation));
+        }
+
+        self.time += delta;
+        self.debug.clear();
+        self.run_behaviours(delta);
+        let mut moved_statics = vec![];
+
+        for (index, water) in self.components.iter::<Water>() {
+            if let Some(obj) = &mut self.slots[index].object {
+                water.displace(obj, self.time);
+            }
+        }
+
+        for (index, animated) in self.components.iter_mut::<Animated>() {
+            let slot = &mut self.slots[index];
+            let Some(obj) = &mut slot.object else {
+                continue;
+            };
+            let base = *animated.base.get_or_insert(obj.transformation);
+            let mut transformation = base;
+
+            if let Some(player) = &mut animated.clip {
+                player.advance(delta);
+                transformation = transformation.mult(player.sample().to_mat4());
+            }
+            if let Some(animation) = &animated.animation {
+                transformation = transformation.mult(animation.evaluate(self.time));
+            }
+
+            obj.transformation = transformation;
+            if slot.is_static {
+                moved_statics.push(EntityId {
+                    index,
+                    generation: slot.generation,
+                });
+            }
+        }
+
+        // Static entities aren't expected to move, but animating one shouldn't leave it stuck in the wrong cell
+        for id in moved_statics {
+            self.reindex_static(id);
+        }
+        self.update_bvh();
+        self.place_blob_shadows();
+    }
+
+    // Finds the ground under each entity with a blob shadow and moves its shadow there
+    fn place_blob_shadows(&mut self) {
+        let ids: Vec<EntityId> = self.query::<Shadow>().map(|(id, _)| id).collect();
+        for id in ids {
+            let ground = self
+                .bounds(id)
+                .filter(|_| self.is_visible(id))
+                .and_then(|bounds| {
+                    let max_height = self.component::<Shadow>(id)?.settings.max_height;
+                    // Start just below the entity, so the ray can't hit the entity itself
+                    let ray = Ray {
+                        origin: Vec3::new(
+                            bounds.centre().x,
+                            bounds.min.y - 1e-6,
+                            bounds.centre().z,
+                        ),
+                        direction: -Y_AXIS,
+                    };
+                    Some((bounds, self.raycast_within(&ray, max_height)?))
+                });
+            let Some(shadow) = self.component_mut::<Shadow>(id) else {
+                continue;
+            };
+            shadow.placed = ground.is_some_and(|(bounds, hit)| {
+                shadow
+                    .settings
+                    .place(&mut shadow.decal, bounds, hit.point, hit.normal)
+            });
+        }
+    }
+
+    fn run_behaviours(&mut self, delta: f64) {
+        let ids: Vec<EntityId> = self.query::<Behaviours>().map(|(id, _)| id).collect();
+        for id in ids {
+            // Behaviours are taken out of the world while they run, so they can be given all of it
+            let Some(mut behaviours) = self.remove_component::<Behaviours>(id) else {
+                continue;
+            };
+            for behaviour in &mut behaviours.0 {
+                behaviour.update(id, self, self.time, delta);
+            }
+
+            // Put them back unless the entity was removed, keeping any added while they ran
+            if let Some(added) = self.remove_component::<Behaviours>(id) {
+                behaviours.0.extend(added.0);
+            }
+            self.insert_component(id, behaviours);
+        }
+    }
+
+    /**
+    Advances every entity with a `Rigidbody` component by `dt`, applying gravity and any forces on the bodies, and
+    moves the entities to match. Static entities are left where they are, and sleeping bodies aren't simulated until
+    they're disturbed.
+    */
+    pub fn step_physics(&mut self, dt: f64) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("physics_step", dt).entered();
+        self.expire_projectiles(dt);
+        self.orient_bodies();
+        self.apply_buoyancy(dt);
+        self.integrate_bodies(dt);
+        let contacts = self.resolve_collisions(dt);
+        self.solve_joints();
+        self.update_sleep(dt, &contacts);
+        self.step_cloth(dt);
+        self.update_bvh();
+        self.last_contacts = contacts.iter().map(|(_, _, contact)| *contact).collect();
+        self.record_contacts(contacts);
+    }
+
+    /**
+    Lines each body's orientation up with the rotation of its entity's transformation, so bodies which were placed or
+    turned by hand spin around the right axes.
+    */
+    fn orient_bodies(&mut self) {
+        for (index, body) in self.components.iter_mut::<Rigidbody>() {
+            if let Some(obj) = &self.slots[index].object {
+                body.set_orientation(Transform::from_mat4(obj.transformation).rotation);
+            }
+        }
+    }
+
+    /**
+    Counts down the lifetimes of projectiles by `dt`, removing those which have run out.
+    */
+    fn expire_projectiles(&mut self, dt: f64) {
+        let mut expired = vec![];
+        for (index, lifetime) in self.components.iter_mut::<Lifetime>() {
+            lifetime.0 -= dt;
+            if lifetime.0 <= 0.0 {
+                expired.push(EntityId {
+                    index,
+                    generation: self.slots[index].generation,
+                });
+            }
+        }
+        for id in expired {
+            self.remove(id);
+        }
+    }
+
+    /**
+    Holds up bodies floating in water, and slows them down with the water's drag. Each body is pushed up by the weight
+    of the water it displaces, through the centre of its submerged part, so bodies turn to float upright and bob up
+    and down with the waves.
+    */
+    fn apply_buoyancy(&mut self, dt: f64) {
+        let waters: Vec<(Water, Mat4)> = self
+            .query::<Water>()
+            .filter_map(|(id, water)| Some((water.clone(), self.get(id)?.transformation)))
+            .collect();
+        if waters.is_empty() {
+            return;
+        }
+        let bodies: Vec<(EntityId, Collider)> = self
+            .query::<Rigidbody>()
+            .filter(|(id, body)| !self.is_static(*id) && !body.trigger && body.inverse_mass() > 0.0)
+            .filter_map(|(id, body)| {
+                Some((
+                    id,
+                    body.collider
+                        .as_ref()?
+                        .transformed(self.get(id)?.transformation),
+                ))
+            })
+            .collect();
+
+        let gravity = self.physics.gravity;
+        for (id, collider) in bodies {
+            let (Some(total), Some(centre_of_mass)) = (collider.volume(), self.centre_of_mass(id))
+            else {
+                continue;
+            };
+            for (water, transformation) in &waters {
+                let Some((volume, centre)) = water.submerged(&collider, *transformation, self.time)
+                else {
+                    continue;
+                };
+                let Some(body) = self.component_mut::<Rigidbody>(id) else {
+                    continue;
+                };
+                let weight = gravity * (body.gravity_scale * water.density * volume);
+                body.apply_force_at_point(-weight, centre - centre_of_mass);
+
+                let fraction = (volume / total).min(1.0);
+                body.velocity = body.velocity * (-water.linear_drag * fraction * dt).exp();
+                body.angular_velocity =
+                    body.angular_velocity * (-water.angular_drag * fraction * dt).exp();
+            }
+        }
+    }
+
+    /**
+    Moves every cloth in the world by `dt`, colliding it with the solid rigidbodies, and updates their meshes to
+    match. Cloth is pushed around by bodies but doesn't push back.
+    */
+    fn step_cloth(&mut self, dt: f64) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("cloth").entered();
+        if self.components.iter::<Cloth>().next().is_none() {
+            return;
+        }
+        let colliders: Vec<Collider> = self
+            .query::<Rigidbody>()
+            .filter(|(_, body)| !body.trigger)
+            .filter_map(|(id, body)| {
+                Some(
+                    body.collider
+                        .as_ref()?
+                        .transformed(self.get(id)?.transformation),
+                )
+            })
+            .collect();
+
+        let gravity = self.physics.gravity;
+        for (index, cloth) in self.components.iter_mut::<Cloth>() {
+            cloth.step(gravity, &colliders, dt);
+            let slot = &mut self.slots[index];
+            if let Some(obj) = &mut slot.object {
+                cloth.update_mesh(obj);
+                slot.bounds = obj.local_bounds();
+                slot.faces = obj.face_bvh();
+            }
+        }
+    }
+
+    /**
+    Runs the simulation at the fixed rate set by `physics.timestep`, taking as many steps as fit in the time elapsed
+    and saving the rest for later. Since this rarely lines up exactly with the frame, `render_transformation` blends
+    bodies between their last two steps by `physics.alpha()`.
+    */
+    pub fn advance_physics(&mut self, elapsed: f64) {
+        let steps = self.physics.take_steps(elapsed);
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("physics", steps).entered();
+        for _ in 0..steps {
+            let ids: Vec<EntityId> = self.query::<Rigidbody>().map(|(id, _)| id).collect();
+            for id in ids {
+                if let Some(obj) = self.get(id) {
+                    let previous = PreviousTransformation(obj.transformation);
+                    self.insert_component(id, previous);
+                }
+            }
+            self.step_physics(self.physics.timestep);
+        }
+    }
+
+    /**
+    Returns the transformation to draw an entity with. Bodies run by `advance_physics` are placed part of the way
+    from where they were at the previous step to where they are now, so they move smoothly between steps. Everything
+    else is placed part of the way between where it was at the last two calls to `update`, by `update_alpha`.
+    */
+    pub fn render_transformation(&self, id: EntityId) -> Option<Mat4> {
+        let current = self.get(id)?.transformation;
+        let (previous, alpha) = if let Some(PreviousTransformation(previous)) = self.component(id) {
+            (*previous, self.physics.alpha())
+        } else if let Some(PreviousUpdate(previous)) = self.component(id) {
+            (*previous, self.update_alpha)
+        } else {
+            return Some(current);
+        };
+        // Entities which haven't moved are drawn exactly as they are, rather than taken apart and put back together
+        if previous == current || alpha >= 1.0 {
+            return Some(current);
+        }
+        let blended = Transform::from_mat4(previous).lerp(Transform::from_mat4(current), alpha);
+        Some(blended.to_mat4())
+    }
+
+    /**
+    Sets how far the frame being drawn falls between the last update and the next, from 0 to 1, which
+    `render_transformation` blends entities by. `run` sets this every frame. It's 1 until it's set, so worlds updated
+    by hand are drawn as they are.
+    */
+    pub fn set_update_alpha(&mut self, alpha: f64) {
+        self.update_alpha = alpha.clamp(0.0, 1.0);
+    }
+
+    /**
+    Returns how far the frame being drawn falls between the last update and the next.
+    */
+    pub fn update_alpha(&self) -> f64 {
+        self.update_alpha
+    }
+
+    fn integrate_bodies(&mut self, dt: f64) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("integrate").entered();
+        let gravity = self.physics.gravity;
+        let ids: Vec<EntityId> = self.query::<Rigidbody>().map(|(id, _)| id).collect();
+        for id in ids {
+            if self.is_static(id) {
+                continue;
+            }
+            if self.is_asleep(id) {
+                // Anything pushing the body, or changing its velocity, wakes it up
+                let Some(body) = self.component::<Rigidbody>(id) else {
+                    continue;
+                };
+                let zero = Vec3::default();
+                let disturbed = [
+                    body.force(),
+                    body.torque(),
+                    body.velocity,
+                    body.angular_velocity,
+                ]
+                .iter()
+                .any(|value| *value != zero);
+                if !disturbed {
+                    continue;
+                }
+                self.wake(id);
+            }
+            let Some(body) = self.component_mut::<Rigidbody>(id) else {
+                continue;
+            };
+            let (offset, rotation) = body.integrate(gravity, dt);
+            self.move_body(id, offset);
+            if rotation == Quat::identity() {
+                continue;
+            }
+            if let Some(centre) = self.centre_of_mass(id) {
+                let pivot = Mat4::identity().translate(-centre);
+                self.transform_body(id, rotation.to_mat4().mult(pivot).translate(centre));
+            }
+        }
+    }
+
+    /**
+    Finds every contact between bodies and resolves them with `resolve_contact`. Static entities are treated as
+    immovable, whatever their mass.
+    */
+    fn resolve_collisions(&mut self, dt: f64) -> Vec<(EntityId, EntityId, Contact)> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("collisions").entered();
+        let mut contacts = vec![];
+        let mut solid = vec![];
+        let resting_speed = self.physics.gravity.length() * dt * 2.0;
+
+        let bodies: Vec<(EntityId, Collider)> = self
+            .query::<Rigidbody>()
+            .filter_map(|(id, body)| {
+                Some((
+                    id,
+                    body.collider
+                        .as_ref()?
+                        .transformed(self.get(id)?.transformation),
+                ))
+            })
+            .collect();
+
+        let bounds: Vec<Option<Aabb>> = bodies.iter().map(|body| body.1.bounds()).collect();
+        for (i, j) in self.physics.broadphase.pairs(&bounds) {
+            let (a, a_collider) = &bodies[i];
+            let (b, b_collider) = &bodies[j];
+            // Nothing changes between bodies which are both asleep or immovable
+            if self.is_resting(*a) && self.is_resting(*b) {
+                continue;
+            }
+            if let Some(contact) = a_collider.collide(b_collider) {
+                // Triggers are only reported, so bodies pass through them
+                if !self.is_trigger(*a) && !self.is_trigger(*b) {
+                    self.separate(*a, Some(*b), &contact);
+                    solid.push((*a, *b, contact));
+                }
+                contacts.push((*a, *b, contact));
+            }
+        }
+
+        // Each impulse can undo some of the others, like a body on top of a stack pushing the one beneath it back
+        // down, so they're applied a few times over to settle on impulses which work for every contact together
+        for _ in 0..self.physics.contact_iterations.max(1) {
+            for (a, b, contact) in &solid {
+                self.apply_contact_impulse(*a, Some(*b), contact, resting_speed);
+            }
+        }
+
+        self.collide_with_meshes(resting_speed, &mut contacts);
+        contacts
+    }
+
+    /**
+    Pushes moving bodies out of the triangles of any collision meshes they overlap, one triangle at a time.
+    */
+    fn collide_with_meshes(
+        &mut self,
+        resting_speed: f64,
+        contacts: &mut Vec<(EntityId, EntityId, Contact)>,
+    ) {
+        let meshes: Vec<EntityId> = self.query::<CollisionMesh>().map(|(id, _)| id).collect();
+        if meshes.is_empty() {
+            return;
+        }
+        let bodies: Vec<(EntityId, Collider)> = self
+            .query::<Rigidbody>()
+            .filter(|(id, _)| !self.is_resting(*id))
+            .filter(|(_, body)| !body.trigger)
+            .filter_map(|(id, body)| {
+                Some((
+                    id,
+                    body.collider
+                        .as_ref()?
+                        .transformed(self.get(id)?.transformation),
+                ))
+            })
+            .collect();
+
+        for (id, mut collider) in bodies {
+            for mesh in &meshes {
+                let Some(bounds) = collider.bounds() else {
+                    break;
+                };
+                if *mesh == id {
+                    continue;
+                }
+                for [a, b, c] in self.mesh_triangles(*mesh, &bounds) {
+                    let Some(contact) = collider.collide_triangle(a, b, c) else {
+                        continue;
+                    };
+                    let offset = self.resolve_contact(id, None, &contact, resting_speed);
+                    contacts.push((id, *mesh, contact));
+                    collider = collider.transformed(Mat4::identity().translate(offset));
+                }
+            }
+        }
+    }
+
+    /**
+    Returns the world space triangles of an entity's object whose bounds overlap a box, using its face hierarchy.
+    */
+    fn mesh_triangles(&self, id: EntityId, bounds: &Aabb) -> Vec<[Vec3; 3]> {
+        let Some(slot) = self.slot(id) else {
+            return vec![];
+        };
+        let (Some(obj), Some(world_bounds)) = (&slot.object, slot.world_bounds()) else {
+            return vec![];
+        };
+        let Some(inverse) = obj.transformation.inverse() else {
+            return vec![];
+        };
+        if !world_bounds.intersects(bounds) {
+            return vec![];
+        }
+
+        let local_bounds = bounds.transform(inverse);
+        slot.faces
+            .query_aabb(&local_bounds)
+            .into_iter()
+            .map(|face| {
+                let (a, b, c) = obj.faces[face].vertices;
+                [a, b, c].map(|vertex| obj.transformation.transform(obj.vertices[vertex]))
+            })
+            .collect()
+    }
+
+    /**
+    Returns how a body responds to contacts. Static entities and entities without a body can't be moved at all, and
+    level geometry without a body takes its surface properties from whatever hits it.
+    */
+    fn contact_body(&self, id: Option<EntityId>) -> Option<ContactBody> {
+        let id = id?;
+        let body = self.component::<Rigidbody>(id)?;
+        let centre = self.centre_of_mass(id)?;
+        if self.is_static(id) {
+            return Some(ContactBody {
+                restitution: body.restitution,
+                static_friction: body.static_friction,
+                dynamic_friction: body.dynamic_friction,
+                ..ContactBody::fixed(centre)
+            });
+        }
+        Some(ContactBody {
+            inverse_mass: body.inverse_mass(),
+            inverse_inertia: body.inverse_inertia(),
+            centre,
+            velocity: body.velocity,
+            angular_velocity: body.angular_velocity,
+            restitution: body.restitution,
+            static_friction: body.static_friction,
+            dynamic_friction: body.dynamic_friction,
+        })
+    }
+
+    /**
+    Returns the centre of mass of an entity's rigidbody in world space.
+    */
+    pub fn centre_of_mass(&self, id: EntityId) -> Option<Vec3> {
+        let centre = self.component::<Rigidbody>(id)?.centre();
+        Some(self.get(id)?.transformation.transform(centre))
+    }
+
+    /**
+    Resolves a contact between body `a` and body `b`, or the world's static geometry if `b` is `None`.
+
+    The bodies are pushed apart in proportion to how easily each can be moved, correcting most of the overlap each
+    step. Then, if the touching points are moving towards each other, an impulse along the contact normal is applied
+    to both. The impulse is sized to bounce them apart at the speed they met at, scaled by the bouncier body's
+    restitution, so momentum is kept. Bodies meeting more slowly than gravity would accelerate them in a couple of
+    steps don't bounce at all, so they settle rather than jittering.
+
+    Friction then pushes back against the bodies sliding across each other. While the push needed to stop them
+    sliding is within the static friction limit they stick, otherwise they slow down by the dynamic friction.
+
+    Impulses act at the contact point rather than the centres of mass, so contacts off to one side of a body set it
+    spinning, and how hard it is to spin is taken into account when sizing them.
+
+    Returns how far `a` was moved.
+    */
+    fn resolve_contact(
+        &mut self,
+        a: EntityId,
+        b: Option<EntityId>,
+        contact: &Contact,
+        resting_speed: f64,
+    ) -> Vec3 {
+        let offset = self.separate(a, b, contact);
+        self.apply_contact_impulse(a, b, contact, resting_speed);
+        offset
+    }
+
+    /**
+    Pushes the bodies in a contact apart, as described for `resolve_contact`, and returns how far `a` was moved.
+    */
+    fn separate(&mut self, a: EntityId, b: Option<EntityId>, contact: &Contact) -> Vec3 {
+        let Some(a_body) = self.contact_body(Some(a)) else {
+            return Vec3::default();
+        };
+        let b_body = self
+            .contact_body(b)
+            .unwrap_or(ContactBody::fixed(contact.point));
+        let total = a_body.inverse_mass + b_body.inverse_mass;
+        if total == 0.0 {
+            return Vec3::default();
+        }
+
+        let correction = physics::correction(contact.depth) / total;
+        let a_offset = -contact.normal * (correction * a_body.inverse_mass);
+        self.move_body(a, a_offset);
+        if let Some(b) = b {
+            self.move_body(b, contact.normal * (correction * b_body.inverse_mass));
+        }
+        a_offset
+    }
+
+    /**
+    Applies the bounce and friction impulses for a contact, as described for `resolve_contact`.
+    */
+    fn apply_contact_impulse(
+        &mut self,
+        a: EntityId,
+        b: Option<EntityId>,
+        contact: &Contact,
+        resting_speed: f64,
+    ) {
+        let Some(mut a_body) = self.contact_body(Some(a)) else {
+            return;
+        };
+        let mut b_body = self.contact_body(b).unwrap_or(ContactBody {
+            static_friction: a_body.static_friction,
+            dynamic_friction: a_body.dynamic_friction,
+            ..ContactBody::fixed(contact.point)
+        });
+        if a_body.inverse_mass + b_body.inverse_mass == 0.0 {
+            return;
+        }
+
+        // Positive when the bodies are moving apart along the normal
+        let relative = b_body.point_velocity(contact.point) - a_body.point_velocity(contact.point);
+        let separating = relative.dot(contact.normal);
+        if separating >= 0.0 {
+            return;
+        }
+        let restitution = if -separating > resting_speed {
+            a_body.restitution.max(b_body.restitution)
+        } else {
+            0.0
+        };
+        let normal_impulse = -(1.0 + restitution) * separating
+            / (a_body.inverse_mass_along(contact.point, contact.normal)
+                + b_body.inverse_mass_along(contact.point, contact.normal));
+        a_body.push(contact.point, -contact.normal * normal_impulse);
+        b_body.push(contact.point, contact.normal * normal_impulse);
+
+        // Friction works against the sliding left once the bodies have stopped moving into each other
+        let relative = b_body.point_velocity(contact.point) - a_body.point_velocity(contact.point);
+        let sliding = relative - contact.normal * relative.dot(contact.normal);
+        let speed = sliding.length();
+        if speed > f64::EPSILON {
+            let tangent = sliding / speed;
+            let static_friction = (a_body.static_friction * b_body.static_friction).sqrt();
+            let dynamic_friction = (a_body.dynamic_friction * b_body.dynamic_friction).sqrt();
+            let stopping = speed
+                / (a_body.inverse_mass_along(contact.point, tangent)
+                    + b_body.inverse_mass_along(contact.point, tangent));
+            let friction = if stopping <= normal_impulse * static_friction {
+                stopping
+            } else {
+                normal_impulse * dynamic_friction
+            };
+            a_body.push(contact.point, tangent * friction);
+            b_body.push(contact.point, -tangent * friction);
+        }
+
+        self.store_velocities(a, &a_body);
+        if let Some(b) = b {
+            self.store_velocities(b, &b_body);
+        }
+    }
+
+    /**
+    Puts groups of bodies to sleep once they've all been still for long enough, and wakes whole groups when any body
+    in them is moving. Bodies are grouped into islands by the contacts and joints between them, so a stack of boxes
+    sleeps and wakes together, but bodies which can't move, like the ground, don't join the islands they touch.
+    */
+    fn update_sleep(&mut self, dt: f64, contacts: &[(EntityId, EntityId, Contact)]) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("sleep").entered();
+        let bodies: Vec<EntityId> = self
+            .query::<Rigidbody>()
+            .filter(|(id, body)| body.inverse_mass() > 0.0 && !body.trigger && !self.is_static(*id))
+            .map(|(id, _)| id)
+            .collect();
+        let indices: HashMap<EntityId, usize> =
+            bodies.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+        // Union-find over the bodies, joining the two sides of every contact and joint
+        let mut parents: Vec<usize> = (0..bodies.len()).collect();
+        fn root(parents: &mut [usize], mut i: usize) -> usize {
+            while parents[i] != i {
+                parents[i] = parents[parents[i]];
+                i = parents[i];
+            }
+            i
+        }
+        let joints = self.query::<Joints>().flat_map(|(id, joints)| {
+            joints
+                .0
+                .iter()
+                .filter_map(move |joint| Some((id, joint.other?)))
+        });
+        let links: Vec<(EntityId, EntityId)> = contacts
+            .iter()
+            .map(|(a, b, _)| (*a, *b))
+            .chain(joints)
+            .collect();
+        for (a, b) in links {
+            if let (Some(&a), Some(&b)) = (indices.get(&a), indices.get(&b)) {
+                let (a, b) = (root(&mut parents, a), root(&mut parents, b));
+                parents[a] = b;
+            }
+        }
+
+        let (speed, time) = (self.physics.sleep_speed, self.physics.sleep_time);
+        let mut restless = HashSet::new();
+        for (i, id) in bodies.iter().enumerate() {
+            let Some(body) = self.component::<Rigidbody>(*id) else {
+                continue;
+            };
+            let still = body.velocity.length() < speed && body.angular_velocity.length() < speed;
+            if !self.has_component::<Sleep>(*id) {
+                self.insert_component(*id, Sleep::default());
+            }
+            let Some(sleep) = self.component_mut::<Sleep>(*id) else {
+                continue;
+            };
+            if !sleep.asleep {
+                sleep.still_time = if still { sleep.still_time + dt } else { 0.0 };
+            }
+            if !sleep.asleep && sleep.still_time < time {
+                restless.insert(root(&mut parents, i));
+            }
+        }
+
+        for (i, id) in bodies.iter().enumerate() {
+            if restless.contains(&root(&mut parents, i)) {
+                if self.is_asleep(*id) {
+                    self.wake(*id);
+                }
+                continue;
+            }
+            if let Some(sleep) = self.component_mut::<Sleep>(*id) {
+                sleep.asleep = true;
+            }
+            if let Some(body) = self.component_mut::<Rigidbody>(*id) {
+                body.velocity = Vec3::default();
+                body.angular_velocity = Vec3::default();
+            }
+        }
+    }
+
+    pub fn is_asleep(&self, id: EntityId) -> bool {
+        self.component::<Sleep>(id)
+            .is_some_and(|sleep| sleep.asleep)
+    }
+
+    /**
+    Wakes a sleeping body, so it's simulated again. Bodies wake by themselves when they're pushed or hit.
+    */
+    pub fn wake(&mut self, id: EntityId) {
+        if let Some(sleep) = self.component_mut::<Sleep>(id) {
+            *sleep = Sleep::default();
+        }
+    }
+
+    /**
+    Returns whether nothing needs simulating for an entity this step, because it's asleep, can't be moved, or has
+    no rigidbody at all, like a collision mesh.
+    */
+    fn is_resting(&self, id: EntityId) -> bool {
+        if !self.contains(id) {
+            return false;
+        }
+        match self.component::<Rigidbody>(id) {
+            Some(body) => self.is_asleep(id) || self.is_static(id) || body.inverse_mass() == 0.0,
+            None => true,
+        }
+    }
+
+    /**
+    Compares the pairs of entities touching this step with those touching last step, queues events for the pairs
+    which started or stopped touching, and passes them to the collision callbacks.
+    */
+    fn record_contacts(&mut self, contacts: Vec<(EntityId, EntityId, Contact)>) {
+        let mut events = vec![];
+        let mut touching = HashSet::new();
+        let mut overlapping = HashSet::new();
+        for (a, b, contact) in contacts {
+            let key = match (self.is_trigger(a), self.is_trigger(b)) {
+                (false, false) => {
+                    // Each pair is only reported once, whichever way round it was found
+                    let key = if a.index <= b.index { (a, b) } else { (b, a) };
+                    if touching.insert(key) && !self.touching.contains(&key) {
+                        events.push(CollisionEvent::Begin { a, b, contact });
+                    }
+                    continue;
+                }
+                (true, false) => (a, b),
+                (false, true) => (b, a),
+                (true, true) => continue,
+            };
+            if overlapping.insert(key) && !self.overlapping.contains(&key) {
+                let (trigger, other) = key;
+                events.push(CollisionEvent::Enter { trigger, other });
+            }
+        }
+
+        // Pairs which weren't tested because both are asleep or immovable are still touching
+        let resting = |(a, b): &&(EntityId, EntityId)| self.is_resting(*a) && self.is_resting(*b);
+        touching.extend(self.touching.iter().filter(resting));
+        overlapping.extend(self.overlapping.iter().filter(resting));
+
+        let mut ended: Vec<&(EntityId, EntityId)> = self.touching.difference(&touching).collect();
+        ended.sort_by_key(|(a, b)| (a.index, b.index));
+        events.extend(
+            ended
+                .into_iter()
+                .map(|&(a, b)| CollisionEvent::End { a, b }),
+        );
+        let mut left: Vec<&(EntityId, EntityId)> =
+            self.overlapping.difference(&overlapping).collect();
+        left.sort_by_key(|(trigger, other)| (trigger.index, other.index));
+        events.extend(
+            left.into_iter()
+                .map(|&(trigger, other)| CollisionEvent::Exit { trigger, other }),
+        );
+        self.touching = touching;
+        self.overlapping = overlapping;
+
+        // Bodies which have lost something they were resting on need to fall
+        for event in &events {
+            if let CollisionEvent::End { a, b } = event {
+                self.wake(*a);
+                self.wake(*b);
+            }
+        }
+
+        // Callbacks are taken out of the world while they run, so they can be given all of it
+        let mut callbacks = std::mem::take(&mut self.collision_callbacks);
+        for event in &events {
+            for callback in &mut callbacks {
+                callback(self, event);
+            }
+        }
+        callbacks.append(&mut self.collision_callbacks);
+        self.collision_callbacks = callbacks;
+        self.collision_events.extend(events);
+    }
+
+    fn is_trigger(&self, id: EntityId) -> bool {
+        self.component::<Rigidbody>(id)
+            .is_some_and(|body| body.trigger)
+    }
+
+    /**
+    Returns every collision event since this was last called. Events are kept until they're drained, so worlds which
+    use them should drain them every frame.
+    */
+    pub fn drain_collision_events(&mut self) -> Vec<CollisionEvent> {
+        std::mem::take(&mut self.collision_events)
+    }
+
+    /**
+    Registers a callback to run for every collision event as soon as its physics step finishes. Callbacks must be
+    `Send`, so the world can still be moved to another thread.
+    */
+    pub fn on_collision<F: FnMut(&mut World, &CollisionEvent) + Send + 'static>(
+        &mut self,
+        callback: F,
+    ) {
+        self.collision_callbacks.push(Box::new(callback));
+    }
+
+    /**
+    Copies the velocities worked out for a body while resolving contacts or joints back into its rigidbody.
+    */
+    fn store_velocities(&mut self, id: EntityId, body: &ContactBody) {
+        if body.inverse_mass == 0.0 {
+            return;
+        }
+        if let Some(rigidbody) = self.component_mut::<Rigidbody>(id) {
+            rigidbody.velocity = body.velocity;
+            rigidbody.angular_velocity = body.angular_velocity;
+        }
+    }
+
+    /**
+    Attaches a joint to an entity's rigidbody, tying it to another body or to the world. Joints are removed along
+    with the entity they were added to.
+    */
+    pub fn add_joint(&mut self, id: EntityId, joint: Joint) {
+        if !self.contains(id) {
+            return;
+        }
+        self.wake(id);
+        if let Some(other) = joint.other {
+            self.wake(other);
+        }
+        match self.component_mut::<Joints>(id) {
+            Some(joints) => joints.0.push(joint),
+            None => {
+                self.insert_component(id, Joints(vec![joint]));
+            }
+        }
+    }
+
+    pub fn joints(&self, id: EntityId) -> &[Joint] {
+        self.component::<Joints>(id)
+            .map_or(&[], |joints| joints.0.as_slice())
+    }
+
+    /**
+    Removes every joint from an entity, waking the bodies they held.
+    */
+    pub fn clear_joints(&mut self, id: EntityId) {
+        let joints = self.remove_component::<Joints>(id);
+        self.wake(id);
+        for joint in joints.iter().flat_map(|joints| &joints.0) {
+            if let Some(other) = joint.other {
+                self.wake(other);
+            }
+        }
+    }
+
+    /**
+    Solves every joint `physics.joint_iterations` times. Each joint is solved on its own, which can pull other
+    joints apart again, so solving them all repeatedly lets chains of joints settle on positions that satisfy all of
+    them together.
+    */
+    fn solve_joints(&mut self) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("joints").entered();
+        let joints: Vec<(EntityId, Joint)> = self
+            .query::<Joints>()
+            .flat_map(|(id, joints)| joints.0.iter().map(move |joint| (id, *joint)))
+            .collect();
+        for _ in 0..self.physics.joint_iterations {
+            for (id, joint) in &joints {
+                if self.is_resting(*id) && joint.other.is_none_or(|other| self.is_resting(other)) {
+                    continue;
+                }
+                self.solve_joint(*id, joint);
+            }
+        }
+    }
+
+    /**
+    Pulls a joint's bodies back into line. Impulses at the anchors first stop the bodies moving in ways the joint
+    doesn't allow, then the bodies are moved to close whatever gap has opened up, in proportion to how easily each
+    can be moved.
+    */
+    fn solve_joint(&mut self, id: EntityId, joint: &Joint) {
+        let Some(mut a) = self.contact_body(Some(id)) else {
+            return;
+        };
+        let (mut b, b_transformation) = match joint.other {
+            Some(other) => match (self.contact_body(Some(other)), self.get(other)) {
+                (Some(body), Some(obj)) => (body, obj.transformation),
+                _ => return,
+            },
+            None => (ContactBody::fixed(joint.other_anchor), Mat4::identity()),
+        };
+        let total = a.inverse_mass + b.inverse_mass;
+        let Some(a_transformation) = self.get(id).map(|obj| obj.transformation) else {
+            return;
+        };
+        if total == 0.0 {
+            return;
+        }
+
+        let a_anchor = a_transformation.transform(joint.anchor);
+        let b_anchor = b_transformation.transform(joint.other_anchor);
+        let between = b_anchor - a_anchor;
+        let push_apart = |a: &mut ContactBody, b: &mut ContactBody, direction: Vec3| {
+            let relative = b.point_velocity(b_anchor) - a.point_velocity(a_anchor);
+            let resistance = a.inverse_mass_along(a_anchor, direction)
+                + b.inverse_mass_along(b_anchor, direction);
+            let impulse = -relative.dot(direction) / resistance;
+            a.push(a_anchor, -direction * impulse);
+            b.push(b_anchor, direction * impulse);
+        };
+
+        let gap = match joint.kind {
+            JointKind::Distance { length, rope } => {
+                let distance = between.length();
+                if distance <= f64::EPSILON || (rope && distance <= length) {
+                    return;
+                }
+                let direction = between / distance;
+                let relative = b.point_velocity(b_anchor) - a.point_velocity(a_anchor);
+                // Ropes can go slack, so only stop the ends moving further apart
+                if !rope || relative.dot(direction) > 0.0 {
+                    push_apart(&mut a, &mut b, direction);
+                }
+                direction * (distance - length)
+            }
+            JointKind::Hinge { axis } => {
+                for direction in [X_AXIS, Y_AXIS, Z_AXIS] {
+                    push_apart(&mut a, &mut b, direction);
+                }
+
+                // Only spinning around the hinge's axis is allowed, so any spin around the two axes across it is
+                // cancelled out
+                let axis = a_transformation.transform_direction(axis).normalise();
+                let helper = if axis.dot(X_AXIS).abs() < 0.9 {
+                    X_AXIS
+                } else {
+                    Y_AXIS
+                };
+                let across = axis.cross_product(helper).normalise();
+                for direction in [across, axis.cross_product(across)] {
+                    let resistance =
+                        a.inverse_inertia_along(direction) + b.inverse_inertia_along(direction);
+                    if resistance <= f64::EPSILON {
+                        continue;
+                    }
+                    let relative = (b.angular_velocity - a.angular_velocity).dot(direction);
+                    a.twist(direction * (relative / resistance));
+                    b.twist(-direction * (relative / resistance));
+                }
+                between
+            }
+        };
+
+        self.store_velocities(id, &a);
+        self.move_body(id, gap * (a.inverse_mass / total));
+        if let Some(other) = joint.other {
+            self.store_velocities(other, &b);
+            self.move_body(other, -gap * (b.inverse_mass / total));
+        }
+    }
+
+    /**
+    Marks an entity's object as level geometry, like terrain or buildings, which rigidbodies collide with triangle by
+    triangle. Collision meshes are never pushed around themselves, and don't need a rigidbody.
+    */
+    pub fn set_collision_mesh(&mut self, id: EntityId, enabled: bool) {
+        if enabled {
+            self.insert_component(id, CollisionMesh);
+        } else {
+            self.remove_component::<CollisionMesh>(id);
+        }
+    }
+
+    pub fn is_collision_mesh(&self, id: EntityId) -> bool {
+        self.has_component::<CollisionMesh>(id)
+    }
+
+    /**
+    Returns everything solid that a collider overlaps which can't be pushed around, for moving things like characters
+    through the level without simulating them. This includes collision mesh triangles, static or immovable rigidbodies,
+    and the terrain. The terrain is treated as flat beneath the collider's centre, tilted to match its slope there.
+
+    Each contact's normal points from the collider into what it hit, so moving the collider by `-normal * depth`
+    separates them.
+    */
+    pub fn solid_contacts(&self, collider: &Collider) -> Vec<Contact> {
+        let Some(bounds) = collider.bounds() else {
+            return vec![];
+        };
+        let mut contacts = vec![];
+
+        for id in self.query_aabb(&bounds) {
+            if self.is_collision_mesh(id) {
+                for [a, b, c] in self.mesh_triangles(id, &bounds) {
+                    contacts.extend(collider.collide_triangle(a, b, c));
+                }
+            }
+        }
+
+        let solid = self.query::<Rigidbody>().filter(|(id, body)| {
+            !body.trigger && (self.is_static(*id) || body.inverse_mass() == 0.0)
+        });
+        for (id, body) in solid {
+            let Some(transformation) = self.get(id).map(|obj| obj.transformation) else {
+                continue;
+            };
+            if let Some(other) = body
+                .collider
+                .as_ref()
+                .map(|other| other.transformed(transformation))
+            {
+                contacts.extend(collider.collide(&other));
+            }
+        }
+
+        let centre = bounds.centre();
+        if let (Some(height), Some(normal)) = (
+            self.terrain_height(centre.x, centre.z),
+            self.terrain()
+                .and_then(|terrain| terrain.normal(centre.x, centre.z)),
+        ) {
+            let surface = Vec3::new(centre.x, height, centre.z);
+            let plane = Plane::new(normal, -normal.dot(surface));
+            contacts.extend(collider.collide(&Collider::Plane(plane)));
+        }
+
+        contacts
+    }
+
+    /**
+    Finds the nearest rigidbody collider along a ray, for line of sight checks. Unlike `raycast`, this tests the
+    bodies' colliders rather than the triangles of their objects.
+    */
+    pub fn raycast_bodies(&self, ray: &Ray) -> Option<BodyHit> {
+        self.cast_bodies(|collider| {
+            let (distance, normal) = collider.raycast(ray)?;
+            Some((distance, ray.at(distance), normal))
+        })
+    }
+
+    /**
+    Sweeps a sphere from `origin` along `direction`, and finds the first rigidbody collider it would touch. This
+    is useful for checking whether there's ground beneath a character, or room for it to move.
+    */
+    pub fn sphere_cast(&self, origin: Vec3, radius: f64, direction: Vec3) -> Option<BodyHit> {
+        let ray = Ray::new(origin, direction);
+        self.cast_bodies(|collider| {
+            let (distance, normal) = collider.sphere_cast(&ray, radius)?;
+            Some((distance, ray.at(distance) - normal * radius, normal))
+        })
+    }
+
+    /**
+    Returns the nearest hit of a cast against each body's collider in world space.
+    */
+    fn cast_bodies(
+        &self,
+        cast: impl Fn(&Collider) -> Option<(f64, Vec3, Vec3)>,
+    ) -> Option<BodyHit> {
+        self.query::<Rigidbody>()
+            .filter_map(|(id, body)| {
+                let collider = body
+                    .collider
+                    .as_ref()?
+                    .transformed(self.get(id)?.transformation);
+                let (distance, point, normal) = cast(&collider)?;
+                Some(BodyHit {
+                    entity: id,
+                    point,
+                    normal,
+                    distance,
+                })
+            })
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    /**
+    Moves a simulated entity through world space. Unlike `set_base_transform`, the bounding volume hierarchy is refit
+    rather than rebuilt, as bodies move every step.
+    */
+    fn move_body(&mut self, id: EntityId, offset: Vec3) {
+        self.transform_body(id, Mat4::identity().translate(offset));
+    }
+
+    /**
+    Applies a world space transformation to a simulated entity, on top of its current transformation.
+    */
+    fn transform_body(&mut self, id: EntityId, change: Mat4) {
+        if let Some(animated) = self.component_mut::<Animated>(id) {
+            if let Some(base) = &mut animated.base {
+                *base = change.mult(*base);
+                return;
+            }
+        }
+        if let Some(obj) = self.object_mut(id) {
+            obj.transformation = change.mult(obj.transformation);
+        }
+    }
+
+    /**
+    Brings the bounding volume hierarchy up to date. If entities have been added or removed it's rebuilt, otherwise
+    the existing tree is refit around the objects' current positions. Static entities aren't part of the hierarchy.
+    This is called by `update`, so it only needs calling directly after moving objects outside of it.
+    */
+    pub fn update_bvh(&mut self) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("bvh").entered();
+        if self.bvh_stale {
+            let items = self
+                .iter_slots()
+                .filter(|(_, slot)| !slot.is_static)
+                .filter_map(|(id, slot)| Some((id, slot.world_bounds()?)))
+                .collect();
+            self.bvh = Bvh::build(items);
+            self.bvh_stale = false;
+        } else {
+            let slots = &self.slots;
+            self.bvh.refit(|id| slots[id.index].world_bounds());
+        }
+    }
+
+    /**
+    Returns the hierarchy over the world space bounds of every non-static object, as of the last `update` or
+    `update_bvh`.
+    */
+    pub fn bvh(&self) -> &Bvh<EntityId> {
+        &self.bvh
+    }
+
+    /**
+    Returns a box containing an entity's object in world space.
+    */
+    pub fn bounds(&self, id: EntityId) -> Option<Aabb> {
+        self.slot(id)?.world_bounds()
+    }
+
+    /**
+    Returns every entity whose bounds overlap a box.
+    */
+    pub fn query_aabb(&self, aabb: &Aabb) -> Vec<EntityId> {
+        let mut result = if self.bvh_stale {
+            self.filter_bounds(|bounds| bounds.intersects(aabb))
+        } else {
+            self.bvh.query_aabb(aabb)
+        };
+        result.extend(self.statics.query_aabb(aabb));
+        result
+    }
+
+    /**
+    Returns every entity which may be visible within a frustum.
+    */
+    pub fn query_frustum(&self, frustum: &Frustum) -> Vec<EntityId> {
+        let mut result = if self.bvh_stale {
+            self.filter_bounds(|bounds| frustum.intersects_aabb(bounds))
+        } else {
+            self.bvh.query_frustum(frustum)
+        };
+        result.extend(self.statics.query_frustum(frustum));
+        result
+    }
+
+    /**
+    Returns every entity whose bounds a ray passes through within `max_distance`, along with the distance at which the
+    ray enters them, nearest first.
+    */
+    pub fn query_ray(&self, ray: &Ray, max_distance: f64) -> Vec<(EntityId, f64)> {
+        let hits = |bounds: &Aabb| {
+            bounds
+                .ray_distance(ray)
+                .filter(|distance| *distance <= max_distance)
+        };
+
+        let mut result = if self.bvh_stale {
+            self.iter_slots()
+                .filter(|(_, slot)| !slot.is_static)
+                .filter_map(|(id, slot)| Some((id, hits(&slot.world_bounds()?)?)))
+                .collect()
+        } else {
+            self.bvh.query_ray(ray, max_distance)
+        };
+        result.extend(self.statics.query_ray(ray, max_distance));
+        result.sort_by(|a, b| a.1.total_cmp(&b.1));
+        result
+    }
+
+    /**
+    Finds the first face a ray hits, for picking objects, firing projectiles or finding the ground beneath a point.
+    Faces are hit from either side. Skinned objects are tested in their rest pose.
+    */
+    pub fn raycast(&self, ray: &Ray) -> Option<RayHit> {
+        self.raycast_within(ray, f64::INFINITY)
+    }
+
+    /**
+    As with `raycast`, but ignores anything further than `max_distance` along the ray.
+    */
+    pub fn raycast_within(&self, ray: &Ray, max_distance: f64) -> Option<RayHit> {
+        let mut nearest: Option<RayHit> = None;
+
+        for (id, entry_distance) in self.query_ray(ray, max_distance) {
+            // Candidates are sorted by where the ray enters their bounds, so none of the rest can be any closer
+            let limit = nearest.map_or(max_distance, |hit| hit.distance);
+            if entry_distance > limit {
+                break;
+            }
+            if let Some(hit) = self.raycast_entity(id, ray, limit) {
+                nearest = Some(hit);
+            }
+        }
+        nearest
+    }
+
+    fn raycast_entity(&self, id: EntityId, ray: &Ray, max_distance: f64) -> Option<RayHit> {
+        let slot = self.slot(id)?;
+        let obj = slot.object.as_ref()?;
+        let inverse = obj.transformation.inverse()?;
+
+        // Distances along the ray are the same in both spaces, as long as the direction isn't normalised again
+        let local_ray = Ray {
+            origin: inverse.transform(ray.origin),
+            direction: inverse.transform_direction(ray.direction),
+        };
+
+        let mut nearest: Option<(usize, f64)> = None;
+        for (face, entry_distance) in slot.faces.query_ray(&local_ray, max_distance) {
+            let limit = nearest.map_or(max_distance, |(_, distance)| distance);
+            if entry_distance > limit {
+                break;
+            }
+            let (a, b, c) = obj.faces[face].vertices;
+            let (a, b, c) = (obj.vertices[a], obj.vertices[b], obj.vertices[c]);
+            if let Some(distance) = local_ray.intersect_triangle(a, b, c) {
+                if distance <= limit {
+                    nearest = Some((face, distance));
+                }
+            }
+        }
+
+        let (face, distance) = nearest?;
+        let (a, b, c) = obj.faces[face].vertices;
+        let (a, b, c) = (obj.vertices[a], obj.vertices[b], obj.vertices[c]);
+        let normal = inverse
+            .transpose()
+            .transform_direction((b - a).cross_product(c - a))
+            .normalise();
+
+        Some(RayHit {
+            entity: id,
+            face,
+            point: ray.at(distance),
+            normal,
+            distance,
+        })
+    }
+
+    /**
+    Names an entity, replacing any name it already had. Names don't have to be unique, but `find_by_name` only
+    finds one entity per name.
+    */
+    pub fn set_name(&mut self, id: EntityId, name: &str) {
+        if let Some(slot) = self.slot_mut(id) {
+            slot.name = Some(String::from(name));
+        }
+    }
+
+    pub fn name(&self, id: EntityId) -> Option<&str> {
+        self.slot(id)?.name.as_deref()
+    }
+
+    /**
+    Returns the first entity with a given name. Entities whose objects are still loading can be found too.
+    */
+    pub fn find_by_name(&self, name: &str) -> Option<EntityId> {
+        self.iter_slots()
+            .find(|(_, slot)| slot.name.as_deref() == Some(name))
+            .map(|(id, _)| id)
+    }
+
+    /**
+    Adds a tag to an entity. Entities can have any number of tags, but each tag only once.
+    */
+    pub fn add_tag(&mut self, id: EntityId, tag: &str) {
+        if let Some(slot) = self.slot_mut(id) {
+            if !slot.tags.iter().any(|existing| existing == tag) {
+                slot.tags.push(String::from(tag));
+            }
+        }
+    }
+
+    /**
+    Removes a tag from an entity, returning true if it had the tag.
+    */
+    pub fn remove_tag(&mut self, id: EntityId, tag: &str) -> bool {
+        let Some(slot) = self.slot_mut(id) else {
+            return false;
+        };
+        let before = slot.tags.len();
+        slot.tags.retain(|existing| existing != tag);
+        slot.tags.len() != before
+    }
+
+    pub fn has_tag(&self, id: EntityId, tag: &str) -> bool {
+        self.slot(id)
+            .is_some_and(|slot| slot.tags.iter().any(|existing| existing == tag))
+    }
+
+    pub fn tags(&self, id: EntityId) -> impl Iterator<Item = &str> {
+        self.slot(id)
+            .into_iter()
+            .flat_map(|slot| slot.tags.iter().map(String::as_str))
+    }
+
+    /**
+    Iterates over every entity with a given tag which has an object, along with its handle
+    */
+    pub fn iter_tagged<'a>(
+        &'a self,
+        tag: &'a str,
+    ) -> impl Iterator<Item = (EntityId, &'a Object)> + 'a {
+        self.iter().filter(move |(id, _)| self.has_tag(*id, tag))
+    }
+
+    /**
+    Shows or hides an entity. Hidden entities aren't drawn, but are otherwise unaffected - they still animate, and can
+    still be found by queries and raycasts.
+    */
+    pub fn set_visible(&mut self, id: EntityId, visible: bool) {
+        if let Some(slot) = self.slot_mut(id) {
+            slot.visible = visible;
+        }
+    }
+
+    /**
+    Returns false if the entity has been hidden with `set_visible`, or doesn't exist.
+    */
+    pub fn is_visible(&self, id: EntityId) -> bool {
+        self.slot(id).is_some_and(|slot| slot.visible)
+    }
+
+    /**
+    Moves an entity to a different render layer.
+    */
+    pub fn set_layer(&mut self, id: EntityId, layer: Layer) {
+        if let Some(slot) = self.slot_mut(id) {
+            slot.layer = layer;
+        }
+    }
+
+    pub fn layer(&self, id: EntityId) -> Option<Layer> {
+        Some(self.slot(id)?.layer)
+    }
+
+    /**
+    Sets where an entity is drawn within its layer - lower orders are drawn first. Entities with the same order are
+    drawn in no particular order, relying on the depth buffer.
+    */
+    pub fn set_draw_order(&mut self, id: EntityId, order: i32) {
+        if let Some(slot) = self.slot_mut(id) {
+            slot.draw_order = order;
+        }
+    }
+
+    pub fn draw_order(&self, id: EntityId) -> Option<i32> {
+        Some(self.slot(id)?.draw_order)
+    }
+
+    /**
+    Sorts a set of entities into the order they should be drawn in, by layer and then by draw order.
+    */
+    pub fn sort_for_drawing(&self, ids: &mut [EntityId]) {
+        // Transparent entities are drawn after the rest of their layer, so there's something behind them to blend with
+        ids.sort_by_key(|id| {
+            self.slot(*id).map_or((Layer::World, false, 0), |slot| {
+                (slot.layer, slot.opacity < 1.0, slot.draw_order)
+            })
+        });
+    }
+
+    /**
+    Sets how opaque an entity is, between 0 and 1. Entities which aren't fully opaque are blended with whatever is
+    behind them.
+    */
+    pub fn set_opacity(&mut self, id: EntityId, opacity: f64) {
+        if let Some(slot) = self.slot_mut(id) {
+            slot.opacity = opacity.clamp(0.0, 1.0);
+        }
+    }
+
+    pub fn opacity(&self, id: EntityId) -> Option<f64> {
+        Some(self.slot(id)?.opacity)
+    }
+
+    /**
+    Sets how an entity's texture is mapped, or with `None` leaves it to the renderer, so affine mapping can be used for
+    distant or stylised objects while the rest of the world is drawn with perspective correction, or the other way
+    round.
+    */
+    pub fn set_texture_mapping(&mut self, id: EntityId, mapping: Option<TextureMapping>) {
+        if let Some(slot) = self.slot_mut(id) {
+            slot.texture_mapping = mapping;
+        }
+    }
+
+    pub fn texture_mapping(&self, id: EntityId) -> Option<TextureMapping> {
+        self.slot(id)?.texture_mapping
+    }
+
+    /**
+    Adds a body of water to the world, centred at a given location. Its waves are moved by `update`, and it's drawn
+    with the water's opacity.
+    */
+    pub fn add_water(&mut self, water: Water, pos: Vec3) -> EntityId {
+        let id = self.add_object(water.mesh(), pos);
+        if let Some(slot) = self.slot_mut(id) {
+            // The cached bounds are of the flat surface, so they're grown to fit the tallest waves
+            slot.bounds = slot.bounds.map(|bounds| bounds.inflate(water.max_height()));
+            slot.opacity = water.opacity.clamp(0.0, 1.0);
+        }
+        self.insert_component(id, water);
+        id
+    }
+
+    /**
+    Returns the water an entity was created from with `add_water`.
+    */
+    pub fn water(&self, id: EntityId) -> Option<&Water> {
+        self.component(id)
+    }
+
+    pub fn water_mut(&mut self, id: EntityId) -> Option<&mut Water> {
+        self.component_mut(id)
+    }
+
+    /**
+    Launches a projectile from a given location, drawn with `object` and moved by a rigidbody built with
+    `Projectile::body`. Projectiles with a lifetime are removed once it runs out, which is counted in physics steps,
+    so they only age while the simulation is running.
+    */
+    pub fn spawn_projectile(
+        &mut self,
+        object: Object,
+        pos: Vec3,
+        projectile: &Projectile,
+    ) -> EntityId {
+        let body = projectile.body(object.clone());
+        let id = self.add_object(object, pos);
+        self.insert_component(id, body);
+        if let Some(lifetime) = projectile.lifetime {
+            self.insert_component(id, Lifetime(lifetime));
+        }
+        id
+    }
+
+    /**
+    Returns how much longer a projectile has before it's removed, or `None` if it lasts until it's removed by hand.
+    */
+    pub fn projectile_lifetime(&self, id: EntityId) -> Option<f64> {
+        self.component::<Lifetime>(id).map(|lifetime| lifetime.0)
+    }
+
+    /**
+    Adds a sheet of cloth to the world. The cloth is simulated along with the world's rigidbodies, and its mesh is
+    moved to match. Its particles are already in world space, so the entity is left at the origin.
+    */
+    pub fn add_cloth(&mut self, cloth: Cloth) -> EntityId {
+        let id = self.add_object(cloth.mesh(), Vec3::default());
+        self.insert_component(id, cloth);
+        id
+    }
+
+    pub fn cloth(&self, id: EntityId) -> Option<&Cloth> {
+        self.component(id)
+    }
+
+    pub fn cloth_mut(&mut self, id: EntityId) -> Option<&mut Cloth> {
+        self.component_mut(id)
+    }
+
+    /**
+    Adds a sprite to the world, centred at a given location. Sprites are entities like any other, so they can be
+    hidden, moved and removed in the same way, but they're drawn as a flat image facing the camera.
+    */
+    pub fn add_sprite(&mut self, sprite: Sprite, pos: Vec3) -> EntityId {
+        let id = self.add_object(sprite.mesh(), pos);
+        if let Some(slot) = self.slot_mut(id) {
+            // The sprite turns to face the camera, so its bounds have to fit it facing any way
+            let radius = sprite.size.dot(sprite.size).sqrt() / 2.0;
+            let corner = Vec3::new(radius, radius, radius);
+            slot.bounds = Some(Aabb::new(-corner, corner));
+        }
+        self.insert_component(id, sprite);
+        id
+    }
+
+    pub fn sprite(&self, id: EntityId) -> Option<&Sprite> {
+        self.component(id)
+    }
+
+    pub fn sprite_mut(&mut self, id: EntityId) -> Option<&mut Sprite> {
+        self.component_mut(id)
+    }
+
+    /**
+    Gives an entity simplified objects to draw in place of its own as it gets further from the camera, replacing any
+    it already had.
+    */
+    pub fn set_lod(&mut self, id: EntityId, lod: Lod) {
+        self.insert_component(id, lod);
+    }
+
+    pub fn clear_lod(&mut self, id: EntityId) -> Option<Lod> {
+        self.remove_component(id)
+    }
+
+    pub fn lod(&self, id: EntityId) -> Option<&Lod> {
+        self.component(id)
+    }
+
+    pub fn lod_mut(&mut self, id: EntityId) -> Option<&mut Lod> {
+        self.component_mut(id)
+    }
+
+    /**
+    Gives an entity a blob shadow, replacing any it already had. The shadow is placed on the ground under the entity
+    straight away, and moved to follow it every update.
+    */
+    pub fn set_blob_shadow(&mut self, id: EntityId, shadow: BlobShadow) {
+        if self.slot(id).is_none() {
+            return;
+        }
+        let decal = shadow.decal();
+        self.insert_component(
+            id,
+            Shadow {
+                settings: shadow,
+                decal,
+                placed: false,
+            },
+        );
+        self.update_bvh();
+        self.place_blob_shadows();
+    }
+
+    pub fn clear_blob_shadow(&mut self, id: EntityId) -> Option<BlobShadow> {
+        Some(self.remove_component::<Shadow>(id)?.settings)
+    }
+
+    pub fn blob_shadow(&self, id: EntityId) -> Option<BlobShadow> {
+        Some(self.component::<Shadow>(id)?.settings)
+    }
+
+    /**
+    Returns the decals blob shadows are drawn with, for the shadows with ground under them.
+    */
+    pub(crate) fn blob_shadow_decals(&self) -> impl Iterator<Item = &Decal> {
+        self.query::<Shadow>()
+            .filter(|(_, shadow)| shadow.placed)
+            .map(|(_, shadow)| &shadow.decal)
+    }
+
+    /**
+    Shades an entity in flat bands by the world's lights, optionally with an outline, replacing any toon shading it
+    already had.
+    */
+    pub fn set_toon(&mut self, id: EntityId, toon: Toon) {
+        if self.slot(id).is_none() {
+            return;
+        }
+        let outline = Texture::solid(toon.outline);
+        self.insert_component(id, ToonShading { toon, outline });
+    }
+
+    pub fn clear_toon(&mut self, id: EntityId) -> Option<Toon> {
+        Some(self.remove_component::<ToonShading>(id)?.toon)
+    }
+
+    pub fn toon(&self, id: EntityId) -> Option<Toon> {
+        Some(self.component::<ToonShading>(id)?.toon)
+    }
+
+    /**
+    Returns an entity's toon shading along with the texture its outline is drawn with.
+    */
+    pub(crate) fn toon_shading(&self, id: EntityId) -> Option<(Toon, &Texture)> {
+        let shading = self.component::<ToonShading>(id)?;
+        Some((shading.toon, &shading.outline))
+    }
+
+    /**
+    Marks an entity as static or not. Static entities are sorted into a grid once, rather than being refit in the
+    bounding volume hierarchy every update, which keeps updates fast in worlds with many objects that never move.
+    Static entities can still be moved with `set_base_transform` or `replace`, but moving one is slower.
+    */
+    pub fn set_static(&mut self, id: EntityId, is_static: bool) {
+        let Some(slot) = self.slot_mut(id) else {
+            return;
+        };
+        slot.is_static = is_static;
+        self.reindex_static(id);
+        self.bvh_stale = true;
+    }
+
+    pub fn is_static(&self, id: EntityId) -> bool {
+        self.slot(id).is_some_and(|slot| slot.is_static)
+    }
+
+    /**
+    Moves a static entity to the grid cells matching its current bounds, or takes it out of the grid if it isn't
+    static.
+    */
+    fn reindex_static(&mut self, id: EntityId) {
+        match self
+            .slot(id)
+            .filter(|slot| slot.is_static)
+            .and_then(Slot::world_bounds)
+        {
+            Some(bounds) => self.statics.insert(id, bounds),
+            None => {
+                self.statics.remove(id);
+            }
+        }
+    }
+
+    /**
+    Tests every non-static entity's bounds individually, for when entities have changed since the hierarchy was last
+    built.
+    */
+    fn filter_bounds<F>(&self, test: F) -> Vec<EntityId>
+    where
+        F: Fn(&Aabb) -> bool,
+    {
+        self.iter_slots()
+            .filter(|(_, slot)| !slot.is_static)
+            .filter(|(_, slot)| slot.world_bounds().is_some_and(|bounds| test(&bounds)))
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    fn iter_slots(&self) -> impl Iterator<Item = (EntityId, &Slot)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| slot.alive)
+            .map(|(index, slot)| {
+                let id = EntityId {
+                    index,
+                    generation: slot.generation,
+                };
+                (id, slot)
+            })
+    }
+
+    /**
+    Attaches a component to an entity, returning the component of the same type it already had. Any type which is
+    `Send + Sync` can be a component - each system keeps its per-entity state in its own type, e.g. a `Rigidbody` or
+    a `Cloth`, and finds the entities it works on with `query`. Components are dropped when their entity is removed.
+    */
+    pub fn insert_component<T: Send + Sync + 'static>(
+        &mut self,
+        id: EntityId,
+        component: T,
+    ) -> Option<T> {
+        self.slot(id)?;
+        self.components.insert(id.index, component)
+    }
+
+    pub fn remove_component<T: Send + Sync + 'static>(&mut self, id: EntityId) -> Option<T> {
+        self.slot(id)?;
+        self.components.remove(id.index)
+    }
+
+    pub fn component<T: Send + Sync + 'static>(&self, id: EntityId) -> Option<&T> {
+        self.slot(id)?;
+        self.components.get(id.index)
+    }
+
+    pub fn component_mut<T: Send + Sync + 'static>(&mut self, id: EntityId) -> Option<&mut T> {
+        self.slot(id)?;
+        self.components.get_mut(id.index)
+    }
+
+    pub fn has_component<T: Send + Sync + 'static>(&self, id: EntityId) -> bool {
+        self.component::<T>(id).is_some()
+    }
+
+    /**
+    Iterates over every entity with a component of type `T`, along with the component.
+    */
+    pub fn query<T: Send + Sync + 'static>(&self) -> impl Iterator<Item = (EntityId, &T)> {
+        let slots = &self.slots;
+        self.components.iter::<T>().map(move |(index, component)| {
+            let id = EntityId {
+                index,
+                generation: slots[index].generation,
+            };
+            (id, component)
+        })
+    }
+
+    pub fn query_mut<T: Send + Sync + 'static>(
+        &mut self,
+    ) -> impl Iterator<Item = (EntityId, &mut T)> {
+        let slots = &self.slots;
+        self.components
+            .iter_mut::<T>()
+            .map(move |(index, component)| {
+                let id = EntityId {
+                    index,
+                    generation: slots[index].generation,
+                };
+                (id, component)
+            })
+    }
+
+    /**
+    Iterates over every entity with components of both type `A` and type `B`.
+    */
+    pub fn query_pair<A: Send + Sync + 'static, B: Send + Sync + 'static>(
+        &self,
+    ) -> impl Iterator<Item = (EntityId, &A, &B)> {
+        self.query::<A>()
+            .filter_map(|(id, a)| Some((id, a, self.components.get::<B>(id.index)?)))
+    }
+
+    /**
+    Adds a behaviour to an entity, to be run every frame by `update` after any it already has.
+    */
+    pub fn add_behaviour<B: Behaviour + 'static>(&mut self, id: EntityId, behaviour: B) {
+        if !self.contains(id) {
+            return;
+        }
+        if !self.has_component::<Behaviours>(id) {
+            self.insert_component(id, Behaviours::default());
+        }
+        if let Some(behaviours) = self.component_mut::<Behaviours>(id) {
+            behaviours.0.push(Box::new(behaviour));
+        }
+    }
+
+    /**
+    Removes every behaviour from an entity.
+    */
+    pub fn clear_behaviours(&mut self, id: EntityId) {
+        self.remove_component::<Behaviours>(id);
+    }
+
+    /**
+    Sets the modifier an entity's vertices are moved by whenever it's drawn, replacing any it already has. `reach` is
+    the furthest the modifier moves a vertex from where it started, which the entity's bounds are grown by so it isn't
+    culled while part of it is still on screen. Set the modifier after the entity's object has loaded, as loading
+    resets the bounds.
+    */
+    pub fn set_vertex_modifier<M: VertexModifier + 'static>(
+        &mut self,
+        id: EntityId,
+        reach: f64,
+        modifier: M,
+    ) {
+        self.remove_vertex_modifier(id);
+        let Some(slot) = self.slot_mut(id) else {
+            return;
+        };
+        let reach = reach.max(0.0);
+        slot.bounds = slot.bounds.map(|bounds| bounds.inflate(reach));
+        self.insert_component(
+            id,
+            Modifier {
+                modifier: Box::new(modifier),
+                reach,
+            },
+        );
+        self.reindex_static(id);
+        self.bvh_stale = true;
+    }
+
+    /**
+    Stops moving an entity's vertices when it's drawn, shrinking its bounds back to fit its mesh.
+    */
+    pub fn remove_vertex_modifier(&mut self, id: EntityId) {
+        let Some(Modifier { reach, .. }) = self.remove_component::<Modifier>(id) else {
+            return;
+        };
+        if let Some(slot) = self.slot_mut(id) {
+            slot.bounds = slot.bounds.map(|bounds| bounds.inflate(-reach));
+        }
+        self.reindex_static(id);
+        self.bvh_stale = true;
+    }
+
+    pub fn vertex_modifier(&self, id: EntityId) -> Option<&dyn VertexModifier> {
+        Some(self.component::<Modifier>(id)?.modifier.as_ref())
+    }
+
+    /**
+    Attaches an animation to an entity, replacing any it already has. The animation is applied on top of the
+    object's current transformation, so moving an animated object should be done with `set_base_transform`.
+    */
+    pub fn set_animation(&mut self, id: EntityId, animation: Animation) {
+        if let Some(animated) = self.animated(id) {
+            animated.animation = Some(animation);
+        }
+    }
+
+    /**
+    Removes an entity's animation, returning the object to its unanimated transformation if it has no clip either.
+    */
+    pub fn clear_animation(&mut self, id: EntityId) -> Option<Animation> {
+        let animation = self.component_mut::<Animated>(id)?.animation.take();
+        self.restore_if_unanimated(id);
+        animation
+    }
+
+    /**
+    Attaches a keyframed clip to an entity and starts playing it from the beginning, replacing any clip it already
+    has. As with `set_animation`, the clip is applied on top of the object's current transformation.
+    */
+    pub fn set_clip(&mut self, id: EntityId, clip: AnimationClip) {
+        if let Some(animated) = self.animated(id) {
+            animated.clip = Some(ClipPlayer::new(clip));
+        }
+    }
+
+    /**
+    Removes an entity's clip, returning the object to its unanimated transformation if it has no animation either.
+    */
+    pub fn clear_clip(&mut self, id: EntityId) -> Option<AnimationClip> {
+        let player = self.component_mut::<Animated>(id)?.clip.take();
+        self.restore_if_unanimated(id);
+        player.map(|player| player.clip)
+    }
+
+    /**
+    Returns an entity's animation, if it has one.
+    */
+    pub fn animation(&self, id: EntityId) -> Option<&Animation> {
+        self.component::<Animated>(id)?.animation.as_ref()
+    }
+
+    /**
+    Returns the transformation of an entity without its animations applied.
+    */
+    pub fn base_transform(&self, id: EntityId) -> Option<Mat4> {
+        self.component::<Animated>(id)
+            .and_then(|animated| animated.base)
+            .or_else(|| Some(self.get(id)?.transformation))
+    }
+
+    /**
+    Returns the player for an entity's clip, which can be used to play, pause and seek it.
+    */
+    pub fn clip_player(&mut self, id: EntityId) -> Option<&mut ClipPlayer> {
+        self.component_mut::<Animated>(id)?.clip.as_mut()
+    }
+
+    /**
+    Returns an entity's animations, attaching an empty set if it doesn't have any yet.
+    */
+    fn animated(&mut self, id: EntityId) -> Option<&mut Animated> {
+        if self.component::<Animated>(id).is_none() {
+            self.insert_component(id, Animated::default());
+        }
+        self.component_mut(id)
+    }
+
+    fn restore_if_unanimated(&mut self, id: EntityId) {
+        let unanimated = self
+            .component::<Animated>(id)
+            .is_some_and(|animated| animated.animation.is_none() && animated.clip.is_none());
+        if !unanimated {
+            return;
+        }
+        if let (
+            Some(Animated {
+                base: Some(base), ..
+            }),
+            Some(obj),
+        ) = (self.remove_component::<Animated>(id), self.object_mut(id))
+        {
+            obj.transformation = base;
+        }
+    }
+
+    /**
+    Sets the transformation an entity's animation is applied on top of. For entities without an animation, this
+    is the same as setting the object's transformation directly.
+    */
+    pub fn set_base_transform(&mut self, id: EntityId, transformation: Mat4) {
+        if let Some(animated) = self.component_mut::<Animated>(id) {
+            animated.base = Some(transformation);
+        } else if let Some(obj) = self.object_mut(id) {
+            obj.transformation = transformation;
+            self.reindex_static(id);
+            self.bvh_stale = true;
+        }
+    }
+
+    /**
+    Returns the number of objects that are still loading.
+    */
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /**
+    Adds terrain to the world, replacing any it already has. Each chunk is added as a static entity tagged
+    `"terrain"`, starting at the highest level of detail until `update_terrain` is called.
+    */
+    pub fn set_terrain(&mut self, terrain: Terrain) {
+        self.remove_terrain();
+
+        let (chunks_x, chunks_z) = terrain.chunk_count();
+        let mut chunks = vec![];
+        for cz in 0..chunks_z {
+            for cx in 0..chunks_x {
+                let id = self.add_named_object(
+                    terrain.chunk_mesh(cx, cz, 0),
+                    Vec3::new(0.0, 0.0, 0.0),
+                    None,
+                    &["terrain"],
+                );
+                self.set_static(id, true);
+                chunks.push((id, 0));
+            }
+        }
+        self.terrain = Some(TerrainChunks { terrain, chunks });
+    }
+
+    /**
+    Removes the world's terrain and the entities drawing it, returning the terrain.
+    */
+    pub fn remove_terrain(&mut self) -> Option<Terrain> {
+        let TerrainChunks { terrain, chunks } = self.terrain.take()?;
+        for (id, _) in chunks {
+            self.remove(id);
+        }
+        Some(terrain)
+    }
+
+    pub fn terrain(&self) -> Option<&Terrain> {
+        self.terrain.as_ref().map(|terrain| &terrain.terrain)
+    }
+
+    /**
+    Re-meshes any terrain chunks whose level of detail should change, given where they're being viewed from.
+    This should be called each frame with the camera's position.
+    */
+    pub fn update_terrain(&mut self, viewer: Vec3) {
+        let Some(TerrainChunks { terrain, chunks }) = &self.terrain else {
+            return;
+        };
+
+        let chunks_x = terrain.chunk_count().0;
+        let changes: Vec<(usize, EntityId, usize, Object)> = chunks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (id, lod))| {
+                let (cx, cz) = (i % chunks_x, i / chunks_x);
+                let wanted = terrain.lod_for(cx, cz, viewer);
+                (wanted != *lod).then(|| (i, *id, wanted, terrain.chunk_mesh(cx, cz, wanted)))
+            })
+            .collect();
+
+        for (i, id, lod, mesh) in changes {
+            self.replace(id, mesh);
+            if let Some(terrain) = &mut self.terrain {
+                terrain.chunks[i].1 = lod;
+            }
+        }
+    }
+
+    /**
+    Returns the height of the terrain's surface at a given X and Z position, for placing objects or the camera on the
+    ground. Returns `None` if the world has no terrain, or the position is outside it.
+    */
+    pub fn terrain_height(&self, x: f64, z: f64) -> Option<f64> {
+        self.terrain()?.height(x, z)
+    }
+
+    /**
+    Draws a line over the scene on the next frame. Like the other debug shapes, the line is cleared by the next
+    `update`, so it needs adding again every frame it should be shown.
+    */
+    pub fn debug_line(&mut self, from: Vec3, to: Vec3, colour: Colour) {
+        self.debug.line(from, to, colour);
+    }
+
+    /**
+    Draws the X, Y and Z axes of a transformation in red, green and blue.
+    */
+    pub fn debug_axes(&mut self, transformation: Mat4) {
+        self.debug.axes(transformation);
+    }
+
+    /**
+    Draws the edges of a world space box.
+    */
+    pub fn debug_aabb(&mut self, aabb: &Aabb, colour: Colour) {
+        self.debug.aabb(aabb, colour);
+    }
+
+    /**
+    Draws the vertex normals of an entity's object in world space, as short yellow lines.
+    */
+    pub fn debug_normals(&mut self, id: EntityId) {
+        let Some(obj) = self.get(id) else {
+            return;
+        };
+        let normal_transformation = obj
+            .transformation
+            .inverse()
+            .map_or(obj.transformation, Mat4::transpose);
+
+        let lines: Vec<(Vec3, Vec3)> = obj
+            .vertices
+            .iter()
+            .zip(obj.normals.iter())
+            .map(|(vertex, normal)| {
+                let from = obj.transformation.transform(*vertex);
+                let direction = normal_transformation
+                    .transform_direction(*normal)
+                    .normalise();
+                (from, from + direction * DEBUG_NORMAL_LENGTH)
+            })
+            .collect();
+        for (from, to) in lines {
+            self.debug.line(from, to, DEBUG_NORMAL_COLOUR);
+        }
+    }
+
+    /**
+    Draws the state of the physics simulation over the scene on the next frame, to see what it's doing:
+    - each body's collider, in green while it's awake, blue while it's asleep, grey if it can't be moved, and orange if
+      it's a trigger
+    - each moving body's velocity, as a cyan line from its centre of mass
+    - the contacts found in the latest step, as red crosses with their normals in magenta
+
+    Like the other debug shapes, it needs calling every frame it should be shown, so it can be switched on and off
+    as the world runs.
+    */
+    pub fn debug_physics(&mut self) {
+        let mut lines = DebugDraw::new();
+        for (id, body) in self.query::<Rigidbody>() {
+            let (Some(collider), Some(obj)) = (&body.collider, self.get(id)) else {
+                continue;
+            };
+            let colour = if body.trigger {
+                DEBUG_TRIGGER_COLOUR
+            } else if self.is_static(id) || body.inverse_mass() == 0.0 {
+                DEBUG_FIXED_COLOUR
+            } else if self.is_asleep(id) {
+                DEBUG_ASLEEP_COLOUR
+            } else {
+                DEBUG_AWAKE_COLOUR
+            };
+            lines.collider(&collider.transformed(obj.transformation), colour);
+
+            if let Some(centre) = self.centre_of_mass(id) {
+                let velocity = body.velocity * DEBUG_VELOCITY_SCALE;
+                if velocity != Vec3::default() {
+                    lines.line(centre, centre + velocity, DEBUG_VELOCITY_COLOUR);
+                }
+            }
+        }
+        for contact in &self.last_contacts {
+            lines.point(contact.point, DEBUG_CONTACT_SIZE, DEBUG_CONTACT_COLOUR);
+            let normal = contact.point + contact.normal * DEBUG_NORMAL_LENGTH;
+            lines.line(contact.point, normal, DEBUG_CONTACT_NORMAL_COLOUR);
+        }
+        for line in lines.lines() {
+            self.debug.line(line.from, line.to, line.colour);
+        }
+    }
+
+    /**
+    Draws a grid of lines `spacing` apart on the ground plane, centred on the origin.
+    */
+    pub fn debug_grid(&mut self, spacing: f64) {
+        self.debug.grid(spacing);
+    }
+
+    /**
+    Returns the debug lines to be drawn this frame.
+    */
+    pub fn debug_lines(&self) -> &[DebugLine] {
+        self.debug.lines()
+    }
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+
+    use crate::{
+        animation::{Channel, Interpolation, Keyframe, LoopMode},
+        camera::Camera,
+        config::RendererConfig,
+        geometry::{frustum::Plane, hull::ConvexHull, obb::Obb},
+        mat4::Mat4,
+        object::{Face, Object, Texture},
+        renderer::Renderer,
+        vec::{
+            vec2::Vec2,
+            vec3::{ORIGIN, X_AXIS, Y_AXIS, Z_AXIS},
+        },
+        water::Wave,
+    };
+
+    fn empty_object() -> Object {
+        Object::new(vec![], vec![], vec![], vec![], Texture::new(0, 0, vec![]))
+    }
+
+    #[test]
+    fn add_object() {
+        let mut world = World::new();
+        let object = empty_object();
+        world.add_object(object, ORIGIN);
+        assert_eq!(world.len(), 1);
+    }
+
+    #[test]
+    fn remove_object() {
+        let mut world = World::new();
+        let id = world.add_object(empty_object(), ORIGIN);
+        assert!(world.get(id).is_some());
+
+        assert!(world.remove(id).is_some());
+        assert!(world.get(id).is_none());
+        assert!(world.remove(id).is_none());
+        assert!(world.is_empty());
+    }
+
+    #[test]
+    fn stale_handles_are_rejected() {
+        let mut world = World::new();
+        let old = world.add_object(empty_object(), ORIGIN);
+        world.remove(old);
+
+        // The new entity reuses the old slot, but the old handle must not reach it
+        let new = world.add_object(empty_object(), ORIGIN);
+        assert!(world.get(new).is_some());
+        assert!(world.get(old).is_none());
+        assert!(!world.contains(old));
+    }
+
+    #[test]
+    fn iterate_objects() {
+        let mut world = World::new();
+        let a = world.add_object(empty_object(), ORIGIN);
+        let b = world.add_object(empty_object(), ORIGIN);
+        world.remove(a);
+
+        let ids: Vec<EntityId> = world.iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec![b]);
+    }
+
+    #[test]
+    fn update_animation() {
+        let mut world = World::new();
+        let id = world.add_object(empty_object(), Vec3::new(0.0, 5.0, 0.0));
+        world.set_animation(
+            id,
+            Animation::new(vec![Channel::Oscillate {
+                direction: Vec3::new(1.0, 0.0, 0.0),
+                amplitude: 1.0,
+                frequency: 1.0,
+            }]),
+        );
+
+        world.update(0.25);
+        let position = world.get(id).unwrap().transformation.transform(ORIGIN);
+        assert!((position - Vec3::new(1.0, 5.0, 0.0)).length() < 1e-9);
+
+        world.clear_animation(id);
+        let position = world.get(id).unwrap().transformation.transform(ORIGIN);
+        assert_eq!(position, Vec3::new(0.0, 5.0, 0.0));
+    }
+
+    #[test]
+    fn play_clip() {
+        let mut world = World::new();
+        let id = world.add_object(empty_object(), ORIGIN);
+        let clip = AnimationClip::new(
+            vec![
+                Keyframe {
+                    time: 0.0,
+                    transform: Transform::identity(),
+                },
+                Keyframe {
+                    time: 1.0,
+                    transform: Transform::from_translation(Vec3::new(0.0, 0.0, 4.0)),
+                },
+            ],
+            Interpolation::Linear,
+            LoopMode::Once,
+        );
+        world.set_clip(id, clip);
+
+        world.update(0.5);
+        let position = world.get(id).unwrap().transformation.transform(ORIGIN);
+        assert_eq!(position, Vec3::new(0.0, 0.0, 2.0));
+
+        world.clip_player(id).unwrap().pause();
+        world.update(0.5);
+        let position = world.get(id).unwrap().transformation.transform(ORIGIN);
+        assert_eq!(position, Vec3::new(0.0, 0.0, 2.0));
+
+        world.clear_clip(id);
+        assert_eq!(world.get(id).unwrap().transformation, Mat4::identity());
+    }
+
+    #[test]
+    fn reload_object() {
+        let mut world = World::new();
+        let mut object = empty_object();
+        object.source = Some(String::from("cube"));
+        let id = world.add_object(object.clone(), Vec3::new(1.0, 0.0, 0.0));
+
+        Arc::make_mut(&mut object.vertices).push(ORIGIN);
+        world.reload(&object);
+
+        assert_eq!(world.get(id).unwrap().vertices.len(), 1);
+        assert_eq!(
+            world.get(id).unwrap().transformation,
+            Mat4::identity().translate(Vec3::new(1.0, 0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn query_bounds() {
+        let mut world = World::new();
+        let mut point = empty_object();
+        Arc::make_mut(&mut point.vertices).push(ORIGIN);
+        let visible = world.add_object(point.clone(), ORIGIN);
+        let hidden = world.add_object(point, Vec3::new(0.0, 0.0, -30.0));
+
+        // Queries work before the hierarchy has been built. This is the camera used by the demo, which sees the origin
+        let frustum = Camera::new(Vec3::new(0.0, -1.0, 20.0)).frustum(640, 480);
+        assert_eq!(world.query_frustum(&frustum), vec![visible]);
+
+        world.update(0.0);
+        let around_hidden = Aabb::new(Vec3::new(-1.0, -1.0, -31.0), Vec3::new(1.0, 1.0, -29.0));
+        assert_eq!(world.query_aabb(&around_hidden), vec![hidden]);
+
+        // Moving an animated entity refits the hierarchy around its new position
+        world.set_animation(
+            hidden,
+            Animation::new(vec![Channel::Oscillate {
+                direction: Vec3::new(1.0, 0.0, 0.0),
+                amplitude: 10.0,
+                frequency: 1.0,
+            }]),
+        );
+        world.update(0.25);
+        assert!(world.query_aabb(&around_hidden).is_empty());
+        assert_eq!(world.bvh().len(), 2);
+    }
+
+    #[test]
+    fn query_static_entities() {
+        let mut world = World::new();
+        let mut point = empty_object();
+        Arc::make_mut(&mut point.vertices).push(ORIGIN);
+        let id = world.add_object(point, Vec3::new(100.0, 0.0, 0.0));
+        world.set_static(id, true);
+        world.update(0.0);
+
+        // Static entities are found through the grid, not the hierarchy
+        assert!(world.bvh().is_empty());
+        let around =
+            |x: f64| Aabb::new(Vec3::new(x - 1.0, -1.0, -1.0), Vec3::new(x + 1.0, 1.0, 1.0));
+        assert_eq!(world.query_aabb(&around(100.0)), vec![id]);
+
+        world.set_base_transform(id, Mat4::identity().translate(Vec3::new(-100.0, 0.0, 0.0)));
+        assert!(world.query_aabb(&around(100.0)).is_empty());
+        assert_eq!(world.query_aabb(&around(-100.0)), vec![id]);
+
+        world.remove(id);
+        assert!(world.query_aabb(&around(-100.0)).is_empty());
+    }
+
+    fn quad() -> Object {
+        // A 2x2 square in the XZ plane, facing up
+        let mut quad = empty_object();
+        quad.vertices = Arc::new(vec![
+            Vec3::new(-1.0, 0.0, -1.0),
+            Vec3::new(-1.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, -1.0),
+        ]);
+        quad.faces = Arc::new(vec![
+            Face {
+                vertices: (0, 1, 2),
+                tex_coords: (0, 0, 0),
+                normals: (0, 0, 0),
+                material: 0,
+            },
+            Face {
+                vertices: (0, 2, 3),
+                tex_coords: (0, 0, 0),
+                normals: (0, 0, 0),
+                material: 0,
+            },
+        ]);
+        quad
+    }
+
+    #[test]
+    fn raycast_nearest_face() {
+        let mut world = World::new();
+        let lower = world.add_object(quad(), Vec3::new(0.0, -5.0, 0.0));
+        let upper = world.place_object(
+            quad(),
+            Mat4::identity()
+                .scale(Vec3::new(2.0, 2.0, 2.0))
+                .translate(Vec3::new(0.0, 2.0, 0.0)),
+        );
+        let down = Ray::new(Vec3::new(0.5, 10.0, 0.5), Vec3::new(0.0, -1.0, 0.0));
+
+        let hit = world.raycast(&down).unwrap();
+        assert_eq!(hit.entity, upper);
+        assert!((hit.point - Vec3::new(0.5, 2.0, 0.5)).length() < 1e-9);
+        assert!((hit.normal - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-9);
+        assert!((hit.distance - 8.0).abs() < 1e-9);
+
+        // Beside both quads, this time going through the hierarchy rather than testing every entity
+        world.update(0.0);
+        let edge = Ray::new(Vec3::new(0.0, 10.0, -2.5), Vec3::new(0.0, -1.0, 0.0));
+        assert!(world.raycast(&edge).is_none());
+
+        world.remove(upper);
+        assert_eq!(world.raycast(&down).unwrap().entity, lower);
+        assert!(world.raycast_within(&down, 10.0).is_none());
+    }
+
+    #[test]
+    fn edited_meshes_refresh_their_bounds() {
+        let mut world = World::new();
+        let id = world.add_object(quad(), ORIGIN);
+        world.update(0.0);
+
+        // Stretching the quad well past where it was cached, then dropping one of its faces
+        if let Some(mut obj) = world.get_mut(id) {
+            Arc::make_mut(&mut obj.vertices)[2] = Vec3::new(5.0, 0.0, 5.0);
+            Arc::make_mut(&mut obj.faces).pop();
+        }
+        assert_eq!(
+            world.bounds(id),
+            Some(Aabb::new(
+                Vec3::new(-1.0, 0.0, -1.0),
+                Vec3::new(5.0, 0.0, 5.0)
+            ))
+        );
+        assert_eq!(
+            world.query_aabb(&Aabb::new(
+                Vec3::new(4.0, -1.0, 4.0),
+                Vec3::new(6.0, 1.0, 6.0)
+            )),
+            vec![id]
+        );
+
+        let down = |x, z| Ray::new(Vec3::new(x, 10.0, z), Vec3::new(0.0, -1.0, 0.0));
+        assert_eq!(world.raycast(&down(1.0, 2.0)).unwrap().face, 0);
+        assert!(world.raycast(&down(0.5, -0.5)).is_none());
+    }
+
+    #[test]
+    fn names_and_tags() {
+        let mut world = World::new();
+        let player = world.add_named_object(empty_object(), ORIGIN, Some("player"), &[]);
+        let a = world.add_named_object(empty_object(), ORIGIN, None, &["enemy", "flying"]);
+        let b = world.add_named_object(empty_object(), ORIGIN, Some("boss"), &["enemy"]);
+
+        assert_eq!(world.find_by_name("player"), Some(player));
+        assert_eq!(world.find_by_name("nobody"), None);
+
+        let enemies: Vec<EntityId> = world.iter_tagged("enemy").map(|(id, _)| id).collect();
+        assert_eq!(enemies, vec![a, b]);
+
+        assert!(world.remove_tag(a, "enemy"));
+        assert!(!world.remove_tag(a, "enemy"));
+        assert_eq!(world.tags(a).collect::<Vec<_>>(), vec!["flying"]);
+
+        // Names and tags don't carry over to entities which reuse a removed entity's slot
+        world.remove(b);
+        let reused = world.add_object(empty_object(), ORIGIN);
+        assert_eq!(world.find_by_name("boss"), None);
+        assert_eq!(world.name(reused), None);
+        assert!(!world.has_tag(reused, "enemy"));
+    }
+
+    #[test]
+    fn sort_by_layer_and_order() {
+        let mut world = World::new();
+        let overlay = world.add_object(empty_object(), ORIGIN);
+        let late = world.add_object(empty_object(), ORIGIN);
+        let early = world.add_object(empty_object(), ORIGIN);
+        let sky = world.add_object(empty_object(), ORIGIN);
+        world.set_layer(overlay, Layer::Overlay);
+        world.set_layer(sky, Layer::Background);
+        world.set_draw_order(late, 1);
+        world.set_draw_order(early, -1);
+
+        let mut ids: Vec<EntityId> = world.iter().map(|(id, _)| id).collect();
+        world.sort_for_drawing(&mut ids);
+        assert_eq!(ids, vec![sky, early, late, overlay]);
+    }
+
+    #[test]
+    fn texture_mapping() {
+        let mut world = World::new();
+        let id = world.add_object(empty_object(), ORIGIN);
+        assert_eq!(world.texture_mapping(id), None);
+        world.set_texture_mapping(id, Some(TextureMapping::Affine));
+        assert_eq!(world.texture_mapping(id), Some(TextureMapping::Affine));
+
+        // Reused slots go back to following the renderer
+        world.remove(id);
+        let reused = world.add_object(empty_object(), ORIGIN);
+        assert_eq!(world.texture_mapping(reused), None);
+    }
+
+    #[test]
+    fn hide_and_show() {
+        let mut world = World::new();
+        let id = world.add_object(empty_object(), ORIGIN);
+        assert!(world.is_visible(id));
+
+        world.set_visible(id, false);
+        assert!(!world.is_visible(id));
+        assert!(world.get(id).is_some());
+
+        world.set_visible(id, true);
+        assert!(world.is_visible(id));
+
+        world.remove(id);
+        assert!(!world.is_visible(id));
+    }
+
+    #[test]
+    fn worlds_can_be_sent_between_threads() {
+        fn assert_send<T: Send>() {}
+        assert_send::<World>();
+
+        let mut world = World::new();
+        world.add_object(empty_object(), ORIGIN);
+        let world = std::thread::spawn(move || world).join().unwrap();
+        assert_eq!(world.iter().count(), 1);
+    }
+
+    #[test]
+    fn components() {
+        let mut world = World::new();
+        let lit = world.add_object(empty_object(), ORIGIN);
+        let both = world.add_object(empty_object(), ORIGIN);
+        let light = Light::Point {
+            position: ORIGIN,
+            colour: Vec3::new(1.0, 1.0, 1.0),
+            intensity: 1.0,
+            range: 10.0,
+        };
+        world.insert_component(lit, light);
+        world.insert_component(both, light);
+        world.insert_component(both, 5_u32);
+
+        assert_eq!(world.query::<Light>().count(), 2);
+        let pairs: Vec<EntityId> = world
+            .query_pair::<Light, u32>()
+            .map(|(id, ..)| id)
+            .collect();
+        assert_eq!(pairs, vec![both]);
+
+        for (_, count) in world.query_mut::<u32>() {
+            *count += 1;
+        }
+        assert_eq!(world.component::<u32>(both), Some(&6));
+
+        // Components don't outlive their entity, even when its slot is reused
+        world.remove(both);
+        let reused = world.add_object(empty_object(), ORIGIN);
+        assert!(!world.has_component::<u32>(reused));
+        assert_eq!(world.remove_component::<Light>(lit), Some(light));
+        assert_eq!(world.query::<Light>().count(), 0);
+    }
+
+    #[test]
+    fn spawn_prefabs() {
+        let mut world = World::new();
+        let prefab = Prefab::new(quad())
+            .with_offset(Mat4::identity().translate(Vec3::new(0.0, 1.0, 0.0)))
+            .with_tag("prop")
+            .with_component(3_u32);
+
+        let first = world.spawn(&prefab, ORIGIN);
+        let second = world.spawn(&prefab, Vec3::new(10.0, 0.0, 0.0));
+        assert_eq!(
+            world.bounds(second),
+            Some(Aabb::new(
+                Vec3::new(9.0, 1.0, -1.0),
+                Vec3::new(11.0, 1.0, 1.0)
+            ))
+        );
+        assert_eq!(world.iter_tagged("prop").count(), 2);
+
+        // Each spawned entity has its own copy of the prefab's components
+        *world.component_mut::<u32>(first).unwrap() += 1;
+        assert_eq!(world.component::<u32>(second), Some(&3));
+
+        // ...but they share the prefab's mesh and texture rather than copying them
+        let (first, second) = (world.get(first).unwrap(), world.get(second).unwrap());
+        assert!(Arc::ptr_eq(&first.vertices, &second.vertices));
+        assert!(Arc::ptr_eq(&first.texture, &prefab.object().texture));
+    }
+
+    #[test]
+    fn behaviours_run_each_update() {
+        let mut world = World::new();
+        let id = world.add_object(empty_object(), ORIGIN);
+        world.add_behaviour(id, |id, world: &mut World, _, delta| {
+            let base = world.base_transform(id).unwrap();
+            world.set_base_transform(id, base.translate(Vec3::new(delta, 0.0, 0.0)));
+        });
+        // Behaviours can remove their own entity
+        world.add_behaviour(id, |id, world: &mut World, time, _| {
+            if time >= 3.0 {
+                world.remove(id);
+            }
+        });
+
+        world.update(1.0);
+        world.update(1.0);
+        let position = world.get(id).unwrap().transformation.transform(ORIGIN);
+        assert_eq!(position, Vec3::new(2.0, 0.0, 0.0));
+
+        world.update(1.0);
+        assert!(!world.contains(id));
+        assert_eq!(world.query::<Behaviours>().count(), 0);
+    }
+
+    #[test]
+    fn vertex_modifiers() {
+        let mut world = World::new();
+        let id = world.add_object(Water::new(2.0, 1).mesh(), ORIGIN);
+        let flat = world.bounds(id).unwrap();
+        world.set_vertex_modifier(id, 0.5, |position: Vec3, normal: Vec3, time: f64| {
+            position + normal * (0.5 * time.sin())
+        });
+
+        let modifier = world.vertex_modifier(id).unwrap();
+        assert_eq!(modifier.modify(ORIGIN, Y_AXIS, 0.0), ORIGIN);
+        assert_eq!(world.bounds(id).unwrap(), flat.inflate(0.5));
+
+        // Replacing the modifier doesn't grow the bounds twice, and removing it shrinks them back
+        world.set_vertex_modifier(id, 0.25, |position: Vec3, _, _| position);
+        assert_eq!(world.bounds(id).unwrap(), flat.inflate(0.25));
+        world.remove_vertex_modifier(id);
+        assert!(world.vertex_modifier(id).is_none());
+        assert_eq!(world.bounds(id).unwrap(), flat);
+    }
+
+    #[test]
+    fn rigidbodies_fall() {
+        let mut world = World::new();
+        world.physics.gravity = Vec3::new(0.0, -10.0, 0.0);
+        let falling = world.add_object(quad(), ORIGIN);
+        world.insert_component(falling, Rigidbody::new(quad()));
+        let fixed = world.add_object(quad(), ORIGIN);
+        world.insert_component(fixed, Rigidbody::new(quad()).with_mass(0.0));
+
+        world.step_physics(0.5);
+        world.step_physics(0.5);
+
+        let position = |id| world.get(id).unwrap().transformation.transform(ORIGIN);
+        assert_eq!(position(falling), Vec3::new(0.0, -7.5, 0.0));
+        assert_eq!(position(fixed), ORIGIN);
+        let body = world.component::<Rigidbody>(falling).unwrap();
+        assert_eq!(body.velocity, Vec3::new(0.0, -10.0, 0.0));
+    }
+
+    #[test]
+    fn fixed_steps_interpolate() {
+        let mut world = World::new();
+        world.physics.gravity = Vec3::default();
+        world.physics.timestep = 0.1;
+        let id = world.add_object(quad(), ORIGIN);
+        let mut body = Rigidbody::new(quad());
+        body.velocity = Vec3::new(1.0, 0.0, 0.0);
+        world.insert_component(id, body);
+
+        // Two whole steps are taken, and the body is drawn halfway between them
+        world.advance_physics(0.25);
+        let position = world.get(id).unwrap().transformation.transform(ORIGIN);
+        assert!((position.x - 0.2).abs() < 1e-9);
+        let drawn = world.render_transformation(id).unwrap().transform(ORIGIN);
+        assert!((drawn.x - 0.15).abs() < 1e-9, "{}", drawn);
+
+        // Not enough time for another step, so it's only drawn further along
+        world.advance_physics(0.04);
+        let drawn = world.render_transformation(id).unwrap().transform(ORIGIN);
+        assert!((drawn.x - 0.19).abs() < 1e-9, "{}", drawn);
+    }
+
+    #[test]
+    fn updates_interpolate() {
+        let mut world = World::new();
+        let id = world.add_object(empty_object(), ORIGIN);
+        world.add_behaviour(id, |id, world: &mut World, _, delta| {
+            let base = world.base_transform(id).unwrap();
+            world.set_base_transform(id, base.translate(Vec3::new(delta, 0.0, 0.0)));
+        });
+
+        // Worlds updated by hand are drawn where they are
+        world.update(1.0);
+        world.update(1.0);
+        let drawn = world.render_transformation(id).unwrap().transform(ORIGIN);
+        assert_eq!(drawn, Vec3::new(2.0, 0.0, 0.0));
+
+        // Halfway to the next update, the entity is drawn halfway between the last two
+        world.set_update_alpha(0.5);
+        let drawn = world.render_transformation(id).unwrap().transform(ORIGIN);
+        assert!((drawn.x - 1.5).abs() < 1e-9, "{}", drawn);
+    }
+
+    #[test]
+    fn casts_find_nearest_body() {
+        let mut world = World::new();
+        let sphere = Collider::Sphere {
+            centre: ORIGIN,
+            radius: 1.0,
+        };
+        let near = world.add_object(quad(), Vec3::new(0.0, 0.0, -5.0));
+        world.insert_component(near, Rigidbody::new(quad()).with_collider(sphere.clone()));
+        let far = world.add_object(quad(), Vec3::new(0.0, 0.0, -10.0));
+        world.insert_component(far, Rigidbody::new(quad()).with_collider(sphere.clone()));
+
+        let hit = world.raycast_bodies(&Ray::new(ORIGIN, -Z_AXIS)).unwrap();
+        assert_eq!(hit.entity, near);
+        assert_eq!(hit.point, Vec3::new(0.0, 0.0, -4.0));
+        assert_eq!(hit.normal, Z_AXIS);
+        assert_eq!(hit.distance, 4.0);
+
+        // A sphere passing beside the nearer body still clips it
+        let hit = world
+            .sphere_cast(Vec3::new(1.5, 0.0, 0.0), 1.0, -Z_AXIS)
+            .unwrap();
+        assert_eq!(hit.entity, near);
+        let from_centre = hit.point - Vec3::new(0.0, 0.0, -5.0);
+        assert!((from_centre.length() - 1.0).abs() < 1e-9);
+        assert!(hit.distance < 5.0);
+        assert!(world.raycast_bodies(&Ray::new(ORIGIN, Y_AXIS)).is_none());
+    }
+
+    #[test]
+    fn pendulums_keep_their_length() {
+        let mut world = World::new();
+        let sphere = Collider::Sphere {
+            centre: ORIGIN,
+            radius: 0.5,
+        };
+        let bob = world.add_object(quad(), Vec3::new(2.0, 0.0, 0.0));
+        world.insert_component(bob, Rigidbody::new(quad()).with_collider(sphere.clone()));
+        world.add_joint(bob, Joint::distance(None, 2.0));
+
+        let mut lowest: f64 = 0.0;
+        for _ in 0..120 {
+            world.step_physics(1.0 / 60.0);
+            let position = world.get(bob).unwrap().transformation.transform(ORIGIN);
+            assert!((position.length() - 2.0).abs() < 0.02, "{}", position);
+            lowest = lowest.min(position.y);
+        }
+        // It swings down through the bottom of the arc rather than falling
+        assert!(lowest < -1.9, "{}", lowest);
+        assert_eq!(world.joints(bob).len(), 1);
+    }
+
+    #[test]
+    fn ropes_go_slack() {
+        let mut world = World::new();
+        let weight = world.add_object(quad(), Vec3::new(0.0, -1.0, 0.0));
+        world.insert_component(weight, Rigidbody::new(quad()));
+        world.add_joint(weight, Joint::rope(None, 3.0));
+
+        // While the rope is slack the weight falls freely, until the rope catches it
+        world.step_physics(0.1);
+        let height = |world: &World| {
+            world
+                .get(weight)
+                .unwrap()
+                .transformation
+                .transform(ORIGIN)
+                .y
+        };
+        assert!((height(&world) - (-1.0 - 9.81 * 0.01)).abs() < 1e-9);
+        for _ in 0..100 {
+            world.step_physics(1.0 / 60.0);
+        }
+        assert!((height(&world) + 3.0).abs() < 0.01, "{}", height(&world));
+
+        world.clear_joints(weight);
+        world.step_physics(0.1);
+        assert!(height(&world) < -3.05);
+    }
+
+    #[test]
+    fn hinged_doors_swing() {
+        let mut world = World::new();
+        world.physics.gravity = Vec3::default();
+        // A door hung by its left edge
+        let door = world.add_object(quad(), Vec3::new(1.0, 0.0, 0.0));
+        let slab = Collider::Obb(Obb::new(ORIGIN, Vec3::new(1.0, 2.0, 0.1)));
+        let mut body = Rigidbody::new(quad()).with_collider(slab);
+        body.velocity = Vec3::new(0.0, 0.0, -1.0);
+        body.angular_velocity = Vec3::new(1.0, 0.0, 0.0);
+        world.insert_component(door, body);
+        let hinge = Joint::hinge(None, Y_AXIS).with_anchors(Vec3::new(-1.0, 0.0, 0.0), ORIGIN);
+        world.add_joint(door, hinge);
+
+        for _ in 0..60 {
+            world.step_physics(1.0 / 60.0);
+        }
+
+        // It swings around the hinge, staying upright with its edge in place
+        let transformation = world.get(door).unwrap().transformation;
+        let edge = transformation.transform(Vec3::new(-1.0, 0.0, 0.0));
+        assert!(edge.length() < 0.01, "{}", edge);
+        let up = transformation.transform_direction(Y_AXIS);
+        assert!(up.dot(Y_AXIS) > 0.999, "{}", up);
+        let centre = transformation.transform(ORIGIN);
+        assert!(centre.z < -0.5, "{}", centre);
+        let spin = world.component::<Rigidbody>(door).unwrap().angular_velocity;
+        assert!(spin.cross_product(Y_AXIS).length() < 0.02, "{}", spin);
+    }
+
+    #[test]
+    fn rotated_bodies_spin_around_their_own_axes() {
+        let mut world = World::new();
+        world.physics.gravity = Vec3::default();
+        // A long box lying along its local X axis, placed turned so it lies along the world's Z axis
+        let placed = Mat4::identity()
+            .rotate(Y_AXIS, std::f64::consts::FRAC_PI_2)
+            .translate(Vec3::new(0.0, 5.0, 0.0));
+        let plank = world.place_object(quad(), placed);
+        let long = Collider::Obb(Obb::new(ORIGIN, Vec3::new(2.0, 0.25, 0.25)));
+        world.insert_component(plank, Rigidbody::new(quad()).with_collider(long));
+
+        // Twisting it around its length spins it far faster than the same torque across its length
+        let body = world.component_mut::<Rigidbody>(plank).unwrap();
+        body.apply_torque(Z_AXIS + X_AXIS);
+        world.step_physics(1.0 / 60.0);
+        let spin = world
+            .component::<Rigidbody>(plank)
+            .unwrap()
+            .angular_velocity;
+        assert!(spin.z > spin.x * 10.0, "{}", spin);
+        let body = world.component::<Rigidbody>(plank).unwrap();
+        let expected = Transform::from_mat4(world.get(plank).unwrap().transformation).rotation;
+        assert!((body.orientation().dot(expected).abs() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn spheres_rest_on_planes() {
+        let mut world = World::new();
+        let floor = world.add_object(quad(), ORIGIN);
+        world.insert_component(
+            floor,
+            Rigidbody::new(quad())
+                .with_mass(0.0)
+                .with_collider(Collider::Plane(Plane::new(Y_AXIS, 0.0))),
+        );
+        let sphere = Collider::Sphere {
+            centre: ORIGIN,
+            radius: 1.0,
+        };
+        let lower = world.add_object(quad(), Vec3::new(0.0, 3.0, 0.0));
+        world.insert_component(lower, Rigidbody::new(quad()).with_collider(sphere.clone()));
+        let upper = world.add_object(quad(), Vec3::new(0.0, 6.0, 0.0));
+        world.insert_component(upper, Rigidbody::new(quad()).with_collider(sphere.clone()));
+
+        for _ in 0..600 {
+            world.step_physics(1.0 / 60.0);
+        }
+
+        // The spheres end up stacked on the floor, rather than falling through it or each other
+        let height = |id| world.get(id).unwrap().transformation.transform(ORIGIN).y;
+        assert!((height(lower) - 1.0).abs() < 0.1, "{}", height(lower));
+        assert!((height(upper) - 3.0).abs() < 0.2, "{}", height(upper));
+    }
+
+    #[test]
+    fn resting_bodies_sleep() {
+        let mut world = World::new();
+        let floor = world.add_object(quad(), ORIGIN);
+        world.insert_component(
+            floor,
+            Rigidbody::new(quad())
+                .with_mass(0.0)
+                .with_collider(Collider::Plane(Plane::new(Y_AXIS, 0.0))),
+        );
+        let sphere = Collider::Sphere {
+            centre: ORIGIN,
+            radius: 1.0,
+        };
+        let add_ball = |world: &mut World, height: f64| {
+            let id = world.add_object(quad(), Vec3::new(0.0, height, 0.0));
+            let body = Rigidbody::new(quad())
+                .with_collider(sphere.clone())
+                .with_restitution(0.0);
+            world.insert_component(id, body);
+            id
+        };
+        let position = |world: &World, id| world.get(id).unwrap().transformation.transform(ORIGIN);
+        let lower = add_ball(&mut world, 1.0);
+        let upper = add_ball(&mut world, 3.0);
+        for _ in 0..120 {
+            world.step_physics(1.0 / 60.0);
+        }
+
+        // The stack settles and goes to sleep, still touching
+        assert!(world.is_asleep(lower) && world.is_asleep(upper));
+        let resting = position(&world, lower);
+        world.drain_collision_events();
+        world.step_physics(1.0 / 60.0);
+        assert_eq!(position(&world, lower), resting);
+        assert!(world.drain_collision_events().is_empty());
+
+        // Dropping another ball on top wakes the whole stack
+        add_ball(&mut world, 6.0);
+        let mut woken = false;
+        for _ in 0..60 {
+            world.step_physics(1.0 / 60.0);
+            woken |= !world.is_asleep(lower);
+        }
+        assert!(woken);
+
+        // And so does pushing it
+        for _ in 0..120 {
+            world.step_physics(1.0 / 60.0);
+        }
+        assert!(world.is_asleep(lower));
+        let body = world.component_mut::<Rigidbody>(lower).unwrap();
+        body.apply_impulse(Vec3::new(1.0, 0.0, 0.0));
+        world.step_physics(1.0 / 60.0);
+        assert!(!world.is_asleep(lower));
+        assert!(position(&world, lower).x > resting.x);
+    }
+
+    #[test]
+    fn collisions_keep_momentum() {
+        for (restitution, expected) in [(1.0, (-1.5, 1.5)), (0.0, (0.75, 0.75))] {
+            let mut world = World::new();
+            world.physics.gravity = Vec3::default();
+            let sphere = Collider::Sphere {
+                centre: ORIGIN,
+                radius: 1.0,
+            };
+            let moving = world.add_object(quad(), Vec3::new(0.0, 0.0, 0.0));
+            let mut body = Rigidbody::new(quad())
+                .with_collider(sphere.clone())
+                .with_restitution(restitution);
+            body.velocity = Vec3::new(3.0, 0.0, 0.0);
+            world.insert_component(moving, body);
+            // Three times heavier, so it's only sped up by a third as much
+            let heavy = world.add_object(quad(), Vec3::new(2.05, 0.0, 0.0));
+            let body = Rigidbody::new(quad())
+                .with_collider(sphere.clone())
+                .with_restitution(restitution)
+                .with_mass(3.0);
+            world.insert_component(heavy, body);
+
+            world.step_physics(0.1);
+            let velocity = |id| world.component::<Rigidbody>(id).unwrap().velocity.x;
+            assert!((velocity(moving) - expected.0).abs() < 1e-9);
+            assert!((velocity(heavy) - expected.1).abs() < 1e-9);
+            // The total momentum is the same as before they hit
+            assert!((velocity(moving) + velocity(heavy) * 3.0 - 3.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn boxes_tumble_onto_their_faces() {
+        let mut world = World::new();
+        let floor = world.add_object(quad(), ORIGIN);
+        world.insert_component(
+            floor,
+            Rigidbody::new(quad())
+                .with_mass(0.0)
+                .with_collider(Collider::Plane(Plane::new(Y_AXIS, 0.0))),
+        );
+        // Dropped tilted, so it lands on one edge
+        let tilted = Mat4::identity()
+            .rotate(Z_AXIS, 0.5)
+            .translate(Vec3::new(0.0, 3.0, 0.0));
+        let id = world.place_object(quad(), tilted);
+        let cube = Collider::Obb(Obb::new(ORIGIN, Vec3::new(1.0, 1.0, 1.0)));
+        world.insert_component(
+            id,
+            Rigidbody::new(quad())
+                .with_collider(cube)
+                .with_restitution(0.0),
+        );
+
+        let mut spun = false;
+        for _ in 0..600 {
+            world.step_physics(1.0 / 60.0);
+            let body = world.component::<Rigidbody>(id).unwrap();
+            spun |= body.angular_velocity.length() > 0.5;
+        }
+
+        // It falls over onto a face and settles there, rather than balancing on its edge
+        assert!(spun);
+        let transformation = world.get(id).unwrap().transformation;
+        let up = transformation.transform_direction(Y_AXIS);
+        let lined_up = [X_AXIS, Y_AXIS]
+            .iter()
+            .any(|axis| up.dot(*axis).abs() > 0.999);
+        assert!(lined_up, "{}", up);
+        let height = transformation.transform(ORIGIN).y;
+        assert!((height - 1.0).abs() < 0.05, "{}", height);
+    }
+
+    #[test]
+    fn hulls_settle_on_their_base() {
+        let mut world = World::new();
+        let floor = world.add_object(quad(), ORIGIN);
+        world.insert_component(
+            floor,
+            Rigidbody::new(quad())
+                .with_mass(0.0)
+                .with_collider(Collider::Plane(Plane::new(Y_AXIS, 0.0))),
+        );
+        // A squat pyramid, which has to land on its base
+        let pyramid = ConvexHull::from_points(&[
+            Vec3::new(-1.0, -0.25, -1.0),
+            Vec3::new(1.0, -0.25, -1.0),
+            Vec3::new(1.0, -0.25, 1.0),
+            Vec3::new(-1.0, -0.25, 1.0),
+            Vec3::new(0.0, 0.75, 0.0),
+        ])
+        .unwrap();
+        let id = world.add_object(quad(), Vec3::new(0.0, 2.0, 0.0));
+        world.insert_component(
+            id,
+            Rigidbody::new(quad())
+                .with_collider(Collider::Hull(pyramid.clone()))
+                .with_restitution(0.0),
+        );
+
+        for _ in 0..300 {
+            world.step_physics(1.0 / 60.0);
+        }
+
+        let transformation = world.get(id).unwrap().transformation;
+        let lowest = pyramid
+            .points()
+            .iter()
+            .map(|point| transformation.transform(*point).y)
+            .fold(f64::INFINITY, f64::min);
+        assert!(lowest.abs() < 0.05, "{}", lowest);
+        let up = transformation.transform_direction(Y_AXIS);
+        assert!(up.y > 0.999, "{}", up);
+    }
+
+    #[test]
+    fn collisions_send_events() {
+        let mut world = World::new();
+        world.physics.gravity = Vec3::default();
+        let sphere = Collider::Sphere {
+            centre: ORIGIN,
+            radius: 1.0,
+        };
+        let moving = world.add_object(quad(), ORIGIN);
+        let mut body = Rigidbody::new(quad())
+            .with_collider(sphere.clone())
+            .with_restitution(1.0);
+        body.velocity = Vec3::new(3.0, 0.0, 0.0);
+        world.insert_component(moving, body);
+        let still = world.add_object(quad(), Vec3::new(2.05, 0.0, 0.0));
+        world.insert_component(still, Rigidbody::new(quad()).with_collider(sphere.clone()));
+
+        let hits = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = hits.clone();
+        world.on_collision(move |_, event| {
+            if matches!(event, CollisionEvent::Begin { .. }) {
+                counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+        });
+
+        // They hit on the first step, and have bounced apart by the next
+        world.step_physics(0.1);
+        let events = world.drain_collision_events();
+        assert_eq!(events.len(), 1);
+        let CollisionEvent::Begin { a, b, contact } = events[0] else {
+            panic!("{:?}", events[0]);
+        };
+        assert_eq!((a, b), (moving, still));
+        assert_eq!(contact.normal, Vec3::new(1.0, 0.0, 0.0));
+        assert!(world.drain_collision_events().is_empty());
+
+        world.step_physics(0.1);
+        let events = world.drain_collision_events();
+        assert_eq!(
+            events,
+            vec![CollisionEvent::End {
+                a: moving,
+                b: still
+            }]
+        );
+        assert_eq!(hits.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn triggers_report_without_blocking() {
+        let mut world = World::new();
+        world.physics.gravity = Vec3::default();
+        let zone = world.add_object(quad(), Vec3::new(5.0, 0.0, 0.0));
+        let volume = Collider::Obb(Obb::new(ORIGIN, Vec3::new(1.0, 1.0, 1.0)));
+        world.insert_component(
+            zone,
+            Rigidbody::new(quad())
+                .with_mass(0.0)
+                .with_collider(volume)
+                .with_trigger(true),
+        );
+        let ball = world.add_object(quad(), ORIGIN);
+        let mut body = Rigidbody::new(quad()).with_collider(Collider::Sphere {
+            centre: ORIGIN,
+            radius: 0.5,
+        });
+        body.velocity = Vec3::new(10.0, 0.0, 0.0);
+        world.insert_component(ball, body);
+
+        let mut events = vec![];
+        for _ in 0..10 {
+            world.step_physics(0.1);
+            events.extend(world.drain_collision_events());
+        }
+
+        // The ball goes straight through, and is reported going in and coming out again
+        let enter = CollisionEvent::Enter {
+            trigger: zone,
+            other: ball,
+        };
+        let exit = CollisionEvent::Exit {
+            trigger: zone,
+            other: ball,
+        };
+        assert_eq!(events, vec![enter, exit]);
+        let body = world.component::<Rigidbody>(ball).unwrap();
+        assert_eq!(body.velocity, Vec3::new(10.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn bodies_land_on_collision_meshes() {
+        // A gentle slope
+        let slope = Mat4::identity()
+            .scale(Vec3::new(20.0, 20.0, 20.0))
+            .rotate(Z_AXIS, 0.2);
+        let normal = slope.transform_direction(Y_AXIS).normalise();
+        let land = |collider: Collider, friction: f64| {
+            let mut world = World::new();
+            let ground = world.place_object(quad(), slope);
+            world.set_collision_mesh(ground, true);
+            assert!(world.is_collision_mesh(ground));
+            // Lined up with the slope, so boxes land flat on it
+            let start = Mat4::identity()
+                .rotate(Z_AXIS, 0.2)
+                .translate(Vec3::new(0.0, 5.0, 0.0));
+            let id = world.place_object(quad(), start);
+            let body = Rigidbody::new(quad())
+                .with_collider(collider)
+                .with_restitution(0.0)
+                .with_friction(friction, friction);
+            world.insert_component(id, body);
+
+            for _ in 0..120 {
+                world.step_physics(1.0 / 60.0);
+            }
+
+            // The body rests on the slope's surface rather than falling through it
+            let position = world.get(id).unwrap().transformation.transform(ORIGIN);
+            let height = (position - slope.transform(ORIGIN)).dot(normal);
+            assert!((height - 1.0).abs() < 0.05, "{}", height);
+            let spin = world.component::<Rigidbody>(id).unwrap().angular_velocity;
+            (position.x, spin.length())
+        };
+        let sphere = Collider::Sphere {
+            centre: ORIGIN,
+            radius: 1.0,
+        };
+
+        // With friction, a ball rolls down the slope, and without it, it slides down further without turning
+        let (rolled, rolling) = land(sphere.clone(), 0.6);
+        let (slid, sliding) = land(sphere, 0.0);
+        assert!(rolled < -1.0 && rolling > 1.0, "{} {}", rolled, rolling);
+        assert!(slid < rolled && sliding < 1e-9, "{} {}", slid, sliding);
+
+        // A box grips the slope instead
+        let cube = Collider::Obb(Obb::new(ORIGIN, Vec3::new(1.0, 1.0, 1.0)));
+        let (held, _) = land(cube, 0.6);
+        assert!(held.abs() < 1.0, "{}", held);
+    }
+
+    #[test]
+    fn sprites_face_camera() {
+        let mut world = World::new();
+        let texture = Texture::new(2, 1, vec![0xff0000, 0x0000ff]);
+        let id = world.add_sprite(Sprite::new(texture, Vec2::new(4.0, 4.0)), ORIGIN);
+        world.update(0.0);
+
+        // Whichever way the camera looks at it, the left of the texture is on the left of the screen
+        for (position, yaw) in [
+            (Vec3::new(0.0, 0.0, 10.0), -90.0),
+            (Vec3::new(-10.0, 0.0, 0.0), 0.0),
+        ] {
+            let mut camera = Camera::new(position);
+            camera.set_rotation(Vec3::new(0.0, f64::to_radians(yaw), 0.0));
+            let mut renderer = Renderer::new(RendererConfig::new(20, 20));
+            camera.render_world(&mut renderer, &world);
+
+            assert_eq!(renderer.buffer[10 * 20 + 8], 0xff0000);
+            assert_eq!(renderer.buffer[10 * 20 + 12], 0x0000ff);
+        }
+        assert!(world.sprite(id).is_some());
+    }
+
+    #[test]
+    fn debug_lines_last_one_frame() {
+        let mut world = World::new();
+        let mut obj = quad();
+        obj.normals = Arc::new(vec![Vec3::new(0.0, 1.0, 0.0); 4]);
+        let id = world.place_object(obj, Mat4::identity().scale(Vec3::new(1.0, 4.0, 1.0)));
+
+        world.debug_normals(id);
+        world.debug_axes(Mat4::identity());
+        assert_eq!(world.debug_lines().len(), 7);
+
+        // Normals stay a fixed length, however the object is scaled
+        let normal = world.debug_lines()[0];
+        assert_eq!(normal.from, Vec3::new(-1.0, 0.0, -1.0));
+        assert_eq!(normal.to, Vec3::new(-1.0, DEBUG_NORMAL_LENGTH, -1.0));
+
+        world.update(1.0);
+        assert!(world.debug_lines().is_empty());
+    }
+
+    #[test]
+    fn debug_physics_shows_bodies_and_contacts() {
+        let mut world = World::new();
+        let floor = world.add_object(quad(), ORIGIN);
+        world.insert_component(
+            floor,
+            Rigidbody::new(quad())
+                .with_mass(0.0)
+                .with_collider(Collider::Plane(Plane::new(Y_AXIS, 0.0))),
+        );
+        let ball = world.add_object(quad(), Vec3::new(0.0, 0.9, 0.0));
+        let sphere = Collider::Sphere {
+            centre: ORIGIN,
+            radius: 1.0,
+        };
+        let mut body = Rigidbody::new(quad()).with_collider(sphere);
+        body.velocity = X_AXIS;
+        world.insert_component(ball, body);
+        world.step_physics(1.0 / 60.0);
+
+        world.debug_physics();
+        let count = |world: &World, colour: Colour| {
+            world
+                .debug_lines()
+                .iter()
+                .filter(|line| line.colour == colour)
+                .count()
+        };
+        assert!(count(&world, DEBUG_FIXED_COLOUR) > 0);
+        assert!(count(&world, DEBUG_AWAKE_COLOUR) > 0);
+        assert_eq!(count(&world, DEBUG_CONTACT_COLOUR), 3);
+        assert_eq!(count(&world, DEBUG_CONTACT_NORMAL_COLOUR), 1);
+        assert_eq!(count(&world, DEBUG_VELOCITY_COLOUR), 1);
+
+        // Once the ball has stopped and settled, it's drawn asleep and still
+        let body = world.component_mut::<Rigidbody>(ball).unwrap();
+        body.velocity = Vec3::default();
+        body.angular_velocity = Vec3::default();
+        for _ in 0..300 {
+            world.update(1.0 / 60.0);
+            world.step_physics(1.0 / 60.0);
+        }
+        assert!(world.is_asleep(ball));
+        world.debug_physics();
+        assert!(count(&world, DEBUG_ASLEEP_COLOUR) > 0);
+        assert_eq!(count(&world, DEBUG_AWAKE_COLOUR), 0);
+        assert_eq!(count(&world, DEBUG_VELOCITY_COLOUR), 0);
+    }
+
+    #[test]
+    fn projectiles_follow_their_arc_and_expire() {
+        let mut world = World::new();
+        let start = Vec3::new(0.0, 1.0, 0.0);
+        let throw = Projectile::new(Vec3::new(4.0, 6.0, 0.0)).with_lifetime(1.0);
+        let id = world.spawn_projectile(quad(), start, &throw);
+
+        // Stepped with semi-implicit Euler, the projectile stays close to the exact arc
+        let dt = 1.0 / 120.0;
+        for _ in 0..60 {
+            world.step_physics(dt);
+        }
+        let predicted = throw.position_at(start, world.physics.gravity, 0.5);
+        let position = world.get(id).unwrap().transformation.transform(ORIGIN);
+        assert!((position - predicted).length() < 0.05, "{}", position);
+        assert!((world.projectile_lifetime(id).unwrap() - 0.5).abs() < 1e-9);
+
+        // It's removed once its lifetime runs out
+        for _ in 0..61 {
+            world.step_physics(dt);
+        }
+        assert!(world.get(id).is_none());
+    }
+
+    #[test]
+    fn bodies_float_in_water() {
+        let mut world = World::new();
+        world.add_water(Water::new(20.0, 1), ORIGIN);
+        let cube = Collider::Obb(Obb::new(ORIGIN, Vec3::new(0.5, 0.5, 0.5)));
+
+        // Half as dense as the water, so it floats half under the surface
+        let light = world.add_object(quad(), Vec3::new(-3.0, 2.0, 0.0));
+        world.insert_component(
+            light,
+            Rigidbody::new(quad())
+                .with_mass(0.5)
+                .with_collider(cube.clone()),
+        );
+        // Twice as dense, so it sinks
+        let heavy = world.add_object(quad(), Vec3::new(3.0, 2.0, 0.0));
+        world.insert_component(
+            heavy,
+            Rigidbody::new(quad())
+                .with_mass(2.0)
+                .with_collider(cube.clone()),
+        );
+
+        for _ in 0..600 {
+            world.step_physics(1.0 / 60.0);
+        }
+        let height = |id| world.get(id).unwrap().transformation.transform(ORIGIN).y;
+        assert!(height(light).abs() < 0.05, "{}", height(light));
+        assert!(height(heavy) < -5.0, "{}", height(heavy));
+
+        // The floating body has settled rather than bobbing forever
+        let body = world.component::<Rigidbody>(light).unwrap();
+        assert!(body.velocity.length() < 0.05, "{}", body.velocity);
+    }
+
+    #[test]
+    fn terrain_chunks() {
+        let mut world = World::new();
+        let mut terrain = Terrain::from_fn(
+            2,
+            1,
+            4,
+            1.0,
+            Texture::clone(&empty_object().texture),
+            |x, _| x,
+        );
+        terrain.lod_distances = vec![4.0];
+        world.set_terrain(terrain);
+
+        assert_eq!(world.iter_tagged("terrain").count(), 2);
+        assert_eq!(world.terrain_height(2.5, 1.0), Some(2.5));
+        assert_eq!(world.terrain_height(-1.0, 1.0), None);
+
+        // Viewing from beyond the second chunk leaves only the first far enough away to lose detail
+        world.update_terrain(Vec3::new(10.0, 0.0, 2.0));
+        let vertex_counts: Vec<usize> = world
+            .iter_tagged("terrain")
+            .map(|(_, obj)| obj.vertices.len())
+            .collect();
+        assert_eq!(vertex_counts, vec![9 + 8, 25 + 16]);
+
+        assert!(world.remove_terrain().is_some());
+        assert!(world.is_empty());
+        assert_eq!(world.terrain_height(2.5, 1.0), None);
+    }
+
+    #[test]
+    fn blob_shadows_follow_their_entity() {
+        let mut world = World::new();
+        let terrain = Terrain::from_fn(
+            2,
+            2,
+            4,
+            1.0,
+            Texture::clone(&empty_object().texture),
+            |_, _| 0.0,
+        );
+        world.set_terrain(terrain);
+        let cube = Object::from_obj("./resources/cube").unwrap();
+        let id = world.add_object(cube, Vec3::new(4.0, 3.0, 4.0));
+        world.set_blob_shadow(id, BlobShadow::default());
+
+        // The shadow lies on the ground under the cube, lighter for the height it's floating at
+        let decals: Vec<&Decal> = world.blob_shadow_decals().collect();
+        assert_eq!(decals.len(), 1);
+        assert!((decals[0].volume.centre - Vec3::new(4.0, 0.0, 4.0)).length() < 1e-6);
+        assert!((decals[0].opacity - 0.6 * 0.75).abs() < 1e-6);
+
+        // It follows the cube, and disappears once the cube is too high or hidden
+        world.set_base_transform(id, Mat4::identity().translate(Vec3::new(5.0, 2.0, 3.0)));
+        world.update(0.1);
+        let centre = world.blob_shadow_decals().next().unwrap().volume.centre;
+        assert!((centre - Vec3::new(5.0, 0.0, 3.0)).length() < 1e-6);
+        world.set_base_transform(id, Mat4::identity().translate(Vec3::new(5.0, 9.5, 3.0)));
+        world.update(0.1);
+        assert_eq!(world.blob_shadow_decals().count(), 0);
+        world.set_base_transform(id, Mat4::identity().translate(Vec3::new(5.0, 2.0, 3.0)));
+        world.set_visible(id, false);
+        world.update(0.1);
+        assert_eq!(world.blob_shadow_decals().count(), 0);
+
+        assert_eq!(world.clear_blob_shadow(id), Some(BlobShadow::default()));
+        assert_eq!(world.blob_shadow(id), None);
+    }
+
+    #[test]
+    fn cloth_falls_onto_bodies() {
+        let mut world = World::new();
+        let ball = world.add_object(quad(), Vec3::new(0.0, -2.0, 0.0));
+        let sphere = Collider::Sphere {
+            centre: ORIGIN,
+            radius: 1.0,
+        };
+        world.insert_component(
+            ball,
+            Rigidbody::new(quad())
+                .with_mass(0.0)
+                .with_collider(sphere.clone()),
+        );
+        let cloth = Cloth::new(Vec3::new(-2.0, 0.0, -2.0), X_AXIS * 4.0, Z_AXIS * 4.0, 8, 8);
+        let id = world.add_cloth(cloth);
+
+        for _ in 0..120 {
+            world.step_physics(1.0 / 60.0);
+        }
+
+        // The mesh follows the cloth, which has settled over the ball, and can be found where it fell to
+        let cloth = world.cloth(id).unwrap();
+        assert_eq!(*world.get(id).unwrap().vertices, cloth.positions());
+        let top = cloth.position(4, 4).unwrap();
+        assert!((top.y - (-1.0 + cloth.thickness)).abs() < 0.05, "{:?}", top);
+        let below = Aabb::new(Vec3::new(-0.5, -1.5, -0.5), Vec3::new(0.5, -1.0, 0.5));
+        assert!(world.query_aabb(&below).contains(&id));
+    }
+
+    #[test]
+    fn water_waves() {
+        let mut world = World::new();
+        let mut water = Water::new(10.0, 4);
+        water.waves.push(Wave {
+            direction: Vec2::new(1.0, 0.0),
+            amplitude: 0.5,
+            wavelength: 4.0,
+            speed: 1.0,
+        });
+        let id = world.add_water(water, Vec3::new(0.0, -2.0, 0.0));
+        let opaque = world.add_object(empty_object(), ORIGIN);
+
+        world.update(1.0);
+        let water = world.water(id).unwrap();
+        for vertex in world.get(id).unwrap().vertices.iter() {
+            assert_eq!(vertex.y, water.height(vertex.x, vertex.z, 1.0));
+        }
+        assert!(world.bounds(id).unwrap().max.y >= -1.5);
+
+        // Water is transparent, so it's drawn after opaque objects
+        let mut ids = vec![id, opaque];
+        world.sort_for_drawing(&mut ids);
+        assert_eq!(ids, vec![opaque, id]);
     }
 }
@@ -0,0 +1,280 @@
+//! Renders small reference scenes without a window and compares them against the images checked in to `tests/golden`,
+//! so that changes to the rasteriser (fill rules, clipping, blending) can't quietly change what's drawn.
+//!
+//! A scene which doesn't match has its render saved next to its reference as `<name>.actual.png`, along with
+//! `<name>.diff.png`, which shows the reference faded out with every pixel that's out of tolerance in red. When a
+//! change to the output is intended, run with `MEGAVERTEX_BLESS=1` to replace the references with the new renders.
+
+use std::{env, fmt::Write, fs, path::PathBuf};
+
+use megavertex::{
+    Colour, Dither, FillMode, Fog, Hdr, Headless, Light, Mat4, Object, Palette, RendererConfig,
+    Retro, Texture, Tonemapper, Vec3, Water,
+};
+
+// The size every scene is drawn at. Small images keep the checked in references tiny
+const WIDTH: usize = 64;
+const HEIGHT: usize = 48;
+
+// How far each colour channel may be from the reference by default, out of 255. Other platforms can round the odd
+// pixel differently, but anything more than that is a real change
+const TOLERANCE: u8 = 2;
+
+///
+/// A reference scene, drawn after `frames` frames have passed, and how closely it has to match its reference image.
+///
+struct Golden {
+    name: &'static str,
+    frames: usize,
+    tolerance: u8,
+    config: fn() -> RendererConfig,
+    build: fn(&mut Headless),
+}
+
+impl Golden {
+    fn new(name: &'static str, build: fn(&mut Headless)) -> Golden {
+        Golden {
+            name,
+            frames: 1,
+            tolerance: TOLERANCE,
+            config: || RendererConfig::new(WIDTH, HEIGHT),
+            build,
+        }
+    }
+
+    fn render(&self) -> Texture {
+        let mut headless = Headless::from_config((self.config)());
+        (self.build)(&mut headless);
+        for _ in 0..self.frames {
+            headless.render_frame();
+        }
+        Texture::new(
+            headless.renderer.width(),
+            headless.renderer.height(),
+            headless.renderer.buffer.clone(),
+        )
+    }
+
+    ///
+    /// Renders the scene and checks it against its reference, saving the render and a diff if they differ. Returns a
+    /// description of what went wrong.
+    ///
+    fn check(&self) -> Result<(), String> {
+        let actual = self.render();
+        let path = |suffix: &str| {
+            let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            path.push("tests/golden");
+            path.push(format!("{}{}.png", self.name, suffix));
+            path.to_string_lossy().into_owned()
+        };
+        let save = |image: &Texture, path: &str| {
+            image
+                .save_png(path)
+                .map_err(|error| format!("failed to save {}: {}", path, error))
+        };
+
+        if env::var_os("MEGAVERTEX_BLESS").is_some() {
+            return save(&actual, &path(""));
+        }
+        let expected = Texture::load_from(&path("")).map_err(|error| {
+            format!(
+                "no reference for {} ({}) - run with MEGAVERTEX_BLESS=1 to create one",
+                self.name, error
+            )
+        })?;
+        if (expected.width, expected.height) != (actual.width, actual.height) {
+            save(&actual, &path(".actual"))?;
+            return Err(format!(
+                "{} was drawn at {}x{}, but its reference is {}x{}",
+                self.name, actual.width, actual.height, expected.width, expected.height
+            ));
+        }
+
+        let diff = diff(&expected, &actual, self.tolerance);
+        let wrong = diff
+            .pixels
+            .iter()
+            .filter(|pixel| **pixel == WRONG.to_0rgb())
+            .count();
+        if wrong == 0 {
+            // Leftovers from an earlier failure would only be confusing
+            let _ = fs::remove_file(path(".actual"));
+            let _ = fs::remove_file(path(".diff"));
+            return Ok(());
+        }
+        save(&actual, &path(".actual"))?;
+        save(&diff, &path(".diff"))?;
+        Err(format!(
+            "{} has {} pixels more than {} away from its reference - see {}",
+            self.name,
+            wrong,
+            self.tolerance,
+            path(".diff")
+        ))
+    }
+}
+
+// The colour pixels out of tolerance are marked with in diffs
+const WRONG: Colour = Colour::from_hex(0xff0000);
+
+///
+/// Marks every pixel with a channel more than `tolerance` away from the reference in red, over a faded out copy of the
+/// reference so it's clear where in the image they are.
+///
+fn diff(expected: &Texture, actual: &Texture, tolerance: u8) -> Texture {
+    let channels = |pixel: u32| {
+        let colour = Colour::from(pixel);
+        [colour.r, colour.g, colour.b]
+    };
+    let pixels = expected
+        .pixels
+        .iter()
+        .zip(&actual.pixels)
+        .map(|(expected, actual)| {
+            let out = channels(*expected)
+                .iter()
+                .zip(channels(*actual))
+                .any(|(a, b)| a.abs_diff(b) > tolerance);
+            if out {
+                WRONG.to_0rgb()
+            } else {
+                // A quarter of the brightness, in grey, so red always stands out
+                let [r, g, b] = channels(*expected);
+                let grey = ((r as u32 + g as u32 + b as u32) / 12) as u8;
+                Colour::new(grey, grey, grey).to_0rgb()
+            }
+        })
+        .collect();
+    Texture::new(expected.width, expected.height, pixels)
+}
+
+fn cube() -> Object {
+    Object::from_obj("./resources/cube").expect("the cube model is in resources")
+}
+
+fn sun() -> Light {
+    Light::Directional {
+        direction: Vec3::new(-0.4, -1.0, 0.6),
+        colour: Vec3::new(1.0, 1.0, 1.0),
+        intensity: 1.0,
+    }
+}
+
+///
+/// A cube turned so three of its faces show, lit from above.
+///
+fn lit_cube(headless: &mut Headless) {
+    headless.world.lights.push(sun());
+    let mut cube = cube();
+    cube.transform(
+        Mat4::identity()
+            .rotate(Vec3::new(0.0, 1.0, 0.0), 0.6)
+            .rotate(Vec3::new(1.0, 0.0, 0.0), 0.4),
+    );
+    headless.world.add_object(cube, Vec3::new(0.0, 0.0, 5.0));
+}
+
+///
+/// Cubes cut off by the edges of the screen, and one passing through the camera, which all have to be clipped.
+///
+fn clipped(headless: &mut Headless) {
+    headless.world.lights.push(sun());
+    for position in [
+        Vec3::new(-2.2, 0.5, 4.0),
+        Vec3::new(2.4, -1.2, 4.5),
+        Vec3::new(0.0, 1.9, 5.0),
+        Vec3::new(1.2, -0.8, 1.0),
+    ] {
+        let mut cube = cube();
+        cube.transform(Mat4::identity().rotate(Vec3::new(1.0, 1.0, 0.0), 0.5));
+        headless.world.add_object(cube, position);
+    }
+}
+
+///
+/// See-through cubes overlapping each other over water, in fog.
+///
+fn blended(headless: &mut Headless) {
+    headless.world.lights.push(sun());
+    headless.world.environment.fog = Some(Fog {
+        colour: Vec3::new(0.6, 0.7, 0.8),
+        start: 4.0,
+        end: 20.0,
+    });
+    for (i, x) in [-1.2, 0.0, 1.2].into_iter().enumerate() {
+        let id = headless
+            .world
+            .add_object(cube(), Vec3::new(x, 0.0, 5.0 + i as f64 * 2.0));
+        headless.world.set_opacity(id, 0.5);
+    }
+    headless
+        .world
+        .add_water(Water::new(20.0, 8), Vec3::new(0.0, -2.0, 10.0));
+}
+
+///
+/// The lit cube overexposed, which only keeps its shading when drawn in HDR.
+///
+fn overexposed(headless: &mut Headless) {
+    lit_cube(headless);
+    headless.world.environment.ambient = Vec3::new(2.5, 2.0, 1.5);
+}
+
+///
+/// The lit cube reduced to a handful of colours, dithered between them.
+///
+fn paletted(headless: &mut Headless) {
+    lit_cube(headless);
+    let colours = [0x000000, 0x3b1f14, 0x7a3b22, 0xc0603a, 0xe8a878, 0xffffff];
+    let palette = Palette::new(colours.into_iter().map(Colour::from_hex).collect());
+    headless
+        .renderer
+        .set_palette(Some(palette.with_dither(Dither::Ordered)));
+}
+
+#[test]
+fn golden_images() {
+    let scenes = [
+        Golden::new("lit_cube", lit_cube),
+        Golden::new("clipped", clipped),
+        // A few frames in, so the waves have moved
+        Golden {
+            frames: 10,
+            ..Golden::new("blended", blended)
+        },
+        Golden {
+            config: || RendererConfig {
+                fill_mode: FillMode::Wireframe,
+                ..RendererConfig::new(WIDTH, HEIGHT)
+            },
+            ..Golden::new("wireframe", lit_cube)
+        },
+        Golden {
+            config: || RendererConfig {
+                hdr: Some(Hdr {
+                    tonemapper: Tonemapper::Aces,
+                    exposure: 1.2,
+                }),
+                ..RendererConfig::new(WIDTH, HEIGHT)
+            },
+            ..Golden::new("hdr", overexposed)
+        },
+        Golden::new("palette", paletted),
+        Golden {
+            config: || RendererConfig {
+                retro: Some(Retro::default()),
+                ..RendererConfig::new(WIDTH, HEIGHT)
+            },
+            ..Golden::new("retro", clipped)
+        },
+    ];
+
+    // Every scene is checked before failing, so one run shows everything that's changed
+    let mut failures = String::new();
+    for scene in &scenes {
+        if let Err(error) = scene.check() {
+            writeln!(failures, "{}", error).unwrap();
+        }
+    }
+    assert!(failures.is_empty(), "golden images differ:\n{}", failures);
+}